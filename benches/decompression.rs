@@ -0,0 +1,84 @@
+//! Throughput of reading compressed response bodies.
+//!
+//! Compare the decoder backends by running the benchmark with and without them:
+//!
+//! ```text
+//! cargo bench --bench decompression --features gzip,zstd
+//! cargo bench --bench decompression --features gzip,zstd,zlib-ng,libdeflate
+//! ```
+
+#[path = "../tests/support/mod.rs"]
+mod support;
+
+use std::io::Write;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use flate2::{Compression, write::GzEncoder};
+use support::server;
+
+/// The size of the decoded bodies.
+const BODY_SIZE: usize = 8 * 1024 * 1024;
+
+fn content() -> Vec<u8> {
+    let mut content = Vec::with_capacity(BODY_SIZE);
+    let mut i = 0u64;
+    while content.len() < BODY_SIZE {
+        write!(content, "{{\"id\":{i},\"name\":\"item {}\"}},", i % 997).unwrap();
+        i += 1;
+    }
+    content.truncate(BODY_SIZE);
+    content
+}
+
+fn decompression(c: &mut Criterion) {
+    let content = content();
+    let gzip = {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&content).unwrap();
+        encoder.finish().unwrap()
+    };
+    let zstd = zstd::encode_all(&content[..], 3).unwrap();
+
+    let server = server::http(move |req| {
+        let (coding, body) = match req.uri().path() {
+            "/gzip" => ("gzip", gzip.clone()),
+            _ => ("zstd", zstd.clone()),
+        };
+        async move {
+            http::Response::builder()
+                .header("content-encoding", coding)
+                .body(body.into())
+                .unwrap()
+        }
+    });
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = {
+        let _enter = rt.enter();
+        wreq::Client::builder().no_proxy().build().unwrap()
+    };
+
+    let mut group = c.benchmark_group("decompression");
+    group.throughput(Throughput::Bytes(BODY_SIZE as u64));
+    group.sample_size(20);
+    for coding in ["gzip", "zstd"] {
+        let url = format!("http://{}/{coding}", server.addr());
+        group.bench_function(coding, |b| {
+            b.to_async(&rt).iter(|| async {
+                let body = client
+                    .get(&url)
+                    .send()
+                    .await
+                    .unwrap()
+                    .bytes()
+                    .await
+                    .unwrap();
+                assert_eq!(body.len(), BODY_SIZE);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decompression);
+criterion_main!(benches);
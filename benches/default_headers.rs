@@ -0,0 +1,106 @@
+//! Cost of adding the default headers of a client to a request.
+//!
+//! Compares the per-name insert loop the client used to run, the merge that copies the missing
+//! defaults into the request, and the shared map the client now writes under the request's own
+//! headers.
+//!
+//! ```text
+//! cargo bench --bench default_headers --features __bench
+//! ```
+
+use std::sync::Arc;
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use http::{HeaderMap, HeaderName, HeaderValue, Request};
+use wreq::{
+    __merge_default_headers as merge_default_headers,
+    __share_default_headers as share_default_headers,
+};
+
+/// Returns `count` default headers, sized like those of a browser emulation.
+fn default_headers(count: usize) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for i in 0..count {
+        let name = HeaderName::from_bytes(format!("x-default-{i}").as_bytes()).unwrap();
+        let value = HeaderValue::from_str(&"v".repeat(48)).unwrap();
+        headers.insert(name, value);
+    }
+    headers
+}
+
+/// Returns a request without headers of its own.
+fn bare_request() -> Request<()> {
+    Request::new(())
+}
+
+/// Returns a request that sets one of the defaults and one header of its own.
+fn own_request() -> Request<()> {
+    let mut req = Request::new(());
+    let headers = req.headers_mut();
+    headers.insert("x-default-0", HeaderValue::from_static("own"));
+    headers.insert("x-request", HeaderValue::from_static("1"));
+    req
+}
+
+/// The per-name insert loop the client ran before the defaults were shared.
+fn insert_loop(headers: &mut HeaderMap, defaults: &HeaderMap) {
+    for name in defaults.keys() {
+        if !headers.contains_key(name) {
+            for value in defaults.get_all(name) {
+                headers.append(name, value.clone());
+            }
+        }
+    }
+}
+
+fn defaults(c: &mut Criterion) {
+    let mut group = c.benchmark_group("default_headers");
+    for count in [0, 16, 64] {
+        let defaults = Arc::new(default_headers(count));
+
+        for (kind, request) in [
+            ("bare", bare_request as fn() -> Request<()>),
+            ("own", own_request),
+        ] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{kind}/insert_loop"), count),
+                &defaults,
+                |b, defaults| {
+                    b.iter_batched_ref(
+                        request,
+                        |req| insert_loop(req.headers_mut(), defaults),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("{kind}/merge"), count),
+                &defaults,
+                |b, defaults| {
+                    b.iter_batched_ref(
+                        request,
+                        |req| merge_default_headers(req.headers_mut(), defaults),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("{kind}/shared"), count),
+                &defaults,
+                |b, defaults| {
+                    b.iter_batched_ref(
+                        request,
+                        |req| share_default_headers(req, defaults.clone()),
+                        BatchSize::SmallInput,
+                    )
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, defaults);
+criterion_main!(benches);
@@ -0,0 +1,161 @@
+/* The C API of wreq, enabled with the `capi` feature. */
+
+#ifndef WREQ_H
+#define WREQ_H
+
+/* Generated with cbindgen from src/capi.rs, do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * The call succeeded.
+ */
+#define WREQ_OK 0
+
+/**
+ * An argument was null or invalid.
+ */
+#define WREQ_INVALID_ARGUMENT -1
+
+/**
+ * The request failed.
+ */
+#define WREQ_REQUEST_FAILED -2
+
+/**
+ * The body callback aborted the request.
+ */
+#define WREQ_ABORTED -3
+
+/**
+ * The library panicked. The client may be left in an inconsistent state and should be freed.
+ */
+#define WREQ_PANIC -4
+
+/**
+ * A client, with the runtime driving its connections.
+ */
+typedef struct WreqClient WreqClient;
+
+/**
+ * A request header.
+ */
+typedef struct {
+  /**
+   * The nul-terminated name of the header.
+   */
+  const char *name;
+  /**
+   * The nul-terminated value of the header.
+   */
+  const char *value;
+} WreqHeader;
+
+/**
+ * A request to execute with [`wreq_request_execute`].
+ */
+typedef struct {
+  /**
+   * The nul-terminated method, or null for `GET`.
+   */
+  const char *method;
+  /**
+   * The nul-terminated URL.
+   */
+  const char *url;
+  /**
+   * The headers, appended to the default headers of the client.
+   */
+  const WreqHeader *headers;
+  /**
+   * The number of `headers`.
+   */
+  size_t headers_len;
+  /**
+   * The body, or null for none.
+   */
+  const uint8_t *body;
+  /**
+   * The length of `body`.
+   */
+  size_t body_len;
+  /**
+   * The total timeout of the request in milliseconds, or 0 for the timeout of the client.
+   */
+  uint64_t timeout_ms;
+} WreqRequest;
+
+/**
+ * The callbacks receiving a response, each passed the `user_data` of the request.
+ *
+ * Every callback is optional. The pointers passed to them are only valid during the call.
+ */
+typedef struct {
+  /**
+   * Receives the status code.
+   */
+  void (*on_status)(void *user_data, uint16_t status);
+  /**
+   * Receives each header, as its name and its value.
+   */
+  void (*on_header)(void *user_data,
+                    const uint8_t *name,
+                    size_t name_len,
+                    const uint8_t *value,
+                    size_t value_len);
+  /**
+   * Receives each chunk of the body, returning non-zero to abort the request.
+   */
+  int (*on_body)(void *user_data, const uint8_t *data, size_t len);
+} WreqCallbacks;
+
+#ifdef __cplusplus
+extern "C" {
+#endif  // __cplusplus
+
+/**
+ * Creates a client with the default configuration.
+ *
+ * Returns null on failure, with the error available from [`wreq_last_error`].
+ */
+WreqClient *wreq_client_new(void);
+
+/**
+ * Frees a client, closing its connections.
+ *
+ * # Safety
+ *
+ * `client` must be null or a pointer returned by [`wreq_client_new`] or
+ * [`WreqClient::into_raw`], not freed yet, and no request may be executing with it.
+ */
+void wreq_client_free(WreqClient *client);
+
+/**
+ * Executes a request, blocking the calling thread until the response body has been read.
+ *
+ * Returns [`WREQ_OK`] on success, or else an error code with the error available from
+ * [`wreq_last_error`]. A response with an error status is a success.
+ *
+ * # Safety
+ *
+ * `client` must be a valid client, and the pointers of `request` valid for their lengths.
+ * `callbacks` may be null. This function must not be called from a callback.
+ */
+int wreq_request_execute(const WreqClient *client,
+                         const WreqRequest *request,
+                         const WreqCallbacks *callbacks,
+                         void *user_data);
+
+/**
+ * Returns the message of the last error on the calling thread, or null.
+ *
+ * The message is valid until the next failing call on the same thread.
+ */
+const char *wreq_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* WREQ_H */
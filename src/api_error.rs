@@ -0,0 +1,96 @@
+//! typed errors of JSON APIs
+
+use std::{error::Error as StdError, fmt};
+
+use http::StatusCode;
+
+use crate::Error;
+
+/// The error of a request to a JSON API, returned by
+/// [`Response::json_or_error`](crate::Response::json_or_error).
+///
+/// It is either the error body the API answered with, deserialized to `E`, or an error of the
+/// request itself. It can be built from an [`Error`] with `?`, so that sending the request and
+/// decoding its response can fail with the same type.
+///
+/// # Example
+///
+/// ```
+/// use wreq::ApiError;
+///
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Item {
+///     id: u64,
+/// }
+///
+/// #[derive(Debug, serde::Deserialize)]
+/// struct Problem {
+///     message: String,
+/// }
+///
+/// async fn item(id: u64) -> Result<Item, ApiError<Problem>> {
+///     wreq::Client::new()
+///         .get(format!("https://api.example.com/items/{id}"))
+///         .send()
+///         .await?
+///         .json_or_error::<Item, Problem>()
+///         .await
+/// }
+/// ```
+#[derive(Debug)]
+pub enum ApiError<E> {
+    /// The server answered with a status outside of `2xx`, and an error body.
+    Status {
+        /// The status of the response.
+        status: StatusCode,
+        /// The deserialized body of the response.
+        error: E,
+    },
+    /// The request failed, or its response couldn't be read or deserialized.
+    ///
+    /// A response with an error status whose body isn't an `E` is reported as a status error,
+    /// see [`Error::is_status`].
+    Request(Error),
+}
+
+impl<E> ApiError<E> {
+    /// Returns the status of the response, if one was received.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Status { status, .. } => Some(*status),
+            ApiError::Request(err) => err.status(),
+        }
+    }
+
+    /// Returns the error body the API answered with, if any.
+    pub fn api_error(&self) -> Option<&E> {
+        match self {
+            ApiError::Status { error, .. } => Some(error),
+            ApiError::Request(_) => None,
+        }
+    }
+}
+
+impl<E> From<Error> for ApiError<E> {
+    fn from(err: Error) -> ApiError<E> {
+        ApiError::Request(err)
+    }
+}
+
+impl<E> fmt::Display for ApiError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Status { status, .. } => write!(f, "API error response ({status})"),
+            ApiError::Request(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: fmt::Debug> StdError for ApiError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            ApiError::Status { .. } => None,
+            ApiError::Request(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Memory budgets for buffered response bodies
+//!
+//! Reading a response body with [`Response::bytes`], [`Response::text`] or
+//! [`Response::json`] holds the whole body in memory. A service fetching many large responses
+//! concurrently can bound the memory held by these bodies with a [`BufferBudget`], shared by all
+//! the requests of a client, or of several clients.
+//!
+//! A body reserves its bytes from the budget while it is buffered: its `Content-Length` up
+//! front when known, or else each chunk as it arrives. The reservation is released once the
+//! body has been returned, or deserialized. When the budget is exhausted, the body either waits
+//! for other bodies to release their reservations or fails, as set by [`OnExhausted`].
+//!
+//! Only a body holding none of the budget waits for it, so that bodies can't wait for each
+//! other: a body of unknown length that needs more than its first chunk once the budget is
+//! exhausted fails instead.
+//!
+//! A single request can also be bounded with
+//! [`RequestBuilder::max_buffered_bytes`](crate::RequestBuilder::max_buffered_bytes). Bodies
+//! exceeding a limit fail with an error for which [`Error::buffer_limit_exceeded`] returns the
+//! limit.
+//!
+//! ```no_run
+//! # async fn run() -> wreq::Result<()> {
+//! use wreq::budget::{BufferBudget, OnExhausted};
+//!
+//! // At most 64 MiB of response bodies are buffered at any time.
+//! let client = wreq::Client::builder()
+//!     .buffer_budget(BufferBudget::new(64 << 20, OnExhausted::Wait))
+//!     .build()?;
+//!
+//! let body = client
+//!     .get("https://example.com/large.json")
+//!     .max_buffered_bytes(8 << 20)
+//!     .send()
+//!     .await?
+//!     .bytes()
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Response::bytes`]: crate::Response::bytes
+//! [`Response::text`]: crate::Response::text
+//! [`Response::json`]: crate::Response::json
+//! [`Error::buffer_limit_exceeded`]: crate::Error::buffer_limit_exceeded
+
+use std::{error::Error as StdError, fmt, sync::Arc};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A budget of bytes held by the response bodies being buffered.
+///
+/// Cloning the budget shares it, so it can be given to several clients.
+#[derive(Clone)]
+pub struct BufferBudget {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+    on_exhausted: OnExhausted,
+}
+
+/// What a body does when the [`BufferBudget`] is exhausted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OnExhausted {
+    /// Wait for other bodies to release their reservations.
+    #[default]
+    Wait,
+    /// Fail with an error for which
+    /// [`Error::buffer_limit_exceeded`](crate::Error::buffer_limit_exceeded) returns the
+    /// capacity of the budget.
+    Fail,
+}
+
+impl BufferBudget {
+    /// Creates a budget of `capacity` bytes.
+    pub fn new(capacity: usize, on_exhausted: OnExhausted) -> BufferBudget {
+        let capacity = capacity.min(Semaphore::MAX_PERMITS);
+        BufferBudget {
+            inner: Arc::new(Inner {
+                semaphore: Arc::new(Semaphore::new(capacity)),
+                capacity,
+                on_exhausted,
+            }),
+        }
+    }
+
+    /// Returns the capacity of the budget in bytes.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    /// Returns the number of bytes not reserved by a body.
+    pub fn available(&self) -> usize {
+        self.inner.semaphore.available_permits()
+    }
+}
+
+impl fmt::Debug for BufferBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferBudget")
+            .field("capacity", &self.inner.capacity)
+            .field("available", &self.available())
+            .field("on_exhausted", &self.inner.on_exhausted)
+            .finish()
+    }
+}
+
+/// The error of a response body exceeding a buffering limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferLimitExceeded {
+    limit: usize,
+}
+
+impl BufferLimitExceeded {
+    /// Returns the limit exceeded, in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for BufferLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "response body exceeds the buffering limit of {} bytes",
+            self.limit
+        )
+    }
+}
+
+impl StdError for BufferLimitExceeded {}
+
+/// The limits of buffering a response body, carried by the response.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferLimits {
+    budget: Option<BufferBudget>,
+    max_bytes: Option<usize>,
+}
+
+impl BufferLimits {
+    pub(crate) fn new(budget: Option<BufferBudget>, max_bytes: Option<usize>) -> Option<Self> {
+        if budget.is_none() && max_bytes.is_none() {
+            return None;
+        }
+        Some(BufferLimits { budget, max_bytes })
+    }
+
+    /// Starts buffering a body, with nothing reserved yet.
+    pub(crate) fn reservation(&self) -> Reservation {
+        Reservation {
+            limits: self.clone(),
+            permit: None,
+            reserved: 0,
+        }
+    }
+}
+
+/// The bytes reserved by a body being buffered, released once dropped.
+pub(crate) struct Reservation {
+    limits: BufferLimits,
+    permit: Option<OwnedSemaphorePermit>,
+    reserved: usize,
+}
+
+impl Reservation {
+    /// Reserves up to `total` bytes for the body.
+    pub(crate) async fn reserve_to(&mut self, total: usize) -> Result<(), BufferLimitExceeded> {
+        if total <= self.reserved {
+            return Ok(());
+        }
+        if let Some(limit) = self.limits.max_bytes.filter(|limit| total > *limit) {
+            return Err(BufferLimitExceeded { limit });
+        }
+
+        if let Some(budget) = &self.limits.budget {
+            let exceeded = || BufferLimitExceeded {
+                limit: budget.capacity(),
+            };
+            if total > budget.capacity() {
+                return Err(exceeded());
+            }
+            let additional = u32::try_from(total - self.reserved).map_err(|_| exceeded())?;
+
+            let semaphore = budget.inner.semaphore.clone();
+            let permit = if self.permit.is_none() && budget.inner.on_exhausted == OnExhausted::Wait
+            {
+                semaphore
+                    .acquire_many_owned(additional)
+                    .await
+                    .map_err(|_| exceeded())?
+            } else {
+                semaphore
+                    .try_acquire_many_owned(additional)
+                    .map_err(|_| exceeded())?
+            };
+            match &mut self.permit {
+                Some(held) => held.merge(permit),
+                None => self.permit = Some(permit),
+            }
+        }
+
+        self.reserved = total;
+        Ok(())
+    }
+}
@@ -0,0 +1,366 @@
+//! C-compatible API
+//!
+//! The `capi` module exports a small set of `extern "C"` functions, so that a client, with the
+//! emulation it was built with, can be driven from other languages through their FFI, such as
+//! `ctypes` in Python, cgo in Go or Node-API, without writing bindings for each of them. The
+//! symbols are exported by any `cdylib` or `staticlib` linking this crate with the `capi`
+//! feature enabled.
+//!
+//! A [`WreqClient`] owns a `Client` and the Tokio runtime driving its connections. A request is
+//! executed on the calling thread, which is blocked until the response body has been read, and
+//! the response is handed to the [`WreqCallbacks`] as it arrives: the status, each header, then
+//! each chunk of the body. A body callback returning non-zero aborts the request.
+//!
+//! ```c
+//! WreqClient *client = wreq_client_new();
+//! WreqRequest req = { .method = "GET", .url = "https://example.com" };
+//! WreqCallbacks callbacks = { .on_status = on_status, .on_body = on_body };
+//! if (wreq_request_execute(client, &req, &callbacks, ctx) != WREQ_OK) {
+//!     fprintf(stderr, "%s\n", wreq_last_error());
+//! }
+//! wreq_client_free(client);
+//! ```
+//!
+//! Clients built with an emulation are handed over to C with [`WreqClient::into_raw`].
+//!
+//! The declarations of these functions are in the `include/wreq.h` header of the repository,
+//! generated with `cbindgen --config cbindgen.toml --output include/wreq.h`. A panic never
+//! unwinds into the caller: the function returns [`WREQ_PANIC`], or null, instead.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{CStr, CString, c_char, c_int, c_void},
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+    time::Duration,
+};
+
+use http::Method;
+use tokio::runtime::Runtime;
+
+use crate::{Client, Error, RequestBuilder};
+
+/// The call succeeded.
+pub const WREQ_OK: c_int = 0;
+/// An argument was null or invalid.
+pub const WREQ_INVALID_ARGUMENT: c_int = -1;
+/// The request failed.
+pub const WREQ_REQUEST_FAILED: c_int = -2;
+/// The body callback aborted the request.
+pub const WREQ_ABORTED: c_int = -3;
+/// The library panicked. The client may be left in an inconsistent state and should be freed.
+pub const WREQ_PANIC: c_int = -4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(err: impl fmt::Display) {
+    let message =
+        CString::new(err.to_string().replace('\0', "")).expect("the message has no nul byte");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs the body of an exported function, returning `on_panic` if it panics, as a panic must
+/// not unwind into C.
+fn catch_panic<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        set_last_error(format_args!("panicked: {}", panic_message(&*payload)));
+        on_panic
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic payload"
+    }
+}
+
+/// A client, with the runtime driving its connections.
+pub struct WreqClient {
+    client: Client,
+    runtime: Runtime,
+}
+
+impl WreqClient {
+    /// Hands a client over to C, returning the pointer to pass to the other functions.
+    ///
+    /// This lets a binding configure the client, with its emulation, in Rust. The pointer is
+    /// released with [`wreq_client_free`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the runtime driving the client cannot be started.
+    pub fn into_raw(client: Client) -> crate::Result<*mut WreqClient> {
+        let runtime = runtime()?;
+        Ok(Box::into_raw(Box::new(WreqClient { client, runtime })))
+    }
+}
+
+fn runtime() -> crate::Result<Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::builder)
+}
+
+/// A request header.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct WreqHeader {
+    /// The nul-terminated name of the header.
+    pub name: *const c_char,
+    /// The nul-terminated value of the header.
+    pub value: *const c_char,
+}
+
+/// A request to execute with [`wreq_request_execute`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct WreqRequest {
+    /// The nul-terminated method, or null for `GET`.
+    pub method: *const c_char,
+    /// The nul-terminated URL.
+    pub url: *const c_char,
+    /// The headers, appended to the default headers of the client.
+    pub headers: *const WreqHeader,
+    /// The number of `headers`.
+    pub headers_len: usize,
+    /// The body, or null for none.
+    pub body: *const u8,
+    /// The length of `body`.
+    pub body_len: usize,
+    /// The total timeout of the request in milliseconds, or 0 for the timeout of the client.
+    pub timeout_ms: u64,
+}
+
+/// The callbacks receiving a response, each passed the `user_data` of the request.
+///
+/// Every callback is optional. The pointers passed to them are only valid during the call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WreqCallbacks {
+    /// Receives the status code.
+    pub on_status: Option<unsafe extern "C" fn(user_data: *mut c_void, status: u16)>,
+    /// Receives each header, as its name and its value.
+    pub on_header: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            name: *const u8,
+            name_len: usize,
+            value: *const u8,
+            value_len: usize,
+        ),
+    >,
+    /// Receives each chunk of the body, returning non-zero to abort the request.
+    pub on_body:
+        Option<unsafe extern "C" fn(user_data: *mut c_void, data: *const u8, len: usize) -> c_int>,
+}
+
+/// Creates a client with the default configuration.
+///
+/// Returns null on failure, with the error available from [`wreq_last_error`].
+#[unsafe(no_mangle)]
+pub extern "C" fn wreq_client_new() -> *mut WreqClient {
+    catch_panic(ptr::null_mut(), || {
+        let result = runtime().and_then(|runtime| {
+            let client = {
+                let _enter = runtime.enter();
+                Client::builder().build()?
+            };
+            Ok(WreqClient { client, runtime })
+        });
+
+        match result {
+            Ok(client) => Box::into_raw(Box::new(client)),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Frees a client, closing its connections.
+///
+/// # Safety
+///
+/// `client` must be null or a pointer returned by [`wreq_client_new`] or
+/// [`WreqClient::into_raw`], not freed yet, and no request may be executing with it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wreq_client_free(client: *mut WreqClient) {
+    catch_panic((), || {
+        if !client.is_null() {
+            drop(unsafe { Box::from_raw(client) });
+        }
+    })
+}
+
+/// Executes a request, blocking the calling thread until the response body has been read.
+///
+/// Returns [`WREQ_OK`] on success, or else an error code with the error available from
+/// [`wreq_last_error`]. A response with an error status is a success.
+///
+/// # Safety
+///
+/// `client` must be a valid client, and the pointers of `request` valid for their lengths.
+/// `callbacks` may be null. This function must not be called from a callback.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn wreq_request_execute(
+    client: *const WreqClient,
+    request: *const WreqRequest,
+    callbacks: *const WreqCallbacks,
+    user_data: *mut c_void,
+) -> c_int {
+    catch_panic(WREQ_PANIC, || {
+        let (Some(client), Some(request)) =
+            (unsafe { client.as_ref() }, unsafe { request.as_ref() })
+        else {
+            set_last_error("the client and the request must not be null");
+            return WREQ_INVALID_ARGUMENT;
+        };
+        let callbacks = unsafe { callbacks.as_ref() }.copied().unwrap_or_default();
+
+        let builder = match unsafe { request_builder(&client.client, request) } {
+            Ok(builder) => builder,
+            Err(err) => return failed(err),
+        };
+        client
+            .runtime
+            .block_on(execute(builder, callbacks, user_data))
+    })
+}
+
+/// Returns the message of the last error on the calling thread, or null.
+///
+/// The message is valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn wreq_last_error() -> *const c_char {
+    catch_panic(ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+/// # Safety
+///
+/// The pointers of `request` must be valid for their lengths.
+unsafe fn request_builder(client: &Client, request: &WreqRequest) -> crate::Result<RequestBuilder> {
+    let method = if request.method.is_null() {
+        Method::GET
+    } else {
+        let method = unsafe { CStr::from_ptr(request.method) };
+        Method::from_bytes(method.to_bytes()).map_err(Error::builder)?
+    };
+    let url = unsafe { str_arg(request.url, "url") }?;
+    let mut builder = client.request(method, url);
+
+    if !request.headers.is_null() {
+        let headers = unsafe { slice::from_raw_parts(request.headers, request.headers_len) };
+        for header in headers {
+            let name = unsafe { str_arg(header.name, "header name") }?;
+            let value = unsafe { str_arg(header.value, "header value") }?;
+            builder = builder.header_append(name, value);
+        }
+    }
+    if !request.body.is_null() && request.body_len > 0 {
+        let body = unsafe { slice::from_raw_parts(request.body, request.body_len) };
+        builder = builder.body(body.to_vec());
+    }
+    if request.timeout_ms > 0 {
+        builder = builder.timeout(Duration::from_millis(request.timeout_ms));
+    }
+    Ok(builder)
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a nul-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> crate::Result<&'a str> {
+    if ptr.is_null() {
+        return Err(Error::builder(format!("{name} must not be null")));
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map_err(Error::builder)
+}
+
+async fn execute(
+    builder: RequestBuilder,
+    callbacks: WreqCallbacks,
+    user_data: *mut c_void,
+) -> c_int {
+    let mut res = match builder.send().await {
+        Ok(res) => res,
+        Err(err) => return failed(err),
+    };
+
+    if let Some(on_status) = callbacks.on_status {
+        unsafe { on_status(user_data, res.status().as_u16()) };
+    }
+    if let Some(on_header) = callbacks.on_header {
+        for (name, value) in res.headers() {
+            let (name, value) = (name.as_str().as_bytes(), value.as_bytes());
+            unsafe {
+                on_header(
+                    user_data,
+                    name.as_ptr(),
+                    name.len(),
+                    value.as_ptr(),
+                    value.len(),
+                )
+            };
+        }
+    }
+
+    loop {
+        match res.chunk().await {
+            Ok(Some(chunk)) => {
+                let Some(on_body) = callbacks.on_body else {
+                    continue;
+                };
+                if unsafe { on_body(user_data, chunk.as_ptr(), chunk.len()) } != 0 {
+                    set_last_error("the request was aborted by the body callback");
+                    return WREQ_ABORTED;
+                }
+            }
+            Ok(None) => return WREQ_OK,
+            Err(err) => return failed(err),
+        }
+    }
+}
+
+fn failed(err: Error) -> c_int {
+    let code = if err.is_builder() {
+        WREQ_INVALID_ARGUMENT
+    } else {
+        WREQ_REQUEST_FAILED
+    };
+    set_last_error(err);
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::{WREQ_OK, WREQ_PANIC, catch_panic, wreq_last_error};
+
+    #[test]
+    fn panic_is_caught() {
+        assert_eq!(catch_panic(WREQ_PANIC, || WREQ_OK), WREQ_OK);
+        assert_eq!(catch_panic(WREQ_PANIC, || panic!("boom")), WREQ_PANIC);
+
+        let message = unsafe { CStr::from_ptr(wreq_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "panicked: boom");
+    }
+}
@@ -5,15 +5,19 @@ use std::{
 };
 
 use bytes::Bytes;
-use http_body::Body as HttpBody;
+use http_body::{Body as HttpBody, Frame};
 use http_body_util::combinators::BoxBody;
 use pin_project_lite::pin_project;
 #[cfg(feature = "stream")]
 use tokio::fs::File;
+use tokio::io::AsyncWrite;
 #[cfg(feature = "stream")]
 use tokio_util::io::ReaderStream;
 
-use crate::error::{BoxError, Error};
+use crate::{
+    digest::{BodyDigest, DigestAlgorithm, Hasher},
+    error::{BoxError, Error},
+};
 
 /// An request body.
 pub struct Body {
@@ -129,6 +133,64 @@ impl Body {
         }
     }
 
+    /// Hashes the body with `algorithm` while it is sent.
+    ///
+    /// The returned [`BodyDigest`] holds the digest once the body has been fully sent. For a
+    /// body built from bytes, the digest is computed right away, so it can be used to set a
+    /// `Content-MD5`, `Digest`, `Content-Digest` or `Repr-Digest` header before sending the
+    /// request; for a streaming body, it is available after the request was sent, to verify
+    /// the upload against what the server reports.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wreq::{Body, digest::DigestAlgorithm};
+    /// let (body, digest) = Body::from("hello").with_digest(DigestAlgorithm::Sha256);
+    /// let header = digest.get().unwrap().to_header_value();
+    /// # drop((body, header));
+    /// ```
+    pub fn with_digest(self, algorithm: DigestAlgorithm) -> (Body, BodyDigest) {
+        match self.inner {
+            Inner::Reusable(ref bytes) => {
+                let digest = BodyDigest::ready(crate::digest::digest(algorithm, bytes));
+                (self, digest)
+            }
+            Inner::Streaming(body) => {
+                let digest = BodyDigest::default();
+                let body = Body::wrap(DigestBody {
+                    inner: body,
+                    hasher: Some(Hasher::new(algorithm)),
+                    digest: digest.clone(),
+                });
+                (body, digest)
+            }
+        }
+    }
+
+    /// Returns a body yielding `prefix` before the data of this body.
+    pub(crate) fn prepend(self, prefix: Bytes) -> Body {
+        if prefix.is_empty() {
+            return self;
+        }
+
+        Body::wrap(PrefixedBody {
+            prefix: Some(prefix),
+            inner: self,
+        })
+    }
+
+    /// Returns a body writing its data to `sink` before yielding it.
+    pub(crate) fn tee<W>(self, sink: W) -> Body
+    where
+        W: AsyncWrite + Send + Sync + Unpin + 'static,
+    {
+        Body::wrap(TeeBody {
+            inner: self,
+            sink,
+            step: TeeStep::Read,
+        })
+    }
+
     pub(crate) fn try_clone(&self) -> Option<Body> {
         match self.inner {
             Inner::Reusable(ref chunk) => Some(Body::reusable(chunk.clone())),
@@ -334,9 +396,215 @@ where
     }
 }
 
+// ===== impl PrefixedBody =====
+
+pin_project! {
+    struct PrefixedBody<B> {
+        prefix: Option<Bytes>,
+        #[pin]
+        inner: B,
+    }
+}
+
+impl<B> HttpBody for PrefixedBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        if let Some(prefix) = this.prefix.take() {
+            return Poll::Ready(Some(Ok(http_body::Frame::data(prefix))));
+        }
+        this.inner.poll_frame(cx)
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        let prefix = self.prefix.as_ref().map_or(0, |prefix| prefix.len() as u64);
+        let inner = self.inner.size_hint();
+        let mut hint = http_body::SizeHint::new();
+        hint.set_lower(inner.lower() + prefix);
+        if let Some(upper) = inner.upper() {
+            hint.set_upper(upper + prefix);
+        }
+        hint
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.prefix.is_none() && self.inner.is_end_stream()
+    }
+}
+
+// ===== impl TeeBody =====
+
+pin_project! {
+    struct TeeBody<B, W> {
+        #[pin]
+        inner: B,
+        sink: W,
+        step: TeeStep,
+    }
+}
+
+enum TeeStep {
+    Read,
+    /// Writing a chunk to the sink, `written` bytes of it so far.
+    Write {
+        chunk: Bytes,
+        written: usize,
+    },
+    /// Flushing the sink before yielding the frame ending the body, if any.
+    Flush {
+        last: Option<Frame<Bytes>>,
+    },
+    Done,
+}
+
+impl<B, W> HttpBody for TeeBody<B, W>
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+    W: AsyncWrite + Unpin,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        loop {
+            match this.step {
+                TeeStep::Read => match ready!(this.inner.as_mut().poll_frame(cx)) {
+                    Some(Ok(frame)) => match frame.into_data() {
+                        Ok(chunk) => *this.step = TeeStep::Write { chunk, written: 0 },
+                        Err(frame) => *this.step = TeeStep::Flush { last: Some(frame) },
+                    },
+                    Some(Err(err)) => {
+                        *this.step = TeeStep::Done;
+                        return Poll::Ready(Some(Err(err.into())));
+                    }
+                    None => *this.step = TeeStep::Flush { last: None },
+                },
+                TeeStep::Write { chunk, written } => {
+                    if *written == chunk.len() {
+                        let chunk = std::mem::take(chunk);
+                        *this.step = TeeStep::Read;
+                        return Poll::Ready(Some(Ok(Frame::data(chunk))));
+                    }
+                    match ready!(Pin::new(&mut *this.sink).poll_write(cx, &chunk[*written..])) {
+                        Ok(0) => return sink_failed(this.step, std::io::ErrorKind::WriteZero),
+                        Ok(n) => *written += n,
+                        Err(err) => return sink_failed(this.step, err),
+                    }
+                }
+                TeeStep::Flush { last } => {
+                    if let Err(err) = ready!(Pin::new(&mut *this.sink).poll_flush(cx)) {
+                        return sink_failed(this.step, err);
+                    }
+                    let last = last.take();
+                    // Trailers may still be followed by the end of the body.
+                    *this.step = match last {
+                        Some(_) => TeeStep::Read,
+                        None => TeeStep::Done,
+                    };
+                    return Poll::Ready(last.map(Ok));
+                }
+                TeeStep::Done => return Poll::Ready(None),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        // The sink is only flushed once the end of the body has been polled.
+        matches!(self.step, TeeStep::Done)
+    }
+}
+
+/// Ends a body whose sink failed with `err`.
+fn sink_failed<T>(
+    step: &mut TeeStep,
+    err: impl Into<std::io::Error>,
+) -> Poll<Option<Result<T, BoxError>>> {
+    let err: std::io::Error = err.into();
+    *step = TeeStep::Done;
+    Poll::Ready(Some(Err(err.into())))
+}
+
+// ===== impl DigestBody =====
+
+pin_project! {
+    struct DigestBody<B> {
+        #[pin]
+        inner: B,
+        hasher: Option<Hasher>,
+        digest: BodyDigest,
+    }
+}
+
+impl<B> HttpBody for DigestBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        match frame {
+            Some(Ok(ref frame)) => {
+                if let (Some(hasher), Some(data)) = (this.hasher.as_mut(), frame.data_ref()) {
+                    hasher.update(data);
+                }
+            }
+            Some(Err(_)) => *this.hasher = None,
+            None => {
+                if let Some(hasher) = this.hasher.take() {
+                    this.digest.set(hasher.finish());
+                }
+            }
+        }
+        Poll::Ready(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    use bytes::Bytes;
     use http_body::Body as _;
+    use tokio::io::AsyncWrite;
 
     use super::Body;
 
@@ -362,4 +630,68 @@ mod tests {
         assert!(stream_body.is_end_stream());
         assert_eq!(stream_body.size_hint().exact(), Some(0));
     }
+
+    #[tokio::test]
+    async fn body_tee() {
+        use http_body_util::BodyExt;
+
+        /// A sink taking at most 4 bytes per write.
+        struct Sink(Arc<Mutex<Vec<u8>>>);
+
+        impl AsyncWrite for Sink {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<std::io::Result<usize>> {
+                let n = buf.len().min(4);
+                self.0.lock().unwrap().extend_from_slice(&buf[..n]);
+                Poll::Ready(Ok(n))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+            ) -> Poll<std::io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let copy = Arc::new(Mutex::new(Vec::new()));
+        let body = Body::wrap(String::from("hello world")).tee(Sink(copy.clone()));
+        let bytes = body.collect().await.unwrap().to_bytes();
+        assert_eq!(bytes, "hello world");
+        assert_eq!(*copy.lock().unwrap(), b"hello world");
+    }
+
+    #[tokio::test]
+    async fn body_prepend() {
+        use http_body_util::BodyExt;
+
+        let body = Body::wrap(String::from(" world")).prepend(Bytes::from_static(b"hello"));
+        assert_eq!(body.size_hint().exact(), Some(11));
+        let bytes = body.collect().await.unwrap().to_bytes();
+        assert_eq!(bytes, "hello world");
+    }
+
+    #[tokio::test]
+    async fn body_with_digest() {
+        use http_body_util::BodyExt;
+
+        use crate::digest::DigestAlgorithm;
+
+        let (body, digest) = Body::from("hello").with_digest(DigestAlgorithm::Sha256);
+        let expected = digest.get().expect("buffered body digest is ready");
+        assert_eq!(body.as_bytes(), Some(&b"hello"[..]));
+
+        let (body, digest) = Body::wrap(String::from("hello")).with_digest(DigestAlgorithm::Sha256);
+        assert!(digest.get().is_none());
+        let bytes = body.collect().await.unwrap().to_bytes();
+        assert_eq!(bytes, "hello");
+        assert_eq!(digest.get(), Some(expected));
+    }
 }
@@ -0,0 +1,261 @@
+//! Chunked request bodies
+
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, ready},
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{Stream, TryStream, TryStreamExt};
+use http::HeaderMap;
+use http_body::{Body as HttpBody, Frame};
+use sync_wrapper::SyncWrapper;
+
+use crate::error::BoxError;
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<Chunk, BoxError>> + Send>>;
+
+/// A chunk of a [`ChunkedBody`], with its chunk extensions.
+///
+/// # Example
+///
+/// ```
+/// use wreq::Chunk;
+///
+/// let chunk = Chunk::new("hello").extension("chunk-signature", "ad80c730a21e5b8d");
+/// let last = Chunk::last().extension("chunk-signature", "b6c6ea8a5354eaf1");
+/// ```
+#[derive(Clone, Default)]
+pub struct Chunk {
+    data: Bytes,
+    extensions: BytesMut,
+    trailers: Option<HeaderMap>,
+    error: Option<&'static str>,
+}
+
+impl Chunk {
+    /// Creates a chunk of data.
+    ///
+    /// An empty chunk is the last chunk of the body.
+    pub fn new(data: impl Into<Bytes>) -> Chunk {
+        Chunk {
+            data: data.into(),
+            ..Chunk::default()
+        }
+    }
+
+    /// Creates the last, empty chunk of the body, which can carry extensions and trailers.
+    pub fn last() -> Chunk {
+        Chunk::default()
+    }
+
+    /// Adds an extension, sent as `;name=value` after the size of the chunk.
+    ///
+    /// The value is sent as a quoted string if it isn't a token. The body fails when the
+    /// name isn't a token, or the value contains control characters.
+    pub fn extension(mut self, name: &str, value: &str) -> Chunk {
+        if name.is_empty() || !name.bytes().all(is_tchar) {
+            self.error = Some("invalid chunk extension name");
+            return self;
+        }
+        if value.bytes().any(|b| b.is_ascii_control() && b != b'\t') {
+            self.error = Some("invalid chunk extension value");
+            return self;
+        }
+
+        self.extensions.put_u8(b';');
+        self.extensions.put_slice(name.as_bytes());
+        self.extensions.put_u8(b'=');
+        if !value.is_empty() && value.bytes().all(is_tchar) {
+            self.extensions.put_slice(value.as_bytes());
+        } else {
+            self.extensions.put_u8(b'"');
+            for b in value.bytes() {
+                if b == b'"' || b == b'\\' {
+                    self.extensions.put_u8(b'\\');
+                }
+                self.extensions.put_u8(b);
+            }
+            self.extensions.put_u8(b'"');
+        }
+        self
+    }
+
+    /// Sets the trailers sent after the last chunk.
+    ///
+    /// Trailers are only sent with the last chunk. Over HTTP/1, they must be announced in the
+    /// `Trailer` header of the request for servers to expect them.
+    pub fn trailers(mut self, trailers: HeaderMap) -> Chunk {
+        self.trailers = Some(trailers);
+        self
+    }
+
+    fn is_last(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Encodes the chunk as in the HTTP/1 chunked transfer coding.
+    fn encode(self) -> Bytes {
+        let len = self.data.len();
+        let mut buf = BytesMut::with_capacity(len + self.extensions.len() + 16);
+        buf.put_slice(format!("{len:X}").as_bytes());
+        buf.put_slice(&self.extensions);
+        buf.put_slice(b"\r\n");
+        if self.is_last() {
+            for (name, value) in self.trailers.iter().flatten() {
+                buf.put_slice(name.as_str().as_bytes());
+                buf.put_slice(b": ");
+                buf.put_slice(value.as_bytes());
+                buf.put_slice(b"\r\n");
+            }
+        } else {
+            buf.put_slice(&self.data);
+        }
+        buf.put_slice(b"\r\n");
+        buf.freeze()
+    }
+}
+
+impl fmt::Debug for Chunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chunk")
+            .field("len", &self.data.len())
+            .field("extensions", &self.extensions)
+            .field("trailers", &self.trailers)
+            .finish()
+    }
+}
+
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// A streaming request body whose chunks carry extensions, followed by trailers.
+///
+/// Over HTTP/1.1, the body is sent with the chunked transfer coding, each [`Chunk`] being sent
+/// as one chunk with its extensions, which protocols signing each chunk of an upload rely on.
+/// Over HTTP/2, chunk extensions don't exist: the data is sent as is, followed by the
+/// trailers.
+///
+/// Send it with [`RequestBuilder::chunked_body`](crate::RequestBuilder::chunked_body).
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> wreq::Result<()> {
+/// use wreq::{Chunk, ChunkedBody};
+///
+/// let chunks = futures_util::stream::iter([
+///     Ok::<_, std::io::Error>(Chunk::new("hello").extension("sig", "1")),
+///     Ok(Chunk::last().extension("sig", "2")),
+/// ]);
+/// let res = wreq::Client::new()
+///     .put("http://localhost/upload")
+///     .chunked_body(ChunkedBody::new(chunks))
+///     .send()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChunkedBody {
+    stream: SyncWrapper<ChunkStream>,
+    framing: Arc<AtomicBool>,
+    done: bool,
+}
+
+impl ChunkedBody {
+    /// Creates a body from a stream of chunks.
+    ///
+    /// The body ends with the first empty chunk, or with an empty chunk added when the stream
+    /// ends.
+    pub fn new<S>(chunks: S) -> ChunkedBody
+    where
+        S: TryStream<Ok = Chunk> + Send + 'static,
+        S::Error: Into<BoxError>,
+    {
+        let stream: ChunkStream = Box::pin(chunks.map_err(Into::into).into_stream());
+        ChunkedBody {
+            stream: SyncWrapper::new(stream),
+            framing: Arc::new(AtomicBool::new(false)),
+            done: false,
+        }
+    }
+
+    /// Returns the flag set by the connection when the body is sent with the chunked transfer
+    /// coding, in which case the body frames its chunks itself.
+    pub(crate) fn framing(&self) -> Arc<AtomicBool> {
+        self.framing.clone()
+    }
+}
+
+impl HttpBody for ChunkedBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let framed = this.framing.load(Ordering::Acquire);
+        let chunk = match ready!(this.stream.get_mut().as_mut().poll_next(cx)) {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+            None => Chunk::last(),
+        };
+        if let Some(err) = chunk.error {
+            return Poll::Ready(Some(Err(err.into())));
+        }
+
+        this.done = chunk.is_last();
+        if framed {
+            return Poll::Ready(Some(Ok(Frame::data(chunk.encode()))));
+        }
+
+        if chunk.is_last() {
+            Poll::Ready(chunk.trailers.map(|trailers| Ok(Frame::trailers(trailers))))
+        } else {
+            Poll::Ready(Some(Ok(Frame::data(chunk.data))))
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+impl fmt::Debug for ChunkedBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedBody").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Chunk;
+
+    #[test]
+    fn encode_chunks() {
+        let chunk = Chunk::new("hello")
+            .extension("sig", "abc")
+            .extension("note", "a b");
+        assert_eq!(&chunk.encode()[..], b"5;sig=abc;note=\"a b\"\r\nhello\r\n");
+
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("x-checksum", http::HeaderValue::from_static("42"));
+        let last = Chunk::last().extension("sig", "def").trailers(trailers);
+        assert_eq!(&last.encode()[..], b"0;sig=def\r\nx-checksum: 42\r\n\r\n");
+
+        assert!(Chunk::new("a").extension("bad name", "x").error.is_some());
+        assert!(Chunk::new("a").extension("sig", "x\r\n").error.is_some());
+    }
+}
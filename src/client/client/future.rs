@@ -1,9 +1,15 @@
 use std::{
+    error::Error as StdError,
+    future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, ready},
 };
 
-use http::Response as HttpResponse;
+use http::{Response as HttpResponse, StatusCode, Uri, header::PROXY_AUTHORIZATION};
 use pin_project_lite::pin_project;
 use tower::util::Oneshot;
 use url::Url;
@@ -14,10 +20,27 @@ use super::{
 };
 use crate::{
     Body, Error,
-    client::{body, middleware::redirect::RequestUri},
-    core::body::Incoming,
+    budget::BufferLimits,
+    client::{
+        body,
+        duplicates::DuplicateHeaders,
+        local::LocalResponse,
+        middleware::{context::RequestContext, redirect::RequestUri},
+        transfer::{Counted, TransferCounter},
+    },
+    connect::{Connector, SharedProxies},
+    core::{
+        body::Incoming,
+        client::{Client as NativeClient, Error as ClientError, connect::proxy::TunnelError},
+        ext::{ReplayHead, RequestConfig, RequestNewConnection, RequestProxyMatcher},
+    },
     error::BoxError,
     into_url::IntoUrlSealed,
+    profile::ProfileProbe,
+    proxy::{Matcher as ProxyMatcher, ProxyAuth, ProxyCredentials},
+    redirect::History,
+    scan::{BodyScanner, ScannedBody},
+    scheme::Handling,
 };
 
 pin_project! {
@@ -25,13 +48,20 @@ pin_project! {
     pub enum Pending {
         BoxedRequest {
             url: Option<Url>,
+            context: RequestContext,
+            scanner: Option<Arc<dyn BodyScanner>>,
             #[pin]
             fut: Oneshot<BoxedClientService, HttpRequest<Body>>,
         },
         GenericRequest {
             url: Option<Url>,
+            context: RequestContext,
+            scanner: Option<Arc<dyn BodyScanner>>,
             fut: Pin<Box<Oneshot<GenericClientService, HttpRequest<Body>>>>,
         },
+        Local {
+            fut: LocalResponse,
+        },
         Error {
             error: Option<Error>,
         },
@@ -44,6 +74,15 @@ pin_project! {
         Request {
             #[pin]
             fut: CoreResponseFuture,
+            probe: Option<ProfileProbe>,
+            duplicates: DuplicateHeaders,
+            replay: Option<Box<Replay>>,
+            buffer_limits: Option<BufferLimits>,
+            transfer: Option<TransferCounter>,
+            refresh: Option<Box<Refresh>>,
+        },
+        Handler {
+            fut: Handling,
         },
         Error {
             error: Option<Error>,
@@ -51,40 +90,193 @@ pin_project! {
     }
 }
 
+/// A copy of a request, sent again on a new connection if the server closes the reused
+/// connection the request is written to before responding, or if its proxy rejects its
+/// credentials.
+pub(super) struct Replay {
+    pub(super) client: NativeClient<Connector, Body>,
+    /// The head of the request, filled once it is known the request may have to be sent again.
+    pub(super) head: ReplayHead,
+    pub(super) body: Body,
+    pub(super) retries: Arc<AtomicU64>,
+    /// Whether the request can be sent again after a stale connection, as its method is
+    /// idempotent.
+    pub(super) idempotent: bool,
+    pub(super) reauth: Option<ProxyReauth>,
+}
+
+/// The proxy a request goes through, with the provider of new credentials for it.
+pub(super) struct ProxyReauth {
+    pub(super) credentials: Arc<dyn ProxyCredentials>,
+    pub(super) proxies: SharedProxies,
+    pub(super) matcher: ProxyMatcher,
+    pub(super) proxy: Uri,
+    /// Whether the request is forwarded to the proxy, rather than tunneled through it.
+    pub(super) forward: bool,
+}
+
+/// The credentials of a proxy being refreshed, along with the outcome of the request the proxy
+/// rejected, returned if no new credentials are provided.
+pub(super) struct Refresh {
+    fut: Pin<Box<dyn Future<Output = Result<Option<ProxyAuth>, BoxError>> + Send>>,
+    replay: Box<Replay>,
+    rejected: Result<HttpResponse<Incoming>, ClientError>,
+}
+
+impl Replay {
+    /// Starts refreshing the credentials of the proxy that rejected the request.
+    fn refresh(
+        self: Box<Self>,
+        rejected: Result<HttpResponse<Incoming>, ClientError>,
+    ) -> Box<Refresh> {
+        let reauth = self
+            .reauth
+            .as_ref()
+            .expect("replay of a request through a proxy");
+        trace!(
+            "proxy {} rejected its credentials, refreshing them",
+            reauth.proxy
+        );
+        Box::new(Refresh {
+            fut: reauth.credentials.refresh(reauth.proxy.clone()),
+            replay: self,
+            rejected,
+        })
+    }
+
+    /// Sends the request again with the new credentials of its proxy, which replace the
+    /// previous ones for the requests sent afterwards.
+    fn reauthorize(self: Box<Self>, auth: ProxyAuth) -> CoreResponseFuture {
+        let Replay {
+            client,
+            head,
+            body,
+            reauth,
+            ..
+        } = *self;
+        let head = head
+            .take()
+            .expect("request through a proxy is copied up front");
+        let mut req = HttpRequest::from_parts(head, body);
+
+        let auth = auth.into_header();
+        if let Some(reauth) = reauth {
+            let matcher = reauth.matcher.with_auth(auth.clone());
+            reauth.proxies.rcu(|proxies| {
+                proxies
+                    .iter()
+                    .map(|proxy| match *proxy == reauth.matcher {
+                        true => matcher.clone(),
+                        false => proxy.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            });
+            if reauth.forward {
+                req.headers_mut().insert(PROXY_AUTHORIZATION, auth);
+            }
+            RequestConfig::<RequestProxyMatcher>::get_mut(req.extensions_mut()).replace(matcher);
+        }
+
+        if let Some(context) = req.extensions().get::<RequestContext>() {
+            context.retried();
+        }
+        RequestConfig::<RequestNewConnection>::get_mut(req.extensions_mut()).replace(true);
+        client.request(req)
+    }
+}
+
 // ======== Pending impl ========
 
 impl Future for Pending {
     type Output = Result<Response, Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let (url, res) = match self.project() {
-            PendingProj::BoxedRequest { url, fut } => (url, fut.poll(cx)),
-            PendingProj::GenericRequest { url, fut } => (url, fut.as_mut().poll(cx)),
+        let (url, context, scanner, res) = match self.project() {
+            PendingProj::BoxedRequest {
+                url,
+                context,
+                scanner,
+                fut,
+            } => (url, context, scanner, fut.poll(cx)),
+            PendingProj::GenericRequest {
+                url,
+                context,
+                scanner,
+                fut,
+            } => (url, context, scanner, fut.as_mut().poll(cx)),
+            PendingProj::Local { fut } => return fut.as_mut().poll(cx),
             PendingProj::Error { error } => return Poll::Ready(Err(take_err!(error))),
         };
 
-        let res = match res {
-            Poll::Ready(Ok(res)) => res.map(body::boxed),
+        let mut res = match res {
+            Poll::Ready(Ok(res)) => {
+                let transfer = context.transfer();
+                // Without a decompression layer, the body is read as received.
+                #[cfg(not(any(
+                    feature = "gzip",
+                    feature = "zstd",
+                    feature = "brotli",
+                    feature = "deflate",
+                )))]
+                let res = res.map(|body| transfer.count(body, Counted::ResponseBody));
+                let mut res =
+                    res.map(|body| body::boxed(transfer.count(body, Counted::ResponseDecodedBody)));
+                res.extensions_mut().insert(transfer.clone());
+                res
+            }
             Poll::Ready(Err(err)) => {
                 let mut err = match err.downcast::<Error>() {
                     Ok(err) => *err,
                     Err(e) => Error::request(e),
                 };
 
+                // Report the last request of the redirect chain, whichever layer failed.
+                let snapshot = context.snapshot();
                 if err.url().is_none() {
-                    err = err.with_url(take_url!(url));
+                    let final_url = snapshot
+                        .uri
+                        .and_then(|uri| IntoUrlSealed::into_url(uri.to_string()).ok());
+                    err = match final_url {
+                        Some(final_url) => err.with_url(final_url),
+                        None => err.with_url(take_url!(url)),
+                    };
                 }
 
+                #[cfg(feature = "metrics")]
+                context.finished(None, None);
+
+                let err = err.with_context(snapshot.method, snapshot.attempt, snapshot.elapsed);
                 return Poll::Ready(Err(err));
             }
             Poll::Pending => return Poll::Pending,
         };
 
+        #[cfg(feature = "metrics")]
+        context.finished(
+            Some(res.status()),
+            res.extensions()
+                .get::<crate::core::ext::ConnectionInfo>()
+                .map(|conn| conn.was_reused()),
+        );
+
         if let Some(uri) = res.extensions().get::<RequestUri>() {
             *url = Some(IntoUrlSealed::into_url(uri.0.to_string())?);
         }
 
-        Poll::Ready(Ok(Response::new(res, take_url!(url))))
+        let history = context.history();
+        if !history.is_empty() {
+            res.extensions_mut().insert(History::new(history));
+        }
+
+        let url = take_url!(url);
+        let scan = scanner
+            .as_ref()
+            .and_then(|scanner| scanner.scan(&url, res.status(), res.headers()));
+        if let Some(scan) = scan {
+            res = res.map(|body| body::boxed(ScannedBody::new(body, scan)));
+        }
+
+        Poll::Ready(Ok(Response::new(res, url)))
     }
 }
 
@@ -95,16 +287,120 @@ impl Future for CorePending {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.project() {
-            CorePendingProj::Request { fut } => match fut.poll(cx) {
-                Poll::Ready(Ok(res)) => Poll::Ready(Ok(res)),
-                Poll::Ready(Err(err)) => Poll::Ready(Err(err.into())),
-                Poll::Pending => Poll::Pending,
+            CorePendingProj::Request {
+                mut fut,
+                probe,
+                duplicates,
+                replay,
+                buffer_limits,
+                transfer,
+                refresh,
+            } => loop {
+                let outcome = match refresh.as_mut() {
+                    Some(pending) => {
+                        let auth = ready!(pending.fut.as_mut().poll(cx));
+                        let Refresh {
+                            replay, rejected, ..
+                        } = *refresh.take().expect("refresh is pending");
+                        match auth {
+                            Ok(Some(auth)) => {
+                                fut.set(replay.reauthorize(auth));
+                                continue;
+                            }
+                            Ok(None) => rejected,
+                            Err(err) => {
+                                debug!("failed to refresh the proxy credentials: {}", err);
+                                rejected
+                            }
+                        }
+                    }
+                    None => ready!(fut.as_mut().poll(cx)),
+                };
+
+                return match outcome {
+                    Ok(mut res) => {
+                        if res.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+                            let forwarded = |replay: &mut Box<Replay>| {
+                                replay.reauth.as_ref().is_some_and(|reauth| reauth.forward)
+                            };
+                            if let Some(replay) = replay.take_if(forwarded) {
+                                *refresh = Some(replay.refresh(Ok(res)));
+                                continue;
+                            }
+                        }
+
+                        if let Some(probe) = probe.take() {
+                            probe.finish(&res);
+                        }
+                        if let Err(err) = duplicates.apply(res.headers_mut()) {
+                            return Poll::Ready(Err(Error::decode(err).into()));
+                        }
+                        if let Some(limits) = buffer_limits.take() {
+                            res.extensions_mut().insert(limits);
+                        }
+                        if let Some(transfer) = transfer.as_ref() {
+                            transfer.count_response(&res);
+                        }
+                        Poll::Ready(Ok(res))
+                    }
+                    Err(err) => {
+                        let stale = err.is_stale_connection();
+                        let replay_head = |replay: &mut Box<Replay>| {
+                            stale && replay.idempotent && replay.head.has_copy()
+                        };
+                        if let Some(replay) = replay.take_if(replay_head) {
+                            let Replay {
+                                client,
+                                head,
+                                body,
+                                retries,
+                                ..
+                            } = *replay;
+                            let head = head.take().expect("head of a replayed request");
+                            let mut req = HttpRequest::from_parts(head, body);
+                            trace!("reused connection closed before responding, sending again");
+                            retries.fetch_add(1, Ordering::Relaxed);
+                            if let Some(context) = req.extensions().get::<RequestContext>() {
+                                context.retried();
+                            }
+                            RequestConfig::<RequestNewConnection>::get_mut(req.extensions_mut())
+                                .replace(true);
+                            fut.set(client.request(req));
+                            continue;
+                        }
+                        if is_proxy_auth_required(&err) {
+                            if let Some(replay) = replay.take_if(|replay| replay.reauth.is_some()) {
+                                *refresh = Some(replay.refresh(Err(err)));
+                                continue;
+                            }
+                        }
+                        Poll::Ready(Err(err.into()))
+                    }
+                };
             },
+            CorePendingProj::Handler { fut } => {
+                fut.as_mut().poll(cx).map_ok(|res| res.map(Incoming::boxed))
+            }
             CorePendingProj::Error { error } => Poll::Ready(Err(take_err!(error).into())),
         }
     }
 }
 
+/// Returns true if a proxy refused to open a tunnel without valid credentials.
+fn is_proxy_auth_required(err: &ClientError) -> bool {
+    let mut source = err.source();
+
+    while let Some(err) = source {
+        if let Some(tunnel_err) = err.downcast_ref::<TunnelError>() {
+            return tunnel_err.is_proxy_auth_required();
+        }
+
+        source = err.source();
+    }
+
+    false
+}
+
 #[cfg(test)]
 mod test {
 
@@ -120,4 +416,13 @@ mod test {
         let err = crate::Client::new().get(u).send().await.unwrap_err();
         assert_eq!(err.url().map(AsRef::as_ref), Some(u), "{err:?}");
     }
+
+    #[tokio::test]
+    async fn error_has_request_context() {
+        let u = "http://does.not.exist.local/ever";
+        let err = crate::Client::new().post(u).send().await.unwrap_err();
+        assert_eq!(err.method(), Some(&crate::Method::POST), "{err:?}");
+        assert_eq!(err.attempt(), Some(1), "{err:?}");
+        assert!(err.elapsed().is_some(), "{err:?}");
+    }
 }
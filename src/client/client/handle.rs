@@ -0,0 +1,186 @@
+use std::{fmt, sync::Arc};
+
+use arc_swap::ArcSwap;
+use http::{HeaderMap, header::USER_AGENT};
+
+use crate::{
+    Body, Proxy,
+    config::ConfigSnapshot,
+    connect::{SharedProxies, SharedTls},
+    core::client::SharedPool,
+    proxy::Matcher as ProxyMatcher,
+    tls::CertStore,
+};
+
+/// A handle to update a subset of the settings of a [`Client`](super::Client) at runtime.
+///
+/// Updates apply to all clones of the client and take effect for requests sent afterwards,
+/// without rebuilding the client or dropping its connection pool. Connections already in the
+/// pool keep the proxy and certificates they were established with until they close.
+///
+/// Created with [`Client::config_handle`](super::Client::config_handle).
+///
+/// # Example
+///
+/// ```
+/// # fn run() -> wreq::Result<()> {
+/// use wreq::{Client, Proxy, header::HeaderMap};
+///
+/// let client = Client::new();
+/// let handle = client.config_handle();
+///
+/// // Reload the settings, e.g. on SIGHUP.
+/// handle.set_proxies([Proxy::all("http://proxy:8080")?]);
+/// handle.set_default_headers(HeaderMap::new());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ConfigHandle {
+    default_headers: Arc<ArcSwap<HeaderMap>>,
+    proxies: SharedProxies,
+    tls: SharedTls,
+}
+
+impl ConfigHandle {
+    pub(super) fn new(
+        default_headers: Arc<ArcSwap<HeaderMap>>,
+        proxies: SharedProxies,
+        tls: SharedTls,
+    ) -> ConfigHandle {
+        ConfigHandle {
+            default_headers,
+            proxies,
+            tls,
+        }
+    }
+
+    /// Returns the current default headers.
+    pub fn default_headers(&self) -> HeaderMap {
+        HeaderMap::clone(&self.default_headers.load())
+    }
+
+    /// Replaces the default headers, including the `User-Agent` set on the `ClientBuilder`.
+    pub fn set_default_headers(&self, headers: HeaderMap) {
+        self.default_headers.store(Arc::new(headers));
+    }
+
+    /// Replaces the list of proxies.
+    ///
+    /// Unlike on the `ClientBuilder`, an empty list does not enable the system proxy but
+    /// disables proxies entirely.
+    pub fn set_proxies<I>(&self, proxies: I)
+    where
+        I: IntoIterator<Item = Proxy>,
+    {
+        let proxies = proxies.into_iter().map(Proxy::into_matcher).collect();
+        self.proxies.store(Arc::new(proxies));
+    }
+
+    /// Reads the system proxy settings again, such as the `HTTP_PROXY`, `HTTPS_PROXY` and
+    /// `NO_PROXY` environment variables.
+    ///
+    /// This only has an effect when the client uses the system proxy, that is when no proxy
+    /// was configured on the `ClientBuilder`. Otherwise the proxies are left untouched.
+    pub fn reload_system_proxy(&self) {
+        let system = ProxyMatcher::system();
+        self.proxies.rcu(|proxies| {
+            proxies
+                .iter()
+                .map(|proxy| {
+                    if proxy.is_system() {
+                        system.clone()
+                    } else {
+                        proxy.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+        });
+    }
+
+    /// Replaces the certificate store used to verify servers on new TLS connections.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the TLS connector cannot be built with the new store, in which case
+    /// the previous store is kept.
+    pub fn set_cert_store(&self, cert_store: CertStore) -> crate::Result<()> {
+        let tls = self.tls.load().with_cert_store(cert_store)?;
+        self.tls.store(Arc::new(tls));
+        Ok(())
+    }
+}
+
+impl ConfigHandle {
+    /// Updates `snapshot` with the settings that can change at runtime.
+    pub(super) fn refresh_snapshot(&self, snapshot: &mut ConfigSnapshot) {
+        let headers = self.default_headers.load();
+        snapshot.user_agent = headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+        snapshot.default_headers = headers.keys().map(|name| name.to_string()).collect();
+
+        let proxies = self.proxies.load();
+        snapshot.system_proxy = proxies.iter().any(ProxyMatcher::is_system);
+        snapshot.proxies = proxies.iter().filter(|proxy| !proxy.is_system()).count();
+    }
+}
+
+impl fmt::Debug for ConfigHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigHandle")
+            .field("default_headers", &self.default_headers.load())
+            .finish()
+    }
+}
+
+/// A handle to the connection pool of a [`Client`](super::Client), to share it with other
+/// clients.
+///
+/// Frameworks building many specialized clients, each with its own default headers, middleware
+/// or redirect policy, can have them reuse the same connections instead of each opening its
+/// own. The pool keeps the idle timeout, size limits and connection events of the client it
+/// was taken from, and is closed once all the clients sharing it are dropped.
+///
+/// The clients sharing a pool should be built with the same emulation. Connections are only
+/// reused by the clients that established them with the same TLS settings, but the HTTP/1 and
+/// HTTP/2 settings, TCP options and certificates of the clients are not compared.
+///
+/// Created with [`Client::pool_handle`](super::Client::pool_handle), and shared with
+/// [`ClientBuilder::shared_pool`](super::ClientBuilder::shared_pool).
+///
+/// # Example
+///
+/// ```
+/// # fn run() -> wreq::Result<()> {
+/// use wreq::Client;
+///
+/// let client = Client::new();
+/// let api = Client::builder()
+///     .shared_pool(client.pool_handle())
+///     .default_headers(Default::default())
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PoolHandle {
+    pool: SharedPool<Body>,
+}
+
+impl PoolHandle {
+    pub(super) fn new(pool: SharedPool<Body>) -> PoolHandle {
+        PoolHandle { pool }
+    }
+
+    pub(super) fn into_inner(self) -> SharedPool<Body> {
+        self.pool
+    }
+}
+
+impl fmt::Debug for PoolHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolHandle").finish()
+    }
+}
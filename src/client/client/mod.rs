@@ -1,22 +1,31 @@
 #[macro_use]
 mod macros;
 mod future;
+mod handle;
 mod service;
 mod types;
 
 use std::{
     collections::HashMap,
     convert::TryInto,
+    hash::{DefaultHasher, Hash, Hasher},
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
     num::NonZeroU32,
-    sync::Arc,
+    ops::RangeBounds,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     task::{Context, Poll},
     time::Duration,
 };
 
+use arc_swap::ArcSwap;
 pub use future::Pending;
+pub use handle::{ConfigHandle, PoolHandle};
 use http::{
-    Request as HttpRequest, Response as HttpResponse,
+    Extensions, Request as HttpRequest, Response as HttpResponse, Uri,
     header::{HeaderMap, HeaderValue, USER_AGENT},
 };
 use service::{ClientConfig, ClientService};
@@ -27,7 +36,13 @@ use tower::{
 };
 use types::{BoxedClientService, BoxedClientServiceLayer, GenericClientService, ResponseBody};
 #[cfg(feature = "cookies")]
-use {super::middleware::cookie::CookieManagerLayer, crate::cookie};
+use {
+    super::{
+        middleware::{cookie::CookieManagerLayer, session::SessionManagerLayer},
+        session::Session,
+    },
+    crate::cookie,
+};
 
 #[cfg(any(
     feature = "gzip",
@@ -35,35 +50,68 @@ use {super::middleware::cookie::CookieManagerLayer, crate::cookie};
     feature = "brotli",
     feature = "deflate",
 ))]
-use super::middleware::decoder::{AcceptEncoding, DecompressionLayer};
+use super::middleware::decoder::{AcceptEncoding, ContentDecoders, DecompressionLayer};
+#[cfg(feature = "trace-context")]
+use super::middleware::trace_context::TraceContextLayer;
 #[cfg(feature = "websocket")]
 use super::websocket::WebSocketRequestBuilder;
 use super::{
-    Body, EmulationProviderFactory,
+    Body, EmulationProvider, EmulationProviderFactory,
+    conditional::{GetIfModified, Validators},
+    duplicates::DuplicateHeaders,
+    local::LocalUrls,
     middleware::{
+        context::RequestContext,
+        digest::ContentDigestLayer,
+        hints::ClientHintsLayer,
+        idempotency::IdempotencyKeyLayer,
+        limit::{InFlightLimitLayer, Scheduler},
+        logging::LoggingLayer,
         redirect::FollowRedirectLayer,
+        refresh::MetaRefreshLayer,
         retry::Http2RetryPolicy,
+        robots::RobotsExclusionLayer,
         timeout::{ResponseBodyTimeoutLayer, TimeoutLayer},
     },
-    request::{Request, RequestBuilder},
+    range::FetchRanges,
+    request::{PreparedRequest, Request, RequestBuilder},
     response::Response,
+    transfer::{Counters, TransferCounter, TransferStats},
+    user_agent::{RotationStrategy, UserAgent, UserAgentRotation},
 };
 #[cfg(feature = "hickory-dns")]
 use crate::dns::hickory::{HickoryDnsResolver, LookupIpStrategy};
+#[cfg(feature = "metrics")]
+use crate::metrics::{Metrics, TimedResolver};
 use crate::{
-    IntoUrl, Method, OriginalHeaders, Proxy,
+    ClientHints, IntoUrl, Method, OriginalHeaders, PoolHealthCheck, PoolStats, PoolWaiterOrder,
+    Proxy, Url, UrlPolicy,
+    budget::BufferBudget,
+    config::{ClientConfig, ConfigSnapshot, InvalidOption, InvalidOptions},
     connect::{BoxedConnectorLayer, BoxedConnectorService, Conn, Connector, Unnameable},
     core::{
         client::{Builder, Client as NativeClient, connect::TcpConnectOptions},
+        common::{buf::BufPool, timer},
         ext::RequestConfig,
-        rt::{TokioExecutor, tokio::TokioTimer},
+        proto::h1::MINIMUM_MAX_BUFFER_SIZE,
+        rt::{Clock, Timer, TokioExecutor, tokio::TokioTimer},
     },
+    decoding::{ContentDecoder, UnknownEncoding},
+    digest::DigestAlgorithm,
     dns::{DnsResolverWithOverrides, DynResolver, Resolve, gai::GaiResolver},
     error::{self, BoxError, Error},
+    events::ConnectionEvents,
     http1::Http1Config,
     http2::Http2Config,
-    proxy::Matcher as ProxyMatcher,
-    redirect::{self, RedirectPolicy},
+    idempotency::KeyGenerator,
+    logging::Logging,
+    profile::ProfileStore,
+    proxy::{Matcher as ProxyMatcher, ProxyCredentials},
+    redirect::{self, RedirectPolicy, ReferrerPolicy},
+    retry,
+    scan::BodyScanner,
+    scheme::{SchemeHandler, SchemeHandlers},
+    sign::Signer,
     tls::{
         AlpnProtocol, CertStore, CertificateInput, Identity, KeyLogPolicy, TlsConfig, TlsVersion,
     },
@@ -85,6 +133,18 @@ use crate::{
 #[derive(Clone)]
 pub struct Client {
     inner: Arc<ClientRef>,
+    native: NativeClient<Connector, Body>,
+    handle: ConfigHandle,
+    snapshot: Arc<ConfigSnapshot>,
+    extensions: Arc<Extensions>,
+    url_policy: Option<UrlPolicy>,
+    local_urls: LocalUrls,
+    user_agents: Option<Arc<UserAgentRotation>>,
+    stale_connection_retries: Arc<AtomicU64>,
+    transfer: Arc<Counters>,
+    body_scanner: Option<Arc<dyn BodyScanner>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -109,9 +169,13 @@ enum HttpVersionPref {
 }
 
 struct Config {
-    error: Option<Error>,
+    errors: Vec<InvalidOption>,
+    emulation_name: Option<String>,
     headers: HeaderMap,
+    extensions: Extensions,
     original_headers: Option<OriginalHeaders>,
+    client_hints: Option<ClientHints>,
+    user_agents: Option<UserAgentRotation>,
     #[cfg(any(
         feature = "gzip",
         feature = "zstd",
@@ -119,11 +183,52 @@ struct Config {
         feature = "deflate",
     ))]
     accept_encoding: AcceptEncoding,
+    accept_encoding_value: Option<HeaderValue>,
+    #[cfg(any(
+        feature = "gzip",
+        feature = "zstd",
+        feature = "brotli",
+        feature = "deflate",
+    ))]
+    max_decompression_ratio: Option<u32>,
+    #[cfg(any(
+        feature = "gzip",
+        feature = "zstd",
+        feature = "brotli",
+        feature = "deflate",
+    ))]
+    decompress_on_demand: bool,
+    #[cfg(any(
+        feature = "gzip",
+        feature = "zstd",
+        feature = "brotli",
+        feature = "deflate",
+    ))]
+    content_decoders: ContentDecoders,
+    buffer_pool: Option<BufPool>,
     connect_timeout: Option<Duration>,
     connection_verbose: bool,
+    connection_events: Option<Arc<dyn ConnectionEvents>>,
+    logging: Option<Logging>,
+    verify_digests: bool,
+    content_digest: Option<DigestAlgorithm>,
+    robots_cache_ttl: Duration,
     pool_idle_timeout: Option<Duration>,
+    pool_max_idle: usize,
     pool_max_idle_per_host: usize,
     pool_max_size: Option<NonZeroU32>,
+    pool_checkout_timeout: Option<Duration>,
+    pool_max_waiters_per_host: usize,
+    pool_waiter_order: PoolWaiterOrder,
+    pool_health_check: PoolHealthCheck,
+    keep_warm: Vec<(Uri, usize)>,
+    shared_pool: Option<PoolHandle>,
+    max_in_flight: Option<usize>,
+    in_flight_fairness: bool,
+    buffer_budget: Option<BufferBudget>,
+    crawl_delay: Duration,
+    #[cfg(feature = "psl")]
+    crawl_delay_sites: Option<cookie::PublicSuffixList>,
     tcp_nodelay: bool,
     tcp_reuse_address: bool,
     tcp_keepalive: Option<Duration>,
@@ -134,10 +239,18 @@ struct Config {
     tcp_user_timeout: Option<Duration>,
     proxies: Vec<ProxyMatcher>,
     auto_sys_proxy: bool,
+    proxy_credentials: Option<Arc<dyn ProxyCredentials>>,
     redirect_policy: redirect::Policy,
-    referer: bool,
+    redirect_drain_limit: usize,
+    redirect_downgrades: bool,
+    redirect_upgrades: bool,
+    refresh_limit: usize,
+    referrer_policy: ReferrerPolicy,
     timeout: Option<Duration>,
     read_timeout: Option<Duration>,
+    body_stall_timeout: Option<Duration>,
+    timer: timer::Timer,
+    clock: timer::Clock,
     #[cfg(feature = "cookies")]
     cookie_store: Option<Arc<dyn cookie::CookieStore>>,
     #[cfg(feature = "hickory-dns")]
@@ -146,9 +259,25 @@ struct Config {
     dns_resolver: Option<Arc<dyn Resolve>>,
     http_version_pref: HttpVersionPref,
     https_only: bool,
+    allow_host_override: bool,
+    url_policy: Option<UrlPolicy>,
+    local_urls: LocalUrls,
+    duplicate_headers: DuplicateHeaders,
     http1_config: Http1Config,
+    http1_read_buf_size: Option<usize>,
+    http1_max_buf_size: Option<usize>,
+    http1_writev: Option<bool>,
     http2_config: Http2Config,
-    http2_max_retry: usize,
+    retry_policy: retry::Policy,
+    signer: Option<Arc<dyn Signer>>,
+    idempotency_key: Option<Arc<dyn KeyGenerator>>,
+    origin_profiles: Option<Arc<dyn ProfileStore>>,
+    body_scanner: Option<Arc<dyn BodyScanner>>,
+    scheme_handlers: SchemeHandlers,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+    #[cfg(feature = "trace-context")]
+    trace_context: bool,
     request_layers: Option<Vec<BoxedClientServiceLayer>>,
     connector_layers: Option<Vec<BoxedConnectorLayer>>,
     builder: Builder,
@@ -164,6 +293,74 @@ struct Config {
     tls_config: TlsConfig,
 }
 
+impl Config {
+    /// Records the options that conflict with each other.
+    fn check_conflicts(&mut self) {
+        if let (Some(min), Some(max)) = (self.min_tls_version, self.max_tls_version) {
+            if min.ordinal() > max.ordinal() {
+                self.errors.push(InvalidOption::new(
+                    "min_tls_version",
+                    "minimum TLS version is above the maximum TLS version",
+                ));
+            }
+        }
+
+        if !self.keep_warm.is_empty() && self.pool_max_idle_per_host == 0 {
+            self.errors.push(InvalidOption::new(
+                "keep_warm",
+                "warm connections can't be kept without idle connections, see no_keepalive",
+            ));
+        }
+
+        if let Some(max) = self.http1_max_buf_size {
+            if max < MINIMUM_MAX_BUFFER_SIZE {
+                self.errors.push(InvalidOption::new(
+                    "http1_max_buf_size",
+                    format!(
+                        "the maximum buffer size can't be smaller than {MINIMUM_MAX_BUFFER_SIZE}"
+                    ),
+                ));
+            }
+        }
+
+        if self.in_flight_fairness && self.max_in_flight.is_none() {
+            self.errors.push(InvalidOption::new(
+                "in_flight_fairness",
+                "fairness only applies with max_in_flight",
+            ));
+        }
+    }
+
+    /// Takes a snapshot of the settings, before the client is built.
+    fn snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            emulation: self.emulation_name.clone(),
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            read_timeout: self.read_timeout,
+            pool_idle_timeout: self.pool_idle_timeout,
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+            pool_max_size: self.pool_max_size.map(NonZeroU32::get),
+            max_in_flight: self.max_in_flight,
+            http_version: match self.http_version_pref {
+                HttpVersionPref::Http1 => "http1",
+                HttpVersionPref::Http2 => "http2",
+                HttpVersionPref::All => "all",
+            },
+            https_only: self.https_only,
+            #[cfg(feature = "cookies")]
+            cookie_store: self.cookie_store.is_some(),
+            cert_verification: self.tls_cert_verification,
+            verify_hostname: self.tls_verify_hostname,
+            tls_sni: self.tls_sni,
+            tls_info: self.tls_info,
+            min_tls_version: self.min_tls_version.and_then(|version| version.name()),
+            max_tls_version: self.max_tls_version.and_then(|version| version.name()),
+            ..ConfigSnapshot::default()
+        }
+    }
+}
+
 impl Default for ClientBuilder {
     fn default() -> Self {
         Self::new()
@@ -177,9 +374,13 @@ impl ClientBuilder {
     pub fn new() -> ClientBuilder {
         ClientBuilder {
             config: Config {
-                error: None,
+                errors: Vec::new(),
+                emulation_name: None,
                 headers: HeaderMap::new(),
+                extensions: Extensions::new(),
                 original_headers: None,
+                client_hints: None,
+                user_agents: None,
                 #[cfg(any(
                     feature = "gzip",
                     feature = "zstd",
@@ -187,11 +388,52 @@ impl ClientBuilder {
                     feature = "deflate",
                 ))]
                 accept_encoding: AcceptEncoding::default(),
+                accept_encoding_value: None,
+                #[cfg(any(
+                    feature = "gzip",
+                    feature = "zstd",
+                    feature = "brotli",
+                    feature = "deflate",
+                ))]
+                max_decompression_ratio: None,
+                #[cfg(any(
+                    feature = "gzip",
+                    feature = "zstd",
+                    feature = "brotli",
+                    feature = "deflate",
+                ))]
+                decompress_on_demand: false,
+                #[cfg(any(
+                    feature = "gzip",
+                    feature = "zstd",
+                    feature = "brotli",
+                    feature = "deflate",
+                ))]
+                content_decoders: ContentDecoders::default(),
+                buffer_pool: None,
                 connect_timeout: None,
                 connection_verbose: false,
+                connection_events: None,
+                logging: None,
+                verify_digests: false,
+                content_digest: None,
+                robots_cache_ttl: Duration::from_secs(24 * 60 * 60),
                 pool_idle_timeout: Some(Duration::from_secs(90)),
+                pool_max_idle: usize::MAX,
                 pool_max_idle_per_host: usize::MAX,
                 pool_max_size: None,
+                pool_checkout_timeout: None,
+                pool_max_waiters_per_host: usize::MAX,
+                pool_waiter_order: PoolWaiterOrder::Fifo,
+                pool_health_check: PoolHealthCheck::None,
+                keep_warm: Vec::new(),
+                shared_pool: None,
+                max_in_flight: None,
+                in_flight_fairness: false,
+                buffer_budget: None,
+                crawl_delay: Duration::ZERO,
+                #[cfg(feature = "psl")]
+                crawl_delay_sites: None,
                 // TODO: Re-enable default duration once core's HttpConnector is fixed
                 // to no longer error when an option fails.
                 tcp_keepalive: None,
@@ -204,10 +446,18 @@ impl ClientBuilder {
                 tcp_user_timeout: None,
                 proxies: Vec::new(),
                 auto_sys_proxy: true,
+                proxy_credentials: None,
                 redirect_policy: redirect::Policy::default(),
-                referer: true,
+                redirect_drain_limit: 64 * 1024,
+                redirect_downgrades: true,
+                redirect_upgrades: true,
+                refresh_limit: 0,
+                referrer_policy: ReferrerPolicy::NoReferrerWhenDowngrade,
                 timeout: None,
                 read_timeout: None,
+                body_stall_timeout: None,
+                timer: timer::Timer::new(TokioTimer::new()),
+                clock: timer::Clock::default(),
                 #[cfg(feature = "hickory-dns")]
                 hickory_dns: cfg!(feature = "hickory-dns"),
                 #[cfg(feature = "cookies")]
@@ -217,9 +467,25 @@ impl ClientBuilder {
                 http_version_pref: HttpVersionPref::All,
                 builder: NativeClient::builder(TokioExecutor::new()),
                 https_only: false,
+                allow_host_override: false,
+                url_policy: None,
+                local_urls: LocalUrls::default(),
+                duplicate_headers: DuplicateHeaders::default(),
                 http1_config: Http1Config::default(),
+                http1_read_buf_size: None,
+                http1_max_buf_size: None,
+                http1_writev: None,
                 http2_config: Http2Config::default(),
-                http2_max_retry: 2,
+                retry_policy: retry::Policy::default(),
+                signer: None,
+                idempotency_key: None,
+                origin_profiles: None,
+                body_scanner: None,
+                scheme_handlers: SchemeHandlers::default(),
+                #[cfg(feature = "metrics")]
+                metrics: None,
+                #[cfg(feature = "trace-context")]
+                trace_context: false,
                 request_layers: None,
                 connector_layers: None,
                 tls_keylog_policy: None,
@@ -236,6 +502,90 @@ impl ClientBuilder {
         }
     }
 
+    /// Constructs a `ClientBuilder` with the settings of a [`ClientConfig`], such as one loaded
+    /// from a configuration file.
+    ///
+    /// See the [`config`](crate::config) module for details. A config naming an emulation
+    /// profile fails to build, as profiles are resolved by [`ClientBuilder::from_config_with`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "json")]
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config: wreq::config::ClientConfig =
+    ///     serde_json::from_str(r#"{ "timeout": "30s", "proxies": ["http://proxy:3128"] }"#)?;
+    /// let client = wreq::ClientBuilder::from_config(&config).build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_config(config: &ClientConfig) -> ClientBuilder {
+        ClientBuilder::from_config_with(config, |_| None)
+    }
+
+    /// Constructs a `ClientBuilder` with the settings of a [`ClientConfig`], resolving the name
+    /// of its emulation profile with `emulation`.
+    ///
+    /// A config naming an emulation profile for which `emulation` returns `None` fails to build.
+    pub fn from_config_with<F>(config: &ClientConfig, emulation: F) -> ClientBuilder
+    where
+        F: FnOnce(&str) -> Option<EmulationProvider>,
+    {
+        config.apply(ClientBuilder::new(), emulation)
+    }
+
+    /// Constructs a `ClientBuilder` with the settings of the `WREQ_*` environment variables.
+    ///
+    /// See [`ClientConfig::from_env`] for the variables. A variable naming an emulation profile
+    /// fails to build, as profiles are resolved by [`ClientBuilder::env_overrides`].
+    pub fn from_env() -> ClientBuilder {
+        ClientBuilder::new().env_overrides(|_| None)
+    }
+
+    /// Overrides the settings made so far with those of the `WREQ_*` environment variables,
+    /// resolving the name of the emulation profile with `emulation`.
+    ///
+    /// See [`ClientConfig::from_env`] for the variables. Settings made after this call
+    /// take precedence over the environment.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// // `WREQ_TIMEOUT=5s` overrides the timeout of 30 seconds.
+    /// let client = wreq::Client::builder()
+    ///     .timeout(Duration::from_secs(30))
+    ///     .env_overrides(|_| None)
+    ///     .build()?;
+    /// # Ok::<(), wreq::Error>(())
+    /// ```
+    pub fn env_overrides<F>(self, emulation: F) -> ClientBuilder
+    where
+        F: FnOnce(&str) -> Option<EmulationProvider>,
+    {
+        match ClientConfig::from_env() {
+            Ok(config) => config.apply(self, emulation),
+            Err(err) => self.with_error("env", err),
+        }
+    }
+
+    /// Records the name of the emulation profile, for [`Client::config_snapshot`].
+    pub(crate) fn emulation_name(mut self, name: &str) -> ClientBuilder {
+        self.config.emulation_name = Some(name.to_owned());
+        self
+    }
+
+    /// Records an invalid option, reported when the client is built.
+    pub(crate) fn with_error(
+        mut self,
+        option: &'static str,
+        err: impl Into<BoxError>,
+    ) -> ClientBuilder {
+        self.config.errors.push(InvalidOption::new(option, err));
+        self
+    }
+
     /// Returns a `Client` that uses this `ClientBuilder` configuration.
     ///
     /// # Errors
@@ -245,32 +595,76 @@ impl ClientBuilder {
     pub fn build(self) -> crate::Result<Client> {
         let mut config = self.config;
 
-        if let Some(err) = config.error {
-            return Err(err);
+        config.check_conflicts();
+        if !config.errors.is_empty() {
+            return Err(Error::builder(InvalidOptions(config.errors)));
+        }
+
+        // The requests sent outside of a session get a user agent of their own.
+        let user_agents = config.user_agents.take().map(Arc::new);
+        if let Some(ref user_agents) = user_agents {
+            let agent = user_agents.pick();
+            config.headers.insert(USER_AGENT, agent.value().clone());
+            if let Some(hints) = agent.client_hints() {
+                config.client_hints = Some(hints.clone());
+            }
+        }
+
+        // Without a decoder, the `Accept-Encoding` value is sent as a default header.
+        #[cfg(not(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        )))]
+        if let Some(value) = config.accept_encoding_value.take() {
+            config.headers.insert(http::header::ACCEPT_ENCODING, value);
         }
 
+        let snapshot = Arc::new(config.snapshot());
+
         let mut proxies = config.proxies;
         if config.auto_sys_proxy {
             proxies.push(ProxyMatcher::system());
         }
-        let proxies = Arc::new(proxies);
-        let proxies_maybe_http_auth = proxies.iter().any(ProxyMatcher::maybe_has_http_auth);
-        let proxies_maybe_http_custom_headers = proxies
-            .iter()
-            .any(ProxyMatcher::maybe_has_http_custom_headers);
+        let proxies = Arc::new(ArcSwap::from_pointee(proxies));
+        let default_headers = Arc::new(ArcSwap::from_pointee(config.headers));
+
+        // The buffer sizes set on the builder apply on top of the emulation.
+        if let Some(sz) = config.http1_read_buf_size {
+            config.http1_config.h1_read_buf_init_size = Some(sz);
+            config.http1_config.h1_read_buf_exact_size = None;
+        }
+        if let Some(max) = config.http1_max_buf_size {
+            config.http1_config.h1_max_buf_size = Some(max);
+            config.http1_config.h1_read_buf_exact_size = None;
+        }
+        if config.http1_writev.is_some() {
+            config.http1_config.h1_writev = config.http1_writev;
+        }
 
         config
             .builder
             .http1_config(config.http1_config)
             .http2_config(config.http2_config)
             .http2_only(matches!(config.http_version_pref, HttpVersionPref::Http2))
-            .http2_timer(TokioTimer::new())
-            .pool_timer(TokioTimer::new())
+            .http2_timer(config.timer.clone())
+            .pool_timer(config.timer.clone())
+            .pool_clock(config.clock.clone())
             .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle(config.pool_max_idle)
             .pool_max_idle_per_host(config.pool_max_idle_per_host)
-            .pool_max_size(config.pool_max_size);
+            .pool_max_size(config.pool_max_size)
+            .pool_checkout_timeout(config.pool_checkout_timeout)
+            .pool_max_waiters_per_host(config.pool_max_waiters_per_host)
+            .pool_waiter_order(config.pool_waiter_order)
+            .pool_health_check(config.pool_health_check)
+            .connection_events(config.connection_events.clone());
+        if let Some(pool) = &config.buffer_pool {
+            config.builder.read_buf_pool(pool.clone());
+        }
 
-        let connector = {
+        let (connector, tls, resolver) = {
             let resolver = {
                 let mut resolver: Arc<dyn Resolve> = match config.dns_resolver {
                     Some(dns_resolver) => dns_resolver,
@@ -287,6 +681,11 @@ impl ClientBuilder {
                         config.dns_overrides,
                     ));
                 }
+
+                #[cfg(feature = "metrics")]
+                if let Some(ref metrics) = config.metrics {
+                    resolver = Arc::new(TimedResolver::new(resolver, metrics.clone()));
+                }
                 DynResolver::new(resolver)
             };
 
@@ -300,7 +699,21 @@ impl ClientBuilder {
                 _ => {}
             }
 
-            Connector::builder(proxies.clone(), resolver)
+            // Clients sharing a pool only reuse the connections established with the same TLS
+            // settings.
+            let mut hasher = DefaultHasher::new();
+            config.tls_config.hash(&mut hasher);
+            (
+                config.min_tls_version,
+                config.max_tls_version,
+                config.tls_sni,
+                config.tls_verify_hostname,
+                config.tls_cert_verification,
+            )
+                .hash(&mut hasher);
+            config.builder.pool_partition(hasher.finish());
+
+            let (connector, tls) = Connector::builder(proxies.clone(), resolver.clone())
                 .connect_timeout(config.connect_timeout)
                 .tcp_keepalive(config.tcp_keepalive)
                 .tcp_keepalive_interval(config.tcp_keepalive_interval)
@@ -309,6 +722,7 @@ impl ClientBuilder {
                 .tcp_connect_options(config.tcp_connect_options)
                 .tcp_nodelay(config.tcp_nodelay)
                 .verbose(config.connection_verbose)
+                .connection_events(config.connection_events)
                 .tls_max_version(config.max_tls_version)
                 .tls_min_version(config.min_tls_version)
                 .tls_info(config.tls_info)
@@ -318,27 +732,73 @@ impl ClientBuilder {
                 .tls_cert_store(config.tls_cert_store)
                 .tls_identity(config.tls_identity)
                 .tls_keylog_policy(config.tls_keylog_policy)
+                .metrics(
+                    #[cfg(feature = "metrics")]
+                    config.metrics.clone(),
+                )
                 .tcp_user_timeout(
                     #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
                     config.tcp_user_timeout,
                 )
-                .build(config.tls_config, config.connector_layers)?
+                .build(config.tls_config, config.connector_layers)?;
+            (connector, tls, resolver)
         };
 
+        let native = config
+            .builder
+            .build_with_pool(connector, config.shared_pool.map(PoolHandle::into_inner));
+        native.keep_warm(config.keep_warm);
+        let stale_connection_retries = Arc::new(AtomicU64::new(0));
+
         let service = {
             let service = ClientService {
-                client: config.builder.build(connector),
+                client: native.clone(),
                 config: Arc::new(ClientConfig {
-                    default_headers: config.headers,
+                    default_headers: default_headers.clone(),
                     original_headers: RequestConfig::new(config.original_headers),
                     skip_default_headers: RequestConfig::default(),
+                    signer: RequestConfig::new(config.signer),
+                    buffer_budget: RequestConfig::new(config.buffer_budget),
                     https_only: config.https_only,
-                    proxies,
-                    proxies_maybe_http_auth,
-                    proxies_maybe_http_custom_headers,
+                    allow_host_override: config.allow_host_override,
+                    duplicate_headers: config.duplicate_headers,
+                    proxies: proxies.clone(),
+                    profiles: config.origin_profiles,
+                    stale_connection_retries: stale_connection_retries.clone(),
+                    scheme_handlers: config.scheme_handlers,
+                    resolver,
+                    proxy_credentials: config.proxy_credentials,
                 }),
             };
 
+            let service = ServiceBuilder::new()
+                .layer(LoggingLayer::new(config.logging, default_headers.clone()))
+                .service(service);
+
+            #[cfg(feature = "trace-context")]
+            let service = ServiceBuilder::new()
+                .layer(TraceContextLayer::new(config.trace_context))
+                .service(service);
+
+            let service = ServiceBuilder::new()
+                .layer(ContentDigestLayer::new(
+                    config.verify_digests,
+                    config.content_digest,
+                ))
+                .service(service);
+
+            let service = ServiceBuilder::new()
+                .layer(InFlightLimitLayer::new(
+                    config.max_in_flight,
+                    config.in_flight_fairness,
+                    Scheduler::new(
+                        config.crawl_delay,
+                        #[cfg(feature = "psl")]
+                        config.crawl_delay_sites,
+                    ),
+                ))
+                .service(service);
+
             #[cfg(any(
                 feature = "gzip",
                 feature = "zstd",
@@ -346,13 +806,22 @@ impl ClientBuilder {
                 feature = "deflate",
             ))]
             let service = ServiceBuilder::new()
-                .layer(DecompressionLayer::new(config.accept_encoding))
+                .layer(DecompressionLayer::new(
+                    config.accept_encoding,
+                    config.accept_encoding_value,
+                    config.content_decoders,
+                    config.max_decompression_ratio,
+                    config.decompress_on_demand,
+                    config.buffer_pool.clone(),
+                ))
                 .service(service);
 
             let service = ServiceBuilder::new()
                 .layer(ResponseBodyTimeoutLayer::new(
                     config.timeout,
                     config.read_timeout,
+                    config.body_stall_timeout,
+                    config.timer.clone(),
                 ))
                 .service(service);
 
@@ -361,18 +830,41 @@ impl ClientBuilder {
                 .layer(CookieManagerLayer::new(config.cookie_store))
                 .service(service);
 
+            let service = ServiceBuilder::new()
+                .layer(ClientHintsLayer::new(config.client_hints))
+                .service(service);
+
+            #[cfg(feature = "cookies")]
+            let service = ServiceBuilder::new()
+                .layer(SessionManagerLayer::new())
+                .service(service);
+
+            let service = ServiceBuilder::new()
+                .layer(RobotsExclusionLayer::new(config.robots_cache_ttl))
+                .service(service);
+
             let policy = RedirectPolicy::new(config.redirect_policy)
-                .with_referer(config.referer)
-                .with_https_only(config.https_only);
+                .with_referrer_policy(config.referrer_policy)
+                .with_https_only(config.https_only)
+                .with_url_policy(config.url_policy.clone())
+                .with_drain_limit(config.redirect_drain_limit)
+                .with_scheme_switches(config.redirect_downgrades, config.redirect_upgrades)
+                .with_refresh_limit(config.refresh_limit);
+
+            let service = ServiceBuilder::new()
+                .layer(MetaRefreshLayer::new(config.refresh_limit > 0))
+                .service(service);
 
             let service = ServiceBuilder::new()
                 .layer(FollowRedirectLayer::with_policy(policy))
                 .service(service);
 
             let service = ServiceBuilder::new()
-                .layer(RetryLayer::new(Http2RetryPolicy::new(
-                    config.http2_max_retry,
-                )))
+                .layer(RetryLayer::new(Http2RetryPolicy::new(config.retry_policy)))
+                .service(service);
+
+            let service = ServiceBuilder::new()
+                .layer(IdempotencyKeyLayer::new(config.idempotency_key))
                 .service(service);
 
             match config.request_layers {
@@ -385,7 +877,11 @@ impl ClientBuilder {
                     );
 
                     let service = ServiceBuilder::new()
-                        .layer(TimeoutLayer::new(config.timeout, config.read_timeout))
+                        .layer(TimeoutLayer::new(
+                            config.timeout,
+                            config.read_timeout,
+                            config.timer.clone(),
+                        ))
                         .service(service);
 
                     let service = ServiceBuilder::new()
@@ -396,7 +892,11 @@ impl ClientBuilder {
                 }
                 None => {
                     let service = ServiceBuilder::new()
-                        .layer(TimeoutLayer::new(config.timeout, config.read_timeout))
+                        .layer(TimeoutLayer::new(
+                            config.timeout,
+                            config.read_timeout,
+                            config.timer.clone(),
+                        ))
                         .service(service);
 
                     let service = ServiceBuilder::new()
@@ -410,6 +910,18 @@ impl ClientBuilder {
 
         Ok(Client {
             inner: Arc::new(service),
+            native,
+            handle: ConfigHandle::new(default_headers, proxies, tls),
+            snapshot,
+            extensions: Arc::new(config.extensions),
+            url_policy: config.url_policy,
+            local_urls: config.local_urls,
+            user_agents,
+            stale_connection_retries,
+            transfer: Arc::default(),
+            body_scanner: config.body_scanner,
+            #[cfg(feature = "metrics")]
+            metrics: config.metrics,
         })
     }
 
@@ -439,7 +951,10 @@ impl ClientBuilder {
                 self.config.headers.insert(USER_AGENT, value);
             }
             Err(err) => {
-                self.config.error = Some(Error::builder(err.into()));
+                let err: http::Error = err.into();
+                self.config
+                    .errors
+                    .push(InvalidOption::new("user_agent", err));
             }
         };
         self
@@ -490,12 +1005,74 @@ impl ClientBuilder {
         self
     }
 
+    /// Adds an extension inserted into the extensions of every request.
+    ///
+    /// This lets the layers added with [`ClientBuilder::layer`] receive per-client configuration,
+    /// such as a tenant ID, without global statics. An extension of the same type already set on
+    /// a request is kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[derive(Clone)]
+    /// struct TenantId(&'static str);
+    ///
+    /// let client = wreq::Client::builder()
+    ///     .default_extension(TenantId("acme"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn default_extension<T>(mut self, value: T) -> ClientBuilder
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.config.extensions.insert(value);
+        self
+    }
+
     /// Sets the original headers for every request.
     pub fn original_headers(mut self, original_headers: OriginalHeaders) -> ClientBuilder {
         self.config.original_headers = Some(original_headers);
         self
     }
 
+    /// Rotates the `User-Agent` between the sessions of the client.
+    ///
+    /// Each [`Session`](crate::Session) picks a user agent from `agents` when it is created,
+    /// and sends it on all its requests, so that an origin sees a consistent user agent for the
+    /// session while different sessions look like different browsers. The requests sent
+    /// outside of a session use the user agent picked when the client is built.
+    ///
+    /// When emulating a browser sending client hints, pair each user agent with the hints of
+    /// its browser through [`UserAgent::with_client_hints`], so that the `Sec-CH-UA` headers
+    /// match the rotated `User-Agent`.
+    ///
+    /// An empty list of agents disables the rotation.
+    pub fn user_agent_rotation<I, V>(
+        mut self,
+        agents: I,
+        strategy: RotationStrategy,
+    ) -> ClientBuilder
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<UserAgent>,
+    {
+        self.config.user_agents =
+            UserAgentRotation::new(agents.into_iter().map(Into::into).collect(), strategy);
+        self
+    }
+
+    /// Sets the client hints for every request.
+    ///
+    /// The low-entropy `Sec-CH-UA` hints are sent to secure origins, and the high-entropy hints
+    /// requested by an origin through `Accept-CH` are sent on subsequent requests to that origin.
+    ///
+    /// Client hints are usually configured through [`ClientBuilder::emulation`].
+    pub fn client_hints(mut self, hints: ClientHints) -> ClientBuilder {
+        self.config.client_hints = Some(hints);
+        self
+    }
+
     /// Enable a persistent cookie store for the client.
     ///
     /// Cookies received in responses will be preserved and included in
@@ -535,6 +1112,137 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a hook signing every request right before it is sent.
+    ///
+    /// See the [`sign`](crate::sign) module for details.
+    ///
+    /// By default, requests are not signed.
+    pub fn signer<S: Signer + 'static>(mut self, signer: S) -> ClientBuilder {
+        self.config.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Attach an `Idempotency-Key` header to every request with an unsafe method.
+    ///
+    /// See the [`idempotency`](crate::idempotency) module for details.
+    ///
+    /// By default, no key is attached.
+    pub fn idempotency_key<G: KeyGenerator + 'static>(mut self, generator: G) -> ClientBuilder {
+        self.config.idempotency_key = Some(Arc::new(generator));
+        self
+    }
+
+    /// Set a store of the facts learned about each origin the client talks to.
+    ///
+    /// See the [`profile`](crate::profile) module for details.
+    ///
+    /// By default, no profiles are kept.
+    pub fn origin_profiles<S: ProfileStore + 'static>(mut self, store: Arc<S>) -> ClientBuilder {
+        self.config.origin_profiles = Some(store as _);
+        self
+    }
+
+    /// Pass the decoded chunks of every response body through a scanner as they are read.
+    ///
+    /// See the [`scan`](crate::scan) module for details. A scanner set again replaces the
+    /// previous one.
+    ///
+    /// By default, bodies are not scanned.
+    pub fn body_scanner<S: BodyScanner + 'static>(mut self, scanner: S) -> ClientBuilder {
+        self.config.body_scanner = Some(Arc::new(scanner));
+        self
+    }
+
+    /// Dispatch the requests to the URLs of a non-HTTP scheme, such as `ftp`, to a handler.
+    ///
+    /// See the [`scheme`](crate::scheme) module for details. A handler registered again for
+    /// the same scheme replaces the previous one.
+    ///
+    /// # Errors
+    ///
+    /// [`ClientBuilder::build`] fails if the scheme is `http` or `https`, or not a valid scheme.
+    pub fn scheme_handler<H: SchemeHandler + 'static>(
+        mut self,
+        scheme: &str,
+        handler: H,
+    ) -> ClientBuilder {
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid {
+            return self.with_error("scheme_handler", format!("invalid scheme {scheme:?}"));
+        }
+        if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+            return self.with_error("scheme_handler", "the http schemes can't be handled");
+        }
+        self.config
+            .scheme_handlers
+            .insert(scheme, Arc::new(handler));
+        self
+    }
+
+    /// Enable or disable recording metrics to the globally installed `metrics` recorder.
+    ///
+    /// See the [`metrics`](https://docs.rs/metrics) crate to install a recorder, such as a
+    /// Prometheus exporter. The following metrics are emitted:
+    ///
+    /// - `wreq_requests_total` (counter, labels `method` and `status`): completed requests, with
+    ///   the status code of the final response or `error`.
+    /// - `wreq_request_duration_seconds` (histogram, label `method`): time from sending a request
+    ///   to receiving the headers of its final response, across redirects and retries.
+    /// - `wreq_connection_reuse_total` (counter, label `reused`): responses received on a pooled
+    ///   connection (`true`) or a new one (`false`).
+    /// - `wreq_open_connections` (gauge): connections currently open, idle in the pool or in use.
+    /// - `wreq_dns_lookup_duration_seconds` (histogram): DNS lookup latency.
+    /// - `wreq_retries_total` (counter): requests sent again by the retry policy.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `metrics` feature to be enabled.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn metrics(mut self, enable: bool) -> ClientBuilder {
+        self.config.metrics = enable.then(Metrics::global);
+        self
+    }
+
+    /// Record metrics to the given recorder instead of the globally installed one.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `metrics` feature to be enabled.
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn metrics_recorder<R>(mut self, recorder: R) -> ClientBuilder
+    where
+        R: ::metrics::Recorder + Send + Sync + 'static,
+    {
+        self.config.metrics = Some(Metrics::with_recorder(Arc::new(recorder)));
+        self
+    }
+
+    /// Enable or disable sending the `traceparent` and `tracestate` headers of the current
+    /// `tracing` span, as seen by OpenTelemetry through `tracing-opentelemetry`.
+    ///
+    /// The headers are added to every request sent while the span is entered, including
+    /// redirects and retries, unless the request already has a `traceparent` header. Nothing is
+    /// sent outside of a span with a valid OpenTelemetry context.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `trace-context` feature to be enabled.
+    #[cfg(feature = "trace-context")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "trace-context")))]
+    pub fn trace_context(mut self, enable: bool) -> ClientBuilder {
+        self.config.trace_context = enable;
+        self
+    }
+
     /// Enable auto gzip decompression by checking the `Content-Encoding` response header.
     ///
     /// If auto gzip decompression is turned on:
@@ -691,6 +1399,213 @@ impl ClientBuilder {
         }
     }
 
+    /// Sets the maximum ratio between the decompressed and the compressed size of a response
+    /// body.
+    ///
+    /// Reading an automatically decompressed body that expands past this ratio fails with a
+    /// decode error, which protects against decompression bombs. The limit is checked as the
+    /// body is read, over the bytes received so far.
+    ///
+    /// Default is no limit.
+    ///
+    /// This method exists even if none of the decompression features are enabled.
+    pub fn max_decompression_ratio(self, ratio: u32) -> ClientBuilder {
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        {
+            let mut this = self;
+            this.config.max_decompression_ratio = Some(ratio);
+            this
+        }
+
+        #[cfg(not(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        )))]
+        {
+            let _ = ratio;
+            self
+        }
+    }
+
+    /// Sets whether response bodies are decompressed only when read through a decoding method.
+    ///
+    /// The `Accept-Encoding` header is still sent, but compressed bodies are kept as received,
+    /// along with their `Content-Encoding` and `Content-Length` headers. They are decompressed
+    /// when read with [`Response::bytes`], [`Response::text`], [`Response::json`],
+    /// [`Response::chunk`] and the like, and left compressed when read with
+    /// [`Response::raw_bytes`] or when the response is converted into an [`http::Response`],
+    /// so that proxies and caches re-serving compressed bodies don't spend CPU decompressing
+    /// them.
+    ///
+    /// Default is `false`, decompressing bodies as soon as they are read.
+    ///
+    /// This method exists even if none of the decompression features are enabled.
+    pub fn decompress_on_demand(self, enabled: bool) -> ClientBuilder {
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        {
+            let mut this = self;
+            this.config.decompress_on_demand = enabled;
+            this
+        }
+
+        #[cfg(not(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        )))]
+        {
+            let _ = enabled;
+            self
+        }
+    }
+
+    /// Sets the exact `Accept-Encoding` header sent with every request.
+    ///
+    /// The value is sent as is, with its order and q-values, whichever decoders are enabled, so
+    /// that it can match the one of a browser while the client only decodes some of the codings
+    /// it lists. Bodies with a coding the client doesn't decode are handled as set with
+    /// [`ClientBuilder::unknown_encoding`]. An `Accept-Encoding` header set on a request takes
+    /// precedence.
+    ///
+    /// Default lists the codings the client decodes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let client = wreq::Client::builder()
+    ///     .accept_encoding("gzip, deflate, br, zstd")
+    ///     .build()?;
+    /// # Ok::<(), wreq::Error>(())
+    /// ```
+    pub fn accept_encoding<V>(mut self, value: V) -> ClientBuilder
+    where
+        V: TryInto<HeaderValue>,
+        V::Error: Into<http::Error>,
+    {
+        match value.try_into() {
+            Ok(value) => {
+                self.config.accept_encoding_value = Some(value);
+            }
+            Err(err) => {
+                let err: http::Error = err.into();
+                self.config
+                    .errors
+                    .push(InvalidOption::new("accept_encoding", err));
+            }
+        };
+        self
+    }
+
+    /// Registers a decoder for the response bodies of a content coding, such as `lzma`.
+    ///
+    /// The coding is announced in the `Accept-Encoding` header of every request, and the
+    /// `decoder` function creates a new decoder for each response body of the coding. A decoder
+    /// registered again for the same coding, or for a built-in one, replaces it. See the
+    /// [`decoding`](crate::decoding) module for details.
+    ///
+    /// This method exists even if none of the decompression features are enabled, but bodies
+    /// are only decoded with one of them.
+    ///
+    /// # Errors
+    ///
+    /// [`ClientBuilder::build`] fails if the coding is not a valid token.
+    pub fn content_decoder<F>(self, coding: &str, decoder: F) -> ClientBuilder
+    where
+        F: Fn() -> Box<dyn ContentDecoder> + Send + Sync + 'static,
+    {
+        let valid = !coding.is_empty()
+            && coding
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+        if !valid {
+            return self.with_error("content_decoder", format!("invalid coding {coding:?}"));
+        }
+
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        {
+            let mut this = self;
+            this.config
+                .content_decoders
+                .insert(coding, Arc::new(decoder));
+            this
+        }
+
+        #[cfg(not(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        )))]
+        {
+            let _ = decoder;
+            self
+        }
+    }
+
+    /// Sets what is done with a response body having a content coding the client doesn't
+    /// decode.
+    ///
+    /// Default passes the body through undecoded, see [`UnknownEncoding`].
+    ///
+    /// This method exists even if none of the decompression features are enabled.
+    pub fn unknown_encoding(self, policy: UnknownEncoding) -> ClientBuilder {
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        {
+            let mut this = self;
+            this.config.content_decoders.set_unknown(policy);
+            this
+        }
+
+        #[cfg(not(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        )))]
+        {
+            let _ = policy;
+            self
+        }
+    }
+
+    /// Recycles the buffers that response bodies are read into, keeping up to `capacity`
+    /// buffers of `chunk_size` bytes.
+    ///
+    /// HTTP/1 connections take their read buffer from the pool for each response and give it
+    /// back once the response is read, so idle connections hold none, and decompressed bodies
+    /// are decoded into pooled buffers. A buffer given back while chunks read into it are
+    /// alive is only reused once they have all been dropped, so this reduces the allocations
+    /// of clients sending many requests whose chunks are processed and dropped quickly.
+    ///
+    /// Default is no pool, allocating the buffers of each connection and body.
+    pub fn buffer_pool(mut self, capacity: usize, chunk_size: usize) -> ClientBuilder {
+        self.config.buffer_pool = Some(BufPool::new(capacity, chunk_size.max(1)));
+        self
+    }
+
     // Redirect options
 
     /// Set a `RedirectPolicy` for this client.
@@ -701,11 +1616,84 @@ impl ClientBuilder {
         self
     }
 
-    /// Enable or disable automatic setting of the `Referer` header.
+    /// Set how many bytes of a redirection response body are read and discarded before
+    /// following it.
+    ///
+    /// A body fully read within the limit lets its connection be reused for the redirected
+    /// request. A larger body, or one whose `Content-Length` exceeds the limit, is dropped
+    /// instead, closing its connection rather than downloading it. `0` never reads the body.
+    ///
+    /// Default is 64 KiB.
+    pub fn redirect_drain_limit(mut self, limit: usize) -> ClientBuilder {
+        self.config.redirect_drain_limit = limit;
+        self
+    }
+
+    /// Set whether redirects from `https` to `http` are followed.
+    ///
+    /// A blocked redirect fails with a
+    /// [`RedirectSchemeBlocked`](crate::redirect::RedirectSchemeBlocked) error. WebSocket
+    /// handshakes are sent over `http` and `https`, so this also covers `wss` to `ws`. Unlike
+    /// [`ClientBuilder::https_only`], it doesn't restrict the URL of the first request.
+    ///
+    /// Default is `true`.
+    pub fn redirect_downgrades(mut self, allow: bool) -> ClientBuilder {
+        self.config.redirect_downgrades = allow;
+        self
+    }
+
+    /// Set whether redirects from `http` to `https` are followed.
+    ///
+    /// A blocked redirect fails with a
+    /// [`RedirectSchemeBlocked`](crate::redirect::RedirectSchemeBlocked) error, which can be
+    /// useful to test or pin plain-text endpoints.
     ///
     /// Default is `true`.
-    pub fn referer(mut self, enable: bool) -> ClientBuilder {
-        self.config.referer = enable;
+    pub fn redirect_upgrades(mut self, allow: bool) -> ClientBuilder {
+        self.config.redirect_upgrades = allow;
+        self
+    }
+
+    /// Set how many page refreshes are followed like redirects in a redirect chain.
+    ///
+    /// A `200 OK` HTML page refreshing to another URL, with a `Refresh` header or a
+    /// `<meta http-equiv="refresh">` tag, is then navigated to with a `GET` request, as a
+    /// browser without scripts would, whatever delay it asks for. The first 32 KiB of the page
+    /// are searched for the tag, including `<noscript>` elements. Past the limit, the page is
+    /// returned as-is.
+    ///
+    /// Refreshes go through the [`redirect::Policy`] like other redirects and are listed in the
+    /// [`History`](crate::redirect::History) with a `200` status. A refresh without a URL,
+    /// which reloads the page, is never followed.
+    ///
+    /// Default is `0`, not following refreshes.
+    pub fn refresh_redirects(mut self, limit: usize) -> ClientBuilder {
+        self.config.refresh_limit = limit;
+        self
+    }
+
+    /// Set the [`ReferrerPolicy`] used to set the `Referer` header when following redirects.
+    ///
+    /// A `bool` is also accepted: `true` maps to
+    /// [`ReferrerPolicy::NoReferrerWhenDowngrade`] and `false` to [`ReferrerPolicy::NoReferrer`].
+    ///
+    /// Default is [`ReferrerPolicy::NoReferrerWhenDowngrade`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use wreq::redirect::ReferrerPolicy;
+    ///
+    /// let client = wreq::Client::builder()
+    ///     .referer(ReferrerPolicy::StrictOriginWhenCrossOrigin)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn referer<P>(mut self, policy: P) -> ClientBuilder
+    where
+        P: Into<ReferrerPolicy>,
+    {
+        self.config.referrer_policy = policy.into();
         self
     }
 
@@ -746,6 +1734,40 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a provider of new credentials for the proxies rejecting theirs.
+    ///
+    /// When a proxy answers a request with `407 Proxy Authentication Required`, or refuses to
+    /// open a tunnel with it, the provider is asked for new credentials for the proxy. The
+    /// request is then sent once more with them, on a new connection, and they replace the
+    /// previous credentials of the proxy for the requests sent afterwards. If the provider
+    /// returns `None` or fails, the request ends with the proxy's rejection.
+    ///
+    /// While a provider is set, each request through a proxy holds a copy of its headers and
+    /// extensions until its response arrives, so that it can be sent again. Requests with a
+    /// streaming body can't be sent again.
+    ///
+    /// Default is no provider.
+    ///
+    /// # Example
+    /// ```
+    /// use wreq::{Client, Proxy, ProxyAuth};
+    ///
+    /// let client = Client::builder()
+    ///     .proxy(Proxy::all("http://proxy:8080").unwrap())
+    ///     .proxy_credentials(|_proxy: http::Uri| async {
+    ///         Ok(Some(ProxyAuth::basic("user", "new-password")))
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn proxy_credentials<P: ProxyCredentials + 'static>(
+        mut self,
+        provider: P,
+    ) -> ClientBuilder {
+        self.config.proxy_credentials = Some(Arc::new(provider));
+        self
+    }
+
     // Timeout options
 
     /// Enables a request timeout.
@@ -753,17 +1775,57 @@ impl ClientBuilder {
     /// The timeout is applied from when the request starts connecting until the
     /// response body has finished.
     ///
-    /// Default is no timeout.
-    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
-        self.config.timeout = Some(timeout);
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for only the read phase of a `Client`.
+    ///
+    /// Default is `None`.
+    pub fn read_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set a timeout for the response body to make progress.
+    ///
+    /// The response is aborted with a timeout error when no body bytes arrive for the given
+    /// duration while the body is being read. Unlike the read timeout, it does not apply to
+    /// the response headers and is not reset by frames carrying no data, which catches servers
+    /// keeping the connection open but no longer sending.
+    ///
+    /// Default is `None`.
+    pub fn body_stall_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.body_stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timer that the request and response body timeouts, the idle timeout of pooled
+    /// connections and HTTP/2 keep-alive pings are timed with.
+    ///
+    /// Default is a timer of the tokio runtime.
+    pub fn timer<T>(mut self, timer: T) -> ClientBuilder
+    where
+        T: Timer + Send + Sync + 'static,
+    {
+        self.config.timer = timer::Timer::new(timer);
         self
     }
 
-    /// Set a timeout for only the read phase of a `Client`.
+    /// Sets the clock that the idle times of pooled connections are measured with.
     ///
-    /// Default is `None`.
-    pub fn read_timeout(mut self, timeout: Duration) -> ClientBuilder {
-        self.config.read_timeout = Some(timeout);
+    /// A [`TokioClock`](crate::rt::TokioClock) along with the default timer lets tests pause
+    /// and advance the time with `tokio::time::pause`. Cookie jars take their own clock, see
+    /// [`Jar::with_clock`](crate::cookie::Jar::with_clock).
+    ///
+    /// Default is the system clock.
+    pub fn clock<C>(mut self, clock: C) -> ClientBuilder
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.config.clock = timer::Clock::new(clock);
         self
     }
 
@@ -791,6 +1853,55 @@ impl ClientBuilder {
         self
     }
 
+    /// Set a listener notified of the lifecycle of the connections of this client.
+    ///
+    /// See the [`events`](crate::events) module for details.
+    pub fn connection_events<E>(mut self, events: E) -> ClientBuilder
+    where
+        E: ConnectionEvents + 'static,
+    {
+        self.config.connection_events = Some(Arc::new(events));
+        self
+    }
+
+    /// Hand a structured record of every request sent on the wire to a logger.
+    ///
+    /// See the [`logging`](crate::logging) module for details.
+    pub fn logging(mut self, logging: Logging) -> ClientBuilder {
+        self.config.logging = Some(logging);
+        self
+    }
+
+    /// Verify response bodies against their `Content-Digest` and `Repr-Digest` headers.
+    ///
+    /// The body is hashed while it is read, before any decompression, and reading it fails
+    /// with a [decode error](Error::is_decode) at its end if it doesn't match, see
+    /// [`Error::digest_mismatch`]. Digests with an unsupported algorithm are ignored.
+    ///
+    /// Default is `false`.
+    pub fn verify_digests(mut self, enabled: bool) -> ClientBuilder {
+        self.config.verify_digests = enabled;
+        self
+    }
+
+    /// Add a `Content-Digest` header computed with `algorithm` to requests.
+    ///
+    /// Only requests with a buffered body, and no `Content-Digest` header of their own, get
+    /// the header; use [`Body::with_digest`] to compute the digest of a streaming body.
+    pub fn content_digest(mut self, algorithm: DigestAlgorithm) -> ClientBuilder {
+        self.config.content_digest = Some(algorithm);
+        self
+    }
+
+    /// Set how long robots.txt files are cached for requests sent with
+    /// [`RequestBuilder::respect_robots`].
+    ///
+    /// Default is 24 hours.
+    pub fn robots_cache_ttl(mut self, ttl: Duration) -> ClientBuilder {
+        self.config.robots_cache_ttl = ttl;
+        self
+    }
+
     // HTTP options
 
     /// Set an optional timeout for idle sockets being kept-alive.
@@ -806,6 +1917,20 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the maximum idle connections kept in the pool, across all hosts.
+    ///
+    /// Unlike [`ClientBuilder::pool_max_idle_per_host`], this bounds the sockets held by a
+    /// client talking to many distinct hosts. When the pool is full, the connection idle the
+    /// longest is closed, and reported to the
+    /// [`ConnectionEvents`](crate::events::ConnectionEvents) with
+    /// [`EvictionReason::IdleLimit`](crate::events::EvictionReason::IdleLimit).
+    ///
+    /// Default is no limit.
+    pub fn pool_max_idle(mut self, max: usize) -> ClientBuilder {
+        self.config.pool_max_idle = max;
+        self
+    }
+
     /// Sets the maximum idle connection per host allowed in the pool.
     pub fn pool_max_idle_per_host(mut self, max: usize) -> ClientBuilder {
         self.config.pool_max_idle_per_host = max;
@@ -818,6 +1943,152 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the maximum number of requests per host waiting for a connection to be returned
+    /// to the pool.
+    ///
+    /// Requests beyond this limit do not wait on the pool and only use a new connection.
+    ///
+    /// Default is no limit.
+    pub fn pool_max_waiters_per_host(mut self, max: usize) -> ClientBuilder {
+        self.config.pool_max_waiters_per_host = max;
+        self
+    }
+
+    /// Sets which waiting request a connection returned to the pool is handed to.
+    ///
+    /// Default is [`PoolWaiterOrder::Fifo`].
+    pub fn pool_waiter_order(mut self, order: PoolWaiterOrder) -> ClientBuilder {
+        self.config.pool_waiter_order = order;
+        self
+    }
+
+    /// Sets how idle HTTP/1 connections are probed before they are reused.
+    ///
+    /// Connections dropped by middleboxes while idle are otherwise only detected when a request
+    /// sent on them fails. A connection failing the probe is discarded and the request is sent
+    /// on another connection.
+    ///
+    /// Default is [`PoolHealthCheck::None`].
+    pub fn pool_health_check(mut self, check: PoolHealthCheck) -> ClientBuilder {
+        self.config.pool_health_check = check;
+        self
+    }
+
+    /// Keeps `count` idle connections to the origin of `url` in the pool.
+    ///
+    /// A background task dials new connections whenever idle ones expire or are closed, so
+    /// requests to this origin do not pay the connection handshake cost. Only requests using
+    /// the client's connection settings reuse these connections. For an origin negotiating
+    /// HTTP/2 a single connection is kept, since it is shared by all requests.
+    ///
+    /// The client must be built within a Tokio runtime when this option is used.
+    pub fn keep_warm<U: IntoUrl>(mut self, url: U, count: usize) -> ClientBuilder {
+        match url
+            .into_url()
+            .and_then(|url| Uri::try_from(url.as_str()).map_err(Error::builder))
+        {
+            Ok(uri) => self.config.keep_warm.push((uri, count)),
+            Err(err) => self
+                .config
+                .errors
+                .push(InvalidOption::new("keep_warm", err)),
+        }
+        self
+    }
+
+    /// Reuses the connection pool of another client instead of creating one.
+    ///
+    /// The idle timeout, size limits and connection events of the pool are those of the client
+    /// it was taken from, so the other pool settings of this builder are ignored. Connections
+    /// are only reused by the clients that established them with the same TLS settings. See
+    /// [`PoolHandle`] for details.
+    pub fn shared_pool(mut self, pool: PoolHandle) -> ClientBuilder {
+        self.config.shared_pool = Some(pool);
+        self
+    }
+
+    /// Set a timeout for waiting on a connection, either an idle one from the pool or a newly
+    /// connected one.
+    ///
+    /// Unlike the request [`timeout`](ClientBuilder::timeout), this only bounds the time spent
+    /// before the request is sent. When it elapses the request fails with an error for which
+    /// [`Error::is_pool_timeout`](crate::Error::is_pool_timeout) returns true, and
+    /// [`Error::pool_stats`](crate::Error::pool_stats) describes the state of the pool.
+    ///
+    /// Default is no timeout.
+    pub fn pool_checkout_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.pool_checkout_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of requests awaiting their response at once.
+    ///
+    /// Further requests wait in a queue until a request in flight receives its response headers
+    /// or fails. Each hop of a redirect chain and each retry is queued on its own, and the time
+    /// spent waiting counts towards the request timeout.
+    ///
+    /// Queued requests are served by their [`Priority`](crate::Priority) urgency first, so that
+    /// interactive requests can jump ahead of bulk ones with
+    /// [`RequestBuilder::priority`](crate::RequestBuilder::priority).
+    ///
+    /// By default, the number of requests in flight is not limited.
+    pub fn max_in_flight(mut self, max: usize) -> ClientBuilder {
+        self.config.max_in_flight = Some(max.max(1));
+        self
+    }
+
+    /// Sets whether waiting requests are served in round-robin across hosts, so that a burst of
+    /// requests to one host does not hold back the requests to other hosts.
+    ///
+    /// This only applies with [`ClientBuilder::max_in_flight`]. Default is `false`, serving
+    /// waiting requests in the order they were sent.
+    pub fn in_flight_fairness(mut self, enabled: bool) -> ClientBuilder {
+        self.config.in_flight_fairness = enabled;
+        self
+    }
+
+    /// Bounds the memory held by the response bodies buffered with `bytes()`, `text()` or
+    /// `json()`, across all the requests of this client.
+    ///
+    /// The budget can be shared with other clients by cloning it. See the
+    /// [`budget`](crate::budget) module for details.
+    ///
+    /// By default, buffered bodies are not bounded.
+    pub fn buffer_budget(mut self, budget: BufferBudget) -> ClientBuilder {
+        self.config.buffer_budget = Some(budget);
+        self
+    }
+
+    /// Sets the minimum delay between two requests to the same site.
+    ///
+    /// Requests are scheduled when they are sent, each one being due at least `delay` after
+    /// the previous one to its host, or to its registrable domain with
+    /// [`ClientBuilder::crawl_delay_sites`]. A request waits until it is due before waiting for
+    /// a permit of [`ClientBuilder::max_in_flight`], and the time spent waiting counts towards
+    /// the request timeout. Each hop of a redirect chain and each retry is scheduled on its own.
+    ///
+    /// Requests sent with [`RequestBuilder::respect_robots`] also honor the `crawl-delay` of
+    /// the robots.txt of their origin, when it is longer.
+    ///
+    /// By default, requests are not delayed.
+    pub fn crawl_delay(mut self, delay: Duration) -> ClientBuilder {
+        self.config.crawl_delay = delay;
+        self
+    }
+
+    /// Groups hosts by their registrable domain for [`ClientBuilder::crawl_delay`], so that
+    /// `a.example.co.uk` and `b.example.co.uk` share their delay.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `psl` feature to be enabled.
+    #[cfg(feature = "psl")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "psl")))]
+    pub fn crawl_delay_sites(mut self, list: cookie::PublicSuffixList) -> ClientBuilder {
+        self.config.crawl_delay_sites = Some(list);
+        self
+    }
+
     /// Disable keep-alive for the client.
     pub fn no_keepalive(mut self) -> ClientBuilder {
         self.config.pool_max_idle_per_host = 0;
@@ -831,6 +2102,53 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the size of the read buffer HTTP/1 connections start with.
+    ///
+    /// The read buffer grows as large reads fill it, up to [`ClientBuilder::http1_max_buf_size`],
+    /// and shrinks back when reads get smaller, though never below this size. A larger size
+    /// lets large downloads be read in fewer calls, at the cost of the memory held by each
+    /// connection.
+    ///
+    /// This applies on top of the HTTP/1 settings of an emulation.
+    ///
+    /// Default is 8 KiB.
+    pub fn http1_read_buf_size(mut self, size: usize) -> ClientBuilder {
+        self.config.http1_read_buf_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum size of the read and write buffers of HTTP/1 connections.
+    ///
+    /// A response head that doesn't fit in the read buffer fails, and the write buffer is
+    /// flushed once it reaches this size. A smaller size bounds the memory held by each
+    /// connection when many connections are open.
+    ///
+    /// This applies on top of the HTTP/1 settings of an emulation.
+    ///
+    /// Default is ~400 KiB.
+    ///
+    /// # Errors
+    ///
+    /// [`ClientBuilder::build`] fails if `max` is smaller than 8 KiB.
+    pub fn http1_max_buf_size(mut self, max: usize) -> ClientBuilder {
+        self.config.http1_max_buf_size = Some(max);
+        self
+    }
+
+    /// Sets whether HTTP/1 connections queue the buffers of a request for vectored writes,
+    /// or flatten them into a single buffer.
+    ///
+    /// Flattening copies the body data, but may write faster to transports that don't support
+    /// vectored writes well, such as most TLS implementations.
+    ///
+    /// This applies on top of the HTTP/1 settings of an emulation.
+    ///
+    /// Default is to pick the strategy from the transport.
+    pub fn http1_writev(mut self, enabled: bool) -> ClientBuilder {
+        self.config.http1_writev = Some(enabled);
+        self
+    }
+
     /// Only use HTTP/2.
     pub fn http2_only(mut self) -> ClientBuilder {
         self.config.http_version_pref = HttpVersionPref::Http2;
@@ -838,8 +2156,19 @@ impl ClientBuilder {
     }
 
     /// Sets the maximum number of safe retries for HTTP/2 connections.
-    pub fn http2_max_retry(mut self, max: usize) -> ClientBuilder {
-        self.config.http2_max_retry = max;
+    ///
+    /// This is a shorthand for `retry(retry::Policy::limited(max))`.
+    pub fn http2_max_retry(self, max: usize) -> ClientBuilder {
+        self.retry(retry::Policy::limited(max))
+    }
+
+    /// Set a [`retry::Policy`] for this client.
+    ///
+    /// Requests can override it with [`RequestBuilder::retry`](crate::RequestBuilder::retry).
+    ///
+    /// Default will retry a request up to 2 times.
+    pub fn retry(mut self, policy: retry::Policy) -> ClientBuilder {
+        self.config.retry_policy = policy;
         self
     }
 
@@ -1024,6 +2353,10 @@ impl ClientBuilder {
             );
         }
 
+        if emulation.client_hints.is_some() {
+            swap(&mut self.config.client_hints, &mut emulation.client_hints);
+        }
+
         if let Some(mut http1_config) = emulation.http1_config.take() {
             swap(&mut self.config.http1_config, &mut http1_config);
         }
@@ -1060,7 +2393,10 @@ impl ClientBuilder {
             Ok(store) => {
                 self.config.tls_cert_store = store;
             }
-            Err(err) => self.config.error = Some(err),
+            Err(err) => self
+                .config
+                .errors
+                .push(InvalidOption::new("ssl_pinning", err)),
         }
         self
     }
@@ -1169,6 +2505,76 @@ impl ClientBuilder {
         self
     }
 
+    /// Allow requests to send another host than the one of their URL, with
+    /// [`RequestBuilder::host_header`].
+    ///
+    /// Overriding the host makes a server route a request to another virtual host than the
+    /// one the connection and its certificate were established for, so it is denied unless
+    /// explicitly allowed.
+    ///
+    /// Defaults to false.
+    pub fn allow_host_override(mut self, enabled: bool) -> ClientBuilder {
+        self.config.allow_host_override = enabled;
+        self
+    }
+
+    /// Set how headers received more than once in a response are handled.
+    ///
+    /// Default keeps every value, see [`DuplicateHeaders`].
+    pub fn duplicate_headers(mut self, policy: DuplicateHeaders) -> ClientBuilder {
+        self.config.duplicate_headers = policy;
+        self
+    }
+
+    /// Set the [`UrlPolicy`] applied to the URL of every request and redirect.
+    ///
+    /// A URL breaking the policy fails the request with a builder error. Use
+    /// [`UrlPolicy::strict`] when requesting untrusted URLs from a server.
+    ///
+    /// Default accepts any URL as is.
+    pub fn url_policy(mut self, policy: UrlPolicy) -> ClientBuilder {
+        self.config.url_policy = Some(policy);
+        self
+    }
+
+    /// Enables responding to `data:` URLs with their decoded content.
+    ///
+    /// The response is built by the client, with the media type of the URL as `Content-Type`,
+    /// without going through the network, the layers of the client or the [`UrlPolicy`]. A
+    /// malformed `data:` URL fails the request with a builder error.
+    ///
+    /// Default is `false`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> wreq::Result<()> {
+    /// let client = wreq::Client::builder().data_urls(true).build()?;
+    /// let text = client.get("data:text/plain,Hello%20World").send().await?.text().await?;
+    /// assert_eq!(text, "Hello World");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn data_urls(mut self, enabled: bool) -> ClientBuilder {
+        self.config.local_urls.set_data(enabled);
+        self
+    }
+
+    /// Enables responding to `file:` URLs with the content of the files under `root`.
+    ///
+    /// The response is built by the client, reading the file without blocking the runtime, and
+    /// doesn't go through the network, the layers of the client or the [`UrlPolicy`]. Only
+    /// `GET` and `HEAD` requests are answered, and a `Range` header of a single byte range gets
+    /// a `206 Partial Content` response. A file outside of `root`, including through a symbolic
+    /// link, gets a `403 Forbidden` response, and a missing file a `404 Not Found` one. No
+    /// `Content-Type` is set.
+    ///
+    /// Default is disabled.
+    pub fn file_urls<P: Into<PathBuf>>(mut self, root: P) -> ClientBuilder {
+        self.config.local_urls.set_file_root(root.into());
+        self
+    }
+
     // DNS options
 
     /// Disables the hickory-dns async resolver.
@@ -1320,6 +2726,102 @@ impl Client {
         ClientBuilder::new()
     }
 
+    /// Creates a new [`Session`] sharing the connection pool of this client.
+    ///
+    /// See [`Session`] for details.
+    #[cfg(feature = "cookies")]
+    pub fn session(&self) -> Session {
+        Session::new(self.clone())
+    }
+
+    /// Picks the user agent of a new session, if the client rotates them.
+    #[cfg(feature = "cookies")]
+    pub(crate) fn rotate_user_agent(&self) -> Option<UserAgent> {
+        self.user_agents
+            .as_ref()
+            .map(|user_agents| user_agents.pick().clone())
+    }
+
+    /// Returns a handle to update some settings of this client at runtime.
+    ///
+    /// See [`ConfigHandle`] for details.
+    pub fn config_handle(&self) -> ConfigHandle {
+        self.handle.clone()
+    }
+
+    /// Returns a handle to the connection pool of this client, to share it with other clients.
+    ///
+    /// See [`PoolHandle`] for details.
+    pub fn pool_handle(&self) -> PoolHandle {
+        PoolHandle::new(self.native.shared_pool())
+    }
+
+    /// Returns a snapshot of the effective settings of this client, including those updated
+    /// through its [`ConfigHandle`].
+    ///
+    /// See [`ConfigSnapshot`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "json")]
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = wreq::Client::new();
+    /// let snapshot = serde_json::to_string_pretty(&client.config_snapshot())?;
+    /// println!("{snapshot}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn config_snapshot(&self) -> ConfigSnapshot {
+        let mut snapshot = ConfigSnapshot::clone(&self.snapshot);
+        self.handle.refresh_snapshot(&mut snapshot);
+        snapshot
+    }
+
+    /// Reads the system proxy settings again, such as the `HTTP_PROXY`, `HTTPS_PROXY` and
+    /// `NO_PROXY` environment variables.
+    ///
+    /// The system settings are otherwise only read when the client is built, so long-running
+    /// processes can call this, e.g. on `SIGHUP`, to pick up changes. See
+    /// [`ConfigHandle::reload_system_proxy`] for details.
+    pub fn reload_system_proxy(&self) {
+        self.handle.reload_system_proxy();
+    }
+
+    /// Returns a snapshot of the connection pool state for the connections going through
+    /// `proxy`, summed over all the origins reached through it.
+    ///
+    /// Pooled connections are keyed by both the proxy and the origin, so connections through
+    /// different proxies are never shared.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied proxy `Url` cannot be parsed.
+    pub fn proxy_pool_stats<U: IntoUrl>(&self, proxy: U) -> crate::Result<PoolStats> {
+        let mut url = proxy.into_url()?;
+        // The proxies are matched without their credentials.
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        let uri = Uri::try_from(url.as_str()).map_err(Error::builder)?;
+        Ok(self.native.proxy_pool_stats(&uri))
+    }
+
+    /// Returns the number of requests sent again on a new connection because the server had
+    /// closed the idle HTTP/1 connection they were written to before responding.
+    ///
+    /// Only idempotent requests with a replayable body are sent again, at most once.
+    pub fn stale_connection_retries(&self) -> u64 {
+        self.stale_connection_retries.load(Ordering::Relaxed)
+    }
+
+    /// Returns the bytes transferred by all the requests sent with this client, or any of its
+    /// clones.
+    ///
+    /// See [`TransferStats`] for how they are counted.
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.transfer.stats()
+    }
+
     /// Convenience method to make a `GET` request to a URL.
     ///
     /// # Errors
@@ -1383,16 +2885,102 @@ impl Client {
         self.request(Method::HEAD, url)
     }
 
+    /// Start a conditional `GET` of a URL, fetching the resource only if it changed since it
+    /// was fetched with `validators`.
+    ///
+    /// See [`GetIfModified`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(previous: wreq::Response) -> wreq::Result<()> {
+    /// use wreq::{Conditional, Validators};
+    ///
+    /// let validators = Validators::from_response(&previous);
+    /// let client = wreq::Client::new();
+    /// if let Conditional::Modified(res) = client
+    ///     .get_if_modified("https://example.com/feed", &validators)
+    ///     .send()
+    ///     .await?
+    /// {
+    ///     println!("changed: {}", res.text().await?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_if_modified<U: IntoUrl>(&self, url: U, validators: &Validators) -> GetIfModified {
+        GetIfModified::new(self, url, validators)
+    }
+
+    /// Start fetching byte ranges of the resource at a URL.
+    ///
+    /// Each range is requested with a ranged `GET`, and the bodies are reassembled in the order
+    /// of the ranges. See [`FetchRanges`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), wreq::Error> {
+    /// let bytes = wreq::Client::new()
+    ///     .fetch_ranges("http://httpbin.org/range/1024", [0..512, 512..1024])
+    ///     .concurrency(2)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_ranges<U, I, R>(&self, url: U, ranges: I) -> FetchRanges
+    where
+        U: IntoUrl,
+        I: IntoIterator<Item = R>,
+        R: RangeBounds<u64>,
+    {
+        FetchRanges::new(self.clone(), url, ranges)
+    }
+
     /// Start building a `Request` with the `Method` and `Url`.
     ///
     /// Returns a `RequestBuilder`, which will allow setting headers and
     /// the request body before sending.
     ///
+    /// A `CONNECT` request also accepts an authority such as `example.com:443`, and is sent
+    /// with it as the request target. The request is sent over plain HTTP to the host of the
+    /// authority, or to the proxy of the client, which makes it possible to chain proxies. Once
+    /// the tunnel is established, the connection is taken with [`Response::upgrade`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> wreq::Result<()> {
+    /// use wreq::{Client, Method, Proxy};
+    ///
+    /// let client = Client::builder()
+    ///     .proxy(Proxy::http("http://upstream.proxy:3128")?)
+    ///     .build()?;
+    ///
+    /// let res = client
+    ///     .request(Method::CONNECT, "example.com:443")
+    ///     .send()
+    ///     .await?
+    ///     .error_for_status()?;
+    /// let tunnel = res.upgrade().await?;
+    /// # drop(tunnel);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
     /// # Errors
     ///
     /// This method fails whenever the supplied `Url` cannot be parsed.
     pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
-        let req = url.into_url().map(move |url| Request::new(method, url));
+        let req = if method == Method::CONNECT {
+            crate::into_url::connect_target(url)
+        } else if self.local_urls.accepts(url.as_str()) {
+            Url::parse(url.as_str()).map_err(Error::builder)
+        } else {
+            url.into_url()
+        };
+        let req = req.map(move |url| Request::new(method, url));
         RequestBuilder::new(self.clone(), req)
     }
 
@@ -1408,25 +2996,77 @@ impl Client {
     ///
     /// This method fails if there was an error while sending request,
     /// redirect loop was detected or redirect limit was exhausted.
-    pub fn execute(&self, request: Request) -> Pending {
-        match request.try_into() {
-            Ok((url, req)) => {
-                // Prepare the future request by ensuring we use the exact same Service instance
-                // for both poll_ready and call.
-                match *self.inner {
-                    ClientRef::Boxed(ref service) => Pending::BoxedRequest {
-                        url: Some(url),
-                        fut: service.clone().oneshot(req),
-                    },
-                    ClientRef::Generic(ref service) => Pending::GenericRequest {
-                        url: Some(url),
-                        fut: Box::pin(service.clone().oneshot(req)),
-                    },
-                }
+    pub fn execute(&self, mut request: Request) -> Pending {
+        if let Some(fut) = self.local_urls.respond(&request) {
+            return Pending::Local { fut };
+        }
+
+        if let Some(ref policy) = self.url_policy {
+            match policy.apply(request.url().clone()) {
+                Ok(url) => *request.url_mut() = url,
+                Err(err) => return Pending::Error { error: Some(err) },
             }
+        }
+
+        match request.try_into() {
+            Ok((url, req)) => self.send(url, req),
             Err(err) => Pending::Error { error: Some(err) },
         }
     }
+
+    /// Executes a `PreparedRequest`, which can be sent again afterwards.
+    ///
+    /// A `PreparedRequest` is obtained with `Request::prepared()`. Sending it repeatedly,
+    /// as a polling loop does, skips the parsing and validation of its URL and the copy of
+    /// its body that building and executing a new `Request` each time would take. Its headers
+    /// are shared with each send rather than copied, while its extensions are still cloned.
+    ///
+    /// # Errors
+    ///
+    /// This method fails for the same reasons as `Client::execute()`.
+    pub fn execute_ref(&self, request: &PreparedRequest) -> Pending {
+        // The URLs the client may rewrite or answer itself take the usual path.
+        if self.url_policy.is_some() || self.local_urls.accepts(request.url().as_str()) {
+            return self.execute(request.to_request());
+        }
+
+        let (url, req) = request.to_http();
+        self.send(url, req)
+    }
+
+    fn send(&self, url: Url, mut req: HttpRequest<Body>) -> Pending {
+        // Extensions set on the request take precedence over the client's.
+        if !self.extensions.is_empty() {
+            let mut extensions = (*self.extensions).clone();
+            extensions.extend(std::mem::take(req.extensions_mut()));
+            *req.extensions_mut() = extensions;
+        }
+
+        let context = RequestContext::new(
+            req.method().clone(),
+            TransferCounter::new(self.transfer.clone()),
+            #[cfg(feature = "metrics")]
+            self.metrics.clone(),
+        );
+        req.extensions_mut().insert(context.clone());
+
+        // Prepare the future request by ensuring we use the exact same Service instance
+        // for both poll_ready and call.
+        match *self.inner {
+            ClientRef::Boxed(ref service) => Pending::BoxedRequest {
+                url: Some(url),
+                context,
+                scanner: self.body_scanner.clone(),
+                fut: service.clone().oneshot(req),
+            },
+            ClientRef::Generic(ref service) => Pending::GenericRequest {
+                url: Some(url),
+                context,
+                scanner: self.body_scanner.clone(),
+                fut: Box::pin(service.clone().oneshot(req)),
+            },
+        }
+    }
 }
 
 impl tower_service::Service<Request> for Client {
@@ -1,23 +1,50 @@
 use std::{
-    sync::Arc,
+    sync::{Arc, atomic::AtomicU64},
     task::{Context, Poll},
 };
 
-use http::{HeaderMap, Request, Response, header::PROXY_AUTHORIZATION, uri::Scheme};
+use arc_swap::ArcSwap;
+use http::{
+    HeaderMap, Request, Response, Uri,
+    header::PROXY_AUTHORIZATION,
+    uri::{Parts, PathAndQuery, Scheme},
+};
 use tower::Service;
 
-use super::{Body, future::CorePending};
+use super::{
+    Body,
+    future::{CorePending, ProxyReauth, Replay},
+};
 use crate::{
-    client::middleware::config::RequestSkipDefaultHeaders,
-    connect::Connector,
+    budget::BufferLimits,
+    client::{
+        duplicates::DuplicateHeaders,
+        middleware::{
+            config::{
+                RequestBufferBudget, RequestFetchMode, RequestMaxBufferedBytes,
+                RequestProxySession, RequestSigner, RequestSkipDefaultHeaders,
+            },
+            context::RequestContext,
+            retry::is_idempotent,
+        },
+    },
+    connect::{Connector, SharedProxies},
     core::{
         body::Incoming,
         client::Client,
-        ext::{RequestConfig, RequestOriginalHeaders},
+        ext::{
+            ReplayHead, RequestConfig, RequestDnsResolver, RequestHostOverride,
+            RequestOriginalHeaders, RequestPreferredAddr, RequestProxyMatcher, RequestReplayHead,
+            SharedHeaders,
+        },
     },
+    dns::DynResolver,
     error::{BoxError, Error},
     into_url::IntoUrlSealed,
-    proxy::Matcher as ProxyMatcher,
+    profile::{ProfileProbe, ProfileStore},
+    proxy::{Intercepted, Matcher as ProxyMatcher, ProxyCredentials},
+    scheme::{SchemeContext, SchemeHandlers},
+    sign,
 };
 
 #[derive(Clone)]
@@ -27,59 +54,156 @@ pub struct ClientService {
 }
 
 pub(super) struct ClientConfig {
-    pub(super) default_headers: HeaderMap,
+    pub(super) default_headers: Arc<ArcSwap<HeaderMap>>,
     pub(super) skip_default_headers: RequestConfig<RequestSkipDefaultHeaders>,
     pub(super) original_headers: RequestConfig<RequestOriginalHeaders>,
+    pub(super) signer: RequestConfig<RequestSigner>,
+    pub(super) buffer_budget: RequestConfig<RequestBufferBudget>,
     pub(super) https_only: bool,
-    pub(super) proxies: Arc<Vec<ProxyMatcher>>,
-    pub(super) proxies_maybe_http_auth: bool,
-    pub(super) proxies_maybe_http_custom_headers: bool,
+    pub(super) allow_host_override: bool,
+    pub(super) duplicate_headers: DuplicateHeaders,
+    pub(super) proxies: SharedProxies,
+    pub(super) profiles: Option<Arc<dyn ProfileStore>>,
+    pub(super) stale_connection_retries: Arc<AtomicU64>,
+    pub(super) scheme_handlers: SchemeHandlers,
+    pub(super) resolver: DynResolver,
+    pub(super) proxy_credentials: Option<Arc<dyn ProxyCredentials>>,
 }
 
 impl ClientService {
+    /// Selects the proxy the request is sent through, and records it in the request.
+    ///
+    /// The proxy is picked before a connection is checked out, so that pooled connections are
+    /// keyed by the proxy they go through as well as by their origin.
+    fn select_proxy(&self, req: &mut Request<Body>) {
+        if let Some(proxy) = RequestConfig::<RequestProxyMatcher>::get(req.extensions()) {
+            if proxy.intercept(req.uri()).is_some() {
+                return;
+            }
+        }
+
+        let session = RequestConfig::<RequestProxySession>::get(req.extensions()).copied();
+        let proxies = self.config.proxies.load();
+        let proxy = ProxyMatcher::select(&proxies, req.uri(), session).cloned();
+        *RequestConfig::<RequestProxyMatcher>::get_mut(req.extensions_mut()) = proxy;
+    }
+
+    /// Selects the proxy of a request to a scheme handler.
+    ///
+    /// It is the proxy an `https` request to the same host and port would be tunneled through.
+    fn select_scheme_proxy(&self, req: &Request<Body>) -> Option<Intercepted> {
+        let mut parts = Parts::default();
+        parts.scheme = Some(Scheme::HTTPS);
+        parts.authority = req.uri().authority().cloned();
+        parts.path_and_query = Some(PathAndQuery::from_static("/"));
+        let dst = Uri::from_parts(parts).ok()?;
+
+        if let Some(proxy) = RequestConfig::<RequestProxyMatcher>::get(req.extensions()) {
+            if let Some(intercepted) = proxy.intercept(&dst) {
+                return Some(intercepted);
+            }
+        }
+
+        let session = RequestConfig::<RequestProxySession>::get(req.extensions()).copied();
+        let proxies = self.config.proxies.load();
+        ProxyMatcher::select(&proxies, &dst, session)?.intercept(&dst)
+    }
+
+    /// Loads the profile of the request's origin, and asks for a connection to its last good
+    /// address if the request bypasses proxies.
+    fn probe_origin(&self, req: &mut Request<Body>) -> Option<ProfileProbe> {
+        let store = self.config.profiles.clone()?;
+        let url = IntoUrlSealed::into_url(req.uri().to_string()).ok()?;
+        let direct = RequestConfig::<RequestProxyMatcher>::get(req.extensions())
+            .is_none_or(|proxy| proxy.intercept(req.uri()).is_none());
+
+        let probe = ProfileProbe::start(store, url.origin().ascii_serialization(), direct);
+        if let Some(addr) = probe.preferred_addr() {
+            RequestConfig::<RequestPreferredAddr>::get_mut(req.extensions_mut()).replace(addr);
+        }
+        Some(probe)
+    }
+
+    /// Returns the proxy of a request whose credentials can be refreshed if it rejects them.
+    fn proxy_reauth(&self, req: &Request<Body>) -> Option<ProxyReauth> {
+        let credentials = self.config.proxy_credentials.clone()?;
+        let matcher = RequestConfig::<RequestProxyMatcher>::get(req.extensions())?;
+        let proxy = matcher.intercept(req.uri())?;
+
+        Some(ProxyReauth {
+            credentials,
+            proxies: self.config.proxies.clone(),
+            matcher: matcher.clone(),
+            proxy: proxy.uri().clone(),
+            forward: req.uri().scheme() == Some(&Scheme::HTTP),
+        })
+    }
+
+    /// Prepares a request to be sent again if the server closes the reused connection it is
+    /// written to, as it can't have been processed then unless its method is unsafe, or if its
+    /// proxy rejects its credentials, as it hasn't reached the server then.
+    ///
+    /// Only the body is shared up front, which for a replayable body is a reference count. The
+    /// head of an idempotent request is copied by the client once it is written to a reused
+    /// HTTP/1 connection, and not at all otherwise. A proxy may reject the credentials on any
+    /// connection, so the head of a request whose proxy credentials can be refreshed is copied
+    /// here, which costs a copy of its headers and extensions.
+    fn replay(&self, req: &mut Request<Body>) -> Option<Box<Replay>> {
+        let idempotent = is_idempotent(req);
+        let reauth = self.proxy_reauth(req);
+        if !idempotent && reauth.is_none() {
+            return None;
+        }
+
+        let body = req.body().try_clone()?;
+        let head = ReplayHead::new();
+        if reauth.is_some() {
+            head.store(req);
+        } else {
+            RequestConfig::<RequestReplayHead>::get_mut(req.extensions_mut()).replace(head.clone());
+        }
+
+        Some(Box::new(Replay {
+            client: self.client.clone(),
+            head,
+            body,
+            retries: self.config.stale_connection_retries.clone(),
+            idempotent,
+            reauth,
+        }))
+    }
+
     #[inline]
-    fn apply_proxy_headers(&self, req: &mut Request<Body>) {
+    fn apply_proxy_headers(req: &mut Request<Body>) {
         // Skip if the destination is not plain HTTP.
         // For HTTPS, the proxy headers should be part of the CONNECT tunnel instead.
         if req.uri().scheme() != Some(&Scheme::HTTP) {
             return;
         }
 
-        // Determine whether we need to apply proxy auth and/or custom headers.
-        let need_auth = self.config.proxies_maybe_http_auth
-            && !req.headers_mut().contains_key(PROXY_AUTHORIZATION);
-        let need_custom_headers = self.config.proxies_maybe_http_custom_headers;
-
-        // If no headers need to be applied, return early.
-        if !need_auth && !need_custom_headers {
+        let Some(proxy) = RequestConfig::<RequestProxyMatcher>::get(req.extensions()) else {
             return;
-        }
-
-        let mut inserted_auth = false;
-        let mut inserted_custom = false;
+        };
 
-        for proxy in self.config.proxies.iter() {
-            // Insert basic auth header from the first applicable proxy.
-            if need_auth && !inserted_auth {
-                if let Some(auth_header) = proxy.http_non_tunnel_basic_auth(req.uri()) {
-                    req.headers_mut().insert(PROXY_AUTHORIZATION, auth_header);
-                    inserted_auth = true;
-                }
-            }
+        let auth =
+            if proxy.maybe_has_http_auth() && !SharedHeaders::contains(req, &PROXY_AUTHORIZATION) {
+                proxy.http_non_tunnel_basic_auth(req.uri())
+            } else {
+                None
+            };
+        let custom_headers = if proxy.maybe_has_http_custom_headers() {
+            proxy.http_non_tunnel_custom_headers(req.uri())
+        } else {
+            None
+        };
 
-            // Insert custom headers from the first applicable proxy.
-            if need_custom_headers && !inserted_custom {
-                if let Some(custom_headers) = proxy.http_non_tunnel_custom_headers(req.uri()) {
-                    for (key, value) in custom_headers.iter() {
-                        req.headers_mut().insert(key.clone(), value.clone());
-                    }
-                    inserted_custom = true;
-                }
-            }
+        if let Some(auth) = auth {
+            req.headers_mut().insert(PROXY_AUTHORIZATION, auth);
+        }
 
-            // Stop iterating if both kinds of headers have been inserted.
-            if inserted_auth && inserted_custom {
-                break;
+        if let Some(custom_headers) = custom_headers {
+            for (key, value) in custom_headers.iter() {
+                req.headers_mut().insert(key.clone(), value.clone());
             }
         }
     }
@@ -97,9 +221,12 @@ impl Service<Request<Body>> for ClientService {
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
         let scheme = req.uri().scheme();
+        let handler = scheme
+            .and_then(|scheme| self.config.scheme_handlers.get(scheme.as_str()))
+            .cloned();
 
         // Check for invalid schemes
-        if (scheme != Some(&Scheme::HTTP) && scheme != Some(&Scheme::HTTPS))
+        if (handler.is_none() && scheme != Some(&Scheme::HTTP) && scheme != Some(&Scheme::HTTPS))
             || (self.config.https_only && scheme != Some(&Scheme::HTTPS))
         {
             let error = match IntoUrlSealed::into_url(req.uri().to_string()) {
@@ -110,6 +237,16 @@ impl Service<Request<Body>> for ClientService {
             return CorePending::Error { error: Some(error) };
         }
 
+        if !self.config.allow_host_override
+            && RequestConfig::<RequestHostOverride>::get(req.extensions()).is_some()
+        {
+            return CorePending::Error {
+                error: Some(Error::builder(
+                    "host header override is not allowed, see ClientBuilder::allow_host_override",
+                )),
+            };
+        }
+
         // Only skip setting default headers if skip_default_headers is explicitly Some(true).
         let skip = self
             .config
@@ -118,26 +255,79 @@ impl Service<Request<Body>> for ClientService {
             .copied()
             == Some(true);
 
+        let default_headers = self.config.default_headers.load_full();
+
+        // The headers of the fetch mode replace the default ones, so they are set first, from
+        // all the headers of the request.
+        if let Some(mode) = RequestConfig::<RequestFetchMode>::get(req.extensions()).copied() {
+            SharedHeaders::merge(&mut req);
+            let uri = req.uri().clone();
+            mode.apply(
+                &uri,
+                req.headers_mut(),
+                (!skip).then_some(&*default_headers),
+            );
+        }
+
+        // The default headers are shared with the request rather than copied into it, under its
+        // own headers and those of a prepared request.
         if !skip {
-            let headers = req.headers_mut();
-            // Insert default headers if they are not already present in the request.
-            for name in self.config.default_headers.keys() {
-                if !headers.contains_key(name) {
-                    for value in self.config.default_headers.get_all(name) {
-                        headers.append(name, value.clone());
-                    }
-                }
-            }
+            SharedHeaders::push(&mut req, default_headers);
         }
 
         // Apply original headers if they are set in the request extensions.
         self.config.original_headers.store(req.extensions_mut());
 
+        // The requests to other schemes leave the connection pool here.
+        if let Some(handler) = handler {
+            SharedHeaders::merge(&mut req);
+            let proxy = self.select_scheme_proxy(&req);
+            let resolver = RequestConfig::<RequestDnsResolver>::get(req.extensions())
+                .unwrap_or(&self.config.resolver)
+                .clone();
+            let context = SchemeContext::new(resolver, proxy);
+            return CorePending::Handler {
+                fut: handler.handle(req, context),
+            };
+        }
+
         // Apply proxy headers if the request is routed through a proxy.
-        self.apply_proxy_headers(&mut req);
+        self.select_proxy(&mut req);
+        Self::apply_proxy_headers(&mut req);
+
+        // Sign the request last, once its headers are final.
+        if let Some(signer) = self.config.signer.fetch(req.extensions()).cloned() {
+            SharedHeaders::merge(&mut req);
+            if let Err(err) = sign::sign(&*signer, &mut req) {
+                return CorePending::Error {
+                    error: Some(Error::builder(err)),
+                };
+            }
+        }
+
+        let transfer = req
+            .extensions()
+            .get::<RequestContext>()
+            .map(|context| context.transfer().clone());
+        if let Some(ref transfer) = transfer {
+            transfer.count_request(&mut req);
+        }
+
+        let probe = self.probe_origin(&mut req);
+        let replay = self.replay(&mut req);
+        let buffer_limits = BufferLimits::new(
+            self.config.buffer_budget.fetch(req.extensions()).cloned(),
+            RequestConfig::<RequestMaxBufferedBytes>::get(req.extensions()).copied(),
+        );
 
         CorePending::Request {
             fut: self.client.call(req),
+            probe,
+            duplicates: self.config.duplicate_headers,
+            replay,
+            buffer_limits,
+            transfer,
+            refresh: None,
         }
     }
 }
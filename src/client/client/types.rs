@@ -7,8 +7,15 @@ use tower::{
 use super::{Body, service::ClientService};
 use crate::{
     client::middleware::{
+        digest::{ContentDigest, DigestBody},
+        hints::ClientHintsManager,
+        idempotency::IdempotencyKeyManager,
+        limit::InFlightLimit,
+        logging::RequestLogging,
         redirect::FollowRedirect,
+        refresh::{MetaRefresh, RefreshBody},
         retry::Http2RetryPolicy,
+        robots::RobotsExclusion,
         timeout::{ResponseBodyTimeout, Timeout, TimeoutBody},
     },
     core::body::Incoming,
@@ -22,6 +29,19 @@ type MaybeCookieLayer<T> = T;
 #[cfg(feature = "cookies")]
 type MaybeCookieLayer<T> = crate::client::middleware::cookie::CookieManager<T>;
 
+#[cfg(not(feature = "cookies"))]
+type MaybeSessionLayer<T> = T;
+
+#[cfg(feature = "cookies")]
+type MaybeSessionLayer<T> = crate::client::middleware::session::SessionManager<T>;
+
+#[cfg(not(feature = "trace-context"))]
+type MaybeTraceContextLayer<T> = T;
+
+#[cfg(feature = "trace-context")]
+type MaybeTraceContextLayer<T> =
+    crate::client::middleware::trace_context::TraceContextPropagation<T>;
+
 #[cfg(not(any(
     feature = "gzip",
     feature = "zstd",
@@ -44,7 +64,9 @@ type MaybeDecompression<T> = crate::client::middleware::decoder::Decompression<T
     feature = "brotli",
     feature = "deflate"
 ))]
-pub type ResponseBody = TimeoutBody<tower_http::decompression::DecompressionBody<Incoming>>;
+pub type ResponseBody = RefreshBody<
+    TimeoutBody<crate::client::middleware::decoder::DecompressionBody<DigestBody<Incoming>>>,
+>;
 
 #[cfg(not(any(
     feature = "gzip",
@@ -52,17 +74,37 @@ pub type ResponseBody = TimeoutBody<tower_http::decompression::DecompressionBody
     feature = "brotli",
     feature = "deflate"
 )))]
-pub type ResponseBody = TimeoutBody<Incoming>;
+pub type ResponseBody = RefreshBody<TimeoutBody<DigestBody<Incoming>>>;
 
 type RedirectLayer = FollowRedirect<
-    MaybeCookieLayer<ResponseBodyTimeout<MaybeDecompression<ClientService>>>,
+    MetaRefresh<
+        RobotsExclusion<
+            MaybeSessionLayer<
+                ClientHintsManager<
+                    MaybeCookieLayer<
+                        ResponseBodyTimeout<
+                            MaybeDecompression<
+                                InFlightLimit<
+                                    ContentDigest<
+                                        MaybeTraceContextLayer<RequestLogging<ClientService>>,
+                                    >,
+                                >,
+                            >,
+                        >,
+                    >,
+                >,
+            >,
+        >,
+    >,
     RedirectPolicy,
 >;
 
 pub type CoreResponseFuture = crate::core::client::ResponseFuture;
 
-pub type GenericClientService =
-    MapErr<Timeout<Retry<Http2RetryPolicy, RedirectLayer>>, fn(BoxError) -> BoxError>;
+pub type GenericClientService = MapErr<
+    Timeout<IdempotencyKeyManager<Retry<Http2RetryPolicy, RedirectLayer>>>,
+    fn(BoxError) -> BoxError,
+>;
 
 pub type BoxedClientService =
     BoxCloneSyncService<HttpRequest<Body>, HttpResponse<ResponseBody>, BoxError>;
@@ -0,0 +1,135 @@
+//! Conditional requests
+
+use http::{
+    StatusCode,
+    header::{ETAG, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+
+use super::{client::Client, request::RequestBuilder, response::Response};
+use crate::IntoUrl;
+
+/// The validators of a representation, its `ETag` and `Last-Modified` date, used to ask the
+/// server whether it changed since it was fetched.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> wreq::Result<()> {
+/// use wreq::{Conditional, Validators};
+///
+/// let client = wreq::Client::new();
+/// let mut validators = Validators::new();
+/// loop {
+///     match client.get_if_modified("https://example.com/feed", &validators).send().await? {
+///         Conditional::NotModified => {}
+///         Conditional::Modified(res) => {
+///             validators = Validators::from_response(&res);
+///             println!("{}", res.text().await?);
+///         }
+///     }
+///     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+}
+
+impl Validators {
+    /// Creates empty validators, with which the resource is always fetched.
+    pub fn new() -> Validators {
+        Validators::default()
+    }
+
+    /// Returns the validators of a response, from its `ETag` and `Last-Modified` headers.
+    pub fn from_response(res: &Response) -> Validators {
+        Validators {
+            etag: res.headers().get(ETAG).cloned(),
+            last_modified: res.headers().get(LAST_MODIFIED).cloned(),
+        }
+    }
+
+    /// Sets the entity tag, such as `"33a64df5"` or `W/"0815"`, sent in `If-None-Match`.
+    pub fn etag(mut self, etag: HeaderValue) -> Validators {
+        self.etag = Some(etag);
+        self
+    }
+
+    /// Sets the HTTP date, such as `Wed, 21 Oct 2015 07:28:00 GMT`, sent in
+    /// `If-Modified-Since`.
+    pub fn last_modified(mut self, date: HeaderValue) -> Validators {
+        self.last_modified = Some(date);
+        self
+    }
+
+    /// Returns true if there is no validator.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// The outcome of a conditional `GET`.
+#[derive(Debug)]
+pub enum Conditional {
+    /// The server answered `304 Not Modified`: the representation didn't change.
+    NotModified,
+    /// The representation changed, or the server doesn't support conditional requests.
+    Modified(Response),
+}
+
+impl Conditional {
+    /// Returns true if the representation didn't change.
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self, Conditional::NotModified)
+    }
+}
+
+/// A conditional `GET` request, fetching a resource only if it changed since it was fetched
+/// with some [`Validators`].
+///
+/// The entity tag is sent in `If-None-Match` and the date in `If-Modified-Since`. As
+/// specified, servers ignore the date when they support the entity tag.
+///
+/// To construct a `GetIfModified`, use [`Client::get_if_modified`].
+#[must_use = "GetIfModified does nothing until you 'send' it"]
+pub struct GetIfModified {
+    request: RequestBuilder,
+}
+
+impl GetIfModified {
+    pub(super) fn new<U: IntoUrl>(client: &Client, url: U, validators: &Validators) -> Self {
+        let mut request = client.get(url);
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(date) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, date.clone());
+        }
+        GetIfModified { request }
+    }
+
+    /// Modifies the underlying request, such as to add headers.
+    pub fn map_request<F>(mut self, f: F) -> GetIfModified
+    where
+        F: FnOnce(RequestBuilder) -> RequestBuilder,
+    {
+        self.request = f(self.request);
+        self
+    }
+
+    /// Sends the request.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if there was an error while sending the request.
+    pub async fn send(self) -> crate::Result<Conditional> {
+        let res = self.request.send().await?;
+        if res.status() == StatusCode::NOT_MODIFIED {
+            Ok(Conditional::NotModified)
+        } else {
+            Ok(Conditional::Modified(res))
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Duplicate response headers
+
+use std::{error::Error as StdError, fmt};
+
+use bytes::BytesMut;
+use http::{
+    HeaderMap, HeaderName, HeaderValue,
+    header::{
+        AGE, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, DATE, ETAG, EXPIRES, LAST_MODIFIED,
+        LOCATION, RETRY_AFTER, SET_COOKIE,
+    },
+};
+
+/// How headers received more than once in a response are handled.
+///
+/// `Set-Cookie` is never joined nor dropped, since a cookie can contain commas and each
+/// value is a distinct cookie.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateHeaders {
+    /// Keeps every value, in the order they were received.
+    #[default]
+    KeepAll,
+    /// Joins the values into a single comma-separated value.
+    Join,
+    /// Keeps only the first value.
+    FirstWins,
+    /// Fails the request when a header that can only appear once, such as `Content-Length`,
+    /// `Content-Type` or `Location`, appears more than once. Other headers keep every value.
+    Reject,
+}
+
+impl DuplicateHeaders {
+    /// Applies the policy to the headers of a response.
+    pub(crate) fn apply(self, headers: &mut HeaderMap) -> Result<(), DuplicateHeader> {
+        match self {
+            DuplicateHeaders::KeepAll => Ok(()),
+            DuplicateHeaders::Reject => {
+                match headers
+                    .keys()
+                    .find(|name| is_singleton(name) && is_repeated(headers, name))
+                {
+                    Some(name) => Err(DuplicateHeader(name.clone())),
+                    None => Ok(()),
+                }
+            }
+            DuplicateHeaders::Join | DuplicateHeaders::FirstWins => {
+                let repeated: Vec<HeaderName> = headers
+                    .keys()
+                    .filter(|name| **name != SET_COOKIE && is_repeated(headers, name))
+                    .cloned()
+                    .collect();
+
+                for name in repeated {
+                    let value = if self == DuplicateHeaders::Join {
+                        join(headers, &name)
+                    } else {
+                        headers.get(&name).cloned()
+                    };
+                    if let Some(value) = value {
+                        headers.insert(name, value);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns true if a response can carry the header only once.
+fn is_singleton(name: &HeaderName) -> bool {
+    matches!(
+        *name,
+        AGE | CONTENT_LENGTH
+            | CONTENT_RANGE
+            | CONTENT_TYPE
+            | DATE
+            | ETAG
+            | EXPIRES
+            | LAST_MODIFIED
+            | LOCATION
+            | RETRY_AFTER
+    )
+}
+
+fn is_repeated(headers: &HeaderMap, name: &HeaderName) -> bool {
+    headers.get_all(name).iter().nth(1).is_some()
+}
+
+fn join(headers: &HeaderMap, name: &HeaderName) -> Option<HeaderValue> {
+    let mut buf = BytesMut::new();
+    for (i, value) in headers.get_all(name).iter().enumerate() {
+        if i > 0 {
+            buf.extend_from_slice(b", ");
+        }
+        buf.extend_from_slice(value.as_bytes());
+    }
+    HeaderValue::from_maybe_shared(buf.freeze()).ok()
+}
+
+/// A header that can appear only once was repeated in a response.
+#[derive(Debug)]
+pub(crate) struct DuplicateHeader(HeaderName);
+
+impl fmt::Display for DuplicateHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate `{}` header in response", self.0)
+    }
+}
+
+impl StdError for DuplicateHeader {}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, HeaderValue, header};
+
+    use super::DuplicateHeaders;
+
+    fn headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.append(header::VARY, HeaderValue::from_static("accept"));
+        headers.append(header::VARY, HeaderValue::from_static("origin"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("b=2"));
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("3"));
+        headers
+    }
+
+    #[test]
+    fn join_and_first_wins() {
+        let mut joined = headers();
+        DuplicateHeaders::Join.apply(&mut joined).unwrap();
+        assert_eq!(joined.get_all(header::VARY).iter().count(), 1);
+        assert_eq!(joined[header::VARY], "accept, origin");
+        assert_eq!(joined.get_all(header::SET_COOKIE).iter().count(), 2);
+
+        let mut first = headers();
+        DuplicateHeaders::FirstWins.apply(&mut first).unwrap();
+        assert_eq!(first.get_all(header::VARY).iter().count(), 1);
+        assert_eq!(first[header::VARY], "accept");
+        assert_eq!(first.get_all(header::SET_COOKIE).iter().count(), 2);
+    }
+
+    #[test]
+    fn reject_repeated_singletons() {
+        let mut headers = headers();
+        DuplicateHeaders::Reject.apply(&mut headers).unwrap();
+        assert_eq!(headers.get_all(header::VARY).iter().count(), 2);
+
+        headers.append(header::CONTENT_LENGTH, HeaderValue::from_static("4"));
+        let err = DuplicateHeaders::Reject.apply(&mut headers).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate `content-length` header in response"
+        );
+    }
+}
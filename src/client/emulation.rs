@@ -1,6 +1,6 @@
 use http::HeaderMap;
 
-use crate::{OriginalHeaders, http1::Http1Config, http2::Http2Config, tls::TlsConfig};
+use crate::{ClientHints, OriginalHeaders, http1::Http1Config, http2::Http2Config, tls::TlsConfig};
 
 /// Trait defining the interface for providing an `EmulationProvider`.
 ///
@@ -49,6 +49,7 @@ pub struct EmulationProviderBuilder {
 /// - **TLS Configuration**: Manages secure connection settings.
 /// - **HTTP Settings**: Controls HTTP/1 and HTTP/2 behaviors.
 /// - **Header Management**: Handles default headers and their ordering.
+/// - **Client Hints**: Derives `Sec-CH-UA-*` headers consistent with the emulated browser.
 ///
 /// # Example
 ///
@@ -69,6 +70,7 @@ pub struct EmulationProvider {
     pub(crate) http2_config: Option<Http2Config>,
     pub(crate) default_headers: Option<HeaderMap>,
     pub(crate) original_headers: Option<OriginalHeaders>,
+    pub(crate) client_hints: Option<ClientHints>,
 }
 
 impl EmulationProviderBuilder {
@@ -117,6 +119,15 @@ impl EmulationProviderBuilder {
         self
     }
 
+    /// Sets the client hints for the `EmulationProvider`.
+    pub fn client_hints<H>(mut self, hints: H) -> Self
+    where
+        H: Into<Option<ClientHints>>,
+    {
+        self.provider.client_hints = hints.into();
+        self
+    }
+
     /// Builds the `EmulationProvider` instance.
     pub fn build(self) -> EmulationProvider {
         self.provider
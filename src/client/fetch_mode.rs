@@ -0,0 +1,212 @@
+use http::{
+    HeaderMap, HeaderName, HeaderValue, Uri,
+    header::{ACCEPT, REFERER, UPGRADE_INSECURE_REQUESTS, USER_AGENT},
+};
+
+use super::priority::PRIORITY;
+
+const SEC_FETCH_SITE: HeaderName = HeaderName::from_static("sec-fetch-site");
+const SEC_FETCH_MODE: HeaderName = HeaderName::from_static("sec-fetch-mode");
+const SEC_FETCH_DEST: HeaderName = HeaderName::from_static("sec-fetch-dest");
+const SEC_FETCH_USER: HeaderName = HeaderName::from_static("sec-fetch-user");
+
+/// The kind of request a browser would send, setting the headers it sends with it.
+///
+/// The `sec-fetch-mode`, `sec-fetch-dest`, `accept` and `priority` headers are those of the
+/// browser identified by the `User-Agent` of the request, as set by an emulation: Chrome and
+/// other Chromium-based browsers, Firefox or Safari. `sec-fetch-site` is derived on every hop
+/// from the `Referer` of the request: `none` without one, then `same-origin`, `same-site` when
+/// both hosts share their last two labels, or `cross-site`.
+///
+/// Headers set on the request are kept, while the preset replaces the default headers of the
+/// client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FetchMode {
+    /// A top-level navigation, like following a link.
+    Navigate,
+    /// An `XMLHttpRequest` sent by a script.
+    Xhr,
+    /// A `fetch()` call from a script.
+    Fetch,
+    /// An image loaded by a page.
+    Image,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Browser {
+    Chromium,
+    Firefox,
+    Safari,
+}
+
+impl Browser {
+    fn from_user_agent(user_agent: Option<&HeaderValue>) -> Browser {
+        let user_agent = user_agent
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        if user_agent.contains("Firefox/") {
+            Browser::Firefox
+        } else if user_agent.contains("Safari/")
+            && !user_agent.contains("Chrome/")
+            && !user_agent.contains("Chromium/")
+        {
+            Browser::Safari
+        } else {
+            Browser::Chromium
+        }
+    }
+}
+
+impl FetchMode {
+    /// Adds the headers of this kind of request to `headers`, for the request sent to `uri`.
+    ///
+    /// The `User-Agent` is read from `headers`, or else from the default headers.
+    pub(crate) fn apply(self, uri: &Uri, headers: &mut HeaderMap, defaults: Option<&HeaderMap>) {
+        let user_agent = headers
+            .get(USER_AGENT)
+            .or_else(|| defaults.and_then(|defaults| defaults.get(USER_AGENT)));
+        let browser = Browser::from_user_agent(user_agent);
+
+        let (mode, dest) = match self {
+            FetchMode::Navigate => ("navigate", "document"),
+            FetchMode::Xhr | FetchMode::Fetch => ("cors", "empty"),
+            FetchMode::Image => ("no-cors", "image"),
+        };
+
+        let site = fetch_site(uri, headers.get(REFERER));
+        headers.insert(SEC_FETCH_SITE, HeaderValue::from_static(site));
+        insert(headers, SEC_FETCH_MODE, mode);
+        insert(headers, SEC_FETCH_DEST, dest);
+        insert(headers, ACCEPT, self.accept(browser));
+        insert(headers, PRIORITY, self.priority(browser));
+
+        if self == FetchMode::Navigate {
+            insert(headers, SEC_FETCH_USER, "?1");
+            insert(headers, UPGRADE_INSECURE_REQUESTS, "1");
+        }
+    }
+
+    fn accept(self, browser: Browser) -> &'static str {
+        match (self, browser) {
+            (FetchMode::Navigate, Browser::Chromium) => {
+                "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,\
+                 image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"
+            }
+            (FetchMode::Navigate, Browser::Firefox | Browser::Safari) => {
+                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+            }
+            (FetchMode::Xhr | FetchMode::Fetch, _) => "*/*",
+            (FetchMode::Image, Browser::Chromium) => {
+                "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8"
+            }
+            (FetchMode::Image, Browser::Firefox) => {
+                "image/avif,image/webp,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5"
+            }
+            (FetchMode::Image, Browser::Safari) => {
+                "image/webp,image/avif,image/jxl,image/heic,image/heic-sequence,video/*;q=0.8,\
+                 image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5"
+            }
+        }
+    }
+
+    fn priority(self, browser: Browser) -> &'static str {
+        match (self, browser) {
+            (FetchMode::Navigate, _) => "u=0, i",
+            (FetchMode::Xhr | FetchMode::Fetch, Browser::Chromium) => "u=1, i",
+            (FetchMode::Xhr | FetchMode::Fetch, Browser::Firefox) => "u=4",
+            (FetchMode::Xhr | FetchMode::Fetch, Browser::Safari) => "u=3, i",
+            (FetchMode::Image, Browser::Chromium) => "i",
+            (FetchMode::Image, Browser::Firefox | Browser::Safari) => "u=5, i",
+        }
+    }
+}
+
+fn insert(headers: &mut HeaderMap, name: HeaderName, value: &'static str) {
+    headers
+        .entry(name)
+        .or_insert_with(|| HeaderValue::from_static(value));
+}
+
+/// Returns the `sec-fetch-site` of a request to `uri` initiated from the `referer` page.
+fn fetch_site(uri: &Uri, referer: Option<&HeaderValue>) -> &'static str {
+    let Some(referer) = referer
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Uri>().ok())
+    else {
+        return "none";
+    };
+
+    if referer.scheme() == uri.scheme() && referer.authority() == uri.authority() {
+        return "same-origin";
+    }
+
+    match (referer.host(), uri.host()) {
+        (Some(a), Some(b)) if site(a).eq_ignore_ascii_case(site(b)) => "same-site",
+        _ => "cross-site",
+    }
+}
+
+/// Approximates the site of a host by its last two labels.
+fn site(host: &str) -> &str {
+    let host = host.trim_end_matches('.');
+    match host.rmatch_indices('.').nth(1) {
+        Some((index, _)) => &host[index + 1..],
+        None => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_follow_browser_and_referer() {
+        let uri: Uri = "https://api.example.com/items".parse().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Firefox/128.0"),
+        );
+        headers.insert(
+            REFERER,
+            HeaderValue::from_static("https://www.example.com/"),
+        );
+        FetchMode::Fetch.apply(&uri, &mut headers, None);
+
+        assert_eq!(headers[SEC_FETCH_SITE], "same-site");
+        assert_eq!(headers[SEC_FETCH_MODE], "cors");
+        assert_eq!(headers[SEC_FETCH_DEST], "empty");
+        assert_eq!(headers[PRIORITY], "u=4");
+        assert!(!headers.contains_key(SEC_FETCH_USER));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("text/html"));
+        FetchMode::Navigate.apply(&uri, &mut headers, None);
+
+        assert_eq!(headers[SEC_FETCH_SITE], "none");
+        assert_eq!(headers[ACCEPT], "text/html");
+        assert_eq!(headers[PRIORITY], "u=0, i");
+        assert_eq!(headers[SEC_FETCH_USER], "?1");
+    }
+
+    #[test]
+    fn sites() {
+        let uri: Uri = "https://example.com/".parse().unwrap();
+        let referer = |value| Some(HeaderValue::from_static(value));
+
+        assert_eq!(
+            fetch_site(&uri, referer("https://example.com/a").as_ref()),
+            "same-origin"
+        );
+        assert_eq!(
+            fetch_site(&uri, referer("http://example.com/").as_ref()),
+            "same-site"
+        );
+        assert_eq!(
+            fetch_site(&uri, referer("https://other.org/").as_ref()),
+            "cross-site"
+        );
+    }
+}
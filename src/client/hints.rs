@@ -0,0 +1,325 @@
+//! User-Agent client hints.
+//!
+//! Browsers based on Chromium describe themselves with the `Sec-CH-UA-*` family of headers in
+//! addition to (or instead of) the `User-Agent` header. The low-entropy hints (`sec-ch-ua`,
+//! `sec-ch-ua-mobile` and `sec-ch-ua-platform`) are sent with every request to a secure origin,
+//! while the high-entropy hints are only sent once an origin asks for them with an `Accept-CH`
+//! response header.
+
+use std::borrow::Cow;
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+/// The `sec-ch-ua` header name.
+pub(crate) const SEC_CH_UA: HeaderName = HeaderName::from_static("sec-ch-ua");
+
+/// The `sec-ch-ua-mobile` header name.
+pub(crate) const SEC_CH_UA_MOBILE: HeaderName = HeaderName::from_static("sec-ch-ua-mobile");
+
+/// The `sec-ch-ua-platform` header name.
+pub(crate) const SEC_CH_UA_PLATFORM: HeaderName = HeaderName::from_static("sec-ch-ua-platform");
+
+/// The `sec-ch-ua-full-version-list` header name.
+pub(crate) const SEC_CH_UA_FULL_VERSION_LIST: HeaderName =
+    HeaderName::from_static("sec-ch-ua-full-version-list");
+
+/// The `sec-ch-ua-full-version` header name.
+pub(crate) const SEC_CH_UA_FULL_VERSION: HeaderName =
+    HeaderName::from_static("sec-ch-ua-full-version");
+
+/// The `sec-ch-ua-platform-version` header name.
+pub(crate) const SEC_CH_UA_PLATFORM_VERSION: HeaderName =
+    HeaderName::from_static("sec-ch-ua-platform-version");
+
+/// The `sec-ch-ua-arch` header name.
+pub(crate) const SEC_CH_UA_ARCH: HeaderName = HeaderName::from_static("sec-ch-ua-arch");
+
+/// The `sec-ch-ua-bitness` header name.
+pub(crate) const SEC_CH_UA_BITNESS: HeaderName = HeaderName::from_static("sec-ch-ua-bitness");
+
+/// The `sec-ch-ua-model` header name.
+pub(crate) const SEC_CH_UA_MODEL: HeaderName = HeaderName::from_static("sec-ch-ua-model");
+
+/// A single entry of the `sec-ch-ua` brand list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Brand {
+    name: Cow<'static, str>,
+    version: Cow<'static, str>,
+}
+
+impl Brand {
+    /// Creates a new brand with its full version, e.g. `("Google Chrome", "124.0.6367.60")`.
+    pub fn new<N, V>(name: N, version: V) -> Brand
+    where
+        N: Into<Cow<'static, str>>,
+        V: Into<Cow<'static, str>>,
+    {
+        Brand {
+            name: name.into(),
+            version: version.into(),
+        }
+    }
+
+    /// Returns the brand name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the full version of the brand.
+    #[inline]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the significant (major) version of the brand.
+    #[inline]
+    pub fn major_version(&self) -> &str {
+        self.version.split('.').next().unwrap_or_default()
+    }
+}
+
+/// Builder for [`ClientHints`].
+#[must_use]
+#[derive(Debug)]
+pub struct ClientHintsBuilder {
+    hints: ClientHints,
+}
+
+/// A consistent set of User-Agent client hints.
+///
+/// `ClientHints` is usually attached to an [`EmulationProvider`](crate::EmulationProvider), so
+/// that the generated `Sec-CH-UA-*` headers always match the emulated browser. The client sends
+/// the low-entropy hints to secure origins, and answers `Accept-CH` response headers by sending
+/// the requested high-entropy hints on subsequent requests to the same origin.
+///
+/// # Example
+///
+/// ```rust
+/// use wreq::{Brand, ClientHints};
+///
+/// let hints = ClientHints::builder()
+///     .brand(Brand::new("Chromium", "124.0.6367.60"))
+///     .brand(Brand::new("Google Chrome", "124.0.6367.60"))
+///     .brand(Brand::new("Not-A.Brand", "99.0.0.0"))
+///     .platform("Windows")
+///     .build();
+///
+/// let headers = hints.headers();
+/// assert_eq!(
+///     headers["sec-ch-ua"],
+///     r#""Chromium";v="124", "Google Chrome";v="124", "Not-A.Brand";v="99""#
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientHints {
+    brands: Vec<Brand>,
+    mobile: bool,
+    platform: Option<Cow<'static, str>>,
+    platform_version: Option<Cow<'static, str>>,
+    arch: Option<Cow<'static, str>>,
+    bitness: Option<Cow<'static, str>>,
+    model: Option<Cow<'static, str>>,
+}
+
+impl ClientHintsBuilder {
+    /// Appends a brand to the brand list.
+    ///
+    /// Brands are sent in the order they were added.
+    pub fn brand(mut self, brand: Brand) -> Self {
+        self.hints.brands.push(brand);
+        self
+    }
+
+    /// Sets whether the client is a mobile device.
+    pub fn mobile(mut self, mobile: bool) -> Self {
+        self.hints.mobile = mobile;
+        self
+    }
+
+    /// Sets the platform name, e.g. `Windows`, `macOS`, `Linux` or `Android`.
+    pub fn platform<P>(mut self, platform: P) -> Self
+    where
+        P: Into<Cow<'static, str>>,
+    {
+        self.hints.platform = Some(platform.into());
+        self
+    }
+
+    /// Sets the platform version, e.g. `15.0.0`.
+    pub fn platform_version<V>(mut self, version: V) -> Self
+    where
+        V: Into<Cow<'static, str>>,
+    {
+        self.hints.platform_version = Some(version.into());
+        self
+    }
+
+    /// Sets the CPU architecture, e.g. `x86` or `arm`.
+    pub fn arch<A>(mut self, arch: A) -> Self
+    where
+        A: Into<Cow<'static, str>>,
+    {
+        self.hints.arch = Some(arch.into());
+        self
+    }
+
+    /// Sets the CPU bitness, e.g. `64`.
+    pub fn bitness<B>(mut self, bitness: B) -> Self
+    where
+        B: Into<Cow<'static, str>>,
+    {
+        self.hints.bitness = Some(bitness.into());
+        self
+    }
+
+    /// Sets the device model, mostly used on mobile platforms.
+    pub fn model<M>(mut self, model: M) -> Self
+    where
+        M: Into<Cow<'static, str>>,
+    {
+        self.hints.model = Some(model.into());
+        self
+    }
+
+    /// Builds the `ClientHints` instance.
+    pub fn build(self) -> ClientHints {
+        self.hints
+    }
+}
+
+impl ClientHints {
+    /// Creates a new `ClientHintsBuilder`.
+    pub fn builder() -> ClientHintsBuilder {
+        ClientHintsBuilder {
+            hints: ClientHints::default(),
+        }
+    }
+
+    /// Returns the brand list.
+    #[inline]
+    pub fn brands(&self) -> &[Brand] {
+        &self.brands
+    }
+
+    /// Returns the low-entropy hints sent with every request to a secure origin.
+    ///
+    /// These are `sec-ch-ua`, `sec-ch-ua-mobile` and `sec-ch-ua-platform`.
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::with_capacity(3);
+        for name in [SEC_CH_UA, SEC_CH_UA_MOBILE, SEC_CH_UA_PLATFORM] {
+            if let Some(value) = self.hint(&name) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// Returns the value of the given hint, if it can be derived from these hints.
+    ///
+    /// `sec-ch-ua-full-version-list` and the other high-entropy hints are available on demand
+    /// through this method.
+    pub fn hint(&self, name: &HeaderName) -> Option<HeaderValue> {
+        let value = if name == SEC_CH_UA {
+            self.brand_list(Brand::major_version)?
+        } else if name == SEC_CH_UA_FULL_VERSION_LIST {
+            self.brand_list(Brand::version)?
+        } else if name == SEC_CH_UA_FULL_VERSION {
+            // The deprecated hint reports the version of the first non-GREASE brand.
+            let brand = self
+                .brands
+                .iter()
+                .find(|brand| !is_grease(brand.name()))
+                .or(self.brands.first())?;
+            quote(brand.version())
+        } else if name == SEC_CH_UA_MOBILE {
+            if self.mobile { "?1" } else { "?0" }.to_owned()
+        } else if name == SEC_CH_UA_PLATFORM {
+            quote(self.platform.as_deref()?)
+        } else if name == SEC_CH_UA_PLATFORM_VERSION {
+            quote(self.platform_version.as_deref()?)
+        } else if name == SEC_CH_UA_ARCH {
+            quote(self.arch.as_deref()?)
+        } else if name == SEC_CH_UA_BITNESS {
+            quote(self.bitness.as_deref()?)
+        } else if name == SEC_CH_UA_MODEL {
+            quote(self.model.as_deref().unwrap_or_default())
+        } else {
+            return None;
+        };
+
+        HeaderValue::from_str(&value).ok()
+    }
+
+    fn brand_list(&self, version: fn(&Brand) -> &str) -> Option<String> {
+        if self.brands.is_empty() {
+            return None;
+        }
+
+        let list = self
+            .brands
+            .iter()
+            .map(|brand| format!("{};v={}", quote(brand.name()), quote(version(brand))))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(list)
+    }
+}
+
+#[inline]
+fn quote(value: &str) -> String {
+    format!("\"{value}\"")
+}
+
+/// GREASE brands look like `Not-A.Brand`, `Not A(Brand` or `Not/A)Brand`.
+#[inline]
+fn is_grease(name: &str) -> bool {
+    name.starts_with("Not") && name.ends_with("Brand")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chrome() -> ClientHints {
+        ClientHints::builder()
+            .brand(Brand::new("Not-A.Brand", "99.0.0.0"))
+            .brand(Brand::new("Chromium", "124.0.6367.60"))
+            .brand(Brand::new("Google Chrome", "124.0.6367.60"))
+            .platform("macOS")
+            .platform_version("14.4.0")
+            .build()
+    }
+
+    #[test]
+    fn low_entropy_headers() {
+        let headers = chrome().headers();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(
+            headers[SEC_CH_UA],
+            r#""Not-A.Brand";v="99", "Chromium";v="124", "Google Chrome";v="124""#
+        );
+        assert_eq!(headers[SEC_CH_UA_MOBILE], "?0");
+        assert_eq!(headers[SEC_CH_UA_PLATFORM], r#""macOS""#);
+    }
+
+    #[test]
+    fn high_entropy_hints() {
+        let hints = chrome();
+        assert_eq!(
+            hints.hint(&SEC_CH_UA_FULL_VERSION_LIST).unwrap(),
+            r#""Not-A.Brand";v="99.0.0.0", "Chromium";v="124.0.6367.60", "Google Chrome";v="124.0.6367.60""#
+        );
+        assert_eq!(
+            hints.hint(&SEC_CH_UA_FULL_VERSION).unwrap(),
+            r#""124.0.6367.60""#
+        );
+        assert_eq!(
+            hints.hint(&SEC_CH_UA_PLATFORM_VERSION).unwrap(),
+            r#""14.4.0""#
+        );
+        assert_eq!(hints.hint(&SEC_CH_UA_MODEL).unwrap(), r#""""#);
+        assert!(hints.hint(&SEC_CH_UA_ARCH).is_none());
+        assert!(hints.hint(&HeaderName::from_static("sec-ch-dpr")).is_none());
+    }
+}
@@ -0,0 +1,360 @@
+//! Responses to `data:` and `file:` URLs, built by the client itself
+
+use std::{
+    future::Future,
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use base64::{
+    Engine, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use bytes::{Bytes, BytesMut};
+use http::{
+    HeaderValue, Method, StatusCode,
+    header::{ACCEPT_RANGES, ALLOW, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE},
+};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use http_body_util::Full;
+use percent_encoding::percent_decode_str;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf, Take},
+};
+use url::{Position, Url};
+
+use super::{
+    body::{self, ResponseBody},
+    request::Request,
+    response::Response,
+};
+use crate::Error;
+
+/// Alias for the future of a response built by the client.
+pub(crate) type LocalResponse = Pin<Box<dyn Future<Output = crate::Result<Response>> + Send>>;
+
+/// The size of the chunks a file is read in.
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The media type of a `data:` URL without one.
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// The forgiving base64 decoding of `data:` URLs, accepting missing padding.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+/// The URL schemes a client answers itself, without a network request.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LocalUrls {
+    data: bool,
+    file_root: Option<Arc<Path>>,
+}
+
+impl LocalUrls {
+    pub(crate) fn set_data(&mut self, enabled: bool) {
+        self.data = enabled;
+    }
+
+    pub(crate) fn set_file_root(&mut self, root: PathBuf) {
+        self.file_root = Some(root.into());
+    }
+
+    /// Returns whether the URL in `url` is of a scheme answered by the client.
+    pub(crate) fn accepts(&self, url: &str) -> bool {
+        match url.trim_start().split_once(':') {
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("data") => self.data,
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("file") => self.file_root.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Returns the response to `request`, if its URL is of a scheme answered by the client.
+    pub(crate) fn respond(&self, request: &Request) -> Option<LocalResponse> {
+        let method = request.method().clone();
+        let url = request.url().clone();
+        match url.scheme() {
+            "data" if self.data => Some(Box::pin(async move { data(method, url) })),
+            "file" => {
+                let root = self.file_root.clone()?;
+                let range = request.headers().get(RANGE).cloned();
+                Some(Box::pin(file(method, range, url, root)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Responds with the decoded content of a `data:` URL.
+fn data(method: Method, url: Url) -> crate::Result<Response> {
+    let Some((content_type, content)) = decode_data(&url) else {
+        return Err(Error::builder("invalid data: URL").with_url(url));
+    };
+
+    let len = content.len() as u64;
+    let content = if method == Method::HEAD {
+        Bytes::new()
+    } else {
+        content
+    };
+
+    let mut res = http::Response::new(body::boxed(Full::new(content)));
+    res.headers_mut().insert(CONTENT_TYPE, content_type);
+    res.headers_mut().insert(CONTENT_LENGTH, len.into());
+    Ok(Response::new(res, url))
+}
+
+/// Decodes a `data:` URL into its media type and its content.
+fn decode_data(url: &Url) -> Option<(HeaderValue, Bytes)> {
+    let (media_type, content) = url[Position::BeforePath..Position::AfterQuery].split_once(',')?;
+    let content = percent_decode_str(content).collect::<Vec<u8>>();
+
+    let media_type = media_type.trim_matches(|c: char| c.is_ascii_whitespace());
+    let (media_type, content) = match strip_base64(media_type) {
+        Some(media_type) => {
+            let content = content
+                .into_iter()
+                .filter(|b| !b.is_ascii_whitespace())
+                .collect::<Vec<u8>>();
+            (media_type, BASE64.decode(content).ok()?)
+        }
+        None => (media_type, content),
+    };
+
+    let content_type = if media_type.starts_with(';') {
+        HeaderValue::from_str(&format!("text/plain{media_type}")).ok()
+    } else if media_type.contains('/') {
+        HeaderValue::from_str(media_type).ok()
+    } else {
+        None
+    };
+    let content_type = content_type.unwrap_or(HeaderValue::from_static(DEFAULT_MEDIA_TYPE));
+    Some((content_type, Bytes::from(content)))
+}
+
+/// Strips the `;base64` parameter ending a media type, if any.
+fn strip_base64(media_type: &str) -> Option<&str> {
+    let (rest, param) = media_type.rsplit_once(';')?;
+    param
+        .trim_matches(' ')
+        .eq_ignore_ascii_case("base64")
+        .then(|| rest.trim_end_matches(' '))
+}
+
+/// Responds with the content of a file under `root`.
+async fn file(
+    method: Method,
+    range: Option<HeaderValue>,
+    url: Url,
+    root: Arc<Path>,
+) -> crate::Result<Response> {
+    if method != Method::GET && method != Method::HEAD {
+        let mut res = empty(StatusCode::METHOD_NOT_ALLOWED);
+        res.headers_mut()
+            .insert(ALLOW, HeaderValue::from_static("GET, HEAD"));
+        return Ok(Response::new(res, url));
+    }
+    let Ok(path) = url.to_file_path() else {
+        return Ok(Response::new(empty(StatusCode::NOT_FOUND), url));
+    };
+
+    let head = method == Method::HEAD;
+    let res = match read_file(&root, &path, range.as_ref(), head).await {
+        Ok(res) => res,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => empty(StatusCode::NOT_FOUND),
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => empty(StatusCode::FORBIDDEN),
+        Err(err) => return Err(Error::request(err).with_url(url)),
+    };
+    Ok(Response::new(res, url))
+}
+
+/// Opens the file at `path`, or the byte range of it requested by `range`, as a body reading
+/// it as it is polled.
+async fn read_file(
+    root: &Path,
+    path: &Path,
+    range: Option<&HeaderValue>,
+    head: bool,
+) -> io::Result<http::Response<ResponseBody>> {
+    let path = tokio::fs::canonicalize(path).await?;
+    if !path.starts_with(tokio::fs::canonicalize(root).await?) {
+        return Err(io::ErrorKind::PermissionDenied.into());
+    }
+
+    let mut file = File::open(&path).await?;
+    let metadata = file.metadata().await?;
+    if !metadata.is_file() {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+    let len = metadata.len();
+
+    let (status, start, count) = match range.and_then(|range| parse_range(range, len)) {
+        Some(Some((start, end))) => (StatusCode::PARTIAL_CONTENT, start, end - start + 1),
+        Some(None) => {
+            let mut res = empty(StatusCode::RANGE_NOT_SATISFIABLE);
+            res.headers_mut().insert(
+                CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{len}")).expect("valid header value"),
+            );
+            return Ok(res);
+        }
+        None => (StatusCode::OK, 0, len),
+    };
+
+    let body = if head {
+        body::boxed(Full::new(Bytes::new()))
+    } else {
+        file.seek(SeekFrom::Start(start)).await?;
+        body::boxed(FileBody {
+            file: file.take(count),
+            chunk: BytesMut::new(),
+        })
+    };
+
+    let mut res = http::Response::new(body);
+    *res.status_mut() = status;
+    res.headers_mut()
+        .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    res.headers_mut().insert(CONTENT_LENGTH, count.into());
+    if status == StatusCode::PARTIAL_CONTENT {
+        let value = format!("bytes {}-{}/{len}", start, start + count - 1);
+        res.headers_mut().insert(
+            CONTENT_RANGE,
+            HeaderValue::from_str(&value).expect("valid header value"),
+        );
+    }
+    Ok(res)
+}
+
+/// The content of a file, read in chunks as the body is polled.
+struct FileBody {
+    // Limited to the bytes of the response.
+    file: Take<File>,
+    chunk: BytesMut,
+}
+
+impl HttpBody for FileBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        let this = &mut *self;
+        let remaining = this.file.limit();
+        if remaining == 0 {
+            return Poll::Ready(None);
+        }
+
+        let len = usize::try_from(remaining).map_or(FILE_CHUNK_SIZE, |n| n.min(FILE_CHUNK_SIZE));
+        this.chunk.resize(len, 0);
+        let mut buf = ReadBuf::new(&mut this.chunk[..]);
+        ready!(Pin::new(&mut this.file).poll_read(cx, &mut buf))?;
+        let n = buf.filled().len();
+        if n == 0 {
+            // The file was truncated after its length was sent.
+            return Poll::Ready(Some(Err(io::ErrorKind::UnexpectedEof.into())));
+        }
+        Poll::Ready(Some(Ok(Frame::data(this.chunk.split_to(n).freeze()))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.file.limit() == 0
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.file.limit())
+    }
+}
+
+/// Parses a `Range` header of a single byte range of a resource of `len` bytes.
+///
+/// Returns the inclusive range to send, `Some(None)` if the range is unsatisfiable, or `None`
+/// if the header is to be ignored, such as one requesting several ranges.
+fn parse_range(value: &HeaderValue, len: u64) -> Option<Option<(u64, u64)>> {
+    let spec = value.to_str().ok()?.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        let suffix = end.parse::<u64>().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(None);
+        }
+        return Some(Some((len.saturating_sub(suffix), len - 1)));
+    }
+
+    let start = start.parse::<u64>().ok()?;
+    let end = match end {
+        "" => u64::MAX,
+        end => end.parse::<u64>().ok()?,
+    };
+    if end < start {
+        return None;
+    }
+    if start >= len {
+        return Some(None);
+    }
+    Some(Some((start, end.min(len - 1))))
+}
+
+/// Returns an empty response with `status`.
+fn empty(status: StatusCode) -> http::Response<ResponseBody> {
+    let mut res = http::Response::new(body::boxed(Full::new(Bytes::new())));
+    *res.status_mut() = status;
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(url: &str) -> Option<(HeaderValue, Bytes)> {
+        decode_data(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn data_url() {
+        let (content_type, content) = decode("data:,Hello%2C%20World%21").unwrap();
+        assert_eq!(content_type, DEFAULT_MEDIA_TYPE);
+        assert_eq!(content, "Hello, World!");
+
+        let (content_type, content) = decode("data:text/html;base64,PGI+aGk8L2I+").unwrap();
+        assert_eq!(content_type, "text/html");
+        assert_eq!(content, "<b>hi</b>");
+
+        let (content_type, content) = decode("data:;charset=utf-8 ; BASE64,aGk#frag").unwrap();
+        assert_eq!(content_type, "text/plain;charset=utf-8");
+        assert_eq!(content, "hi");
+
+        assert!(decode("data:text/plain").is_none());
+        assert!(decode("data:;base64,a").is_none());
+    }
+
+    #[test]
+    fn range_header() {
+        let range = |value: &'static str, len| parse_range(&HeaderValue::from_static(value), len);
+
+        assert_eq!(range("bytes=0-4", 10), Some(Some((0, 4))));
+        assert_eq!(range("bytes=5-", 10), Some(Some((5, 9))));
+        assert_eq!(range("bytes=2-100", 10), Some(Some((2, 9))));
+        assert_eq!(range("bytes=-3", 10), Some(Some((7, 9))));
+        assert_eq!(range("bytes=-30", 10), Some(Some((0, 9))));
+        assert_eq!(range("bytes=10-", 10), Some(None));
+        assert_eq!(range("bytes=-0", 10), Some(None));
+        assert_eq!(range("bytes=0-1,3-4", 10), None);
+        assert_eq!(range("bytes=4-2", 10), None);
+        assert_eq!(range("items=0-4", 10), None);
+    }
+}
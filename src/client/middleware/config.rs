@@ -1,6 +1,12 @@
 use std::time::Duration;
+#[cfg(feature = "cookies")]
+use std::{fmt, sync::Arc};
 
-use crate::{core::ext::RequestConfigValue, redirect::Policy};
+use crate::{
+    core::ext::RequestConfigValue,
+    redirect::{Policy, ReferrerPolicy},
+    retry,
+};
 
 // ================================
 //
@@ -25,12 +31,67 @@ pub(crate) type RequestTotalTimeout = RequestTimeout;
 
 pub(crate) type RequestReadTimeout = RequestTimeout;
 
+#[derive(Clone, Copy)]
+pub(crate) struct RequestBodyStallTimeout;
+
+impl RequestConfigValue for RequestBodyStallTimeout {
+    type Value = Duration;
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct RequestRedirectPolicy;
 impl RequestConfigValue for RequestRedirectPolicy {
     type Value = Policy;
 }
 
+#[derive(Clone, Copy)]
+pub(crate) struct RequestRetryPolicy;
+impl RequestConfigValue for RequestRetryPolicy {
+    type Value = retry::Policy;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestProxySession;
+impl RequestConfigValue for RequestProxySession {
+    type Value = u64;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestSigner;
+impl RequestConfigValue for RequestSigner {
+    type Value = std::sync::Arc<dyn crate::sign::Signer>;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestBufferBudget;
+impl RequestConfigValue for RequestBufferBudget {
+    type Value = crate::budget::BufferBudget;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestMaxBufferedBytes;
+impl RequestConfigValue for RequestMaxBufferedBytes {
+    type Value = usize;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestPriority;
+impl RequestConfigValue for RequestPriority {
+    type Value = crate::Priority;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestFetchMode;
+impl RequestConfigValue for RequestFetchMode {
+    type Value = crate::FetchMode;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestReferrerPolicy;
+impl RequestConfigValue for RequestReferrerPolicy {
+    type Value = ReferrerPolicy;
+}
+
 #[cfg(any(
     feature = "gzip",
     feature = "zstd",
@@ -55,3 +116,55 @@ pub(crate) struct RequestSkipDefaultHeaders;
 impl RequestConfigValue for RequestSkipDefaultHeaders {
     type Value = bool;
 }
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestClientHints;
+impl RequestConfigValue for RequestClientHints {
+    type Value = crate::ClientHints;
+}
+
+#[cfg(feature = "cookies")]
+#[derive(Clone, Copy)]
+pub(crate) struct RequestCookieStore;
+#[cfg(feature = "cookies")]
+impl RequestConfigValue for RequestCookieStore {
+    type Value = SharedCookieStore;
+}
+
+/// A shared [`CookieStore`](crate::cookie::CookieStore) that can be carried by a request.
+#[cfg(feature = "cookies")]
+#[derive(Clone)]
+pub(crate) struct SharedCookieStore(pub(crate) Arc<dyn crate::cookie::CookieStore>);
+
+#[cfg(feature = "cookies")]
+impl fmt::Debug for SharedCookieStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SharedCookieStore")
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestAcceptedHints;
+impl RequestConfigValue for RequestAcceptedHints {
+    type Value = crate::client::middleware::hints::AcceptedHints;
+}
+
+#[cfg(feature = "cookies")]
+#[derive(Clone, Copy)]
+pub(crate) struct RequestSession;
+#[cfg(feature = "cookies")]
+impl RequestConfigValue for RequestSession {
+    type Value = crate::client::session::SessionContext;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestRobotsAgent;
+impl RequestConfigValue for RequestRobotsAgent {
+    type Value = String;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestCrawlDelay;
+impl RequestConfigValue for RequestCrawlDelay {
+    type Value = Duration;
+}
@@ -0,0 +1,123 @@
+//! Request context attached to errors.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use http::{Method, Uri};
+
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::{client::transfer::TransferCounter, redirect::HistoryEntry, sync::Mutex};
+
+/// Tracks a request through the client service stack, carried in the request extensions.
+///
+/// The redirect and retry layers record their progress here, so that an error surfacing from
+/// any layer can report the method and URI of the failed hop, its attempt number and the
+/// elapsed time since the request was sent.
+///
+/// It also counts the bytes transferred by the request. With the `metrics` feature, it carries
+/// the metrics of the client, which the retry layer and the response future record to.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestContext(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    start: Instant,
+    hop: Mutex<Hop>,
+    transfer: TransferCounter,
+    #[cfg(feature = "metrics")]
+    metrics: Option<(Metrics, Method)>,
+}
+
+#[derive(Debug)]
+struct Hop {
+    method: Method,
+    uri: Option<Uri>,
+    attempt: u32,
+    history: Vec<HistoryEntry>,
+}
+
+/// A snapshot of a [`RequestContext`].
+#[derive(Debug)]
+pub(crate) struct ContextSnapshot {
+    pub(crate) method: Method,
+    pub(crate) uri: Option<Uri>,
+    pub(crate) attempt: u32,
+    pub(crate) elapsed: Duration,
+}
+
+impl RequestContext {
+    /// Starts tracking a request sent with the given method, counting its bytes with `transfer`.
+    pub(crate) fn new(
+        method: Method,
+        transfer: TransferCounter,
+        #[cfg(feature = "metrics")] metrics: Option<Metrics>,
+    ) -> RequestContext {
+        RequestContext(Arc::new(Inner {
+            start: Instant::now(),
+            transfer,
+            #[cfg(feature = "metrics")]
+            metrics: metrics.map(|metrics| (metrics, method.clone())),
+            hop: Mutex::new(Hop {
+                method,
+                uri: None,
+                attempt: 1,
+                history: Vec::new(),
+            }),
+        }))
+    }
+
+    /// Records that the request is sent to `uri`, following the redirect `followed` if any.
+    pub(crate) fn redirected(&self, method: &Method, uri: &Uri, followed: Option<HistoryEntry>) {
+        let mut hop = self.0.hop.lock();
+        hop.method = method.clone();
+        hop.uri = Some(uri.clone());
+        hop.attempt = 1;
+        hop.history.extend(followed);
+    }
+
+    /// Returns the counter of the bytes transferred by the request.
+    pub(crate) fn transfer(&self) -> &TransferCounter {
+        &self.0.transfer
+    }
+
+    /// Returns the redirects followed so far.
+    pub(crate) fn history(&self) -> Vec<HistoryEntry> {
+        self.0.hop.lock().history.clone()
+    }
+
+    /// Records that the current hop is retried.
+    pub(crate) fn retried(&self) {
+        self.0.hop.lock().attempt += 1;
+
+        #[cfg(feature = "metrics")]
+        if let Some((ref metrics, _)) = self.0.metrics {
+            metrics.retry();
+        }
+    }
+
+    /// Records the outcome of the request, with the status of its final response if any and
+    /// whether that response was received on a reused connection.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn finished(&self, status: Option<http::StatusCode>, reused: Option<bool>) {
+        if let Some((ref metrics, ref method)) = self.0.metrics {
+            metrics.request(method, status, self.0.start);
+            if let Some(reused) = reused {
+                metrics.connection_reused(reused);
+            }
+        }
+    }
+
+    /// Takes a snapshot of the request progress.
+    pub(crate) fn snapshot(&self) -> ContextSnapshot {
+        let hop = self.0.hop.lock();
+        ContextSnapshot {
+            method: hop.method.clone(),
+            uri: hop.uri.clone(),
+            attempt: hop.attempt,
+            elapsed: self.0.start.elapsed(),
+        }
+    }
+}
@@ -8,18 +8,24 @@ use tower::Layer;
 use tower_service::Service;
 
 use super::future::ResponseFuture;
-use crate::cookie::CookieStore;
+use crate::{
+    client::middleware::config::{RequestCookieStore, SharedCookieStore},
+    cookie::CookieStore,
+    core::ext::{RequestConfig, SharedHeaders},
+};
 
 /// Layer to apply [`CookieManager`] middleware.
 #[derive(Clone)]
 pub struct CookieManagerLayer {
-    cookie_store: Option<Arc<dyn CookieStore>>,
+    cookie_store: RequestConfig<RequestCookieStore>,
 }
 
 impl CookieManagerLayer {
     /// Create a new cookie manager layer.
-    pub const fn new(cookie_store: Option<Arc<dyn CookieStore + 'static>>) -> Self {
-        Self { cookie_store }
+    pub fn new(cookie_store: Option<Arc<dyn CookieStore + 'static>>) -> Self {
+        Self {
+            cookie_store: RequestConfig::new(cookie_store.map(SharedCookieStore)),
+        }
     }
 }
 
@@ -38,7 +44,7 @@ impl<S> Layer<S> for CookieManagerLayer {
 #[derive(Clone)]
 pub struct CookieManager<S> {
     inner: S,
-    cookie_store: Option<Arc<dyn CookieStore>>,
+    cookie_store: RequestConfig<RequestCookieStore>,
 }
 
 impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for CookieManager<S>
@@ -55,14 +61,19 @@ where
     }
 
     fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
-        // If a cookie store is present, inject cookies for this URL if not already set.
-        if let Some(ref cookie_store) = self.cookie_store {
-            // Try to extract the request URL.
-            let mut url = None;
-            if req.headers().get(COOKIE).is_none() {
-                url = url::Url::parse(&req.uri().to_string()).ok();
+        // The request may carry its own cookie store, which takes precedence over the client's.
+        let cookie_store = self
+            .cookie_store
+            .fetch(req.extensions())
+            .map(|store| store.0.clone());
 
-                if let Some(ref url) = url {
+        // If a cookie store is present, inject cookies for this URL if not already set.
+        if let Some(cookie_store) = cookie_store {
+            // Try to extract the request URL, to store the cookies of the response even when the
+            // request sets its own.
+            let url = url::Url::parse(&req.uri().to_string()).ok();
+            if let Some(ref url) = url {
+                if !SharedHeaders::contains(&req, &COOKIE) {
                     let headers = req.headers_mut();
                     if let Some(cookie_headers) = cookie_store.cookies(url) {
                         for header in cookie_headers {
@@ -74,7 +85,7 @@ where
 
             ResponseFuture::WithCookieStore {
                 future: self.inner.call(req),
-                cookie_store: cookie_store.clone(),
+                cookie_store,
                 url,
             }
         } else {
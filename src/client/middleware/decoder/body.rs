@@ -0,0 +1,717 @@
+use std::{
+    io::{self, Write},
+    mem,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, ready},
+};
+
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderValue,
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING},
+    response::Parts,
+};
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+
+use super::{AcceptEncoding, Coding, ContentDecoders};
+use crate::{
+    client::transfer::TransferCounter,
+    core::common::buf::{BufPool, PooledBuf},
+    decoding::{ContentDecoder, UnknownEncoding},
+    error::BoxError,
+    sync::Mutex,
+};
+
+pin_project! {
+    /// Response body of [`Decompression`](super::Decompression).
+    ///
+    /// The body is decoded through every content coding of the response, in the reverse order
+    /// of their application. Bodies with a coding the client doesn't decode are handled as set
+    /// by [`UnknownEncoding`].
+    pub struct DecompressionBody<B> {
+        #[pin]
+        inner: B,
+        state: State,
+        trailers: Option<HeaderMap>,
+        // Counts the data of `inner`, before it is decoded.
+        transfer: Option<TransferCounter>,
+    }
+}
+
+enum State {
+    Passthrough,
+    Decoding(Decoder),
+    Failed(Option<io::Error>),
+    Done,
+}
+
+/// The size of the input slices decoded between two decompression ratio checks.
+const RATIO_CHECK_SLICE: usize = 1024;
+
+/// The largest gzip body buffered to be decoded in one shot with libdeflate.
+#[cfg(feature = "libdeflate")]
+const ONE_SHOT_MAX_LEN: u64 = 16 * 1024 * 1024;
+
+/// The largest ratio between the decoded and encoded sizes of a deflate stream.
+#[cfg(feature = "libdeflate")]
+const DEFLATE_MAX_RATIO: u64 = 1032;
+
+/// A chain of decoders, the first one undoing the last coding applied to the body.
+struct Decoder {
+    stages: Vec<Stage>,
+    /// The output of each stage, reused from one chunk to the next.
+    scratch: Vec<Vec<u8>>,
+    /// The decoded chunks, split off a buffer recycled once they have all been dropped.
+    out: PooledBuf,
+    bypass: Arc<AtomicBool>,
+    started: bool,
+    max_ratio: Option<u32>,
+    read: u64,
+    decoded: u64,
+}
+
+/// A switch, stored in the response extensions, to pass the body through without decoding it.
+///
+/// It only takes effect if the body has not been read yet. It keeps the coding headers removed
+/// from the response, so that they can be restored along with the encoded body.
+#[derive(Clone, Debug)]
+pub(crate) struct BypassDecompression {
+    switch: Arc<AtomicBool>,
+    headers: Arc<Mutex<Option<HeaderMap>>>,
+}
+
+impl BypassDecompression {
+    fn new(headers: Option<HeaderMap>) -> Self {
+        BypassDecompression {
+            switch: Arc::new(AtomicBool::new(false)),
+            headers: Arc::new(Mutex::new(headers)),
+        }
+    }
+
+    /// Disables the decompression of the response body.
+    ///
+    /// Returns the coding headers removed from the response, unless the body was already read
+    /// decoded.
+    #[inline]
+    pub(crate) fn bypass(&self) -> Option<HeaderMap> {
+        self.switch.store(true, Ordering::Relaxed);
+        self.headers.lock().take()
+    }
+
+    /// Enables the decompression of the response body, if it is decompressed on demand.
+    ///
+    /// The coding headers removed from the response no longer apply once the body is read.
+    #[inline]
+    pub(crate) fn decompress(&self) {
+        self.switch.store(false, Ordering::Relaxed);
+        self.headers.lock().take();
+    }
+}
+
+/// A coding to undo, as resolved from the response headers.
+enum Codec {
+    Builtin(Coding),
+    Custom(Box<dyn ContentDecoder>),
+    /// A coding failing the body, as set by [`UnknownEncoding::Error`].
+    Unsupported(String),
+}
+
+enum Stage {
+    Custom(Box<dyn ContentDecoder>),
+    Unsupported(String),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::MultiGzDecoder<Vec<u8>>),
+    /// A gzip body of known length, buffered until complete and decoded in one shot.
+    #[cfg(feature = "libdeflate")]
+    GzipOneShot {
+        input: Vec<u8>,
+        max_ratio: Option<u32>,
+    },
+    #[cfg(feature = "deflate")]
+    Deflate {
+        inflate: flate2::Decompress,
+        done: bool,
+    },
+    #[cfg(feature = "brotli")]
+    Brotli(Box<brotli::DecompressorWriter<Vec<u8>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Decoder<'static, Vec<u8>>),
+}
+
+impl<B> DecompressionBody<B> {
+    /// Wraps a body without decoding it, counting its data into `transfer`, if any.
+    #[inline]
+    pub(super) fn passthrough(inner: B, transfer: Option<TransferCounter>) -> Self {
+        DecompressionBody {
+            inner,
+            state: State::Passthrough,
+            trailers: None,
+            transfer,
+        }
+    }
+
+    /// Wraps a body decoded according to the codings of the response headers.
+    ///
+    /// When the body is decoded, a [`BypassDecompression`] switch is added to the response
+    /// extensions. Unless it is decoded `on_demand`, the coding headers that no longer apply are
+    /// moved from the response to the switch. Otherwise, they are kept and the switch starts
+    /// bypassed, so that the body is only decoded once the switch is flipped by a decoding read.
+    /// The decoded chunks are split off a buffer taken from `pool`, if any. The encoded data is
+    /// counted into `transfer`, if any.
+    pub(super) fn new(
+        inner: B,
+        parts: &mut Parts,
+        accept: &AcceptEncoding,
+        decoders: &ContentDecoders,
+        max_ratio: Option<u32>,
+        on_demand: bool,
+        pool: Option<BufPool>,
+        transfer: Option<TransferCounter>,
+    ) -> Self {
+        let codings = match codings(&parts.headers, accept, decoders) {
+            Some(codings) => codings,
+            None => return DecompressionBody::passthrough(inner, transfer),
+        };
+
+        #[cfg(feature = "libdeflate")]
+        let len = parts
+            .headers
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+
+        let bypass = if on_demand {
+            let bypass = BypassDecompression::new(None);
+            bypass.bypass();
+            bypass
+        } else {
+            let mut removed = HeaderMap::new();
+            for name in [CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING] {
+                for value in parts.headers.get_all(&name) {
+                    removed.append(name.clone(), value.clone());
+                }
+            }
+            parts.headers.remove(CONTENT_ENCODING);
+            parts.headers.remove(CONTENT_LENGTH);
+            retain_chunked(&mut parts.headers);
+            BypassDecompression::new(Some(removed))
+        };
+        parts.extensions.insert(bypass.clone());
+
+        let state = match Decoder::new(codings, max_ratio, bypass.switch, pool) {
+            Ok(decoder) => {
+                #[cfg(feature = "libdeflate")]
+                let decoder = decoder.one_shot(len);
+                State::Decoding(decoder)
+            }
+            Err(err) => State::Failed(Some(err)),
+        };
+
+        DecompressionBody {
+            inner,
+            state,
+            trailers: None,
+            transfer,
+        }
+    }
+}
+
+impl<B> Body for DecompressionBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            let decoder = match this.state {
+                State::Passthrough => {
+                    let frame = ready!(this.inner.as_mut().poll_frame(cx));
+                    count(this.transfer, &frame);
+                    return Poll::Ready(frame.map(|frame| frame.map_err(Into::into)));
+                }
+                State::Decoding(decoder)
+                    if !decoder.started && decoder.bypass.load(Ordering::Relaxed) =>
+                {
+                    *this.state = State::Passthrough;
+                    continue;
+                }
+                State::Decoding(decoder) => decoder,
+                State::Failed(err) => {
+                    let err = err.take().map(Into::into);
+                    *this.state = State::Done;
+                    return Poll::Ready(err.map(Err));
+                }
+                State::Done => {
+                    return Poll::Ready(this.trailers.take().map(|t| Ok(Frame::trailers(t))));
+                }
+            };
+
+            let frame = ready!(this.inner.as_mut().poll_frame(cx));
+            count(this.transfer, &frame);
+            match frame {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => match decoder.decode(&data) {
+                        Ok(data) if data.is_empty() => continue,
+                        Ok(data) => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                        Err(err) => *this.state = State::Failed(Some(err)),
+                    },
+                    Err(frame) => {
+                        // Trailers end the body: flush the decoders before yielding them.
+                        *this.trailers = frame.into_trailers().ok();
+                        if let Some(data) = finish(this.state)? {
+                            return Poll::Ready(Some(Ok(Frame::data(data))));
+                        }
+                    }
+                },
+                Some(Err(err)) => {
+                    *this.state = State::Done;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                None => {
+                    if let Some(data) = finish(this.state)? {
+                        return Poll::Ready(Some(Ok(Frame::data(data))));
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self.state {
+            State::Passthrough => self.inner.is_end_stream(),
+            State::Done => self.trailers.is_none(),
+            _ => false,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self.state {
+            State::Passthrough => self.inner.size_hint(),
+            _ => SizeHint::default(),
+        }
+    }
+}
+
+/// Counts the data of a frame of the encoded body into `transfer`, if any.
+fn count<E>(transfer: &Option<TransferCounter>, frame: &Option<Result<Frame<Bytes>, E>>) {
+    if let (Some(transfer), Some(Ok(frame))) = (transfer, frame) {
+        if let Some(data) = frame.data_ref() {
+            transfer.response_body(data.len());
+        }
+    }
+}
+
+/// Finishes the decoders, returning the data they still held.
+fn finish(state: &mut State) -> Result<Option<Bytes>, BoxError> {
+    let data = match mem::replace(state, State::Done) {
+        State::Decoding(mut decoder) => decoder.finish()?,
+        _ => return Ok(None),
+    };
+
+    Ok((!data.is_empty()).then_some(data))
+}
+
+/// Returns the codings to undo, in decoding order, or `None` if the body must be passed through.
+fn codings(
+    headers: &HeaderMap,
+    accept: &AcceptEncoding,
+    decoders: &ContentDecoders,
+) -> Option<Vec<Codec>> {
+    // Transfer codings are applied on top of the content codings.
+    let tokens = headers
+        .get_all(CONTENT_ENCODING)
+        .iter()
+        .chain(headers.get_all(TRANSFER_ENCODING))
+        .map(|value| value.to_str().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut codings = Vec::new();
+    for token in tokens.iter().flat_map(|value| value.split(',')) {
+        // Parameters, such as in `identity;q=0`, have no meaning in a response.
+        let token = token.split(';').next().unwrap_or_default().trim();
+        if token.is_empty()
+            || token.eq_ignore_ascii_case("identity")
+            || token.eq_ignore_ascii_case("chunked")
+        {
+            continue;
+        }
+
+        let codec = if let Some(decoder) = decoders.decoder(token) {
+            Codec::Custom(decoder)
+        } else if let Some(coding) = accept.coding(token) {
+            Codec::Builtin(coding)
+        } else {
+            match decoders.unknown() {
+                // A single unknown coding makes the whole body undecodable.
+                UnknownEncoding::Passthrough => return None,
+                UnknownEncoding::Error => Codec::Unsupported(token.to_owned()),
+                UnknownEncoding::Decode(callback) => Codec::Custom(callback(token)?),
+            }
+        };
+        codings.push(codec);
+    }
+
+    if codings.is_empty() {
+        return None;
+    }
+
+    codings.reverse();
+    Some(codings)
+}
+
+/// Strips the decoded codings from the `Transfer-Encoding` header.
+fn retain_chunked(headers: &mut HeaderMap) {
+    if !headers.contains_key(TRANSFER_ENCODING) {
+        return;
+    }
+
+    let chunked = headers
+        .get_all(TRANSFER_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| token.trim().eq_ignore_ascii_case("chunked"));
+
+    headers.remove(TRANSFER_ENCODING);
+    if chunked {
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+    }
+}
+
+impl Decoder {
+    fn new(
+        codings: Vec<Codec>,
+        max_ratio: Option<u32>,
+        bypass: Arc<AtomicBool>,
+        pool: Option<BufPool>,
+    ) -> io::Result<Decoder> {
+        let stages = codings
+            .into_iter()
+            .map(Stage::new)
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Decoder {
+            scratch: vec![Vec::new(); stages.len()],
+            stages,
+            out: pool.map(PooledBuf::new).unwrap_or_default(),
+            bypass,
+            started: false,
+            max_ratio,
+            read: 0,
+            decoded: 0,
+        })
+    }
+
+    /// Decodes a gzip body of known length in one shot with libdeflate once it is complete,
+    /// provided it is small enough to be buffered.
+    #[cfg(feature = "libdeflate")]
+    fn one_shot(mut self, len: Option<u64>) -> Decoder {
+        match (self.stages.first_mut(), len) {
+            (Some(stage), Some(len))
+                if len <= ONE_SHOT_MAX_LEN && matches!(stage, Stage::Gzip(_)) =>
+            {
+                *stage = Stage::GzipOneShot {
+                    input: Vec::with_capacity(len as usize),
+                    max_ratio: self.max_ratio,
+                };
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Decodes a chunk of the body through every stage.
+    fn decode(&mut self, input: &[u8]) -> io::Result<Bytes> {
+        self.started = true;
+
+        // With a ratio limit, feed the input in small slices so that a highly compressed chunk
+        // cannot expand far past the limit before it is checked.
+        let slice = match self.max_ratio {
+            Some(_) => RATIO_CHECK_SLICE,
+            None => input.len().max(1),
+        };
+
+        for input in input.chunks(slice) {
+            let decoded = self.decode_slice(input)?;
+            self.account(input.len(), decoded)?;
+        }
+
+        Ok(self.out.split().freeze())
+    }
+
+    /// Decodes a slice of the body into the output buffer, returning the decoded length.
+    fn decode_slice(&mut self, input: &[u8]) -> io::Result<usize> {
+        let mut data = input;
+        for (stage, buf) in self.stages.iter_mut().zip(&mut self.scratch) {
+            buf.clear();
+            if data.is_empty() {
+                break;
+            }
+            stage.decode(data, buf)?;
+            data = buf.as_slice();
+        }
+
+        self.out.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    /// Finishes every stage, feeding the remaining output of a stage through the next one.
+    fn finish(&mut self) -> io::Result<Bytes> {
+        let mut data: &[u8] = &[];
+        for (stage, buf) in self.stages.iter_mut().zip(&mut self.scratch) {
+            buf.clear();
+            stage.decode(data, buf)?;
+            stage.finish(buf)?;
+            data = buf.as_slice();
+        }
+
+        let decoded = data.len();
+        self.out.extend_from_slice(data);
+        self.account(0, decoded)?;
+        Ok(self.out.split().freeze())
+    }
+
+    /// Tracks the body sizes, failing once the decompression ratio limit is exceeded.
+    fn account(&mut self, read: usize, decoded: usize) -> io::Result<()> {
+        self.read += read as u64;
+        self.decoded += decoded as u64;
+
+        match self.max_ratio {
+            Some(ratio) if self.decoded > self.read.saturating_mul(u64::from(ratio)) => {
+                Err(ratio_exceeded(ratio))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn unsupported(coding: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unsupported content coding `{coding}`"),
+    )
+}
+
+fn ratio_exceeded(ratio: u32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("decompression ratio exceeds the limit of {ratio}"),
+    )
+}
+
+impl Stage {
+    fn new(codec: Codec) -> io::Result<Stage> {
+        let coding = match codec {
+            Codec::Builtin(coding) => coding,
+            Codec::Custom(decoder) => return Ok(Stage::Custom(decoder)),
+            Codec::Unsupported(coding) => return Ok(Stage::Unsupported(coding)),
+        };
+
+        let stage = match coding {
+            #[cfg(feature = "gzip")]
+            Coding::Gzip => Stage::Gzip(flate2::write::MultiGzDecoder::new(Vec::new())),
+            #[cfg(feature = "deflate")]
+            Coding::Deflate => Stage::Deflate {
+                inflate: flate2::Decompress::new(true),
+                done: false,
+            },
+            #[cfg(feature = "brotli")]
+            Coding::Brotli => {
+                Stage::Brotli(Box::new(brotli::DecompressorWriter::new(Vec::new(), 4096)))
+            }
+            #[cfg(feature = "zstd")]
+            Coding::Zstd => Stage::Zstd(zstd::stream::write::Decoder::new(Vec::new())?),
+        };
+
+        Ok(stage)
+    }
+
+    /// Decodes a chunk, appending the decoded data to `out`.
+    ///
+    /// The decoders write into buffers that are drained rather than replaced, so that they keep
+    /// their capacity from one chunk to the next.
+    fn decode(&mut self, input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Stage::Custom(decoder) => decoder.decode(input, out)?,
+            Stage::Unsupported(coding) => return Err(unsupported(coding)),
+            #[cfg(feature = "gzip")]
+            Stage::Gzip(decoder) => {
+                decoder.write_all(input)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "libdeflate")]
+            Stage::GzipOneShot {
+                input: buffered, ..
+            } => buffered.extend_from_slice(input),
+            #[cfg(feature = "deflate")]
+            Stage::Deflate { inflate, done } => inflate_chunk(inflate, done, input, out)?,
+            #[cfg(feature = "brotli")]
+            Stage::Brotli(decoder) => {
+                decoder.write_all(input)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "zstd")]
+            Stage::Zstd(decoder) => {
+                decoder.write_all(input)?;
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+        }
+        Ok(())
+    }
+
+    /// Finishes decoding, appending the remaining decoded data to `out`.
+    fn finish(&mut self, out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Stage::Custom(decoder) => decoder.finish(out)?,
+            Stage::Unsupported(coding) => return Err(unsupported(coding)),
+            #[cfg(feature = "gzip")]
+            Stage::Gzip(decoder) => {
+                decoder.try_finish()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "libdeflate")]
+            Stage::GzipOneShot { input, max_ratio } => {
+                out.append(&mut gzip_one_shot(input, *max_ratio)?);
+            }
+            #[cfg(feature = "deflate")]
+            Stage::Deflate { done, .. } => {
+                if !*done {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
+            #[cfg(feature = "brotli")]
+            Stage::Brotli(decoder) => {
+                decoder.close()?;
+                out.append(decoder.get_mut());
+            }
+            #[cfg(feature = "zstd")]
+            Stage::Zstd(decoder) => {
+                decoder.flush()?;
+                out.append(decoder.get_mut());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a complete gzip body with libdeflate, into a buffer of the size recorded in its
+/// trailer.
+///
+/// Bodies libdeflate can't decode, such as those whose recorded size is wrong, fall back to
+/// the streaming decoder, and so do those that may hold several members, as libdeflate only
+/// decodes the first one. As the recorded size comes from the server, the ratio limit is
+/// enforced on the decoded data as it is produced there, not on the trailer.
+#[cfg(feature = "libdeflate")]
+fn gzip_one_shot(input: &[u8], max_ratio: Option<u32>) -> io::Result<Vec<u8>> {
+    // Every member starts with the magic bytes and the deflate method; a body that has them
+    // past its start, even by chance, is left to the streaming decoder.
+    let single_member = !input
+        .get(1..)
+        .unwrap_or_default()
+        .windows(3)
+        .any(|bytes| bytes == [0x1f, 0x8b, 0x08]);
+
+    // The trailer ends with the size of the decoded data, modulo 2^32.
+    if let Some(trailer) = input
+        .len()
+        .checked_sub(4)
+        .filter(|_| single_member)
+        .map(|at| &input[at..])
+    {
+        let size = u64::from(u32::from_le_bytes(trailer.try_into().expect("4 bytes")));
+        let read = input.len() as u64;
+        if let Some(ratio) = max_ratio.filter(|ratio| size > read.saturating_mul(u64::from(*ratio)))
+        {
+            return Err(ratio_exceeded(ratio));
+        }
+
+        if size <= read.saturating_mul(DEFLATE_MAX_RATIO) {
+            let mut out = vec![0; size as usize];
+            let mut decompressor = libdeflater::Decompressor::new();
+            if let Ok(len) = decompressor.gzip_decompress(input, &mut out) {
+                if len as u64 == size {
+                    return Ok(out);
+                }
+            }
+        }
+    }
+
+    let limit =
+        max_ratio.map(|ratio| (ratio, (input.len() as u64).saturating_mul(u64::from(ratio))));
+    let check = |out: &Vec<u8>| match limit {
+        Some((ratio, limit)) if out.len() as u64 > limit => Err(ratio_exceeded(ratio)),
+        _ => Ok(()),
+    };
+
+    let mut stage = Stage::Gzip(flate2::write::MultiGzDecoder::new(Vec::new()));
+    let mut out = Vec::new();
+    for input in input.chunks(RATIO_CHECK_SLICE) {
+        stage.decode(input, &mut out)?;
+        check(&out)?;
+    }
+    stage.finish(&mut out)?;
+    check(&out)?;
+    Ok(out)
+}
+
+/// Inflates a chunk of a zlib stream into `out`, rejecting any data past the end of the stream.
+#[cfg(feature = "deflate")]
+fn inflate_chunk(
+    inflate: &mut flate2::Decompress,
+    done: &mut bool,
+    mut input: &[u8],
+    out: &mut Vec<u8>,
+) -> io::Result<()> {
+    use flate2::{FlushDecompress, Status};
+
+    out.reserve(input.len().saturating_mul(2).max(64));
+    loop {
+        if *done {
+            if !input.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "trailing data after the end of a deflate stream",
+                ));
+            }
+            return Ok(());
+        }
+
+        if out.len() == out.capacity() {
+            out.reserve(8 * 1024);
+        }
+
+        let (total_in, total_out) = (inflate.total_in(), out.len());
+        let status = inflate
+            .decompress_vec(input, out, FlushDecompress::None)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        input = &input[(inflate.total_in() - total_in) as usize..];
+
+        match status {
+            Status::StreamEnd => *done = true,
+            // The output buffer has room left, so all the available input was consumed.
+            _ if out.len() < out.capacity() && input.is_empty() => return Ok(()),
+            // No progress was made at all.
+            _ if inflate.total_in() == total_in && out.len() == total_out => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "deflate stream made no progress",
+                ));
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use http::Response;
+use pin_project_lite::pin_project;
+
+use super::{AcceptEncoding, ContentDecoders, DecompressionBody};
+use crate::{client::transfer::TransferCounter, core::common::buf::BufPool};
+
+pin_project! {
+    /// Response future for [`Decompression`](super::Decompression).
+    pub struct ResponseFuture<F> {
+        #[pin]
+        pub(super) future: F,
+        pub(super) accept: Option<AcceptEncoding>,
+        pub(super) decoders: Arc<ContentDecoders>,
+        pub(super) max_ratio: Option<u32>,
+        pub(super) on_demand: bool,
+        pub(super) pool: Option<BufPool>,
+        pub(super) transfer: Option<TransferCounter>,
+    }
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = Result<Response<DecompressionBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.future.poll(cx)?);
+
+        let (mut parts, body) = res.into_parts();
+        let body = match this.accept.take() {
+            Some(accept) => DecompressionBody::new(
+                body,
+                &mut parts,
+                &accept,
+                this.decoders,
+                *this.max_ratio,
+                *this.on_demand,
+                this.pool.clone(),
+                this.transfer.take(),
+            ),
+            None => DecompressionBody::passthrough(body, this.transfer.take()),
+        };
+
+        Poll::Ready(Ok(Response::from_parts(parts, body)))
+    }
+}
@@ -1,83 +1,96 @@
-use std::task::{Context, Poll};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
 
-use http::{Request, Response};
+use http::{HeaderValue, Request, Response, header::ACCEPT_ENCODING};
 use http_body::Body;
 use tower::Layer;
-use tower_http::decompression::{
-    Decompression as TowerDecompression, DecompressionBody, ResponseFuture,
-};
 use tower_service::Service;
 
-use super::AcceptEncoding;
-use crate::{client::middleware::config::RequestAcceptEncoding, core::ext::RequestConfig};
+use super::{AcceptEncoding, ContentDecoders, DecompressionBody, future::ResponseFuture};
+use crate::{
+    client::middleware::{config::RequestAcceptEncoding, context::RequestContext},
+    core::{
+        common::buf::BufPool,
+        ext::{RequestConfig, SharedHeaders},
+    },
+};
 
 /// Decompresses response bodies of the underlying service.
 ///
 /// This adds the `Accept-Encoding` header to requests and transparently decompresses response
-/// bodies based on the `Content-Encoding` header.
+/// bodies based on the `Content-Encoding` and `Transfer-Encoding` headers, including stacked
+/// codings such as `Content-Encoding: gzip, br`.
 #[derive(Clone)]
 pub struct DecompressionLayer {
     accept: AcceptEncoding,
+    advertised: Option<HeaderValue>,
+    decoders: Arc<ContentDecoders>,
+    max_ratio: Option<u32>,
+    on_demand: bool,
+    pool: Option<BufPool>,
 }
 
 impl DecompressionLayer {
-    /// Creates a new `DecompressionLayer` with the specified `Accepts`.
-    pub const fn new(accept: AcceptEncoding) -> Self {
-        Self { accept }
+    /// Creates a new `DecompressionLayer` with the specified `Accepts`, `Accept-Encoding` value
+    /// sent in place of the one listing the decoded codings, custom decoders, maximum
+    /// decompression ratio, whether bodies are only decompressed on demand, and the pool of the
+    /// buffers holding the decoded chunks.
+    pub fn new(
+        accept: AcceptEncoding,
+        advertised: Option<HeaderValue>,
+        decoders: ContentDecoders,
+        max_ratio: Option<u32>,
+        on_demand: bool,
+        pool: Option<BufPool>,
+    ) -> Self {
+        Self {
+            accept,
+            advertised,
+            decoders: Arc::new(decoders),
+            max_ratio,
+            on_demand,
+            pool,
+        }
     }
 }
 
 impl<S> Layer<S> for DecompressionLayer {
     type Service = Decompression<S>;
 
-    fn layer(&self, service: S) -> Self::Service {
-        let decoder = TowerDecompression::new(service);
-        let decoder = Decompression::<S>::accept(decoder, &self.accept);
-        Decompression { decoder }
+    fn layer(&self, inner: S) -> Self::Service {
+        Decompression {
+            inner,
+            accept: RequestConfig::new(Some(self.accept.clone())),
+            advertised: self.advertised.clone(),
+            decoders: self.decoders.clone(),
+            max_ratio: self.max_ratio,
+            on_demand: self.on_demand,
+            pool: self.pool.clone(),
+        }
     }
 }
 
 /// Decompresses response bodies of the underlying service.
 ///
 /// This adds the `Accept-Encoding` header to requests and transparently decompresses response
-/// bodies based on the `Content-Encoding` header.
+/// bodies based on the `Content-Encoding` and `Transfer-Encoding` headers, including stacked
+/// codings such as `Content-Encoding: gzip, br`.
 #[derive(Clone)]
 pub struct Decompression<S> {
-    decoder: TowerDecompression<S>,
-}
-
-impl<S> Decompression<S> {
-    fn accept(
-        mut decoder: TowerDecompression<S>,
-        accept: &AcceptEncoding,
-    ) -> TowerDecompression<S> {
-        #[cfg(feature = "gzip")]
-        {
-            decoder = decoder.gzip(accept.gzip);
-        }
-
-        #[cfg(feature = "deflate")]
-        {
-            decoder = decoder.deflate(accept.deflate);
-        }
-
-        #[cfg(feature = "brotli")]
-        {
-            decoder = decoder.br(accept.brotli);
-        }
-
-        #[cfg(feature = "zstd")]
-        {
-            decoder = decoder.zstd(accept.zstd);
-        }
-
-        decoder
-    }
+    inner: S,
+    accept: RequestConfig<RequestAcceptEncoding>,
+    advertised: Option<HeaderValue>,
+    decoders: Arc<ContentDecoders>,
+    max_ratio: Option<u32>,
+    on_demand: bool,
+    pool: Option<BufPool>,
 }
 
 impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for Decompression<S>
 where
-    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
     ReqBody: Body,
     ResBody: Body,
 {
@@ -87,16 +100,36 @@ where
 
     #[inline(always)]
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.decoder.poll_ready(cx)
+        self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
-        if let Some(accept) = RequestConfig::<RequestAcceptEncoding>::get(req.extensions()) {
-            let mut decoder = self.decoder.clone();
-            decoder = Decompression::accept(decoder, accept);
-            std::mem::swap(&mut self.decoder, &mut decoder);
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let accept = self.accept.fetch(req.extensions()).cloned();
+        let transfer = req
+            .extensions()
+            .get::<RequestContext>()
+            .map(|context| context.transfer().clone());
+
+        let value = match self.advertised {
+            Some(ref value) => Some(value.clone()),
+            None => accept
+                .as_ref()
+                .and_then(|accept| accept.header_value(self.decoders.codings())),
+        };
+        if let Some(value) = value {
+            if !SharedHeaders::contains(&req, &ACCEPT_ENCODING) {
+                req.headers_mut().insert(ACCEPT_ENCODING, value);
+            }
         }
 
-        self.decoder.call(req)
+        ResponseFuture {
+            future: self.inner.call(req),
+            accept,
+            decoders: self.decoders.clone(),
+            max_ratio: self.max_ratio,
+            on_demand: self.on_demand,
+            pool: self.pool.clone(),
+            transfer,
+        }
     }
 }
@@ -1,8 +1,33 @@
 //! Middleware for decoding
 
+mod body;
+mod future;
 mod layer;
 
-pub use layer::{Decompression, DecompressionLayer};
+use std::{fmt, sync::Arc};
+
+use http::HeaderValue;
+
+pub(crate) use self::body::BypassDecompression;
+pub use self::{
+    body::DecompressionBody,
+    future::ResponseFuture,
+    layer::{Decompression, DecompressionLayer},
+};
+use crate::decoding::{ContentDecoder, UnknownEncoding};
+
+/// A content coding supported by the decoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Coding {
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "deflate")]
+    Deflate,
+    #[cfg(feature = "brotli")]
+    Brotli,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
 
 #[derive(Clone, Debug)]
 pub(crate) struct AcceptEncoding {
@@ -40,6 +65,73 @@ impl AcceptEncoding {
     pub fn deflate(&mut self, enabled: bool) {
         self.deflate = enabled;
     }
+
+    /// Returns the enabled coding matching a `Content-Encoding` or `Transfer-Encoding` token.
+    pub(super) fn coding(&self, token: &str) -> Option<Coding> {
+        #[cfg(feature = "gzip")]
+        if self.gzip && (token.eq_ignore_ascii_case("gzip") || token.eq_ignore_ascii_case("x-gzip"))
+        {
+            return Some(Coding::Gzip);
+        }
+
+        #[cfg(feature = "deflate")]
+        if self.deflate && token.eq_ignore_ascii_case("deflate") {
+            return Some(Coding::Deflate);
+        }
+
+        #[cfg(feature = "brotli")]
+        if self.brotli && token.eq_ignore_ascii_case("br") {
+            return Some(Coding::Brotli);
+        }
+
+        #[cfg(feature = "zstd")]
+        if self.zstd && token.eq_ignore_ascii_case("zstd") {
+            return Some(Coding::Zstd);
+        }
+
+        None
+    }
+
+    /// Returns the `Accept-Encoding` header value listing the enabled codings, followed by the
+    /// `custom` ones.
+    pub(super) fn header_value<'a>(
+        &self,
+        custom: impl Iterator<Item = &'a str>,
+    ) -> Option<HeaderValue> {
+        let mut codings = Vec::with_capacity(4);
+
+        #[cfg(feature = "gzip")]
+        if self.gzip {
+            codings.push("gzip");
+        }
+
+        #[cfg(feature = "deflate")]
+        if self.deflate {
+            codings.push("deflate");
+        }
+
+        #[cfg(feature = "brotli")]
+        if self.brotli {
+            codings.push("br");
+        }
+
+        #[cfg(feature = "zstd")]
+        if self.zstd {
+            codings.push("zstd");
+        }
+
+        for coding in custom {
+            if !codings.contains(&coding) {
+                codings.push(coding);
+            }
+        }
+
+        if codings.is_empty() {
+            return None;
+        }
+
+        HeaderValue::from_str(&codings.join(", ")).ok()
+    }
 }
 
 impl Default for AcceptEncoding {
@@ -56,3 +148,54 @@ impl Default for AcceptEncoding {
         }
     }
 }
+
+/// Creates the decoder of a registered coding for each body.
+pub(crate) type DecoderFactory = Arc<dyn Fn() -> Box<dyn ContentDecoder> + Send + Sync>;
+
+/// The custom decoders of a client, and its handling of the codings it doesn't decode.
+#[derive(Clone, Default)]
+pub(crate) struct ContentDecoders {
+    decoders: Vec<(String, DecoderFactory)>,
+    unknown: UnknownEncoding,
+}
+
+impl ContentDecoders {
+    /// Registers the decoder of `coding`, replacing any decoder registered for it before.
+    pub(crate) fn insert(&mut self, coding: &str, factory: DecoderFactory) {
+        let coding = coding.to_ascii_lowercase();
+        match self.decoders.iter_mut().find(|(name, _)| *name == coding) {
+            Some((_, registered)) => *registered = factory,
+            None => self.decoders.push((coding, factory)),
+        }
+    }
+
+    pub(crate) fn set_unknown(&mut self, unknown: UnknownEncoding) {
+        self.unknown = unknown;
+    }
+
+    /// Returns the names of the registered codings, in registration order.
+    pub(crate) fn codings(&self) -> impl Iterator<Item = &str> {
+        self.decoders.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Creates the registered decoder of `coding`, if any.
+    pub(crate) fn decoder(&self, coding: &str) -> Option<Box<dyn ContentDecoder>> {
+        self.decoders
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(coding))
+            .map(|(_, factory)| factory())
+    }
+
+    pub(crate) fn unknown(&self) -> &UnknownEncoding {
+        &self.unknown
+    }
+}
+
+impl fmt::Debug for ContentDecoders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentDecoders")
+            .field("codings", &self.codings().collect::<Vec<_>>())
+            .field("unknown", &self.unknown)
+            .finish()
+    }
+}
@@ -0,0 +1,108 @@
+use std::{
+    mem,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::Bytes;
+use http::{StatusCode, response::Parts};
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+
+use super::layer::{CONTENT_DIGEST, REPR_DIGEST};
+use crate::{Error, digest::Expectation, error::BoxError};
+
+pin_project! {
+    /// Response body of [`ContentDigest`](super::ContentDigest).
+    ///
+    /// The body is hashed as it is read, and fails at its end if it doesn't match the digests
+    /// announced in the response headers.
+    pub struct DigestBody<B> {
+        #[pin]
+        inner: B,
+        expectations: Vec<Expectation>,
+    }
+}
+
+impl<B> DigestBody<B> {
+    /// Creates a body verified against the digest headers of the response.
+    pub(super) fn new(inner: B, parts: &Parts) -> Self {
+        let mut expectations = Vec::new();
+
+        // These responses carry no content for the headers to describe.
+        if parts.status != StatusCode::NO_CONTENT && parts.status != StatusCode::NOT_MODIFIED {
+            for value in parts.headers.get_all(CONTENT_DIGEST) {
+                expectations.extend(Expectation::parse(&CONTENT_DIGEST, value));
+            }
+
+            // The representation is only the content of a response if it isn't partial.
+            if parts.status != StatusCode::PARTIAL_CONTENT {
+                for value in parts.headers.get_all(REPR_DIGEST) {
+                    expectations.extend(Expectation::parse(&REPR_DIGEST, value));
+                }
+            }
+        }
+
+        DigestBody {
+            inner,
+            expectations,
+        }
+    }
+
+    /// Creates a body that is not verified.
+    pub(super) fn passthrough(inner: B) -> Self {
+        DigestBody {
+            inner,
+            expectations: Vec::new(),
+        }
+    }
+}
+
+impl<B> Body for DigestBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    for expectation in this.expectations.iter_mut() {
+                        expectation.update(data);
+                    }
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(err)) => {
+                this.expectations.clear();
+                Poll::Ready(Some(Err(err.into())))
+            }
+            None => {
+                for expectation in mem::take(this.expectations) {
+                    if let Err(mismatch) = expectation.verify() {
+                        return Poll::Ready(Some(Err(Error::decode(mismatch).into())));
+                    }
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline(always)]
+    fn is_end_stream(&self) -> bool {
+        // The end of the body must be polled for the digests to be verified.
+        self.expectations.is_empty() && self.inner.is_end_stream()
+    }
+}
@@ -0,0 +1,42 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use http::Response;
+use pin_project_lite::pin_project;
+
+use super::DigestBody;
+
+pin_project! {
+    /// Response future for [`ContentDigest`](super::ContentDigest).
+    pub struct ResponseFuture<F> {
+        #[pin]
+        pub(super) future: F,
+        pub(super) verify: bool,
+    }
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = Result<Response<DigestBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.future.poll(cx)?);
+
+        let (parts, body) = res.into_parts();
+        let body = if *this.verify {
+            DigestBody::new(body, &parts)
+        } else {
+            DigestBody::passthrough(body)
+        };
+
+        Poll::Ready(Ok(Response::from_parts(parts, body)))
+    }
+}
@@ -0,0 +1,81 @@
+use std::task::{Context, Poll};
+
+use http::{Method, Request, Response, header::HeaderName};
+use tower::Layer;
+use tower_service::Service;
+
+use super::future::ResponseFuture;
+use crate::{Body, core::ext::SharedHeaders, digest::DigestAlgorithm};
+
+pub(super) const CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+pub(super) const REPR_DIGEST: HeaderName = HeaderName::from_static("repr-digest");
+
+/// Layer to apply [`ContentDigest`] middleware.
+#[derive(Clone)]
+pub struct ContentDigestLayer {
+    verify: bool,
+    algorithm: Option<DigestAlgorithm>,
+}
+
+impl ContentDigestLayer {
+    /// Create a new digest layer.
+    ///
+    /// Responses are verified against their digest headers if `verify` is set, and requests
+    /// with a buffered body get a `Content-Digest` header computed with `algorithm`.
+    pub const fn new(verify: bool, algorithm: Option<DigestAlgorithm>) -> Self {
+        Self { verify, algorithm }
+    }
+}
+
+impl<S> Layer<S> for ContentDigestLayer {
+    type Service = ContentDigest<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ContentDigest {
+            inner,
+            verify: self.verify,
+            algorithm: self.algorithm,
+        }
+    }
+}
+
+/// Middleware adding `Content-Digest` headers to requests and verifying the `Content-Digest`
+/// and `Repr-Digest` headers of responses.
+#[derive(Clone)]
+pub struct ContentDigest<S> {
+    inner: S,
+    verify: bool,
+    algorithm: Option<DigestAlgorithm>,
+}
+
+impl<ResBody, S> Service<Request<Body>> for ContentDigest<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+{
+    type Response = Response<super::DigestBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if let Some(algorithm) = self.algorithm {
+            if !SharedHeaders::contains(&req, &CONTENT_DIGEST) {
+                if let Some(bytes) = req.body().as_bytes() {
+                    let value = crate::digest::digest(algorithm, bytes).to_header_value();
+                    req.headers_mut().insert(CONTENT_DIGEST, value);
+                }
+            }
+        }
+
+        // Responses to HEAD requests describe a body they don't carry.
+        let verify = self.verify && req.method() != Method::HEAD;
+        ResponseFuture {
+            future: self.inner.call(req),
+            verify,
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Middleware for RFC 9530 digest headers.
+
+mod body;
+mod future;
+mod layer;
+
+pub use self::{
+    body::DigestBody,
+    layer::{ContentDigest, ContentDigestLayer},
+};
@@ -0,0 +1,73 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use http::{HeaderName, Response};
+use pin_project_lite::pin_project;
+
+use super::layer::AcceptedHints;
+
+const ACCEPT_CH: HeaderName = HeaderName::from_static("accept-ch");
+
+pin_project! {
+    /// Response future for [`ClientHintsManager`].
+    #[project=ResponseFutureProj]
+    pub enum ResponseFuture<F> {
+        WithClientHints {
+            #[pin]
+            future: F,
+            accepted: AcceptedHints,
+            origin: String,
+        },
+        WithoutClientHints {
+            #[pin]
+            future: F,
+        },
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::WithClientHints {
+                future,
+                accepted,
+                origin,
+            } => {
+                let res = ready!(future.poll(cx)?);
+
+                // A new `Accept-CH` header replaces the hints previously requested by the origin.
+                let mut values = res.headers().get_all(ACCEPT_CH).iter().peekable();
+                if values.peek().is_some() {
+                    let names = values
+                        .filter_map(|value| value.to_str().ok())
+                        .flat_map(|value| value.split(','))
+                        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+                        .collect::<Vec<_>>();
+
+                    let mut accepted = accepted.write();
+                    if names.is_empty() {
+                        accepted.remove(origin.as_str());
+                    } else {
+                        accepted.insert(std::mem::take(origin), names);
+                    }
+                }
+
+                Poll::Ready(Ok(res))
+            }
+            ResponseFutureProj::WithoutClientHints { mut future } => {
+                let res = ready!(future.as_mut().poll(cx)?);
+                Poll::Ready(Ok(res))
+            }
+        }
+    }
+}
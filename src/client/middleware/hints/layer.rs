@@ -0,0 +1,168 @@
+use std::{
+    fmt,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::{HeaderName, Request, Response, Uri};
+use schnellru::ByLength;
+use tower::Layer;
+use tower_service::Service;
+
+use super::future::ResponseFuture;
+use crate::{
+    ClientHints,
+    client::middleware::config::{RequestAcceptedHints, RequestClientHints},
+    core::{
+        ext::{RequestConfig, SharedHeaders},
+        map::{LruMap, RANDOM_STATE},
+    },
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// The number of origins whose `Accept-CH` hints are remembered.
+const MAX_ACCEPTED_ORIGINS: u32 = 256;
+
+/// The hints requested by each origin through `Accept-CH`.
+///
+/// Only the [`MAX_ACCEPTED_ORIGINS`] origins that sent the header last are remembered, so that
+/// a client crawling many origins doesn't keep the hints of all of them.
+#[derive(Clone)]
+pub(crate) struct AcceptedHints(Arc<RwLock<LruMap<String, Vec<HeaderName>>>>);
+
+impl AcceptedHints {
+    /// Locks the hints for reading.
+    #[inline]
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, LruMap<String, Vec<HeaderName>>> {
+        self.0.read()
+    }
+
+    /// Locks the hints for writing.
+    #[inline]
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, LruMap<String, Vec<HeaderName>>> {
+        self.0.write()
+    }
+}
+
+impl Default for AcceptedHints {
+    fn default() -> Self {
+        AcceptedHints(Arc::new(RwLock::new(LruMap::with_hasher(
+            ByLength::new(MAX_ACCEPTED_ORIGINS),
+            RANDOM_STATE,
+        ))))
+    }
+}
+
+impl fmt::Debug for AcceptedHints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AcceptedHints")
+            .field("origins", &self.read().len())
+            .finish()
+    }
+}
+
+/// Layer to apply [`ClientHintsManager`] middleware.
+#[derive(Clone)]
+pub struct ClientHintsLayer {
+    hints: RequestConfig<RequestClientHints>,
+    accepted: RequestConfig<RequestAcceptedHints>,
+}
+
+impl ClientHintsLayer {
+    /// Create a new client hints layer.
+    pub fn new(hints: Option<ClientHints>) -> Self {
+        Self {
+            hints: RequestConfig::new(hints),
+            accepted: RequestConfig::new(Some(AcceptedHints::default())),
+        }
+    }
+}
+
+impl<S> Layer<S> for ClientHintsLayer {
+    type Service = ClientHintsManager<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientHintsManager {
+            inner,
+            hints: self.hints.clone(),
+            accepted: self.accepted.clone(),
+        }
+    }
+}
+
+/// Middleware to send [`ClientHints`] and answer `Accept-CH` requests.
+#[derive(Clone)]
+pub struct ClientHintsManager<S> {
+    inner: S,
+    hints: RequestConfig<RequestClientHints>,
+    accepted: RequestConfig<RequestAcceptedHints>,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for ClientHintsManager<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // Client hints are only delivered to secure origins.
+        let origin = match self.hints.fetch(req.extensions()) {
+            Some(hints) => secure_origin(req.uri()).map(|origin| (origin, hints.clone())),
+            None => None,
+        };
+
+        // The request may carry its own `Accept-CH` cache, e.g. when sent from a session.
+        let accepted = self
+            .accepted
+            .fetch(req.extensions())
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some((ref origin, ref hints)) = origin {
+            let accepted = accepted.read();
+            let requested = accepted.peek(origin).map(Vec::as_slice).unwrap_or_default();
+
+            for (name, value) in hints.headers() {
+                if let Some(name) = name {
+                    if !SharedHeaders::contains(&req, &name) {
+                        req.headers_mut().insert(name, value);
+                    }
+                }
+            }
+            for name in requested {
+                if !SharedHeaders::contains(&req, name) {
+                    if let Some(value) = hints.hint(name) {
+                        req.headers_mut().insert(name.clone(), value);
+                    }
+                }
+            }
+        }
+
+        match origin {
+            Some((origin, _)) => ResponseFuture::WithClientHints {
+                future: self.inner.call(req),
+                accepted,
+                origin,
+            },
+            None => ResponseFuture::WithoutClientHints {
+                future: self.inner.call(req),
+            },
+        }
+    }
+}
+
+fn secure_origin(uri: &Uri) -> Option<String> {
+    let scheme = uri.scheme_str()?;
+    if !scheme.eq_ignore_ascii_case("https") && !scheme.eq_ignore_ascii_case("wss") {
+        return None;
+    }
+    let authority = uri.authority()?;
+    Some(format!("{scheme}://{authority}").to_ascii_lowercase())
+}
@@ -0,0 +1,7 @@
+//! Middleware to send User-Agent client hints.
+
+mod future;
+mod layer;
+
+pub(crate) use self::layer::AcceptedHints;
+pub use self::layer::{ClientHintsLayer, ClientHintsManager};
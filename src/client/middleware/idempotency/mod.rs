@@ -0,0 +1,73 @@
+//! Middleware to attach an `Idempotency-Key` to unsafe requests.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::Request;
+use tower::Layer;
+use tower_service::Service;
+
+use crate::{
+    core::ext::SharedHeaders,
+    idempotency::{IDEMPOTENCY_KEY, KeyGenerator},
+};
+
+/// Layer to apply [`IdempotencyKeyManager`] middleware.
+#[derive(Clone)]
+pub struct IdempotencyKeyLayer {
+    generator: Option<Arc<dyn KeyGenerator>>,
+}
+
+impl IdempotencyKeyLayer {
+    /// Create a new idempotency key layer.
+    ///
+    /// The layer must wrap the retry layer, so that replays carry the key of the request.
+    pub fn new(generator: Option<Arc<dyn KeyGenerator>>) -> Self {
+        Self { generator }
+    }
+}
+
+impl<S> Layer<S> for IdempotencyKeyLayer {
+    type Service = IdempotencyKeyManager<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyKeyManager {
+            inner,
+            generator: self.generator.clone(),
+        }
+    }
+}
+
+/// Middleware adding a generated `Idempotency-Key` header to requests with an unsafe method.
+#[derive(Clone)]
+pub struct IdempotencyKeyManager<S> {
+    inner: S,
+    generator: Option<Arc<dyn KeyGenerator>>,
+}
+
+impl<ReqBody, S> Service<Request<ReqBody>> for IdempotencyKeyManager<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        if let Some(ref generator) = self.generator {
+            if !req.method().is_safe() && !SharedHeaders::contains(&req, &IDEMPOTENCY_KEY) {
+                req.headers_mut()
+                    .insert(IDEMPOTENCY_KEY, generator.generate());
+            }
+        }
+
+        self.inner.call(req)
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use http::Uri;
+
+#[cfg(feature = "psl")]
+use crate::cookie::PublicSuffixList;
+use crate::sync::Mutex;
+
+/// A scheduler spacing out the requests sent to the same site.
+pub(crate) struct Scheduler {
+    delay: Duration,
+    #[cfg(feature = "psl")]
+    sites: Option<PublicSuffixList>,
+    /// The earliest instant the next request may be sent, by site.
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl Scheduler {
+    pub(crate) fn new(
+        delay: Duration,
+        #[cfg(feature = "psl")] sites: Option<PublicSuffixList>,
+    ) -> Scheduler {
+        Scheduler {
+            delay,
+            #[cfg(feature = "psl")]
+            sites,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserves a slot to send a request to `uri`, at least `delay`, or the delay of the
+    /// scheduler if longer, after the previous one to the same site.
+    ///
+    /// Returns the instant to wait for, if the request cannot be sent right away.
+    pub(crate) fn reserve(&self, uri: &Uri, delay: Option<Duration>) -> Option<Instant> {
+        let delay = delay.map_or(self.delay, |delay| delay.max(self.delay));
+        if delay.is_zero() {
+            return None;
+        }
+        let site = self.site(uri)?;
+
+        let now = Instant::now();
+        let mut next = self.next.lock();
+        next.retain(|_, at| *at > now);
+
+        let slot = next.get(&site).copied().unwrap_or(now).max(now);
+        next.insert(site, slot + delay);
+        (slot > now).then_some(slot)
+    }
+
+    /// Returns the registrable domain of `uri` if known, or its host.
+    fn site(&self, uri: &Uri) -> Option<String> {
+        let host = uri.host()?;
+
+        #[cfg(feature = "psl")]
+        if let Some(domain) = self
+            .sites
+            .as_ref()
+            .and_then(|sites| sites.registrable_domain(host))
+        {
+            return Some(domain);
+        }
+
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::Uri;
+
+    use super::Scheduler;
+
+    #[test]
+    fn scheduler_spaces_requests_per_site() {
+        let scheduler = Scheduler::new(
+            Duration::from_secs(10),
+            #[cfg(feature = "psl")]
+            None,
+        );
+        let a: Uri = "http://a.example/".parse().unwrap();
+        let b: Uri = "http://b.example/".parse().unwrap();
+
+        assert!(scheduler.reserve(&a, None).is_none());
+        let first = scheduler.reserve(&a, None).expect("a is delayed");
+        let second = scheduler.reserve(&a, None).expect("a is delayed again");
+        assert!(second - first >= Duration::from_secs(10));
+        assert!(scheduler.reserve(&b, None).is_none());
+
+        let longer = scheduler
+            .reserve(&b, Some(Duration::from_secs(60)))
+            .unwrap();
+        let after = scheduler.reserve(&b, None).unwrap();
+        assert!(after - longer >= Duration::from_secs(60));
+    }
+}
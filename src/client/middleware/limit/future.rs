@@ -0,0 +1,92 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use http::Request;
+use pin_project_lite::pin_project;
+use tokio::time::Sleep;
+use tower_service::Service;
+
+use super::queue::{Acquire, Limiter, Permit};
+use crate::{Priority, client::middleware::config::RequestPriority, core::ext::RequestConfig};
+
+pin_project! {
+    /// Response future for [`InFlightLimit`](super::InFlightLimit).
+    #[project = ResponseFutureProj]
+    pub enum ResponseFuture<S, Req>
+    where
+        S: Service<Req>,
+    {
+        Waiting {
+            delay: Option<Pin<Box<Sleep>>>,
+            limiter: Option<Arc<Limiter>>,
+            acquire: Option<Acquire>,
+            permit: Option<Permit>,
+            service: S,
+            request: Option<Req>,
+        },
+        Sending {
+            #[pin]
+            future: S::Future,
+            permit: Option<Permit>,
+        },
+        Unlimited {
+            #[pin]
+            future: S::Future,
+        },
+    }
+}
+
+impl<S, ReqBody> Future for ResponseFuture<S, Request<ReqBody>>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Output = Result<S::Response, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project() {
+                ResponseFutureProj::Waiting {
+                    delay,
+                    limiter,
+                    acquire,
+                    permit,
+                    service,
+                    request,
+                } => {
+                    if let Some(sleep) = delay {
+                        ready!(sleep.as_mut().poll(cx));
+                        *delay = None;
+                    }
+
+                    if let Some(limiter) = limiter.take() {
+                        let req = request.as_ref().expect("polled after completion");
+                        let urgency = RequestConfig::<RequestPriority>::get(req.extensions())
+                            .map_or(Priority::DEFAULT.urgency(), Priority::urgency);
+                        *acquire = Some(limiter.acquire(req.uri(), urgency));
+                    }
+                    if let Some(pending) = acquire {
+                        *permit = Some(ready!(Pin::new(pending).poll(cx)));
+                        *acquire = None;
+                    }
+                    ready!(service.poll_ready(cx))?;
+
+                    let future = service.call(request.take().expect("polled after completion"));
+                    let permit = permit.take();
+                    self.set(ResponseFuture::Sending { future, permit });
+                }
+                ResponseFutureProj::Sending { future, permit } => {
+                    let res = ready!(future.poll(cx));
+                    permit.take();
+                    return Poll::Ready(res);
+                }
+                ResponseFutureProj::Unlimited { future } => return future.poll(cx),
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::Request;
+use tower::Layer;
+use tower_service::Service;
+
+use super::{delay::Scheduler, future::ResponseFuture, queue::Limiter};
+use crate::{client::middleware::config::RequestCrawlDelay, core::ext::RequestConfig};
+
+/// Layer to apply [`InFlightLimit`] middleware.
+#[derive(Clone)]
+pub struct InFlightLimitLayer {
+    limiter: Option<Arc<Limiter>>,
+    scheduler: Arc<Scheduler>,
+}
+
+impl InFlightLimitLayer {
+    /// Create a new layer allowing at most `max` requests in flight, if any.
+    ///
+    /// Waiting requests get their permits by [`Priority`](crate::Priority), then in the order
+    /// they were sent, or in round-robin across hosts when `fair` is set. Before waiting for a
+    /// permit, requests to the same site are spaced out by the scheduler.
+    pub(crate) fn new(max: Option<usize>, fair: bool, scheduler: Scheduler) -> Self {
+        Self {
+            limiter: max.map(|max| Arc::new(Limiter::new(max, fair))),
+            scheduler: Arc::new(scheduler),
+        }
+    }
+}
+
+impl<S> Layer<S> for InFlightLimitLayer {
+    type Service = InFlightLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InFlightLimit {
+            inner,
+            limiter: self.limiter.clone(),
+            scheduler: self.scheduler.clone(),
+        }
+    }
+}
+
+/// Middleware holding requests back until fewer than a maximum number of requests are awaiting
+/// their response.
+///
+/// A request holds its permit until its response headers are received or it fails. Requests to
+/// the same site wait for their crawl delay before waiting for a permit.
+#[derive(Clone)]
+pub struct InFlightLimit<S> {
+    inner: S,
+    limiter: Option<Arc<Limiter>>,
+    scheduler: Arc<Scheduler>,
+}
+
+impl<ReqBody, S> Service<Request<ReqBody>> for InFlightLimit<S>
+where
+    S: Service<Request<ReqBody>> + Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S, Request<ReqBody>>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let delay = RequestConfig::<RequestCrawlDelay>::get(req.extensions()).copied();
+        let slot = self.scheduler.reserve(req.uri(), delay);
+
+        if slot.is_none() && self.limiter.is_none() {
+            return ResponseFuture::Unlimited {
+                future: self.inner.call(req),
+            };
+        }
+
+        // The request is only handed to the inner service once it is due and has a permit, so
+        // that nothing is prepared for the wire while it waits. The permit is only requested
+        // once the request is due, so that it isn't held while waiting for the delay.
+        let clone = self.inner.clone();
+        ResponseFuture::Waiting {
+            delay: slot.map(|slot| Box::pin(tokio::time::sleep_until(slot.into()))),
+            limiter: self.limiter.clone(),
+            acquire: None,
+            permit: None,
+            service: std::mem::replace(&mut self.inner, clone),
+            request: Some(req),
+        }
+    }
+}
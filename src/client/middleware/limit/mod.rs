@@ -0,0 +1,9 @@
+//! Middleware limiting the number of requests in flight and their rate per site.
+
+mod delay;
+mod future;
+mod layer;
+mod queue;
+
+pub(crate) use self::delay::Scheduler;
+pub use self::layer::{InFlightLimit, InFlightLimitLayer};
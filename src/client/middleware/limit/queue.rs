@@ -0,0 +1,247 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, ready},
+};
+
+use http::{Uri, uri::Authority};
+use tokio::sync::oneshot;
+
+use crate::sync::Mutex;
+
+/// A semaphore handing its permits to waiting requests by urgency, then in round-robin across
+/// hosts when fair.
+pub(crate) struct Limiter {
+    fair: bool,
+    state: Mutex<State>,
+}
+
+struct State {
+    available: usize,
+    /// The waiting requests, by urgency.
+    levels: [Level; 8],
+}
+
+#[derive(Default)]
+struct Level {
+    /// The requests waiting for a permit, by host, or all under `None` when not fair.
+    queues: HashMap<Option<Authority>, VecDeque<oneshot::Sender<()>>>,
+    /// The hosts with waiting requests, in the order they are served.
+    order: VecDeque<Option<Authority>>,
+}
+
+impl Level {
+    /// Hands a permit to the next waiting request, returning whether there was one.
+    fn hand_over(&mut self) -> bool {
+        while let Some(key) = self.order.pop_front() {
+            let Some(queue) = self.queues.get_mut(&key) else {
+                continue;
+            };
+
+            // Skip the requests that stopped waiting.
+            let mut handed = false;
+            while let Some(tx) = queue.pop_front() {
+                if tx.send(()).is_ok() {
+                    handed = true;
+                    break;
+                }
+            }
+
+            if queue.is_empty() {
+                self.queues.remove(&key);
+            } else {
+                self.order.push_back(key);
+            }
+
+            if handed {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Limiter {
+    pub(crate) fn new(max: usize, fair: bool) -> Limiter {
+        Limiter {
+            fair,
+            state: Mutex::new(State {
+                available: max,
+                levels: Default::default(),
+            }),
+        }
+    }
+
+    /// Waits for a permit to send a request to `uri`, with the given urgency from 0 to 7.
+    pub(crate) fn acquire(self: &Arc<Self>, uri: &Uri, urgency: u8) -> Acquire {
+        let mut state = self.state.lock();
+        if state.available > 0 {
+            state.available -= 1;
+            return Acquire {
+                limiter: Some(self.clone()),
+                rx: None,
+            };
+        }
+
+        let key = if self.fair {
+            uri.authority().cloned()
+        } else {
+            None
+        };
+
+        let (tx, rx) = oneshot::channel();
+        let level = &mut state.levels[usize::from(urgency.min(7))];
+        let queue = level.queues.entry(key.clone()).or_default();
+        if queue.is_empty() {
+            level.order.push_back(key);
+        }
+        queue.push_back(tx);
+
+        Acquire {
+            limiter: Some(self.clone()),
+            rx: Some(rx),
+        }
+    }
+
+    /// Hands a returned permit to the next waiting request, if any.
+    fn release(&self) {
+        let mut state = self.state.lock();
+        if !state.levels.iter_mut().any(Level::hand_over) {
+            state.available += 1;
+        }
+    }
+}
+
+/// A future resolving to a [`Permit`] once one is available.
+pub(crate) struct Acquire {
+    limiter: Option<Arc<Limiter>>,
+    rx: Option<oneshot::Receiver<()>>,
+}
+
+impl Future for Acquire {
+    type Output = Permit;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(ref mut rx) = self.rx {
+            // The sender is only dropped after handing over a permit or once the receiver is
+            // gone, so an error cannot happen here.
+            let _ = ready!(Pin::new(rx).poll(cx));
+            self.rx = None;
+        }
+
+        let limiter = self.limiter.take().expect("polled after completion");
+        Poll::Ready(Permit(limiter))
+    }
+}
+
+impl Drop for Acquire {
+    fn drop(&mut self) {
+        let Some(limiter) = self.limiter.take() else {
+            return;
+        };
+
+        match self.rx.take() {
+            // The permit was taken right away.
+            None => limiter.release(),
+            // A permit may have been handed over after the request stopped waiting.
+            Some(mut rx) => {
+                rx.close();
+                if rx.try_recv().is_ok() {
+                    limiter.release();
+                }
+            }
+        }
+    }
+}
+
+/// A permit to send a request, returned when dropped.
+pub(crate) struct Permit(Arc<Limiter>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::{Pin, pin},
+        sync::Arc,
+        task::{Context, Poll, Waker},
+    };
+
+    use http::Uri;
+
+    use super::{Acquire, Limiter, Permit};
+
+    fn poll(acquire: Pin<&mut Acquire>) -> Option<Permit> {
+        match acquire.poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(permit) => Some(permit),
+            Poll::Pending => None,
+        }
+    }
+
+    #[test]
+    fn fair_limiter_round_robins_hosts() {
+        let limiter = Arc::new(Limiter::new(1, true));
+        let a: Uri = "http://a.example/".parse().unwrap();
+        let b: Uri = "http://b.example/".parse().unwrap();
+
+        let permit = poll(pin!(limiter.acquire(&a, 3))).expect("a permit is available");
+        let mut a1 = pin!(limiter.acquire(&a, 3));
+        let mut a2 = pin!(limiter.acquire(&a, 3));
+        let mut b1 = pin!(limiter.acquire(&b, 3));
+        assert!(poll(a1.as_mut()).is_none());
+        assert!(poll(a2.as_mut()).is_none());
+        assert!(poll(b1.as_mut()).is_none());
+
+        // Host `a` queued first and is served first, then `b` gets its turn before `a` again.
+        drop(permit);
+        let permit = poll(a1.as_mut()).expect("a1 is served");
+        assert!(poll(b1.as_mut()).is_none());
+
+        drop(permit);
+        let permit = poll(b1.as_mut()).expect("b1 is served");
+        assert!(poll(a2.as_mut()).is_none());
+
+        drop(permit);
+        assert!(poll(a2.as_mut()).is_some());
+    }
+
+    #[test]
+    fn dropped_waiter_returns_permit() {
+        let limiter = Arc::new(Limiter::new(1, false));
+        let uri: Uri = "http://a.example/".parse().unwrap();
+
+        let permit = poll(pin!(limiter.acquire(&uri, 3))).expect("a permit is available");
+        let waiter = limiter.acquire(&uri, 3);
+
+        // The permit is handed to the waiter, which is dropped before taking it.
+        drop(permit);
+        drop(waiter);
+
+        assert!(poll(pin!(limiter.acquire(&uri, 3))).is_some());
+    }
+
+    #[test]
+    fn urgent_requests_are_served_first() {
+        let limiter = Arc::new(Limiter::new(1, true));
+        let uri: Uri = "http://a.example/".parse().unwrap();
+
+        let permit = poll(pin!(limiter.acquire(&uri, 3))).expect("a permit is available");
+        let mut bulk = pin!(limiter.acquire(&uri, 7));
+        let mut interactive = pin!(limiter.acquire(&uri, 0));
+
+        drop(permit);
+        assert!(poll(bulk.as_mut()).is_none());
+        let permit = poll(interactive.as_mut()).expect("the urgent request is served first");
+
+        drop(permit);
+        assert!(poll(bulk.as_mut()).is_some());
+    }
+}
@@ -0,0 +1,76 @@
+//! [`Future`] types.
+
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+    time::Instant,
+};
+
+use http::Response;
+use http_body::Body;
+use pin_project_lite::pin_project;
+
+use super::layer::content_length;
+use crate::logging::{LogRecord, Logging};
+
+pin_project! {
+    /// Response future for [`RequestLogging`](super::RequestLogging).
+    #[project=ResponseFutureProj]
+    pub enum ResponseFuture<F> {
+        WithLogging {
+            #[pin]
+            future: F,
+            logging: Logging,
+            record: Option<LogRecord>,
+            start: Instant,
+        },
+        WithoutLogging {
+            #[pin]
+            future: F,
+        },
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+    ResBody: Body,
+    E: Display,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::WithLogging {
+                future,
+                logging,
+                record,
+                start,
+            } => {
+                let res = ready!(future.poll(cx));
+
+                if let Some(mut record) = record.take() {
+                    record.elapsed = start.elapsed();
+                    match res {
+                        Ok(ref res) => {
+                            record.status = Some(res.status().as_u16());
+                            record.response_size = res
+                                .body()
+                                .size_hint()
+                                .exact()
+                                .or_else(|| content_length(res.headers()));
+                            record.response_headers = logging.select(res.headers(), &[]);
+                        }
+                        Err(ref err) => record.error = Some(err.to_string()),
+                    }
+                    logging.logger.log(&record);
+                }
+
+                Poll::Ready(res)
+            }
+            ResponseFutureProj::WithoutLogging { future } => future.poll(cx),
+        }
+    }
+}
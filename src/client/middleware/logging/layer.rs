@@ -0,0 +1,119 @@
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use arc_swap::ArcSwap;
+use http::{HeaderMap, Request, Response, header::CONTENT_LENGTH};
+use http_body::Body;
+use tower::Layer;
+use tower_service::Service;
+
+use super::future::ResponseFuture;
+use crate::{
+    client::middleware::config::RequestSkipDefaultHeaders,
+    core::ext::{RequestConfig, SharedHeaders},
+    logging::{LogRecord, Logging, redact_uri},
+};
+
+/// Layer to apply [`RequestLogging`] middleware.
+#[derive(Clone)]
+pub struct LoggingLayer {
+    logging: Option<Logging>,
+    default_headers: Arc<ArcSwap<HeaderMap>>,
+}
+
+impl LoggingLayer {
+    /// Create a new logging layer.
+    ///
+    /// The default headers are those the client adds to requests after this layer, so that
+    /// they are recorded too.
+    pub fn new(logging: Option<Logging>, default_headers: Arc<ArcSwap<HeaderMap>>) -> Self {
+        Self {
+            logging,
+            default_headers,
+        }
+    }
+}
+
+impl<S> Layer<S> for LoggingLayer {
+    type Service = RequestLogging<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestLogging {
+            inner,
+            logging: self.logging.clone(),
+            default_headers: self.default_headers.clone(),
+        }
+    }
+}
+
+/// Middleware handing a [`LogRecord`] of each request to a logger.
+#[derive(Clone)]
+pub struct RequestLogging<S> {
+    inner: S,
+    logging: Option<Logging>,
+    default_headers: Arc<ArcSwap<HeaderMap>>,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for RequestLogging<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    ReqBody: Body,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let Some(ref logging) = self.logging else {
+            return ResponseFuture::WithoutLogging {
+                future: self.inner.call(req),
+            };
+        };
+
+        let skip_defaults = RequestConfig::<RequestSkipDefaultHeaders>::get(req.extensions())
+            .copied()
+            == Some(true);
+        let defaults = (!skip_defaults).then(|| self.default_headers.load_full());
+        let fallbacks = SharedHeaders::get(req.extensions())
+            .into_iter()
+            .flat_map(SharedHeaders::maps)
+            .chain(defaults.as_deref())
+            .collect::<Vec<_>>();
+
+        let record = LogRecord {
+            method: req.method().to_string(),
+            url: redact_uri(req.uri()),
+            status: None,
+            elapsed: Duration::ZERO,
+            request_size: req
+                .body()
+                .size_hint()
+                .exact()
+                .or_else(|| content_length(req.headers())),
+            response_size: None,
+            request_headers: logging.select(req.headers(), &fallbacks),
+            response_headers: Vec::new(),
+            error: None,
+        };
+
+        ResponseFuture::WithLogging {
+            future: self.inner.call(req),
+            logging: logging.clone(),
+            record: Some(record),
+            start: Instant::now(),
+        }
+    }
+}
+
+/// Returns the value of the `Content-Length` header, if valid.
+pub(super) fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers.get(CONTENT_LENGTH)?.to_str().ok()?.parse().ok()
+}
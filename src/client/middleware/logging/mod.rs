@@ -0,0 +1,6 @@
+//! Middleware to log requests.
+
+mod future;
+mod layer;
+
+pub use self::layer::{LoggingLayer, RequestLogging};
@@ -1,6 +1,7 @@
 //! Middleware for the client.
 
 pub mod config;
+pub mod context;
 #[cfg(feature = "cookies")]
 pub mod cookie;
 #[cfg(any(
@@ -10,6 +11,17 @@ pub mod cookie;
     feature = "deflate",
 ))]
 pub mod decoder;
+pub mod digest;
+pub mod hints;
+pub mod idempotency;
+pub mod limit;
+pub mod logging;
 pub mod redirect;
+pub mod refresh;
 pub mod retry;
+pub mod robots;
+#[cfg(feature = "cookies")]
+pub mod session;
 pub mod timeout;
+#[cfg(feature = "trace-context")]
+pub mod trace_context;
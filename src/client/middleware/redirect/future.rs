@@ -1,23 +1,25 @@
 use std::{
     future::Future,
-    pin::Pin,
+    pin::{Pin, pin},
     str,
     task::{Context, Poll, ready},
 };
 
+use bytes::Buf;
 use futures_util::future::Either;
 use http::{
     Extensions, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri, Version,
     header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, LOCATION, TRANSFER_ENCODING},
 };
 use http_body::Body;
+use http_body_util::BodyExt;
 use pin_project_lite::pin_project;
 use tower::util::Oneshot;
 use tower_service::Service;
 use url::Url;
 
 use super::{
-    BodyRepr, RequestUri,
+    BodyRepr, Refresh, RequestUri,
     policy::{Action, Attempt, Policy},
 };
 
@@ -39,6 +41,9 @@ pin_project! {
             headers: HeaderMap<HeaderValue>,
             extensions: Extensions,
             body: BodyRepr<B>,
+            // The body of the previous redirection response being drained, and the redirected
+            // request to send once it is.
+            drain: Option<(Pin<Box<dyn Future<Output = ()> + Send>>, Request<B>)>,
         },
 
         NoRedirect {
@@ -52,6 +57,9 @@ impl<S, ReqBody, ResBody, P> Future for ResponseFuture<S, ReqBody, P>
 where
     S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
     ReqBody: Body + Default,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Send,
     P: Policy<ReqBody, S::Error>,
 {
     type Output = Result<Response<ResBody>, S::Error>;
@@ -68,7 +76,15 @@ where
                 headers,
                 extensions,
                 body,
+                drain,
             } => {
+                if let Some((draining, _)) = drain {
+                    ready!(draining.as_mut().poll(cx));
+                    if let Some((_, req)) = drain.take() {
+                        future.set(Either::Right(Oneshot::new(service.clone(), req)));
+                    }
+                }
+
                 let mut res = ready!(future.as_mut().poll(cx)?);
                 res.extensions_mut().insert(RequestUri(uri.clone()));
 
@@ -101,6 +117,15 @@ where
                         drop_payload_headers(headers);
                     }
                     StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {}
+                    // A page refreshing to another location is navigated to like a `303`.
+                    StatusCode::OK
+                        if res.extensions().get::<Refresh>().is_some()
+                            && policy.follow_refresh() =>
+                    {
+                        *method = Method::GET;
+                        *body = BodyRepr::Empty;
+                        drop_payload_headers(headers);
+                    }
                     _ => return Poll::Ready(Ok(res)),
                 };
 
@@ -110,10 +135,15 @@ where
                     return Poll::Ready(Ok(res));
                 };
 
-                let location = res
-                    .headers()
-                    .get(&LOCATION)
-                    .and_then(|loc| resolve_uri(str::from_utf8(loc.as_bytes()).ok()?, uri));
+                let location = match res.extensions().get::<Refresh>() {
+                    Some(Refresh(target)) if res.status() == StatusCode::OK => {
+                        resolve_uri(target, uri)
+                    }
+                    _ => res
+                        .headers()
+                        .get(&LOCATION)
+                        .and_then(|loc| resolve_uri(str::from_utf8(loc.as_bytes()).ok()?, uri)),
+                };
                 let location = if let Some(loc) = location {
                     loc
                 } else {
@@ -122,6 +152,7 @@ where
 
                 let attempt = Attempt {
                     status: res.status(),
+                    headers: res.headers(),
                     location: &location,
                     previous: uri,
                 };
@@ -137,7 +168,19 @@ where
                         *req.headers_mut() = headers.clone();
                         *req.extensions_mut() = extensions.clone();
                         policy.on_request(&mut req);
-                        future.set(Either::Right(Oneshot::new(service.clone(), req)));
+
+                        // Read the rest of a small body so that its connection can be reused,
+                        // rather than closing it by dropping the body.
+                        let limit = policy.drain_limit();
+                        let res_body = res.into_body();
+                        if limit > 0
+                            && res_body.size_hint().lower() <= limit
+                            && !res_body.is_end_stream()
+                        {
+                            *drain = Some((Box::pin(drain_body(res_body, limit)), req));
+                        } else {
+                            future.set(Either::Right(Oneshot::new(service.clone(), req)));
+                        }
 
                         cx.waker().wake_by_ref();
                         Poll::Pending
@@ -153,6 +196,20 @@ where
     }
 }
 
+/// Reads and discards `body`, giving up once more than `limit` bytes were read or on error.
+async fn drain_body<B: Body>(body: B, limit: u64) {
+    let mut body = pin!(body);
+    let mut read = 0;
+    while let Some(Ok(frame)) = body.frame().await {
+        if let Some(data) = frame.data_ref() {
+            read += data.remaining() as u64;
+            if read > limit {
+                return;
+            }
+        }
+    }
+}
+
 /// Try to resolve a URI reference `relative` against a base URI `base`.
 fn resolve_uri(relative: &str, base: &Uri) -> Option<Uri> {
     let mut buffer = String::with_capacity(relative.len() + 10);
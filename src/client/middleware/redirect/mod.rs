@@ -62,6 +62,9 @@ impl<ReqBody, ResBody, S, P> Service<Request<ReqBody>> for FollowRedirect<S, P>
 where
     S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone,
     ReqBody: Body + Default,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Send,
     P: Policy<ReqBody, S::Error> + Clone,
 {
     type Response = Response<ResBody>;
@@ -90,6 +93,7 @@ where
                 headers: req.headers().clone(),
                 extensions: req.extensions().clone(),
                 body,
+                drain: None,
                 future: Either::Left(service.call(req)),
                 service,
                 policy,
@@ -110,6 +114,13 @@ where
 #[derive(Clone)]
 pub struct RequestUri(pub Uri);
 
+/// Response [`http::Extensions`] value marking a `200 OK` response that refreshes to another
+/// location, e.g. with a `<meta http-equiv="refresh">` tag.
+///
+/// It holds the refresh URL, which may be relative to the URI of the request.
+#[derive(Clone)]
+pub(crate) struct Refresh(pub(crate) String);
+
 #[derive(Debug)]
 enum BodyRepr<B> {
     Some(B),
@@ -1,6 +1,6 @@
 //! Tools for customizing the behavior of a [`FollowRedirect`][super::FollowRedirect] middleware.
 
-use http::{Request, StatusCode, Uri};
+use http::{HeaderMap, Request, StatusCode, Uri};
 
 /// Trait for the policy on handling redirection responses.
 pub trait Policy<B, E> {
@@ -46,6 +46,29 @@ pub trait Policy<B, E> {
     fn clone_body(&self, _body: &B) -> Option<B> {
         None
     }
+
+    /// Returns how many bytes of a redirection response body are read and discarded before
+    /// following it.
+    ///
+    /// A body fully read within the limit lets its connection return to the pool for the
+    /// redirected request. A larger body, or one whose `Content-Length` exceeds the limit, is
+    /// dropped instead, closing its connection.
+    ///
+    /// The default implementation returns `0`, never reading the body.
+    fn drain_limit(&self) -> u64 {
+        0
+    }
+
+    /// Invoked when the service received a `200 OK` response refreshing to another location,
+    /// e.g. with a `<meta http-equiv="refresh">` tag.
+    ///
+    /// This method returns whether the refresh is handled as a `303 See Other` redirection,
+    /// which is then passed to [`Policy::redirect`].
+    ///
+    /// The default implementation returns `false`.
+    fn follow_refresh(&mut self) -> bool {
+        false
+    }
 }
 
 impl<B, E, P> Policy<B, E> for &mut P
@@ -76,11 +99,22 @@ where
     fn clone_body(&self, body: &B) -> Option<B> {
         (**self).clone_body(body)
     }
+
+    #[inline(always)]
+    fn drain_limit(&self) -> u64 {
+        (**self).drain_limit()
+    }
+
+    #[inline(always)]
+    fn follow_refresh(&mut self) -> bool {
+        (**self).follow_refresh()
+    }
 }
 
 /// A type that holds information on a redirection attempt.
 pub struct Attempt<'a> {
     pub(crate) status: StatusCode,
+    pub(crate) headers: &'a HeaderMap,
     pub(crate) location: &'a Uri,
     pub(crate) previous: &'a Uri,
 }
@@ -92,6 +126,12 @@ impl<'a> Attempt<'a> {
         self.status
     }
 
+    /// Returns the headers of the redirection response.
+    #[inline(always)]
+    pub fn headers(&self) -> &'a HeaderMap {
+        self.headers
+    }
+
     /// Returns the destination URI of the redirection.
     #[inline(always)]
     pub fn location(&self) -> &'a Uri {
@@ -0,0 +1,101 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// A response body whose beginning may have been read to look for a refresh.
+    #[project = RefreshBodyProj]
+    pub enum RefreshBody<B>
+    where
+        B: Body,
+    {
+        Plain {
+            #[pin]
+            body: B,
+        },
+        Scanned {
+            prefix: Option<Bytes>,
+            error: Option<B::Error>,
+            body: Pin<Box<B>>,
+        },
+    }
+}
+
+impl<B: Body> RefreshBody<B> {
+    /// Wraps a body that was not read.
+    #[inline]
+    pub(super) fn plain(body: B) -> Self {
+        RefreshBody::Plain { body }
+    }
+
+    /// Wraps a body that was read up to `prefix`, or up to `error`.
+    pub(super) fn scanned(prefix: Bytes, error: Option<B::Error>, body: Pin<Box<B>>) -> Self {
+        RefreshBody::Scanned {
+            prefix: Some(prefix).filter(|prefix| !prefix.is_empty()),
+            error,
+            body,
+        }
+    }
+}
+
+impl<B> Body for RefreshBody<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.project() {
+            RefreshBodyProj::Plain { body } => body.poll_frame(cx),
+            RefreshBodyProj::Scanned {
+                prefix,
+                error,
+                body,
+            } => {
+                if let Some(prefix) = prefix.take() {
+                    return Poll::Ready(Some(Ok(Frame::data(prefix))));
+                }
+                if let Some(error) = error.take() {
+                    return Poll::Ready(Some(Err(error)));
+                }
+                body.as_mut().poll_frame(cx)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            RefreshBody::Plain { body } => body.size_hint(),
+            RefreshBody::Scanned { prefix, body, .. } => {
+                let prefix = prefix.as_ref().map_or(0, |prefix| prefix.len() as u64);
+                let inner = body.size_hint();
+                let mut hint = SizeHint::new();
+                hint.set_lower(inner.lower() + prefix);
+                if let Some(upper) = inner.upper() {
+                    hint.set_upper(upper + prefix);
+                }
+                hint
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            RefreshBody::Plain { body } => body.is_end_stream(),
+            RefreshBody::Scanned {
+                prefix,
+                error,
+                body,
+            } => prefix.is_none() && error.is_none() && body.is_end_stream(),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use http::{
+    HeaderMap, Response, StatusCode,
+    header::{CONTENT_ENCODING, CONTENT_TYPE, REFRESH},
+};
+use http_body::Body as HttpBody;
+use http_body_util::BodyExt;
+use pin_project_lite::pin_project;
+
+use super::{body::RefreshBody, parse};
+use crate::client::middleware::redirect::Refresh;
+
+/// The number of bytes of a page searched for a `<meta http-equiv="refresh">` tag.
+const MAX_SCAN: usize = 32 * 1024;
+
+pin_project! {
+    /// Response future for [`MetaRefresh`](super::MetaRefresh).
+    #[project = ResponseFutureProj]
+    pub enum ResponseFuture<F, B, E>
+    where
+        B: HttpBody,
+    {
+        Plain {
+            #[pin]
+            future: F,
+        },
+        Scan {
+            future: Pin<Box<dyn Future<Output = Result<Response<RefreshBody<B>>, E>> + Send>>,
+        },
+    }
+}
+
+impl<F, B, E> Future for ResponseFuture<F, B, E>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: HttpBody,
+{
+    type Output = Result<Response<RefreshBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Plain { future } => future
+                .poll(cx)
+                .map(|res| res.map(|res| res.map(RefreshBody::plain))),
+            ResponseFutureProj::Scan { future } => future.as_mut().poll(cx),
+        }
+    }
+}
+
+/// Awaits the response of `future`, marking it with a [`Refresh`] if it is an HTML page
+/// refreshing to another location.
+pub(super) async fn scan<F, B, E>(future: F) -> Result<Response<RefreshBody<B>>, E>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: HttpBody<Data = Bytes>,
+{
+    let mut res = future.await?;
+    if res.status() != StatusCode::OK || !is_html(res.headers()) {
+        return Ok(res.map(RefreshBody::plain));
+    }
+
+    if let Some(target) = res
+        .headers()
+        .get(REFRESH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse::refresh)
+    {
+        res.extensions_mut().insert(Refresh(target));
+        return Ok(res.map(RefreshBody::plain));
+    }
+
+    // A body decompressed on demand can't be searched yet.
+    if res.headers().contains_key(CONTENT_ENCODING) {
+        return Ok(res.map(RefreshBody::plain));
+    }
+
+    let (mut parts, body) = res.into_parts();
+    let mut body = Box::pin(body);
+    let mut buf = BytesMut::new();
+    let mut error = None;
+    while buf.len() < MAX_SCAN {
+        match body.frame().await {
+            Some(Ok(frame)) => {
+                if let Ok(data) = frame.into_data() {
+                    buf.extend_from_slice(&data);
+                }
+            }
+            Some(Err(err)) => {
+                error = Some(err);
+                break;
+            }
+            None => break,
+        }
+    }
+
+    let buf = buf.freeze();
+    if let Some(target) = parse::meta_refresh(&buf[..buf.len().min(MAX_SCAN)]) {
+        parts.extensions.insert(Refresh(target));
+    }
+    Ok(Response::from_parts(
+        parts,
+        RefreshBody::scanned(buf, error, body),
+    ))
+}
+
+fn is_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|essence| {
+            let essence = essence.trim();
+            essence.eq_ignore_ascii_case("text/html")
+                || essence.eq_ignore_ascii_case("application/xhtml+xml")
+        })
+}
@@ -0,0 +1,77 @@
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::{Method, Request, Response};
+use http_body::Body as HttpBody;
+use tower::Layer;
+use tower_service::Service;
+
+use super::{
+    body::RefreshBody,
+    future::{ResponseFuture, scan},
+};
+
+/// Layer to apply [`MetaRefresh`] middleware.
+#[derive(Clone, Copy)]
+pub struct MetaRefreshLayer {
+    enabled: bool,
+}
+
+impl MetaRefreshLayer {
+    /// Create a new refresh detection layer.
+    ///
+    /// The layer must be wrapped by the redirect layer, which follows the detected refreshes.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl<S> Layer<S> for MetaRefreshLayer {
+    type Service = MetaRefresh<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetaRefresh {
+            inner,
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Middleware marking `200 OK` HTML responses that refresh to another location, with a
+/// `Refresh` header or a `<meta http-equiv="refresh">` tag.
+#[derive(Clone)]
+pub struct MetaRefresh<S> {
+    inner: S,
+    enabled: bool,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for MetaRefresh<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+    ResBody::Error: Send,
+{
+    type Response = Response<RefreshBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, ResBody, S::Error>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // A response to a HEAD request has no body to refresh from.
+        if !self.enabled || req.method() == Method::HEAD {
+            return ResponseFuture::Plain {
+                future: self.inner.call(req),
+            };
+        }
+
+        ResponseFuture::Scan {
+            future: Box::pin(scan(self.inner.call(req))),
+        }
+    }
+}
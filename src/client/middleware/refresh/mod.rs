@@ -0,0 +1,12 @@
+//! Middleware to detect HTML pages refreshing to another location.
+
+mod body;
+mod future;
+mod layer;
+mod parse;
+
+pub use self::{
+    body::RefreshBody,
+    future::ResponseFuture,
+    layer::{MetaRefresh, MetaRefreshLayer},
+};
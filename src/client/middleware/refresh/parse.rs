@@ -0,0 +1,175 @@
+//! Parsing of refresh declarations, following the [HTML standard].
+//!
+//! [HTML standard]: https://html.spec.whatwg.org/multipage/semantics.html#shared-declarative-refresh-steps
+
+/// Returns the URL of a `Refresh` header or `<meta http-equiv="refresh">` content, like
+/// `0; url=/next`.
+///
+/// A refresh without a URL reloads the page itself and is ignored.
+pub(super) fn refresh(content: &str) -> Option<String> {
+    let content = content.trim_start_matches(is_whitespace);
+
+    // The delay, which is not waited for.
+    let rest = content.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if rest.len() == content.len() {
+        return None;
+    }
+
+    let rest = rest.trim_start_matches(is_whitespace);
+    let rest = rest
+        .strip_prefix([';', ','])
+        .unwrap_or(rest)
+        .trim_start_matches(is_whitespace);
+
+    let rest = match rest.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url") => {
+            let after = rest[3..].trim_start_matches(is_whitespace);
+            match after.strip_prefix('=') {
+                Some(after) => after.trim_start_matches(is_whitespace),
+                None => rest,
+            }
+        }
+        _ => rest,
+    };
+
+    let url = match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let rest = &rest[1..];
+            rest.split(quote).next().unwrap_or(rest)
+        }
+        _ => rest,
+    };
+
+    let url = url.trim_end_matches(is_whitespace);
+    (!url.is_empty()).then(|| url.to_owned())
+}
+
+/// Returns the URL of the first `<meta http-equiv="refresh">` tag of an HTML document.
+///
+/// Tags in `<noscript>` elements count, as a browser without scripts honors them too.
+pub(super) fn meta_refresh(html: &[u8]) -> Option<String> {
+    let html = String::from_utf8_lossy(html);
+    let mut rest = &*html;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        if let Some(comment) = rest.strip_prefix("!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let is_meta = rest
+            .get(..4)
+            .is_some_and(|name| name.eq_ignore_ascii_case("meta"))
+            && rest[4..].starts_with(|c: char| is_whitespace(c) || c == '/');
+        if !is_meta {
+            continue;
+        }
+
+        let (attributes, end) = attributes(&rest[4..]);
+        rest = &rest[4 + end..];
+
+        let http_equiv = attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("http-equiv"));
+        let content = attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content"));
+        if let (Some((_, http_equiv)), Some((_, content))) = (http_equiv, content) {
+            if http_equiv.trim().eq_ignore_ascii_case("refresh") {
+                return refresh(&content.replace("&amp;", "&"));
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses the attributes of a tag up to its closing `>`, returning them along with the
+/// length of the tag parsed.
+fn attributes(tag: &str) -> (Vec<(&str, &str)>, usize) {
+    let mut attributes = Vec::new();
+    let mut pos = 0;
+
+    loop {
+        pos += tag[pos..]
+            .find(|c: char| !is_whitespace(c) && c != '/')
+            .unwrap_or(tag.len() - pos);
+        if pos == tag.len() || tag[pos..].starts_with('>') {
+            return (attributes, (pos + 1).min(tag.len()));
+        }
+
+        let name_len = tag[pos..]
+            .find(|c: char| is_whitespace(c) || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(tag.len() - pos)
+            .max(1);
+        let name = &tag[pos..pos + name_len];
+        pos += name_len;
+
+        let after = tag[pos..].trim_start_matches(is_whitespace);
+        let Some(value) = after.strip_prefix('=') else {
+            attributes.push((name, ""));
+            continue;
+        };
+        let value = value.trim_start_matches(is_whitespace);
+        pos = tag.len() - value.len();
+
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let len = value[1..].find(quote).unwrap_or(value.len() - 1);
+                pos += len + 2;
+                &value[1..1 + len]
+            }
+            _ => {
+                let len = value
+                    .find(|c: char| is_whitespace(c) || c == '>')
+                    .unwrap_or(value.len());
+                pos += len;
+                &value[..len]
+            }
+        };
+        pos = pos.min(tag.len());
+        attributes.push((name, value));
+    }
+}
+
+fn is_whitespace(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\x0C' | '\r' | ' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_content() {
+        assert_eq!(refresh("0; url=/next").as_deref(), Some("/next"));
+        assert_eq!(refresh("5,URL = 'a b'").as_deref(), Some("a b"));
+        assert_eq!(
+            refresh("0;https://example.com/").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(refresh("30"), None);
+        assert_eq!(refresh("url=/next"), None);
+    }
+
+    #[test]
+    fn meta_refresh_tags() {
+        let html = br#"<html><head>
+            <!-- <meta http-equiv="refresh" content="0; url=/commented"> -->
+            <meta charset="utf-8">
+            <noscript><META HTTP-EQUIV=Refresh CONTENT="0;URL=/check?a=1&amp;b=2"></noscript>
+        </head></html>"#;
+        assert_eq!(meta_refresh(html).as_deref(), Some("/check?a=1&b=2"));
+
+        assert_eq!(
+            meta_refresh(b"<meta name=refresh content='0; url=/'>"),
+            None
+        );
+        assert_eq!(
+            meta_refresh(b"<metadata http-equiv=refresh content='0; url=/'>"),
+            None
+        );
+    }
+}
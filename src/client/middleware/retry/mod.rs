@@ -1,104 +1,131 @@
 //! Middleware for retrying requests.
 
 use futures_util::future;
-use http::{Request, Response};
+use http::{Method, Request, Response};
+use http2::Reason;
 use tower::retry::Policy;
-#[cfg(any(
-    feature = "gzip",
-    feature = "zstd",
-    feature = "brotli",
-    feature = "deflate",
-))]
-use tower_http::decompression::DecompressionBody;
 
-use super::timeout::TimeoutBody;
-use crate::{Body, core::body::Incoming, error::BoxError};
+use super::{config::RequestRetryPolicy, context::RequestContext};
+use crate::{
+    Body,
+    core::ext::{RequestConfig, SharedHeaders},
+    error::BoxError,
+    idempotency::IDEMPOTENCY_KEY,
+    retry,
+};
 
 /// A retry policy for HTTP/2 requests that safely determines whether and how many times
 /// a request should be retried based on error type and a maximum retry count.
 ///
 /// This policy helps avoid unsafe or infinite retries by tracking the number of attempts
 /// and only retrying errors that are considered safe to repeat (such as connection-level errors).
+/// A refused stream or a graceful `GOAWAY` is retried whatever the method, while a `GOAWAY`
+/// or a stream reset that may come after the request was processed is only retried for
+/// idempotent methods and requests carrying an `Idempotency-Key`.
+///
+/// The retry policy of a request, if any, overrides the policy of the client.
 #[derive(Clone)]
-pub struct Http2RetryPolicy(usize);
+pub struct Http2RetryPolicy {
+    policy: RequestConfig<RequestRetryPolicy>,
+    attempts: Option<usize>,
+}
 
 impl Http2RetryPolicy {
-    /// Create a new `Http2RetryPolicy` policy with the specified number of attempts.
+    /// Create a new `Http2RetryPolicy` policy with the specified retry policy.
     #[inline]
-    pub const fn new(attempts: usize) -> Self {
-        Self(attempts)
+    pub const fn new(policy: retry::Policy) -> Self {
+        Self {
+            policy: RequestConfig::new(Some(policy)),
+            attempts: None,
+        }
+    }
+
+    /// Returns the maximum number of retries of the given request.
+    fn max_retries(&self, req: &Req) -> usize {
+        self.policy
+            .fetch(req.extensions())
+            .map_or(0, retry::Policy::max_retries)
     }
 
     /// Determines whether the given error is considered retryable for HTTP/2 requests.
     ///
     /// Returns `true` if the error type or content indicates that the request can be retried,
     /// otherwise returns `false`.
-    fn is_retryable_error(&self, err: &(dyn std::error::Error + 'static)) -> bool {
-        let err = if let Some(err) = err.source() {
-            err
-        } else {
-            return false;
+    fn is_retryable_error(&self, req: &Req, err: &(dyn std::error::Error + 'static)) -> bool {
+        let mut source = err.source();
+        let err = loop {
+            match source {
+                Some(err) => match err.downcast_ref::<http2::Error>() {
+                    Some(err) => break err,
+                    None => source = err.source(),
+                },
+                None => return false,
+            }
         };
 
-        if let Some(cause) = err.source() {
-            if let Some(err) = cause.downcast_ref::<http2::Error>() {
-                // They sent us a graceful shutdown, try with a new connection!
-                if err.is_go_away()
-                    && err.is_remote()
-                    && err.reason() == Some(http2::Reason::NO_ERROR)
-                {
-                    return true;
-                }
+        if !err.is_remote() {
+            return false;
+        }
 
-                // REFUSED_STREAM was sent from the server, which is safe to retry.
-                // https://www.rfc-editor.org/rfc/rfc9113.html#section-8.7-3.2
-                if err.is_reset()
-                    && err.is_remote()
-                    && err.reason() == Some(http2::Reason::REFUSED_STREAM)
-                {
-                    return true;
-                }
+        match err.reason() {
+            // They sent us a graceful shutdown, try with a new connection!
+            Some(Reason::NO_ERROR) if err.is_go_away() => true,
+            // REFUSED_STREAM was sent from the server, which is safe to retry.
+            // https://www.rfc-editor.org/rfc/rfc9113.html#section-8.7-3.2
+            Some(Reason::REFUSED_STREAM) if err.is_reset() => true,
+            // The server went away or reset the stream, possibly after processing the request,
+            // so only a request that can be repeated without side effects is sent again.
+            Some(Reason::INTERNAL_ERROR | Reason::ENHANCE_YOUR_CALM) if err.is_go_away() => {
+                is_idempotent(req)
+            }
+            Some(Reason::NO_ERROR | Reason::CANCEL | Reason::INTERNAL_ERROR) if err.is_reset() => {
+                is_idempotent(req)
             }
+            _ => false,
         }
-        false
     }
 }
 
+/// Returns true if the request can be repeated without side effects, either from its method or
+/// because the server deduplicates it by its `Idempotency-Key`.
+pub(crate) fn is_idempotent(req: &Req) -> bool {
+    matches!(
+        *req.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE | Method::PUT | Method::DELETE
+    ) || SharedHeaders::contains(req, &IDEMPOTENCY_KEY)
+}
+
 type Req = Request<Body>;
-#[cfg(not(any(
-    feature = "gzip",
-    feature = "zstd",
-    feature = "brotli",
-    feature = "deflate",
-)))]
-type Res = Response<TimeoutBody<Incoming>>;
-#[cfg(any(
-    feature = "gzip",
-    feature = "zstd",
-    feature = "brotli",
-    feature = "deflate",
-))]
-type Res = Response<TimeoutBody<DecompressionBody<Incoming>>>;
-
-impl Policy<Req, Res, BoxError> for Http2RetryPolicy {
+
+impl<ResBody> Policy<Req, Response<ResBody>, BoxError> for Http2RetryPolicy {
     type Future = future::Ready<()>;
 
     fn retry(
         &mut self,
-        _req: &mut Req,
-        result: &mut Result<Res, BoxError>,
+        req: &mut Req,
+        result: &mut Result<Response<ResBody>, BoxError>,
     ) -> Option<Self::Future> {
         if let Err(err) = result {
-            if !self.is_retryable_error(err.as_ref()) {
+            if !self.is_retryable_error(req, err.as_ref()) {
                 return None;
             }
 
+            // The policy is cloned for every request, so the attempts are resolved on the
+            // first failure, from the request's own policy if it has one.
+            let attempts = match self.attempts {
+                Some(attempts) => attempts,
+                None => self.max_retries(req),
+            };
+
             // Treat all errors as failures...
             // But we limit the number of attempts...
-            return if self.0 > 0 {
-                trace!("Retrying HTTP/2 request, attempts left: {}", self.0);
+            return if attempts > 0 {
+                trace!("Retrying HTTP/2 request, attempts left: {}", attempts);
+                if let Some(context) = req.extensions().get::<RequestContext>() {
+                    context.retried();
+                }
                 // Try again!
-                self.0 -= 1;
+                self.attempts = Some(attempts - 1);
                 Some(future::ready(()))
             } else {
                 // Used all our attempts, no retry...
@@ -110,6 +137,11 @@ impl Policy<Req, Res, BoxError> for Http2RetryPolicy {
     }
 
     fn clone_request(&mut self, req: &Req) -> Option<Req> {
+        // Don't bother cloning a request that is never retried.
+        if self.max_retries(req) == 0 {
+            return None;
+        }
+
         let mut new_req = Request::builder()
             .method(req.method().clone())
             .uri(req.uri().clone())
@@ -123,3 +155,20 @@ impl Policy<Req, Res, BoxError> for Http2RetryPolicy {
         Some(new_req)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_policy_overrides_client_policy() {
+        let mut policy = Http2RetryPolicy::new(retry::Policy::default());
+
+        let mut req = Request::new(Body::from("hello"));
+        assert!(policy.clone_request(&req).is_some());
+
+        *RequestConfig::<RequestRetryPolicy>::get_mut(req.extensions_mut()) =
+            Some(retry::Policy::none());
+        assert!(policy.clone_request(&req).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::{Pin, pin},
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use http::{Extensions, Request, Response, Uri, header::LOCATION};
+use http_body::Body as HttpBody;
+use http_body_util::BodyExt;
+use pin_project_lite::pin_project;
+use tower::ServiceExt;
+use tower_service::Service;
+use url::Url;
+
+use crate::{
+    Body, Error,
+    client::middleware::config::RequestCrawlDelay,
+    core::ext::RequestConfig,
+    error::BoxError,
+    robots::{Robots, RobotsCache, RobotsDisallowed},
+};
+
+/// The number of redirects followed to fetch a robots.txt file.
+const MAX_REDIRECTS: usize = 5;
+
+/// The size of a robots.txt file beyond which it is truncated.
+const MAX_SIZE: usize = 512 * 1024;
+
+pin_project! {
+    /// Response future for [`RobotsExclusion`](super::RobotsExclusion).
+    #[project = ResponseFutureProj]
+    pub enum ResponseFuture<F, B> {
+        Unchecked {
+            #[pin]
+            future: F,
+        },
+        Checked {
+            future: Pin<Box<dyn Future<Output = Result<Response<B>, BoxError>> + Send>>,
+        },
+    }
+}
+
+impl<F, B> Future for ResponseFuture<F, B>
+where
+    F: Future<Output = Result<Response<B>, BoxError>>,
+{
+    type Output = Result<Response<B>, BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Unchecked { future } => future.poll(cx),
+            ResponseFutureProj::Checked { future } => future.as_mut().poll(cx),
+        }
+    }
+}
+
+/// Sends `req` with `inner` if the robots.txt of its origin allows it for `agent`.
+pub(super) async fn check<S, B>(
+    inner: S,
+    cache: Arc<RobotsCache>,
+    agent: String,
+    mut req: Request<Body>,
+) -> Result<Response<B>, BoxError>
+where
+    S: Service<Request<Body>, Response = Response<B>, Error = BoxError> + Clone,
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    if let Some(origin) = origin(req.uri()) {
+        let robots = match cache.get(&origin) {
+            Some(robots) => robots,
+            None => {
+                let (robots, cacheable) = fetch(inner.clone(), &origin, req.extensions()).await?;
+                let robots = Arc::new(robots);
+                if cacheable {
+                    cache.insert(origin, robots.clone());
+                }
+                robots
+            }
+        };
+
+        let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+        if !robots.is_allowed(&agent, path) {
+            return Err(Error::request(RobotsDisallowed::new(agent)).into());
+        }
+
+        // Spaced out by the in-flight limit layer.
+        if let Some(delay) = robots.crawl_delay(&agent) {
+            RequestConfig::<RequestCrawlDelay>::get_mut(req.extensions_mut()).replace(delay);
+        }
+    }
+
+    inner.oneshot(req).await
+}
+
+/// Fetches the robots.txt of `origin`, returning whether the outcome may be cached.
+///
+/// A missing file allows everything, while a file that is unreachable because of a server
+/// error disallows everything until it is fetched again.
+async fn fetch<S, B>(
+    inner: S,
+    origin: &str,
+    extensions: &Extensions,
+) -> Result<(Robots, bool), BoxError>
+where
+    S: Service<Request<Body>, Response = Response<B>, Error = BoxError> + Clone,
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let mut url = Url::parse(&format!("{origin}/robots.txt"))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut req = Request::new(Body::empty());
+        *req.uri_mut() = Uri::try_from(url.as_str())?;
+        *req.extensions_mut() = extensions.clone();
+
+        let res = inner.clone().oneshot(req).await?;
+        let status = res.status();
+
+        if status.is_redirection() {
+            let location = res
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| url.join(location).ok());
+            match location {
+                Some(location) => {
+                    url = location;
+                    continue;
+                }
+                None => return Ok((Robots::allow_all(), true)),
+            }
+        }
+
+        if status.is_success() {
+            let mut body = pin!(res.into_body());
+            let mut buf = BytesMut::new();
+            while let Some(frame) = body.frame().await {
+                if let Ok(data) = frame.map_err(Into::into)?.into_data() {
+                    let len = data.len().min(MAX_SIZE - buf.len());
+                    buf.extend_from_slice(&data[..len]);
+                    if buf.len() == MAX_SIZE {
+                        break;
+                    }
+                }
+            }
+            return Ok((Robots::parse(&String::from_utf8_lossy(&buf)), true));
+        }
+
+        if status.is_client_error() {
+            return Ok((Robots::allow_all(), true));
+        }
+
+        return Ok((Robots::disallow_all(), false));
+    }
+
+    // Too many redirects, the file is considered missing.
+    Ok((Robots::allow_all(), true))
+}
+
+/// Returns the origin of `uri` if it is an HTTP one.
+fn origin(uri: &Uri) -> Option<String> {
+    let scheme = uri.scheme_str()?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let host = uri.host()?;
+    let origin = match uri.port() {
+        Some(port) => format!("{scheme}://{host}:{port}"),
+        None => format!("{scheme}://{host}"),
+    };
+    Some(origin.to_ascii_lowercase())
+}
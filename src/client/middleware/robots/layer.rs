@@ -0,0 +1,87 @@
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::Body as HttpBody;
+use tower::Layer;
+use tower_service::Service;
+
+use super::future::{ResponseFuture, check};
+use crate::{
+    Body, client::middleware::config::RequestRobotsAgent, core::ext::RequestConfig,
+    error::BoxError, robots::RobotsCache,
+};
+
+/// Layer to apply [`RobotsExclusion`] middleware.
+#[derive(Clone)]
+pub struct RobotsExclusionLayer {
+    cache: Arc<RobotsCache>,
+}
+
+impl RobotsExclusionLayer {
+    /// Create a new robots.txt layer caching the files for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: Arc::new(RobotsCache::new(ttl)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RobotsExclusionLayer {
+    type Service = RobotsExclusion<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RobotsExclusion {
+            inner,
+            agent: RequestConfig::default(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+/// Middleware checking requests against the robots.txt of their origin.
+#[derive(Clone)]
+pub struct RobotsExclusion<S> {
+    inner: S,
+    agent: RequestConfig<RequestRobotsAgent>,
+    cache: Arc<RobotsCache>,
+}
+
+impl<ResBody, S> Service<Request<Body>> for RobotsExclusion<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>, Error = BoxError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+    ResBody: HttpBody<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Future, ResBody>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let Some(agent) = self.agent.fetch(req.extensions()).cloned() else {
+            return ResponseFuture::Unchecked {
+                future: self.inner.call(req),
+            };
+        };
+
+        // Keep the service that was polled ready for the request itself.
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        ResponseFuture::Checked {
+            future: Box::pin(check(inner, self.cache.clone(), agent, req)),
+        }
+    }
+}
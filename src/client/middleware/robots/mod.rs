@@ -0,0 +1,6 @@
+//! Middleware to respect robots.txt.
+
+mod future;
+mod layer;
+
+pub use self::layer::{RobotsExclusion, RobotsExclusionLayer};
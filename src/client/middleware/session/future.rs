@@ -0,0 +1,54 @@
+//! [`Future`] types.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use http::{Response, Uri};
+use pin_project_lite::pin_project;
+
+use crate::client::session::SessionContext;
+
+pin_project! {
+    /// Response future for [`SessionManager`].
+    #[project=ResponseFutureProj]
+    pub enum ResponseFuture<F> {
+        WithSession {
+            #[pin]
+            future: F,
+            session: SessionContext,
+            uri: Uri,
+        },
+        WithoutSession {
+            #[pin]
+            future: F,
+        },
+    }
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<ResBody>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::WithSession {
+                future,
+                session,
+                uri,
+            } => {
+                let res = ready!(future.poll(cx)?);
+                session.observe(uri, &res);
+                Poll::Ready(Ok(res))
+            }
+            ResponseFutureProj::WithoutSession { mut future } => {
+                let res = ready!(future.as_mut().poll(cx)?);
+                Poll::Ready(Ok(res))
+            }
+        }
+    }
+}
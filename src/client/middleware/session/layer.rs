@@ -0,0 +1,92 @@
+use std::task::{Context, Poll};
+
+use http::{Method, Request, Response, Uri, uri::Scheme};
+use tower::Layer;
+use tower_service::Service;
+
+use super::future::ResponseFuture;
+use crate::{client::middleware::config::RequestSession, core::ext::RequestConfig};
+
+/// Layer to apply [`SessionManager`] middleware.
+#[derive(Clone, Default)]
+pub struct SessionManagerLayer {
+    _priv: (),
+}
+
+impl SessionManagerLayer {
+    /// Create a new session manager layer.
+    pub const fn new() -> Self {
+        Self { _priv: () }
+    }
+}
+
+impl<S> Layer<S> for SessionManagerLayer {
+    type Service = SessionManager<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SessionManager { inner }
+    }
+}
+
+/// Middleware to update the [`Session`](crate::Session) a request was sent from.
+#[derive(Clone)]
+pub struct SessionManager<S> {
+    inner: S,
+}
+
+impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for SessionManager<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let session = match RequestConfig::<RequestSession>::get(req.extensions()) {
+            Some(session) => session.clone(),
+            None => {
+                return ResponseFuture::WithoutSession {
+                    future: self.inner.call(req),
+                };
+            }
+        };
+
+        // Redirect hops to a known HSTS host are upgraded to HTTPS as well. A `CONNECT` is sent
+        // to a proxy, over plain HTTP whatever the host.
+        if req.uri().scheme() == Some(&Scheme::HTTP) && req.method() != Method::CONNECT {
+            if let Some(host) = req.uri().host() {
+                if session.state.is_hsts(host) {
+                    if let Some(uri) = upgrade_uri(req.uri()) {
+                        *req.uri_mut() = uri;
+                    }
+                }
+            }
+        }
+
+        let uri = req.uri().clone();
+        ResponseFuture::WithSession {
+            future: self.inner.call(req),
+            session,
+            uri,
+        }
+    }
+}
+
+fn upgrade_uri(uri: &Uri) -> Option<Uri> {
+    let host = uri.host()?;
+    let authority = match uri.port_u16() {
+        Some(80) | None => host.to_owned(),
+        Some(port) => format!("{host}:{port}"),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.scheme = Some(Scheme::HTTPS);
+    parts.authority = Some(authority.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
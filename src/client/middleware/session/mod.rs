@@ -0,0 +1,6 @@
+//! Middleware to track per-origin session state.
+
+mod future;
+mod layer;
+
+pub use self::layer::{SessionManager, SessionManagerLayer};
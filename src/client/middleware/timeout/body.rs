@@ -5,12 +5,16 @@ use std::{
     time::Duration,
 };
 
+use bytes::Buf;
 use http_body::Body;
 use pin_project_lite::pin_project;
-use tokio::time::{Sleep, sleep};
 
 use crate::{
     Error,
+    core::{
+        common::timer::Timer,
+        rt::{Sleep, Timer as _},
+    },
     error::{BoxError, TimedOut},
 };
 
@@ -46,19 +50,25 @@ pin_project! {
     pub struct TotalTimeoutBody<B> {
         #[pin]
         body: B,
-        timeout: Pin<Box<Sleep>>,
+        timeout: Pin<Box<dyn Sleep>>,
     }
 }
 
 pin_project! {
-    /// A body wrapper that enforces a timeout for each read operation.
+    /// A body wrapper that enforces a timeout for each read operation, and a timeout for the
+    /// body to make progress.
     ///
-    /// The timeout resets after every successful read. If a single read
+    /// The read timeout resets after every successful read. If a single read
     /// takes longer than the specified duration, an error is returned.
+    ///
+    /// The stall timeout only resets when a read yields data, so that a body which keeps
+    /// yielding empty frames or stops sending is aborted after the specified duration.
     pub struct ReadTimeoutBody<B> {
-        timeout: Duration,
-        #[pin]
-        sleep: Option<Sleep>,
+        timer: Timer,
+        timeout: Option<Duration>,
+        sleep: Option<Pin<Box<dyn Sleep>>>,
+        stall_timeout: Option<Duration>,
+        stall: Option<Pin<Box<dyn Sleep>>>,
         #[pin]
         body: B,
     }
@@ -67,28 +77,27 @@ pin_project! {
 /// ==== impl TimeoutBody ====
 impl<B> TimeoutBody<B> {
     /// Creates a new [`TimeoutBody`] with no timeout.
-    pub fn new(deadline: Option<Duration>, read_timeout: Option<Duration>, body: B) -> Self {
-        let deadline = deadline.map(sleep).map(Box::pin);
-        match (deadline, read_timeout) {
-            (Some(total_timeout), Some(read_timeout)) => TimeoutBody::CombinedTimeout {
+    pub fn new(
+        timer: Timer,
+        deadline: Option<Duration>,
+        read_timeout: Option<Duration>,
+        stall_timeout: Option<Duration>,
+        body: B,
+    ) -> Self {
+        let deadline = deadline.map(|deadline| timer.sleep(deadline));
+        let read = (read_timeout.is_some() || stall_timeout.is_some()).then_some(());
+        match (deadline, read) {
+            (Some(total_timeout), Some(())) => TimeoutBody::CombinedTimeout {
                 body: TotalTimeoutBody {
                     timeout: total_timeout,
-                    body: ReadTimeoutBody {
-                        timeout: read_timeout,
-                        sleep: None,
-                        body,
-                    },
+                    body: ReadTimeoutBody::new(timer, read_timeout, stall_timeout, body),
                 },
             },
             (Some(timeout), None) => TimeoutBody::TotalTimeout {
                 body: TotalTimeoutBody { body, timeout },
             },
-            (None, Some(timeout)) => TimeoutBody::ReadTimeout {
-                body: ReadTimeoutBody {
-                    timeout,
-                    sleep: None,
-                    body,
-                },
+            (None, Some(())) => TimeoutBody::ReadTimeout {
+                body: ReadTimeoutBody::new(timer, read_timeout, stall_timeout, body),
             },
             (None, None) => TimeoutBody::Plain { body },
         }
@@ -182,6 +191,24 @@ where
 }
 
 /// ==== impl ReadTimeoutBody ====
+impl<B> ReadTimeoutBody<B> {
+    fn new(
+        timer: Timer,
+        timeout: Option<Duration>,
+        stall_timeout: Option<Duration>,
+        body: B,
+    ) -> Self {
+        ReadTimeoutBody {
+            timer,
+            timeout,
+            sleep: None,
+            stall_timeout,
+            stall: None,
+            body,
+        }
+    }
+}
+
 impl<B> Body for ReadTimeoutBody<B>
 where
     B: Body,
@@ -194,16 +221,24 @@ where
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
-        let mut this = self.project();
+        let this = self.project();
 
         // Error if the timeout has expired.
-        if this.sleep.is_none() {
-            this.sleep.set(Some(sleep(*this.timeout)));
+        if let (true, Some(timeout)) = (this.sleep.is_none(), *this.timeout) {
+            *this.sleep = Some(this.timer.sleep(timeout));
+        }
+        if let (true, Some(timeout)) = (this.stall.is_none(), *this.stall_timeout) {
+            *this.stall = Some(this.timer.sleep(timeout));
         }
 
         // Error if the timeout has expired.
-        if let Some(sleep) = this.sleep.as_mut().as_pin_mut() {
-            if sleep.poll(cx).is_ready() {
+        if let Some(sleep) = this.sleep.as_mut() {
+            if sleep.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Some(Err(Box::new(TimedOut))));
+            }
+        }
+        if let Some(stall) = this.stall.as_mut() {
+            if stall.as_mut().poll(cx).is_ready() {
                 return Poll::Ready(Some(Err(Box::new(TimedOut))));
             }
         }
@@ -212,7 +247,11 @@ where
         match ready!(this.body.poll_frame(cx)) {
             Some(Ok(frame)) => {
                 // Reset timeout on successful read
-                this.sleep.set(None);
+                *this.sleep = None;
+                // Reset the stall timeout only once the body makes progress
+                if frame.data_ref().is_some_and(|data| data.has_remaining()) {
+                    *this.stall = None;
+                }
                 Poll::Ready(Some(Ok(frame)))
             }
             Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
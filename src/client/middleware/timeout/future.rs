@@ -7,24 +7,21 @@ use std::{
 
 use http::{Response, Uri};
 use pin_project_lite::pin_project;
-use tokio::time::Sleep;
 
 use super::body::TimeoutBody;
 use crate::{
+    core::{common::timer::Timer, rt::Sleep},
     error::{BoxError, Error, TimedOut},
     into_url::IntoUrlSealed,
 };
 
 pin_project! {
     /// [`Timeout`] response future
-    #[derive(Debug)]
     pub struct ResponseFuture<T> {
         #[pin]
         pub(crate) response: T,
-        #[pin]
-        pub(crate) total_timeout: Option<Sleep>,
-        #[pin]
-        pub(crate) read_timeout: Option<Sleep>,
+        pub(crate) total_timeout: Option<Pin<Box<dyn Sleep>>>,
+        pub(crate) read_timeout: Option<Pin<Box<dyn Sleep>>>,
         pub(crate) uri: Uri,
     }
 }
@@ -37,7 +34,7 @@ where
     type Output = Result<T, BoxError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut this = self.project();
+        let this = self.project();
 
         // First, try polling the future
         match this.response.poll(cx) {
@@ -46,9 +43,9 @@ where
         }
 
         // Helper closure for polling a timeout and returning a TimedOut error
-        let mut check_timeout = |sleep: Option<Pin<&mut Sleep>>| {
+        let mut check_timeout = |sleep: Option<&mut Pin<Box<dyn Sleep>>>| {
             if let Some(sleep) = sleep {
-                if sleep.poll(cx).is_ready() {
+                if sleep.as_mut().poll(cx).is_ready() {
                     let err = match IntoUrlSealed::into_url(this.uri.to_string()) {
                         Ok(url) => Error::request(TimedOut).with_url(url).into(),
                         Err(_err) => {
@@ -66,12 +63,12 @@ where
         };
 
         // Check total timeout first
-        if let Some(poll) = check_timeout(this.total_timeout.as_mut().as_pin_mut()) {
+        if let Some(poll) = check_timeout(this.total_timeout.as_mut()) {
             return poll;
         }
 
         // Check read timeout
-        if let Some(poll) = check_timeout(this.read_timeout.as_mut().as_pin_mut()) {
+        if let Some(poll) = check_timeout(this.read_timeout.as_mut()) {
             return poll;
         }
 
@@ -86,6 +83,8 @@ pin_project! {
         pub(crate) inner: Fut,
         pub(crate) total_timeout: Option<Duration>,
         pub(crate) read_timeout: Option<Duration>,
+        pub(crate) stall_timeout: Option<Duration>,
+        pub(crate) timer: Timer,
     }
 }
 
@@ -98,10 +97,12 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let total_timeout = self.total_timeout;
         let read_timeout = self.read_timeout;
+        let stall_timeout = self.stall_timeout;
         let this = self.project();
         let res = ready!(this.inner.poll(cx))?;
-        Poll::Ready(Ok(
-            res.map(|body| TimeoutBody::new(total_timeout, read_timeout, body))
-        ))
+        let timer = this.timer.clone();
+        Poll::Ready(Ok(res.map(|body| {
+            TimeoutBody::new(timer, total_timeout, read_timeout, stall_timeout, body)
+        })))
     }
 }
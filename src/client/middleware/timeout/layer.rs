@@ -10,10 +10,10 @@ use tower_service::Service;
 use super::future::{ResponseBodyTimeoutFuture, ResponseFuture};
 use crate::{
     client::middleware::{
-        config::{RequestReadTimeout, RequestTotalTimeout},
+        config::{RequestBodyStallTimeout, RequestReadTimeout, RequestTotalTimeout},
         timeout::TimeoutBody,
     },
-    core::ext::RequestConfig,
+    core::{common::timer::Timer, ext::RequestConfig, rt::Timer as _},
     error::BoxError,
 };
 
@@ -23,14 +23,20 @@ use crate::{
 pub struct TimeoutLayer {
     total_timeout: RequestConfig<RequestTotalTimeout>,
     read_timeout: RequestConfig<RequestReadTimeout>,
+    timer: Timer,
 }
 
 impl TimeoutLayer {
-    /// Create a timeout from a duration
-    pub const fn new(total_timeout: Option<Duration>, read_timeout: Option<Duration>) -> Self {
+    /// Create a timeout from a duration, timed with the given timer
+    pub fn new(
+        total_timeout: Option<Duration>,
+        read_timeout: Option<Duration>,
+        timer: Timer,
+    ) -> Self {
         TimeoutLayer {
             total_timeout: RequestConfig::new(total_timeout),
             read_timeout: RequestConfig::new(read_timeout),
+            timer,
         }
     }
 }
@@ -43,6 +49,7 @@ impl<S> Layer<S> for TimeoutLayer {
             inner: service,
             total_timeout: self.total_timeout,
             read_timeout: self.read_timeout,
+            timer: self.timer.clone(),
         }
     }
 }
@@ -54,6 +61,7 @@ pub struct Timeout<T> {
     inner: T,
     total_timeout: RequestConfig<RequestTotalTimeout>,
     read_timeout: RequestConfig<RequestReadTimeout>,
+    timer: Timer,
 }
 
 impl<ReqBody, ResBody, S> Service<Request<ReqBody>> for Timeout<S>
@@ -74,13 +82,13 @@ where
             .total_timeout
             .fetch(req.extensions())
             .copied()
-            .map(tokio::time::sleep);
+            .map(|timeout| self.timer.sleep(timeout));
 
         let read_timeout = self
             .read_timeout
             .fetch(req.extensions())
             .copied()
-            .map(tokio::time::sleep);
+            .map(|timeout| self.timer.sleep(timeout));
 
         let uri = req.uri().clone();
         let response = self.inner.call(req);
@@ -94,19 +102,29 @@ where
 }
 
 /// [`Layer`] that applies a [`ResponseBodyTimeout`] middleware to a service.
-// This layer allows you to set a total timeout and a read timeout for the response body.
+// This layer allows you to set a total timeout, a read timeout and a stall timeout for the
+// response body.
 #[derive(Clone)]
 pub struct ResponseBodyTimeoutLayer {
     total_timeout: RequestConfig<RequestTotalTimeout>,
     read_timeout: RequestConfig<RequestReadTimeout>,
+    stall_timeout: RequestConfig<RequestBodyStallTimeout>,
+    timer: Timer,
 }
 
 impl ResponseBodyTimeoutLayer {
-    /// Creates a new [`ResponseBodyTimeoutLayer`].
-    pub const fn new(total_timeout: Option<Duration>, read_timeout: Option<Duration>) -> Self {
+    /// Creates a new [`ResponseBodyTimeoutLayer`], timed with the given timer.
+    pub fn new(
+        total_timeout: Option<Duration>,
+        read_timeout: Option<Duration>,
+        stall_timeout: Option<Duration>,
+        timer: Timer,
+    ) -> Self {
         Self {
             total_timeout: RequestConfig::new(total_timeout),
             read_timeout: RequestConfig::new(read_timeout),
+            stall_timeout: RequestConfig::new(stall_timeout),
+            timer,
         }
     }
 }
@@ -119,17 +137,21 @@ impl<S> Layer<S> for ResponseBodyTimeoutLayer {
             inner,
             total_timeout: self.total_timeout,
             read_timeout: self.read_timeout,
+            stall_timeout: self.stall_timeout,
+            timer: self.timer.clone(),
         }
     }
 }
 
-/// Middleware that timeouts the response body of a request with a [`Service`] to a total timeout
-/// and a read timeout.
+/// Middleware that timeouts the response body of a request with a [`Service`] to a total timeout,
+/// a read timeout and a stall timeout.
 #[derive(Clone)]
 pub struct ResponseBodyTimeout<S> {
     inner: S,
     total_timeout: RequestConfig<RequestTotalTimeout>,
     read_timeout: RequestConfig<RequestReadTimeout>,
+    stall_timeout: RequestConfig<RequestBodyStallTimeout>,
+    timer: Timer,
 }
 
 impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ResponseBodyTimeout<S>
@@ -148,10 +170,13 @@ where
     fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
         let total_timeout = self.total_timeout.fetch(req.extensions()).copied();
         let read_timeout = self.read_timeout.fetch(req.extensions()).copied();
+        let stall_timeout = self.stall_timeout.fetch(req.extensions()).copied();
         ResponseBodyTimeoutFuture {
             inner: self.inner.call(req),
             total_timeout,
             read_timeout,
+            stall_timeout,
+            timer: self.timer.clone(),
         }
     }
 }
@@ -0,0 +1,124 @@
+//! Middleware to propagate the W3C trace context of the current span.
+
+use std::task::{Context, Poll};
+
+use http::{HeaderMap, HeaderName, HeaderValue, Request};
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use tower::Layer;
+use tower_service::Service;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::core::ext::SharedHeaders;
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+
+/// Layer to apply [`TraceContextPropagation`] middleware.
+#[derive(Clone)]
+pub struct TraceContextLayer {
+    enabled: bool,
+}
+
+impl TraceContextLayer {
+    /// Create a new trace context layer.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl<S> Layer<S> for TraceContextLayer {
+    type Service = TraceContextPropagation<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceContextPropagation {
+            inner,
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Middleware adding the `traceparent` and `tracestate` headers of the current `tracing` span,
+/// as seen by OpenTelemetry.
+#[derive(Clone)]
+pub struct TraceContextPropagation<S> {
+    inner: S,
+    enabled: bool,
+}
+
+impl<ReqBody, S> Service<Request<ReqBody>> for TraceContextPropagation<S>
+where
+    S: Service<Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    #[inline(always)]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        // A trace context set on the request is left as is.
+        if self.enabled && !SharedHeaders::contains(&req, &TRACEPARENT) {
+            let context = tracing::Span::current().context();
+            inject(req.headers_mut(), context.span().span_context());
+        }
+
+        self.inner.call(req)
+    }
+}
+
+/// Adds the headers of a sampled or unsampled, but valid, span context.
+fn inject(headers: &mut HeaderMap, span_context: &SpanContext) {
+    if !span_context.is_valid() {
+        return;
+    }
+
+    let traceparent = format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    );
+    if let Ok(value) = HeaderValue::from_str(&traceparent) {
+        headers.insert(TRACEPARENT, value);
+    }
+
+    let tracestate = span_context.trace_state().header();
+    if !tracestate.is_empty() {
+        if let Ok(value) = HeaderValue::from_str(&tracestate) {
+            headers.insert(TRACESTATE, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+
+    use super::*;
+
+    #[test]
+    fn injects_valid_span_context() {
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::from_key_value([("vendor", "value")]).unwrap(),
+        );
+
+        let mut headers = HeaderMap::new();
+        inject(&mut headers, &span_context);
+        assert_eq!(
+            headers[TRACEPARENT],
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+        assert_eq!(headers[TRACESTATE], "vendor=value");
+
+        let mut headers = HeaderMap::new();
+        inject(&mut headers, &SpanContext::empty_context());
+        assert!(headers.is_empty());
+    }
+}
@@ -1,21 +1,45 @@
+#[cfg(feature = "cookies")]
+pub use self::session::Session;
 pub use self::{
     body::Body,
-    client::{Client, ClientBuilder},
+    chunked::{Chunk, ChunkedBody},
+    client::{Client, ClientBuilder, ConfigHandle, PoolHandle},
+    conditional::{Conditional, GetIfModified, Validators},
+    duplicates::DuplicateHeaders,
     emulation::{EmulationProvider, EmulationProviderFactory},
-    request::{Request, RequestBuilder},
+    fetch_mode::FetchMode,
+    hints::{Brand, ClientHints, ClientHintsBuilder},
+    priority::Priority,
+    range::FetchRanges,
+    request::{PreparedRequest, Request, RequestBuilder},
     response::Response,
+    transfer::TransferStats,
     upgrade::Upgraded,
+    user_agent::{RotationStrategy, UserAgent},
 };
 
 pub mod body;
+mod chunked;
 #[allow(clippy::module_inception)]
 mod client;
+mod conditional;
+mod duplicates;
 mod emulation;
+mod fetch_mode;
+mod hints;
+mod local;
 pub(crate) mod middleware;
 #[cfg(feature = "multipart")]
 pub mod multipart;
+mod priority;
+pub(crate) mod range;
 pub(crate) mod request;
 mod response;
+#[cfg(feature = "cookies")]
+mod session;
+mod sniff;
+pub(crate) mod transfer;
 mod upgrade;
+mod user_agent;
 #[cfg(feature = "websocket")]
 pub mod websocket;
@@ -1,9 +1,12 @@
-//! multipart/form-data
-#[cfg(feature = "stream")]
-use std::io;
+//! multipart/form-data requests and multipart responses
 #[cfg(feature = "stream")]
 use std::path::Path;
-use std::{borrow::Cow, fmt, pin::Pin};
+use std::{
+    borrow::Cow,
+    fmt,
+    io::{self, Write},
+    pin::Pin,
+};
 
 use bytes::Bytes;
 use futures_util::{Stream, StreamExt, future, stream};
@@ -12,9 +15,12 @@ use percent_encoding::{self, AsciiSet, NON_ALPHANUMERIC};
 #[cfg(feature = "stream")]
 use tokio::fs::File;
 
+pub use self::reader::{BodyPart, Multipart};
 use super::Body;
 use crate::header::HeaderMap;
 
+mod reader;
+
 /// An async multipart/form-data request.
 pub struct Form {
     inner: FormParts<Part>,
@@ -37,9 +43,25 @@ pub(crate) struct FormParts<P> {
 pub(crate) struct PartMetadata {
     mime: Option<Mime>,
     file_name: Option<Cow<'static, str>>,
+    encoding: Option<Encoding>,
     pub(crate) headers: HeaderMap,
 }
 
+/// A content coding used to compress a [`Part`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// The `gzip` coding.
+    Gzip,
+    /// The `deflate` coding, i.e. a zlib stream.
+    Deflate,
+}
+
+enum Encoder {
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+    Deflate(flate2::write::ZlibEncoder<Vec<u8>>),
+}
+
 pub(crate) trait PartProps {
     fn value_len(&self) -> Option<u64>;
     fn metadata(&self) -> &PartMetadata;
@@ -194,10 +216,18 @@ impl Form {
             h.extend_from_slice(b"\r\n\r\n");
             h.into()
         })));
-        // then append form data followed by terminating CRLF
+        // then append form data, compressed if needed, followed by terminating CRLF
+        // (text and bytes parts are compressed upfront and keep a known length)
+        let value = match part.meta.encoding {
+            Some(encoding) if part.body_length.is_none() => {
+                Box::pin(compress_stream(part.value.into_stream(), encoding))
+                    as Pin<Box<dyn Stream<Item = crate::Result<Bytes>> + Send + Sync>>
+            }
+            _ => Box::pin(part.value.into_stream()),
+        };
         boundary
             .chain(header)
-            .chain(part.value.into_stream())
+            .chain(value)
             .chain(stream::once(future::ready(Ok("\r\n".into()))))
     }
 
@@ -320,6 +350,40 @@ impl Part {
         self.with_inner(move |inner| inner.headers(headers))
     }
 
+    /// Compresses the value of the part with the given encoding.
+    ///
+    /// The part gets a matching `Content-Encoding` header. A part made from text or bytes is
+    /// compressed right away and keeps a known length, while a streaming part is compressed
+    /// as it is sent, which makes the length of the whole form unknown.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the value cannot be compressed.
+    pub fn compress(mut self, encoding: Encoding) -> crate::Result<Part> {
+        if self.meta.encoding.is_some() {
+            return Err(crate::Error::builder(
+                "multipart part is already compressed",
+            ));
+        }
+
+        if let Some(bytes) = self.value.as_bytes() {
+            let mut encoder = Encoder::new(encoding);
+            let compressed = encoder
+                .encode(bytes)
+                .and_then(|mut compressed| {
+                    compressed.extend(encoder.finish()?);
+                    Ok(compressed)
+                })
+                .map_err(crate::Error::builder)?;
+            self.body_length = Some(compressed.len() as u64);
+            self.value = Body::from(compressed);
+        } else {
+            self.body_length = None;
+        }
+
+        Ok(self.with_inner(move |inner| inner.encoding(encoding)))
+    }
+
     fn with_inner<F>(self, func: F) -> Self
     where
         F: FnOnce(PartMetadata) -> PartMetadata,
@@ -344,6 +408,9 @@ impl PartProps for Part {
     fn value_len(&self) -> Option<u64> {
         if self.body_length.is_some() {
             self.body_length
+        } else if self.meta.encoding.is_some() {
+            // The compressed length of a stream is unknown.
+            None
         } else {
             self.value.content_length()
         }
@@ -454,10 +521,16 @@ impl PartMetadata {
         PartMetadata {
             mime: None,
             file_name: None,
+            encoding: None,
             headers: HeaderMap::default(),
         }
     }
 
+    pub(crate) fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
     pub(crate) fn mime(mut self, mime: Mime) -> Self {
         self.mime = Some(mime);
         self
@@ -488,6 +561,7 @@ impl PartMetadata {
         debug_struct
             .field("mime", &self.mime)
             .field("file_name", &self.file_name)
+            .field("encoding", &self.encoding)
             .field("headers", &self.headers)
     }
 }
@@ -563,6 +637,11 @@ impl PercentEncoding {
             buf.extend_from_slice(mime.as_ref().as_bytes());
         }
 
+        if let Some(encoding) = field.encoding {
+            buf.extend_from_slice(b"\r\nContent-Encoding: ");
+            buf.extend_from_slice(encoding.as_str().as_bytes());
+        }
+
         for (k, v) in field.headers.iter() {
             buf.extend_from_slice(b"\r\n");
             buf.extend_from_slice(k.as_str().as_bytes());
@@ -583,6 +662,90 @@ impl PercentEncoding {
     }
 }
 
+// ===== impl Encoding =====
+
+impl Encoding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Encoder {
+        let level = flate2::Compression::default();
+        match encoding {
+            Encoding::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(Vec::new(), level)),
+            Encoding::Deflate => {
+                Encoder::Deflate(flate2::write::ZlibEncoder::new(Vec::new(), level))
+            }
+        }
+    }
+
+    /// Compresses a chunk, returning the compressed data produced so far.
+    fn encode(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Gzip(encoder) => {
+                encoder.write_all(chunk)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            Encoder::Deflate(encoder) => {
+                encoder.write_all(chunk)?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// Finishes the compressed stream, returning the remaining compressed data.
+    fn finish(&mut self) -> io::Result<Vec<u8>> {
+        match self {
+            Encoder::Gzip(encoder) => {
+                encoder.try_finish()?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+            Encoder::Deflate(encoder) => {
+                encoder.try_finish()?;
+                Ok(std::mem::take(encoder.get_mut()))
+            }
+        }
+    }
+}
+
+/// Compresses a stream of bytes as it is polled.
+fn compress_stream<S>(
+    stream: S,
+    encoding: Encoding,
+) -> impl Stream<Item = crate::Result<Bytes>> + Send + Sync
+where
+    S: Stream<Item = crate::Result<Bytes>> + Send + Sync + 'static,
+{
+    let state = Some((Box::pin(stream), Encoder::new(encoding)));
+    stream::unfold(state, |state| async move {
+        let (mut stream, mut encoder) = state?;
+        loop {
+            let chunk = match stream.next().await {
+                Some(Ok(chunk)) => encoder.encode(&chunk),
+                Some(Err(err)) => return Some((Err(err), None)),
+                None => {
+                    let last = encoder
+                        .finish()
+                        .map(Bytes::from)
+                        .map_err(crate::Error::body);
+                    return Some((last, None));
+                }
+            };
+
+            match chunk {
+                Ok(chunk) if chunk.is_empty() => continue,
+                Ok(chunk) => return Some((Ok(Bytes::from(chunk)), Some((stream, encoder)))),
+                Err(err) => return Some((Err(crate::Error::body(err)), None)),
+            }
+        }
+    })
+}
+
 fn gen_boundary() -> String {
     use crate::util::fast_random as random;
 
@@ -744,4 +907,60 @@ mod tests {
             &b"Content-Disposition: form-data; name*=utf-8''start%25%27%22%0D%0A%C3%9Fend"[..]
         );
     }
+
+    #[test]
+    fn compressed_part() {
+        use std::io::Read;
+
+        let part = Part::text("hello hello hello hello")
+            .compress(Encoding::Gzip)
+            .unwrap();
+        assert!(part.value_len().is_some());
+
+        let headers = PercentEncoding::PathSegment.encode_headers("key", &part.meta);
+        assert_eq!(
+            std::str::from_utf8(&headers).unwrap(),
+            "Content-Disposition: form-data; name=\"key\"\r\nContent-Encoding: gzip"
+        );
+
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(part.value.as_bytes().unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "hello hello hello hello");
+
+        let err = part.compress(Encoding::Deflate).unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[test]
+    fn compressed_stream_part() {
+        use std::io::Read;
+
+        let stream = futures_util::stream::iter(
+            [b"hello ".as_slice(), b"world"].map(|c| Ok::<_, std::io::Error>(Bytes::from(c))),
+        );
+        let part = Part::stream(Body::stream(stream))
+            .compress(Encoding::Deflate)
+            .unwrap();
+        assert!(part.value_len().is_none());
+
+        let rt = runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("new rt");
+        let compressed = rt
+            .block_on(
+                compress_stream(part.value.into_stream(), Encoding::Deflate)
+                    .map_ok(|c| c.to_vec())
+                    .try_concat(),
+            )
+            .unwrap();
+
+        let mut decoded = String::new();
+        flate2::read::ZlibDecoder::new(&compressed[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, "hello world");
+    }
 }
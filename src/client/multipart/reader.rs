@@ -0,0 +1,324 @@
+//! multipart responses
+
+use bytes::{Buf, Bytes, BytesMut};
+use http::{HeaderMap, HeaderName, HeaderValue, header::CONTENT_TYPE};
+use http_body_util::BodyExt;
+use mime_guess::Mime;
+
+use crate::{Body, Error};
+
+/// The maximum number of headers of a single part.
+const MAX_HEADERS: usize = 32;
+
+/// A streaming reader of a `multipart/*` response body, such as `multipart/mixed` or
+/// `multipart/byteranges`.
+///
+/// Parts are read one after the other with [`Multipart::next_part`], each one borrowing the
+/// reader until it is dropped. The body of a part does not need to be fully read: the rest of it
+/// is skipped when the next part is requested.
+///
+/// Created with [`Response::into_multipart`](crate::Response::into_multipart).
+///
+/// # Example
+///
+/// ```
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut multipart = wreq::Client::new()
+///     .get("http://httpbin.org/range/1024")
+///     .header("range", "bytes=0-9, 20-29")
+///     .send()
+///     .await?
+///     .into_multipart()?;
+///
+/// while let Some(mut part) = multipart.next_part().await? {
+///     println!("part: {:?}", part.headers());
+///     while let Some(chunk) = part.chunk().await? {
+///         println!("chunk: {chunk:?}");
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Multipart {
+    body: Body,
+    buf: BytesMut,
+    /// The delimiter of the parts: `\r\n--` followed by the boundary.
+    delimiter: Vec<u8>,
+    state: State,
+    eof: bool,
+}
+
+/// A part of a [`Multipart`] response body.
+#[derive(Debug)]
+pub struct BodyPart<'a> {
+    multipart: &'a mut Multipart,
+    headers: HeaderMap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the first delimiter.
+    Preamble,
+    /// Right after a delimiter, before the end of its line.
+    Delimiter,
+    /// Inside the body of a part.
+    Body,
+    /// After the close delimiter.
+    Done,
+}
+
+impl Multipart {
+    /// Creates a reader of a multipart body using the given boundary.
+    pub(crate) fn new(body: Body, boundary: &str) -> Multipart {
+        Multipart {
+            body,
+            buf: BytesMut::new(),
+            delimiter: [b"\r\n--", boundary.as_bytes()].concat(),
+            state: State::Preamble,
+            eof: false,
+        }
+    }
+
+    /// Creates a reader of a multipart body, using the boundary of its `Content-Type`.
+    pub(crate) fn from_parts(headers: &HeaderMap, body: Body) -> crate::Result<Multipart> {
+        let mime = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+            .filter(|mime| mime.type_() == "multipart")
+            .ok_or_else(|| Error::decode("response is not a multipart body"))?;
+
+        let boundary = mime
+            .get_param("boundary")
+            .ok_or_else(|| Error::decode("multipart response has no boundary"))?;
+
+        Ok(Multipart::new(body, boundary.as_str()))
+    }
+
+    /// Returns the next part of the body, or `None` once all parts have been read.
+    pub async fn next_part(&mut self) -> crate::Result<Option<BodyPart<'_>>> {
+        loop {
+            match self.state {
+                State::Preamble => self.skip_preamble().await?,
+                State::Body => {
+                    // Skip the rest of the previous part.
+                    while self.read_body().await?.is_some() {}
+                }
+                State::Delimiter => break,
+                State::Done => return Ok(None),
+            }
+        }
+
+        // A close delimiter is followed by `--`, a delimiter by the end of its line.
+        self.fill_to(2).await?;
+        if self.buf.starts_with(b"--") {
+            self.state = State::Done;
+            return Ok(None);
+        }
+
+        let line = self.find(b"\r\n").await?;
+        self.buf.advance(line + 2);
+
+        let headers = loop {
+            let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+            match httparse::parse_headers(&self.buf, &mut headers).map_err(Error::decode)? {
+                httparse::Status::Complete((len, headers)) => {
+                    let headers = to_header_map(headers)?;
+                    self.buf.advance(len);
+                    break headers;
+                }
+                httparse::Status::Partial => {
+                    if !self.fill().await? {
+                        return Err(incomplete());
+                    }
+                }
+            }
+        };
+
+        self.state = State::Body;
+        Ok(Some(BodyPart {
+            multipart: self,
+            headers,
+        }))
+    }
+
+    async fn skip_preamble(&mut self) -> crate::Result<()> {
+        // The first delimiter may start the body without the leading CRLF.
+        let dash_boundary = self.delimiter[2..].to_vec();
+        self.fill_to(dash_boundary.len()).await?;
+        let start = if self.buf.starts_with(&dash_boundary) {
+            dash_boundary.len()
+        } else {
+            let delimiter = self.delimiter.clone();
+            self.find(&delimiter).await? + delimiter.len()
+        };
+
+        self.buf.advance(start);
+        self.state = State::Delimiter;
+        Ok(())
+    }
+
+    /// Reads the next chunk of the body of the current part.
+    async fn read_body(&mut self) -> crate::Result<Option<Bytes>> {
+        if self.state != State::Body {
+            return Ok(None);
+        }
+
+        loop {
+            if let Some(pos) = memmem(&self.buf, &self.delimiter) {
+                let chunk = self.buf.split_to(pos).freeze();
+                self.buf.advance(self.delimiter.len());
+                self.state = State::Delimiter;
+                return Ok((!chunk.is_empty()).then_some(chunk));
+            }
+
+            // Keep what could be the start of a delimiter split across chunks.
+            let safe = self.buf.len().saturating_sub(self.delimiter.len() - 1);
+            if safe > 0 {
+                return Ok(Some(self.buf.split_to(safe).freeze()));
+            }
+
+            if !self.fill().await? {
+                return Err(incomplete());
+            }
+        }
+    }
+
+    /// Returns the position of `needle` in the buffer, reading more of the body as needed.
+    async fn find(&mut self, needle: &[u8]) -> crate::Result<usize> {
+        loop {
+            if let Some(pos) = memmem(&self.buf, needle) {
+                return Ok(pos);
+            }
+            if !self.fill().await? {
+                return Err(incomplete());
+            }
+        }
+    }
+
+    /// Reads the body until the buffer holds at least `len` bytes.
+    async fn fill_to(&mut self, len: usize) -> crate::Result<()> {
+        while self.buf.len() < len {
+            if !self.fill().await? {
+                return Err(incomplete());
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the next data frame of the body into the buffer, returning `false` at the end.
+    async fn fill(&mut self) -> crate::Result<bool> {
+        while !self.eof {
+            match self.body.frame().await {
+                Some(frame) => {
+                    if let Ok(data) = frame?.into_data() {
+                        self.buf.extend_from_slice(&data);
+                        return Ok(true);
+                    }
+                }
+                None => self.eof = true,
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl BodyPart<'_> {
+    /// Returns the headers of this part.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Stream a chunk of the body of this part.
+    ///
+    /// When the body of the part has been exhausted, this will return `None`.
+    pub async fn chunk(&mut self) -> crate::Result<Option<Bytes>> {
+        self.multipart.read_body().await
+    }
+
+    /// Get the full body of this part as `Bytes`.
+    pub async fn bytes(mut self) -> crate::Result<Bytes> {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+        Ok(buf.freeze())
+    }
+}
+
+fn to_header_map(headers: &[httparse::Header<'_>]) -> crate::Result<HeaderMap> {
+    let mut map = HeaderMap::with_capacity(headers.len());
+    for header in headers {
+        let name = HeaderName::from_bytes(header.name.as_bytes()).map_err(Error::decode)?;
+        let value = HeaderValue::from_bytes(header.value).map_err(Error::decode)?;
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+fn memmem(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[inline]
+fn incomplete() -> Error {
+    Error::decode("incomplete multipart body")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multipart(chunks: &[&'static str]) -> Multipart {
+        let stream = futures_util::stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::from_static(chunk.as_bytes())))
+                .collect::<Vec<_>>(),
+        );
+        Multipart::new(Body::stream(stream), "sep")
+    }
+
+    #[tokio::test]
+    async fn read_parts() {
+        let mut multipart = multipart(&[
+            "preamble\r\n--sep\r\nContent-Type: text/plain\r\n",
+            "Content-Range: bytes 0-4/10\r\n\r\nhello\r\n-",
+            "-sep\r\n\r\nworld\r\n--se",
+            "p--\r\nepilogue",
+        ]);
+
+        let part = multipart.next_part().await.unwrap().unwrap();
+        assert_eq!(part.headers()["content-type"], "text/plain");
+        assert_eq!(part.headers()["content-range"], "bytes 0-4/10");
+        assert_eq!(part.bytes().await.unwrap(), "hello");
+
+        let part = multipart.next_part().await.unwrap().unwrap();
+        assert!(part.headers().is_empty());
+        assert_eq!(part.bytes().await.unwrap(), "world");
+
+        assert!(multipart.next_part().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn skip_unread_part() {
+        let mut multipart = multipart(&["--sep\r\n\r\nfirst\r\n--sep\r\n\r\nsecond\r\n--sep--"]);
+
+        multipart.next_part().await.unwrap().unwrap();
+        let part = multipart.next_part().await.unwrap().unwrap();
+        assert_eq!(part.bytes().await.unwrap(), "second");
+        assert!(multipart.next_part().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn incomplete_body() {
+        let mut multipart = multipart(&["--sep\r\n\r\ntruncated"]);
+
+        let part = multipart.next_part().await.unwrap().unwrap();
+        assert!(part.bytes().await.unwrap_err().is_decode());
+    }
+}
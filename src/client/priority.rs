@@ -0,0 +1,82 @@
+use http::{HeaderName, HeaderValue};
+
+pub(crate) const PRIORITY: HeaderName = HeaderName::from_static("priority");
+
+/// The priority of a request, as defined by [RFC 9218].
+///
+/// A priority is sent to the server in the `priority` header, and orders the requests waiting
+/// for a permit when [`ClientBuilder::max_in_flight`](crate::ClientBuilder::max_in_flight)
+/// applies: requests of a lower urgency value are sent first.
+///
+/// [RFC 9218]: https://www.rfc-editor.org/rfc/rfc9218
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Priority {
+    urgency: u8,
+    incremental: bool,
+}
+
+impl Priority {
+    /// The highest urgency, for requests blocking user interaction.
+    pub const HIGHEST: Priority = Priority::new(0);
+
+    /// The default urgency of requests.
+    pub const DEFAULT: Priority = Priority::new(3);
+
+    /// The lowest urgency, for background requests such as prefetches.
+    pub const LOWEST: Priority = Priority::new(7);
+
+    /// Creates a priority with the given urgency, from 0 (highest) to 7 (lowest).
+    ///
+    /// Values above 7 are clamped to 7.
+    pub const fn new(urgency: u8) -> Priority {
+        Priority {
+            urgency: if urgency > 7 { 7 } else { urgency },
+            incremental: false,
+        }
+    }
+
+    /// Sets whether the response can be processed incrementally, such as a progressive image.
+    pub const fn incremental(mut self, incremental: bool) -> Priority {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Returns the urgency, from 0 (highest) to 7 (lowest).
+    #[inline]
+    pub const fn urgency(&self) -> u8 {
+        self.urgency
+    }
+
+    /// Returns whether the response can be processed incrementally.
+    #[inline]
+    pub const fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Returns the value of the `priority` header.
+    pub(crate) fn header_value(&self) -> HeaderValue {
+        let value = if self.incremental {
+            format!("u={}, i", self.urgency)
+        } else {
+            format!("u={}", self.urgency)
+        };
+        HeaderValue::try_from(value).expect("priority is a valid header value")
+    }
+}
+
+impl Default for Priority {
+    fn default() -> Priority {
+        Priority::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Priority;
+
+    #[test]
+    fn header_value() {
+        assert_eq!(Priority::HIGHEST.header_value(), "u=0");
+        assert_eq!(Priority::new(9).incremental(true).header_value(), "u=7, i");
+    }
+}
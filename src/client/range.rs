@@ -0,0 +1,257 @@
+//! Byte range requests
+
+use std::ops::{Bound, RangeBounds};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use http::{
+    StatusCode,
+    header::{ACCEPT_ENCODING, CONTENT_RANGE, HeaderValue, RANGE},
+};
+
+use super::{client::Client, request::RequestBuilder, response::Response};
+use crate::{Error, IntoUrl, Url};
+
+/// A range of bytes of a resource, with an inclusive end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ByteRange {
+    start: u64,
+    end: Option<u64>,
+}
+
+impl ByteRange {
+    /// Converts any range of `u64` into a `ByteRange`, rejecting empty ranges.
+    pub(crate) fn new<R: RangeBounds<u64>>(range: R) -> crate::Result<ByteRange> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => Some(start),
+            Bound::Excluded(&start) => start.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => Some(Some(end)),
+            Bound::Excluded(&end) => end.checked_sub(1).map(Some),
+            Bound::Unbounded => Some(None),
+        };
+
+        match (start, end) {
+            (Some(start), Some(end)) if end.is_none_or(|end| start <= end) => {
+                Ok(ByteRange { start, end })
+            }
+            _ => Err(Error::builder("empty byte range")),
+        }
+    }
+
+    /// Returns the rest of this range after its first `n` bytes, if any.
+    pub(crate) fn skip(&self, n: u64) -> Option<ByteRange> {
+        let start = self.start.checked_add(n)?;
+        match self.end {
+            Some(end) if start > end => None,
+            end => Some(ByteRange { start, end }),
+        }
+    }
+
+    /// Returns the value of the `Range` header requesting this range.
+    pub(crate) fn header_value(&self) -> HeaderValue {
+        let value = match self.end {
+            Some(end) => format!("bytes={}-{}", self.start, end),
+            None => format!("bytes={}-", self.start),
+        };
+        HeaderValue::from_str(&value).expect("byte range is a valid header value")
+    }
+
+    /// Builds a `GET` request for this range of the resource at `url`.
+    pub(crate) fn get(&self, client: &Client, url: Url) -> RequestBuilder {
+        // Ranges apply to the encoded representation, so ask for it unencoded.
+        client
+            .get(url)
+            .header(RANGE, self.header_value())
+            .header(ACCEPT_ENCODING, HeaderValue::from_static("identity"))
+    }
+
+    /// Checks that a response is the `206 Partial Content` of this range, returning its
+    /// `Content-Range`.
+    pub(crate) fn validate(&self, res: &Response) -> crate::Result<ContentRange> {
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::decode(
+                "server did not answer the range request with partial content",
+            )
+            .with_url(res.url().clone()));
+        }
+
+        let content_range = res
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(ContentRange::parse)
+            .ok_or_else(|| {
+                Error::decode("invalid Content-Range in response").with_url(res.url().clone())
+            })?;
+
+        // The range may only be cut short by the end of the resource.
+        let end_matches = match self.end {
+            Some(end) => {
+                content_range.end == end
+                    || (content_range.end < end
+                        && content_range.complete_length == Some(content_range.end + 1))
+            }
+            None => true,
+        };
+        if content_range.start != self.start || !end_matches {
+            return Err(Error::decode(format!(
+                "Content-Range bytes {}-{} does not match the requested range",
+                content_range.start, content_range.end
+            ))
+            .with_url(res.url().clone()));
+        }
+
+        Ok(content_range)
+    }
+}
+
+/// The `Content-Range` of a partial response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ContentRange {
+    pub(crate) start: u64,
+    pub(crate) end: u64,
+    /// The length of the whole resource, if known.
+    pub(crate) complete_length: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parses a `bytes <start>-<end>/<complete-length>` header value.
+    fn parse(value: &str) -> Option<ContentRange> {
+        let range = value.trim().strip_prefix("bytes ")?;
+        let (range, complete_length) = range.split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+        let complete_length = match complete_length.trim() {
+            "*" => None,
+            length => Some(length.parse().ok()?),
+        };
+
+        (start <= end).then_some(ContentRange {
+            start,
+            end,
+            complete_length,
+        })
+    }
+
+    /// Returns the number of bytes in the range.
+    #[inline]
+    pub(crate) fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// A builder for fetching several byte ranges of a resource.
+///
+/// Each range is requested with its own `GET` request carrying a `Range` header. The responses
+/// must be `206 Partial Content` with a `Content-Range` matching the requested range, and their
+/// bodies are concatenated in the order of the ranges.
+///
+/// To construct a `FetchRanges`, use [`Client::fetch_ranges`].
+#[must_use = "FetchRanges does nothing until you 'send' it"]
+pub struct FetchRanges {
+    client: Client,
+    url: crate::Result<Url>,
+    ranges: crate::Result<Vec<ByteRange>>,
+    concurrency: usize,
+}
+
+impl FetchRanges {
+    pub(super) fn new<U, I, R>(client: Client, url: U, ranges: I) -> FetchRanges
+    where
+        U: IntoUrl,
+        I: IntoIterator<Item = R>,
+        R: RangeBounds<u64>,
+    {
+        FetchRanges {
+            client,
+            url: url.into_url(),
+            ranges: ranges.into_iter().map(ByteRange::new).collect(),
+            concurrency: 1,
+        }
+    }
+
+    /// Sets how many ranges are fetched in parallel.
+    ///
+    /// Default is 1, fetching the ranges one after the other.
+    pub fn concurrency(mut self, concurrency: usize) -> FetchRanges {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Fetches all ranges and returns their bodies concatenated.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if any request fails, if the server does not answer with
+    /// `206 Partial Content`, or if a returned range does not match the requested one.
+    pub async fn send(self) -> crate::Result<Bytes> {
+        let url = self.url?;
+        let ranges = self.ranges?;
+        let client = &self.client;
+
+        let parts = stream::iter(ranges)
+            .map(|range| fetch_range(client, url.clone(), range))
+            .buffered(self.concurrency)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut buf = BytesMut::with_capacity(parts.iter().map(Bytes::len).sum());
+        for part in parts {
+            buf.extend_from_slice(&part);
+        }
+        Ok(buf.freeze())
+    }
+}
+
+async fn fetch_range(client: &Client, url: Url, range: ByteRange) -> crate::Result<Bytes> {
+    let res = range.get(client, url).send().await?.error_for_status()?;
+    let len = range.validate(&res)?.len();
+    let url = res.url().clone();
+
+    let bytes = res.bytes().await?;
+    if bytes.len() as u64 != len {
+        return Err(Error::decode("body length does not match the Content-Range").with_url(url));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_range_header() {
+        let value = |range: ByteRange| range.header_value();
+
+        assert_eq!(value(ByteRange::new(0..10).unwrap()), "bytes=0-9");
+        assert_eq!(value(ByteRange::new(5..=5).unwrap()), "bytes=5-5");
+        assert_eq!(value(ByteRange::new(100..).unwrap()), "bytes=100-");
+        assert_eq!(value(ByteRange::new(..).unwrap()), "bytes=0-");
+        assert!(ByteRange::new(5..5).unwrap_err().is_builder());
+        assert!(ByteRange::new(..0).unwrap_err().is_builder());
+    }
+
+    #[test]
+    fn byte_range_skip() {
+        let range = ByteRange::new(10..20).unwrap();
+        assert_eq!(range.skip(4), Some(ByteRange::new(14..20).unwrap()));
+        assert_eq!(range.skip(10), None);
+        assert_eq!(
+            ByteRange::new(10..).unwrap().skip(100),
+            Some(ByteRange::new(110..).unwrap())
+        );
+    }
+
+    #[test]
+    fn content_range() {
+        let parse = |value| ContentRange::parse(value).map(|r| (r.start, r.end, r.complete_length));
+
+        assert_eq!(parse("bytes 0-9/100"), Some((0, 9, Some(100))));
+        assert_eq!(parse("bytes 10-19/*"), Some((10, 19, None)));
+        assert_eq!(parse("bytes */100"), None);
+        assert_eq!(parse("bytes 9-0/100"), None);
+    }
+}
@@ -1,12 +1,16 @@
 use std::{
+    collections::HashMap,
     convert::TryFrom,
     fmt,
     future::Future,
-    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    hash::{DefaultHasher, Hash, Hasher},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    ops::RangeBounds,
+    sync::{Arc, atomic::AtomicBool},
     time::Duration,
 };
 
-use http::{Extensions, Request as HttpRequest, Uri, Version, request::Parts};
+use http::{Extensions, Request as HttpRequest, Uri, Version, request::Parts, uri::Authority};
 use serde::Serialize;
 
 #[cfg(any(
@@ -21,23 +25,46 @@ use super::multipart;
 use super::{
     body::Body,
     client::{Client, Pending},
-    middleware::config::{
-        RequestReadTimeout, RequestRedirectPolicy, RequestSkipDefaultHeaders, RequestTotalTimeout,
+    middleware::{
+        config::{
+            RequestAcceptedHints, RequestBodyStallTimeout, RequestBufferBudget, RequestClientHints,
+            RequestFetchMode, RequestMaxBufferedBytes, RequestPriority, RequestProxySession,
+            RequestReadTimeout, RequestRedirectPolicy, RequestReferrerPolicy, RequestRetryPolicy,
+            RequestRobotsAgent, RequestSigner, RequestSkipDefaultHeaders, RequestTotalTimeout,
+        },
+        hints::AcceptedHints,
     },
+    priority::PRIORITY,
+    range::ByteRange,
     response::Response,
 };
+#[cfg(feature = "cookies")]
+use super::{
+    middleware::config::{RequestCookieStore, RequestSession, SharedCookieStore},
+    session::SessionContext,
+};
 use crate::{
-    EmulationProviderFactory, Error, Method, OriginalHeaders, Proxy, Url,
+    ChunkedBody, ClientHints, EmptyBody, EmulationProviderFactory, Error, FetchMode, Method,
+    OriginalHeaders, Priority, Proxy, RequestTarget, Url, VersionPreference,
+    budget::BufferBudget,
     core::{
         client::{config::TransportConfig, connect::TcpConnectOptions},
         ext::{
-            RequestConfig, RequestEnforcedHttpVersion, RequestOriginalHeaders, RequestProxyMatcher,
-            RequestTcpConnectOptions, RequestTransportConfig,
+            RequestChunkFraming, RequestConfig, RequestConnectTimeout, RequestDnsOverrides,
+            RequestDnsResolver, RequestEmptyBody, RequestEnforcedHttpVersion, RequestHostOverride,
+            RequestOriginalHeaders, RequestProxyMatcher, RequestTargetForm,
+            RequestTcpConnectOptions, RequestTransportConfig, RequestTunnelHeaders,
+            RequestVersionPreference, SharedHeaders,
         },
+        header::OriginalHeaderName,
     },
+    dns::{DynResolver, Resolve},
     header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue},
+    http2::PseudoOrder,
     proxy::Matcher as ProxyMatcher,
-    redirect,
+    redirect::{self, ReferrerPolicy},
+    retry,
+    sign::Signer,
 };
 
 /// A request which can be executed with `Client::execute()`.
@@ -49,6 +76,20 @@ pub struct Request {
     extensions: Extensions,
 }
 
+/// A request prepared to be sent repeatedly with `Client::execute_ref()`.
+///
+/// Made with [`Request::prepared()`], it is meant for the requests sent over and over, such as
+/// those of a polling loop: its URL is parsed and validated once, and each send shares its
+/// body and its headers rather than copying them. The client writes its own headers alongside
+/// the shared ones. The extensions are still cloned for each send, as the client records the
+/// state of the request in them.
+#[derive(Debug)]
+pub struct PreparedRequest {
+    request: Request,
+    headers: Arc<HeaderMap>,
+    uri: Uri,
+}
+
 /// A builder to construct the properties of a `Request`.
 ///
 /// To construct a `RequestBuilder`, refer to the `Client` documentation.
@@ -119,6 +160,48 @@ impl Request {
         RequestConfig::<RequestRedirectPolicy>::get_mut(&mut self.extensions)
     }
 
+    /// Get a mutable reference to the retry policy.
+    #[inline(always)]
+    pub fn retry_mut(&mut self) -> &mut Option<retry::Policy> {
+        RequestConfig::<RequestRetryPolicy>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the request signer.
+    #[inline(always)]
+    pub fn signer_mut(&mut self) -> &mut Option<Arc<dyn Signer>> {
+        RequestConfig::<RequestSigner>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the buffering budget.
+    #[inline(always)]
+    pub fn buffer_budget_mut(&mut self) -> &mut Option<BufferBudget> {
+        RequestConfig::<RequestBufferBudget>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the maximum number of bytes buffered from the response body.
+    #[inline(always)]
+    pub fn max_buffered_bytes_mut(&mut self) -> &mut Option<usize> {
+        RequestConfig::<RequestMaxBufferedBytes>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the priority.
+    #[inline(always)]
+    pub fn priority_mut(&mut self) -> &mut Option<Priority> {
+        RequestConfig::<RequestPriority>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the fetch mode.
+    #[inline(always)]
+    pub(crate) fn fetch_mode_mut(&mut self) -> &mut Option<FetchMode> {
+        RequestConfig::<RequestFetchMode>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the referrer policy.
+    #[inline(always)]
+    pub fn referrer_policy_mut(&mut self) -> &mut Option<ReferrerPolicy> {
+        RequestConfig::<RequestReferrerPolicy>::get_mut(&mut self.extensions)
+    }
+
     /// Get the body.
     #[inline(always)]
     pub fn body(&self) -> Option<&Body> {
@@ -143,6 +226,12 @@ impl Request {
         RequestConfig::<RequestEnforcedHttpVersion>::get_mut(&mut self.extensions)
     }
 
+    /// Get a mutable reference to the HTTP versions preferred in order.
+    #[inline(always)]
+    pub(crate) fn version_preference_mut(&mut self) -> &mut Option<VersionPreference> {
+        RequestConfig::<RequestVersionPreference>::get_mut(&mut self.extensions)
+    }
+
     /// Get a mutable reference to the timeout.
     #[inline(always)]
     pub fn timeout_mut(&mut self) -> &mut Option<Duration> {
@@ -155,6 +244,62 @@ impl Request {
         RequestConfig::<RequestReadTimeout>::get_mut(&mut self.extensions)
     }
 
+    /// Get a mutable reference to the body stall timeout.
+    #[inline(always)]
+    pub fn body_stall_timeout_mut(&mut self) -> &mut Option<Duration> {
+        RequestConfig::<RequestBodyStallTimeout>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the connect timeout.
+    #[inline(always)]
+    pub fn connect_timeout_mut(&mut self) -> &mut Option<Duration> {
+        RequestConfig::<RequestConnectTimeout>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the host sent in place of the URL host.
+    #[inline(always)]
+    pub(crate) fn host_override_mut(&mut self) -> &mut Option<Authority> {
+        RequestConfig::<RequestHostOverride>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the form of the HTTP/1 request-target.
+    #[inline(always)]
+    pub(crate) fn request_target_mut(&mut self) -> &mut Option<RequestTarget> {
+        RequestConfig::<RequestTargetForm>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the headers sent when the request has no body.
+    #[inline(always)]
+    pub(crate) fn empty_body_mut(&mut self) -> &mut Option<EmptyBody> {
+        RequestConfig::<RequestEmptyBody>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the flag of a body framing its own chunks.
+    #[inline(always)]
+    pub(crate) fn chunk_framing_mut(&mut self) -> &mut Option<Arc<AtomicBool>> {
+        RequestConfig::<RequestChunkFraming>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the DNS overrides of this request.
+    #[inline(always)]
+    pub(crate) fn dns_overrides_mut(
+        &mut self,
+    ) -> &mut Option<Arc<HashMap<String, Vec<SocketAddr>>>> {
+        RequestConfig::<RequestDnsOverrides>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the DNS resolver of this request.
+    #[inline(always)]
+    pub(crate) fn dns_resolver_mut(&mut self) -> &mut Option<DynResolver> {
+        RequestConfig::<RequestDnsResolver>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the extra headers of the proxy `CONNECT` request.
+    #[inline(always)]
+    pub fn tunnel_headers_mut(&mut self) -> &mut Option<HeaderMap> {
+        RequestConfig::<RequestTunnelHeaders>::get_mut(&mut self.extensions)
+    }
+
     /// Get a mutable reference to the tcp connect options.
     #[inline(always)]
     pub(crate) fn tcp_connect_options_mut(&mut self) -> &mut Option<TcpConnectOptions> {
@@ -167,6 +312,18 @@ impl Request {
         RequestConfig::<RequestProxyMatcher>::get_mut(&mut self.extensions)
     }
 
+    /// Get a mutable reference to the proxy session key.
+    #[inline(always)]
+    pub(crate) fn proxy_session_mut(&mut self) -> &mut Option<u64> {
+        RequestConfig::<RequestProxySession>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the user agent checked against robots.txt.
+    #[inline(always)]
+    pub(crate) fn robots_agent_mut(&mut self) -> &mut Option<String> {
+        RequestConfig::<RequestRobotsAgent>::get_mut(&mut self.extensions)
+    }
+
     /// Get the accepts encoding.
     #[cfg(any(
         feature = "gzip",
@@ -185,6 +342,32 @@ impl Request {
         RequestConfig::<RequestSkipDefaultHeaders>::get_mut(&mut self.extensions)
     }
 
+    /// Get a mutable reference to the client hints.
+    #[inline(always)]
+    pub(crate) fn client_hints_mut(&mut self) -> &mut Option<ClientHints> {
+        RequestConfig::<RequestClientHints>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the cookie store.
+    #[cfg(feature = "cookies")]
+    #[inline(always)]
+    pub(crate) fn cookie_store_mut(&mut self) -> &mut Option<SharedCookieStore> {
+        RequestConfig::<RequestCookieStore>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the `Accept-CH` cache.
+    #[inline(always)]
+    pub(crate) fn accepted_hints_mut(&mut self) -> &mut Option<AcceptedHints> {
+        RequestConfig::<RequestAcceptedHints>::get_mut(&mut self.extensions)
+    }
+
+    /// Get a mutable reference to the session.
+    #[cfg(feature = "cookies")]
+    #[inline(always)]
+    pub(crate) fn session_mut(&mut self) -> &mut Option<SessionContext> {
+        RequestConfig::<RequestSession>::get_mut(&mut self.extensions)
+    }
+
     #[inline(always)]
     pub(crate) fn transport_config_mut(&mut self) -> &mut Option<TransportConfig> {
         RequestConfig::<RequestTransportConfig>::get_mut(&mut self.extensions)
@@ -217,6 +400,87 @@ impl Request {
         req.body = body;
         Some(req)
     }
+
+    /// Prepares the request to be sent repeatedly with `Client::execute_ref()`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the URL is not a valid URI, or if the body is a stream, which can
+    /// only be sent once.
+    pub fn prepared(mut self) -> crate::Result<PreparedRequest> {
+        if self
+            .body
+            .as_ref()
+            .is_some_and(|body| body.try_clone().is_none())
+        {
+            return Err(Error::builder("a streaming body can't be prepared").with_url(self.url));
+        }
+
+        match Uri::try_from(self.url.as_str()) {
+            Ok(uri) => Ok(PreparedRequest {
+                headers: Arc::new(std::mem::take(&mut self.headers)),
+                request: self,
+                uri,
+            }),
+            Err(err) => Err(Error::builder(err).with_url(self.url)),
+        }
+    }
+}
+
+impl PreparedRequest {
+    /// Get the method.
+    #[inline]
+    pub fn method(&self) -> &Method {
+        &self.request.method
+    }
+
+    /// Get the url.
+    #[inline]
+    pub fn url(&self) -> &Url {
+        &self.request.url
+    }
+
+    /// Get the headers.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Get the body.
+    #[inline]
+    pub fn body(&self) -> Option<&Body> {
+        self.request.body.as_ref()
+    }
+
+    /// Returns a copy of the request, to be modified or sent with `Client::execute()`.
+    pub fn to_request(&self) -> Request {
+        let mut req = self
+            .request
+            .try_clone()
+            .expect("prepared request has a cloneable body");
+        req.headers = (*self.headers).clone();
+        req
+    }
+
+    /// Returns the request to send, without parsing its URL again.
+    pub(crate) fn to_http(&self) -> (Url, HttpRequest<Body>) {
+        let body = match self.request.body {
+            Some(ref body) => body
+                .try_clone()
+                .expect("prepared request has a cloneable body"),
+            None => Body::empty(),
+        };
+
+        let mut req = HttpRequest::new(body);
+        *req.method_mut() = self.request.method.clone();
+        *req.uri_mut() = self.uri.clone();
+        if let Some(version) = self.request.version() {
+            *req.version_mut() = *version;
+        }
+        *req.extensions_mut() = self.request.extensions.clone();
+        SharedHeaders::push(&mut req, self.headers.clone());
+        (self.request.url.clone(), req)
+    }
 }
 
 impl RequestBuilder {
@@ -340,6 +604,73 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the order in which headers are sent for this request.
+    ///
+    /// This overrides the client-level `original_headers` for just this request. Headers that are
+    /// not listed are sent after the listed ones. The spelling of each name is also preserved
+    /// for HTTP/1 requests.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # async fn run() -> wreq::Result<()> {
+    /// let client = wreq::Client::new();
+    /// let res = client
+    ///     .get("https://www.rust-lang.org")
+    ///     .header_order(["Accept", "User-Agent", "Accept-Language", "Cookie"])
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn header_order<I>(self, order: I) -> RequestBuilder
+    where
+        I: IntoIterator,
+        I::Item: TryInto<OriginalHeaderName>,
+    {
+        let mut original_headers = OriginalHeaders::new();
+        original_headers.extend(order);
+        self.original_headers(original_headers)
+    }
+
+    /// Set the HTTP/2 pseudo-header order for this request.
+    ///
+    /// This overrides the order configured through the client's HTTP/2 configuration. Since the
+    /// pseudo-header order is applied per connection, requests with a different order are not
+    /// sent over connections pooled with another order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use wreq::http2::{PseudoId, PseudoOrder};
+    ///
+    /// # async fn run() -> wreq::Result<()> {
+    /// let order = PseudoOrder::builder()
+    ///     .extend([
+    ///         PseudoId::Method,
+    ///         PseudoId::Authority,
+    ///         PseudoId::Scheme,
+    ///         PseudoId::Path,
+    ///     ])
+    ///     .build();
+    ///
+    /// let client = wreq::Client::new();
+    /// let res = client
+    ///     .get("https://www.rust-lang.org")
+    ///     .headers_pseudo_order(order)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn headers_pseudo_order(mut self, order: PseudoOrder) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let transport_config = req.transport_config_mut().get_or_insert_default();
+            transport_config.set_headers_pseudo_order(order);
+        }
+        self
+    }
+
     /// Set skip client default headers for this request.
     pub fn default_headers(mut self, skip: bool) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
@@ -348,6 +679,15 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the `User-Agent` header of this request, replacing the one of the client or session.
+    pub fn user_agent<V>(self, value: V) -> RequestBuilder
+    where
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        self.header_operation(crate::header::USER_AGENT, value, false, true, false)
+    }
+
     /// Enable HTTP authentication.
     pub fn auth<V>(self, value: V) -> RequestBuilder
     where
@@ -402,14 +742,74 @@ impl RequestBuilder {
         )
     }
 
+    /// Request a range of bytes of the resource with a `Range` header.
+    ///
+    /// The range uses Rust syntax, so `0..1024` requests the first 1024 bytes, and `1024..`
+    /// everything after them.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the range is empty.
+    pub fn range<R: RangeBounds<u64>>(mut self, range: R) -> RequestBuilder {
+        match ByteRange::new(range) {
+            Ok(range) => {
+                if let Ok(ref mut req) = self.request {
+                    req.headers_mut()
+                        .insert(crate::header::RANGE, range.header_value());
+                }
+            }
+            Err(err) => self.request = Err(err),
+        }
+        self
+    }
+
     /// Set the request body.
     pub fn body<T: Into<Body>>(mut self, body: T) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
+            *req.chunk_framing_mut() = None;
             *req.body_mut() = Some(body.into());
         }
         self
     }
 
+    /// Set the body-related headers sent when the request has no body.
+    ///
+    /// This tells apart a `GET` or `DELETE` sent with `Content-Length: 0`, with an empty
+    /// chunked body, or with no body-related header at all.
+    ///
+    /// ```
+    /// # use wreq::Error;
+    /// use wreq::EmptyBody;
+    ///
+    /// # async fn run() -> Result<(), Error> {
+    /// let res = wreq::Client::new()
+    ///     .delete("http://httpbin.org/delete")
+    ///     .empty_body(EmptyBody::ContentLength)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn empty_body(mut self, empty_body: EmptyBody) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.empty_body_mut() = Some(empty_body);
+        }
+        self
+    }
+
+    /// Set a request body streamed in chunks carrying extensions, followed by trailers.
+    ///
+    /// Over HTTP/1.1, the request is sent with `Transfer-Encoding: chunked`, in place of any
+    /// `Content-Length`, and each [`Chunk`](crate::Chunk) is sent as one chunk of the body. See
+    /// [`ChunkedBody`] for an example.
+    pub fn chunked_body(mut self, body: ChunkedBody) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.chunk_framing_mut() = Some(body.framing());
+            *req.body_mut() = Some(Body::wrap(body));
+        }
+        self
+    }
+
     /// Enables a request timeout.
     ///
     /// The timeout is applied from when the request starts connecting until the
@@ -434,6 +834,136 @@ impl RequestBuilder {
         self
     }
 
+    /// Enables a body stall timeout.
+    ///
+    /// The response is aborted when no body bytes arrive for the given duration while the body
+    /// is being read. It affects only this request and overrides the stall timeout configured
+    /// using `ClientBuilder::body_stall_timeout()`.
+    pub fn body_stall_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.body_stall_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Enables a connect timeout.
+    ///
+    /// The connect timeout is applied when a new connection has to be established for this
+    /// request, and does not apply to a connection reused from the pool. It affects only this
+    /// request and overrides the connect timeout configured using
+    /// `ClientBuilder::connect_timeout()`, except that the latter still bounds the connection
+    /// when connector layers are configured.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.connect_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Override the host sent to the server, in the `Host` header of HTTP/1 and the
+    /// `:authority` of HTTP/2, while still connecting to the host of the URL.
+    ///
+    /// DNS resolution and TLS SNI keep using the URL host, which allows testing the virtual
+    /// hosts of a server, or domain fronting. Connections sending an overridden host are only
+    /// reused by requests overriding the same host, and a redirect to another host drops the
+    /// override.
+    ///
+    /// The client must allow overrides with
+    /// [`ClientBuilder::allow_host_override`](crate::ClientBuilder::allow_host_override),
+    /// otherwise sending the request fails.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `host` isn't a `host[:port]` authority.
+    pub fn host_header(mut self, host: &str) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            match Authority::try_from(host) {
+                Ok(authority) if !authority.as_str().contains('@') => {
+                    *req.host_override_mut() = Some(authority);
+                }
+                Ok(_) => {
+                    self.request = Err(Error::builder("host header must not contain userinfo"))
+                }
+                Err(err) => self.request = Err(Error::builder(err)),
+            }
+        }
+        self
+    }
+
+    /// Set the form of the request-target sent in the request line, when the request goes out
+    /// on an HTTP/1 connection.
+    ///
+    /// This allows testing how proxies and servers handle each form, and sending a server-wide
+    /// `OPTIONS *` request with [`RequestTarget::Asterisk`]. HTTP/2 requests are unaffected.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the asterisk-form is set on another method than `OPTIONS`.
+    pub fn request_target(mut self, target: RequestTarget) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            if target == RequestTarget::Asterisk && req.method() != Method::OPTIONS {
+                self.request = Err(Error::builder(
+                    "asterisk-form request-target is only valid for OPTIONS",
+                ));
+            } else {
+                *req.request_target_mut() = Some(target);
+            }
+        }
+        self
+    }
+
+    /// Override DNS resolution of a domain to a particular IP address for this request.
+    ///
+    /// The override applies to every hop of a redirect chain: a redirect to the same domain
+    /// keeps using the pinned address, while a redirect to another domain resolves it normally,
+    /// and is marked as unpinned in the [`redirect::History`] of the response. Connections to
+    /// pinned addresses are only reused by requests pinning the same addresses.
+    ///
+    /// As with [`ClientBuilder::resolve`](crate::ClientBuilder::resolve), the port of the
+    /// address is ignored in favor of the port of the URL.
+    pub fn resolve(self, domain: &str, addr: SocketAddr) -> RequestBuilder {
+        self.resolve_to_addrs(domain, &[addr])
+    }
+
+    /// Override DNS resolution of a domain to particular IP addresses for this request.
+    ///
+    /// See [`RequestBuilder::resolve`].
+    pub fn resolve_to_addrs(mut self, domain: &str, addrs: &[SocketAddr]) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            Arc::make_mut(req.dns_overrides_mut().get_or_insert_default())
+                .insert(domain.to_ascii_lowercase(), addrs.to_vec());
+        }
+        self
+    }
+
+    /// Resolves the hosts of this request with `resolver`, in place of the resolver of the
+    /// client.
+    ///
+    /// It replaces the resolver set with
+    /// [`ClientBuilder::dns_resolver`](crate::ClientBuilder::dns_resolver), along with the
+    /// overrides set with [`ClientBuilder::resolve`](crate::ClientBuilder::resolve), for every
+    /// hop of a redirect chain. The overrides set with [`RequestBuilder::resolve`] still apply.
+    /// Connections established through a resolver are only reused by requests resolving with
+    /// the same one.
+    pub fn dns_resolver<R: Resolve + 'static>(mut self, resolver: Arc<R>) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.dns_resolver_mut() = Some(DynResolver::new(resolver));
+        }
+        self
+    }
+
+    /// Adds headers to the `CONNECT` request opening a tunnel through a proxy.
+    ///
+    /// They take precedence over the headers configured on the [`Proxy`], and are only sent
+    /// when a new tunnel is opened. Connections tunneled with different headers are not
+    /// shared between requests.
+    pub fn tunnel_headers(mut self, headers: HeaderMap) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.tunnel_headers_mut() = Some(headers);
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -511,10 +1041,48 @@ impl RequestBuilder {
         self
     }
 
-    /// Set HTTP version
-    pub fn version(mut self, version: Version) -> RequestBuilder {
+    /// Set HTTP version, or the versions to try in order of preference.
+    ///
+    /// A single version is enforced, and the request fails if the connection can't use it.
+    /// Several versions are all offered with ALPN over TLS, so the request falls back to a later
+    /// one when the server doesn't support the first. The version used is
+    /// [`Response::version`](crate::Response::version).
+    ///
+    /// ```
+    /// # use wreq::Error;
+    /// use wreq::Version;
+    ///
+    /// # async fn run() -> Result<(), Error> {
+    /// let res = wreq::Client::new()
+    ///     .get("https://hyper.rs")
+    ///     .version([Version::HTTP_2, Version::HTTP_11])
+    ///     .send()
+    ///     .await?;
+    /// println!("negotiated {:?}", res.version());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if no version is given, or if HTTP/3 is among several versions.
+    pub fn version(mut self, version: impl Into<VersionPreference>) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
-            *req.version_mut() = Some(version);
+            let preference = version.into();
+            match *preference.versions() {
+                [] => self.request = Err(Error::builder("no HTTP version to prefer")),
+                [version] => {
+                    *req.version_mut() = Some(version);
+                    *req.version_preference_mut() = None;
+                }
+                ref versions if versions.contains(&Version::HTTP_3) => {
+                    self.request = Err(Error::builder("HTTP/3 can't be negotiated with ALPN"));
+                }
+                _ => {
+                    *req.version_mut() = None;
+                    *req.version_preference_mut() = Some(preference);
+                }
+            }
         }
         self
     }
@@ -527,6 +1095,92 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the retry policy for this request.
+    ///
+    /// This overrides the retry policy of the client.
+    pub fn retry(mut self, policy: retry::Policy) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.retry_mut() = Some(policy);
+        }
+        self
+    }
+
+    /// Disable retries for this request.
+    ///
+    /// This is a shorthand for `retry(retry::Policy::none())`.
+    pub fn no_retry(self) -> RequestBuilder {
+        self.retry(retry::Policy::none())
+    }
+
+    /// Sign this request with the given signer, overriding the client's signer.
+    ///
+    /// See the [`sign`](crate::sign) module for details.
+    pub fn signer<S: Signer + 'static>(mut self, signer: S) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.signer_mut() = Some(Arc::new(signer));
+        }
+        self
+    }
+
+    /// Buffer the response body of this request within the given budget, overriding the
+    /// client's budget.
+    ///
+    /// See the [`budget`](crate::budget) module for details.
+    pub fn buffer_budget(mut self, budget: BufferBudget) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.buffer_budget_mut() = Some(budget);
+        }
+        self
+    }
+
+    /// Limit the number of bytes buffered from the response body by `bytes()`, `text()` or
+    /// `json()`, which fail once the body exceeds it.
+    ///
+    /// The limit applies to the decompressed body. See the [`budget`](crate::budget) module for
+    /// details.
+    pub fn max_buffered_bytes(mut self, max: usize) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.max_buffered_bytes_mut() = Some(max);
+        }
+        self
+    }
+
+    /// Set the priority of this request.
+    ///
+    /// The priority is sent in the `priority` header, unless one is already set, and orders the
+    /// waiting requests when the client limits the requests in flight. See [`Priority`].
+    pub fn priority(mut self, priority: Priority) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.headers_mut()
+                .entry(PRIORITY)
+                .or_insert_with(|| priority.header_value());
+            *req.priority_mut() = Some(priority);
+        }
+        self
+    }
+
+    /// Send this request with the headers a browser sends for the given kind of request.
+    ///
+    /// The `sec-fetch-*`, `accept` and `priority` headers match the browser of the `User-Agent`
+    /// set by the emulation. See [`FetchMode`].
+    pub fn fetch_mode(mut self, mode: FetchMode) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.fetch_mode_mut() = Some(mode);
+        }
+        self
+    }
+
+    /// Set the referrer policy used when following redirects for this request.
+    ///
+    /// This is useful to emulate navigation-style flows, where browsers apply
+    /// [`ReferrerPolicy::StrictOriginWhenCrossOrigin`] unless the page says otherwise.
+    pub fn referrer_policy(mut self, policy: ReferrerPolicy) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.referrer_policy_mut() = Some(policy);
+        }
+        self
+    }
+
     /// Sets if this request will announce that it accepts gzip encoding.
     #[cfg(feature = "gzip")]
     pub fn gzip(mut self, gzip: bool) -> RequestBuilder {
@@ -593,6 +1247,52 @@ impl RequestBuilder {
         self
     }
 
+    /// Pins this request to a proxy chosen by a session key.
+    ///
+    /// When several proxies of the client match the request, the one used is derived from
+    /// `key` instead of being the first match, so requests sharing a key go through the same
+    /// proxy for as long as it stays configured. Requests without a key use the first match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn run() -> wreq::Result<()> {
+    /// let client = wreq::Client::builder()
+    ///     .proxy(wreq::Proxy::all("http://proxy-a.local:8080")?)
+    ///     .proxy(wreq::Proxy::all("http://proxy-b.local:8080")?)
+    ///     .build()?;
+    ///
+    /// let resp = client
+    ///     .get("https://hyper.rs")
+    ///     .proxy_session("user-42")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn proxy_session<K: Hash>(mut self, key: K) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            let mut hasher = DefaultHasher::new();
+            key.hash(&mut hasher);
+            *req.proxy_session_mut() = Some(hasher.finish());
+        }
+        self
+    }
+
+    /// Checks this request, and the redirects it follows, against robots.txt for `agent`.
+    ///
+    /// The request fails without being sent if the robots.txt of its origin disallows it for
+    /// the product token of `agent`, see [`Error::is_robots_disallowed`]. The `User-Agent`
+    /// header is left untouched.
+    ///
+    /// See the [`robots`](crate::robots) module for details.
+    pub fn respect_robots<A: Into<String>>(mut self, agent: A) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.robots_agent_mut() = Some(agent.into());
+        }
+        self
+    }
+
     /// Set the local address for this request.
     pub fn local_address<V>(mut self, local_address: V) -> RequestBuilder
     where
@@ -660,6 +1360,10 @@ impl RequestBuilder {
             transport_config.set_http2_config(emulation.http2_config);
             transport_config.set_tls_config(emulation.tls_config);
 
+            if let Some(client_hints) = emulation.client_hints {
+                *req.client_hints_mut() = Some(client_hints);
+            }
+
             if let Some(default_headers) = emulation.default_headers {
                 self = self.headers(default_headers);
             }
@@ -1,6 +1,6 @@
 use std::{fmt, net::SocketAddr};
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 #[cfg(feature = "charset")]
 use encoding_rs::{Encoding, UTF_8};
 use http::{HeaderMap, StatusCode, Version};
@@ -8,14 +8,20 @@ use http::{HeaderMap, StatusCode, Version};
 use mime::Mime;
 #[cfg(feature = "json")]
 use serde::de::DeserializeOwned;
+use tokio::io::AsyncWrite;
 use url::Url;
 
-use super::body::{Body, ResponseBody};
+use super::{
+    body::{Body, ResponseBody},
+    transfer::{TransferCounter, TransferStats},
+};
 #[cfg(feature = "cookies")]
 use crate::cookie;
 use crate::{
     Error, Upgraded,
+    budget::{BufferLimits, Reservation},
     core::{client::connect::HttpInfo, ext::ReasonPhrase},
+    digest::{Digest, DigestAlgorithm, Hasher},
 };
 
 /// A Response to a submitted `Request`.
@@ -95,6 +101,10 @@ impl Response {
     }
 
     /// Get the remote address used to get this `Response`.
+    ///
+    /// This is the peer of the underlying TCP connection. When the request went through a
+    /// proxy, including a tunnel opened with `CONNECT` or a SOCKS proxy, it is the address of
+    /// the proxy rather than the one of the origin server.
     pub fn remote_addr(&self) -> Option<SocketAddr> {
         self.res
             .extensions()
@@ -102,6 +112,28 @@ impl Response {
             .map(|info| info.remote_addr())
     }
 
+    /// Get the local address used to get this `Response`.
+    ///
+    /// This is the local end of the underlying TCP connection.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.res
+            .extensions()
+            .get::<HttpInfo>()
+            .map(|info| info.local_addr())
+    }
+
+    /// Returns the bytes transferred by the request so far.
+    ///
+    /// It includes the redirects and retries the request went through, and grows as the body
+    /// is read. The stats are empty for responses not sent by a [`Client`](crate::Client).
+    pub fn transfer_stats(&self) -> TransferStats {
+        self.res
+            .extensions()
+            .get::<TransferCounter>()
+            .map(TransferCounter::stats)
+            .unwrap_or_default()
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &http::Extensions {
         self.res.extensions()
@@ -151,7 +183,7 @@ impl Response {
 
         #[cfg(not(feature = "charset"))]
         {
-            let full = self.bytes().await?;
+            let (full, _reservation) = self.buffered().await?;
             let text = String::from_utf8_lossy(&full);
             Ok(text.into_owned())
         }
@@ -203,7 +235,7 @@ impl Response {
             .unwrap_or(default_encoding);
         let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
 
-        let full = self.bytes().await?;
+        let (full, _reservation) = self.buffered().await?;
 
         let (text, _, _) = encoding.decode(&full);
         Ok(text.into_owned())
@@ -255,11 +287,94 @@ impl Response {
     #[cfg(feature = "json")]
     #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
-        let full = self.bytes().await?;
+        let (full, _reservation) = self.buffered().await?;
 
         serde_json::from_slice(&full).map_err(Error::decode)
     }
 
+    /// Deserialize the response body as JSON, to `T` for a success and to `E` for an error.
+    ///
+    /// A response with a `2xx` status has its body deserialized to `T`. Any other status has
+    /// its body deserialized to the error type `E` of the API, returned as
+    /// [`ApiError::Status`](crate::ApiError::Status). An error body that isn't an `E`, such as
+    /// the HTML page of a gateway, is reported as a status error, as with
+    /// [`Response::error_for_status`].
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Ip {
+    ///     origin: String,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Problem {
+    ///     detail: String,
+    /// }
+    ///
+    /// # async fn run() -> Result<(), wreq::ApiError<Problem>> {
+    /// match wreq::Client::new()
+    ///     .get("http://httpbin.org/ip")
+    ///     .send()
+    ///     .await?
+    ///     .json_or_error::<Ip, Problem>()
+    ///     .await
+    /// {
+    ///     Ok(ip) => println!("ip: {}", ip.origin),
+    ///     Err(wreq::ApiError::Status { status, error }) => println!("{status}: {}", error.detail),
+    ///     Err(err) => return Err(err),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`ApiError::Request`](crate::ApiError::Request) if the body
+    /// couldn't be read, or if a success body couldn't be deserialized to `T`.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub async fn json_or_error<T, E>(self) -> Result<T, crate::ApiError<E>>
+    where
+        T: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self.json().await?);
+        }
+
+        let url = self.url().clone();
+        let reason = self.extensions().get::<ReasonPhrase>().cloned();
+        let (full, _reservation) = self.buffered().await?;
+        match serde_json::from_slice(&full) {
+            Ok(error) => Err(crate::ApiError::Status { status, error }),
+            Err(_) => Err(Error::status_code(url, status, reason).into()),
+        }
+    }
+
+    /// Deserialize the response body as a stream of JSON values, as the body arrives.
+    ///
+    /// The elements of a JSON array body, or the values of a newline-delimited JSON body, are
+    /// yielded as soon as each of them is received, without buffering the whole body first.
+    /// See [`JsonStream`](crate::JsonStream) for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json_stream<T: DeserializeOwned>(self) -> crate::JsonStream<T> {
+        self.decompress();
+        crate::JsonStream::new(self.res.into_body(), *self.url)
+    }
+
     /// Get the full response body as `Bytes`.
     ///
     /// # Example
@@ -278,11 +393,222 @@ impl Response {
     /// # }
     /// ```
     pub async fn bytes(self) -> crate::Result<Bytes> {
+        self.buffered().await.map(|(full, _)| full)
+    }
+
+    async fn collect(self) -> crate::Result<Bytes> {
+        self.collect_reserved().await.map(|(full, _)| full)
+    }
+
+    /// Collects the decompressed body, along with the bytes it reserves from the buffering
+    /// budget until it is dropped.
+    async fn buffered(self) -> crate::Result<(Bytes, Option<Reservation>)> {
+        self.decompress();
+        self.collect_reserved().await
+    }
+
+    async fn collect_reserved(self) -> crate::Result<(Bytes, Option<Reservation>)> {
         use http_body_util::BodyExt;
 
-        BodyExt::collect(self.res.into_body())
-            .await
-            .map(|buf| buf.to_bytes())
+        let reservation = self
+            .res
+            .extensions()
+            .get::<BufferLimits>()
+            .map(BufferLimits::reservation);
+        let Some(mut reservation) = reservation else {
+            return BodyExt::collect(self.res.into_body())
+                .await
+                .map(|buf| (buf.to_bytes(), None));
+        };
+
+        let exceeded = |err| Error::body(err).with_url(Url::clone(&self.url));
+        if let Some(len) = self.content_length() {
+            let len = usize::try_from(len).unwrap_or(usize::MAX);
+            reservation.reserve_to(len).await.map_err(exceeded)?;
+        }
+
+        let mut body = self.res.into_body();
+        let mut buf = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame?.into_data() {
+                reservation
+                    .reserve_to(buf.len() + data.len())
+                    .await
+                    .map_err(exceeded)?;
+                buf.extend_from_slice(&data);
+            }
+        }
+        Ok((buf.freeze(), Some(reservation)))
+    }
+
+    /// Get the full response body as `Bytes` along with its digest.
+    ///
+    /// The body is hashed with `algorithm` while it is read, so verifying a download doesn't
+    /// require a second pass over the data. The digest covers the body as returned, after
+    /// any decompression.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wreq::digest::DigestAlgorithm;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (bytes, digest) = wreq::Client::new()
+    ///     .get("http://httpbin.org/ip")
+    ///     .send()
+    ///     .await?
+    ///     .bytes_with_digest(DigestAlgorithm::Sha256)
+    ///     .await?;
+    ///
+    /// println!("bytes: {bytes:?}, sha-256: {}", digest.to_hex());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes_with_digest(
+        self,
+        algorithm: DigestAlgorithm,
+    ) -> crate::Result<(Bytes, Digest)> {
+        use http_body_util::BodyExt;
+
+        self.decompress();
+        let mut body = self.res.into_body();
+        let mut hasher = Hasher::new(algorithm);
+        let mut buf = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            if let Ok(data) = frame?.into_data() {
+                hasher.update(&data);
+                buf.extend_from_slice(&data);
+            }
+        }
+        Ok((buf.freeze(), hasher.finish()))
+    }
+
+    /// Get the response body as `Bytes`, keeping the data received before any error.
+    ///
+    /// Unlike [`Response::bytes`], which discards everything on failure, this method returns
+    /// the bytes read so far along with the error that interrupted the body, if any: a
+    /// connection reset, a timeout, a decoding error, or even a panic while polling the body.
+    /// At most `limit` bytes are collected; a longer body is truncated and reported as an
+    /// error. This is useful to diagnose truncated responses from flaky origins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (bytes, err) = wreq::Client::new()
+    ///     .get("http://httpbin.org/drip")
+    ///     .send()
+    ///     .await?
+    ///     .bytes_lossy(1024 * 1024)
+    ///     .await;
+    ///
+    /// if let Some(err) = err {
+    ///     println!("body interrupted after {} bytes: {err}", bytes.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes_lossy(mut self, limit: usize) -> (Bytes, Option<crate::Error>) {
+        use std::{
+            future::poll_fn,
+            panic::{AssertUnwindSafe, catch_unwind},
+            pin::Pin,
+            task::Poll,
+        };
+
+        use http_body::Body as _;
+
+        self.decompress();
+        let body = self.res.body_mut();
+        let mut buf = BytesMut::new();
+
+        loop {
+            // A panicking body must not lose the data collected so far.
+            let frame = poll_fn(|cx| {
+                match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut *body).poll_frame(cx))) {
+                    Ok(poll) => poll.map(Some),
+                    Err(_) => Poll::Ready(None),
+                }
+            })
+            .await;
+
+            let err = match frame {
+                Some(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) if data.len() > limit - buf.len() => {
+                        buf.extend_from_slice(&data[..limit - buf.len()]);
+                        Error::body(format!("response body exceeds the limit of {limit} bytes"))
+                    }
+                    Ok(data) => {
+                        buf.extend_from_slice(&data);
+                        continue;
+                    }
+                    // ignore trailers and unrecognized frames
+                    Err(_) => continue,
+                },
+                Some(Some(Err(err))) => err,
+                Some(None) => return (buf.freeze(), None),
+                None => Error::body("response body panicked while being read"),
+            };
+
+            return (buf.freeze(), Some(err.with_url(*self.url)));
+        }
+    }
+
+    /// Get the full response body as `Bytes`, without automatic decompression.
+    ///
+    /// The body is returned exactly as it was received, even if the client would otherwise
+    /// decompress it. This is useful to pass a body through untouched, e.g. in a proxy.
+    ///
+    /// The `Content-Encoding` and `Content-Length` headers of a response that would be
+    /// decompressed are removed from [`Response::headers`]. Call
+    /// [`Response::without_decompression`] first to read them back along with the raw body. If
+    /// part of the body was already read with [`Response::chunk`], the rest of the body is still
+    /// decompressed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let raw = wreq::Client::new()
+    ///     .get("http://httpbin.org/gzip")
+    ///     .send()
+    ///     .await?
+    ///     .raw_bytes()
+    ///     .await?;
+    ///
+    /// println!("compressed bytes: {raw:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_bytes(self) -> crate::Result<Bytes> {
+        self.without_decompression().collect().await
+    }
+
+    /// Disables the automatic decompression of the body, so that it is read as it was received.
+    ///
+    /// The `Content-Encoding`, `Content-Length` and `Transfer-Encoding` headers removed from the
+    /// response when it was to be decompressed are restored, so that the headers describe the
+    /// body read afterwards. This is useful to pass a response through untouched, e.g. in a
+    /// proxy. It has no effect once part of the body was read with [`Response::chunk`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let res = wreq::Client::new()
+    ///     .get("http://httpbin.org/gzip")
+    ///     .send()
+    ///     .await?
+    ///     .without_decompression();
+    ///
+    /// println!("content-encoding: {:?}", res.headers().get("content-encoding"));
+    /// println!("compressed bytes: {:?}", res.raw_bytes().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn without_decompression(mut self) -> Response {
+        self.bypass_decompression();
+        self
     }
 
     /// Stream a chunk of the response body.
@@ -304,6 +630,7 @@ impl Response {
     pub async fn chunk(&mut self) -> crate::Result<Option<Bytes>> {
         use http_body_util::BodyExt;
 
+        self.decompress();
         // loop to ignore unrecognized frames
         loop {
             if let Some(res) = self.res.body_mut().frame().await {
@@ -318,6 +645,115 @@ impl Response {
         }
     }
 
+    /// Determine the MIME type of the response the way a browser would.
+    ///
+    /// The type is computed following the [WHATWG MIME Sniffing] standard from the
+    /// `Content-Type` and `X-Content-Type-Options` headers and the first 1445 bytes of the
+    /// body: HTML, XML and PDF documents, common image, audio, video and archive formats, plain
+    /// text and binary data are recognized. The supplied `Content-Type` is returned unchanged
+    /// whenever the standard keeps it, e.g. for `text/html` or with `nosniff`.
+    ///
+    /// Sniffing is opt-in: it only happens when this method is called. The bytes read are kept,
+    /// so the whole body can still be read afterwards, decompressed.
+    ///
+    /// [WHATWG MIME Sniffing]: https://mimesniff.spec.whatwg.org/
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut res = wreq::Client::new().get("http://httpbin.org/image").send().await?;
+    ///
+    /// if res.sniffed_content_type().await?.starts_with("image/") {
+    ///     let image = res.bytes().await?;
+    ///     println!("image of {} bytes", image.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn sniffed_content_type(&mut self) -> crate::Result<String> {
+        use http_body_util::BodyExt;
+
+        use super::sniff::{RESOURCE_HEADER_LEN, sniff};
+
+        self.decompress();
+        let mut buf = BytesMut::new();
+        let mut result = Ok(());
+        while buf.len() < RESOURCE_HEADER_LEN {
+            match self.res.body_mut().frame().await {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        buf.extend_from_slice(&data);
+                    }
+                }
+                Some(Err(err)) => {
+                    result = Err(err);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        // Put the bytes read back in front of the rest of the body.
+        let resource = buf.freeze();
+        let body = std::mem::take(self.res.body_mut());
+        *self.res.body_mut() = body.prepend(resource.clone());
+        result?;
+
+        let headers = self.res.headers();
+        let supplied = headers
+            .get(crate::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok());
+        let no_sniff = headers
+            .get(crate::header::X_CONTENT_TYPE_OPTIONS)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value
+                    .split(',')
+                    .next()
+                    .is_some_and(|value| value.trim().eq_ignore_ascii_case("nosniff"))
+            });
+
+        Ok(sniff(supplied, no_sniff, &resource).to_owned())
+    }
+
+    /// Duplicate the response body into `sink` while it is read.
+    ///
+    /// Each chunk of the body is written to the sink before it is returned to the caller, and
+    /// the sink is flushed once the body ends, so that a body can be cached, stored or hashed
+    /// while it is processed, without downloading it twice. The sink receives the body as it is
+    /// read: decompressed by [`Response::bytes`] and the like, as received by
+    /// [`Response::raw_bytes`]. Nothing is written unless the body is read.
+    ///
+    /// A slow sink slows the reading of the body down, and a failing sink fails it with an
+    /// error for which [`Error::is_body`](crate::Error::is_body) returns true.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let cache = tokio::fs::File::create("page.html").await?;
+    /// let text = wreq::Client::new()
+    ///     .get("http://httpbin.org/html")
+    ///     .send()
+    ///     .await?
+    ///     .tee(cache)
+    ///     .text()
+    ///     .await?;
+    ///
+    /// println!("cached {} characters", text.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tee<W>(mut self, sink: W) -> Response
+    where
+        W: AsyncWrite + Send + Sync + Unpin + 'static,
+    {
+        let body = std::mem::take(self.res.body_mut());
+        *self.res.body_mut() = body.tee(sink);
+        self
+    }
+
     /// Convert the response into a `Stream` of `Bytes` from the body.
     ///
     /// # Example
@@ -345,9 +781,80 @@ impl Response {
     #[cfg(feature = "stream")]
     #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
     pub fn bytes_stream(self) -> impl futures_util::Stream<Item = crate::Result<Bytes>> {
+        self.decompress();
         super::body::DataStream(self.res.into_body())
     }
 
+    /// Convert the response into a `Stream` of `Bytes` from the body, without automatic
+    /// decompression.
+    ///
+    /// See [`Response::raw_bytes`] for details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn raw_bytes_stream(self) -> impl futures_util::Stream<Item = crate::Result<Bytes>> {
+        super::body::DataStream(self.without_decompression().res.into_body())
+    }
+
+    /// Decompresses the body if the client only decompresses bodies on demand.
+    #[inline]
+    fn decompress(&self) {
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        if let Some(switch) = self
+            .res
+            .extensions()
+            .get::<super::middleware::decoder::BypassDecompression>()
+        {
+            switch.decompress();
+        }
+    }
+
+    /// Passes the body through undecoded, restoring the coding headers removed for decoding.
+    #[inline]
+    fn bypass_decompression(&mut self) {
+        #[cfg(any(
+            feature = "gzip",
+            feature = "zstd",
+            feature = "brotli",
+            feature = "deflate",
+        ))]
+        if let Some(headers) = self
+            .res
+            .extensions()
+            .get::<super::middleware::decoder::BypassDecompression>()
+            .and_then(|bypass| bypass.bypass())
+        {
+            self.res.headers_mut().extend(headers);
+        }
+    }
+
+    /// Convert the response into a reader of the parts of a `multipart/*` body, such as
+    /// `multipart/mixed` or `multipart/byteranges`.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the `Content-Type` of the response is not `multipart/*` with a boundary.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `multipart` feature to be enabled.
+    #[cfg(feature = "multipart")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+    pub fn into_multipart(self) -> crate::Result<super::multipart::Multipart> {
+        self.decompress();
+        let (parts, body) = self.res.into_parts();
+        super::multipart::Multipart::from_parts(&parts.headers, body)
+            .map_err(|err| err.with_url(*self.url))
+    }
+
     // util methods
 
     /// Turn a response into an error if the server returned an error.
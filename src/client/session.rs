@@ -0,0 +1,388 @@
+//! A browser-tab-like session over a shared [`Client`].
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use http::{
+    HeaderValue, Response, Uri,
+    header::{ALT_SVC, REFERER, STRICT_TRANSPORT_SECURITY, USER_AGENT},
+};
+
+use super::{
+    client::Client,
+    middleware::{config::SharedCookieStore, hints::AcceptedHints},
+    request::{Request, RequestBuilder},
+    user_agent::UserAgent,
+};
+use crate::{IntoUrl, Method, Url, cookie::Jar, redirect::ReferrerPolicy, sync::RwLock};
+
+/// A browser-tab-like session layered over a [`Client`].
+///
+/// A `Session` keeps its own per-origin state, isolated from the client and from other sessions:
+///
+/// - a cookie [`Jar`],
+/// - the client hints requested by each origin through `Accept-CH`,
+/// - the alternative services advertised through `Alt-Svc`,
+/// - the HSTS hosts learned through `Strict-Transport-Security`, to which plain HTTP requests
+///   are upgraded to HTTPS,
+/// - the navigation history, used to set the `Referer` header of [`Session::navigate`],
+/// - the user agent picked when the client rotates them, see
+///   [`ClientBuilder::user_agent_rotation`](crate::ClientBuilder::user_agent_rotation).
+///
+/// Requests sent from a session still go through the client, so all sessions share the
+/// client's connection pool. Cloning a `Session` shares its state.
+///
+/// # Example
+///
+/// ```rust
+/// # async fn run() -> wreq::Result<()> {
+/// let client = wreq::Client::new();
+/// let session = client.session();
+///
+/// session.navigate("https://www.rust-lang.org").send().await?;
+/// let res = session
+///     .navigate("https://www.rust-lang.org/learn")
+///     .send()
+///     .await?;
+///
+/// assert_eq!(
+///     session.current_url().unwrap().as_str(),
+///     "https://www.rust-lang.org/learn"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Session {
+    client: Client,
+    jar: Arc<Jar>,
+    accepted_hints: AcceptedHints,
+    state: Arc<SessionState>,
+    referrer_policy: ReferrerPolicy,
+    user_agent: Option<UserAgent>,
+}
+
+/// The per-origin state learned from responses.
+#[derive(Debug, Default)]
+pub(crate) struct SessionState {
+    alt_svc: RwLock<HashMap<String, HeaderValue>>,
+    hsts: RwLock<HashMap<String, HstsEntry>>,
+    history: RwLock<Vec<Url>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct HstsEntry {
+    expires: Instant,
+    include_subdomains: bool,
+}
+
+/// The session a request was sent from, carried in the request extensions.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionContext {
+    pub(crate) state: Arc<SessionState>,
+    navigation: bool,
+}
+
+impl Session {
+    /// Creates a new session sending its requests through the given client.
+    pub fn new(client: Client) -> Session {
+        Session {
+            user_agent: client.rotate_user_agent(),
+            client,
+            jar: Arc::new(Jar::default()),
+            accepted_hints: AcceptedHints::default(),
+            state: Arc::new(SessionState::default()),
+            referrer_policy: ReferrerPolicy::StrictOriginWhenCrossOrigin,
+        }
+    }
+
+    /// Uses the given cookie jar for this session.
+    pub fn with_cookie_jar(mut self, jar: Arc<Jar>) -> Session {
+        self.jar = jar;
+        self
+    }
+
+    /// Sets the referrer policy used by [`Session::navigate`].
+    ///
+    /// Default is [`ReferrerPolicy::StrictOriginWhenCrossOrigin`], as in browsers.
+    pub fn with_referrer_policy(mut self, policy: ReferrerPolicy) -> Session {
+        self.referrer_policy = policy;
+        self
+    }
+
+    /// Returns the user agent of this session, if the client rotates them.
+    #[inline]
+    pub fn user_agent(&self) -> Option<&UserAgent> {
+        self.user_agent.as_ref()
+    }
+
+    /// Returns the underlying client.
+    #[inline]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Returns the cookie jar of this session.
+    #[inline]
+    pub fn cookie_jar(&self) -> &Arc<Jar> {
+        &self.jar
+    }
+
+    /// Convenience method to make a `GET` request to a URL.
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Convenience method to make a `POST` request to a URL.
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Start building a `Request` with the `Method` and `Url`, using the state of this session.
+    ///
+    /// Unlike [`Session::navigate`], the request does not update the navigation history.
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        self.build_request(method, url, false)
+    }
+
+    /// Start building a navigation-style `GET` request, like following a link in a browser tab.
+    ///
+    /// The `Referer` header is derived from the current page according to the session's
+    /// referrer policy, which also applies to any redirect that follows. Once the response is
+    /// received, its final URL becomes the current page.
+    pub fn navigate<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.build_request(Method::GET, url, true)
+    }
+
+    /// Returns the URL of the current page, if any.
+    pub fn current_url(&self) -> Option<Url> {
+        self.state.history.read().last().cloned()
+    }
+
+    /// Returns the URLs of the pages visited through [`Session::navigate`], oldest first.
+    pub fn history(&self) -> Vec<Url> {
+        self.state.history.read().clone()
+    }
+
+    /// Returns the `Alt-Svc` value last advertised by the origin of the given URL.
+    pub fn alt_svc(&self, url: &Url) -> Option<HeaderValue> {
+        let origin = url.origin().ascii_serialization();
+        self.state.alt_svc.read().get(&origin).cloned()
+    }
+
+    /// Returns `true` if plain HTTP requests to the given host are upgraded to HTTPS.
+    pub fn is_hsts_host(&self, host: &str) -> bool {
+        self.state.is_hsts(host)
+    }
+
+    /// Clears all cookies, cached directives and history of this session.
+    pub fn clear(&self) {
+        self.jar.clear();
+        self.accepted_hints.write().clear();
+        self.state.alt_svc.write().clear();
+        self.state.hsts.write().clear();
+        self.state.history.write().clear();
+    }
+
+    fn build_request<U: IntoUrl>(
+        &self,
+        method: Method,
+        url: U,
+        navigation: bool,
+    ) -> RequestBuilder {
+        let mut url = match url.into_url() {
+            Ok(url) => url,
+            Err(err) => return RequestBuilder::new(self.client.clone(), Err(err)),
+        };
+
+        if url.scheme() == "http" && url.host_str().is_some_and(|host| self.state.is_hsts(host)) {
+            let _ = url.set_scheme("https");
+            if url.port() == Some(80) {
+                let _ = url.set_port(None);
+            }
+        }
+
+        let mut req = Request::new(method, url);
+        *req.cookie_store_mut() = Some(SharedCookieStore(self.jar.clone()));
+        *req.accepted_hints_mut() = Some(self.accepted_hints.clone());
+        *req.session_mut() = Some(SessionContext {
+            state: self.state.clone(),
+            navigation,
+        });
+
+        if let Some(ref user_agent) = self.user_agent {
+            req.headers_mut()
+                .insert(USER_AGENT, user_agent.value().clone());
+            if let Some(hints) = user_agent.client_hints() {
+                *req.client_hints_mut() = Some(hints.clone());
+            }
+        }
+
+        if navigation {
+            *req.referrer_policy_mut() = Some(self.referrer_policy);
+
+            if let Some(current) = self.current_url() {
+                if let Some(referer) = self.referrer_policy.referrer(req.url(), &current) {
+                    req.headers_mut().insert(REFERER, referer);
+                }
+            }
+        }
+
+        RequestBuilder::new(self.client.clone(), Ok(req))
+    }
+}
+
+impl SessionState {
+    pub(crate) fn is_hsts(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let now = Instant::now();
+        let hsts = self.hsts.read();
+
+        // Walk up the domain labels, honoring `includeSubDomains` on parent domains.
+        let mut domain = host.as_str();
+        let mut exact = true;
+        loop {
+            if let Some(entry) = hsts.get(domain) {
+                if entry.expires > now && (exact || entry.include_subdomains) {
+                    return true;
+                }
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+            exact = false;
+        }
+    }
+}
+
+impl SessionContext {
+    /// Updates the session state from a response received for `uri`.
+    pub(crate) fn observe<B>(&self, uri: &Uri, res: &Response<B>) {
+        let url = match Url::parse(&uri.to_string()) {
+            Ok(url) => url,
+            Err(_) => return,
+        };
+
+        // HSTS is only honored over secure transport, and never for IP addresses.
+        if url.scheme() == "https" {
+            if let (Some(url::Host::Domain(host)), Some(value)) =
+                (url.host(), res.headers().get(STRICT_TRANSPORT_SECURITY))
+            {
+                if let Some((max_age, include_subdomains)) = parse_hsts(value) {
+                    let host = host.trim_end_matches('.').to_ascii_lowercase();
+                    let mut hsts = self.state.hsts.write();
+                    if max_age.is_zero() {
+                        hsts.remove(&host);
+                    } else {
+                        hsts.insert(
+                            host,
+                            HstsEntry {
+                                expires: Instant::now() + max_age,
+                                include_subdomains,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(value) = res.headers().get(ALT_SVC) {
+            let origin = url.origin().ascii_serialization();
+            let mut alt_svc = self.state.alt_svc.write();
+            if value.as_bytes().eq_ignore_ascii_case(b"clear") {
+                alt_svc.remove(&origin);
+            } else {
+                alt_svc.insert(origin, value.clone());
+            }
+        }
+
+        // Only the final response of a navigation, not a redirect hop, becomes the current page.
+        if self.navigation && !res.status().is_redirection() {
+            self.state.history.write().push(url);
+        }
+    }
+}
+
+/// The longest an HSTS policy is kept, two years, as a `max-age` is chosen by the server.
+const HSTS_MAX_AGE: Duration = Duration::from_secs(2 * 365 * 24 * 60 * 60);
+
+/// Parses a `Strict-Transport-Security` header into its `max-age`, capped at
+/// [`HSTS_MAX_AGE`], and `includeSubDomains`.
+fn parse_hsts(value: &HeaderValue) -> Option<(Duration, bool)> {
+    let mut max_age = None;
+    let mut include_subdomains = false;
+
+    for directive in value.to_str().ok()?.split(';') {
+        let (name, value) = match directive.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+            None => (directive.trim(), None),
+        };
+
+        if name.eq_ignore_ascii_case("max-age") {
+            max_age = value.and_then(|v| v.parse::<u64>().ok());
+        } else if name.eq_ignore_ascii_case("includesubdomains") {
+            include_subdomains = true;
+        }
+    }
+
+    max_age.map(|secs| {
+        (
+            Duration::from_secs(secs).min(HSTS_MAX_AGE),
+            include_subdomains,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hsts() {
+        let value = HeaderValue::from_static("max-age=31536000; includeSubDomains; preload");
+        assert_eq!(
+            parse_hsts(&value),
+            Some((Duration::from_secs(31536000), true))
+        );
+
+        let value = HeaderValue::from_static("max-age=\"0\"");
+        assert_eq!(parse_hsts(&value), Some((Duration::ZERO, false)));
+
+        let value = HeaderValue::from_static("includeSubDomains");
+        assert_eq!(parse_hsts(&value), None);
+
+        let value = HeaderValue::from_static("max-age=18446744073709551615");
+        let (max_age, _) = parse_hsts(&value).unwrap();
+        assert_eq!(max_age, HSTS_MAX_AGE);
+        assert!(Instant::now().checked_add(max_age).is_some());
+    }
+
+    #[test]
+    fn test_hsts_subdomains() {
+        let state = SessionState::default();
+        state.hsts.write().insert(
+            "example.com".to_owned(),
+            HstsEntry {
+                expires: Instant::now() + Duration::from_secs(60),
+                include_subdomains: true,
+            },
+        );
+        state.hsts.write().insert(
+            "other.com".to_owned(),
+            HstsEntry {
+                expires: Instant::now() + Duration::from_secs(60),
+                include_subdomains: false,
+            },
+        );
+
+        assert!(state.is_hsts("example.com"));
+        assert!(state.is_hsts("WWW.Example.com."));
+        assert!(state.is_hsts("other.com"));
+        assert!(!state.is_hsts("www.other.com"));
+        assert!(!state.is_hsts("com"));
+    }
+}
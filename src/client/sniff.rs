@@ -0,0 +1,261 @@
+//! A subset of the [WHATWG MIME Sniffing] standard.
+//!
+//! [WHATWG MIME Sniffing]: https://mimesniff.spec.whatwg.org/
+
+/// The number of bytes of a resource read to sniff its type.
+pub(crate) const RESOURCE_HEADER_LEN: usize = 1445;
+
+/// Returns the computed MIME type of a resource, from its supplied `Content-Type`, whether
+/// `X-Content-Type-Options: nosniff` was received and the first bytes of the resource.
+pub(crate) fn sniff<'a>(supplied: Option<&'a str>, no_sniff: bool, resource: &[u8]) -> &'a str {
+    let resource = &resource[..resource.len().min(RESOURCE_HEADER_LEN)];
+
+    let Some(supplied) = supplied.filter(|supplied| !supplied.trim().is_empty()) else {
+        return unknown(resource, !no_sniff);
+    };
+    let essence = essence(supplied);
+
+    if matches!(
+        essence.as_str(),
+        "unknown/unknown" | "application/unknown" | "*/*"
+    ) {
+        return unknown(resource, !no_sniff);
+    }
+    if no_sniff {
+        return supplied;
+    }
+
+    // Apache used to label every file as `text/plain`.
+    if matches!(
+        supplied,
+        "text/plain"
+            | "text/plain; charset=ISO-8859-1"
+            | "text/plain; charset=iso-8859-1"
+            | "text/plain; charset=UTF-8"
+    ) {
+        return text_or_binary(resource);
+    }
+
+    let sniffed = if essence.starts_with("image/") {
+        image(resource)
+    } else if essence.starts_with("audio/") || essence.starts_with("video/") {
+        audio_or_video(resource)
+    } else {
+        None
+    };
+
+    match sniffed {
+        Some(sniffed) => sniffed,
+        None => supplied,
+    }
+}
+
+fn essence(supplied: &str) -> String {
+    supplied
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// The rules for identifying an unknown MIME type.
+fn unknown(resource: &[u8], sniff_scriptable: bool) -> &'static str {
+    if sniff_scriptable {
+        if let Some(sniffed) = scriptable(resource) {
+            return sniffed;
+        }
+    }
+
+    if resource.starts_with(b"%!PS-Adobe-") {
+        return "application/postscript";
+    }
+    if resource.starts_with(b"\xFE\xFF")
+        || resource.starts_with(b"\xFF\xFE")
+        || resource.starts_with(b"\xEF\xBB\xBF")
+    {
+        return "text/plain";
+    }
+
+    image(resource)
+        .or_else(|| audio_or_video(resource))
+        .or_else(|| archive(resource))
+        .unwrap_or_else(|| text_or_binary(resource))
+}
+
+fn scriptable(resource: &[u8]) -> Option<&'static str> {
+    const HTML: [&[u8]; 17] = [
+        b"<!DOCTYPE HTML",
+        b"<HTML",
+        b"<HEAD",
+        b"<SCRIPT",
+        b"<IFRAME",
+        b"<H1",
+        b"<DIV",
+        b"<FONT",
+        b"<TABLE",
+        b"<A",
+        b"<STYLE",
+        b"<TITLE",
+        b"<B",
+        b"<BODY",
+        b"<BR",
+        b"<P",
+        b"<!--",
+    ];
+
+    let start = resource
+        .iter()
+        .position(|byte| !matches!(byte, b'\t' | b'\n' | b'\x0C' | b'\r' | b' '))
+        .unwrap_or(resource.len());
+    let trimmed = &resource[start..];
+
+    for pattern in HTML {
+        if trimmed.len() > pattern.len()
+            && trimmed[..pattern.len()].eq_ignore_ascii_case(pattern)
+            // Comments need no tag-terminating byte.
+            && (pattern == b"<!--" || matches!(trimmed[pattern.len()], b' ' | b'>'))
+        {
+            return Some("text/html");
+        }
+    }
+
+    if trimmed.starts_with(b"<?xml") {
+        return Some("text/xml");
+    }
+    if resource.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    None
+}
+
+fn image(resource: &[u8]) -> Option<&'static str> {
+    let sniffed =
+        if resource.starts_with(b"\x00\x00\x01\x00") || resource.starts_with(b"\x00\x00\x02\x00") {
+            "image/x-icon"
+        } else if resource.starts_with(b"BM") {
+            "image/bmp"
+        } else if resource.starts_with(b"GIF87a") || resource.starts_with(b"GIF89a") {
+            "image/gif"
+        } else if riff(resource, b"WEBPVP") {
+            "image/webp"
+        } else if resource.starts_with(b"\x89PNG\r\n\x1A\n") {
+            "image/png"
+        } else if resource.starts_with(b"\xFF\xD8\xFF") {
+            "image/jpeg"
+        } else {
+            return None;
+        };
+    Some(sniffed)
+}
+
+fn audio_or_video(resource: &[u8]) -> Option<&'static str> {
+    let sniffed = if resource.starts_with(b"\x1A\x45\xDF\xA3") {
+        "video/webm"
+    } else if resource.starts_with(b".snd") {
+        "audio/basic"
+    } else if resource.len() >= 12 && &resource[..4] == b"FORM" && &resource[8..12] == b"AIFF" {
+        "audio/aiff"
+    } else if resource.starts_with(b"ID3") {
+        "audio/mpeg"
+    } else if resource.starts_with(b"OggS\x00") {
+        "application/ogg"
+    } else if resource.starts_with(b"MThd\x00\x00\x00\x06") {
+        "audio/midi"
+    } else if riff(resource, b"AVI ") {
+        "video/avi"
+    } else if riff(resource, b"WAVE") {
+        "audio/wave"
+    } else if mp4(resource) {
+        "video/mp4"
+    } else {
+        return None;
+    };
+    Some(sniffed)
+}
+
+fn archive(resource: &[u8]) -> Option<&'static str> {
+    let sniffed = if resource.starts_with(b"\x1F\x8B\x08") {
+        "application/x-gzip"
+    } else if resource.starts_with(b"PK\x03\x04") {
+        "application/zip"
+    } else if resource.starts_with(b"Rar!\x1A\x07\x00") {
+        "application/x-rar-compressed"
+    } else {
+        return None;
+    };
+    Some(sniffed)
+}
+
+fn text_or_binary(resource: &[u8]) -> &'static str {
+    let binary = resource
+        .iter()
+        .any(|byte| matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F));
+    if binary {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Matches a RIFF container of the given format.
+fn riff(resource: &[u8], format: &[u8]) -> bool {
+    resource.starts_with(b"RIFF")
+        && resource.len() >= 8 + format.len()
+        && &resource[8..8 + format.len()] == format
+}
+
+/// Matches an ISO base media file with an `mp4` brand.
+fn mp4(resource: &[u8]) -> bool {
+    if resource.len() < 12 {
+        return false;
+    }
+    let box_size =
+        u32::from_be_bytes([resource[0], resource[1], resource[2], resource[3]]) as usize;
+    if resource.len() < box_size || box_size % 4 != 0 || box_size < 12 {
+        return false;
+    }
+    if &resource[4..8] != b"ftyp" {
+        return false;
+    }
+    if &resource[8..11] == b"mp4" {
+        return true;
+    }
+    // The compatible brands follow the minor version.
+    resource[16.min(box_size)..box_size]
+        .chunks_exact(4)
+        .any(|brand| &brand[..3] == b"mp4")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sniff;
+
+    #[test]
+    fn unknown_types() {
+        assert_eq!(sniff(None, false, b"  <html><body>"), "text/html");
+        assert_eq!(sniff(Some("*/*"), false, b"<!-- x"), "text/html");
+        assert_eq!(sniff(None, false, b"<?xml version"), "text/xml");
+        assert_eq!(sniff(None, false, b"\x89PNG\r\n\x1A\n...."), "image/png");
+        assert_eq!(sniff(None, false, b"PK\x03\x04"), "application/zip");
+        assert_eq!(sniff(None, false, b"plain text"), "text/plain");
+        assert_eq!(sniff(None, false, b"\x00\x01"), "application/octet-stream");
+        // Without sniffing scriptable types, HTML is text.
+        assert_eq!(sniff(None, true, b"<html>"), "text/plain");
+    }
+
+    #[test]
+    fn supplied_types() {
+        assert_eq!(
+            sniff(Some("text/plain"), false, b"\x00\x01"),
+            "application/octet-stream"
+        );
+        assert_eq!(sniff(Some("text/plain"), true, b"\x00\x01"), "text/plain");
+        assert_eq!(sniff(Some("image/png"), false, b"GIF89a"), "image/gif");
+        assert_eq!(sniff(Some("image/png"), false, b"????"), "image/png");
+        assert_eq!(
+            sniff(Some("text/html; charset=utf-8"), false, b"GIF89a"),
+            "text/html; charset=utf-8"
+        );
+    }
+}
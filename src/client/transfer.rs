@@ -0,0 +1,280 @@
+//! Accounting of the bytes transferred by requests
+
+use std::{
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, ready},
+};
+
+use bytes::Bytes;
+use http::{HeaderName, HeaderValue, Request, Response};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pin_project_lite::pin_project;
+
+use super::body::Body;
+use crate::core::ext::SharedHeaders;
+
+/// The bytes transferred by a request, or by all the requests of a client.
+///
+/// Returned by [`Response::transfer_stats`](crate::Response::transfer_stats) for a request,
+/// including the redirects and retries it went through, and by
+/// [`Client::transfer_stats`](crate::Client::transfer_stats) for all the requests of a
+/// client. The body sizes grow as the bodies are sent and read.
+///
+/// The header sizes are those of their HTTP/1.1 encoding, with the request or status line.
+/// Over HTTP/2 and HTTP/3, the headers are sent compressed, so fewer bytes actually go over the
+/// wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferStats {
+    request_header_bytes: u64,
+    request_body_bytes: u64,
+    response_header_bytes: u64,
+    response_body_bytes: u64,
+    response_decoded_body_bytes: u64,
+}
+
+impl TransferStats {
+    /// Returns the size of the request headers sent.
+    pub fn request_header_bytes(&self) -> u64 {
+        self.request_header_bytes
+    }
+
+    /// Returns the size of the request bodies sent.
+    pub fn request_body_bytes(&self) -> u64 {
+        self.request_body_bytes
+    }
+
+    /// Returns the size of the response headers received.
+    pub fn response_header_bytes(&self) -> u64 {
+        self.response_header_bytes
+    }
+
+    /// Returns the size of the response bodies received, before decompression.
+    pub fn response_body_bytes(&self) -> u64 {
+        self.response_body_bytes
+    }
+
+    /// Returns the size of the response bodies read, after decompression.
+    pub fn response_decoded_body_bytes(&self) -> u64 {
+        self.response_decoded_body_bytes
+    }
+
+    /// Returns the total size of the request headers and bodies sent.
+    pub fn bytes_sent(&self) -> u64 {
+        self.request_header_bytes + self.request_body_bytes
+    }
+
+    /// Returns the total size of the response headers and bodies received, before
+    /// decompression.
+    pub fn bytes_received(&self) -> u64 {
+        self.response_header_bytes + self.response_body_bytes
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    request_headers: AtomicU64,
+    request_body: AtomicU64,
+    response_headers: AtomicU64,
+    response_body: AtomicU64,
+    response_decoded_body: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn stats(&self) -> TransferStats {
+        TransferStats {
+            request_header_bytes: self.request_headers.load(Ordering::Relaxed),
+            request_body_bytes: self.request_body.load(Ordering::Relaxed),
+            response_header_bytes: self.response_headers.load(Ordering::Relaxed),
+            response_body_bytes: self.response_body.load(Ordering::Relaxed),
+            response_decoded_body_bytes: self.response_decoded_body.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A part of the exchange counted by a [`CountedBody`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Counted {
+    RequestBody,
+    ResponseBody,
+    ResponseDecodedBody,
+}
+
+/// The counters of a request, and of the client that sent it, carried in its context.
+#[derive(Clone, Debug)]
+pub(crate) struct TransferCounter {
+    request: Arc<Counters>,
+    client: Arc<Counters>,
+}
+
+impl TransferCounter {
+    /// Starts counting the bytes of a request of the client counting into `client`.
+    pub(crate) fn new(client: Arc<Counters>) -> TransferCounter {
+        TransferCounter {
+            request: Arc::default(),
+            client,
+        }
+    }
+
+    /// Returns the bytes transferred by the request so far.
+    pub(crate) fn stats(&self) -> TransferStats {
+        self.request.stats()
+    }
+
+    fn add(&self, counted: Counted, n: u64) {
+        for counters in [&self.request, &self.client] {
+            let counter = match counted {
+                Counted::RequestBody => &counters.request_body,
+                Counted::ResponseBody => &counters.response_body,
+                Counted::ResponseDecodedBody => &counters.response_decoded_body,
+            };
+            counter.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts the head of a request about to be sent, and the body as it is sent.
+    pub(crate) fn count_request(&self, req: &mut Request<Body>) {
+        let path = req
+            .uri()
+            .path_and_query()
+            .map_or(1, |path| path.as_str().len());
+        // `GET /path HTTP/1.1\r\n`
+        let line = req.method().as_str().len() + path + 12;
+        let shared = SharedHeaders::get(req.extensions())
+            .into_iter()
+            .flat_map(|shared| shared.iter(req.headers()));
+        let len = (line + header_block_len(req.headers().iter().chain(shared))) as u64;
+        for counters in [&self.request, &self.client] {
+            counters.request_headers.fetch_add(len, Ordering::Relaxed);
+        }
+
+        match req.body().as_bytes() {
+            Some(bytes) => self.add(Counted::RequestBody, bytes.len() as u64),
+            None => {
+                let body = std::mem::take(req.body_mut());
+                *req.body_mut() = Body::wrap(self.count(body, Counted::RequestBody));
+            }
+        }
+    }
+
+    /// Counts the head of a response received.
+    pub(crate) fn count_response<B>(&self, res: &Response<B>) {
+        let reason = res.status().canonical_reason().map_or(0, str::len);
+        // `HTTP/1.1 200 OK\r\n`
+        let line = reason + 15;
+        let len = (line + header_block_len(res.headers())) as u64;
+        for counters in [&self.request, &self.client] {
+            counters.response_headers.fetch_add(len, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts the data of `body` as it is polled.
+    pub(crate) fn count<B>(&self, body: B, counted: Counted) -> CountedBody<B> {
+        CountedBody {
+            inner: body,
+            counter: self.clone(),
+            counted,
+        }
+    }
+
+    /// Counts `n` bytes of a response body received, before decompression.
+    pub(crate) fn response_body(&self, n: usize) {
+        self.add(Counted::ResponseBody, n as u64);
+    }
+}
+
+/// Returns the size of the HTTP/1.1 encoding of `headers`, with the empty line ending them.
+fn header_block_len<'a>(
+    headers: impl IntoIterator<Item = (&'a HeaderName, &'a HeaderValue)>,
+) -> usize {
+    let fields = headers
+        .into_iter()
+        .map(|(name, value)| name.as_str().len() + value.len() + 4)
+        .sum::<usize>();
+    fields + 2
+}
+
+pin_project! {
+    /// A body counting its data as it is polled.
+    pub(crate) struct CountedBody<B> {
+        #[pin]
+        inner: B,
+        counter: TransferCounter,
+        counted: Counted,
+    }
+}
+
+impl<B> HttpBody for CountedBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        if let Some(data) = frame
+            .as_ref()
+            .and_then(|frame| frame.as_ref().ok()?.data_ref())
+        {
+            this.counter.add(*this.counted, data.len() as u64);
+        }
+        Poll::Ready(frame)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_request_and_response() {
+        use http_body_util::BodyExt;
+
+        let client = Arc::new(Counters::default());
+        let counter = TransferCounter::new(client.clone());
+
+        let mut req = Request::post("http://example.com/path")
+            .header("a", "b")
+            .body(Body::from("hello"))
+            .unwrap();
+        counter.count_request(&mut req);
+
+        let res = Response::builder().header("c", "de").body(()).unwrap();
+        counter.count_response(&res);
+
+        let body = counter.count(Body::from("body"), Counted::ResponseDecodedBody);
+        body.collect().await.unwrap();
+
+        let stats = counter.stats();
+        assert_eq!(
+            stats.request_header_bytes(),
+            "POST /path HTTP/1.1\r\na: b\r\n\r\n".len() as u64
+        );
+        assert_eq!(stats.request_body_bytes(), 5);
+        assert_eq!(
+            stats.response_header_bytes(),
+            "HTTP/1.1 200 OK\r\nc: de\r\n\r\n".len() as u64
+        );
+        assert_eq!(stats.response_decoded_body_bytes(), 4);
+        assert_eq!(stats.bytes_sent(), 34);
+        assert_eq!(client.stats(), stats);
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http::HeaderValue;
+
+use super::hints::ClientHints;
+
+/// A `User-Agent` value, with the client hints of the browser it identifies.
+///
+/// The hints replace those of the emulation wherever the user agent is sent, so that the
+/// `Sec-CH-UA` headers stay consistent with the `User-Agent`.
+#[derive(Debug, Clone)]
+pub struct UserAgent {
+    value: HeaderValue,
+    client_hints: Option<ClientHints>,
+}
+
+impl UserAgent {
+    /// Creates a user agent from its header value.
+    pub fn new(value: HeaderValue) -> UserAgent {
+        UserAgent {
+            value,
+            client_hints: None,
+        }
+    }
+
+    /// Sends the given client hints along with this user agent.
+    pub fn with_client_hints(mut self, hints: ClientHints) -> UserAgent {
+        self.client_hints = Some(hints);
+        self
+    }
+
+    /// Returns the header value of this user agent.
+    #[inline]
+    pub fn value(&self) -> &HeaderValue {
+        &self.value
+    }
+
+    /// Returns the client hints sent with this user agent, if any.
+    #[inline]
+    pub fn client_hints(&self) -> Option<&ClientHints> {
+        self.client_hints.as_ref()
+    }
+}
+
+impl From<HeaderValue> for UserAgent {
+    fn from(value: HeaderValue) -> UserAgent {
+        UserAgent::new(value)
+    }
+}
+
+/// Panics if the value is not a valid header value, like [`HeaderValue::from_static`].
+impl From<&'static str> for UserAgent {
+    fn from(value: &'static str) -> UserAgent {
+        UserAgent::new(HeaderValue::from_static(value))
+    }
+}
+
+/// How the next user agent of a rotation is picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RotationStrategy {
+    /// Picks the user agents in turn.
+    #[default]
+    RoundRobin,
+    /// Picks a user agent at random.
+    Random,
+}
+
+/// The user agents rotated between the sessions of a client.
+#[derive(Debug)]
+pub(crate) struct UserAgentRotation {
+    agents: Vec<UserAgent>,
+    strategy: RotationStrategy,
+    next: AtomicUsize,
+}
+
+impl UserAgentRotation {
+    pub(crate) fn new(agents: Vec<UserAgent>, strategy: RotationStrategy) -> Option<Self> {
+        if agents.is_empty() {
+            return None;
+        }
+
+        Some(UserAgentRotation {
+            agents,
+            strategy,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Picks the user agent of a new session.
+    pub(crate) fn pick(&self) -> &UserAgent {
+        let index = match self.strategy {
+            RotationStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+            RotationStrategy::Random => crate::util::fast_random() as usize,
+        };
+        &self.agents[index % self.agents.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let rotation =
+            UserAgentRotation::new(vec!["a".into(), "b".into()], RotationStrategy::RoundRobin)
+                .unwrap();
+
+        let picked: Vec<_> = (0..3).map(|_| rotation.pick().value().clone()).collect();
+        assert_eq!(picked, ["a", "b", "a"]);
+        assert!(UserAgentRotation::new(Vec::new(), RotationStrategy::Random).is_none());
+    }
+}
@@ -22,7 +22,11 @@ use tungstenite::protocol::WebSocketConfig;
 pub use self::message::{CloseCode, CloseFrame, Message, Utf8Bytes};
 use crate::{
     EmulationProviderFactory, Error, OriginalHeaders, RequestBuilder, Response,
-    core::ext::Protocol, proxy::Proxy,
+    core::{
+        ext::{Protocol, WebSocketUpgrade},
+        header::OriginalHeaderName,
+    },
+    proxy::Proxy,
 };
 
 /// A WebSocket stream.
@@ -71,13 +75,17 @@ impl WebSocketRequestBuilder {
     /// Sets the websocket subprotocols to request.
     ///
     /// This method allows you to specify the subprotocols that the websocket client
-    /// should request during the handshake. Subprotocols are used to define the type
-    /// of communication expected over the websocket connection.
+    /// should request during the handshake, in order of preference. Subprotocols are used to
+    /// define the type of communication expected over the websocket connection, and the one
+    /// selected by the server is available from [`WebSocket::protocol`].
+    ///
+    /// Sending the request fails if a subprotocol is not a valid token or is repeated, and the
+    /// handshake fails if the server selects a subprotocol which was not requested.
     ///
     /// # Arguments
     ///
     /// * `protocols` - A list of subprotocols, which can be converted into a `Cow<'static,
-    ///   [String]>`.
+    ///   str>`.
     ///
     /// # Returns
     ///
@@ -86,9 +94,18 @@ impl WebSocketRequestBuilder {
     /// # Example
     ///
     /// ```
-    /// let request = WebSocketRequestBuilder::new(builder)
-    ///     .protocols(["protocol1", "protocol2"])
-    ///     .build();
+    /// # async fn run() -> wreq::Result<()> {
+    /// let websocket = wreq::Client::new()
+    ///     .websocket("wss://example.com/graphql")
+    ///     .protocols(["graphql-transport-ws", "graphql-ws"])
+    ///     .send()
+    ///     .await?
+    ///     .into_websocket()
+    ///     .await?;
+    ///
+    /// let protocol = websocket.protocol();
+    /// # Ok(())
+    /// # }
     /// ```
     pub fn protocols<P>(mut self, protocols: P) -> Self
     where
@@ -100,7 +117,11 @@ impl WebSocketRequestBuilder {
         self
     }
 
-    /// Sets the websocket max_frame_size configuration.
+    /// Sets the maximum size of an incoming frame, in bytes.
+    ///
+    /// A frame exceeding it fails the connection with a body error, after sending a close frame
+    /// with the [`CloseCode::SIZE`] code to the server, so that a malicious server cannot exhaust
+    /// the memory of the client. Defaults to 16 MiB.
     pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
         self.config.max_frame_size = Some(max_frame_size);
         self
@@ -124,7 +145,10 @@ impl WebSocketRequestBuilder {
         self
     }
 
-    /// Sets the websocket max_message_size configuration.
+    /// Sets the maximum size of an incoming message, in bytes.
+    ///
+    /// A message exceeding it, including one split across several frames, fails the connection
+    /// like an oversized frame does. Defaults to 64 MiB.
     pub fn max_message_size(mut self, max_message_size: usize) -> Self {
         self.config.max_message_size = Some(max_message_size);
         self
@@ -136,11 +160,25 @@ impl WebSocketRequestBuilder {
         self
     }
 
+    /// Configures the WebSocket connection to use HTTP/1.1.
+    ///
+    /// By default, the handshake is sent as an HTTP/2 extended CONNECT (RFC 8441) when the
+    /// connection negotiates HTTP/2 and the server advertises `SETTINGS_ENABLE_CONNECT_PROTOCOL`,
+    /// so that pooled HTTP/2 connections are reused, and as an HTTP/1.1 upgrade otherwise.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The modified instance with the HTTP version set to HTTP/1.1.
+    pub fn use_http1(mut self) -> Self {
+        self.inner = self.inner.version(Version::HTTP_11);
+        self
+    }
+
     /// Configures the WebSocket connection to use HTTP/2.
     ///
     /// This method sets the HTTP version to HTTP/2 for the WebSocket connection.
     /// If the server does not support HTTP/2 WebSocket connections, the connection attempt will
-    /// fail.
+    /// fail, instead of falling back to HTTP/1.1.
     ///
     /// # Returns
     ///
@@ -188,6 +226,16 @@ impl WebSocketRequestBuilder {
         self
     }
 
+    /// Set the order in which headers are sent for this request.
+    pub fn header_order<I>(mut self, order: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: TryInto<OriginalHeaderName>,
+    {
+        self.inner = self.inner.header_order(order);
+        self
+    }
+
     /// Enable HTTP authentication.
     pub fn auth<V>(mut self, value: V) -> Self
     where
@@ -303,36 +351,46 @@ impl WebSocketRequestBuilder {
             .map_err(|_| Error::url_bad_scheme(url.clone()))?;
 
         // Get the version of the request
-        // If the version is not set, use the default version
-        let version = request.version().cloned().unwrap_or(Version::HTTP_11);
+        // If the version is not set, negotiate it with the connection
+        let version = request.version().cloned();
 
-        // Set the headers for the websocket handshake
+        // Set the headers for the websocket handshake, in the order browsers send them. Headers
+        // already set on the request keep their place.
         let headers = request.headers_mut();
-        headers.insert(
-            header::SEC_WEBSOCKET_VERSION,
-            HeaderValue::from_static("13"),
-        );
 
         // Ensure the request is HTTP 1.1/HTTP 2
         let accept_key = match version {
-            Version::HTTP_10 | Version::HTTP_11 => {
+            None | Some(Version::HTTP_10 | Version::HTTP_11) => {
                 // Generate a nonce if one wasn't provided
                 let nonce = self
                     .accept_key
                     .unwrap_or_else(|| Cow::Owned(tungstenite::handshake::client::generate_key()));
 
+                headers.insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
                 headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
-                headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+                headers.insert(
+                    header::SEC_WEBSOCKET_VERSION,
+                    HeaderValue::from_static("13"),
+                );
                 headers.insert(
                     header::SEC_WEBSOCKET_KEY,
                     HeaderValue::from_str(&nonce).map_err(Error::builder)?,
                 );
 
                 *request.method_mut() = Method::GET;
-                *request.version_mut() = Some(Version::HTTP_11);
+                if version.is_some() {
+                    *request.version_mut() = Some(Version::HTTP_11);
+                } else {
+                    // Sent as an extended CONNECT instead if the connection is HTTP/2
+                    request.extensions_mut().insert(WebSocketUpgrade);
+                }
                 Some(nonce)
             }
-            Version::HTTP_2 => {
+            Some(Version::HTTP_2) => {
+                headers.insert(
+                    header::SEC_WEBSOCKET_VERSION,
+                    HeaderValue::from_static("13"),
+                );
                 *request.method_mut() = Method::CONNECT;
                 *request.version_mut() = Some(Version::HTTP_2);
                 request
@@ -349,6 +407,14 @@ impl WebSocketRequestBuilder {
         if let Some(ref protocols) = self.protocols {
             // Sets subprotocols
             if !protocols.is_empty() {
+                for (i, protocol) in protocols.iter().enumerate() {
+                    if !is_token(protocol) || protocols[..i].contains(protocol) {
+                        return Err(Error::builder(format!(
+                            "invalid websocket subprotocol: {protocol:?}"
+                        )));
+                    }
+                }
+
                 let subprotocols = protocols
                     .iter()
                     .map(|s| s.as_ref())
@@ -362,16 +428,31 @@ impl WebSocketRequestBuilder {
             }
         }
 
-        client
-            .execute(request)
-            .await
-            .map(|inner| WebSocketResponse {
-                inner,
-                accept_key,
-                protocols: self.protocols,
-                config: self.config,
-                version,
-            })
+        // Keep an HTTP/1.1 upgrade to fall back to, if the server does not support the
+        // extended CONNECT on its HTTP/2 connection
+        let fallback = match version {
+            None => request.try_clone().map(|mut request| {
+                request.extensions_mut().remove::<WebSocketUpgrade>();
+                *request.version_mut() = Some(Version::HTTP_11);
+                request
+            }),
+            Some(_) => None,
+        };
+
+        let inner = match (client.execute(request).await, fallback) {
+            (Err(err), Some(fallback)) if err.is_extended_connect_disabled() => {
+                client.execute(fallback).await?
+            }
+            (res, _) => res?,
+        };
+
+        Ok(WebSocketResponse {
+            version: inner.version(),
+            inner,
+            accept_key,
+            protocols: self.protocols,
+            config: self.config,
+        })
     }
 }
 
@@ -406,7 +487,7 @@ impl WebSocketResponse {
     /// Turns the response into a websocket. This checks if the websocket
     /// handshake was successful.
     pub async fn into_websocket(self) -> Result<WebSocket, Error> {
-        let (inner, protocol) = {
+        let (inner, protocol, headers) = {
             let status = self.inner.status();
             let headers = self.inner.headers();
 
@@ -498,6 +579,7 @@ impl WebSocketResponse {
                 }
             }
 
+            let headers = headers.clone();
             let upgraded = self.inner.upgrade().await?;
             let inner = WebSocketStream::from_raw_socket(
                 upgraded,
@@ -506,13 +588,25 @@ impl WebSocketResponse {
             )
             .await;
 
-            (inner, protocol)
+            (inner, protocol, headers)
         };
 
-        Ok(WebSocket { inner, protocol })
+        Ok(WebSocket {
+            inner,
+            protocol,
+            headers,
+        })
     }
 }
 
+/// Checks if the value is a valid token, as required of subprotocol names.
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
 /// Checks if the header value is equal to the given value.
 fn header_eq(headers: &HeaderMap, key: HeaderName, value: &'static str) -> bool {
     if let Some(header) = headers.get(&key) {
@@ -542,6 +636,7 @@ fn header_contains(headers: &HeaderMap, key: HeaderName, value: &'static str) ->
 pub struct WebSocket {
     inner: WebSocketStream,
     protocol: Option<HeaderValue>,
+    headers: HeaderMap,
 }
 
 impl WebSocket {
@@ -565,6 +660,11 @@ impl WebSocket {
         self.protocol.as_ref()
     }
 
+    /// Return the headers of the server's handshake response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
     /// Closes the connection with a given code and (optional) reason.
     pub async fn close(self, code: CloseCode, reason: Option<Utf8Bytes>) -> Result<(), Error> {
         let mut inner = self.inner;
@@ -591,6 +691,22 @@ impl Stream for WebSocket {
                         return Poll::Ready(Some(Ok(msg)));
                     }
                 }
+                Some(Err(tungstenite::Error::Capacity(err))) => {
+                    // Tell the server why the connection is dropped, on a best-effort basis.
+                    let close = Message::Close(Some(CloseFrame {
+                        code: CloseCode::SIZE,
+                        reason: Utf8Bytes::from_static("Message too big"),
+                    }));
+                    if self
+                        .inner
+                        .start_send_unpin(close.into_tungstenite())
+                        .is_ok()
+                    {
+                        let _ = self.inner.poll_flush_unpin(cx);
+                    }
+
+                    return Poll::Ready(Some(Err(Error::body(err))));
+                }
                 Some(Err(err)) => return Poll::Ready(Some(Err(Error::body(err)))),
                 None => return Poll::Ready(None),
             }
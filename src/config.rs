@@ -0,0 +1,604 @@
+//! Declarative client configuration
+//!
+//! A [`ClientConfig`] describes the settings of a client as plain data implementing
+//! [`Deserialize`], so that it can be loaded from a TOML, JSON or YAML file managed by
+//! operations, and turned into a [`ClientBuilder`](crate::ClientBuilder) with
+//! [`ClientBuilder::from_config`](crate::ClientBuilder::from_config). The builder can then be
+//! customized further in code before it is built.
+//!
+//! Every field is optional, and a missing field keeps the default of the builder. Durations are
+//! either a number of seconds, such as `2.5`, or a string with a unit among `ms`, `s`, `m` and
+//! `h`, such as `"500ms"`. Unknown fields are rejected, so that a typo doesn't go unnoticed.
+//!
+//! ```toml
+//! emulation = "chrome_136"
+//! timeout = "30s"
+//! connect_timeout = "5s"
+//! proxies = ["http://proxy.internal:3128"]
+//! no_proxy = "localhost, .internal"
+//! pool_max_idle_per_host = 8
+//! min_tls_version = "TLSv1.2"
+//!
+//! [default_headers]
+//! x-team = "crawler"
+//! ```
+//!
+//! The same settings can be read from `WREQ_*` environment variables with
+//! [`ClientConfig::from_env`], and layered on top of those set in code with
+//! [`ClientBuilder::env_overrides`](crate::ClientBuilder::env_overrides), for operational
+//! overrides in containers.
+//!
+//! The crate doesn't ship emulation profiles, so the `emulation` name is resolved by the
+//! function given to [`ClientBuilder::from_config_with`](crate::ClientBuilder::from_config_with).
+
+use std::{error::Error as StdError, fmt, time::Duration};
+
+use http::{HeaderName, HeaderValue};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, MapAccess, Visitor},
+};
+
+use crate::{
+    ClientBuilder, EmulationProvider, Proxy,
+    error::{BoxError, Error},
+    proxy::NoProxy,
+    tls::TlsVersion,
+};
+
+/// The settings of a client, as loaded from a configuration file.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ClientConfig {
+    /// The name of the emulation profile, resolved by
+    /// [`ClientBuilder::from_config_with`](crate::ClientBuilder::from_config_with).
+    pub emulation: Option<String>,
+
+    /// The `User-Agent` header of every request.
+    pub user_agent: Option<String>,
+
+    /// The default headers of every request, in order.
+    #[serde(deserialize_with = "deserialize_headers")]
+    pub default_headers: Vec<(String, String)>,
+
+    /// The total timeout of a request, see [`ClientBuilder::timeout`].
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub timeout: Option<Duration>,
+
+    /// The connect timeout, see [`ClientBuilder::connect_timeout`].
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub connect_timeout: Option<Duration>,
+
+    /// The read timeout, see [`ClientBuilder::read_timeout`].
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub read_timeout: Option<Duration>,
+
+    /// The URLs of the proxies every request is sent through, for all schemes.
+    pub proxies: Vec<String>,
+
+    /// The hosts bypassing [`ClientConfig::proxies`], in the format of the `NO_PROXY`
+    /// environment variable.
+    pub no_proxy: Option<String>,
+
+    /// Whether to disable all proxies, including the system ones.
+    pub disable_proxies: bool,
+
+    /// The idle timeout of pooled connections, see [`ClientBuilder::pool_idle_timeout`].
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub pool_idle_timeout: Option<Duration>,
+
+    /// The maximum idle connections, see [`ClientBuilder::pool_max_idle`].
+    pub pool_max_idle: Option<usize>,
+
+    /// The maximum idle connections per host, see [`ClientBuilder::pool_max_idle_per_host`].
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// The maximum number of pooled connections, see [`ClientBuilder::pool_max_size`].
+    pub pool_max_size: Option<u32>,
+
+    /// Whether to only use HTTP/1.
+    pub http1_only: bool,
+
+    /// Whether to only use HTTP/2.
+    pub http2_only: bool,
+
+    /// Whether to only send requests to `https` URLs.
+    pub https_only: bool,
+
+    /// Whether to verify the certificates of servers, see [`ClientBuilder::cert_verification`].
+    pub cert_verification: Option<bool>,
+
+    /// Whether to verify the hostname of servers, see [`ClientBuilder::verify_hostname`].
+    pub verify_hostname: Option<bool>,
+
+    /// Whether to send the TLS Server Name Indication, see [`ClientBuilder::tls_sni`].
+    pub tls_sni: Option<bool>,
+
+    /// Whether to record [`TlsInfo`](crate::tls::TlsInfo) on responses.
+    pub tls_info: Option<bool>,
+
+    /// The minimum TLS version, such as `TLSv1.2`.
+    pub min_tls_version: Option<String>,
+
+    /// The maximum TLS version, such as `TLSv1.3`.
+    pub max_tls_version: Option<String>,
+}
+
+impl ClientConfig {
+    /// Loads the settings set by `WREQ_*` environment variables.
+    ///
+    /// Each field of the config is read from the variable named after it in upper case and
+    /// prefixed with `WREQ_`, such as `WREQ_TIMEOUT` or `WREQ_MIN_TLS_VERSION`, except for
+    /// [`ClientConfig::proxies`], read as a comma-separated list from `WREQ_PROXY`, and
+    /// [`ClientConfig::default_headers`], which can't be set from the environment. Booleans
+    /// are `true`, `false`, `1`, `0`, `yes`, `no`, `on` or `off`.
+    ///
+    /// Variables that are not set leave their field empty, and a variable that can't be parsed
+    /// is an error naming it.
+    pub fn from_env() -> crate::Result<ClientConfig> {
+        ClientConfig::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup<F>(lookup: F) -> crate::Result<ClientConfig>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        Ok(ClientConfig {
+            emulation: lookup("WREQ_EMULATION"),
+            user_agent: lookup("WREQ_USER_AGENT"),
+            default_headers: Vec::new(),
+            timeout: env_var(&lookup, "WREQ_TIMEOUT", parse_duration)?,
+            connect_timeout: env_var(&lookup, "WREQ_CONNECT_TIMEOUT", parse_duration)?,
+            read_timeout: env_var(&lookup, "WREQ_READ_TIMEOUT", parse_duration)?,
+            proxies: lookup("WREQ_PROXY")
+                .map(|proxies| {
+                    proxies
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|proxy| !proxy.is_empty())
+                        .map(str::to_owned)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            no_proxy: lookup("WREQ_NO_PROXY"),
+            disable_proxies: env_var(&lookup, "WREQ_DISABLE_PROXIES", parse_bool)?.unwrap_or(false),
+            pool_idle_timeout: env_var(&lookup, "WREQ_POOL_IDLE_TIMEOUT", parse_duration)?,
+            pool_max_idle: env_var(&lookup, "WREQ_POOL_MAX_IDLE", parse_number)?,
+            pool_max_idle_per_host: env_var(&lookup, "WREQ_POOL_MAX_IDLE_PER_HOST", parse_number)?,
+            pool_max_size: env_var(&lookup, "WREQ_POOL_MAX_SIZE", parse_number)?,
+            http1_only: env_var(&lookup, "WREQ_HTTP1_ONLY", parse_bool)?.unwrap_or(false),
+            http2_only: env_var(&lookup, "WREQ_HTTP2_ONLY", parse_bool)?.unwrap_or(false),
+            https_only: env_var(&lookup, "WREQ_HTTPS_ONLY", parse_bool)?.unwrap_or(false),
+            cert_verification: env_var(&lookup, "WREQ_CERT_VERIFICATION", parse_bool)?,
+            verify_hostname: env_var(&lookup, "WREQ_VERIFY_HOSTNAME", parse_bool)?,
+            tls_sni: env_var(&lookup, "WREQ_TLS_SNI", parse_bool)?,
+            tls_info: env_var(&lookup, "WREQ_TLS_INFO", parse_bool)?,
+            min_tls_version: lookup("WREQ_MIN_TLS_VERSION"),
+            max_tls_version: lookup("WREQ_MAX_TLS_VERSION"),
+        })
+    }
+
+    /// Applies the settings to `builder`, resolving the emulation profile with `emulation`.
+    ///
+    /// Settings that are invalid are recorded as errors of the builder.
+    pub(crate) fn apply<F>(&self, mut builder: ClientBuilder, emulation: F) -> ClientBuilder
+    where
+        F: FnOnce(&str) -> Option<EmulationProvider>,
+    {
+        // The emulation replaces the default headers, so it goes first.
+        if let Some(name) = &self.emulation {
+            builder = match emulation(name) {
+                Some(provider) => builder.emulation(provider).emulation_name(name),
+                None => builder.with_error("emulation", format!("unknown emulation `{name}`")),
+            };
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            builder = match HeaderValue::try_from(user_agent) {
+                Ok(value) => builder.user_agent(value),
+                Err(err) => builder.with_error("user_agent", err),
+            };
+        }
+        if !self.default_headers.is_empty() {
+            let mut headers = http::HeaderMap::new();
+            for (name, value) in &self.default_headers {
+                match (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+                    (Ok(name), Ok(value)) => {
+                        headers.append(name, value);
+                    }
+                    (Err(err), _) => builder = builder.with_error("default_headers", err),
+                    (_, Err(err)) => builder = builder.with_error("default_headers", err),
+                }
+            }
+            builder = builder.default_headers(headers);
+        }
+
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+
+        if self.disable_proxies {
+            builder = builder.no_proxy();
+        } else {
+            let no_proxy = self.no_proxy.as_deref().and_then(NoProxy::from_string);
+            for proxy in &self.proxies {
+                builder = match Proxy::all(proxy.as_str()) {
+                    Ok(proxy) => builder.proxy(proxy.no_proxy(no_proxy.clone())),
+                    Err(err) => builder.with_error("proxies", err),
+                };
+            }
+        }
+
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.pool_max_idle {
+            builder = builder.pool_max_idle(max);
+        }
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(max) = self.pool_max_size {
+            builder = builder.pool_max_size(max);
+        }
+
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        if self.http2_only {
+            builder = builder.http2_only();
+        }
+        if self.https_only {
+            builder = builder.https_only(true);
+        }
+
+        if let Some(enabled) = self.cert_verification {
+            builder = builder.cert_verification(enabled);
+        }
+        if let Some(enabled) = self.verify_hostname {
+            builder = builder.verify_hostname(enabled);
+        }
+        if let Some(enabled) = self.tls_sni {
+            builder = builder.tls_sni(enabled);
+        }
+        if let Some(enabled) = self.tls_info {
+            builder = builder.tls_info(enabled);
+        }
+        if let Some(name) = &self.min_tls_version {
+            builder = match parse_tls_version(name) {
+                Ok(version) => builder.min_tls_version(version),
+                Err(err) => builder.with_error("min_tls_version", err),
+            };
+        }
+        if let Some(name) = &self.max_tls_version {
+            builder = match parse_tls_version(name) {
+                Ok(version) => builder.max_tls_version(version),
+                Err(err) => builder.with_error("max_tls_version", err),
+            };
+        }
+
+        builder
+    }
+}
+
+/// Parses the environment variable `name`, if it is set.
+fn env_var<T>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> crate::Result<Option<T>> {
+    lookup(name)
+        .map(|value| {
+            parse(&value).ok_or_else(|| {
+                Error::builder(format!(
+                    "invalid value `{value}` of environment variable {name}"
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Option<T> {
+    s.trim().parse().ok()
+}
+
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// A snapshot of the effective settings of a [`Client`](crate::Client), for support bundles
+/// and debugging.
+///
+/// It is redacted: only the names of the default headers are recorded, and only the number
+/// of proxies, whose URLs may hold credentials. Durations are serialized in milliseconds.
+///
+/// Created with [`Client::config_snapshot`](crate::Client::config_snapshot).
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+pub struct ConfigSnapshot {
+    /// The name of the emulation profile, if the client was configured from a
+    /// [`ClientConfig`] naming one.
+    pub emulation: Option<String>,
+    /// The `User-Agent` default header.
+    pub user_agent: Option<String>,
+    /// The names of the default headers.
+    pub default_headers: Vec<String>,
+    /// The total timeout of a request.
+    #[serde(rename = "timeout_ms", serialize_with = "serialize_millis")]
+    pub timeout: Option<Duration>,
+    /// The connect timeout.
+    #[serde(rename = "connect_timeout_ms", serialize_with = "serialize_millis")]
+    pub connect_timeout: Option<Duration>,
+    /// The read timeout.
+    #[serde(rename = "read_timeout_ms", serialize_with = "serialize_millis")]
+    pub read_timeout: Option<Duration>,
+    /// The number of configured proxies.
+    pub proxies: usize,
+    /// Whether the system proxy settings are used.
+    pub system_proxy: bool,
+    /// The idle timeout of pooled connections.
+    #[serde(rename = "pool_idle_timeout_ms", serialize_with = "serialize_millis")]
+    pub pool_idle_timeout: Option<Duration>,
+    /// The maximum idle connections per host.
+    pub pool_max_idle_per_host: usize,
+    /// The maximum number of pooled connections.
+    pub pool_max_size: Option<u32>,
+    /// The maximum number of requests in flight.
+    pub max_in_flight: Option<usize>,
+    /// The HTTP versions used, `http1`, `http2` or `all`.
+    pub http_version: &'static str,
+    /// Whether only `https` URLs are allowed.
+    pub https_only: bool,
+    /// Whether a cookie store is used.
+    pub cookie_store: bool,
+    /// Whether server certificates are verified.
+    pub cert_verification: bool,
+    /// Whether server hostnames are verified.
+    pub verify_hostname: bool,
+    /// Whether the TLS Server Name Indication is sent.
+    pub tls_sni: bool,
+    /// Whether [`TlsInfo`](crate::tls::TlsInfo) is recorded on responses.
+    pub tls_info: bool,
+    /// The minimum TLS version, such as `TLSv1.2`.
+    pub min_tls_version: Option<&'static str>,
+    /// The maximum TLS version, such as `TLSv1.3`.
+    pub max_tls_version: Option<&'static str>,
+}
+
+fn serialize_millis<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => serializer.serialize_f64(duration.as_secs_f64() * 1000.0),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// An invalid option of a [`ClientBuilder`].
+///
+/// Building a client reports all its invalid options at once, see
+/// [`Error::invalid_options`](crate::Error::invalid_options).
+#[derive(Debug)]
+pub struct InvalidOption {
+    name: &'static str,
+    source: BoxError,
+}
+
+impl InvalidOption {
+    pub(crate) fn new(name: &'static str, source: impl Into<BoxError>) -> InvalidOption {
+        InvalidOption {
+            name,
+            source: source.into(),
+        }
+    }
+
+    /// Returns the name of the option, such as `user_agent`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns why the option is invalid.
+    pub fn error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        &*self.source
+    }
+}
+
+impl fmt::Display for InvalidOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.source)
+    }
+}
+
+/// The invalid options of a [`ClientBuilder`], the source of the error building the client.
+#[derive(Debug)]
+pub(crate) struct InvalidOptions(pub(crate) Vec<InvalidOption>);
+
+impl fmt::Display for InvalidOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid client options: ")?;
+        for (i, option) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{option}")?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for InvalidOptions {}
+
+fn parse_tls_version(name: &str) -> Result<TlsVersion, String> {
+    TlsVersion::from_name(name).ok_or_else(|| format!("unknown TLS version `{name}`"))
+}
+
+/// Parses a duration such as `500ms`, `30s`, `5m` or `1h`.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value.parse().ok()?;
+    let secs = match unit.trim() {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 60.0 * 60.0,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DurationVisitor;
+
+    impl Visitor<'_> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number of seconds or a duration such as \"500ms\"")
+        }
+
+        fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Duration, E> {
+            Ok(Duration::from_secs(secs))
+        }
+
+        fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Duration, E> {
+            u64::try_from(secs)
+                .map(Duration::from_secs)
+                .map_err(|_| E::invalid_value(de::Unexpected::Signed(secs), &self))
+        }
+
+        fn visit_f64<E: de::Error>(self, secs: f64) -> Result<Duration, E> {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|_| E::invalid_value(de::Unexpected::Float(secs), &self))
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Duration, E> {
+            parse_duration(s).ok_or_else(|| E::invalid_value(de::Unexpected::Str(s), &self))
+        }
+    }
+
+    deserializer.deserialize_any(DurationVisitor).map(Some)
+}
+
+fn deserialize_headers<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct HeadersVisitor;
+
+    impl<'de> Visitor<'de> for HeadersVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of header names to values")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            // Keep the order of the document, as header order is part of a fingerprint.
+            let mut headers = Vec::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some(entry) = map.next_entry()? {
+                headers.push(entry);
+            }
+            Ok(headers)
+        }
+    }
+
+    deserializer.deserialize_map(HeadersVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ClientConfig, parse_duration};
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("2.5"), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("1d"), None);
+        assert_eq!(parse_duration("s"), None);
+    }
+
+    #[test]
+    fn client_config_from_env() {
+        let vars = [
+            ("WREQ_TIMEOUT", "10s"),
+            ("WREQ_PROXY", "http://a:3128, socks5://b:1080"),
+            ("WREQ_HTTP2_ONLY", "yes"),
+            ("WREQ_POOL_MAX_SIZE", "16"),
+            ("WREQ_EMULATION", "firefox"),
+        ];
+        let lookup = |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        };
+
+        let config = ClientConfig::from_lookup(lookup).unwrap();
+        assert_eq!(config.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(config.proxies, ["http://a:3128", "socks5://b:1080"]);
+        assert!(config.http2_only);
+        assert!(!config.http1_only);
+        assert_eq!(config.pool_max_size, Some(16));
+        assert_eq!(config.emulation.as_deref(), Some("firefox"));
+        assert_eq!(config.connect_timeout, None);
+
+        let err = ClientConfig::from_lookup(|name| {
+            (name == "WREQ_HTTPS_ONLY").then(|| "maybe".to_owned())
+        })
+        .unwrap_err();
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.to_string().contains("WREQ_HTTPS_ONLY"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn client_config_from_json() {
+        let config: ClientConfig = serde_json::from_str(
+            r#"{
+                "timeout": "30s",
+                "connect_timeout": 5,
+                "proxies": ["http://proxy.internal:3128"],
+                "default_headers": {"x-b": "1", "x-a": "2"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(config.proxies, ["http://proxy.internal:3128"]);
+        assert_eq!(
+            config.default_headers,
+            [
+                ("x-b".to_owned(), "1".to_owned()),
+                ("x-a".to_owned(), "2".to_owned())
+            ]
+        );
+
+        assert!(serde_json::from_str::<ClientConfig>(r#"{"timeoutt": 1}"#).is_err());
+    }
+}
@@ -4,9 +4,10 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use arc_swap::ArcSwap;
 use http::uri::Scheme;
 use pin_project_lite::pin_project;
 use tls_conn::TlsConn;
@@ -20,6 +21,8 @@ use tower::{
 use tower_service::Service;
 
 pub(crate) use self::conn::{Conn, Unnameable};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
 use crate::{
     core::{
         client::{
@@ -30,10 +33,11 @@ use crate::{
     },
     dns::DynResolver,
     error::{BoxError, TimedOut, map_timeout_to_connector_error},
-    proxy::{Intercepted, Matcher as ProxyMatcher},
+    events::ConnectionEvents,
+    proxy::{Intercepted, Matcher as ProxyMatcher, ProxyInfo},
     tls::{
-        CertStore, EstablishedConn, HttpsConnector, Identity, KeyLogPolicy, MaybeHttpsStream,
-        TlsConfig, TlsConnector, TlsConnectorBuilder, TlsInfo, TlsVersion,
+        AlpnNegotiation, CertStore, EstablishedConn, HttpsConnector, Identity, KeyLogPolicy,
+        MaybeHttpsStream, TlsConfig, TlsConnector, TlsConnectorBuilder, TlsInfo, TlsVersion,
     },
 };
 
@@ -48,9 +52,42 @@ pub(crate) type BoxedConnectorService = BoxCloneSyncService<Unnameable, Conn, Bo
 pub(crate) type BoxedConnectorLayer =
     BoxCloneSyncServiceLayer<BoxedConnectorService, Unnameable, Conn, BoxError>;
 
+/// The proxies of a connector, which can be replaced at runtime.
+pub(crate) type SharedProxies = Arc<ArcSwap<Vec<ProxyMatcher>>>;
+
+/// The TLS state of a connector, which can be replaced at runtime.
+pub(crate) type SharedTls = Arc<ArcSwap<TlsState>>;
+
+/// The TLS connectors of a [`ConnectorService`], replaced as a whole when the certificate store
+/// changes.
+pub(crate) struct TlsState {
+    builder: TlsConnectorBuilder,
+    config: TlsConfig,
+    connector: TlsConnector,
+}
+
+impl TlsState {
+    fn new(builder: TlsConnectorBuilder, config: TlsConfig) -> crate::Result<TlsState> {
+        let connector = builder.build(config.clone())?;
+        Ok(TlsState {
+            builder,
+            config,
+            connector,
+        })
+    }
+
+    /// Returns a copy of this state verifying peers with the given certificate store.
+    pub(crate) fn with_cert_store(&self, cert_store: CertStore) -> crate::Result<TlsState> {
+        TlsState::new(
+            self.builder.clone().cert_store(cert_store),
+            self.config.clone(),
+        )
+    }
+}
+
 pub(crate) struct ConnectorBuilder {
     http: HttpConnector,
-    proxies: Arc<Vec<ProxyMatcher>>,
+    proxies: SharedProxies,
     verbose: verbose::Wrapper,
     /// When there is a single timeout layer and no other layers,
     /// we embed it directly inside our base Service::call().
@@ -63,6 +100,9 @@ pub(crate) struct ConnectorBuilder {
 
     tls_info: bool,
     tls_builder: TlsConnectorBuilder,
+    events: Option<Arc<dyn ConnectionEvents>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 impl ConnectorBuilder {
@@ -140,6 +180,29 @@ impl ConnectorBuilder {
         self
     }
 
+    /// Set the listener notified of the connections opened by this connector.
+    #[inline(always)]
+    pub(crate) fn connection_events(
+        mut self,
+        events: Option<Arc<dyn ConnectionEvents>>,
+    ) -> ConnectorBuilder {
+        self.events = events;
+        self
+    }
+
+    /// Set the metrics recording the connections opened by this connector.
+    #[inline(always)]
+    pub(crate) fn metrics(
+        #[allow(unused_mut)] mut self,
+        #[cfg(feature = "metrics")] metrics: Option<Metrics>,
+    ) -> ConnectorBuilder {
+        #[cfg(feature = "metrics")]
+        {
+            self.metrics = metrics;
+        }
+        self
+    }
+
     /// Set connecting verbose mode.
     #[inline(always)]
     pub(crate) fn verbose(mut self, enabled: bool) -> ConnectorBuilder {
@@ -219,15 +282,20 @@ impl ConnectorBuilder {
         self
     }
 
-    /// Builds the connector with the provided TLS configuration and optional layers.
+    /// Builds the connector with the provided TLS configuration and optional layers, returning it
+    /// along with its shared TLS state.
     pub(crate) fn build(
         self,
         tls_config: TlsConfig,
         layers: Option<Vec<BoxedConnectorLayer>>,
-    ) -> crate::Result<Connector> {
+    ) -> crate::Result<(Connector, SharedTls)> {
+        let tls = Arc::new(ArcSwap::from_pointee(TlsState::new(
+            self.tls_builder,
+            tls_config,
+        )?));
         let mut service = ConnectorService {
             http: self.http,
-            tls: self.tls_builder.build(tls_config)?,
+            tls: tls.clone(),
             proxies: self.proxies,
             verbose: self.verbose,
             // The timeout is initially set to None and will be reassigned later
@@ -237,7 +305,9 @@ impl ConnectorBuilder {
             #[cfg(feature = "socks")]
             resolver: self.resolver,
             tls_info: self.tls_info,
-            tls_builder: Arc::new(self.tls_builder),
+            events: self.events,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         };
 
         if let Some(layers) = layers {
@@ -266,7 +336,7 @@ impl ConnectorBuilder {
                         .map_err(map_timeout_to_connector_error)
                         .service(service);
                     let service = BoxCloneSyncService::new(service);
-                    Ok(Connector::WithLayers(service))
+                    Ok((Connector::WithLayers(service), tls))
                 }
                 None => {
                     // no timeout, but still map err
@@ -276,13 +346,13 @@ impl ConnectorBuilder {
                         .map_err(map_timeout_to_connector_error)
                         .service(service);
                     let service = BoxCloneSyncService::new(service);
-                    Ok(Connector::WithLayers(service))
+                    Ok((Connector::WithLayers(service), tls))
                 }
             }
         } else {
             // we have no user-provided layers, only use concrete types
             service.timeout = self.timeout;
-            Ok(Connector::Simple(service))
+            Ok((Connector::Simple(service), tls))
         }
     }
 }
@@ -297,10 +367,7 @@ pub(crate) enum Connector {
 }
 
 impl Connector {
-    pub(crate) fn builder(
-        proxies: Arc<Vec<ProxyMatcher>>,
-        resolver: DynResolver,
-    ) -> ConnectorBuilder {
+    pub(crate) fn builder(proxies: SharedProxies, resolver: DynResolver) -> ConnectorBuilder {
         ConnectorBuilder {
             #[cfg(feature = "socks")]
             resolver: resolver.clone(),
@@ -315,6 +382,9 @@ impl Connector {
             tcp_nodelay: false,
             tls_info: false,
             tls_builder: TlsConnector::builder(),
+            events: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 }
@@ -344,8 +414,8 @@ impl Service<ConnRequest> for Connector {
 #[derive(Clone)]
 pub(crate) struct ConnectorService {
     http: HttpConnector,
-    tls: TlsConnector,
-    proxies: Arc<Vec<ProxyMatcher>>,
+    tls: SharedTls,
+    proxies: SharedProxies,
     verbose: verbose::Wrapper,
     /// When there is a single timeout layer and no other layers,
     /// we embed it directly inside our base Service::call().
@@ -357,10 +427,12 @@ pub(crate) struct ConnectorService {
     resolver: DynResolver,
 
     // TLS configuration
-    // Note: these are not used in the `TlsConnectorBuilder` but rather
+    // Note: this is not used in the `TlsConnectorBuilder` but rather
     // in the `TlsConnector` that is built from it.
     tls_info: bool,
-    tls_builder: Arc<TlsConnectorBuilder>,
+    events: Option<Arc<dyn ConnectionEvents>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 impl ConnectorService {
@@ -373,9 +445,15 @@ impl ConnectorService {
     ) -> Result<HttpsConnector<HttpConnector>, BoxError> {
         let ex_data = req.ex_data();
         http.set_tcp_connect_options(ex_data.tcp_connect_options().cloned());
+        http.set_pinned_addrs(ex_data.pinned_addrs().cloned());
+        http.set_preferred_addr(req.preferred_addr());
+        if let Some(timeout) = req.connect_timeout() {
+            http.set_connect_timeout(Some(timeout));
+        }
+        let state = self.tls.load();
         let tls = match ex_data.tls_config() {
-            Some(cfg) => self.tls_builder.build(cfg.clone())?,
-            None => self.tls.clone(),
+            Some(cfg) => state.builder.build(cfg.clone())?,
+            None => state.connector.clone(),
         };
         Ok(HttpsConnector::with_connector(http, tls))
     }
@@ -388,6 +466,11 @@ impl ConnectorService {
         let uri = req.uri().clone();
         let mut http = self.http.clone();
 
+        // The resolver of the request resolves its own host, not the one of a proxy.
+        if let Some(resolver) = req.ex_data().dns_resolver().filter(|_| !is_proxy) {
+            http.set_resolver(resolver.clone());
+        }
+
         // Disable Nagle's algorithm for TLS handshake
         //
         // https://www.openssl.org/docs/man1.1.1/man3/SSL_connect.html#NOTES
@@ -415,6 +498,9 @@ impl ConnectorService {
             inner,
             is_proxy,
             tls_info: self.tls_info,
+            proxy: None,
+            #[cfg(feature = "metrics")]
+            open: None,
         })
     }
 
@@ -441,14 +527,15 @@ impl ConnectorService {
             } {
                 trace!("connecting via SOCKS proxy: {:?}", proxy_uri);
 
-                let mut socks = Socks::new_with_resolver(
-                    self.http.clone(),
-                    self.resolver.clone(),
-                    proxy_uri.clone(),
-                )
-                .with_auth(proxy.raw_auth())
-                .with_version(version)
-                .with_local_dns(dns_resolve);
+                let resolver = match req.ex_data().dns_resolver() {
+                    Some(resolver) => resolver.clone(),
+                    None => self.resolver.clone(),
+                };
+                let mut socks =
+                    Socks::new_with_resolver(self.http.clone(), resolver, proxy_uri.clone())
+                        .with_auth(proxy.raw_auth())
+                        .with_version(version)
+                        .with_local_dns(dns_resolve);
 
                 let conn = socks.call(uri.clone()).await?;
 
@@ -464,12 +551,18 @@ impl ConnectorService {
                         }),
                         is_proxy: false,
                         tls_info: self.tls_info,
+                        proxy: None,
+                        #[cfg(feature = "metrics")]
+                        open: None,
                     })
                 } else {
                     Ok(Conn {
                         inner: self.verbose.wrap(conn),
                         is_proxy: false,
                         tls_info: false,
+                        proxy: None,
+                        #[cfg(feature = "metrics")]
+                        open: None,
                     })
                 };
             }
@@ -489,6 +582,14 @@ impl ConnectorService {
                 tunnel = tunnel.with_headers(headers.clone());
             }
 
+            if let Some(headers) = proxy.tunnel_headers() {
+                tunnel = tunnel.with_headers(headers.clone());
+            }
+
+            if let Some(headers) = req.ex_data().tunnel_headers() {
+                tunnel = tunnel.with_headers(headers.clone());
+            }
+
             // We don't wrap this again in an HttpsConnector since that uses Maybe,
             // and we know this is definitely HTTPS.
             let tunneled = tunnel.call(uri).await?;
@@ -503,6 +604,9 @@ impl ConnectorService {
                 }),
                 is_proxy: false,
                 tls_info: self.tls_info,
+                proxy: None,
+                #[cfg(feature = "metrics")]
+                open: None,
             });
         }
 
@@ -522,11 +626,25 @@ impl ConnectorService {
             .and_then(|scheme| scheme.intercept(req.uri()))
             .or_else(|| {
                 self.proxies
+                    .load()
                     .iter()
                     .find_map(|prox| prox.intercept(req.uri()))
             });
 
-        let timeout = self.timeout;
+        let timeout = req.connect_timeout().or(self.timeout);
+        let events = self.events.clone();
+        #[cfg(feature = "metrics")]
+        let metrics = self.metrics.clone();
+
+        let uri = events.as_ref().map(|events| {
+            events.connect_started(req.uri());
+            (req.uri().clone(), Instant::now())
+        });
+
+        let proxy = intercepted
+            .as_ref()
+            .map(|intercepted| ProxyInfo::new(intercepted.uri().clone()));
+
         let fut = async {
             if let Some(intercepted) = intercepted {
                 self.connect_with_proxy(req, intercepted).await
@@ -535,13 +653,46 @@ impl ConnectorService {
             }
         };
 
-        if let Some(to) = timeout {
+        let mut res = if let Some(to) = timeout {
             tokio::time::timeout(to, fut)
                 .await
-                .map_err(|_| BoxError::from(TimedOut))?
+                .unwrap_or_else(|_| Err(BoxError::from(TimedOut)))
         } else {
             fut.await
+        };
+
+        if let (Some(events), Some((uri, start))) = (events, uri) {
+            let veto = match res {
+                Ok(ref conn) => conn
+                    .inner
+                    .alpn_negotiation()
+                    .and_then(|negotiation| events.alpn_negotiated(&uri, &negotiation).err()),
+                Err(_) => None,
+            };
+            if let Some(err) = veto {
+                res = Err(err);
+            }
+
+            match res {
+                Ok(ref conn) => {
+                    if let Some(info) = conn.inner.tls_info() {
+                        events.tls_handshake_done(&uri, &info);
+                    }
+                    events.connect_succeeded(&uri, start.elapsed());
+                }
+                Err(ref err) => events.connect_failed(&uri, &**err),
+            }
         }
+
+        let mut conn = res?;
+        conn.proxy = proxy;
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = metrics {
+            conn.open = Some(metrics.open_connection());
+        }
+
+        Ok(conn)
     }
 }
 
@@ -563,28 +714,37 @@ impl Service<ConnRequest> for ConnectorService {
 
 trait TlsInfoFactory {
     fn tls_info(&self) -> Option<TlsInfo>;
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation>;
 }
 
 impl TlsInfoFactory for TcpStream {
     fn tls_info(&self) -> Option<TlsInfo> {
         None
     }
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+        None
+    }
 }
 
 impl<T: TlsInfoFactory> TlsInfoFactory for TokioIo<T> {
     fn tls_info(&self) -> Option<TlsInfo> {
         self.inner().tls_info()
     }
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+        self.inner().alpn_negotiation()
+    }
 }
 
 impl TlsInfoFactory for SslStream<TcpStream> {
     fn tls_info(&self) -> Option<TlsInfo> {
-        self.ssl()
-            .peer_certificate()
-            .and_then(|c| c.to_der().ok())
-            .map(|c| TlsInfo {
-                peer_certificate: Some(c),
-            })
+        Some(TlsInfo::new(self.ssl()))
+    }
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+        AlpnNegotiation::new(self.ssl())
     }
 }
 
@@ -595,16 +755,22 @@ impl TlsInfoFactory for MaybeHttpsStream<TcpStream> {
             MaybeHttpsStream::Http(_) => None,
         }
     }
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+        match self {
+            MaybeHttpsStream::Https(tls) => tls.alpn_negotiation(),
+            MaybeHttpsStream::Http(_) => None,
+        }
+    }
 }
 
 impl TlsInfoFactory for SslStream<TokioIo<MaybeHttpsStream<TcpStream>>> {
     fn tls_info(&self) -> Option<TlsInfo> {
-        self.ssl()
-            .peer_certificate()
-            .and_then(|c| c.to_der().ok())
-            .map(|c| TlsInfo {
-                peer_certificate: Some(c),
-            })
+        Some(TlsInfo::new(self.ssl()))
+    }
+
+    fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+        AlpnNegotiation::new(self.ssl())
     }
 }
 
@@ -635,22 +801,31 @@ mod conn {
             pub(super) inner: BoxConn,
             pub(super) is_proxy: bool,
             pub(super) tls_info: bool,
+            pub(super) proxy: Option<ProxyInfo>,
+            #[cfg(feature = "metrics")]
+            pub(super) open: Option<crate::metrics::OpenConnection>,
         }
     }
 
     impl Connection for Conn {
         fn connected(&self) -> Connected {
-            let connected = self.inner.connected().proxy(self.is_proxy);
+            let mut connected = self.inner.connected().proxy(self.is_proxy);
 
             if self.tls_info {
                 if let Some(tls_info) = self.inner.tls_info() {
-                    connected.extra(tls_info)
-                } else {
-                    connected
+                    connected = connected.extra(tls_info);
                 }
-            } else {
-                connected
             }
+
+            if let Some(negotiation) = self.inner.alpn_negotiation() {
+                connected = connected.extra(negotiation);
+            }
+
+            if let Some(ref proxy) = self.proxy {
+                connected = connected.extra(proxy.clone());
+            }
+
+            connected
         }
     }
 
@@ -714,7 +889,7 @@ mod tls_conn {
     };
     use tokio_boring2::SslStream;
 
-    use super::{TlsInfo, TlsInfoFactory};
+    use super::{AlpnNegotiation, TlsInfo, TlsInfoFactory};
     use crate::{
         core::{
             client::connect::{Connected, Connection},
@@ -810,6 +985,10 @@ mod tls_conn {
         fn tls_info(&self) -> Option<TlsInfo> {
             self.inner.tls_info()
         }
+
+        fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+            self.inner.alpn_negotiation()
+        }
     }
 }
 
@@ -854,7 +1033,7 @@ mod verbose {
                 client::connect::{Connected, Connection},
                 rt::{Read, ReadBufCursor, Write},
             },
-            tls::TlsInfo,
+            tls::{AlpnNegotiation, TlsInfo},
             util::Escape,
         };
 
@@ -954,6 +1133,10 @@ mod verbose {
             fn tls_info(&self) -> Option<TlsInfo> {
                 self.inner.tls_info()
             }
+
+            fn alpn_negotiation(&self) -> Option<AlpnNegotiation> {
+                self.inner.alpn_negotiation()
+            }
         }
 
         struct Vectored<'a, 'b> {
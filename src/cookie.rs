@@ -1,11 +1,12 @@
 //! HTTP Cookies
 
-use std::{borrow::Cow, convert::TryInto, fmt, time::SystemTime};
+use std::{borrow::Cow, convert::TryInto, fmt, sync::Arc, time::SystemTime};
 
 use bytes::BufMut;
 pub use cookie_crate::{Cookie as RawCookie, Expiration, SameSite, time::Duration};
 
 use crate::{
+    core::{common::timer::Clock, rt::Clock as _},
     error::Error,
     header::{HeaderValue, SET_COOKIE},
     sync::RwLock,
@@ -20,6 +21,18 @@ pub trait CookieStore: Send + Sync {
     fn cookies(&self, url: &url::Url) -> Option<Vec<HeaderValue>>;
 }
 
+impl<C: CookieStore + ?Sized> CookieStore for Arc<C> {
+    #[inline]
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
+        (**self).set_cookies(cookie_headers, url)
+    }
+
+    #[inline]
+    fn cookies(&self, url: &url::Url) -> Option<Vec<HeaderValue>> {
+        (**self).cookies(url)
+    }
+}
+
 /// A single HTTP cookie.
 #[derive(Debug, Clone)]
 pub struct Cookie<'a>(cookie_crate::Cookie<'a>);
@@ -28,13 +41,87 @@ pub struct Cookie<'a>(cookie_crate::Cookie<'a>);
 #[derive(Debug, Clone)]
 pub struct CookieBuilder<'a>(cookie_crate::CookieBuilder<'a>);
 
+/// A [Public Suffix List](https://publicsuffix.org/) used to reject cookies set for
+/// public suffixes.
+///
+/// When a [`Jar`] is configured with a list, any cookie whose `Domain` attribute is a
+/// public suffix (e.g. `co.uk` or `github.io`) is rejected, the same way browsers do.
+///
+/// # Optional
+///
+/// This requires the optional `psl` feature to be enabled.
+#[cfg(feature = "psl")]
+#[derive(Debug, Clone)]
+pub struct PublicSuffixList(publicsuffix::List);
+
+/// The serialized cookie formats understood by [`Jar::import`] and [`Jar::export`].
+///
+/// # Optional
+///
+/// This requires the optional `json` feature to be enabled.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CookieFormat {
+    /// The JSON array produced by browser cookie export extensions for Chrome and Firefox
+    /// (e.g. *EditThisCookie* or *Cookie-Editor*).
+    Browser,
+    /// The Puppeteer/Playwright `storageState` JSON document.
+    ///
+    /// A bare JSON array of cookies, as returned by Puppeteer's `page.cookies()`, is
+    /// accepted on import as well.
+    StorageState,
+}
+
 /// A good default `CookieStore` implementation.
 ///
 /// This is the implementation used when simply calling `cookie_store(true)`.
 /// This type is exposed to allow creating one and filling it with some
 /// existing cookies more easily, before creating a `Client`.
 #[derive(Debug)]
-pub struct Jar(RwLock<cookie_store::CookieStore>);
+pub struct Jar(RwLock<cookie_store::CookieStore>, Clock);
+
+/// A `CookieStore` wrapper that restricts which cookies are stored and sent.
+///
+/// This allows a single shared store (e.g. an `Arc<Jar>`) to be used by several clients with
+/// different trust levels: one client may be allowed to update the jar, while another only
+/// reads from it, or only sees the cookies of a handful of domains.
+///
+/// Rules are evaluated in this order:
+///
+/// 1. Denied domains and names always win.
+/// 2. If any domains (or names) are allowed, everything else is rejected.
+/// 3. With `secure_only`, only `Secure` cookies are stored and cookies are only sent over `https`.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use wreq::cookie::{FilteredStore, Jar};
+///
+/// let jar = Arc::new(Jar::default());
+///
+/// let store = FilteredStore::new(jar.clone())
+///     .read_only(true)
+///     .allow_domain("example.com")
+///     .deny_name("session");
+///
+/// let client = wreq::Client::builder()
+///     .cookie_provider(Arc::new(store))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilteredStore<S> {
+    inner: S,
+    read_only: bool,
+    secure_only: bool,
+    allowed_domains: Vec<String>,
+    denied_domains: Vec<String>,
+    allowed_names: Vec<String>,
+    denied_names: Vec<String>,
+}
 
 // ===== impl Cookie =====
 impl<'a> Cookie<'a> {
@@ -219,6 +306,208 @@ impl<'c> CookieBuilder<'c> {
     }
 }
 
+// ===== impl PublicSuffixList =====
+#[cfg(feature = "psl")]
+impl PublicSuffixList {
+    /// The official URL of the list.
+    pub const URL: &'static str = publicsuffix::LIST_URL;
+
+    /// Parses a list in the `public_suffix_list.dat` format from raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the list is not UTF-8 encoded or its format is invalid.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<PublicSuffixList> {
+        publicsuffix::List::from_bytes(bytes)
+            .map(PublicSuffixList)
+            .map_err(Error::builder)
+    }
+
+    /// Returns true if `domain` is listed as a public suffix.
+    pub fn is_public_suffix(&self, domain: &str) -> bool {
+        use publicsuffix::Psl;
+
+        let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+        self.0
+            .suffix(domain.as_bytes())
+            .filter(publicsuffix::Suffix::is_known)
+            .is_some_and(|suffix| suffix == domain.as_bytes())
+    }
+
+    /// Returns the registrable domain of `host`, such as `example.co.uk` for
+    /// `www.example.co.uk`.
+    pub(crate) fn registrable_domain(&self, host: &str) -> Option<String> {
+        use publicsuffix::Psl;
+
+        let host = host.to_ascii_lowercase();
+        self.0
+            .domain(host.as_bytes())
+            .filter(|domain| domain.suffix().is_known())
+            .and_then(|domain| {
+                std::str::from_utf8(domain.as_bytes())
+                    .ok()
+                    .map(str::to_owned)
+            })
+    }
+}
+
+#[cfg(feature = "psl")]
+impl std::str::FromStr for PublicSuffixList {
+    type Err = Error;
+
+    fn from_str(s: &str) -> crate::Result<PublicSuffixList> {
+        s.parse().map(PublicSuffixList).map_err(Error::builder)
+    }
+}
+
+// ===== impl FilteredStore =====
+impl<S: CookieStore> FilteredStore<S> {
+    /// Wraps `inner` without any restriction.
+    pub fn new(inner: S) -> FilteredStore<S> {
+        FilteredStore {
+            inner,
+            read_only: false,
+            secure_only: false,
+            allowed_domains: Vec::new(),
+            denied_domains: Vec::new(),
+            allowed_names: Vec::new(),
+            denied_names: Vec::new(),
+        }
+    }
+
+    /// Never store cookies received in responses, only send the existing ones.
+    ///
+    /// Default is `false`.
+    pub fn read_only(mut self, enabled: bool) -> FilteredStore<S> {
+        self.read_only = enabled;
+        self
+    }
+
+    /// Only store cookies with the `Secure` attribute, and only send cookies over `https`.
+    ///
+    /// Default is `false`.
+    pub fn secure_only(mut self, enabled: bool) -> FilteredStore<S> {
+        self.secure_only = enabled;
+        self
+    }
+
+    /// Allow cookies for `domain` and its subdomains.
+    ///
+    /// Once a domain is allowed, cookies for any other domain are rejected.
+    pub fn allow_domain<D: Into<String>>(mut self, domain: D) -> FilteredStore<S> {
+        self.allowed_domains.push(normalize_domain(domain.into()));
+        self
+    }
+
+    /// Reject cookies for `domain` and its subdomains.
+    pub fn deny_domain<D: Into<String>>(mut self, domain: D) -> FilteredStore<S> {
+        self.denied_domains.push(normalize_domain(domain.into()));
+        self
+    }
+
+    /// Allow cookies named `name`.
+    ///
+    /// Once a name is allowed, cookies with any other name are rejected.
+    pub fn allow_name<N: Into<String>>(mut self, name: N) -> FilteredStore<S> {
+        self.allowed_names.push(name.into());
+        self
+    }
+
+    /// Reject cookies named `name`.
+    pub fn deny_name<N: Into<String>>(mut self, name: N) -> FilteredStore<S> {
+        self.denied_names.push(name.into());
+        self
+    }
+
+    /// Returns a reference to the wrapped store.
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Consumes the wrapper, returning the wrapped store.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn domain_allowed(&self, domain: &str) -> bool {
+        let domain = domain.trim_start_matches('.');
+        let matches = |rule: &String| {
+            domain.eq_ignore_ascii_case(rule)
+                || (domain.len() > rule.len()
+                    && domain.as_bytes()[domain.len() - rule.len() - 1] == b'.'
+                    && domain[domain.len() - rule.len()..].eq_ignore_ascii_case(rule))
+        };
+
+        if self.denied_domains.iter().any(matches) {
+            return false;
+        }
+
+        self.allowed_domains.is_empty() || self.allowed_domains.iter().any(matches)
+    }
+
+    fn name_allowed(&self, name: &str) -> bool {
+        if self.denied_names.iter().any(|rule| rule == name) {
+            return false;
+        }
+
+        self.allowed_names.is_empty() || self.allowed_names.iter().any(|rule| rule == name)
+    }
+
+    fn url_allowed(&self, url: &url::Url) -> bool {
+        if self.secure_only && url.scheme() != "https" {
+            return false;
+        }
+
+        url.host_str().is_some_and(|host| self.domain_allowed(host))
+    }
+}
+
+impl<S: CookieStore> CookieStore for FilteredStore<S> {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
+        if self.read_only || !self.url_allowed(url) {
+            return;
+        }
+
+        let mut cookies = cookie_headers.filter(|value| {
+            Cookie::parse(value).is_ok_and(|cookie| {
+                self.name_allowed(cookie.name())
+                    && (!self.secure_only || cookie.secure())
+                    && cookie
+                        .domain()
+                        .is_none_or(|domain| self.domain_allowed(domain))
+            })
+        });
+
+        self.inner.set_cookies(&mut cookies, url);
+    }
+
+    fn cookies(&self, url: &url::Url) -> Option<Vec<HeaderValue>> {
+        if !self.url_allowed(url) {
+            return None;
+        }
+
+        let mut cookies = self.inner.cookies(url)?;
+        if !self.allowed_names.is_empty() || !self.denied_names.is_empty() {
+            cookies.retain(|value| {
+                let name = value.as_bytes().split(|b| *b == b'=').next().unwrap_or(&[]);
+                std::str::from_utf8(name).is_ok_and(|name| self.name_allowed(name.trim()))
+            });
+        }
+
+        if cookies.is_empty() {
+            None
+        } else {
+            Some(cookies)
+        }
+    }
+}
+
+fn normalize_domain(domain: String) -> String {
+    domain.trim_start_matches('.').to_ascii_lowercase()
+}
+
 pub(crate) fn extract_response_cookies(
     headers: &http::HeaderMap,
 ) -> impl Iterator<Item = crate::Result<Cookie<'_>>> {
@@ -227,6 +516,67 @@ pub(crate) fn extract_response_cookies(
 
 // ===== impl Jar =====
 impl Jar {
+    /// Creates an empty jar that rejects cookies set for public suffixes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wreq::cookie::{Jar, PublicSuffixList};
+    ///
+    /// let list = "// ===BEGIN ICANN DOMAINS===\nco.uk\n// ===END ICANN DOMAINS===\n"
+    ///     .parse::<PublicSuffixList>()
+    ///     .unwrap();
+    /// let jar = Jar::with_public_suffix_list(list);
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `psl` feature to be enabled.
+    #[cfg(feature = "psl")]
+    pub fn with_public_suffix_list(list: PublicSuffixList) -> Jar {
+        Jar(
+            RwLock::new(cookie_store::CookieStore::default().with_suffix_list(list.0)),
+            Clock::default(),
+        )
+    }
+
+    /// Expires the cookies of this jar by the system time of the given clock, instead of the
+    /// system clock.
+    ///
+    /// Cookies expired by the system clock are never sent, so a clock running ahead of it,
+    /// like a [`TokioClock`](crate::rt::TokioClock) under a paused tokio runtime, expires
+    /// cookies as it is advanced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wreq::{cookie::Jar, rt::TokioClock};
+    ///
+    /// let jar = Jar::default().with_clock(TokioClock::new());
+    /// ```
+    pub fn with_clock<C>(mut self, clock: C) -> Jar
+    where
+        C: crate::rt::Clock + Send + Sync + 'static,
+    {
+        self.1 = Clock::new(clock);
+        self
+    }
+
+    /// Replaces the public suffix list used by this jar.
+    ///
+    /// This can be called at runtime, e.g. after downloading a fresh copy of the list from
+    /// [`PublicSuffixList::URL`]. Cookies already in the jar are kept; only cookies stored
+    /// afterwards are validated against the new list.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `psl` feature to be enabled.
+    #[cfg(feature = "psl")]
+    pub fn set_public_suffix_list(&self, list: PublicSuffixList) {
+        let mut store = self.0.write();
+        *store = std::mem::take(&mut *store).with_suffix_list(list.0);
+    }
+
     /// Add a cookie str to this jar.
     ///
     /// # Example
@@ -326,6 +676,225 @@ impl Jar {
     }
 }
 
+#[cfg(feature = "json")]
+impl Jar {
+    /// Imports cookies exported from a real browser into this jar.
+    ///
+    /// Expired cookies are skipped, and cookies that the jar would not accept from a
+    /// response (e.g. invalid domains) are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wreq::cookie::{CookieFormat, Jar};
+    ///
+    /// let state = r#"{"cookies":[{"name":"sid","value":"1","domain":".example.com",
+    ///     "path":"/","expires":-1,"httpOnly":true,"secure":true,"sameSite":"Lax"}]}"#;
+    ///
+    /// let jar = Jar::default();
+    /// jar.import(CookieFormat::StorageState, state).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `input` is not valid JSON in the given format.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature to be enabled.
+    pub fn import(&self, format: CookieFormat, input: &str) -> crate::Result<()> {
+        let cookies = match format {
+            CookieFormat::Browser => serde_json::from_str::<Vec<ExportedCookie>>(input),
+            CookieFormat::StorageState => {
+                serde_json::from_str::<StorageState>(input).map(StorageState::into_cookies)
+            }
+        }
+        .map_err(Error::decode)?;
+
+        let mut store = self.0.write();
+        for cookie in cookies {
+            if let Some((cookie, url)) = cookie.into_raw() {
+                let _ = store.insert_raw(&cookie, &url);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Exports the unexpired cookies of this jar so they can be loaded into a browser.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the cookies cannot be serialized.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature to be enabled.
+    pub fn export(&self, format: CookieFormat) -> crate::Result<String> {
+        let now = self.now();
+        let cookies = self
+            .0
+            .read()
+            .iter_unexpired()
+            .filter(|cookie| !cookie.expires_by(&now))
+            .map(|cookie| ExportedCookie::from_stored(cookie, format))
+            .collect::<Vec<_>>();
+
+        match format {
+            CookieFormat::Browser => serde_json::to_string(&cookies),
+            CookieFormat::StorageState => serde_json::to_string(&StorageState::Document {
+                cookies,
+                origins: Vec::new(),
+            }),
+        }
+        .map_err(Error::builder)
+    }
+}
+
+/// A cookie as it appears in browser exports and `storageState` documents.
+#[cfg(feature = "json")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedCookie {
+    name: String,
+    value: String,
+    domain: String,
+    #[serde(default = "ExportedCookie::default_path")]
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiration_date: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    host_only: Option<bool>,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    same_site: Option<String>,
+}
+
+/// The top-level `storageState` document, or a bare cookie array.
+#[cfg(feature = "json")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum StorageState {
+    Document {
+        cookies: Vec<ExportedCookie>,
+        #[serde(default)]
+        origins: Vec<serde_json::Value>,
+    },
+    Cookies(Vec<ExportedCookie>),
+}
+
+#[cfg(feature = "json")]
+impl StorageState {
+    fn into_cookies(self) -> Vec<ExportedCookie> {
+        match self {
+            StorageState::Document { cookies, .. } | StorageState::Cookies(cookies) => cookies,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl ExportedCookie {
+    fn default_path() -> String {
+        String::from("/")
+    }
+
+    fn from_stored(cookie: &cookie_store::Cookie<'static>, format: CookieFormat) -> Self {
+        let (domain, host_only) = match cookie.domain {
+            cookie_store::CookieDomain::HostOnly(ref domain) => (domain.clone(), true),
+            ref domain => (format!(".{}", String::from(domain)), false),
+        };
+
+        let expires = match cookie.expires {
+            cookie_store::CookieExpiration::AtUtc(ref at) => Some(at.unix_timestamp() as f64),
+            cookie_store::CookieExpiration::SessionEnd => None,
+        };
+
+        let same_site = cookie
+            .same_site()
+            .map(|same_site| match (format, same_site) {
+                (CookieFormat::Browser, SameSite::Strict) => "strict",
+                (CookieFormat::Browser, SameSite::Lax) => "lax",
+                (CookieFormat::Browser, SameSite::None) => "no_restriction",
+                (_, SameSite::Strict) => "Strict",
+                (_, SameSite::Lax) => "Lax",
+                (_, SameSite::None) => "None",
+            });
+
+        let mut exported = ExportedCookie {
+            name: cookie.name().to_owned(),
+            value: cookie.value().to_owned(),
+            domain,
+            path: String::from(&cookie.path),
+            expires: None,
+            expiration_date: None,
+            host_only: None,
+            http_only: cookie.http_only().unwrap_or(false),
+            secure: cookie.secure().unwrap_or(false),
+            session: None,
+            same_site: same_site.map(String::from),
+        };
+
+        match format {
+            CookieFormat::Browser => {
+                exported.expiration_date = expires;
+                exported.host_only = Some(host_only);
+                exported.session = Some(expires.is_none());
+                exported
+                    .same_site
+                    .get_or_insert_with(|| String::from("unspecified"));
+            }
+            CookieFormat::StorageState => {
+                exported.expires = Some(expires.unwrap_or(-1.0));
+                exported
+                    .same_site
+                    .get_or_insert_with(|| String::from("Lax"));
+            }
+        }
+
+        exported
+    }
+
+    fn into_raw(self) -> Option<(RawCookie<'static>, url::Url)> {
+        let host = self.domain.trim_start_matches('.');
+        let host_only = self.host_only.unwrap_or(!self.domain.starts_with('.'));
+        let url = url::Url::parse(&format!("https://{host}{}", self.path)).ok()?;
+
+        let mut cookie = RawCookie::new(self.name, self.value);
+        if !host_only {
+            cookie.set_domain(host.to_owned());
+        }
+        cookie.set_path(self.path);
+        cookie.set_http_only(self.http_only);
+        cookie.set_secure(self.secure);
+        cookie.set_same_site(self.same_site.as_deref().and_then(|same_site| {
+            match same_site.to_ascii_lowercase().as_str() {
+                "strict" => Some(SameSite::Strict),
+                "lax" => Some(SameSite::Lax),
+                "none" | "no_restriction" => Some(SameSite::None),
+                _ => None,
+            }
+        }));
+
+        // Browsers use `-1` or a `session` flag to mark cookies that expire with the session.
+        let session = self.session.unwrap_or(false);
+        let expires = self.expires.or(self.expiration_date);
+        if let Some(expires) = expires.filter(|t| !session && *t > 0.0) {
+            let at =
+                cookie_crate::time::OffsetDateTime::from_unix_timestamp(expires as i64).ok()?;
+            cookie.set_expires(at);
+        }
+
+        Some((cookie, url))
+    }
+}
+
 impl CookieStore for Jar {
     fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
         let iter =
@@ -336,8 +905,14 @@ impl CookieStore for Jar {
 
     fn cookies(&self, url: &url::Url) -> Option<Vec<HeaderValue>> {
         let mut cookies = Vec::new();
+        let now = self.now();
         let lock = self.0.read();
-        for (name, value) in lock.get_request_values(url) {
+        for (name, value) in lock
+            .matches(url)
+            .into_iter()
+            .filter(|cookie| !cookie.expires_by(&now))
+            .map(|cookie| cookie.name_value())
+        {
             let mut cookie = bytes::BytesMut::with_capacity(64);
             cookie.put(name.as_bytes());
             cookie.put(&b"="[..]);
@@ -355,8 +930,17 @@ impl CookieStore for Jar {
     }
 }
 
+impl Jar {
+    fn now(&self) -> cookie_crate::time::OffsetDateTime {
+        self.1.system_time().into()
+    }
+}
+
 impl Default for Jar {
     fn default() -> Self {
-        Self(RwLock::new(cookie_store::CookieStore::default()))
+        Self(
+            RwLock::new(cookie_store::CookieStore::default()),
+            Clock::default(),
+        )
     }
 }
@@ -10,9 +10,16 @@ use futures_channel::{mpsc, oneshot};
 use futures_util::{Stream, stream::FusedStream};
 use http::HeaderMap;
 use http_body::{Body, Frame, SizeHint};
+use http_body_util::{BodyExt, combinators::BoxBody};
 
 use super::DecodedLength;
-use crate::core::{common::watch, proto::h2::ping};
+use crate::{
+    core::{
+        common::watch,
+        proto::h2::{ping, streams::StreamSlot},
+    },
+    error::BoxError,
+};
 
 type BodySender = mpsc::Sender<Result<Bytes, crate::core::Error>>;
 type TrailersSender = oneshot::Sender<HeaderMap>;
@@ -50,7 +57,11 @@ enum Kind {
         data_done: bool,
         ping: ping::Recorder,
         recv: http2::RecvStream,
+        // The stream of the connection held until the body is done.
+        slot: Option<StreamSlot>,
     },
+    // A body produced without a connection, such as by a scheme handler.
+    Boxed(BoxBody<Bytes, BoxError>),
 }
 
 /// A sender half created through [`Body::channel()`].
@@ -120,6 +131,14 @@ impl Incoming {
         Incoming::new(Kind::Empty)
     }
 
+    pub(crate) fn boxed<B>(body: B) -> Incoming
+    where
+        B: Body<Data = Bytes> + Send + Sync + 'static,
+        B::Error: Into<BoxError>,
+    {
+        Incoming::new(Kind::Boxed(body.map_err(Into::into).boxed()))
+    }
+
     pub(crate) fn h2(
         recv: http2::RecvStream,
         mut content_length: DecodedLength,
@@ -136,8 +155,24 @@ impl Incoming {
             ping,
             content_length,
             recv,
+            slot: None,
         })
     }
+
+    /// Holds a stream of its connection until this body is done or dropped.
+    pub(crate) fn hold_stream(mut self, stream: StreamSlot) -> Self {
+        if let Kind::H2 {
+            ref recv,
+            ref mut slot,
+            ..
+        } = self.kind
+        {
+            if !recv.is_end_stream() {
+                *slot = Some(stream);
+            }
+        }
+        self
+    }
 }
 
 impl Body for Incoming {
@@ -176,6 +211,7 @@ impl Body for Incoming {
                 ref ping,
                 recv: ref mut h2,
                 content_length: ref mut len,
+                ref mut slot,
             } => {
                 if !*data_done {
                     match ready!(h2.poll_data(cx)) {
@@ -186,6 +222,7 @@ impl Body for Incoming {
                             return Poll::Ready(Some(Ok(Frame::data(bytes))));
                         }
                         Some(Err(e)) => {
+                            slot.take();
                             return match e.reason() {
                                 // These reasons should cause the body reading to stop, but not fail
                                 // it. The same logic as for `Read
@@ -204,7 +241,9 @@ impl Body for Incoming {
                 }
 
                 // after data, check trailers
-                match ready!(h2.poll_trailers(cx)) {
+                let trailers = ready!(h2.poll_trailers(cx));
+                slot.take();
+                match trailers {
                     Ok(t) => {
                         ping.record_non_data();
                         Poll::Ready(Ok(t.map(Frame::trailers)).transpose())
@@ -212,6 +251,9 @@ impl Body for Incoming {
                     Err(e) => Poll::Ready(Some(Err(crate::core::Error::new_h2(e)))),
                 }
             }
+            Kind::Boxed(ref mut body) => Pin::new(body)
+                .poll_frame(cx)
+                .map_err(crate::core::Error::new_body),
         }
     }
 
@@ -220,6 +262,7 @@ impl Body for Incoming {
             Kind::Empty => true,
             Kind::Chan { content_length, .. } => content_length == DecodedLength::ZERO,
             Kind::H2 { recv: ref h2, .. } => h2.is_end_stream(),
+            Kind::Boxed(ref body) => body.is_end_stream(),
         }
     }
 
@@ -236,6 +279,7 @@ impl Body for Incoming {
             Kind::Empty => SizeHint::with_exact(0),
             Kind::Chan { content_length, .. } => opt_len(content_length),
             Kind::H2 { content_length, .. } => opt_len(content_length),
+            Kind::Boxed(ref body) => body.size_hint(),
         }
     }
 }
@@ -2,6 +2,7 @@
 
 use httparse::ParserConfig;
 
+pub use crate::core::header::HeaderCase;
 use crate::core::proto;
 
 /// Builder for `Http1Config`.
@@ -24,7 +25,10 @@ pub struct Http1Config {
     pub(crate) h1_preserve_header_case: bool,
     pub(crate) h1_max_headers: Option<usize>,
     pub(crate) h1_read_buf_exact_size: Option<usize>,
+    pub(crate) h1_read_buf_init_size: Option<usize>,
     pub(crate) h1_max_buf_size: Option<usize>,
+    pub(crate) h1_lenient: bool,
+    pub(crate) h1_header_case: HeaderCase,
 }
 
 impl Http1ConfigBuilder {
@@ -67,6 +71,14 @@ impl Http1ConfigBuilder {
         self
     }
 
+    /// Set the capitalization of the header names written in requests.
+    ///
+    /// Default is [`HeaderCase::AsTyped`].
+    pub fn header_case(mut self, case: HeaderCase) -> Self {
+        self.config.h1_header_case = case;
+        self
+    }
+
     /// Set the maximum number of headers.
     ///
     /// When a response is received, the parser will reserve a buffer to store headers for optimal
@@ -96,6 +108,17 @@ impl Http1ConfigBuilder {
         self
     }
 
+    /// Sets the size of the read buffer to start with.
+    ///
+    /// The adaptive read buffer grows from this size up to the `max_buf_size` as larger reads
+    /// fill it, and never shrinks below it. It has no effect with `read_buf_exact_size`.
+    ///
+    /// Default is 8kb.
+    pub fn read_buf_init_size(mut self, sz: Option<usize>) -> Self {
+        self.config.h1_read_buf_init_size = sz;
+        self
+    }
+
     /// Set the maximum buffer size for the connection.
     ///
     /// Default is ~400kb.
@@ -168,6 +191,22 @@ impl Http1ConfigBuilder {
         self
     }
 
+    /// Set whether HTTP/1 connections will accept responses that are ambiguous about their
+    /// framing.
+    ///
+    /// By default, responses are parsed strictly to defend against request smuggling by hostile
+    /// origins or intermediaries: a response with both `Content-Length` and `Transfer-Encoding`,
+    /// a head with a CR not followed by LF, or a chunk extension larger than 4KB is rejected.
+    /// Enabling this restores the permissive behavior, where `Transfer-Encoding` wins over
+    /// `Content-Length`. Heads with bare LF line endings are accepted either way, as RFC 9112
+    /// allows and browsers do.
+    ///
+    /// Default is false.
+    pub fn lenient_parsing(mut self, enabled: bool) -> Self {
+        self.config.h1_lenient = enabled;
+        self
+    }
+
     /// Build the `Http1Config` instance.
     pub fn build(self) -> Http1Config {
         self.config
@@ -1,9 +1,12 @@
 //! Re-export the `http2` module for HTTP/2 frame types and utilities.
 
 use http2::frame::ExperimentalSettings;
-pub use http2::frame::{
-    Priorities, PrioritiesBuilder, Priority, PseudoId, PseudoOrder, Setting, SettingId,
-    SettingsOrder, SettingsOrderBuilder, StreamDependency, StreamId,
+pub use http2::{
+    Reason,
+    frame::{
+        Priorities, PrioritiesBuilder, Priority, PseudoId, PseudoOrder, Setting, SettingId,
+        SettingsOrder, SettingsOrderBuilder, StreamDependency, StreamId,
+    },
 };
 
 use crate::core::proto::{
@@ -11,6 +14,23 @@ use crate::core::proto::{
     {self},
 };
 
+/// What to do with a request when its HTTP/2 connection reached its limit of concurrent streams.
+///
+/// The limit is the lowest of the `SETTINGS_MAX_CONCURRENT_STREAMS` of the server and the one set
+/// with [`Http2ConfigBuilder::max_concurrent_send_streams`]. A stream is held until its response
+/// body is read to the end or dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StreamsExhausted {
+    /// Waits for a stream of the connection to be done.
+    #[default]
+    Queue,
+    /// Sends the request on another connection, opening one if none has streams left.
+    NewConnection,
+    /// Fails the request, see [`Error::is_streams_exhausted`](crate::Error::is_streams_exhausted).
+    Error,
+}
+
 /// Builder for `Http2Config`.
 #[must_use]
 #[derive(Debug)]
@@ -152,6 +172,39 @@ impl Http2ConfigBuilder {
         self
     }
 
+    /// Sets the maximum number of streams the client opens at once on a connection.
+    ///
+    /// This caps the `SETTINGS_MAX_CONCURRENT_STREAMS` advertised by the server, which can be
+    /// much larger than what the client should multiplex on a single connection.
+    ///
+    /// Passing `None` removes the limit, leaving only the one of the server.
+    pub fn max_concurrent_send_streams(mut self, max: impl Into<Option<usize>>) -> Self {
+        self.config.h2_builder.max_send_streams = max.into();
+        self
+    }
+
+    /// Sets what to do with a request when its connection reached its limit of concurrent
+    /// streams.
+    ///
+    /// Default is [`StreamsExhausted::Queue`].
+    pub fn streams_exhausted(mut self, behavior: StreamsExhausted) -> Self {
+        self.config.h2_builder.streams_exhausted = behavior;
+        self
+    }
+
+    /// Sets the number of active streams from which another connection to the same origin is
+    /// opened, instead of multiplexing every request on a single connection.
+    ///
+    /// Requests go to the pooled connections below the threshold, and a new connection is opened
+    /// once all of them reached it. The connections kept are bounded by
+    /// [`ClientBuilder::pool_max_idle_per_host`](crate::ClientBuilder::pool_max_idle_per_host).
+    ///
+    /// Passing `None` disables the scaling, which is the default.
+    pub fn connection_scaling_threshold(mut self, streams: impl Into<Option<usize>>) -> Self {
+        self.config.h2_builder.scale_streams_threshold = streams.into();
+        self
+    }
+
     /// Enables and disables the push feature for HTTP2.
     ///
     /// Passing `None` will do nothing.
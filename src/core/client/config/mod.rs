@@ -2,7 +2,7 @@ pub mod http1;
 pub mod http2;
 
 use http1::Http1Config;
-use http2::Http2Config;
+use http2::{Http2Config, PseudoOrder};
 
 use crate::tls::TlsConfig;
 
@@ -15,6 +15,7 @@ pub(crate) struct TransportConfig {
     pub(super) http1_config: Option<Http1Config>,
     pub(super) http2_config: Option<Http2Config>,
     pub(super) tls_config: Option<TlsConfig>,
+    pub(super) headers_pseudo_order: Option<PseudoOrder>,
 }
 
 impl TransportConfig {
@@ -44,4 +45,15 @@ impl TransportConfig {
     {
         self.tls_config = config.into();
     }
+
+    /// Sets the HTTP/2 pseudo-header order.
+    ///
+    /// This overrides the order configured in the HTTP/2 configuration, if any.
+    #[inline]
+    pub fn set_headers_pseudo_order<O>(&mut self, order: O)
+    where
+        O: Into<Option<PseudoOrder>>,
+    {
+        self.headers_pseudo_order = order.into();
+    }
 }
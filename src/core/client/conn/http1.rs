@@ -17,6 +17,7 @@ use crate::core::{
         config::http1::Http1Config,
         dispatch::{self, TrySendError},
     },
+    common::buf::BufPool,
     error::BoxError,
     proto,
     rt::{Read, Write},
@@ -89,6 +90,7 @@ where
 #[derive(Clone, Debug)]
 pub struct Builder {
     config: Http1Config,
+    read_buf_pool: Option<BufPool>,
 }
 
 // ===== impl SendRequest
@@ -228,6 +230,7 @@ impl Builder {
     pub fn new() -> Builder {
         Builder {
             config: Default::default(),
+            read_buf_pool: None,
         }
     }
 
@@ -235,6 +238,12 @@ impl Builder {
         self.config = config;
     }
 
+    /// Takes the read buffers of the connections from a pool, giving them back once the
+    /// connections are closed.
+    pub(crate) fn read_buf_pool(&mut self, pool: BufPool) {
+        self.read_buf_pool = Some(pool);
+    }
+
     /// Constructs a connection with the configured options and IO.
     /// See [`client::conn`](crate::core::client::conn) for more.
     ///
@@ -251,6 +260,7 @@ impl Builder {
         B::Error: Into<BoxError>,
     {
         let opts = self.config.clone();
+        let read_buf_pool = self.read_buf_pool.clone();
 
         async move {
             trace!("client handshake HTTP/1");
@@ -268,6 +278,7 @@ impl Builder {
             if opts.h1_preserve_header_case {
                 conn.set_preserve_header_case();
             }
+            conn.set_header_case(opts.h1_header_case);
             if let Some(max_headers) = opts.h1_max_headers {
                 conn.set_http1_max_headers(max_headers);
             }
@@ -276,12 +287,22 @@ impl Builder {
                 conn.set_h09_responses();
             }
 
+            if opts.h1_lenient {
+                conn.set_lenient_parsing();
+            }
+
             if let Some(sz) = opts.h1_read_buf_exact_size {
                 conn.set_read_buf_exact_size(sz);
             }
             if let Some(max) = opts.h1_max_buf_size {
                 conn.set_max_buf_size(max);
             }
+            if let Some(sz) = opts.h1_read_buf_init_size {
+                conn.set_read_buf_init_size(sz);
+            }
+            if let Some(pool) = read_buf_pool {
+                conn.set_read_buf_pool(pool);
+            }
             let cd = proto::h1::dispatch::Client::new(rx);
             let proto = proto::h1::Dispatcher::new(cd, conn);
 
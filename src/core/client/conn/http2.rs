@@ -18,21 +18,25 @@ use crate::{
         body::Incoming as IncomingBody,
         client::dispatch::{self, TrySendError},
         common::time::Time,
-        proto,
+        proto::{self, h2::streams::Streams},
         rt::{Read, Timer, Write, bounds::Http2ClientConnExec},
     },
-    http2::Http2Config,
+    http2::{Http2Config, PseudoOrder, StreamsExhausted},
 };
 
 /// The sender side of an established connection.
 pub struct SendRequest<B> {
     dispatch: dispatch::UnboundedSender<Request<B>, Response<IncomingBody>>,
+    streams: Arc<Streams>,
+    streams_exhausted: StreamsExhausted,
 }
 
 impl<B> Clone for SendRequest<B> {
     fn clone(&self) -> SendRequest<B> {
         SendRequest {
             dispatch: self.dispatch.clone(),
+            streams: self.streams.clone(),
+            streams_exhausted: self.streams_exhausted,
         }
     }
 }
@@ -103,6 +107,15 @@ impl<B> SendRequest<B> {
     pub fn is_closed(&self) -> bool {
         self.dispatch.is_closed()
     }
+
+    /// Checks if a request should be sent on this connection rather than another one, as its
+    /// streams are neither exhausted nor over the scaling threshold.
+    pub(crate) fn has_capacity(&self) -> bool {
+        if self.streams.is_loaded() {
+            return false;
+        }
+        self.streams_exhausted != StreamsExhausted::NewConnection || !self.streams.is_exhausted()
+    }
 }
 
 impl<B> SendRequest<B>
@@ -121,11 +134,35 @@ where
         &mut self,
         req: Request<B>,
     ) -> impl Future<Output = Result<Response<IncomingBody>, TrySendError<Request<B>>>> {
-        let sent = self.dispatch.try_send(req);
+        let mut dispatch = self.dispatch.clone();
+        let streams = self.streams.clone();
+        let streams_exhausted = self.streams_exhausted;
         async move {
-            match sent {
+            let slot = match streams_exhausted {
+                // Without a limit of its own, the client leaves the connection to queue the
+                // streams over the limit of the server.
+                StreamsExhausted::Queue if !streams.has_local_max() => streams.force_open(),
+                StreamsExhausted::Queue => streams.open().await,
+                // The pool only gives out connections with streams left, but another request
+                // may have taken the last one in the meantime.
+                StreamsExhausted::NewConnection => {
+                    streams.try_open().unwrap_or_else(|| streams.force_open())
+                }
+                StreamsExhausted::Error => match streams.try_open() {
+                    Some(slot) => slot,
+                    None => {
+                        debug!("connection reached its limit of concurrent streams");
+                        return Err(TrySendError {
+                            error: crate::core::Error::new_user_streams_exhausted(),
+                            message: None,
+                        });
+                    }
+                },
+            };
+
+            match dispatch.try_send(req) {
                 Ok(rx) => match rx.await {
-                    Ok(Ok(res)) => Ok(res),
+                    Ok(Ok(res)) => Ok(res.map(|body| body.hold_stream(slot))),
                     Ok(Err(err)) => Err(err),
                     // this is definite bug if it happens, but it shouldn't happen!
                     Err(_) => panic!("dispatch dropped without returning error"),
@@ -213,6 +250,12 @@ where
         self
     }
 
+    /// Overrides the pseudo-header order of the current configuration.
+    pub(crate) fn headers_pseudo_order(&mut self, order: PseudoOrder) -> &mut Builder<Ex> {
+        self.config.h2_builder.headers_pseudo_order = Some(order);
+        self
+    }
+
     /// Constructs a connection with the configured options and IO.
     /// See [`client::conn`](crate::core::client::conn) for more.
     ///
@@ -246,6 +289,8 @@ where
             Ok((
                 SendRequest {
                     dispatch: tx.unbound(),
+                    streams: h2.streams().clone(),
+                    streams_exhausted: opts.config.h2_builder.streams_exhausted,
                 },
                 Connection {
                     inner: (PhantomData, h2),
@@ -187,6 +187,27 @@ impl fmt::Debug for GaiAddrs {
     }
 }
 
+/// The addresses a request pins the host of its URI to, bypassing the resolver.
+///
+/// It is part of the key of pooled connections, so that pinned connections are not shared with
+/// requests resolving the host normally.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct PinnedAddrs {
+    host: String,
+    addrs: Vec<SocketAddr>,
+}
+
+impl PinnedAddrs {
+    pub(crate) fn new(host: String, addrs: Vec<SocketAddr>) -> PinnedAddrs {
+        PinnedAddrs { host, addrs }
+    }
+
+    /// Returns the addresses pinned for `host`, if it is the pinned host.
+    pub(super) fn get(&self, host: &str) -> Option<&[SocketAddr]> {
+        (self.host == host).then_some(self.addrs.as_slice())
+    }
+}
+
 pub(super) struct SocketAddrs {
     iter: vec::IntoIter<SocketAddr>,
 }
@@ -219,6 +240,15 @@ impl SocketAddrs {
         SocketAddrs::new(self.iter.filter(predicate).collect())
     }
 
+    /// Moves `addr` first if it is one of the addresses.
+    pub(super) fn prefer(self, addr: SocketAddr) -> SocketAddrs {
+        let mut addrs: Vec<_> = self.iter.collect();
+        if let Some(pos) = addrs.iter().position(|a| *a == addr) {
+            addrs[..=pos].rotate_right(1);
+        }
+        SocketAddrs::new(addrs)
+    }
+
     pub(super) fn split_by_preference(
         self,
         local_addr_ipv4: Option<Ipv4Addr>,
@@ -23,7 +23,7 @@ use tokio::{
 
 use super::{
     Connected, Connection,
-    dns::{self, GaiResolver, Resolve, resolve},
+    dns::{self, GaiResolver, PinnedAddrs, Resolve, resolve},
 };
 use crate::core::{client::connect::options::TcpConnectOptions, error::BoxError, rt::TokioIo};
 
@@ -74,6 +74,8 @@ struct Config {
     happy_eyeballs_timeout: Option<Duration>,
     tcp_keepalive_config: TcpKeepaliveConfig,
     tcp_connect_options: Option<TcpConnectOptions>,
+    pinned_addrs: Option<PinnedAddrs>,
+    preferred_addr: Option<SocketAddr>,
     nodelay: bool,
     reuse_address: bool,
     send_buffer_size: Option<usize>,
@@ -224,6 +226,8 @@ impl<R> HttpConnector<R> {
                 happy_eyeballs_timeout: Some(Duration::from_millis(300)),
                 tcp_keepalive_config: TcpKeepaliveConfig::default(),
                 tcp_connect_options: None,
+                pinned_addrs: None,
+                preferred_addr: None,
                 nodelay: false,
                 reuse_address: false,
                 send_buffer_size: None,
@@ -294,6 +298,24 @@ impl<R> HttpConnector<R> {
         self.config_mut().tcp_connect_options = options;
     }
 
+    /// Set the resolver of the hosts to connect to.
+    #[inline]
+    pub(crate) fn set_resolver(&mut self, resolver: R) {
+        self.resolver = resolver;
+    }
+
+    /// Set the addresses a host is pinned to, bypassing the resolver.
+    #[inline]
+    pub(crate) fn set_pinned_addrs(&mut self, addrs: Option<PinnedAddrs>) {
+        self.config_mut().pinned_addrs = addrs;
+    }
+
+    /// Set the address to try first if the host resolves to it.
+    #[inline]
+    pub(crate) fn set_preferred_addr(&mut self, addr: Option<SocketAddr>) {
+        self.config_mut().preferred_addr = addr;
+    }
+
     /// Set the connect timeout.
     ///
     /// If a domain resolves to multiple IP addresses, the timeout will be
@@ -442,8 +464,22 @@ where
 
         // If the host is already an IP addr (v4 or v6),
         // skip resolving the dns and start connecting right away.
+        let pinned = config
+            .pinned_addrs
+            .as_ref()
+            .and_then(|pinned| pinned.get(host));
         let addrs = if let Some(addrs) = dns::SocketAddrs::try_parse(host, port) {
             addrs
+        } else if let Some(pinned) = pinned {
+            let addrs = pinned
+                .iter()
+                .map(|addr| {
+                    let mut addr = *addr;
+                    set_port(&mut addr, port, dst.port().is_some());
+                    addr
+                })
+                .collect();
+            dns::SocketAddrs::new(addrs)
         } else {
             let addrs = resolve(&mut self.resolver, dns::Name::new(host.into()))
                 .await
@@ -457,6 +493,10 @@ where
                 .collect();
             dns::SocketAddrs::new(addrs)
         };
+        let addrs = match config.preferred_addr {
+            Some(preferred) => addrs.prefer(preferred),
+            None => addrs,
+        };
 
         let c = ConnectingTcp::new(addrs, config);
 
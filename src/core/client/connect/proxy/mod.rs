@@ -5,4 +5,4 @@ mod tunnel;
 
 #[cfg(feature = "socks")]
 pub use self::socks::{DnsResolve, Socks, SocksVersion};
-pub use self::tunnel::Tunnel;
+pub use self::tunnel::{Tunnel, TunnelError, TunnelResponse};
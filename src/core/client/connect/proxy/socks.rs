@@ -132,7 +132,8 @@ where
     ///
     /// The `auth` parameter is optional and can be used to provide a username and password for
     /// SOCKS authentication. If provided, it should be a tuple containing the username and
-    /// password.
+    /// password. For SOCKS4 the username is sent as the ident user ID and the password is
+    /// ignored.
     pub fn new_with_resolver(inner: C, resolver: R, proxy: Uri) -> Self {
         Socks {
             inner,
@@ -216,8 +217,20 @@ where
 
             match version {
                 SocksVersion::V4 => {
-                    // For SOCKS4, we connect directly to the target address.
-                    let stream = Socks4Stream::connect_with_socket(socket, target_addr).await?;
+                    // SOCKS4 has no password authentication; the username, if any,
+                    // is sent as the ident user ID.
+                    let stream = match auth {
+                        Some((userid, _)) => {
+                            let userid = std::str::from_utf8(&userid)?;
+                            Socks4Stream::connect_with_userid_and_socket(
+                                socket,
+                                target_addr,
+                                userid,
+                            )
+                            .await?
+                        }
+                        None => Socks4Stream::connect_with_socket(socket, target_addr).await?,
+                    };
                     Ok(stream.into_inner().into_inner())
                 }
                 SocksVersion::V5 => {
@@ -5,7 +5,7 @@ use std::{
     task::{self, Poll},
 };
 
-use http::{HeaderMap, HeaderValue, Uri};
+use http::{HeaderMap, HeaderValue, StatusCode, Uri, header::HeaderName};
 use pin_project_lite::pin_project;
 use tower_service::Service;
 
@@ -38,10 +38,45 @@ pub enum TunnelError {
     ConnectFailed(BoxError),
     Io(std::io::Error),
     MissingHost,
-    ProxyAuthRequired,
+    ProxyAuthRequired(Box<TunnelResponse>),
     ProxyHeadersTooLong,
+    ProxyResponseInvalid,
     TunnelUnexpectedEof,
-    TunnelUnsuccessful,
+    TunnelUnsuccessful(Box<TunnelResponse>),
+}
+
+/// The response of a proxy that refused to open a tunnel.
+#[derive(Clone, Debug)]
+pub struct TunnelResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl TunnelResponse {
+    /// Returns the status code of the CONNECT response.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the headers of the CONNECT response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+impl TunnelError {
+    /// Returns true if the proxy refused to open the tunnel without valid credentials.
+    pub(crate) fn is_proxy_auth_required(&self) -> bool {
+        matches!(self, TunnelError::ProxyAuthRequired(_))
+    }
+
+    /// Returns the response of the proxy if it refused to open the tunnel.
+    pub(crate) fn response(&self) -> Option<&TunnelResponse> {
+        match self {
+            TunnelError::ProxyAuthRequired(res) | TunnelError::TunnelUnsuccessful(res) => Some(res),
+            _ => None,
+        }
+    }
 }
 
 pin_project! {
@@ -220,20 +255,42 @@ where
         }
         pos += n;
 
-        let recvd = &buf[..pos];
-        if recvd.starts_with(b"HTTP/1.1 200") || recvd.starts_with(b"HTTP/1.0 200") {
-            if recvd.ends_with(b"\r\n\r\n") {
-                return Ok(conn);
-            }
-            if pos == buf.len() {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut res = httparse::Response::new(&mut headers);
+        match res.parse(&buf[..pos]) {
+            Ok(httparse::Status::Complete(_)) => {}
+            Ok(httparse::Status::Partial) if pos < buf.len() => continue,
+            Ok(httparse::Status::Partial) | Err(httparse::Error::TooManyHeaders) => {
                 return Err(TunnelError::ProxyHeadersTooLong);
             }
-        // else read more
-        } else if recvd.starts_with(b"HTTP/1.1 407") {
-            return Err(TunnelError::ProxyAuthRequired);
-        } else {
-            return Err(TunnelError::TunnelUnsuccessful);
+            Err(_) => return Err(TunnelError::ProxyResponseInvalid),
+        }
+
+        let status = res
+            .code
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .ok_or(TunnelError::ProxyResponseInvalid)?;
+        if status == StatusCode::OK {
+            return Ok(conn);
         }
+
+        let response = Box::new(TunnelResponse {
+            status,
+            headers: res
+                .headers
+                .iter()
+                .filter_map(|header| {
+                    let name = HeaderName::from_bytes(header.name.as_bytes()).ok()?;
+                    let value = HeaderValue::from_bytes(header.value).ok()?;
+                    Some((name, value))
+                })
+                .collect(),
+        });
+        return Err(if status == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+            TunnelError::ProxyAuthRequired(response)
+        } else {
+            TunnelError::TunnelUnsuccessful(response)
+        });
     }
 }
 
@@ -243,13 +300,19 @@ impl std::fmt::Display for TunnelError {
 
         f.write_str(match self {
             TunnelError::MissingHost => "missing destination host",
-            TunnelError::ProxyAuthRequired => "proxy authorization required",
+            TunnelError::ProxyAuthRequired(_) => "proxy authorization required",
             TunnelError::ProxyHeadersTooLong => "proxy response headers too long",
+            TunnelError::ProxyResponseInvalid => "invalid proxy response",
             TunnelError::TunnelUnexpectedEof => "unexpected end of file",
-            TunnelError::TunnelUnsuccessful => "unsuccessful",
+            TunnelError::TunnelUnsuccessful(_) => "unsuccessful",
             TunnelError::ConnectFailed(_) => "failed to create underlying connection",
             TunnelError::Io(_) => "io error establishing tunnel",
-        })
+        })?;
+
+        if let Some(res) = self.response() {
+            write!(f, " ({})", res.status)?;
+        }
+        Ok(())
     }
 }
 
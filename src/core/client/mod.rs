@@ -11,20 +11,28 @@ pub mod connect;
 // designed.
 mod pool;
 
+pub use self::pool::{PoolStats, PoolWaiterOrder};
+
 use std::{
     error::Error as StdError,
     fmt,
     future::Future,
+    hash::{Hash, Hasher},
+    net::SocketAddr,
     num::NonZeroU32,
     pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     task::{self, Poll},
     time::Duration,
 };
 
 use futures_util::future::{self, Either, FutureExt, TryFutureExt};
 use http::{
-    HeaderValue, Method, Request, Response, Uri, Version,
-    header::HOST,
+    HeaderMap, HeaderName, HeaderValue, Method, Request, Response, Uri, Version,
+    header::{CONNECTION, CONTENT_LENGTH, EXPECT, HOST, TE, TRANSFER_ENCODING, UPGRADE},
     uri::{Authority, PathAndQuery, Scheme},
 };
 use http_body::Body;
@@ -35,24 +43,45 @@ use crate::{
     core::{
         body::Incoming,
         client::{
-            config::{TransportConfig, http1::Http1Config, http2::Http2Config},
+            config::{
+                TransportConfig,
+                http1::Http1Config,
+                http2::{Http2Config, PseudoOrder},
+            },
             conn::TrySendError as ConnTrySendError,
-            connect::{Alpn, Connect, Connected, Connection, TcpConnectOptions},
+            connect::{Alpn, Connect, Connected, Connection, TcpConnectOptions, dns::PinnedAddrs},
         },
-        common::{Exec, Lazy, lazy, timer},
+        common::{Exec, Lazy, buf::BufPool, lazy, timer},
         error::BoxError,
         ext::{
-            RequestConfig, RequestEnforcedHttpVersion, RequestProxyMatcher,
-            RequestTcpConnectOptions, RequestTransportConfig,
+            ConnectionInfo, RequestChunkFraming, RequestConfig, RequestConnectTimeout,
+            RequestDnsOverrides, RequestDnsResolver, RequestEnforcedHttpVersion,
+            RequestHostOverride, RequestNewConnection, RequestOriginalHeaders,
+            RequestPreferredAddr, RequestProxyMatcher, RequestReplayHead, RequestTarget,
+            RequestTargetForm, RequestTcpConnectOptions, RequestTransportConfig,
+            RequestTunnelHeaders, RequestVersionPreference, SharedHeaders,
         },
-        rt::{Executor, Timer},
+        rt::{Clock, Executor, Timer},
     },
+    dns::DynResolver,
+    events::ConnectionEvents,
     proxy::Matcher as ProxyMacher,
     tls::{AlpnProtocol, TlsConfig},
 };
 
 type BoxSendFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
 
+/// The headers an HTTP/1 connection reads or sets in the request's own map.
+const CONNECTION_HEADERS: [HeaderName; 7] = [
+    CONNECTION,
+    CONTENT_LENGTH,
+    EXPECT,
+    HOST,
+    TE,
+    TRANSFER_ENCODING,
+    UPGRADE,
+];
+
 /// Describes the reusable connection metadata for a network session.
 ///
 /// This includes all the parameters that uniquely identify a pooled connection,
@@ -66,17 +95,39 @@ type BoxSendFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
 pub struct ConnExtra {
     scheme: Option<Scheme>,
     authority: Option<Authority>,
-    alpn_protocol: Option<AlpnProtocol>,
+    alpn_protocols: Option<Vec<AlpnProtocol>>,
     proxy_matcher: Option<ProxyMacher>,
     tcp_options: Option<TcpConnectOptions>,
+    pinned_addrs: Option<PinnedAddrs>,
+    dns_resolver: Option<DynResolver>,
     tls_config: Option<TlsConfig>,
+    headers_pseudo_order: Option<PseudoOrder>,
+    tunnel_headers: Option<TunnelHeaders>,
+    host_override: Option<Authority>,
+    pool_partition: Option<u64>,
+}
+
+/// Extra headers of the `CONNECT` request opening a tunnel through a proxy.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TunnelHeaders(HeaderMap);
+
+impl Hash for TunnelHeaders {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Hash the headers by name and value bytes, in sorted order for determinism
+        let mut items: Vec<_> = self.0.iter().collect();
+        items.sort_by_key(|(k, _)| k.as_str());
+        for (k, v) in items {
+            state.write(k.as_str().as_bytes());
+            state.write(v.as_bytes());
+        }
+    }
 }
 
 impl ConnExtra {
-    /// Returns the negotiated ALPN protocol.
+    /// Returns the ALPN protocols to offer, in order of preference.
     #[inline]
-    pub(crate) fn alpn_protocol(&self) -> Option<AlpnProtocol> {
-        self.alpn_protocol
+    pub(crate) fn alpn_protocols(&self) -> Option<&[AlpnProtocol]> {
+        self.alpn_protocols.as_deref()
     }
 
     /// Return a reference to the proxy matcher.
@@ -91,11 +142,29 @@ impl ConnExtra {
         self.tcp_options.as_ref()
     }
 
+    /// Return the addresses the host is pinned to.
+    #[inline]
+    pub(crate) fn pinned_addrs(&self) -> Option<&PinnedAddrs> {
+        self.pinned_addrs.as_ref()
+    }
+
+    /// Return the DNS resolver replacing the one of the connector.
+    #[inline]
+    pub(crate) fn dns_resolver(&self) -> Option<&DynResolver> {
+        self.dns_resolver.as_ref()
+    }
+
     /// Return the TLS configuration.
     #[inline]
     pub(crate) fn tls_config(&self) -> Option<&TlsConfig> {
         self.tls_config.as_ref()
     }
+
+    /// Return the extra headers of the `CONNECT` request.
+    #[inline]
+    pub(crate) fn tunnel_headers(&self) -> Option<&HeaderMap> {
+        self.tunnel_headers.as_ref().map(|headers| &headers.0)
+    }
 }
 
 /// Uniquely identifies a reusable connection.
@@ -111,6 +180,31 @@ impl ConnExtra {
 #[derive(Clone, Hash, Debug, Eq, PartialEq)]
 pub(crate) struct ConnKey(Box<ConnExtra>);
 
+impl ConnKey {
+    /// Returns the origin the connections of this key are established to.
+    fn origin(&self) -> Uri {
+        let mut parts = http::uri::Parts::default();
+        parts.scheme = self.0.scheme.clone();
+        parts.authority = self.0.authority.clone();
+        if parts.scheme.is_some() {
+            parts.path_and_query = Some(http::uri::PathAndQuery::from_static("/"));
+        }
+        Uri::from_parts(parts).unwrap_or_default()
+    }
+
+    /// Returns true if the connections of this key go through `proxy`.
+    fn is_proxied_by(&self, proxy: &Uri) -> bool {
+        self.0
+            .proxy_matcher
+            .as_ref()
+            .and_then(|matcher| matcher.intercept(&self.origin()))
+            .is_some_and(|intercepted| {
+                intercepted.uri().scheme() == proxy.scheme()
+                    && intercepted.uri().authority() == proxy.authority()
+            })
+    }
+}
+
 /// Describes all the parameters needed to initiate a client connection.
 ///
 /// A `ConnRequest` encapsulates the information required to initiate
@@ -123,6 +217,9 @@ pub(crate) struct ConnKey(Box<ConnExtra>);
 pub struct ConnRequest {
     uri: Uri,
     extra: Box<ConnExtra>,
+    connect_timeout: Option<Duration>,
+    preferred_addr: Option<SocketAddr>,
+    new_connection: bool,
 }
 
 impl ConnRequest {
@@ -144,6 +241,22 @@ impl ConnRequest {
         &self.extra
     }
 
+    /// Return the connect timeout of this request, overriding the connector's.
+    ///
+    /// It is not part of the [`ConnKey`], as it does not change the established connection.
+    #[inline]
+    pub(crate) fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Return the address to try first if the host resolves to it.
+    ///
+    /// It is not part of the [`ConnKey`], as it only changes the order of connection attempts.
+    #[inline]
+    pub(crate) fn preferred_addr(&self) -> Option<SocketAddr> {
+        self.preferred_addr
+    }
+
     /// Converts the request into its corresponding `ConnKey`.
     #[inline]
     pub(crate) fn into_key(self) -> ConnKey {
@@ -161,16 +274,48 @@ pub struct Client<C, B> {
     exec: Exec,
     h1_builder: conn::http1::Builder,
     h2_builder: conn::http2::Builder<Exec>,
-    pool: pool::Pool<PoolClient<B>, ConnKey>,
+    pool: SharedPool<B>,
+    timer: Option<timer::Timer>,
 }
 
+/// A connection pool, which may be shared by several clients.
+pub(crate) type SharedPool<B> = pool::Pool<PoolClient<B>, ConnKey>;
+
 #[derive(Clone, Copy, Debug)]
 struct Config {
     retry_canceled_requests: bool,
+    pool_checkout_timeout: Option<Duration>,
+    pool_partition: Option<u64>,
+    health_check: PoolHealthCheck,
     set_host: bool,
     ver: Ver,
 }
 
+/// How often the pool is checked for connections to keep warm.
+const KEEP_WARM_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An origin to keep idle connections to.
+struct WarmTarget {
+    conn_req: ConnRequest,
+    count: usize,
+}
+
+/// How an idle HTTP/1 connection is probed before it is reused.
+///
+/// Middleboxes may silently drop idle connections. Probing them first lets the client discard
+/// a stale connection before the real request is sent on it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PoolHealthCheck {
+    /// Reuse idle connections without probing them.
+    #[default]
+    None,
+    /// Give the connection a chance to observe that the peer closed it.
+    Eof,
+    /// Send an `OPTIONS *` request and wait up to the given duration for its response.
+    Options(Duration),
+}
+
 /// Client errors
 pub struct Error {
     kind: ErrorKind,
@@ -195,10 +340,12 @@ enum ErrorKind {
     Canceled,
     ChannelClosed,
     Connect,
+    PoolTimeout,
     UserUnsupportedRequestMethod,
     UserUnsupportedVersion,
     UserAbsoluteUriRequired,
     SendRequest,
+    StaleConnection,
 }
 
 macro_rules! e {
@@ -275,7 +422,7 @@ impl Client<(), ()> {
 impl<C, B> Client<C, B>
 where
     C: Connect + Clone + Send + Sync + 'static,
-    B: Body + Send + 'static + Unpin,
+    B: Body + Default + Send + 'static + Unpin,
     B::Data: Send,
     B::Error: Into<BoxError>,
 {
@@ -329,19 +476,37 @@ where
         };
 
         // Extract config extensions
-        let (transport_cfg, version, proxy_matcher, tcp_options) =
+        let (transport_cfg, version, proxy_matcher, tcp_options, connect_timeout) =
             extract_request_configs(req.extensions_mut());
+        let tunnel_headers =
+            RequestConfig::<RequestTunnelHeaders>::remove(req.extensions_mut()).map(TunnelHeaders);
+        let pinned_addrs = RequestConfig::<RequestDnsOverrides>::remove(req.extensions_mut())
+            .and_then(|overrides| {
+                let host = uri.host()?;
+                let addrs = overrides.get(host)?;
+                Some(PinnedAddrs::new(host.to_owned(), addrs.clone()))
+            });
+        let dns_resolver = RequestConfig::<RequestDnsResolver>::remove(req.extensions_mut());
+        let preferred_addr = RequestConfig::<RequestPreferredAddr>::remove(req.extensions_mut());
+        let new_connection =
+            RequestConfig::<RequestNewConnection>::remove(req.extensions_mut()).unwrap_or(false);
+        // Connections sending another host than the one they are established to are kept apart,
+        // so that the requests of the URL host never go through them.
+        let host_override = RequestConfig::<RequestHostOverride>::remove(req.extensions_mut());
 
         let mut tls_config = None;
+        let mut headers_pseudo_order = None;
         let mut this = self.clone();
 
-        // Parse to specific ALPN protocol
-        let alpn_protocol = match version {
+        // Parse to specific ALPN protocol, or to the ones offered in order of preference
+        let version_preference =
+            RequestConfig::<RequestVersionPreference>::remove(req.extensions_mut());
+        let alpn_protocols = match version {
             Some(Version::HTTP_11 | Version::HTTP_10 | Version::HTTP_09) => {
-                Some(AlpnProtocol::HTTP1)
+                Some(vec![AlpnProtocol::HTTP1])
             }
-            Some(Version::HTTP_2) => Some(AlpnProtocol::HTTP2),
-            _ => None,
+            Some(Version::HTTP_2) => Some(vec![AlpnProtocol::HTTP2]),
+            _ => version_preference.map(|preference| preference.alpn_protocols()),
         };
 
         // Apply transport configuration
@@ -352,6 +517,12 @@ where
             if let Some(config) = cfg.http2_config.take() {
                 this.h2_builder.config(config);
             }
+            if let Some(order) = cfg.headers_pseudo_order.take() {
+                this.h2_builder.headers_pseudo_order(order.clone());
+                // HTTP/2 pseudo-header order is a connection-level setting,
+                // so requests overriding it must not share pooled connections.
+                headers_pseudo_order = Some(order);
+            }
             tls_config = cfg.tls_config.take();
         }
 
@@ -359,12 +530,21 @@ where
             extra: Box::new(ConnExtra {
                 scheme: uri.scheme().cloned(),
                 authority: uri.authority().cloned(),
-                alpn_protocol,
+                alpn_protocols,
                 proxy_matcher,
                 tcp_options,
+                pinned_addrs,
+                dns_resolver,
                 tls_config,
+                headers_pseudo_order,
+                tunnel_headers,
+                host_override,
+                pool_partition: self.config.pool_partition,
             }),
             uri,
+            connect_timeout,
+            preferred_addr,
+            new_connection,
         };
 
         ResponseFuture::new(this.send_request(req, conn_req))
@@ -408,6 +588,7 @@ where
         mut req: Request<B>,
         conn_req: ConnRequest,
     ) -> Result<Response<Incoming>, TrySendError<B>> {
+        let host_override = conn_req.extra.host_override.clone();
         let mut pooled = self
             .connection_for(conn_req)
             .await
@@ -415,6 +596,33 @@ where
             // it returns an error, there's not much else to retry
             .map_err(TrySendError::Nope)?;
 
+        // The server may close a reused HTTP/1 connection as the request is written to it, so
+        // the request is copied, before its URI and `Host` header are adapted to the connection.
+        if pooled.is_reused() && pooled.is_http1() {
+            if let Some(head) = RequestConfig::<RequestReplayHead>::get(req.extensions()) {
+                head.store(&req);
+            }
+        }
+
+        // The shared headers are written as they are to HTTP/1 connections, unless they are
+        // written in their original order, or hold a header the connection reads or sets.
+        if let Some(shared) = SharedHeaders::get(req.extensions()) {
+            if !pooled.is_http1()
+                || RequestConfig::<RequestOriginalHeaders>::get(req.extensions()).is_some()
+                || shared.has_any(&CONNECTION_HEADERS)
+            {
+                SharedHeaders::merge(&mut req);
+            }
+        }
+
+        // A body framing its own chunks does so only for the chunked coding of HTTP/1.1.
+        if let Some(framing) = RequestConfig::<RequestChunkFraming>::get(req.extensions()) {
+            framing.store(
+                pooled.is_http1() && req.version() == Version::HTTP_11,
+                Ordering::Release,
+            );
+        }
+
         if pooled.is_http1() {
             if req.version() == Version::HTTP_2 {
                 warn!("Connection is HTTP/1, but request requires HTTP/2");
@@ -423,7 +631,11 @@ where
                 ));
             }
 
-            if self.config.set_host {
+            if let Some(ref host) = host_override {
+                let host =
+                    HeaderValue::from_str(host.as_str()).expect("authority is a valid header");
+                req.headers_mut().insert(HOST, host);
+            } else if self.config.set_host {
                 let uri = req.uri().clone();
                 req.headers_mut().entry(HOST).or_insert_with(|| {
                     let hostname = uri.host().expect("authority implies host");
@@ -437,8 +649,17 @@ where
                 });
             }
 
-            // CONNECT always sends authority-form, so check it first...
-            if req.method() == Method::CONNECT {
+            let target = RequestConfig::<RequestTargetForm>::get(req.extensions()).copied();
+            if let Some(target) = target {
+                match target {
+                    RequestTarget::Origin => origin_form(req.uri_mut()),
+                    // The URI is still the absolute one the request was sent to.
+                    RequestTarget::Absolute => {}
+                    RequestTarget::Authority => authority_form(req.uri_mut()),
+                    RequestTarget::Asterisk => *req.uri_mut() = Uri::from_static("*"),
+                }
+            } else if req.method() == Method::CONNECT {
+                // CONNECT always sends authority-form, so check it first...
                 authority_form(req.uri_mut());
             } else if pooled.conn_info.is_proxied {
                 absolute_form(req.uri_mut());
@@ -447,6 +668,29 @@ where
             }
         } else if req.method() == Method::CONNECT && !pooled.is_http2() {
             authority_form(req.uri_mut());
+        } else if let Some(host) = host_override {
+            // HTTP/2 sends the `:authority` pseudo-header from the URI.
+            let mut parts = req.uri().clone().into_parts();
+            parts.authority = Some(host);
+            if let Ok(uri) = Uri::from_parts(parts) {
+                *req.uri_mut() = uri;
+            }
+        }
+
+        if pooled.is_reused()
+            && pooled.is_http1()
+            && !self
+                .check_health(&mut pooled, req.headers().get(HOST))
+                .await
+        {
+            trace!("idle connection failed health check");
+            pooled.conn_info.poison();
+            return Err(TrySendError::Retryable {
+                connection_reused: true,
+                error: e!(Canceled, "idle connection failed health check")
+                    .with_connect_info(pooled.conn_info.clone()),
+                req,
+            });
         }
 
         let mut res = match pooled.try_send_request(req).await {
@@ -460,9 +704,16 @@ where
                         req,
                     })
                 } else {
+                    let err = err.into_error();
+                    // The server closed the idle connection as the request was written to it.
+                    let err =
+                        if pooled.is_reused() && pooled.is_http1() && err.is_incomplete_message() {
+                            e!(StaleConnection, err)
+                        } else {
+                            e!(SendRequest, err)
+                        };
                     Err(TrySendError::Nope(
-                        e!(SendRequest, err.into_error())
-                            .with_connect_info(pooled.conn_info.clone()),
+                        err.with_connect_info(pooled.conn_info.clone()),
                     ))
                 };
             }
@@ -472,6 +723,8 @@ where
         if let Some(extra) = &pooled.conn_info.extra {
             extra.set(res.extensions_mut());
         }
+        res.extensions_mut()
+            .insert(ConnectionInfo::new(pooled.id, pooled.is_reused()));
 
         // If pooled is HTTP/2, we can toss this reference immediately.
         //
@@ -493,9 +746,188 @@ where
         Ok(res)
     }
 
+    /// Returns a snapshot of the pool state for the connections going through `proxy`, summed
+    /// over all the origins they are established to.
+    pub(crate) fn proxy_pool_stats(&self, proxy: &Uri) -> PoolStats {
+        self.pool.stats_where(|key| {
+            key.0.pool_partition == self.config.pool_partition && key.is_proxied_by(proxy)
+        })
+    }
+
+    /// Returns the connection pool of this client, to share it with other clients.
+    pub(crate) fn shared_pool(&self) -> SharedPool<B> {
+        self.pool.clone()
+    }
+
+    /// Spawns a task keeping `count` idle connections to each of the `targets` origins.
+    ///
+    /// Connections expiring or dropped from the pool are dialed again in the background, so
+    /// requests to these origins do not pay the handshake cost. The task stops once the
+    /// client is dropped.
+    pub(crate) fn keep_warm(&self, targets: Vec<(Uri, usize)>) {
+        let Some(timer) = self.timer.clone() else {
+            return;
+        };
+        if targets.is_empty() || !self.pool.is_enabled() {
+            return;
+        }
+
+        let mut targets = targets
+            .into_iter()
+            .map(|(uri, count)| WarmTarget {
+                conn_req: ConnRequest {
+                    extra: Box::new(ConnExtra {
+                        scheme: uri.scheme().cloned(),
+                        authority: uri.authority().cloned(),
+                        alpn_protocols: None,
+                        proxy_matcher: None,
+                        tcp_options: None,
+                        pinned_addrs: None,
+                        dns_resolver: None,
+                        tls_config: None,
+                        headers_pseudo_order: None,
+                        tunnel_headers: None,
+                        host_override: None,
+                        pool_partition: self.config.pool_partition,
+                    }),
+                    uri,
+                    connect_timeout: None,
+                    preferred_addr: None,
+                    new_connection: false,
+                },
+                count,
+            })
+            .collect::<Vec<_>>();
+
+        // The task must not keep the pool alive, so it only holds onto it while dialing.
+        let pool = self.pool.downgrade();
+        let template = Client {
+            pool: pool::Pool::disabled(),
+            ..self.clone()
+        };
+
+        self.exec.execute(async move {
+            loop {
+                {
+                    let Some(pool) = pool.upgrade() else {
+                        trace!("pool dropped, stopping keep warm task");
+                        return;
+                    };
+                    let client = Client {
+                        pool,
+                        ..template.clone()
+                    };
+                    for target in &mut targets {
+                        client.warm(target).await;
+                    }
+                }
+                timer.sleep(KEEP_WARM_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn warm(&self, target: &mut WarmTarget) {
+        let key = ConnKey(target.conn_req.extra.clone());
+        let idle = self.pool.stats(&key).idle();
+        for _ in idle..target.count {
+            match self.connect_to(target.conn_req.clone()).await {
+                // Dropping the connection puts it in the pool.
+                Ok(pooled) => {
+                    if pooled.is_http2() {
+                        // A single HTTP/2 connection is shared by all requests.
+                        target.count = 1;
+                        break;
+                    }
+                }
+                Err(_err) => {
+                    debug!("keep warm connect error: {}", _err);
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn check_health(
+        &self,
+        pooled: &mut pool::Pooled<PoolClient<B>, ConnKey>,
+        host: Option<&HeaderValue>,
+    ) -> bool {
+        match self.config.health_check {
+            PoolHealthCheck::None => true,
+            PoolHealthCheck::Eof => {
+                // Yield once, so the connection task can process a pending close.
+                let mut yielded = false;
+                std::future::poll_fn(|cx| {
+                    if yielded {
+                        return Poll::Ready(());
+                    }
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                })
+                .await;
+                pooled.is_ready()
+            }
+            PoolHealthCheck::Options(timeout) => {
+                let mut req = Request::new(B::default());
+                *req.method_mut() = Method::OPTIONS;
+                *req.uri_mut() = Uri::from_static("*");
+                if let Some(host) = host {
+                    req.headers_mut().insert(HOST, host.clone());
+                }
+
+                let ping = Box::pin(async {
+                    let Ok(res) = pooled.try_send_request(req).await else {
+                        return false;
+                    };
+                    // The body must be read for the connection to be reusable.
+                    if http_body_util::BodyExt::collect(res.into_body())
+                        .await
+                        .is_err()
+                    {
+                        return false;
+                    }
+                    std::future::poll_fn(|cx| pooled.poll_ready(cx))
+                        .await
+                        .is_ok()
+                });
+
+                match self.timer {
+                    Some(ref timer) => match future::select(ping, timer.sleep(timeout)).await {
+                        Either::Left((healthy, _)) => healthy,
+                        Either::Right(_) => false,
+                    },
+                    None => ping.await,
+                }
+            }
+        }
+    }
+
     async fn connection_for(
         &self,
         conn_req: ConnRequest,
+    ) -> Result<pool::Pooled<PoolClient<B>, ConnKey>, Error> {
+        let (Some(timeout), Some(timer)) = (self.config.pool_checkout_timeout, &self.timer) else {
+            return self.checkout_connection(conn_req).await;
+        };
+
+        let key = ConnKey(conn_req.extra.clone());
+        let checkout = Box::pin(self.checkout_connection(conn_req));
+        match future::select(checkout, timer.sleep(timeout)).await {
+            Either::Left((res, _)) => res,
+            Either::Right(((), checkout)) => {
+                // Drop the checkout first so it is no longer counted as a waiter.
+                drop(checkout);
+                let stats = self.pool.stats(&key);
+                trace!("pool checkout timed out: {:?}", stats);
+                Err(e!(PoolTimeout, pool::CheckoutTimedOut(stats)))
+            }
+        }
+    }
+
+    async fn checkout_connection(
+        &self,
+        conn_req: ConnRequest,
     ) -> Result<pool::Pooled<PoolClient<B>, ConnKey>, Error> {
         loop {
             match self.one_connection_for(conn_req.clone()).await {
@@ -520,8 +952,9 @@ where
         &self,
         conn_req: ConnRequest,
     ) -> Result<pool::Pooled<PoolClient<B>, ConnKey>, ClientConnectError> {
-        // Return a single connection if pooling is not enabled
-        if !self.pool.is_enabled() {
+        // Return a single connection if pooling is not enabled, or if the request must not be
+        // sent on an idle one.
+        if !self.pool.is_enabled() || conn_req.new_connection {
             return self
                 .connect_to(conn_req)
                 .await
@@ -580,9 +1013,10 @@ where
             //    connecting task.
             // 2. Checkout is canceled if the pool cannot deliver an idle connection reliably.
             //
-            // In both cases, we should just wait for the other future.
+            // In both cases, we should just wait for the other future. The same goes for a
+            // checkout refused because too many requests are already waiting.
             Either::Left((Err(err), connecting)) => {
-                if err.is_canceled() {
+                if err.is_canceled() || err.is_too_many_waiters() {
                     connecting.await.map_err(ClientConnectError::Normal)
                 } else {
                     Err(ClientConnectError::Normal(e!(Connect, err)))
@@ -614,8 +1048,8 @@ where
 
         let h1_builder = self.h1_builder.clone();
         let h2_builder = self.h2_builder.clone();
-        let ver = match conn_req.extra.alpn_protocol {
-            Some(AlpnProtocol::HTTP2) => Ver::Http2,
+        let ver = match conn_req.extra.alpn_protocols() {
+            Some([AlpnProtocol::HTTP2]) => Ver::Http2,
             _ => self.config.ver,
         };
         let is_ver_h2 = ver == Ver::Http2;
@@ -774,6 +1208,7 @@ where
                             Ok(pool.pooled(
                                 connecting,
                                 PoolClient {
+                                    id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
                                     conn_info: connected,
                                     tx,
                                 },
@@ -788,7 +1223,7 @@ where
 impl<C, B> tower_service::Service<Request<B>> for Client<C, B>
 where
     C: Connect + Clone + Send + Sync + 'static,
-    B: Body + Send + 'static + Unpin,
+    B: Body + Default + Send + 'static + Unpin,
     B::Data: Send,
     B::Error: Into<BoxError>,
 {
@@ -808,7 +1243,7 @@ where
 impl<C, B> tower_service::Service<Request<B>> for &'_ Client<C, B>
 where
     C: Connect + Clone + Send + Sync + 'static,
-    B: Body + Send + 'static + Unpin,
+    B: Body + Default + Send + 'static + Unpin,
     B::Data: Send,
     B::Error: Into<BoxError>,
 {
@@ -835,6 +1270,7 @@ impl<C: Clone, B> Clone for Client<C, B> {
             h2_builder: self.h2_builder.clone(),
             connector: self.connector.clone(),
             pool: self.pool.clone(),
+            timer: self.timer.clone(),
         }
     }
 }
@@ -881,11 +1317,15 @@ impl Future for ResponseFuture {
 
 // FIXME: allow() required due to `impl Trait` leaking types to this lint
 #[allow(missing_debug_implementations)]
-struct PoolClient<B> {
+pub(crate) struct PoolClient<B> {
+    id: u64,
     conn_info: Connected,
     tx: PoolTx<B>,
 }
 
+/// The ID given to the next established connection.
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
 enum PoolTx<B> {
     Http1(conn::http1::SendRequest<B>),
 
@@ -955,16 +1395,19 @@ where
     fn reserve(self) -> pool::Reservation<Self> {
         match self.tx {
             PoolTx::Http1(tx) => pool::Reservation::Unique(PoolClient {
+                id: self.id,
                 conn_info: self.conn_info,
                 tx: PoolTx::Http1(tx),
             }),
 
             PoolTx::Http2(tx) => {
                 let b = PoolClient {
+                    id: self.id,
                     conn_info: self.conn_info.clone(),
                     tx: PoolTx::Http2(tx.clone()),
                 };
                 let a = PoolClient {
+                    id: self.id,
                     conn_info: self.conn_info,
                     tx: PoolTx::Http2(tx),
                 };
@@ -976,6 +1419,14 @@ where
     fn can_share(&self) -> bool {
         self.is_http2()
     }
+
+    fn has_capacity(&self) -> bool {
+        match self.tx {
+            PoolTx::Http1(_) => true,
+
+            PoolTx::Http2(ref tx) => tx.has_capacity(),
+        }
+    }
 }
 
 enum ClientConnectError {
@@ -1023,7 +1474,15 @@ fn authority_form(uri: &mut Uri) {
     *uri = match uri.authority() {
         Some(auth) => {
             let mut parts = ::http::uri::Parts::default();
-            parts.authority = Some(auth.clone());
+            // The authority-form always has a port, which the URI may have left out.
+            parts.authority = match (auth.port(), uri.scheme()) {
+                (None, Some(_)) => {
+                    let port = if is_schema_secure(uri) { 443 } else { 80 };
+                    format!("{auth}:{port}").parse().ok()
+                }
+                _ => None,
+            }
+            .or_else(|| Some(auth.clone()));
             Uri::from_parts(parts).expect("authority is valid")
         }
         None => {
@@ -1039,12 +1498,14 @@ fn extract_request_configs(
     Option<Version>,
     Option<ProxyMacher>,
     Option<TcpConnectOptions>,
+    Option<Duration>,
 ) {
     let transport_config = RequestConfig::<RequestTransportConfig>::remove(extensions);
     let version = RequestConfig::<RequestEnforcedHttpVersion>::remove(extensions);
     let proxy = RequestConfig::<RequestProxyMatcher>::remove(extensions);
     let tcp = RequestConfig::<RequestTcpConnectOptions>::remove(extensions);
-    (transport_config, version, proxy, tcp)
+    let connect_timeout = RequestConfig::<RequestConnectTimeout>::remove(extensions);
+    (transport_config, version, proxy, tcp, connect_timeout)
 }
 
 fn normalize_uri<B>(req: &mut Request<B>, is_http_connect: bool) -> Result<Uri, Error> {
@@ -1133,6 +1594,8 @@ pub struct Builder {
     h2_builder: conn::http2::Builder<Exec>,
     pool_config: pool::Config,
     pool_timer: Option<timer::Timer>,
+    pool_clock: timer::Clock,
+    pool_events: Option<pool::Events<ConnKey>>,
 }
 
 impl Builder {
@@ -1145,6 +1608,9 @@ impl Builder {
         Self {
             client_config: Config {
                 retry_canceled_requests: true,
+                pool_checkout_timeout: None,
+                pool_partition: None,
+                health_check: PoolHealthCheck::None,
                 set_host: true,
                 ver: Ver::Auto,
             },
@@ -1154,10 +1620,15 @@ impl Builder {
             h2_builder: conn::http2::Builder::new(exec),
             pool_config: pool::Config {
                 idle_timeout: Some(Duration::from_secs(90)),
+                max_idle: usize::MAX,
                 max_idle_per_host: usize::MAX,
                 max_pool_size: None,
+                max_waiters_per_host: usize::MAX,
+                waiter_order: PoolWaiterOrder::Fifo,
             },
             pool_timer: None,
+            pool_clock: timer::Clock::default(),
+            pool_events: None,
         }
     }
     /// Set an optional timeout for idle sockets being kept-alive.
@@ -1198,6 +1669,16 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum idle connections allowed in the pool, across all hosts.
+    ///
+    /// When the pool is full, the connection idle the longest is evicted.
+    ///
+    /// Default is `usize::MAX` (no limit).
+    pub fn pool_max_idle(&mut self, max_idle: usize) -> &mut Self {
+        self.pool_config.max_idle = max_idle;
+        self
+    }
+
     /// Sets the maximum idle connection per host allowed in the pool.
     ///
     /// Default is `usize::MAX` (no limit).
@@ -1206,6 +1687,16 @@ impl Builder {
         self
     }
 
+    /// Sets the partition of the pool the connections of this client are kept in.
+    ///
+    /// Clients sharing a pool only reuse the connections of their own partition.
+    ///
+    /// Default is `None`.
+    pub fn pool_partition(&mut self, partition: impl Into<Option<u64>>) -> &mut Self {
+        self.client_config.pool_partition = partition.into();
+        self
+    }
+
     /// Sets the maximum number of connections in the pool.
     ///
     /// Default is `None` (no limit).
@@ -1214,6 +1705,50 @@ impl Builder {
         self
     }
 
+    /// Sets the maximum number of requests waiting for an idle connection per host.
+    ///
+    /// Requests beyond this limit do not wait for a connection to be returned to the pool and
+    /// only race their own connection attempt.
+    ///
+    /// Default is `usize::MAX` (no limit).
+    pub fn pool_max_waiters_per_host(&mut self, max_waiters: usize) -> &mut Self {
+        self.pool_config.max_waiters_per_host = max_waiters;
+        self
+    }
+
+    /// Sets which waiting request a connection returned to the pool is handed to.
+    ///
+    /// Default is [`PoolWaiterOrder::Fifo`].
+    pub fn pool_waiter_order(&mut self, order: PoolWaiterOrder) -> &mut Self {
+        self.pool_config.waiter_order = order;
+        self
+    }
+
+    /// Sets how idle HTTP/1 connections are probed before they are reused.
+    ///
+    /// A connection failing the probe is discarded and the request is retried on another one.
+    /// A `Timer` is required for the [`PoolHealthCheck::Options`] timeout to take effect.
+    ///
+    /// Default is [`PoolHealthCheck::None`].
+    pub fn pool_health_check(&mut self, check: PoolHealthCheck) -> &mut Self {
+        self.client_config.health_check = check;
+        self
+    }
+
+    /// Sets how long a request may wait for a pooled connection or a new one to be connected.
+    /// A `Timer` is required for this to take effect. See `Builder::pool_timer`
+    ///
+    /// Pass `None` to disable timeout.
+    ///
+    /// Default is `None`.
+    pub fn pool_checkout_timeout<D>(&mut self, val: D) -> &mut Self
+    where
+        D: Into<Option<Duration>>,
+    {
+        self.client_config.pool_checkout_timeout = val.into();
+        self
+    }
+
     /// Set whether the connection **must** use HTTP/2.
     ///
     /// The destination must either allow HTTP2 Prior Knowledge, or the
@@ -1264,6 +1799,17 @@ impl Builder {
         self
     }
 
+    /// Provide a clock to be used for the idle times of connections in connection pools.
+    ///
+    /// Default is the system clock.
+    pub fn pool_clock<C>(&mut self, clock: C) -> &mut Self
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.pool_clock = timer::Clock::new(clock);
+        self
+    }
+
     /// Set whether to retry requests that get disrupted before ever starting
     /// to write.
     ///
@@ -1293,8 +1839,50 @@ impl Builder {
         self
     }
 
+    /// Provide a pool the HTTP/1 connections take their read buffers from.
+    ///
+    /// Default is `None`, allocating a buffer for each connection.
+    pub fn read_buf_pool(&mut self, pool: BufPool) -> &mut Self {
+        self.h1_builder.read_buf_pool(pool);
+        self
+    }
+
+    /// Set the listener notified when connections are pooled, reused and evicted.
+    ///
+    /// Default is `None`.
+    pub fn connection_events(&mut self, events: Option<Arc<dyn ConnectionEvents>>) -> &mut Self {
+        self.pool_events = events.map(|events| {
+            Arc::new(move |key: &ConnKey, event| {
+                let origin = key.origin();
+                match event {
+                    pool::Event::Pooled => events.connection_pooled(&origin),
+                    pool::Event::Reused => events.connection_reused(&origin),
+                    pool::Event::Evicted(reason) => events.connection_evicted(&origin, reason),
+                }
+            }) as pool::Events<ConnKey>
+        });
+        self
+    }
+
     /// Combine the configuration of this builder with a connector to create a `Client`.
     pub fn build<C, B>(&self, connector: C) -> Client<C, B>
+    where
+        C: Connect + Clone,
+        B: Body + Send,
+        B::Data: Send,
+    {
+        self.build_with_pool(connector, None)
+    }
+
+    /// Combine the configuration of this builder with a connector to create a `Client`, which
+    /// reuses the connections of `pool` if given.
+    ///
+    /// The settings of a shared pool are those of the client it was created by.
+    pub(crate) fn build_with_pool<C, B>(
+        &self,
+        connector: C,
+        pool: Option<SharedPool<B>>,
+    ) -> Client<C, B>
     where
         C: Connect + Clone,
         B: Body + Send,
@@ -1302,14 +1890,20 @@ impl Builder {
     {
         let exec = self.exec.clone();
         let timer = self.pool_timer.clone();
+        let pool = pool.unwrap_or_else(|| {
+            pool::Pool::new(self.pool_config, exec.clone(), timer.clone())
+                .with_clock(self.pool_clock.clone())
+                .with_events(self.pool_events.clone())
+        });
         Client {
             config: self.client_config,
-            exec: exec.clone(),
+            exec,
 
             h1_builder: self.h1_builder.clone(),
             h2_builder: self.h2_builder.clone(),
             connector,
-            pool: pool::Pool::new(self.pool_config, exec, timer),
+            pool,
+            timer,
         }
     }
 }
@@ -1354,6 +1948,18 @@ impl Error {
         matches!(self.kind, ErrorKind::Connect)
     }
 
+    /// Returns the pool state if waiting for a connection timed out.
+    pub(crate) fn pool_timeout(&self) -> Option<PoolStats> {
+        match self.kind {
+            ErrorKind::PoolTimeout => self
+                .source
+                .as_ref()
+                .and_then(|e| e.downcast_ref::<pool::CheckoutTimedOut>())
+                .map(|e| e.0),
+            _ => None,
+        }
+    }
+
     /// Returns the info of the client connection on which this error occurred.
     pub fn connect_info(&self) -> Option<&Connected> {
         self.connect_info.as_ref()
@@ -1369,6 +1975,12 @@ impl Error {
         matches!(self.kind, ErrorKind::Canceled)
     }
 
+    /// Returns true if the server closed a reused HTTP/1 connection before responding, likely
+    /// as the request was written to it, so that it can be sent again on a new connection.
+    pub(crate) fn is_stale_connection(&self) -> bool {
+        matches!(self.kind, ErrorKind::StaleConnection)
+    }
+
     fn tx(src: crate::core::Error) -> Self {
         e!(SendRequest, src)
     }
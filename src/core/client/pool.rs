@@ -20,11 +20,12 @@ use crate::{
     core::{
         common::{
             exec::{self, Exec},
-            timer::Timer,
+            timer::{Clock, Timer},
         },
         map::{HashMap, HashSet, LruMap, RANDOM_STATE},
-        rt::{Sleep, Timer as _},
+        rt::{Clock as _, Sleep, Timer as _},
     },
+    events::EvictionReason,
     sync::Mutex,
 };
 
@@ -47,6 +48,13 @@ pub trait Poolable: Unpin + Send + Sized + 'static {
     /// Allows for HTTP/2 to return a shared reservation.
     fn reserve(self) -> Reservation<Self>;
     fn can_share(&self) -> bool;
+    /// Whether a shared connection can take another request.
+    ///
+    /// A shared connection without capacity stays idle, but a new one is opened for the
+    /// requests checked out in the meantime.
+    fn has_capacity(&self) -> bool {
+        true
+    }
 }
 
 pub trait Key: Eq + Hash + Clone + Debug + Unpin + Send + 'static {}
@@ -77,6 +85,22 @@ pub enum Reservation<T> {
     Unique(T),
 }
 
+/// A hook notified of the lifecycle of the connections of the pool.
+///
+/// It is called while the pool is locked.
+pub(crate) type Events<K> = Arc<dyn Fn(&K, Event) + Send + Sync>;
+
+/// A pool lifecycle event of a connection.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Event {
+    /// The connection was put in the idle list.
+    Pooled,
+    /// The connection was checked out for another request.
+    Reused,
+    /// The connection was dropped from the idle list.
+    Evicted(EvictionReason),
+}
+
 /// Simple type alias in case the key type needs to be adjusted.
 // pub type Key = (http::uri::Scheme, http::uri::Authority); //Arc<String>;
 struct PoolInner<T, K: Eq + Hash> {
@@ -87,7 +111,10 @@ struct PoolInner<T, K: Eq + Hash> {
     // These are internal Conns sitting in the event loop in the KeepAlive
     // state, waiting to receive a new Request to send on the socket.
     idle: LruMap<K, Vec<Idle<T>>>,
+    max_idle: usize,
     max_idle_per_host: usize,
+    max_waiters_per_host: usize,
+    waiter_order: PoolWaiterOrder,
     // These are outstanding Checkouts that are waiting for a socket to be
     // able to send a Request one. This is used when "racing" for a new
     // connection.
@@ -103,9 +130,14 @@ struct PoolInner<T, K: Eq + Hash> {
     idle_interval_ref: Option<oneshot::Sender<Infallible>>,
     exec: Exec,
     timer: Option<Timer>,
+    clock: Clock,
     timeout: Option<Duration>,
+    events: Option<Events<K>>,
 }
 
+/// A handle to a `Pool` that does not keep it alive.
+pub(crate) struct WeakPool<T, K: Key>(WeakOpt<Mutex<PoolInner<T, K>>>);
+
 // This is because `Weak::new()` *allocates* space for `T`, even if it
 // doesn't need it!
 struct WeakOpt<T>(Option<Weak<T>>);
@@ -113,13 +145,57 @@ struct WeakOpt<T>(Option<Weak<T>>);
 #[derive(Clone, Copy, Debug)]
 pub struct Config {
     pub idle_timeout: Option<Duration>,
+    pub max_idle: usize,
     pub max_idle_per_host: usize,
     pub max_pool_size: Option<NonZero<u32>>,
+    pub max_waiters_per_host: usize,
+    pub waiter_order: PoolWaiterOrder,
+}
+
+/// The order in which requests waiting for a connection are served when one is returned to
+/// the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolWaiterOrder {
+    /// The connection is handed to the request that has been waiting the longest.
+    ///
+    /// This bounds how long any single request waits.
+    #[default]
+    Fifo,
+    /// The connection is handed to the request that started waiting most recently.
+    ///
+    /// Under saturation this keeps most requests fast at the cost of a few very slow ones,
+    /// which are likely to be timed out anyway.
+    Lifo,
 }
 
 impl Config {
     pub fn is_enabled(&self) -> bool {
-        self.max_idle_per_host > 0
+        self.max_idle > 0 && self.max_idle_per_host > 0
+    }
+}
+
+/// A snapshot of the connection pool state for a single destination.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    idle: usize,
+    waiting: usize,
+    connecting: bool,
+}
+
+impl PoolStats {
+    /// Returns the number of idle connections kept for the destination.
+    pub fn idle(&self) -> usize {
+        self.idle
+    }
+
+    /// Returns the number of requests waiting for a connection to the destination.
+    pub fn waiting(&self) -> usize {
+        self.waiting
+    }
+
+    /// Returns true if an HTTP/2 connection to the destination was being established.
+    pub fn connecting(&self) -> bool {
+        self.connecting
     }
 }
 
@@ -137,11 +213,16 @@ impl<T, K: Key> Pool<T, K> {
                     RANDOM_STATE,
                 ),
                 idle_interval_ref: None,
+                max_idle: config.max_idle,
                 max_idle_per_host: config.max_idle_per_host,
+                max_waiters_per_host: config.max_waiters_per_host,
+                waiter_order: config.waiter_order,
                 waiters: HashMap::with_hasher(RANDOM_STATE),
                 exec: Exec::new(executor),
                 timer: timer.map(Timer::new),
+                clock: Clock::default(),
                 timeout: config.idle_timeout,
+                events: None,
             })))
         } else {
             None
@@ -150,12 +231,96 @@ impl<T, K: Key> Pool<T, K> {
         Pool { inner }
     }
 
+    /// Sets the hook notified of the lifecycle of the connections of the pool.
+    pub(crate) fn with_events(self, events: Option<Events<K>>) -> Pool<T, K> {
+        if let Some(ref inner) = self.inner {
+            inner.lock().events = events;
+        }
+        self
+    }
+
+    /// Sets the clock that idle connections are timed with.
+    pub(crate) fn with_clock(self, clock: Clock) -> Pool<T, K> {
+        if let Some(ref inner) = self.inner {
+            inner.lock().clock = clock;
+        }
+        self
+    }
+
     pub(crate) fn is_enabled(&self) -> bool {
         self.inner.is_some()
     }
+
+    /// Returns a pool that is disabled.
+    pub(crate) fn disabled() -> Pool<T, K> {
+        Pool { inner: None }
+    }
+
+    /// Returns a handle to this pool that does not keep it alive.
+    pub(crate) fn downgrade(&self) -> WeakPool<T, K> {
+        WeakPool(
+            self.inner
+                .as_ref()
+                .map_or_else(WeakOpt::none, WeakOpt::downgrade),
+        )
+    }
+}
+
+impl<T, K: Key> WeakPool<T, K> {
+    /// Returns the pool if it is still alive.
+    pub(crate) fn upgrade(&self) -> Option<Pool<T, K>> {
+        self.0.upgrade().map(|inner| Pool { inner: Some(inner) })
+    }
 }
 
 impl<T: Poolable, K: Key> Pool<T, K> {
+    /// Returns a snapshot of the pool state for `key`.
+    pub(crate) fn stats(&self, key: &K) -> PoolStats {
+        let Some(ref inner) = self.inner else {
+            return PoolStats::default();
+        };
+
+        let inner = inner.lock();
+        PoolStats {
+            idle: inner.idle.peek(key).map_or(0, |list| {
+                list.iter().filter(|idle| idle.value.is_open()).count()
+            }),
+            waiting: inner.waiters.get(key).map_or(0, |waiters| {
+                waiters.iter().filter(|tx| !tx.is_closed()).count()
+            }),
+            connecting: inner.connecting.contains(key),
+        }
+    }
+
+    /// Returns a snapshot of the pool state summed over the keys matching `f`.
+    pub(crate) fn stats_where<F>(&self, f: F) -> PoolStats
+    where
+        F: Fn(&K) -> bool,
+    {
+        let Some(ref inner) = self.inner else {
+            return PoolStats::default();
+        };
+
+        let inner = inner.lock();
+        PoolStats {
+            idle: inner
+                .idle
+                .iter()
+                .filter(|(key, _)| f(key))
+                .flat_map(|(_, list)| list.iter())
+                .filter(|idle| idle.value.is_open())
+                .count(),
+            waiting: inner
+                .waiters
+                .iter()
+                .filter(|(key, _)| f(key))
+                .flat_map(|(_, waiters)| waiters.iter())
+                .filter(|tx| !tx.is_closed())
+                .count(),
+            connecting: inner.connecting.iter().any(f),
+        }
+    }
+
     /// Returns a `Checkout` which is a future that resolves if an idle
     /// connection becomes available.
     pub fn checkout(&self, key: K) -> Checkout<T, K> {
@@ -263,18 +428,30 @@ impl<T: Poolable, K: Key> Pool<T, K> {
 
 /// Pop off this list, looking for a usable connection that hasn't expired.
 struct IdlePopper<'a, T, K> {
-    #[allow(dead_code)]
     key: &'a K,
     list: &'a mut Vec<Idle<T>>,
+    events: Option<&'a Events<K>>,
 }
 
 impl<'a, T: Poolable + 'a, K: Debug> IdlePopper<'a, T, K> {
-    fn pop(self, expiration: &Expiration) -> Option<Idle<T>> {
+    fn pop(mut self, expiration: &Expiration) -> Option<Idle<T>> {
+        let mut exhausted = Vec::new();
+        let popped = self.pop_open(expiration, &mut exhausted);
+        self.list.append(&mut exhausted);
+        popped
+    }
+
+    fn pop_open(
+        &mut self,
+        expiration: &Expiration,
+        exhausted: &mut Vec<Idle<T>>,
+    ) -> Option<Idle<T>> {
         while let Some(entry) = self.list.pop() {
             // If the connection has been closed, or is older than our idle
             // timeout, simply drop it and keep looking...
             if !entry.value.is_open() {
                 trace!("removing closed connection for {:?}", self.key);
+                self.notify(Event::Evicted(EvictionReason::Closed));
                 continue;
             }
             // TODO: Actually, since the `idle` list is pushed to the end always,
@@ -285,13 +462,19 @@ impl<'a, T: Poolable + 'a, K: Debug> IdlePopper<'a, T, K> {
             // whole list...
             if expiration.expires(entry.idle_at) {
                 trace!("removing expired connection for {:?}", self.key);
+                self.notify(Event::Evicted(EvictionReason::IdleTimeout));
+                continue;
+            }
+            if entry.value.can_share() && !entry.value.has_capacity() {
+                trace!("skipping exhausted connection for {:?}", self.key);
+                exhausted.push(entry);
                 continue;
             }
 
             let value = match entry.value.reserve() {
                 Reservation::Shared(to_reinsert, to_checkout) => {
                     self.list.push(Idle {
-                        idle_at: Instant::now(),
+                        idle_at: expiration.now(),
                         value: to_reinsert,
                     });
                     to_checkout
@@ -299,6 +482,7 @@ impl<'a, T: Poolable + 'a, K: Debug> IdlePopper<'a, T, K> {
                 Reservation::Unique(unique) => unique,
             };
 
+            self.notify(Event::Reused);
             return Some(Idle {
                 idle_at: entry.idle_at,
                 value,
@@ -307,11 +491,22 @@ impl<'a, T: Poolable + 'a, K: Debug> IdlePopper<'a, T, K> {
 
         None
     }
+
+    fn notify(&self, event: Event) {
+        if let Some(events) = self.events {
+            events(self.key, event);
+        }
+    }
 }
 
 impl<T: Poolable, K: Key> PoolInner<T, K> {
     fn put(&mut self, key: &K, value: T, __pool_ref: &Arc<Mutex<PoolInner<T, K>>>) {
-        if value.can_share() && self.idle.peek(key).is_some() {
+        if value.can_share()
+            && self
+                .idle
+                .peek(key)
+                .is_some_and(|list| list.iter().any(|idle| idle.value.has_capacity()))
+        {
             trace!("put; existing idle HTTP/2 connection for {:?}", key);
             return;
         }
@@ -319,7 +514,11 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
         let mut remove_waiters = false;
         let mut value = Some(value);
         if let Some(waiters) = self.waiters.get_mut(key) {
-            while let Some(tx) = waiters.pop_front() {
+            let order = self.waiter_order;
+            while let Some(tx) = match order {
+                PoolWaiterOrder::Fifo => waiters.pop_front(),
+                PoolWaiterOrder::Lifo => waiters.pop_back(),
+            } {
                 if !tx.is_closed() {
                     let reserved = value.take().expect("value already sent");
                     let reserved = match reserved.reserve() {
@@ -331,6 +530,9 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
                     };
                     match tx.send(reserved) {
                         Ok(()) => {
+                            if let Some(ref events) = self.events {
+                                events(key, Event::Reused);
+                            }
                             if value.is_none() {
                                 break;
                             } else {
@@ -362,16 +564,23 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
                 if let Some(idle_list) = idle_list {
                     if self.max_idle_per_host <= idle_list.len() {
                         trace!("max idle per host for {:?}, dropping connection", key);
+                        if let Some(ref events) = self.events {
+                            events(key, Event::Evicted(EvictionReason::PoolFull));
+                        }
                         return;
                     }
 
                     debug!("pooling idle connection for {:?}", key);
                     idle_list.push(Idle {
                         value,
-                        idle_at: Instant::now(),
+                        idle_at: self.clock.now(),
                     });
+                    if let Some(ref events) = self.events {
+                        events(key, Event::Pooled);
+                    }
                 }
             }
+            self.evict_over_max_idle();
 
             self.spawn_idle_interval(__pool_ref);
         } else {
@@ -379,6 +588,40 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
         }
     }
 
+    /// Drops the connections idle the longest, across all hosts, until the pool holds no more
+    /// than `max_idle` idle connections.
+    fn evict_over_max_idle(&mut self) {
+        if self.max_idle == usize::MAX {
+            return;
+        }
+
+        let mut idle = self.idle.iter().map(|(_, list)| list.len()).sum::<usize>();
+        while idle > self.max_idle {
+            // Each idle list is in the order the connections were put back.
+            let Some(key) = self
+                .idle
+                .iter()
+                .filter_map(|(key, list)| list.first().map(|entry| (key, entry.idle_at)))
+                .min_by_key(|(_, idle_at)| *idle_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            trace!("max idle connections, evicting the oldest for {:?}", key);
+            if let Some(list) = self.idle.peek_mut(&key) {
+                list.remove(0);
+                if list.is_empty() {
+                    self.idle.remove(&key);
+                }
+            }
+            if let Some(ref events) = self.events {
+                events(&key, Event::Evicted(EvictionReason::IdleLimit));
+            }
+            idle -= 1;
+        }
+    }
+
     /// A `Connecting` task is complete. Not necessarily successfully,
     /// but the lock is going away, so clean up.
     fn connected(&mut self, key: &K) {
@@ -410,11 +653,13 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
         let (tx, rx) = oneshot::channel();
         self.idle_interval_ref = Some(tx);
 
+        let now = self.clock.now();
         let interval = IdleTask {
             timer: timer.clone(),
+            clock: self.clock.clone(),
             duration: dur,
-            deadline: Instant::now(),
-            fut: timer.sleep_until(Instant::now()), // ready at first tick
+            deadline: now,
+            fut: timer.sleep_until(now), // ready at first tick
             pool: WeakOpt::downgrade(pool_ref),
             pool_drop_notifier: rx,
         };
@@ -444,24 +689,28 @@ impl<T: Poolable, K: Key> PoolInner<T, K> {
     /// This should *only* be called by the IdleTask
     fn clear_expired(&mut self) {
         let dur = self.timeout.expect("interval assumes timeout");
-        let now = Instant::now();
+        let now = self.clock.now();
 
         let mut keys_to_remove = Vec::new();
+        let events = self.events.as_ref();
         for (key, values) in self.idle.iter_mut() {
             values.retain(|entry| {
-                if !entry.value.is_open() {
+                let reason = if !entry.value.is_open() {
                     trace!("idle interval evicting closed for {:?}", key);
-                    return false;
-                }
-
-                // Avoid `Instant::sub` to avoid issues like rust-lang/rust#86470.
-                if now.saturating_duration_since(entry.idle_at) > dur {
+                    EvictionReason::Closed
+                } else if now.saturating_duration_since(entry.idle_at) > dur {
+                    // Avoid `Instant::sub` to avoid issues like rust-lang/rust#86470.
                     trace!("idle interval evicting expired for {:?}", key);
-                    return false;
-                }
+                    EvictionReason::IdleTimeout
+                } else {
+                    // Otherwise, keep this value...
+                    return true;
+                };
 
-                // Otherwise, keep this value...
-                true
+                if let Some(events) = events {
+                    events(key, Event::Evicted(reason));
+                }
+                false
             });
 
             // If the list is empty, remove the key.
@@ -571,12 +820,17 @@ pub enum Error {
     PoolDisabled,
     CheckoutNoLongerWanted,
     CheckedOutClosedValue,
+    TooManyWaiters,
 }
 
 impl Error {
     pub(super) fn is_canceled(&self) -> bool {
         matches!(self, Error::CheckedOutClosedValue)
     }
+
+    pub(super) fn is_too_many_waiters(&self) -> bool {
+        matches!(self, Error::TooManyWaiters)
+    }
 }
 
 impl fmt::Display for Error {
@@ -585,12 +839,29 @@ impl fmt::Display for Error {
             Error::PoolDisabled => "pool is disabled",
             Error::CheckedOutClosedValue => "checked out connection was closed",
             Error::CheckoutNoLongerWanted => "request was canceled",
+            Error::TooManyWaiters => "too many requests waiting for a pooled connection",
         })
     }
 }
 
 impl StdError for Error {}
 
+/// The error returned when a request waited too long for a connection.
+#[derive(Debug)]
+pub(crate) struct CheckoutTimedOut(pub(crate) PoolStats);
+
+impl fmt::Display for CheckoutTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "timed out waiting for a connection (idle: {}, waiting: {}, connecting: {})",
+            self.0.idle, self.0.waiting, self.0.connecting
+        )
+    }
+}
+
+impl StdError for CheckoutTimedOut {}
+
 impl<T: Poolable, K: Key> Checkout<T, K> {
     fn poll_waiter(
         &mut self,
@@ -621,33 +892,38 @@ impl<T: Poolable, K: Key> Checkout<T, K> {
     fn checkout(&mut self, cx: &mut task::Context<'_>) -> Option<Pooled<T, K>> {
         let entry = {
             let mut inner = self.pool.inner.as_ref()?.lock();
-            let expiration = Expiration::new(inner.timeout);
-            let maybe_entry = inner.idle.get(&self.key).and_then(|list| {
+            let expiration = Expiration::new(inner.timeout, inner.clock.clone());
+            let events = inner.events.clone();
+            let maybe_entry = inner.idle.get(&self.key).map(|list| {
                 trace!("take? {:?}: expiration = {:?}", self.key, expiration.0);
                 // A block to end the mutable borrow on list,
-                // so the map below can check is_empty()
-                {
+                // so the tuple below can check is_empty()
+                let entry = {
                     let popper = IdlePopper {
                         key: &self.key,
                         list,
+                        events: events.as_ref(),
                     };
                     popper.pop(&expiration)
-                }
-                .map(|e| (e, list.is_empty()))
+                };
+                // Without an entry, the list only keeps exhausted shared connections.
+                (entry, list.is_empty())
             });
 
-            let (entry, empty) = if let Some((e, empty)) = maybe_entry {
-                (Some(e), empty)
-            } else {
-                // No entry found means nuke the list for sure.
-                (None, true)
-            };
+            let (entry, empty) = maybe_entry.unwrap_or((None, true));
 
             if empty {
                 inner.idle.remove(&self.key);
             }
 
             if entry.is_none() && self.waiter.is_none() {
+                inner.clean_waiters(&self.key);
+                let waiting = inner.waiters.get(&self.key).map_or(0, VecDeque::len);
+                if waiting >= inner.max_waiters_per_host {
+                    trace!("checkout has too many waiters: {:?}", self.key);
+                    return None;
+                }
+
                 let (tx, mut rx) = oneshot::channel();
                 trace!("checkout waiting for idle connection: {:?}", self.key);
                 inner
@@ -683,6 +959,8 @@ impl<T: Poolable, K: Key> Future for Checkout<T, K> {
             Poll::Ready(Ok(pooled))
         } else if !self.pool.is_enabled() {
             Poll::Ready(Err(Error::PoolDisabled))
+        } else if self.waiter.is_none() {
+            Poll::Ready(Err(Error::TooManyWaiters))
         } else {
             // There's a new waiter, already registered in self.checkout()
             debug_assert!(self.waiter.is_some());
@@ -730,17 +1008,21 @@ impl<T: Poolable, K: Key> Drop for Connecting<T, K> {
     }
 }
 
-struct Expiration(Option<Duration>);
+struct Expiration(Option<Duration>, Clock);
 
 impl Expiration {
-    fn new(dur: Option<Duration>) -> Expiration {
-        Expiration(dur)
+    fn new(dur: Option<Duration>, clock: Clock) -> Expiration {
+        Expiration(dur, clock)
+    }
+
+    fn now(&self) -> Instant {
+        self.1.now()
     }
 
     fn expires(&self, instant: Instant) -> bool {
         match self.0 {
             // Avoid `Instant::elapsed` to avoid issues like rust-lang/rust#86470.
-            Some(timeout) => Instant::now().saturating_duration_since(instant) > timeout,
+            Some(timeout) => self.now().saturating_duration_since(instant) > timeout,
             None => false,
         }
     }
@@ -749,6 +1031,7 @@ impl Expiration {
 pin_project_lite::pin_project! {
     struct IdleTask<T, K: Key> {
         timer: Timer,
+        clock: Clock,
         duration: Duration,
         deadline: Instant,
         fut: Pin<Box<dyn Sleep>>,
@@ -781,8 +1064,9 @@ impl<T: Poolable + 'static, K: Key> Future for IdleTask<T, K> {
             // If the poll missed the deadline by a lot, set the deadline
             // from the current time instead
             *this.deadline += *this.duration;
-            if *this.deadline < Instant::now() - Duration::from_millis(5) {
-                *this.deadline = Instant::now() + *this.duration;
+            let now = this.clock.now();
+            if *this.deadline < now - Duration::from_millis(5) {
+                *this.deadline = now + *this.duration;
             }
             *this.fut = this.timer.sleep_until(*this.deadline);
 
@@ -820,11 +1104,12 @@ mod tests {
         hash::Hash,
         num::NonZero,
         pin::Pin,
+        sync::Arc,
         task::{self, Poll},
         time::Duration,
     };
 
-    use super::{Connecting, Key, Pool, Poolable, Reservation, WeakOpt};
+    use super::{Connecting, Key, Pool, PoolWaiterOrder, Poolable, Reservation, WeakOpt};
     use crate::{
         core::{
             common::timer,
@@ -873,8 +1158,29 @@ mod tests {
         Pool::new(
             super::Config {
                 idle_timeout: Some(Duration::from_millis(100)),
+                max_idle: usize::MAX,
                 max_idle_per_host: max_idle,
                 max_pool_size: None,
+                max_waiters_per_host: usize::MAX,
+                waiter_order: PoolWaiterOrder::Fifo,
+            },
+            TokioExecutor::new(),
+            Option::<timer::Timer>::None,
+        )
+    }
+
+    fn pool_max_waiters_no_timer<T, K: Key>(
+        max_waiters: usize,
+        waiter_order: PoolWaiterOrder,
+    ) -> Pool<T, K> {
+        Pool::new(
+            super::Config {
+                idle_timeout: Some(Duration::from_millis(100)),
+                max_idle: usize::MAX,
+                max_idle_per_host: usize::MAX,
+                max_pool_size: None,
+                max_waiters_per_host: max_waiters,
+                waiter_order,
             },
             TokioExecutor::new(),
             Option::<timer::Timer>::None,
@@ -973,13 +1279,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pool_max_idle() {
+        let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events: super::Events<KeyImpl> = {
+            let evicted = evicted.clone();
+            Arc::new(move |key: &KeyImpl, event: super::Event| {
+                if let super::Event::Evicted(reason) = event {
+                    evicted.lock().unwrap().push((key.clone(), reason));
+                }
+            })
+        };
+        let pool = Pool::new(
+            super::Config {
+                idle_timeout: None,
+                max_idle: 2,
+                max_idle_per_host: usize::MAX,
+                max_pool_size: None,
+                max_waiters_per_host: usize::MAX,
+                waiter_order: PoolWaiterOrder::Fifo,
+            },
+            TokioExecutor::new(),
+            Option::<timer::Timer>::None,
+        )
+        .with_events(Some(events));
+        let (foo, bar) = (host_key("foo"), host_key("bar"));
+
+        for (key, value) in [(&foo, 41), (&bar, 5), (&bar, 99)] {
+            pool.pooled(c(key.clone()), Uniq(value));
+            // Idle connections are ordered by the time they were put back.
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // the connection idle the longest is evicted, across hosts
+        assert!(pool.locked().idle.get(&foo).is_none());
+        assert_eq!(
+            pool.locked().idle.get(&bar).map(|entries| entries.len()),
+            Some(2)
+        );
+        assert_eq!(
+            *evicted.lock().unwrap(),
+            [(foo, crate::events::EvictionReason::IdleLimit)]
+        );
+    }
+
     #[tokio::test]
     async fn test_pool_timer_removes_expired() {
         let pool = Pool::new(
             super::Config {
                 idle_timeout: Some(Duration::from_millis(10)),
+                max_idle: usize::MAX,
                 max_idle_per_host: usize::MAX,
                 max_pool_size: None,
+                max_waiters_per_host: usize::MAX,
+                waiter_order: PoolWaiterOrder::Fifo,
             },
             TokioExecutor::new(),
             Some(TokioTimer::new()),
@@ -1050,6 +1403,50 @@ mod tests {
         assert!(!pool.locked().waiters.contains_key(&key));
     }
 
+    #[tokio::test]
+    async fn test_pool_checkout_max_waiters_per_host() {
+        let pool = pool_max_waiters_no_timer::<Uniq<i32>, KeyImpl>(1, PoolWaiterOrder::Fifo);
+        let key = host_key("foo");
+
+        let mut checkout1 = pool.checkout(key.clone());
+        PollOnce(&mut checkout1).await;
+        assert_eq!(pool.locked().waiters.get(&key).unwrap().len(), 1);
+
+        let err = pool.checkout(key.clone()).await.unwrap_err();
+        assert!(err.is_too_many_waiters());
+
+        // a dropped waiter frees its slot
+        drop(checkout1);
+        let mut checkout2 = pool.checkout(key.clone());
+        assert!(PollOnce(&mut checkout2).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pool_waiter_order() {
+        for (order, newest_served) in [
+            (PoolWaiterOrder::Fifo, false),
+            (PoolWaiterOrder::Lifo, true),
+        ] {
+            let pool = pool_max_waiters_no_timer(usize::MAX, order);
+            let key = host_key("foo");
+
+            let mut oldest = pool.checkout(key.clone());
+            let mut newest = pool.checkout(key.clone());
+            PollOnce(&mut oldest).await;
+            PollOnce(&mut newest).await;
+
+            pool.pooled(c(key.clone()), Uniq(41));
+
+            let (served, waiting) = if newest_served {
+                (newest, &mut oldest)
+            } else {
+                (oldest, &mut newest)
+            };
+            assert_eq!(*served.await.unwrap(), Uniq(41));
+            assert!(PollOnce(waiting).await.is_none());
+        }
+    }
+
     #[derive(Debug)]
     struct CanClose {
         #[allow(unused)]
@@ -1091,8 +1488,11 @@ mod tests {
         let pool = Pool::new(
             super::Config {
                 idle_timeout: Some(Duration::from_millis(100)),
+                max_idle: usize::MAX,
                 max_idle_per_host: usize::MAX,
                 max_pool_size: Some(NonZero::new(2).expect("max pool size")),
+                max_waiters_per_host: usize::MAX,
+                waiter_order: PoolWaiterOrder::Fifo,
             },
             TokioExecutor::new(),
             Option::<timer::Timer>::None,
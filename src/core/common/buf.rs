@@ -1,7 +1,16 @@
-use std::{collections::VecDeque, io::IoSlice};
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::IoSlice,
+    mem,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 
+use crate::sync::Mutex;
+
 pub(crate) struct BufList<T> {
     bufs: VecDeque<T>,
 }
@@ -90,12 +99,204 @@ impl<T: Buf> Buf for BufList<T> {
     }
 }
 
+/// A pool of buffers, recycled once the message or decoder holding them is done with them.
+///
+/// The chunks split off a buffer share its allocation, so a buffer given back while some of
+/// them are alive is set aside, and reused once they have all been dropped.
+#[derive(Clone)]
+pub(crate) struct BufPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    bufs: Mutex<Bufs>,
+    capacity: usize,
+    chunk_size: usize,
+}
+
+struct Bufs {
+    /// The buffers ready to be reused.
+    free: Vec<BytesMut>,
+    /// The buffers still sharing their allocation with chunks split off them, oldest first.
+    pending: VecDeque<BytesMut>,
+}
+
+impl BufPool {
+    /// Creates a pool keeping up to `capacity` buffers of at least `chunk_size` bytes.
+    pub(crate) fn new(capacity: usize, chunk_size: usize) -> BufPool {
+        BufPool {
+            inner: Arc::new(PoolInner {
+                bufs: Mutex::new(Bufs {
+                    free: Vec::with_capacity(capacity),
+                    pending: VecDeque::with_capacity(capacity),
+                }),
+                capacity,
+                chunk_size,
+            }),
+        }
+    }
+
+    /// Takes a buffer from the pool, or allocates one if no buffer can be reused.
+    pub(crate) fn take(&self) -> BytesMut {
+        let chunk_size = self.inner.chunk_size;
+        let mut bufs = self.inner.bufs.lock();
+        if let Some(buf) = bufs.free.pop() {
+            return buf;
+        }
+
+        // The chunks of the oldest buffers set aside are the likeliest to have been dropped.
+        let reclaimed = bufs
+            .pending
+            .iter_mut()
+            .position(|buf| buf.try_reclaim(chunk_size));
+        match reclaimed.and_then(|i| bufs.pending.remove(i)) {
+            Some(buf) => buf,
+            None => BytesMut::with_capacity(chunk_size),
+        }
+    }
+
+    /// Gives a buffer back to the pool, unless the pool is full.
+    ///
+    /// A buffer still sharing its allocation with chunks split off it is set aside until they
+    /// are dropped, replacing the oldest one set aside if there is no room left.
+    pub(crate) fn recycle(&self, mut buf: BytesMut) {
+        buf.clear();
+        let mut bufs = self.inner.bufs.lock();
+        if buf.try_reclaim(self.inner.chunk_size) {
+            if bufs.free.len() < self.inner.capacity {
+                bufs.free.push(buf);
+            }
+            return;
+        }
+
+        if bufs.pending.len() == self.inner.capacity {
+            bufs.pending.pop_front();
+        }
+        if self.inner.capacity > 0 {
+            bufs.pending.push_back(buf);
+        }
+    }
+}
+
+impl fmt::Debug for BufPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufPool")
+            .field("capacity", &self.inner.capacity)
+            .field("chunk_size", &self.inner.chunk_size)
+            .finish()
+    }
+}
+
+/// A buffer given back to its pool once dropped.
+#[derive(Default)]
+pub(crate) struct PooledBuf {
+    buf: BytesMut,
+    pool: Option<BufPool>,
+}
+
+impl PooledBuf {
+    /// Takes a buffer from the pool.
+    pub(crate) fn new(pool: BufPool) -> PooledBuf {
+        PooledBuf {
+            buf: pool.take(),
+            pool: Some(pool),
+        }
+    }
+
+    /// Takes the buffer out, without giving it back to the pool.
+    pub(crate) fn detach(&mut self) -> BytesMut {
+        self.pool = None;
+        mem::take(&mut self.buf)
+    }
+
+    /// Gives the buffer back to the pool until [`PooledBuf::refill`] is called, so that it is
+    /// reused in the meantime.
+    pub(crate) fn release(&mut self) {
+        if let Some(ref pool) = self.pool {
+            pool.recycle(mem::take(&mut self.buf));
+        }
+    }
+
+    /// Takes a buffer from the pool again if the previous one was released.
+    pub(crate) fn refill(&mut self) {
+        if let Some(ref pool) = self.pool {
+            if self.buf.capacity() == 0 {
+                self.buf = pool.take();
+            }
+        }
+    }
+}
+
+impl Deref for PooledBuf {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuf {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+}
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.recycle(mem::take(&mut self.buf));
+        }
+    }
+}
+
+impl fmt::Debug for PooledBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.buf.fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::ptr;
 
     use super::*;
 
+    #[test]
+    fn pool_reuses_buffer_once_chunks_dropped() {
+        let pool = BufPool::new(1, 64);
+        let mut buf = pool.take();
+        buf.put_slice(b"Hello");
+        let chunk = buf.split_to(5).freeze();
+
+        // The chunk still shares the allocation, so the buffer is set aside.
+        pool.recycle(buf);
+        let other = pool.take();
+        assert!(!ptr::eq(other.as_ptr(), chunk.as_ptr()));
+
+        let allocation = chunk.as_ptr();
+        drop(chunk);
+        let reused = pool.take();
+        assert!(ptr::eq(reused.as_ptr(), allocation));
+        assert!(reused.capacity() >= 64);
+    }
+
+    #[test]
+    fn pooled_buf_released_between_messages() {
+        let pool = BufPool::new(1, 64);
+        let mut buf = PooledBuf::new(pool.clone());
+        let allocation = buf.as_ptr();
+
+        buf.release();
+        assert_eq!(buf.capacity(), 0);
+        // Another connection takes the released buffer in the meantime.
+        let mut other = PooledBuf::new(pool.clone());
+        assert!(ptr::eq(other.as_ptr(), allocation));
+        other.release();
+
+        buf.refill();
+        assert!(ptr::eq(buf.as_ptr(), allocation));
+    }
+
     fn hello_world_buf() -> BufList<Bytes> {
         BufList {
             bufs: vec![Bytes::from("Hello"), Bytes::from(" "), Bytes::from("World")].into(),
@@ -146,4 +347,22 @@ mod tests {
     fn buf_to_bytes_too_many() {
         hello_world_buf().copy_to_bytes(42);
     }
+
+    #[test]
+    fn pool_recycles_unshared_buffers() {
+        let pool = BufPool::new(1, 64);
+
+        let mut buf = PooledBuf::new(pool.clone());
+        buf.extend_from_slice(b"Hello");
+        let ptr = buf.as_ptr();
+        drop(buf);
+        assert!(ptr::eq(pool.take().as_ptr(), ptr));
+
+        // A buffer still shared with a chunk split off it is not recycled.
+        let mut buf = PooledBuf::new(pool.clone());
+        buf.extend_from_slice(b"Hello");
+        let chunk = buf.split().freeze();
+        drop(buf);
+        assert!(!ptr::eq(pool.take().as_ptr(), chunk.as_ptr()));
+    }
 }
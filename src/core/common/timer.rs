@@ -4,10 +4,10 @@ use std::{
     fmt,
     pin::Pin,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
-use crate::core::rt::Sleep;
+use crate::core::rt::{Sleep, SystemClock};
 
 #[derive(Clone)]
 pub(crate) struct Timer(Arc<dyn crate::core::rt::Timer + Send + Sync>);
@@ -37,3 +37,38 @@ impl crate::core::rt::Timer for Timer {
         self.0.sleep_until(deadline)
     }
 }
+
+#[derive(Clone)]
+pub(crate) struct Clock(Arc<dyn crate::core::rt::Clock + Send + Sync>);
+
+// =====impl Clock=====
+impl Clock {
+    pub(crate) fn new<C>(inner: C) -> Self
+    where
+        C: crate::core::rt::Clock + Send + Sync + 'static,
+    {
+        Self(Arc::new(inner))
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clock").finish()
+    }
+}
+
+impl crate::core::rt::Clock for Clock {
+    fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.0.system_time()
+    }
+}
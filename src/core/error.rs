@@ -79,6 +79,8 @@ pub(crate) enum Header {
     Token,
     ContentLengthInvalid,
     TransferEncodingUnexpected,
+    LengthConflict,
+    LineEnding,
 }
 
 #[derive(Debug)]
@@ -91,6 +93,13 @@ pub(super) enum User {
     /// User tried to send a connect request with a nonzero body
     InvalidConnectWithBody,
 
+    /// User tried to send an extended CONNECT request to a server not supporting it.
+    #[cfg(feature = "websocket")]
+    ExtendedConnectDisabled,
+
+    /// User tried to open a stream on an HTTP/2 connection having reached its limit.
+    StreamsExhausted,
+
     /// Error from future of user's Service.
     Service,
 
@@ -130,6 +139,18 @@ impl Error {
         matches!(self.inner.kind, Kind::Canceled)
     }
 
+    /// Returns true if the server does not support the extended CONNECT protocol.
+    #[cfg(feature = "websocket")]
+    pub(crate) fn is_extended_connect_disabled(&self) -> bool {
+        matches!(self.inner.kind, Kind::User(User::ExtendedConnectDisabled))
+    }
+
+    /// Returns true if no stream could be opened on an HTTP/2 connection having reached its
+    /// limit of concurrent streams.
+    pub fn is_streams_exhausted(&self) -> bool {
+        matches!(self.inner.kind, Kind::User(User::StreamsExhausted))
+    }
+
     /// Returns true if a sender's channel is closed.
     pub fn is_closed(&self) -> bool {
         matches!(self.inner.kind, Kind::ChannelClosed)
@@ -246,6 +267,15 @@ impl Error {
         Error::new_user(User::InvalidConnectWithBody)
     }
 
+    #[cfg(feature = "websocket")]
+    pub(super) fn new_user_extended_connect_disabled() -> Error {
+        Error::new_user(User::ExtendedConnectDisabled)
+    }
+
+    pub(super) fn new_user_streams_exhausted() -> Error {
+        Error::new_user(User::StreamsExhausted)
+    }
+
     pub(super) fn new_shutdown(cause: std::io::Error) -> Error {
         Error::new(Kind::Shutdown).with(cause)
     }
@@ -275,6 +305,12 @@ impl Error {
             Kind::Parse(Parse::Header(Header::TransferEncodingUnexpected)) => {
                 "unexpected transfer-encoding parsed"
             }
+            Kind::Parse(Parse::Header(Header::LengthConflict)) => {
+                "both content-length and transfer-encoding parsed"
+            }
+            Kind::Parse(Parse::Header(Header::LineEnding)) => {
+                "bare CR in message head parsed"
+            }
             Kind::Parse(Parse::TooLarge) => "message head is too large",
             Kind::Parse(Parse::Status) => "invalid HTTP status-code parsed",
             Kind::Parse(Parse::Internal) => {
@@ -296,6 +332,13 @@ impl Error {
             Kind::User(User::InvalidConnectWithBody) => {
                 "user sent CONNECT request with non-zero body"
             }
+            #[cfg(feature = "websocket")]
+            Kind::User(User::ExtendedConnectDisabled) => {
+                "server does not support the extended CONNECT protocol"
+            }
+            Kind::User(User::StreamsExhausted) => {
+                "http2 connection reached its limit of concurrent streams"
+            }
             Kind::User(User::Service) => "error from user's Service",
             Kind::User(User::NoUpgrade) => "no upgrade available",
             Kind::User(User::ManualUpgrade) => "upgrade expected but low level API in use",
@@ -345,6 +388,14 @@ impl Parse {
     pub(crate) fn transfer_encoding_unexpected() -> Self {
         Parse::Header(Header::TransferEncodingUnexpected)
     }
+
+    pub(crate) fn length_conflict() -> Self {
+        Parse::Header(Header::LengthConflict)
+    }
+
+    pub(crate) fn line_ending() -> Self {
+        Parse::Header(Header::LineEnding)
+    }
 }
 
 impl From<httparse::Error> for Parse {
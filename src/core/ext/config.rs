@@ -134,6 +134,83 @@ impl RequestConfigValue for RequestTcpConnectOptions {
     type Value = crate::core::client::connect::TcpConnectOptions;
 }
 
+#[derive(Clone, Copy)]
+pub(crate) struct RequestDnsOverrides;
+
+impl RequestConfigValue for RequestDnsOverrides {
+    type Value = std::sync::Arc<std::collections::HashMap<String, Vec<std::net::SocketAddr>>>;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestDnsResolver;
+
+impl RequestConfigValue for RequestDnsResolver {
+    type Value = crate::dns::DynResolver;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestPreferredAddr;
+
+impl RequestConfigValue for RequestPreferredAddr {
+    type Value = std::net::SocketAddr;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestHostOverride;
+
+impl RequestConfigValue for RequestHostOverride {
+    type Value = http::uri::Authority;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestTargetForm;
+
+impl RequestConfigValue for RequestTargetForm {
+    type Value = super::RequestTarget;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestEmptyBody;
+
+impl RequestConfigValue for RequestEmptyBody {
+    type Value = super::EmptyBody;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestChunkFraming;
+
+impl RequestConfigValue for RequestChunkFraming {
+    type Value = std::sync::Arc<std::sync::atomic::AtomicBool>;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestNewConnection;
+
+impl RequestConfigValue for RequestNewConnection {
+    type Value = bool;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestReplayHead;
+
+impl RequestConfigValue for RequestReplayHead {
+    type Value = super::ReplayHead;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestSharedHeaders;
+
+impl RequestConfigValue for RequestSharedHeaders {
+    type Value = super::SharedHeaders;
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct RequestConnectTimeout;
+
+impl RequestConfigValue for RequestConnectTimeout {
+    type Value = std::time::Duration;
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct RequestTransportConfig;
 
@@ -148,6 +225,13 @@ impl RequestConfigValue for RequestEnforcedHttpVersion {
     type Value = http::Version;
 }
 
+#[derive(Clone, Copy)]
+pub(crate) struct RequestVersionPreference;
+
+impl RequestConfigValue for RequestVersionPreference {
+    type Value = super::VersionPreference;
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct RequestProxyMatcher;
 
@@ -155,6 +239,13 @@ impl RequestConfigValue for RequestProxyMatcher {
     type Value = crate::proxy::Matcher;
 }
 
+#[derive(Clone, Copy)]
+pub(crate) struct RequestTunnelHeaders;
+
+impl RequestConfigValue for RequestTunnelHeaders {
+    type Value = http::HeaderMap;
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct RequestOriginalHeaders;
 
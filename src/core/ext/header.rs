@@ -1 +1,91 @@
+//! Headers shared between requests.
 
+use std::sync::Arc;
+
+use http::{Extensions, HeaderMap, HeaderName, HeaderValue, Request};
+
+use super::{RequestConfig, RequestSharedHeaders};
+
+/// Header maps shared between requests, written under the headers of a request.
+///
+/// A request takes the values of a name from its own headers, or else from the first shared map
+/// that has the name. The maps are reference counted, so that the headers sent with every
+/// request, such as those of a prepared request, are not copied into each of them. The steps
+/// that need all the headers of a request in its own map first [`merge`](SharedHeaders::merge)
+/// them into it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedHeaders(Vec<Arc<HeaderMap>>);
+
+impl SharedHeaders {
+    /// Shares `headers` with a request, under its own headers and those already shared.
+    pub(crate) fn push<B>(req: &mut Request<B>, headers: Arc<HeaderMap>) {
+        if headers.is_empty() {
+            return;
+        }
+        RequestConfig::<RequestSharedHeaders>::get_mut(req.extensions_mut())
+            .get_or_insert_default()
+            .0
+            .push(headers);
+    }
+
+    /// Returns the headers shared with a request, if any.
+    #[inline]
+    pub(crate) fn get(ext: &Extensions) -> Option<&SharedHeaders> {
+        RequestConfig::<RequestSharedHeaders>::get(ext)
+    }
+
+    /// Returns the first value of a header of a request, be it its own or a shared one.
+    pub(crate) fn value<'a, B>(req: &'a Request<B>, name: &HeaderName) -> Option<&'a HeaderValue> {
+        req.headers().get(name).or_else(|| {
+            SharedHeaders::get(req.extensions())?
+                .0
+                .iter()
+                .find_map(|headers| headers.get(name))
+        })
+    }
+
+    /// Returns true if a request has a header, be it its own or a shared one.
+    #[inline]
+    pub(crate) fn contains<B>(req: &Request<B>, name: &HeaderName) -> bool {
+        SharedHeaders::value(req, name).is_some()
+    }
+
+    /// Returns the shared maps, in the order they apply.
+    #[inline]
+    pub(crate) fn maps(&self) -> impl Iterator<Item = &HeaderMap> {
+        self.0.iter().map(|headers| &**headers)
+    }
+
+    /// Returns true if any of the shared maps has one of `names`.
+    pub(crate) fn has_any(&self, names: &[HeaderName]) -> bool {
+        self.maps()
+            .any(|headers| names.iter().any(|name| headers.contains_key(name)))
+    }
+
+    /// Iterates the shared headers that apply to a request whose own headers are `own`.
+    pub(crate) fn iter<'a>(
+        &'a self,
+        own: &'a HeaderMap,
+    ) -> impl Iterator<Item = (&'a HeaderName, &'a HeaderValue)> {
+        self.0.iter().enumerate().flat_map(move |(i, headers)| {
+            let above = &self.0[..i];
+            headers.iter().filter(move |(name, _)| {
+                !own.contains_key(*name) && !above.iter().any(|above| above.contains_key(*name))
+            })
+        })
+    }
+
+    /// Copies the headers shared with a request into its own map.
+    pub(crate) fn merge<B>(req: &mut Request<B>) {
+        if let Some(shared) = RequestConfig::<RequestSharedHeaders>::remove(req.extensions_mut()) {
+            shared.merge_into(req.headers_mut());
+        }
+    }
+
+    /// Copies the shared headers into `own`, the headers of the request they are shared with.
+    pub(crate) fn merge_into(self, own: &mut HeaderMap) {
+        for headers in &self.0 {
+            crate::util::merge_default_headers(own, headers);
+        }
+    }
+}
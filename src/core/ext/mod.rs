@@ -4,13 +4,213 @@ mod config;
 mod h1_reason_phrase;
 mod header;
 
-use std::fmt;
+use std::{fmt, sync::Arc};
+
+use http::{Request, Version, request::Parts};
+
+use crate::{sync::Mutex, tls::AlpnProtocol};
 
 pub(crate) use config::{
-    RequestConfig, RequestConfigValue, RequestEnforcedHttpVersion, RequestOriginalHeaders,
-    RequestProxyMatcher, RequestTcpConnectOptions, RequestTransportConfig,
+    RequestChunkFraming, RequestConfig, RequestConfigValue, RequestConnectTimeout,
+    RequestDnsOverrides, RequestDnsResolver, RequestEmptyBody, RequestEnforcedHttpVersion,
+    RequestHostOverride, RequestNewConnection, RequestOriginalHeaders, RequestPreferredAddr,
+    RequestProxyMatcher, RequestReplayHead, RequestSharedHeaders, RequestTargetForm,
+    RequestTcpConnectOptions, RequestTransportConfig, RequestTunnelHeaders,
+    RequestVersionPreference,
 };
 pub(crate) use h1_reason_phrase::ReasonPhrase;
+pub(crate) use header::SharedHeaders;
+
+/// The connection a response was received on.
+///
+/// It is available in the [`Response`](crate::Response) extensions, so requests that shared a
+/// connection can be correlated.
+///
+/// # Example
+///
+/// ```rust
+/// # async fn run() -> wreq::Result<()> {
+/// let res = wreq::get("https://hyper.rs").send().await?;
+/// if let Some(conn) = res.extensions().get::<wreq::ConnectionInfo>() {
+///     println!("connection #{}, reused: {}", conn.id(), conn.was_reused());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    id: u64,
+    reused: bool,
+}
+
+impl ConnectionInfo {
+    pub(crate) fn new(id: u64, reused: bool) -> ConnectionInfo {
+        ConnectionInfo { id, reused }
+    }
+
+    /// Returns the ID of the connection.
+    ///
+    /// IDs are given in increasing order as connections are established, and are unique
+    /// within the process.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns true if the connection was reused from the pool, rather than established for
+    /// this request.
+    pub fn was_reused(&self) -> bool {
+        self.reused
+    }
+}
+
+/// The form of the request-target of an HTTP/1 request, as defined in [RFC 9112].
+///
+/// By default, the form is derived from the request: authority-form for `CONNECT`,
+/// absolute-form through an HTTP proxy, and origin-form otherwise.
+///
+/// [RFC 9112]: https://datatracker.ietf.org/doc/html/rfc9112#section-3.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RequestTarget {
+    /// The path and query of the URL, such as `/where?q=now`.
+    Origin,
+    /// The whole URL, such as `http://www.example.org/pub/WWW/TheProject.html`.
+    Absolute,
+    /// The host and port of the URL, such as `www.example.com:80`.
+    Authority,
+    /// A single asterisk, `*`, for a server-wide `OPTIONS` request.
+    Asterisk,
+}
+
+/// The body-related headers sent with a request that has no body.
+///
+/// By default, a request without a body carries no `Content-Length` nor `Transfer-Encoding`
+/// over HTTP/1, and `Content-Length: 0` over HTTP/2 for methods that define a meaning for a
+/// body, such as `POST` and `PUT`. Some servers tell these cases apart, so the headers can be
+/// chosen explicitly. Headers set on the request are always sent as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EmptyBody {
+    /// Sends neither `Content-Length` nor `Transfer-Encoding`.
+    Omit,
+    /// Sends `Content-Length: 0`.
+    ContentLength,
+    /// Sends `Transfer-Encoding: chunked` followed by the last chunk, over HTTP/1.1.
+    ///
+    /// HTTP/1.0 and HTTP/2 have no chunked coding, so no header is sent over them.
+    Chunked,
+}
+
+/// The HTTP versions a request may be sent with, in order of preference.
+///
+/// A single version enforces it. Several versions are all offered with ALPN when connecting
+/// over TLS, in order, so the server can fall back to a later one. The version negotiated is
+/// that of the [`Response`](crate::Response).
+///
+/// # Example
+///
+/// ```
+/// use wreq::{Version, VersionPreference};
+///
+/// let prefer_h2 = VersionPreference::from([Version::HTTP_2, Version::HTTP_11]);
+/// let only_h1 = VersionPreference::from(Version::HTTP_11);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionPreference(Vec<Version>);
+
+impl VersionPreference {
+    /// Returns the versions, in order of preference.
+    #[inline]
+    pub fn versions(&self) -> &[Version] {
+        &self.0
+    }
+
+    /// Returns the ALPN protocols offering the versions, in order of preference.
+    pub(crate) fn alpn_protocols(&self) -> Vec<AlpnProtocol> {
+        let mut protocols = Vec::with_capacity(self.0.len());
+        for version in &self.0 {
+            let protocol = if *version == Version::HTTP_2 {
+                AlpnProtocol::HTTP2
+            } else {
+                AlpnProtocol::HTTP1
+            };
+            if !protocols.contains(&protocol) {
+                protocols.push(protocol);
+            }
+        }
+        protocols
+    }
+}
+
+impl From<Version> for VersionPreference {
+    fn from(version: Version) -> VersionPreference {
+        VersionPreference(vec![version])
+    }
+}
+
+impl<const N: usize> From<[Version; N]> for VersionPreference {
+    fn from(versions: [Version; N]) -> VersionPreference {
+        VersionPreference(versions.to_vec())
+    }
+}
+
+impl From<&[Version]> for VersionPreference {
+    fn from(versions: &[Version]) -> VersionPreference {
+        VersionPreference(versions.to_vec())
+    }
+}
+
+impl From<Vec<Version>> for VersionPreference {
+    fn from(versions: Vec<Version>) -> VersionPreference {
+        VersionPreference(versions)
+    }
+}
+
+/// Marks a WebSocket upgrade request to be sent as an [Extended CONNECT] request instead, when it
+/// goes out on an HTTP/2 connection.
+///
+/// [Extended CONNECT]: https://datatracker.ietf.org/doc/html/rfc8441#section-4
+#[cfg(feature = "websocket")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WebSocketUpgrade;
+
+/// A copy of the head of a request, kept so that the request can be sent again.
+///
+/// The client fills it when it writes the request to a reused HTTP/1 connection, the only
+/// case where the server may close the connection before processing the request, so that
+/// the other requests aren't copied.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayHead(Arc<Mutex<Option<Parts>>>);
+
+impl ReplayHead {
+    /// Creates an empty copy, filled by [`ReplayHead::store`].
+    pub(crate) fn new() -> ReplayHead {
+        ReplayHead(Arc::new(Mutex::new(None)))
+    }
+
+    /// Copies the method, URI, version, headers and extensions of a request.
+    pub(crate) fn store<B>(&self, req: &Request<B>) {
+        let mut head = Request::new(());
+        *head.method_mut() = req.method().clone();
+        *head.uri_mut() = req.uri().clone();
+        *head.version_mut() = req.version();
+        *head.headers_mut() = req.headers().clone();
+        *head.extensions_mut() = req.extensions().clone();
+        // The copy doesn't refer to itself, or it would never be freed.
+        RequestConfig::<RequestReplayHead>::remove(head.extensions_mut());
+        *self.0.lock() = Some(head.into_parts().0);
+    }
+
+    /// Returns true if the head of the request was copied.
+    pub(crate) fn has_copy(&self) -> bool {
+        self.0.lock().is_some()
+    }
+
+    /// Takes the copy of the request head, if one was stored.
+    pub(crate) fn take(&self) -> Option<Parts> {
+        self.0.lock().take()
+    }
+}
 
 /// Represents the `:protocol` pseudo-header used by
 /// the [Extended CONNECT Protocol].
@@ -0,0 +1,21 @@
+/// The capitalization of header names written in HTTP/1 requests.
+///
+/// HTTP/1 header names are case-insensitive, but servers can tell clients apart by how they
+/// capitalize them. The profile applies to every header, including those generated by the
+/// client such as `Host` and `Content-Length`; [`OriginalHeaders`] still sets their order.
+///
+/// HTTP/2 always sends lowercase names, so the profile doesn't apply to it.
+///
+/// [`OriginalHeaders`]: crate::OriginalHeaders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderCase {
+    /// Writes the names listed in [`OriginalHeaders`](crate::OriginalHeaders) as they were
+    /// typed, and all other names in lowercase.
+    #[default]
+    AsTyped,
+    /// Writes every name in Title-Case, such as `Content-Length`.
+    TitleCase,
+    /// Writes every name in lowercase, such as `content-length`.
+    Lowercase,
+}
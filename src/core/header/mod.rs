@@ -1,4 +1,7 @@
+mod case;
 mod map;
 mod name;
 
+pub use case::HeaderCase;
 pub use map::OriginalHeaders;
+pub(crate) use name::OriginalHeaderName;
@@ -4,7 +4,7 @@ pub use self::error::{Error, Result};
 
 pub mod body;
 pub mod client;
-mod common;
+pub(crate) mod common;
 mod error;
 pub mod ext;
 
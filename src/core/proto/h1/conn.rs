@@ -18,6 +18,8 @@ use super::{
 };
 use crate::core::{
     body::DecodedLength,
+    common::buf::BufPool,
+    header::HeaderCase,
     proto::{BodyLength, MessageHead, headers},
     rt::{Read, Write},
 };
@@ -56,6 +58,8 @@ where
                 h1_max_headers: None,
                 preserve_header_case: false,
                 h09_responses: false,
+                lenient: false,
+                header_case: HeaderCase::default(),
                 notify_read: false,
                 reading: Reading::Init,
                 writing: Writing::Init,
@@ -81,6 +85,14 @@ where
         self.io.set_read_buf_exact_size(sz);
     }
 
+    pub(crate) fn set_read_buf_init_size(&mut self, sz: usize) {
+        self.io.set_read_buf_init_size(sz);
+    }
+
+    pub(crate) fn set_read_buf_pool(&mut self, pool: BufPool) {
+        self.io.set_read_buf_pool(pool);
+    }
+
     pub(crate) fn set_write_strategy_flatten(&mut self) {
         self.io.set_write_strategy_flatten();
     }
@@ -93,10 +105,18 @@ where
         self.state.preserve_header_case = true;
     }
 
+    pub(crate) fn set_header_case(&mut self, case: HeaderCase) {
+        self.state.header_case = case;
+    }
+
     pub(crate) fn set_h09_responses(&mut self) {
         self.state.h09_responses = true;
     }
 
+    pub(crate) fn set_lenient_parsing(&mut self) {
+        self.state.lenient = true;
+    }
+
     pub(crate) fn set_http1_max_headers(&mut self, val: usize) {
         self.state.h1_max_headers = Some(val);
     }
@@ -163,6 +183,7 @@ where
                 h1_max_headers: self.state.h1_max_headers,
                 preserve_header_case: self.state.preserve_header_case,
                 h09_responses: self.state.h09_responses,
+                lenient: self.state.lenient,
             },
         ) {
             Poll::Ready(Ok(msg)) => msg,
@@ -204,6 +225,7 @@ where
                 msg.decode,
                 self.state.h1_max_headers,
                 h1_max_header_size,
+                !self.state.lenient,
             ));
             wants = wants.add(Wants::EXPECT);
         } else {
@@ -212,6 +234,7 @@ where
                 msg.decode,
                 self.state.h1_max_headers,
                 h1_max_header_size,
+                !self.state.lenient,
             ));
         }
 
@@ -463,6 +486,10 @@ where
 
     fn try_keep_alive(&mut self, cx: &mut Context<'_>) {
         self.state.try_keep_alive::<T>();
+        // An idle connection doesn't hold a read buffer of the pool.
+        if self.state.is_idle() {
+            self.io.release_read_buf();
+        }
         self.maybe_notify(cx);
     }
 
@@ -519,6 +546,7 @@ where
                 head: &mut head,
                 body,
                 req_method: &mut self.state.method,
+                header_case: self.state.header_case,
             },
             buf,
         ) {
@@ -799,6 +827,9 @@ struct State {
     h1_max_headers: Option<usize>,
     preserve_header_case: bool,
     h09_responses: bool,
+    /// Accept responses with ambiguous framing instead of rejecting them.
+    lenient: bool,
+    header_case: HeaderCase,
     /// Set to true when the Dispatcher should poll read operations
     /// again. See the `maybe_notify` method for more.
     notify_read: bool,
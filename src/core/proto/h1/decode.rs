@@ -16,6 +16,9 @@ use super::{DecodedLength, io::MemRead, role::DEFAULT_MAX_HEADERS};
 /// This limit is currentlty applied for the entire body, not per chunk.
 const CHUNKED_EXTENSIONS_LIMIT: u64 = 1024 * 16;
 
+/// Maximum amount of bytes allowed in the extensions of a single chunk, when parsing strictly.
+const STRICT_CHUNK_EXTENSION_LIMIT: u64 = 1024 * 4;
+
 /// Maximum number of bytes allowed for all trailer fields.
 ///
 /// TODO: remove this when we land h1_max_header_size support
@@ -39,6 +42,8 @@ enum Kind {
         state: ChunkedState,
         chunk_len: u64,
         extensions_cnt: u64,
+        chunk_extension_len: u64,
+        strict: bool,
         trailers_buf: Option<BytesMut>,
         trailers_cnt: usize,
         h1_max_headers: Option<usize>,
@@ -98,6 +103,8 @@ impl Decoder {
                 state: ChunkedState::new(),
                 chunk_len: 0,
                 extensions_cnt: 0,
+                chunk_extension_len: 0,
+                strict: false,
                 trailers_buf: None,
                 trailers_cnt: 0,
                 h1_max_headers,
@@ -116,14 +123,25 @@ impl Decoder {
         len: DecodedLength,
         h1_max_headers: Option<usize>,
         h1_max_header_size: Option<usize>,
+        strict: bool,
     ) -> Self {
         match len {
-            DecodedLength::CHUNKED => Decoder::chunked(h1_max_headers, h1_max_header_size),
+            DecodedLength::CHUNKED => {
+                Decoder::chunked(h1_max_headers, h1_max_header_size).strict(strict)
+            }
             DecodedLength::CLOSE_DELIMITED => Decoder::eof(),
             length => Decoder::length(length.danger_len()),
         }
     }
 
+    /// Sets whether a chunked decoder also limits the extensions of each chunk.
+    fn strict(mut self, enabled: bool) -> Decoder {
+        if let Chunked { ref mut strict, .. } = self.kind {
+            *strict = enabled;
+        }
+        self
+    }
+
     // methods
 
     pub(crate) fn is_eof(&self) -> bool {
@@ -169,6 +187,8 @@ impl Decoder {
                 ref mut state,
                 ref mut chunk_len,
                 ref mut extensions_cnt,
+                ref mut chunk_extension_len,
+                strict,
                 ref mut trailers_buf,
                 ref mut trailers_cnt,
                 ref h1_max_headers,
@@ -184,6 +204,8 @@ impl Decoder {
                         body,
                         chunk_len,
                         extensions_cnt,
+                        chunk_extension_len,
+                        strict,
                         &mut buf,
                         trailers_buf,
                         trailers_cnt,
@@ -300,6 +322,8 @@ impl ChunkedState {
         body: &mut R,
         size: &mut u64,
         extensions_cnt: &mut u64,
+        chunk_extension_len: &mut u64,
+        strict: bool,
         buf: &mut Option<Bytes>,
         trailers_buf: &mut Option<BytesMut>,
         trailers_cnt: &mut usize,
@@ -311,7 +335,9 @@ impl ChunkedState {
             Start => ChunkedState::read_start(cx, body, size),
             Size => ChunkedState::read_size(cx, body, size),
             SizeLws => ChunkedState::read_size_lws(cx, body),
-            Extension => ChunkedState::read_extension(cx, body, extensions_cnt),
+            Extension => {
+                ChunkedState::read_extension(cx, body, extensions_cnt, chunk_extension_len, strict)
+            }
             SizeLf => ChunkedState::read_size_lf(cx, body, *size),
             Body => ChunkedState::read_body(cx, body, size, buf),
             BodyCr => ChunkedState::read_body_cr(cx, body),
@@ -416,6 +442,8 @@ impl ChunkedState {
         cx: &mut Context<'_>,
         rdr: &mut R,
         extensions_cnt: &mut u64,
+        chunk_extension_len: &mut u64,
+        strict: bool,
     ) -> Poll<Result<ChunkedState, io::Error>> {
         trace!("read_extension");
         // We don't care about extensions really at all. Just ignore them.
@@ -425,18 +453,27 @@ impl ChunkedState {
         // them from themselves, we reject extensions containing plain LF as
         // well.
         match byte!(rdr, cx) {
-            b'\r' => Poll::Ready(Ok(ChunkedState::SizeLf)),
+            b'\r' => {
+                *chunk_extension_len = 0;
+                Poll::Ready(Ok(ChunkedState::SizeLf))
+            }
             b'\n' => Poll::Ready(Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "invalid chunk extension contains newline",
             ))),
             _ => {
                 *extensions_cnt += 1;
+                *chunk_extension_len += 1;
                 if *extensions_cnt >= CHUNKED_EXTENSIONS_LIMIT {
                     Poll::Ready(Err(io::Error::new(
                         io::ErrorKind::InvalidData,
                         "chunk extensions over limit",
                     )))
+                } else if strict && *chunk_extension_len > STRICT_CHUNK_EXTENSION_LIMIT {
+                    Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "chunk extension over strict limit",
+                    )))
                 } else {
                     Poll::Ready(Ok(ChunkedState::Extension))
                 }
@@ -952,6 +989,31 @@ mod tests {
         assert_eq!(err.to_string(), "chunk extensions over limit");
     }
 
+    #[tokio::test]
+    async fn test_read_chunked_extension_over_strict_limit() {
+        let mut scratch = vec![];
+        scratch.extend(b"1;");
+        scratch.extend(b"x".repeat(super::STRICT_CHUNK_EXTENSION_LIMIT as usize + 1));
+        scratch.extend(b"\r\nA\r\n0\r\n\r\n");
+
+        let mut lenient = Decoder::chunked(None, None);
+        let buf = lenient
+            .decode_fut(&mut Bytes::from(scratch.clone()))
+            .await
+            .expect("decode lenient")
+            .into_data()
+            .expect("unknown frame type");
+        assert_eq!(&buf[..], b"A");
+
+        let mut strict = Decoder::new(DecodedLength::CHUNKED, None, None, true);
+        let err = strict
+            .decode_fut(&mut Bytes::from(scratch))
+            .await
+            .expect_err("decode strict");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "chunk extension over strict limit");
+    }
+
     #[cfg(not(miri))]
     #[tokio::test]
     async fn test_read_chunked_trailer_with_missing_lf() {
@@ -39,6 +39,10 @@ enum Kind {
     ///
     /// Enforces that the body is not longer than the Content-Length header.
     Length(u64),
+    /// An Encoder for a chunked body that frames its chunks itself.
+    ///
+    /// The body is written as is, including its last chunk and trailers.
+    Framed,
 }
 
 #[derive(Debug)]
@@ -65,6 +69,10 @@ impl Encoder {
         Encoder::new(Kind::Length(len))
     }
 
+    pub(crate) fn framed() -> Encoder {
+        Encoder::new(Kind::Framed)
+    }
+
     pub(crate) fn into_chunked_with_trailing_fields(self, trailers: Vec<HeaderValue>) -> Encoder {
         match self.kind {
             Kind::Chunked(_) => Encoder {
@@ -93,7 +101,7 @@ impl Encoder {
 
     pub(crate) fn end<B>(&self) -> Result<Option<EncodedBuf<B>>, NotEof> {
         match self.kind {
-            Kind::Length(0) => Ok(None),
+            Kind::Length(0) | Kind::Framed => Ok(None),
             Kind::Chunked(_) => Ok(Some(EncodedBuf {
                 kind: BufKind::ChunkedEnd(b"0\r\n\r\n"),
             })),
@@ -127,6 +135,7 @@ impl Encoder {
                     BufKind::Exact(msg)
                 }
             }
+            Kind::Framed => BufKind::Exact(msg),
         };
         EncodedBuf { kind }
     }
@@ -195,6 +204,10 @@ impl Encoder {
                 dst.buffer(buf);
                 !self.is_last
             }
+            Kind::Framed => {
+                dst.buffer(msg);
+                !self.is_last
+            }
             Kind::Length(remaining) => {
                 use std::cmp::Ordering;
 
@@ -5,11 +5,11 @@ use std::{
     task::{Context, Poll, ready},
 };
 
-use bytes::{Buf, BufMut, Bytes, BytesMut};
+use bytes::{Buf, BufMut, Bytes};
 
 use super::{Http1Transaction, ParseContext, ParsedMessage};
 use crate::core::{
-    common::buf::BufList,
+    common::buf::{BufList, BufPool, PooledBuf},
     rt::{Read, ReadBuf, Write},
 };
 
@@ -36,7 +36,7 @@ pub(crate) struct Buffered<T, B> {
     io: T,
     partial_len: Option<usize>,
     read_blocked: bool,
-    read_buf: BytesMut,
+    read_buf: PooledBuf,
     read_buf_strategy: ReadStrategy,
     write_buf: WriteBuf<B>,
 }
@@ -70,7 +70,7 @@ where
             io,
             partial_len: None,
             read_blocked: false,
-            read_buf: BytesMut::with_capacity(0),
+            read_buf: PooledBuf::default(),
             read_buf_strategy: ReadStrategy::default(),
             write_buf,
         }
@@ -89,6 +89,17 @@ where
         self.read_buf_strategy = ReadStrategy::Exact(sz);
     }
 
+    pub(crate) fn set_read_buf_init_size(&mut self, sz: usize) {
+        self.read_buf_strategy = self.read_buf_strategy.with_init(sz);
+    }
+
+    pub(crate) fn set_read_buf_pool(&mut self, pool: BufPool) {
+        // this should always be called only at construction time,
+        // so this assert is here to catch myself
+        debug_assert!(self.read_buf.is_empty());
+        self.read_buf = PooledBuf::new(pool);
+    }
+
     pub(crate) fn set_write_strategy_flatten(&mut self) {
         // this should always be called only at construction time,
         // so this assert is here to catch myself
@@ -104,7 +115,7 @@ where
     }
 
     pub(crate) fn read_buf(&self) -> &[u8] {
-        self.read_buf.as_ref()
+        &self.read_buf[..]
     }
 
     /// Return the "allocated" available space, not the potential space
@@ -170,6 +181,7 @@ where
                     h1_max_headers: parse_ctx.h1_max_headers,
                     preserve_header_case: parse_ctx.preserve_header_case,
                     h09_responses: parse_ctx.h09_responses,
+                    lenient: parse_ctx.lenient,
                 },
             )? {
                 Some(msg) => {
@@ -200,8 +212,16 @@ where
         }
     }
 
+    /// Gives the read buffer back to its pool between messages, if nothing is left in it.
+    pub(crate) fn release_read_buf(&mut self) {
+        if self.read_buf.is_empty() {
+            self.read_buf.release();
+        }
+    }
+
     pub(crate) fn poll_read_from_io(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
         self.read_blocked = false;
+        self.read_buf.refill();
         let next = self.read_buf_strategy.next();
         if self.read_buf_remaining_mut() < next {
             self.read_buf.reserve(next);
@@ -232,8 +252,8 @@ where
         }
     }
 
-    pub(crate) fn into_inner(self) -> (T, Bytes) {
-        (self.io, self.read_buf.freeze())
+    pub(crate) fn into_inner(mut self) -> (T, Bytes) {
+        (self.io, self.read_buf.detach().freeze())
     }
 
     pub(crate) fn io_mut(&mut self) -> &mut T {
@@ -338,6 +358,7 @@ enum ReadStrategy {
     Adaptive {
         decrease_now: bool,
         next: usize,
+        min: usize,
         max: usize,
     },
     Exact(usize),
@@ -348,10 +369,27 @@ impl ReadStrategy {
         ReadStrategy::Adaptive {
             decrease_now: false,
             next: INIT_BUFFER_SIZE,
+            min: INIT_BUFFER_SIZE,
             max,
         }
     }
 
+    /// Starts an adaptive strategy at `init`, never decreasing below it.
+    fn with_init(self, init: usize) -> ReadStrategy {
+        match self {
+            ReadStrategy::Adaptive { max, .. } => {
+                let init = cmp::min(init, max);
+                ReadStrategy::Adaptive {
+                    decrease_now: false,
+                    next: init,
+                    min: init,
+                    max,
+                }
+            }
+            exact => exact,
+        }
+    }
+
     fn next(&self) -> usize {
         match *self {
             ReadStrategy::Adaptive { next, .. } => next,
@@ -371,8 +409,8 @@ impl ReadStrategy {
             ReadStrategy::Adaptive {
                 ref mut decrease_now,
                 ref mut next,
+                min,
                 max,
-                ..
             } => {
                 if bytes_read >= *next {
                     *next = cmp::min(incr_power_of_two(*next), max);
@@ -381,7 +419,7 @@ impl ReadStrategy {
                     let decr_to = prev_power_of_two(*next);
                     if bytes_read < decr_to {
                         if *decrease_now {
-                            *next = cmp::max(decr_to, INIT_BUFFER_SIZE);
+                            *next = cmp::max(decr_to, min);
                             *decrease_now = false;
                         } else {
                             // Decreasing is a two "record" process.
@@ -651,6 +689,7 @@ mod tests {
                 h1_max_headers: None,
                 preserve_header_case: false,
                 h09_responses: false,
+                lenient: false,
             };
             assert!(
                 buffered
@@ -662,7 +701,7 @@ mod tests {
         .await;
 
         assert_eq!(
-            buffered.read_buf,
+            *buffered.read_buf,
             b"HTTP/1.1 200 OK\r\nServer: crate::core:\r\n"[..]
         );
     }
@@ -745,6 +784,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_strategy_adaptive_init() {
+        let mut strategy = ReadStrategy::default().with_init(65536);
+        assert_eq!(strategy.next(), 65536);
+
+        strategy.record(65536);
+        assert_eq!(strategy.next(), 131072);
+
+        strategy.record(1);
+        strategy.record(1);
+        assert_eq!(strategy.next(), 65536);
+        strategy.record(1);
+        strategy.record(1);
+        assert_eq!(strategy.next(), 65536, "doesn't decrement under init");
+
+        let strategy = ReadStrategy::with_max(16384).with_init(65536);
+        assert_eq!(strategy.next(), 16384, "never starts over max");
+
+        let strategy = ReadStrategy::Exact(4096).with_init(65536);
+        assert_eq!(strategy.next(), 4096, "exact is kept");
+    }
+
     #[test]
     fn read_strategy_adaptive_max_fuzz() {
         fn fuzz(max: usize) {
@@ -12,6 +12,7 @@ pub(crate) use self::{
 };
 use crate::core::{
     body::DecodedLength,
+    header::HeaderCase,
     proto::{BodyLength, MessageHead},
 };
 
@@ -72,6 +73,7 @@ pub(crate) struct ParseContext<'a> {
     h1_max_headers: Option<usize>,
     preserve_header_case: bool,
     h09_responses: bool,
+    lenient: bool,
 }
 
 /// Passed to Http1Transaction::encode
@@ -79,6 +81,7 @@ pub(crate) struct Encode<'a, T> {
     head: &'a mut MessageHead<T>,
     body: Option<BodyLength>,
     req_method: &'a mut Option<Method>,
+    header_case: HeaderCase,
 }
 
 /// Extra flags that a request "wants", like expect-continue or upgrades.
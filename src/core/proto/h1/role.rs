@@ -1,6 +1,7 @@
 use std::{
     fmt::{self, Write as _},
     mem::MaybeUninit,
+    sync::atomic::Ordering,
 };
 
 use bytes::{Bytes, BytesMut};
@@ -13,8 +14,11 @@ use smallvec::{SmallVec, smallvec, smallvec_inline};
 use crate::core::{
     body::DecodedLength,
     error::Parse,
-    ext::{RequestConfig, RequestOriginalHeaders},
-    header::OriginalHeaders,
+    ext::{
+        EmptyBody, RequestChunkFraming, RequestConfig, RequestEmptyBody, RequestOriginalHeaders,
+        SharedHeaders,
+    },
+    header::{HeaderCase, OriginalHeaders},
     proto::{
         BodyLength, MessageHead, RequestHead, RequestLine,
         h1::{Encode, Encoder, Http1Transaction, ParseContext, ParseResult, ParsedMessage},
@@ -144,6 +148,10 @@ impl Http1Transaction for Client {
                 ) {
                     Ok(httparse::Status::Complete(len)) => {
                         trace!("Response.parse Complete({})", len);
+                        if !ctx.lenient && headers::has_bare_cr(&bytes[..len]) {
+                            debug!("bare CR in response head");
+                            return Err(Parse::line_ending());
+                        }
                         let status = StatusCode::from_u16(res.code.unwrap())?;
 
                         let reason = {
@@ -244,7 +252,8 @@ impl Http1Transaction for Client {
                 headers,
                 extensions,
             };
-            if let Some((decode, is_upgrade)) = Client::decoder(&head, ctx.req_method)? {
+            if let Some((decode, is_upgrade)) = Client::decoder(&head, ctx.req_method, ctx.lenient)?
+            {
                 return Ok(Some(ParsedMessage {
                     head,
                     decode,
@@ -272,7 +281,19 @@ impl Http1Transaction for Client {
 
         *msg.req_method = Some(msg.head.subject.0.clone());
 
-        let body = Client::set_length(msg.head, msg.body);
+        let framed = RequestConfig::<RequestChunkFraming>::get(&msg.head.extensions)
+            .is_some_and(|framing| framing.load(Ordering::Acquire));
+        let body = if framed {
+            // The body encodes its own chunks, extensions and trailers.
+            msg.head.headers.remove(header::CONTENT_LENGTH);
+            msg.head.headers.insert(
+                header::TRANSFER_ENCODING,
+                HeaderValue::from_static("chunked"),
+            );
+            Encoder::framed()
+        } else {
+            Client::set_length(msg.head, msg.body)
+        };
 
         let init_cap = 30 + msg.head.headers.len() * AVERAGE_HEADER_SIZE;
         dst.reserve(init_cap);
@@ -296,9 +317,18 @@ impl Http1Transaction for Client {
         if let Some(orig_headers) =
             RequestConfig::<RequestOriginalHeaders>::get(&msg.head.extensions)
         {
-            write_headers_original_case(&mut msg.head.headers, orig_headers, dst);
+            write_headers_original_case(&mut msg.head.headers, orig_headers, msg.header_case, dst);
         } else {
-            write_headers(&msg.head.headers, dst);
+            // The shared headers the request doesn't override follow its own.
+            let shared = SharedHeaders::get(&msg.head.extensions)
+                .into_iter()
+                .flat_map(|shared| shared.iter(&msg.head.headers));
+            let headers = msg.head.headers.iter().chain(shared);
+            if msg.header_case == HeaderCase::TitleCase {
+                write_headers_title_case(headers, dst);
+            } else {
+                write_headers(headers, dst);
+            }
         }
 
         extend(dst, b"\r\n");
@@ -324,6 +354,7 @@ impl Client {
     fn decoder(
         inc: &MessageHead<StatusCode>,
         method: &mut Option<Method>,
+        lenient: bool,
     ) -> Result<Option<(DecodedLength, bool)>, Parse> {
         // According to https://tools.ietf.org/html/rfc7230#section-3.3.3
         // 1. HEAD responses, and Status 1xx, 204, and 304 cannot have a body.
@@ -360,6 +391,11 @@ impl Client {
             }
         }
 
+        if !lenient && headers::has_conflicting_length(&inc.headers) {
+            debug!("both Transfer-Encoding and Content-Length headers");
+            return Err(Parse::length_conflict());
+        }
+
         if inc.headers.contains_key(header::TRANSFER_ENCODING) {
             // https://tools.ietf.org/html/rfc7230#section-3.3.3
             // If Transfer-Encoding header is present, and 'chunked' is
@@ -388,8 +424,7 @@ impl Client {
         let body = if let Some(body) = body {
             body
         } else {
-            head.headers.remove(header::TRANSFER_ENCODING);
-            return Encoder::length(0);
+            return Client::set_empty_length(head);
         };
 
         // HTTP/1.0 doesn't know about chunked
@@ -508,6 +543,29 @@ impl Client {
         set_content_length(headers, len)
     }
 
+    fn set_empty_length(head: &mut RequestHead) -> Encoder {
+        let empty_body = RequestConfig::<RequestEmptyBody>::get(&head.extensions).copied();
+        match empty_body {
+            Some(EmptyBody::ContentLength) => {
+                head.headers.remove(header::TRANSFER_ENCODING);
+                headers::set_content_length_if_missing(&mut head.headers, 0);
+                Encoder::length(0)
+            }
+            Some(EmptyBody::Chunked) if head.version == Version::HTTP_11 => {
+                head.headers.remove(header::CONTENT_LENGTH);
+                head.headers.insert(
+                    header::TRANSFER_ENCODING,
+                    HeaderValue::from_static("chunked"),
+                );
+                Encoder::chunked()
+            }
+            _ => {
+                head.headers.remove(header::TRANSFER_ENCODING);
+                Encoder::length(0)
+            }
+        }
+    }
+
     fn obs_fold_line(all: &mut [u8], idx: &mut HeaderIndices) {
         // If the value has obs-folded text, then in-place shift the bytes out
         // of here.
@@ -633,7 +691,10 @@ fn record_header_indices(
     Ok(())
 }
 
-pub(crate) fn write_headers(headers: &HeaderMap, dst: &mut Vec<u8>) {
+pub(crate) fn write_headers<'a>(
+    headers: impl IntoIterator<Item = (&'a HeaderName, &'a HeaderValue)>,
+    dst: &mut Vec<u8>,
+) {
     for (name, value) in headers {
         extend(dst, name.as_str().as_bytes());
         extend(dst, b": ");
@@ -642,9 +703,36 @@ pub(crate) fn write_headers(headers: &HeaderMap, dst: &mut Vec<u8>) {
     }
 }
 
+fn title_case(dst: &mut Vec<u8>, name: &[u8]) {
+    dst.reserve(name.len());
+
+    // Ensure first character is uppercased
+    let mut prev = b'-';
+    for &(mut c) in name {
+        if prev == b'-' {
+            c.make_ascii_uppercase();
+        }
+        dst.push(c);
+        prev = c;
+    }
+}
+
+fn write_headers_title_case<'a>(
+    headers: impl IntoIterator<Item = (&'a HeaderName, &'a HeaderValue)>,
+    dst: &mut Vec<u8>,
+) {
+    for (name, value) in headers {
+        title_case(dst, name.as_str().as_bytes());
+        extend(dst, b": ");
+        extend(dst, value.as_bytes());
+        extend(dst, b"\r\n");
+    }
+}
+
 fn write_headers_original_case(
     headers: &mut HeaderMap,
     orig_case: &OriginalHeaders,
+    case: HeaderCase,
     dst: &mut Vec<u8>,
 ) {
     crate::core::proto::headers::sort_headers(headers, orig_case);
@@ -658,10 +746,10 @@ fn write_headers_original_case(
         let mut names = orig_case.get_all(name);
 
         for value in headers.get_all(name) {
-            if let Some(orig_name) = names.next() {
-                extend(dst, orig_name.as_ref());
-            } else {
-                extend(dst, name.as_str().as_bytes());
+            match (case, names.next()) {
+                (HeaderCase::AsTyped, Some(orig_name)) => extend(dst, orig_name.as_ref()),
+                (HeaderCase::TitleCase, _) => title_case(dst, name.as_str().as_bytes()),
+                _ => extend(dst, name.as_str().as_bytes()),
             }
 
             // Wanted for curl test cases that send `X-Custom-Header:\r\n`
@@ -3,6 +3,7 @@ use std::{
     future::Future,
     marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll, ready},
     time::Duration,
 };
@@ -29,13 +30,17 @@ use pin_project_lite::pin_project;
 use super::{
     H2Upgraded, PipeToSendStream, SendBuf, ping,
     ping::{Ponger, Recorder},
+    streams::Streams,
 };
 use crate::core::{
     body::Incoming as IncomingBody,
-    client::dispatch::{self, Callback, SendWhen, TrySendError},
+    client::{
+        config::http2::StreamsExhausted,
+        dispatch::{self, Callback, SendWhen, TrySendError},
+    },
     common::{io::Compat, time::Time},
     error::BoxError,
-    ext::{Protocol, RequestConfig, RequestOriginalHeaders},
+    ext::{EmptyBody, Protocol, RequestConfig, RequestEmptyBody, RequestOriginalHeaders},
     proto::{Dispatched, h2::UpgradedSendStream, headers},
     rt::{Read, Write, bounds::Http2ClientConnExec},
     upgrade::Upgraded,
@@ -81,6 +86,9 @@ pub(crate) struct Config {
     pub(crate) max_concurrent_reset_streams: Option<usize>,
     pub(crate) max_send_buffer_size: usize,
     pub(crate) max_concurrent_streams: Option<u32>,
+    pub(crate) max_send_streams: Option<usize>,
+    pub(crate) streams_exhausted: StreamsExhausted,
+    pub(crate) scale_streams_threshold: Option<usize>,
     pub(crate) max_header_list_size: Option<u32>,
     pub(crate) max_pending_accept_reset_streams: Option<usize>,
     pub(crate) enable_push: Option<bool>,
@@ -112,6 +120,9 @@ impl Default for Config {
             max_pending_accept_reset_streams: None,
             header_table_size: None,
             max_concurrent_streams: None,
+            max_send_streams: None,
+            streams_exhausted: StreamsExhausted::default(),
+            scale_streams_threshold: None,
             enable_push: None,
             enable_connect_protocol: None,
             no_rfc7540_priorities: None,
@@ -238,11 +249,18 @@ where
         task: ConnTask::new(conn, conn_drop_rx, cancel_tx),
     });
 
+    let streams = Streams::new(
+        config.max_send_streams,
+        config.initial_max_send_streams,
+        config.scale_streams_threshold,
+    );
+
     Ok(ClientTask {
         ping,
         conn_drop_ref,
         conn_eof,
         executor: exec,
+        streams,
         h2_tx,
         req_rx,
         fut_ctx: None,
@@ -475,6 +493,7 @@ where
     conn_drop_ref: ConnDropRef,
     conn_eof: ConnEof,
     executor: E,
+    streams: Arc<Streams>,
     h2_tx: SendRequest<SendBuf<B::Data>>,
     req_rx: ClientRx<B>,
     fut_ctx: Option<FutCtx<B>>,
@@ -520,6 +539,17 @@ where
     }
 }
 
+impl<B, E, T> ClientTask<B, E, T>
+where
+    B: Body,
+    E: Unpin,
+{
+    /// Returns the streams opened on the connection.
+    pub(crate) fn streams(&self) -> &Arc<Streams> {
+        &self.streams
+    }
+}
+
 impl<B, E, T> ClientTask<B, E, T>
 where
     B: Body + 'static + Unpin,
@@ -669,7 +699,9 @@ where
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         loop {
             match ready!(self.h2_tx.poll_ready(cx)) {
-                Ok(()) => (),
+                Ok(()) => self
+                    .streams
+                    .set_remote_max(self.h2_tx.current_max_send_streams()),
                 Err(err) => {
                     self.ping.ensure_not_timed_out()?;
                     return if err.reason() == Some(::http2::Reason::NO_ERROR) {
@@ -697,9 +729,42 @@ where
                     }
                     let (head, body) = req.into_parts();
                     let mut req = ::http::Request::from_parts(head, ());
+
+                    // Turn an HTTP/1.1 WebSocket upgrade into an extended CONNECT (RFC 8441),
+                    // which the server must have enabled in its settings.
+                    #[cfg(feature = "websocket")]
+                    if req
+                        .extensions_mut()
+                        .remove::<crate::core::ext::WebSocketUpgrade>()
+                        .is_some()
+                    {
+                        if !self.h2_tx.is_extended_connect_protocol_enabled() {
+                            debug!("h2 extended connect not enabled by the server");
+                            cb.send(Err(TrySendError {
+                                error: crate::core::Error::new_user_extended_connect_disabled(),
+                                message: None,
+                            }));
+                            continue;
+                        }
+
+                        *req.method_mut() = Method::CONNECT;
+                        req.headers_mut().remove(http::header::UPGRADE);
+                        req.headers_mut().remove(http::header::CONNECTION);
+                        req.headers_mut().remove(http::header::SEC_WEBSOCKET_KEY);
+                        req.extensions_mut()
+                            .insert(Protocol::from_static("websocket"));
+                    }
+
                     super::strip_connection_headers(req.headers_mut(), true);
                     if let Some(len) = body.size_hint().exact() {
-                        if len != 0 || headers::method_has_defined_payload_semantics(req.method()) {
+                        let empty_body =
+                            RequestConfig::<RequestEmptyBody>::get(req.extensions()).copied();
+                        let send_len = match empty_body {
+                            _ if len != 0 => true,
+                            Some(empty_body) => empty_body == EmptyBody::ContentLength,
+                            None => headers::method_has_defined_payload_semantics(req.method()),
+                        };
+                        if send_len {
                             headers::set_content_length_if_missing(req.headers_mut(), len);
                         }
                     }
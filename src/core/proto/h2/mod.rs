@@ -1,5 +1,6 @@
 pub(crate) mod client;
 pub(crate) mod ping;
+pub(crate) mod streams;
 
 use std::{
     future::Future,
@@ -0,0 +1,146 @@
+//! Accounting of the streams opened on an HTTP/2 connection.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use tokio::sync::Notify;
+
+/// The streams of a connection opened by its requests, against the limits of both peers.
+///
+/// A stream is held from when its request is sent until its response body is done or dropped.
+#[derive(Debug)]
+pub(crate) struct Streams {
+    active: AtomicUsize,
+    // The SETTINGS_MAX_CONCURRENT_STREAMS of the server.
+    remote_max: AtomicUsize,
+    // The limit set on the client.
+    local_max: usize,
+    // The number of streams from which another connection should be opened.
+    scale_at: usize,
+    released: Notify,
+}
+
+impl Streams {
+    pub(crate) fn new(
+        local_max: Option<usize>,
+        initial_remote_max: usize,
+        scale_at: Option<usize>,
+    ) -> Arc<Streams> {
+        Arc::new(Streams {
+            active: AtomicUsize::new(0),
+            remote_max: AtomicUsize::new(initial_remote_max),
+            local_max: local_max.unwrap_or(usize::MAX),
+            scale_at: scale_at.unwrap_or(usize::MAX),
+            released: Notify::new(),
+        })
+    }
+
+    /// Updates the limit of the server, as received in its settings.
+    pub(crate) fn set_remote_max(&self, max: usize) {
+        if self.remote_max.swap(max, Ordering::AcqRel) < max {
+            self.released.notify_waiters();
+        }
+    }
+
+    /// Returns true if the client set its own limit.
+    pub(crate) fn has_local_max(&self) -> bool {
+        self.local_max != usize::MAX
+    }
+
+    /// Returns the number of streams currently open.
+    pub(crate) fn active(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// Returns true if no other stream can be opened without exceeding a limit.
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.active() >= self.max()
+    }
+
+    /// Returns true if the streams reached the threshold from which requests should rather go to
+    /// another connection.
+    pub(crate) fn is_loaded(&self) -> bool {
+        self.active() >= self.scale_at
+    }
+
+    fn max(&self) -> usize {
+        self.local_max.min(self.remote_max.load(Ordering::Acquire))
+    }
+
+    /// Opens a stream, unless a limit is reached.
+    pub(crate) fn try_open(self: &Arc<Self>) -> Option<StreamSlot> {
+        let max = self.max();
+        self.active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < max).then_some(active + 1)
+            })
+            .ok()
+            .map(|_| StreamSlot(self.clone()))
+    }
+
+    /// Opens a stream, waiting for another one to be done while a limit is reached.
+    pub(crate) async fn open(self: &Arc<Self>) -> StreamSlot {
+        loop {
+            let released = self.released.notified();
+            if let Some(slot) = self.try_open() {
+                return slot;
+            }
+            released.await;
+        }
+    }
+
+    /// Opens a stream whatever the limits, leaving the connection to queue it.
+    pub(crate) fn force_open(self: &Arc<Self>) -> StreamSlot {
+        self.active.fetch_add(1, Ordering::AcqRel);
+        StreamSlot(self.clone())
+    }
+}
+
+/// A stream held open on a connection, released when dropped.
+#[derive(Debug)]
+pub(crate) struct StreamSlot(Arc<Streams>);
+
+impl Drop for StreamSlot {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::AcqRel);
+        self.0.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Streams;
+
+    #[test]
+    fn limits() {
+        let streams = Streams::new(Some(2), 100, None);
+        let first = streams.try_open().unwrap();
+        let _second = streams.try_open().unwrap();
+        assert!(streams.is_exhausted());
+        assert!(streams.try_open().is_none());
+
+        drop(first);
+        assert_eq!(streams.active(), 1);
+        let _third = streams.try_open().unwrap();
+
+        streams.set_remote_max(1);
+        assert!(streams.try_open().is_none());
+        let _forced = streams.force_open();
+        assert_eq!(streams.active(), 3);
+    }
+
+    #[test]
+    fn scaling() {
+        let streams = Streams::new(None, 100, Some(2));
+        let _first = streams.try_open().unwrap();
+        assert!(!streams.is_loaded());
+        let second = streams.try_open().unwrap();
+        assert!(streams.is_loaded());
+        assert!(!streams.is_exhausted());
+
+        drop(second);
+        assert!(!streams.is_loaded());
+    }
+}
@@ -1,7 +1,7 @@
 use bytes::BytesMut;
 use http::{
     HeaderMap, Method,
-    header::{CONTENT_LENGTH, HeaderValue, ValueIter},
+    header::{CONTENT_LENGTH, HeaderValue, TRANSFER_ENCODING, ValueIter},
 };
 
 use crate::OriginalHeaders;
@@ -97,7 +97,25 @@ pub(super) fn set_content_length_if_missing(headers: &mut HeaderMap, len: u64) {
 }
 
 pub(super) fn transfer_encoding_is_chunked(headers: &HeaderMap) -> bool {
-    is_chunked(headers.get_all(http::header::TRANSFER_ENCODING).into_iter())
+    is_chunked(headers.get_all(TRANSFER_ENCODING).into_iter())
+}
+
+/// Returns true if the message is framed by both `Content-Length` and `Transfer-Encoding`.
+///
+/// Peers disagreeing on which one wins is the root of request smuggling, so a strict parser
+/// rejects such a message instead of letting `Transfer-Encoding` override the length.
+pub(super) fn has_conflicting_length(headers: &HeaderMap) -> bool {
+    headers.contains_key(TRANSFER_ENCODING) && headers.contains_key(CONTENT_LENGTH)
+}
+
+/// Returns true if a message head has a CR not followed by LF.
+///
+/// A bare LF is not reported: RFC 9112 lets recipients accept it as a line terminator, and
+/// browsers do.
+pub(super) fn has_bare_cr(head: &[u8]) -> bool {
+    head.iter()
+        .enumerate()
+        .any(|(i, &b)| b == b'\r' && head.get(i + 1) != Some(&b'\n'))
 }
 
 pub(super) fn is_chunked(mut encodings: ValueIter<'_, HeaderValue>) -> bool {
@@ -169,3 +187,30 @@ pub(super) fn sort_headers(headers: &mut HeaderMap, orig: &OriginalHeaders) {
 
     std::mem::swap(headers, &mut sorted_headers);
 }
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, HeaderValue, header};
+
+    use super::{has_bare_cr, has_conflicting_length};
+
+    #[test]
+    fn bare_cr() {
+        assert!(!has_bare_cr(b"HTTP/1.1 200 OK\r\nA: b\r\n\r\n"));
+        assert!(!has_bare_cr(b"HTTP/1.1 200 OK\nA: b\n\n"));
+        assert!(has_bare_cr(b"HTTP/1.1 200 OK\r\nA: b\r\r\n\r\n"));
+        assert!(has_bare_cr(b"HTTP/1.1 200 OK\r"));
+    }
+
+    #[test]
+    fn conflicting_length() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("5"));
+        assert!(!has_conflicting_length(&headers));
+        headers.insert(
+            header::TRANSFER_ENCODING,
+            HeaderValue::from_static("chunked"),
+        );
+        assert!(has_conflicting_length(&headers));
+    }
+}
@@ -67,6 +67,15 @@ enum Auth {
 struct NoProxy {
     ips: IpMatcher,
     domains: DomainMatcher,
+    ports: Vec<PortRule>,
+}
+
+/// A `NO_PROXY` entry only applying to a single destination port, such as `example.com:8080`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PortRule {
+    port: u16,
+    ips: IpMatcher,
+    domains: DomainMatcher,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
@@ -108,8 +117,14 @@ impl Matcher {
     /// If the proxy rules match the destination, a new `Uri` will be returned
     /// to connect to.
     pub fn intercept(&self, dst: &http::Uri) -> Option<Intercept> {
+        let port = dst.port_u16().or(match dst.scheme_str() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        })?;
+
         // TODO(perf): don't need to check `no` if below doesn't match...
-        if self.no.contains(dst.host()?) {
+        if self.no.contains_port(dst.host()?, port) {
             return None;
         }
 
@@ -331,7 +346,7 @@ fn parse_env_uri(val: &str) -> Option<Intercept> {
             if s == &http::uri::Scheme::HTTP || s == &http::uri::Scheme::HTTPS {
                 is_httpish = true;
                 s.clone()
-            } else if s.as_str() == "socks5" || s.as_str() == "socks5h" {
+            } else if matches!(s.as_str(), "socks4" | "socks4a" | "socks5" | "socks5h") {
                 s.clone()
             } else {
                 // can't use this proxy scheme
@@ -348,7 +363,13 @@ fn parse_env_uri(val: &str) -> Option<Intercept> {
     let authority = uri.authority()?;
 
     if let Some((userinfo, host_port)) = authority.as_str().split_once('@') {
-        let (user, pass) = userinfo.split_once(':')?;
+        // SOCKS4 only carries a user ID (ident), so a bare user is allowed for
+        // non-HTTP schemes and is paired with an empty password.
+        let (user, pass) = match userinfo.split_once(':') {
+            Some(pair) => pair,
+            None if !is_httpish => (userinfo, ""),
+            None => return None,
+        };
         let user = percent_decode_str(user).decode_utf8_lossy();
         let pass = percent_decode_str(pass).decode_utf8_lossy();
         if is_httpish {
@@ -395,6 +416,7 @@ impl NoProxy {
         NoProxy {
             ips: IpMatcher(Vec::new()),
             domains: DomainMatcher(Vec::new()),
+            ports: Vec::new(),
         }
     }
 
@@ -406,10 +428,14 @@ impl NoProxy {
     /// * Entries are expected to be comma-separated (whitespace between entries is ignored)
     /// * IP addresses (both IPv4 and IPv6) are allowed, as are optional subnet masks (by adding
     ///   /size, for example "`192.168.1.0/24`").
-    /// * An entry "`*`" matches all hostnames (this is the only wildcard allowed)
+    /// * An entry "`*`" matches all hostnames
+    /// * An entry starting with "`*.`" is a wildcard domain, for example `*.internal` is equivalent
+    ///   to `.internal`
     /// * Any other entry is considered a domain name (and may contain a leading dot, for example
     ///   `google.com` and `.google.com` are equivalent) and would match both that domain AND all
     ///   subdomains.
+    /// * Any entry may end with a port, for example `google.com:8080`, `10.0.0.0/8:443` or
+    ///   `[::1]:8080`, to only match destinations on that port.
     ///
     /// For example, if `"NO_PROXY=google.com, 192.168.1.0/24"` was set, all of the following would
     /// match (and therefore would bypass the proxy):
@@ -419,49 +445,96 @@ impl NoProxy {
     ///
     /// The URL `http://notgoogle.com/` would not match.
     pub fn from_string(no_proxy_list: &str) -> Self {
-        let mut ips = Vec::new();
-        let mut domains = Vec::new();
+        let mut no_proxy = NoProxy::empty();
         let parts = no_proxy_list.split(',').map(str::trim);
         for part in parts {
-            match part.parse::<IpNet>() {
+            if part.is_empty() {
+                continue;
+            }
+
+            let (host, port) = split_port(part);
+            let (ips, domains) = match port {
+                Some(port) => {
+                    no_proxy.ports.push(PortRule {
+                        port,
+                        ips: IpMatcher(Vec::new()),
+                        domains: DomainMatcher(Vec::new()),
+                    });
+                    let rule = no_proxy.ports.last_mut().expect("just pushed");
+                    (&mut rule.ips.0, &mut rule.domains.0)
+                }
+                None => (&mut no_proxy.ips.0, &mut no_proxy.domains.0),
+            };
+
+            match host.parse::<IpNet>() {
                 // If we can parse an IP net or address, then use it, otherwise, assume it is a
                 // domain
                 Ok(ip) => ips.push(Ip::Network(ip)),
-                Err(_) => match part.parse::<IpAddr>() {
+                Err(_) => match host.parse::<IpAddr>() {
                     Ok(addr) => ips.push(Ip::Address(addr)),
-                    Err(_) => {
-                        if !part.trim().is_empty() {
-                            domains.push(part.to_owned())
-                        }
-                    }
+                    Err(_) => match host.strip_prefix("*.") {
+                        Some(domain) => domains.push(format!(".{domain}")),
+                        None => domains.push(host.to_owned()),
+                    },
                 },
             }
         }
-        NoProxy {
-            ips: IpMatcher(ips),
-            domains: DomainMatcher(domains),
-        }
+        no_proxy
     }
 
     /// Return true if this matches the host (domain or IP).
     pub fn contains(&self, host: &str) -> bool {
-        // According to RFC3986, raw IPv6 hosts will be wrapped in []. So we need to strip those off
-        // the end in order to parse correctly
-        let host = if host.starts_with('[') {
-            let x: &[_] = &['[', ']'];
-            host.trim_matches(x)
-        } else {
-            host
-        };
-        match host.parse::<IpAddr>() {
-            // If we can parse an IP addr, then use it, otherwise, assume it is a domain
-            Ok(ip) => self.ips.contains(ip),
-            Err(_) => self.domains.contains(host),
-        }
+        host_matches(&self.ips, &self.domains, host)
+    }
+
+    /// Return true if this matches the host (domain or IP) on the given port.
+    fn contains_port(&self, host: &str, port: u16) -> bool {
+        self.contains(host)
+            || self
+                .ports
+                .iter()
+                .any(|rule| rule.port == port && host_matches(&rule.ips, &rule.domains, host))
     }
 
     fn is_empty(&self) -> bool {
-        self.ips.0.is_empty() && self.domains.0.is_empty()
+        self.ips.0.is_empty() && self.domains.0.is_empty() && self.ports.is_empty()
+    }
+}
+
+fn host_matches(ips: &IpMatcher, domains: &DomainMatcher, host: &str) -> bool {
+    // According to RFC3986, raw IPv6 hosts will be wrapped in []. So we need to strip those off
+    // the end in order to parse correctly
+    let host = if host.starts_with('[') {
+        let x: &[_] = &['[', ']'];
+        host.trim_matches(x)
+    } else {
+        host
+    };
+    match host.parse::<IpAddr>() {
+        // If we can parse an IP addr, then use it, otherwise, assume it is a domain
+        Ok(ip) => ips.contains(ip),
+        Err(_) => domains.contains(host),
+    }
+}
+
+/// Splits the port off a `no_proxy` entry, such as `example.com:8080` or `[::1]:8080`.
+fn split_port(entry: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = entry.strip_prefix('[') {
+        if let Some((host, port)) = rest.split_once(']') {
+            return (
+                host,
+                port.strip_prefix(':').and_then(|port| port.parse().ok()),
+            );
+        }
+    }
+
+    match entry.rsplit_once(':') {
+        // More than one colon is an IPv6 address without brackets, not a port.
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (entry, None),
+        },
+        _ => (entry, None),
     }
 }
 
@@ -748,6 +821,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_no_proxy_wildcard_domains_and_ports() {
+        let no_proxy =
+            NoProxy::from_string("*.internal, example.com:8080, 10.0.0.0/8:443, [::1]:80");
+
+        assert!(no_proxy.contains("internal"));
+        assert!(no_proxy.contains("svc.internal"));
+        assert!(!no_proxy.contains("notinternal"));
+
+        // Rules with a port only match that port.
+        assert!(!no_proxy.contains("example.com"));
+        assert!(no_proxy.contains_port("example.com", 8080));
+        assert!(no_proxy.contains_port("www.example.com", 8080));
+        assert!(!no_proxy.contains_port("example.com", 80));
+
+        assert!(no_proxy.contains_port("10.1.2.3", 443));
+        assert!(!no_proxy.contains_port("10.1.2.3", 80));
+        assert!(!no_proxy.contains_port("11.1.2.3", 443));
+
+        assert!(no_proxy.contains_port("[::1]", 80));
+        assert!(!no_proxy.contains_port("[::1]", 443));
+    }
+
     macro_rules! p {
         ($($n:ident = $v:expr,)*) => ({Builder {
             $($n: $v.into(),)*
@@ -825,6 +921,53 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "socks")]
+    fn test_parse_socks4_ident() {
+        let p = p! {
+            all = "socks4a://ident@y.ep:1080",
+        };
+
+        let proxy = intercept(&p, "https://example.local");
+        assert_eq!(proxy.uri(), "socks4a://y.ep:1080");
+        let (user, pass) = proxy.raw_auth().expect("raw_auth");
+        assert_eq!(user, "ident");
+        assert!(pass.is_empty());
+
+        let p = p! {
+            all = "socks4://y.ep:1080",
+        };
+
+        let proxy = intercept(&p, "http://example.local");
+        assert_eq!(proxy.uri(), "socks4://y.ep:1080");
+        assert!(proxy.raw_auth().is_none());
+    }
+
+    #[test]
+    fn test_no_proxy_port_uses_default_port() {
+        let p = p! {
+            all = "http://y.ep",
+            no = "example.local:443, other.local:8080",
+        };
+
+        assert!(
+            p.intercept(&"https://example.local".parse().unwrap())
+                .is_none()
+        );
+        assert!(
+            p.intercept(&"http://example.local".parse().unwrap())
+                .is_some()
+        );
+        assert!(
+            p.intercept(&"http://other.local:8080".parse().unwrap())
+                .is_none()
+        );
+        assert!(
+            p.intercept(&"http://other.local".parse().unwrap())
+                .is_some()
+        );
+    }
+
     #[test]
     fn test_dont_parse_http_when_is_cgi() {
         let mut builder = Matcher::builder();
@@ -0,0 +1,36 @@
+//! Provides a clock trait reading the current time
+
+use std::time::{Instant, SystemTime};
+
+/// A clock which provides the current time.
+///
+/// Deadlines handed to a [`Timer`](super::Timer) are computed from this clock, so both
+/// should follow the same time source.
+pub trait Clock {
+    /// Return the current instant.
+    fn now(&self) -> Instant;
+
+    /// Return the current system time.
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that reads the time of the operating system.
+#[non_exhaustive]
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// Create a new clock reading the time of the operating system.
+    pub fn new() -> Self {
+        SystemClock
+    }
+}
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
@@ -5,15 +5,18 @@
 //!
 //! - Executors
 //! - Timers
+//! - Clocks
 //! - IO transports
 
 pub mod bounds;
+mod clock;
 mod io;
 mod timer;
 pub mod tokio;
 
 pub(crate) use self::io::{read, write_all};
 pub use self::{
+    clock::{Clock, SystemClock},
     io::{Read, ReadBuf, ReadBufCursor, Write},
     timer::{Sleep, Timer},
     tokio::{TokioExecutor, TokioIo},
@@ -3,12 +3,12 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 use pin_project_lite::pin_project;
 
-use crate::core::rt::{Executor, Read, ReadBuf, ReadBufCursor, Sleep, Timer, Write};
+use crate::core::rt::{Clock, Executor, Read, ReadBuf, ReadBufCursor, Sleep, Timer, Write};
 
 /// Future executor that utilises `tokio` threads.
 #[non_exhaustive]
@@ -31,6 +31,17 @@ pin_project! {
 #[derive(Default, Clone, Debug)]
 pub struct TokioTimer;
 
+/// A Clock that follows the time of the tokio runtime.
+///
+/// Unlike the system clock, it stops when the time is paused with `tokio::time::pause` and
+/// moves as it is advanced, so that deadlines and expiries can be tested deterministically.
+/// The system time it returns starts at the system time of its creation.
+#[derive(Clone, Debug)]
+pub struct TokioClock {
+    start: tokio::time::Instant,
+    system: SystemTime,
+}
+
 // Use TokioSleep to get tokio::time::Sleep to implement Unpin.
 // see https://docs.rs/tokio/latest/tokio/time/struct.Sleep.html
 pin_project! {
@@ -249,6 +260,34 @@ impl TokioTimer {
     }
 }
 
+// ==== impl TokioClock =====
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.system + self.start.elapsed()
+    }
+}
+
+impl TokioClock {
+    /// Create a new TokioClock
+    pub fn new() -> Self {
+        Self {
+            start: tokio::time::Instant::now(),
+            system: SystemTime::now(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Future for TokioSleep {
     type Output = ();
 
@@ -0,0 +1,97 @@
+//! Custom content codings of response bodies
+//!
+//! Besides the codings built into the client with the `gzip`, `brotli`, `zstd` and `deflate`
+//! features, response bodies can be decoded with decoders of your own, registered for a coding
+//! such as `lzma` or `snappy` with
+//! [`ClientBuilder::content_decoder`](crate::ClientBuilder::content_decoder). The registered
+//! codings are announced in the `Accept-Encoding` header of every request, after the built-in
+//! ones, and take precedence over a built-in coding of the same name.
+//!
+//! A body with a coding that is neither built in, enabled nor registered is handled as set with
+//! [`ClientBuilder::unknown_encoding`](crate::ClientBuilder::unknown_encoding): passed through
+//! undecoded, failed, or decoded with a decoder picked for the coding by a callback.
+//!
+//! Bodies are only decoded when at least one of the decompression features is enabled.
+//!
+//! ```
+//! use std::io;
+//!
+//! use wreq::decoding::ContentDecoder;
+//!
+//! /// Decodes the `reverse` coding, which reverses the whole body.
+//! #[derive(Default)]
+//! struct Reverse(Vec<u8>);
+//!
+//! impl ContentDecoder for Reverse {
+//!     fn decode(&mut self, input: &[u8], _out: &mut Vec<u8>) -> io::Result<()> {
+//!         self.0.extend_from_slice(input);
+//!         Ok(())
+//!     }
+//!
+//!     fn finish(&mut self, out: &mut Vec<u8>) -> io::Result<()> {
+//!         out.extend(self.0.drain(..).rev());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let client = wreq::Client::builder()
+//!     .content_decoder("reverse", || Box::new(Reverse::default()))
+//!     .build()?;
+//! # Ok::<(), wreq::Error>(())
+//! ```
+
+use std::{fmt, io, sync::Arc};
+
+/// A streaming decoder of a content coding.
+///
+/// A new decoder is created for each response body. Its errors fail the reading of the body
+/// with an error for which [`Error::is_decode`](crate::Error::is_decode) returns true.
+pub trait ContentDecoder: Send + Sync {
+    /// Decodes a chunk of the body, appending the decoded data to `out`.
+    fn decode(&mut self, input: &[u8], out: &mut Vec<u8>) -> io::Result<()>;
+
+    /// Finishes decoding at the end of the body, appending the remaining decoded data to `out`.
+    fn finish(&mut self, out: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Alias for the callback picking a decoder for a coding not otherwise decoded.
+pub type DecoderCallback = Arc<dyn Fn(&str) -> Option<Box<dyn ContentDecoder>> + Send + Sync>;
+
+/// What is done with a response body having a coding the client doesn't decode.
+///
+/// It applies to the codings that are neither built in nor registered, and to the built-in ones
+/// disabled for the request, such as `gzip` after
+/// [`ClientBuilder::no_gzip`](crate::ClientBuilder::no_gzip).
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum UnknownEncoding {
+    /// Pass the body through undecoded, along with its `Content-Encoding` header.
+    #[default]
+    Passthrough,
+    /// Fail the reading of the body with a decode error.
+    Error,
+    /// Decode the body with the decoder returned by the callback for the coding, or pass it
+    /// through undecoded if the callback returns `None`.
+    Decode(DecoderCallback),
+}
+
+impl UnknownEncoding {
+    /// Creates a policy decoding the body with the decoder returned by `callback` for the
+    /// coding.
+    pub fn decode_with<F>(callback: F) -> UnknownEncoding
+    where
+        F: Fn(&str) -> Option<Box<dyn ContentDecoder>> + Send + Sync + 'static,
+    {
+        UnknownEncoding::Decode(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for UnknownEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnknownEncoding::Passthrough => f.write_str("Passthrough"),
+            UnknownEncoding::Error => f.write_str("Error"),
+            UnknownEncoding::Decode(_) => f.write_str("Decode(..)"),
+        }
+    }
+}
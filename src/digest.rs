@@ -0,0 +1,380 @@
+//! Body checksums
+//!
+//! [`Response::bytes_with_digest`](crate::Response::bytes_with_digest) and
+//! [`Body::with_digest`](crate::Body::with_digest) hash a body while it is read or sent, so it
+//! doesn't have to be buffered a second time to be verified or to fill a `Content-MD5`,
+//! `Digest`, `Content-Digest` or `Repr-Digest` header.
+//!
+//! A client built with [`ClientBuilder::verify_digests`](crate::ClientBuilder::verify_digests)
+//! checks the [RFC 9530] `Content-Digest` and `Repr-Digest` headers of responses against their
+//! body while it is read, failing with a [`DigestMismatch`] error when they differ. With
+//! [`ClientBuilder::content_digest`](crate::ClientBuilder::content_digest), it adds a
+//! `Content-Digest` header to requests with a buffered body.
+//!
+//! [RFC 9530]: https://www.rfc-editor.org/rfc/rfc9530
+//!
+//! ```
+//! use wreq::digest::DigestAlgorithm;
+//!
+//! # async fn run() -> wreq::Result<()> {
+//! let (bytes, digest) = wreq::Client::new()
+//!     .get("http://httpbin.org/bytes/64")
+//!     .send()
+//!     .await?
+//!     .bytes_with_digest(DigestAlgorithm::Sha256)
+//!     .await?;
+//!
+//! println!("{} bytes, sha-256 {}", bytes.len(), digest.to_hex());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use boring2::{
+    hash::MessageDigest,
+    sha::{Sha256, Sha512},
+};
+use bytes::Bytes;
+use http::{HeaderName, HeaderValue};
+
+use crate::sync::Mutex;
+
+/// A hash algorithm a body can be digested with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DigestAlgorithm {
+    /// MD5, only meant for the legacy `Content-MD5` header.
+    Md5,
+    /// SHA-256.
+    Sha256,
+    /// SHA-512.
+    Sha512,
+    /// BLAKE3, with a 32 bytes output.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `blake3` feature to be enabled.
+    #[cfg(feature = "blake3")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blake3")))]
+    Blake3,
+}
+
+impl DigestAlgorithm {
+    /// Returns the name of the algorithm, as registered for the `Content-Digest` and
+    /// `Repr-Digest` headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Md5 => "md5",
+            DigestAlgorithm::Sha256 => "sha-256",
+            DigestAlgorithm::Sha512 => "sha-512",
+            #[cfg(feature = "blake3")]
+            DigestAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Returns the algorithm registered under `name`, ignoring case.
+    fn from_name(name: &str) -> Option<DigestAlgorithm> {
+        [
+            DigestAlgorithm::Md5,
+            DigestAlgorithm::Sha256,
+            DigestAlgorithm::Sha512,
+            #[cfg(feature = "blake3")]
+            DigestAlgorithm::Blake3,
+        ]
+        .into_iter()
+        .find(|algorithm| algorithm.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// The checksum of a body.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Digest {
+    algorithm: DigestAlgorithm,
+    value: Bytes,
+}
+
+impl Digest {
+    /// Returns the algorithm the digest was computed with.
+    pub fn algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the raw digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// Returns the digest as lowercase hexadecimal.
+    pub fn to_hex(&self) -> String {
+        use std::fmt::Write;
+
+        self.value.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+
+    /// Returns the digest encoded as standard base64, as used by `Content-MD5`.
+    pub fn to_base64(&self) -> String {
+        BASE64_STANDARD.encode(&self.value)
+    }
+
+    /// Returns the digest as a `Content-Digest` or `Repr-Digest` header value, such as
+    /// `sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:`.
+    pub fn to_header_value(&self) -> HeaderValue {
+        let value = format!("{}=:{}:", self.algorithm.name(), self.to_base64());
+        HeaderValue::from_str(&value).expect("digest is always a valid HeaderValue")
+    }
+}
+
+impl fmt::Debug for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Digest")
+            .field("algorithm", &self.algorithm)
+            .field("value", &self.to_hex())
+            .finish()
+    }
+}
+
+/// The error returned when a body doesn't match the digest announced in its headers.
+///
+/// It is the source of a [decode error](crate::Error::is_decode), see
+/// [`Error::digest_mismatch`](crate::Error::digest_mismatch).
+#[derive(Clone, Debug)]
+pub struct DigestMismatch {
+    header: HeaderName,
+    expected: Digest,
+    actual: Digest,
+}
+
+impl DigestMismatch {
+    /// Returns the name of the header announcing the digest.
+    pub fn header(&self) -> &HeaderName {
+        &self.header
+    }
+
+    /// Returns the digest announced in the header.
+    pub fn expected(&self) -> &Digest {
+        &self.expected
+    }
+
+    /// Returns the digest of the received body.
+    pub fn actual(&self) -> &Digest {
+        &self.actual
+    }
+}
+
+impl fmt::Display for DigestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "body does not match its {} header ({} expected {}, got {})",
+            self.header,
+            self.expected.algorithm.name(),
+            self.expected.to_base64(),
+            self.actual.to_base64()
+        )
+    }
+}
+
+impl std::error::Error for DigestMismatch {}
+
+/// A digest that becomes available once a body has been fully sent.
+///
+/// Returned by [`Body::with_digest`](crate::Body::with_digest).
+#[derive(Clone, Default)]
+pub struct BodyDigest {
+    digest: Arc<Mutex<Option<Digest>>>,
+}
+
+impl BodyDigest {
+    pub(crate) fn ready(digest: Digest) -> BodyDigest {
+        BodyDigest {
+            digest: Arc::new(Mutex::new(Some(digest))),
+        }
+    }
+
+    pub(crate) fn set(&self, digest: Digest) {
+        *self.digest.lock() = Some(digest);
+    }
+
+    /// Returns the digest of the body, or `None` if it hasn't been fully sent yet.
+    pub fn get(&self) -> Option<Digest> {
+        self.digest.lock().clone()
+    }
+}
+
+impl fmt::Debug for BodyDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BodyDigest").field(&self.get()).finish()
+    }
+}
+
+/// An incremental hasher for any [`DigestAlgorithm`].
+pub(crate) struct Hasher {
+    algorithm: DigestAlgorithm,
+    state: State,
+}
+
+enum State {
+    Md5(boring2::hash::Hasher),
+    Sha256(Sha256),
+    Sha512(Box<Sha512>),
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub(crate) fn new(algorithm: DigestAlgorithm) -> Hasher {
+        let state = match algorithm {
+            DigestAlgorithm::Md5 => State::Md5(
+                boring2::hash::Hasher::new(MessageDigest::md5()).expect("md5 is always supported"),
+            ),
+            DigestAlgorithm::Sha256 => State::Sha256(Sha256::new()),
+            DigestAlgorithm::Sha512 => State::Sha512(Box::new(Sha512::new())),
+            #[cfg(feature = "blake3")]
+            DigestAlgorithm::Blake3 => State::Blake3(Box::new(blake3::Hasher::new())),
+        };
+        Hasher { algorithm, state }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self.state {
+            State::Md5(ref mut hasher) => hasher.update(data).expect("md5 update never fails"),
+            State::Sha256(ref mut hasher) => hasher.update(data),
+            State::Sha512(ref mut hasher) => hasher.update(data),
+            #[cfg(feature = "blake3")]
+            State::Blake3(ref mut hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Digest {
+        let value = match self.state {
+            State::Md5(mut hasher) => {
+                Bytes::copy_from_slice(&hasher.finish().expect("md5 finish never fails"))
+            }
+            State::Sha256(hasher) => Bytes::copy_from_slice(&hasher.finish()),
+            State::Sha512(hasher) => Bytes::copy_from_slice(&hasher.finish()),
+            #[cfg(feature = "blake3")]
+            State::Blake3(hasher) => Bytes::copy_from_slice(hasher.finalize().as_bytes()),
+        };
+        Digest {
+            algorithm: self.algorithm,
+            value,
+        }
+    }
+}
+
+/// A digest announced in a response header, checked against the body as it is read.
+pub(crate) struct Expectation {
+    header: HeaderName,
+    expected: Digest,
+    hasher: Hasher,
+}
+
+impl Expectation {
+    /// Parses the `Content-Digest` or `Repr-Digest` header `value`, a structured field
+    /// dictionary such as `sha-256=:<base64>:, sha-512=:<base64>:`.
+    ///
+    /// Members with an unsupported algorithm or an invalid value are skipped.
+    pub(crate) fn parse(header: &HeaderName, value: &HeaderValue) -> Vec<Expectation> {
+        let Ok(value) = value.to_str() else {
+            return Vec::new();
+        };
+
+        value
+            .split(',')
+            .filter_map(|member| {
+                let (name, value) = member.trim().split_once('=')?;
+                let algorithm = DigestAlgorithm::from_name(name.trim())?;
+                let value = value.trim().strip_prefix(':')?.strip_suffix(':')?;
+                let value = BASE64_STANDARD.decode(value).ok()?;
+                Some(Expectation {
+                    header: header.clone(),
+                    expected: Digest {
+                        algorithm,
+                        value: value.into(),
+                    },
+                    hasher: Hasher::new(algorithm),
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Checks the digest of the data fed so far against the expected one.
+    pub(crate) fn verify(self) -> Result<(), DigestMismatch> {
+        let actual = self.hasher.finish();
+        if actual == self.expected {
+            Ok(())
+        } else {
+            Err(DigestMismatch {
+                header: self.header,
+                expected: self.expected,
+                actual,
+            })
+        }
+    }
+}
+
+/// Computes the digest of `data` in one go.
+pub(crate) fn digest(algorithm: DigestAlgorithm, data: &[u8]) -> Digest {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(data);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderName, HeaderValue};
+
+    use super::{DigestAlgorithm, Expectation, digest};
+
+    #[test]
+    fn digest_encodings() {
+        let sha256 = digest(DigestAlgorithm::Sha256, b"hello");
+        assert_eq!(
+            sha256.to_hex(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(
+            sha256.to_header_value(),
+            "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:"
+        );
+
+        let md5 = digest(DigestAlgorithm::Md5, b"hello");
+        assert_eq!(md5.to_base64(), "XUFAKrxLKna5cZ2REBfFkg==");
+        assert_eq!(
+            digest(DigestAlgorithm::Sha512, b"hello").as_bytes().len(),
+            64
+        );
+    }
+
+    #[test]
+    fn parse_digest_header() {
+        let header = HeaderName::from_static("content-digest");
+        let value = HeaderValue::from_static(
+            "SHA-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:, unixsum=:MTIz:, sha-512=bad",
+        );
+        let expectations = Expectation::parse(&header, &value);
+        assert_eq!(expectations.len(), 1);
+
+        let mut expectation = expectations.into_iter().next().unwrap();
+        expectation.update(b"hel");
+        expectation.update(b"lo");
+        assert!(expectation.verify().is_ok());
+
+        let mut expectation = Expectation::parse(&header, &value).pop().unwrap();
+        expectation.update(b"hello!");
+        let mismatch = expectation.verify().unwrap_err();
+        assert_eq!(mismatch.header(), &header);
+        assert_eq!(mismatch.actual().algorithm(), DigestAlgorithm::Sha256);
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
     collections::HashMap,
+    fmt,
     future::Future,
+    hash::{Hash, Hasher},
     net::SocketAddr,
     pin::Pin,
     str::FromStr,
@@ -56,6 +58,10 @@ impl FromStr for Name {
     }
 }
 
+/// A shared resolver.
+///
+/// Two `DynResolver`s are equal if they share the same resolver, so that the connections
+/// established through a resolver are only reused by the requests resolving with it.
 #[derive(Clone)]
 pub(crate) struct DynResolver {
     resolver: Arc<dyn Resolve>,
@@ -65,6 +71,30 @@ impl DynResolver {
     pub(crate) fn new(resolver: Arc<dyn Resolve>) -> Self {
         Self { resolver }
     }
+
+    pub(crate) fn resolve(&self, name: Name) -> Resolving {
+        self.resolver.resolve(name)
+    }
+}
+
+impl PartialEq for DynResolver {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.resolver, &other.resolver)
+    }
+}
+
+impl Eq for DynResolver {}
+
+impl Hash for DynResolver {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.resolver).cast::<()>().hash(state);
+    }
+}
+
+impl fmt::Debug for DynResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("DynResolver")
+    }
 }
 
 impl Service<NativeName> for DynResolver {
@@ -0,0 +1,439 @@
+//! Segmented downloads
+//!
+//! A [`Downloader`] fetches a large resource as a series of byte range segments, several of them
+//! in parallel over the connection pool of its `Client`, and writes them in order to any
+//! `AsyncWrite`, such as a `tokio::fs::File`.
+//!
+//! The first segment doubles as a probe: when the server does not answer it with
+//! `206 Partial Content`, the whole response body is streamed to the writer instead. A segment
+//! failing midway is retried from the first byte it is missing.
+//!
+//! The following segments are requested with `If-Range`, carrying the strong `ETag` or the
+//! `Last-Modified` date of the first response, so that the segments of a resource modified
+//! during the download are never mixed: the download fails if a segment comes back whole or
+//! with another validator.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use wreq::downloader::Downloader;
+//!
+//! let mut file = tokio::fs::File::create("big.iso").await?;
+//! let written = Downloader::new(wreq::Client::new())
+//!     .concurrency(4)
+//!     .on_progress(|progress| println!("{:?}/{:?}", progress.downloaded(), progress.total()))
+//!     .download("https://example.com/big.iso", &mut file)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{StreamExt, TryStreamExt, stream};
+use http::{
+    HeaderName, HeaderValue, StatusCode,
+    header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED},
+};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{Client, Error, IntoUrl, Response, Url, client::range::ByteRange};
+
+type ProgressCallback = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// A manager of parallel segmented downloads.
+///
+/// At most `concurrency` segments of `segment_size` bytes are held in memory at once, while the
+/// segments preceding them are written. The rest of a resource whose size the server doesn't
+/// give is written as it is received instead.
+#[derive(Clone)]
+pub struct Downloader {
+    client: Client,
+    concurrency: usize,
+    segment_size: u64,
+    retries: usize,
+    on_progress: Option<ProgressCallback>,
+}
+
+/// The progress of a download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+impl Progress {
+    /// Returns the number of bytes downloaded so far, across all segments.
+    #[inline]
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    /// Returns the size of the resource, if known.
+    #[inline]
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+}
+
+/// The validator of the representation a download started with, which all its segments must
+/// match.
+struct Validator {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl Validator {
+    /// Returns the strong entity tag of a response, or else its modification date.
+    ///
+    /// A weak entity tag can't be used in `If-Range`.
+    fn of(res: &Response) -> Option<Validator> {
+        let etag = res
+            .headers()
+            .get(ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"));
+        let (name, value) = match etag {
+            Some(etag) => (ETAG, etag),
+            None => (LAST_MODIFIED, res.headers().get(LAST_MODIFIED)?),
+        };
+        Some(Validator {
+            name,
+            value: value.clone(),
+        })
+    }
+
+    /// Checks that a response to an `If-Range` request is a part of the same representation.
+    fn check(&self, res: &Response) -> crate::Result<()> {
+        let changed = res.status() != StatusCode::PARTIAL_CONTENT
+            || res
+                .headers()
+                .get(&self.name)
+                .is_some_and(|value| *value != self.value);
+        if changed {
+            return Err(
+                Error::decode("resource changed during the download").with_url(res.url().clone())
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Aggregates the progress of the segments of a download.
+struct Tracker<'a> {
+    downloaded: AtomicU64,
+    total: Option<u64>,
+    on_progress: Option<&'a ProgressCallback>,
+}
+
+impl Tracker<'_> {
+    fn advance(&self, n: usize) {
+        let downloaded = self.downloaded.fetch_add(n as u64, Ordering::Relaxed) + n as u64;
+        if let Some(on_progress) = self.on_progress {
+            on_progress(Progress {
+                downloaded,
+                total: self.total,
+            });
+        }
+    }
+}
+
+impl Downloader {
+    /// Creates a `Downloader` sending its requests with the given `Client`.
+    pub fn new(client: Client) -> Downloader {
+        Downloader {
+            client,
+            concurrency: 4,
+            segment_size: 8 * 1024 * 1024,
+            retries: 3,
+            on_progress: None,
+        }
+    }
+
+    /// Sets the number of segments downloaded in parallel.
+    ///
+    /// Default is 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Downloader {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the size of a segment in bytes.
+    ///
+    /// Default is 8 MiB.
+    pub fn segment_size(mut self, size: u64) -> Downloader {
+        self.segment_size = size.max(1);
+        self
+    }
+
+    /// Sets how many times a failed segment is retried.
+    ///
+    /// Default is 3.
+    pub fn retries(mut self, retries: usize) -> Downloader {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets a callback called with the aggregate progress whenever data is received.
+    pub fn on_progress<F>(mut self, on_progress: F) -> Downloader
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Downloads the resource at `url` into `writer`, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if a segment still fails after all its retries, if the server answers
+    /// with an error status, if the resource changes during the download, or if writing fails.
+    /// An empty resource, whose range the server refuses with `416 Range Not Satisfiable`, is
+    /// downloaded as such.
+    pub async fn download<U, W>(&self, url: U, writer: &mut W) -> crate::Result<u64>
+    where
+        U: IntoUrl,
+        W: AsyncWrite + Unpin,
+    {
+        let url = url.into_url()?;
+        let first = ByteRange::new(0..self.segment_size)?;
+        let res = first.get(&self.client, url.clone()).send().await?;
+        if is_empty_resource(&res) {
+            writer.flush().await.map_err(Error::body)?;
+            return Ok(0);
+        }
+        let mut res = res.error_for_status()?;
+
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            // Ranges are not supported: stream the whole body.
+            let tracker = self.tracker(res.content_length());
+            let mut written = 0;
+            while let Some(chunk) = res.chunk().await? {
+                tracker.advance(chunk.len());
+                write(writer, &chunk).await?;
+                written += chunk.len() as u64;
+            }
+            writer.flush().await.map_err(Error::body)?;
+            return Ok(written);
+        }
+
+        let content_range = first.validate(&res)?;
+        let validator = Validator::of(&res);
+        let validator = validator.as_ref();
+        let mut rest = Vec::new();
+        let mut tail = None;
+        match content_range.complete_length {
+            Some(total) => {
+                let mut start = content_range.end + 1;
+                while start < total {
+                    let end = start.saturating_add(self.segment_size).min(total);
+                    rest.push(ByteRange::new(start..end)?);
+                    start = end;
+                }
+            }
+            // The size is unknown: stream everything after the first segment at once, unless the
+            // first segment already reached the end.
+            None if content_range.len() == self.segment_size => {
+                tail = Some(ByteRange::new(content_range.end + 1..)?);
+            }
+            None => (),
+        }
+
+        let tracker = self.tracker(content_range.complete_length);
+        let segments =
+            std::iter::once(self.fetch_segment(&url, first, Some(res), validator, &tracker)).chain(
+                rest.into_iter()
+                    .map(|range| self.fetch_segment(&url, range, None, validator, &tracker)),
+            );
+
+        let mut segments = stream::iter(segments).buffered(self.concurrency);
+        let mut written = 0;
+        while let Some(segment) = segments.try_next().await? {
+            write(writer, &segment).await?;
+            written += segment.len() as u64;
+        }
+        if let Some(tail) = tail {
+            written += self
+                .stream_tail(&url, tail, validator, &tracker, writer)
+                .await?;
+        }
+        writer.flush().await.map_err(Error::body)?;
+        Ok(written)
+    }
+
+    fn tracker(&self, total: Option<u64>) -> Tracker<'_> {
+        Tracker {
+            downloaded: AtomicU64::new(0),
+            total,
+            on_progress: self.on_progress.as_ref(),
+        }
+    }
+
+    /// Fetches a segment, retrying from its first missing byte when it fails.
+    async fn fetch_segment(
+        &self,
+        url: &Url,
+        range: ByteRange,
+        mut res: Option<Response>,
+        validator: Option<&Validator>,
+        tracker: &Tracker<'_>,
+    ) -> crate::Result<Bytes> {
+        let mut buf = BytesMut::new();
+        let mut retries = 0;
+        loop {
+            match self
+                .read_segment(url, range, &mut buf, res.take(), validator, tracker)
+                .await
+            {
+                Ok(()) => return Ok(buf.freeze()),
+                Err(err) if retries < self.retries && is_retryable(&err) => retries += 1,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn read_segment(
+        &self,
+        url: &Url,
+        range: ByteRange,
+        buf: &mut BytesMut,
+        res: Option<Response>,
+        validator: Option<&Validator>,
+        tracker: &Tracker<'_>,
+    ) -> crate::Result<()> {
+        let range = match range.skip(buf.len() as u64) {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+
+        let mut res = match res {
+            Some(res) => res,
+            None => self.request(url, range, validator).await?,
+        };
+
+        let len = range.validate(&res)?.len();
+        let mut read = 0;
+        while let Some(chunk) = res.chunk().await? {
+            read += chunk.len() as u64;
+            if read > len {
+                return Err(
+                    Error::decode("segment is longer than its Content-Range").with_url(url.clone())
+                );
+            }
+            tracker.advance(chunk.len());
+            buf.extend_from_slice(&chunk);
+        }
+
+        if read < len {
+            return Err(Error::body("segment ended early").with_url(url.clone()));
+        }
+        Ok(())
+    }
+
+    /// Streams the open-ended `range` of a resource of unknown size into `writer` as it is
+    /// received, rather than buffering it as a segment, returning the number of bytes written.
+    ///
+    /// A failed response is requested again from its first byte not yet written.
+    async fn stream_tail<W: AsyncWrite + Unpin>(
+        &self,
+        url: &Url,
+        range: ByteRange,
+        validator: Option<&Validator>,
+        tracker: &Tracker<'_>,
+        writer: &mut W,
+    ) -> crate::Result<u64> {
+        let mut written = 0;
+        let mut retries = 0;
+        loop {
+            let range = range.skip(written).expect("an open-ended range never ends");
+            let err = match self.request(url, range, validator).await {
+                Ok(mut res) => {
+                    let len = range.validate(&res)?.len();
+                    let mut read = 0;
+                    loop {
+                        let chunk = match res.chunk().await {
+                            Ok(Some(chunk)) => chunk,
+                            Ok(None) if read < len => {
+                                break Error::body("segment ended early").with_url(url.clone());
+                            }
+                            Ok(None) => return Ok(written),
+                            Err(err) => break err,
+                        };
+                        read += chunk.len() as u64;
+                        if read > len {
+                            return Err(Error::decode("segment is longer than its Content-Range")
+                                .with_url(url.clone()));
+                        }
+                        tracker.advance(chunk.len());
+                        // A failed write is not retried.
+                        write(writer, &chunk).await?;
+                        written += chunk.len() as u64;
+                    }
+                }
+                Err(err) => err,
+            };
+
+            if retries >= self.retries || !is_retryable(&err) {
+                return Err(err);
+            }
+            retries += 1;
+        }
+    }
+
+    /// Requests a range of the resource, which must not have changed since `validator`.
+    async fn request(
+        &self,
+        url: &Url,
+        range: ByteRange,
+        validator: Option<&Validator>,
+    ) -> crate::Result<Response> {
+        let mut req = range.get(&self.client, url.clone());
+        if let Some(validator) = validator {
+            req = req.header(IF_RANGE, validator.value.clone());
+        }
+        let res = req.send().await?.error_for_status()?;
+        if let Some(validator) = validator {
+            validator.check(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl fmt::Debug for Downloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Downloader")
+            .field("concurrency", &self.concurrency)
+            .field("segment_size", &self.segment_size)
+            .field("retries", &self.retries)
+            .finish()
+    }
+}
+
+async fn write<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> crate::Result<()> {
+    writer.write_all(buf).await.map_err(Error::body)
+}
+
+/// Returns true if a range request was refused as the resource is empty.
+fn is_empty_resource(res: &Response) -> bool {
+    res.status() == StatusCode::RANGE_NOT_SATISFIABLE
+        && res
+            .headers()
+            .get(CONTENT_RANGE)
+            .is_some_and(|value| value.as_bytes().trim_ascii() == b"bytes */0")
+}
+
+/// Returns whether a failed segment may succeed when requested again.
+///
+/// A response that doesn't match its request, such as that of a resource that changed, is
+/// answered the same way again.
+fn is_retryable(err: &Error) -> bool {
+    !err.is_builder()
+        && !err.is_decode()
+        && !err.status().is_some_and(|status| status.is_client_error())
+}
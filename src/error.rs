@@ -1,6 +1,21 @@
-use std::{error::Error as StdError, fmt, io};
-
-use crate::{StatusCode, Url, core::ext::ReasonPhrase, util::Escape};
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use std::{error::Error as StdError, fmt, io, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use crate::wasm::ReasonPhrase;
+use crate::{Method, StatusCode, Url, util::Escape};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    PoolStats, TunnelResponse,
+    budget::BufferLimitExceeded,
+    config::{InvalidOption, InvalidOptions},
+    core::{client::connect::proxy::TunnelError, ext::ReasonPhrase},
+    digest::DigestMismatch,
+    redirect::RedirectSchemeBlocked,
+    robots::RobotsDisallowed,
+    scan::ScanRejected,
+};
 
 /// A `Result` alias where the `Err` case is `wreq::Error`.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -21,6 +36,14 @@ struct Inner {
     kind: Kind,
     source: Option<BoxError>,
     url: Option<Url>,
+    context: Option<Context>,
+}
+
+/// The request an error occurred for.
+struct Context {
+    method: Method,
+    attempt: u32,
+    elapsed: Duration,
 }
 
 impl Error {
@@ -33,6 +56,7 @@ impl Error {
                 kind,
                 source: source.map(Into::into),
                 url: None,
+                context: None,
             }),
         }
     }
@@ -121,6 +145,37 @@ impl Error {
         self
     }
 
+    /// Returns the method of the request this error occurred for.
+    ///
+    /// When the request was redirected, this is the method of the last request in the redirect
+    /// chain, which [`Error::url`] is the URL of.
+    pub fn method(&self) -> Option<&Method> {
+        self.inner.context.as_ref().map(|context| &context.method)
+    }
+
+    /// Returns the attempt number of the request this error occurred for, starting at 1.
+    ///
+    /// The number is greater than 1 when the request was retried, and is reset when the request
+    /// is redirected.
+    pub fn attempt(&self) -> Option<u32> {
+        self.inner.context.as_ref().map(|context| context.attempt)
+    }
+
+    /// Returns the time elapsed between sending the request and this error.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.inner.context.as_ref().map(|context| context.elapsed)
+    }
+
+    /// Attach the request context to this error (overwriting any existing).
+    pub(crate) fn with_context(mut self, method: Method, attempt: u32, elapsed: Duration) -> Self {
+        self.inner.context = Some(Context {
+            method,
+            attempt,
+            elapsed,
+        });
+        self
+    }
+
     /// Returns true if the error is from a type Builder.
     pub fn is_builder(&self) -> bool {
         matches!(self.inner.kind, Kind::Builder)
@@ -145,12 +200,20 @@ impl Error {
                 return true;
             }
 
+            #[cfg(not(target_arch = "wasm32"))]
             if let Some(core_err) = err.downcast_ref::<crate::core::Error>() {
                 if core_err.is_timeout() {
                     return true;
                 }
             }
 
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(client_err) = err.downcast_ref::<crate::core::client::Error>() {
+                if client_err.pool_timeout().is_some() {
+                    return true;
+                }
+            }
+
             if let Some(io) = err.downcast_ref::<io::Error>() {
                 if io.kind() == io::ErrorKind::TimedOut {
                     return true;
@@ -173,6 +236,7 @@ impl Error {
         let mut source = self.source();
 
         while let Some(err) = source {
+            #[cfg(not(target_arch = "wasm32"))]
             if let Some(hyper_err) = err.downcast_ref::<crate::core::client::Error>() {
                 if hyper_err.is_connect() {
                     return true;
@@ -185,6 +249,199 @@ impl Error {
         false
     }
 
+    /// Returns true if the request timed out waiting for a connection from the pool.
+    ///
+    /// See [`ClientBuilder::pool_checkout_timeout`](crate::ClientBuilder::pool_checkout_timeout).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_pool_timeout(&self) -> bool {
+        self.pool_stats().is_some()
+    }
+
+    /// Returns the state of the connection pool when waiting for a connection timed out.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_stats(&self) -> Option<PoolStats> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(client_err) = err.downcast_ref::<crate::core::client::Error>() {
+                if let Some(stats) = client_err.pool_timeout() {
+                    return Some(stats);
+                }
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the response of the proxy if it refused to open a tunnel to the destination.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tunnel_response(&self) -> Option<&TunnelResponse> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(tunnel_err) = err.downcast_ref::<TunnelError>() {
+                return tunnel_err.response();
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the digest mismatch if the response body didn't match its `Content-Digest` or
+    /// `Repr-Digest` header.
+    ///
+    /// See [`ClientBuilder::verify_digests`](crate::ClientBuilder::verify_digests).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn digest_mismatch(&self) -> Option<&DigestMismatch> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(mismatch) = err.downcast_ref::<DigestMismatch>() {
+                return Some(mismatch);
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the rejection if the response body was rejected by its scan.
+    ///
+    /// See [`ClientBuilder::body_scanner`](crate::ClientBuilder::body_scanner).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn scan_rejected(&self) -> Option<&ScanRejected> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(rejected) = err.downcast_ref::<ScanRejected>() {
+                return Some(rejected);
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the exceeded limit if a response body was buffered beyond its limit or the
+    /// capacity of its budget.
+    ///
+    /// See the [`budget`](crate::budget) module.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn buffer_limit_exceeded(&self) -> Option<&BufferLimitExceeded> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(exceeded) = err.downcast_ref::<BufferLimitExceeded>() {
+                return Some(exceeded);
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the blocked scheme switch if a redirect went between `http` and `https` in a
+    /// direction the client doesn't allow.
+    ///
+    /// See [`ClientBuilder::redirect_downgrades`](crate::ClientBuilder::redirect_downgrades).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn redirect_scheme_blocked(&self) -> Option<&RedirectSchemeBlocked> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(blocked) = err.downcast_ref::<RedirectSchemeBlocked>() {
+                return Some(blocked);
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns the invalid options of the builder if building a client failed because of them.
+    ///
+    /// All the invalid options are reported at once, such as header values that couldn't be
+    /// parsed and options conflicting with each other.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn invalid_options(&self) -> Option<&[InvalidOption]> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(options) = err.downcast_ref::<InvalidOptions>() {
+                return Some(&options.0);
+            }
+
+            source = err.source();
+        }
+
+        None
+    }
+
+    /// Returns true if the request was disallowed by the robots.txt of its origin.
+    ///
+    /// See [`RequestBuilder::respect_robots`](crate::RequestBuilder::respect_robots).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_robots_disallowed(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if err.is::<RobotsDisallowed>() {
+                return true;
+            }
+
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the server does not support the HTTP/2 extended CONNECT protocol.
+    #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+    pub(crate) fn is_extended_connect_disabled(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(core_err) = err.downcast_ref::<crate::core::Error>() {
+                if core_err.is_extended_connect_disabled() {
+                    return true;
+                }
+            }
+
+            source = err.source();
+        }
+
+        false
+    }
+
+    /// Returns true if the request was refused because its HTTP/2 connection reached its limit of
+    /// concurrent streams.
+    ///
+    /// See [`Http2ConfigBuilder::streams_exhausted`](crate::http2::Http2ConfigBuilder::streams_exhausted).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_streams_exhausted(&self) -> bool {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(core_err) = err.downcast_ref::<crate::core::Error>() {
+                if core_err.is_streams_exhausted() {
+                    return true;
+                }
+            }
+
+            source = err.source();
+        }
+
+        false
+    }
+
     /// Returns true if the error is related to a connection reset.
     pub fn is_connection_reset(&self) -> bool {
         let mut source = self.source();
@@ -201,6 +458,24 @@ impl Error {
         false
     }
 
+    /// Returns the HTTP/2 error code the server closed the stream or the connection with.
+    ///
+    /// This tells apart, for instance, a stream refused with `REFUSED_STREAM` from a connection
+    /// shut down with `ENHANCE_YOUR_CALM`, once retries are exhausted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_reason(&self) -> Option<crate::http2::Reason> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(h2) = err.downcast_ref::<http2::Error>() {
+                return h2.reason();
+            }
+            source = err.source();
+        }
+
+        None
+    }
+
     /// Returns true if the error is related to the request or response body
     pub fn is_body(&self) -> bool {
         matches!(self.inner.kind, Kind::Body)
@@ -234,6 +509,7 @@ impl Error {
 /// to the internal `TimedOut` error type used for connector operations.
 /// Returns the original error if it is not a timeout.
 #[inline]
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn map_timeout_to_connector_error(error: BoxError) -> BoxError {
     if error.is::<tower::timeout::error::Elapsed>() {
         Box::new(TimedOut) as BoxError
@@ -246,6 +522,7 @@ pub(crate) fn map_timeout_to_connector_error(error: BoxError) -> BoxError {
 /// to the internal request-level `Error` type.
 /// Returns the original error if it is not a timeout.
 #[inline]
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn map_timeout_to_request_error(error: BoxError) -> BoxError {
     if error.is::<tower::timeout::error::Elapsed>() {
         Box::new(Error::request(TimedOut)) as BoxError
@@ -263,6 +540,11 @@ impl fmt::Debug for Error {
         if let Some(ref url) = self.inner.url {
             builder.field("url", &url.as_str());
         }
+        if let Some(ref context) = self.inner.context {
+            builder.field("method", &context.method);
+            builder.field("attempt", &context.attempt);
+            builder.field("elapsed", &context.elapsed);
+        }
         if let Some(ref source) = self.inner.source {
             builder.field("source", source);
         }
@@ -353,6 +635,17 @@ impl fmt::Display for BadScheme {
 
 impl StdError for BadScheme {}
 
+#[derive(Debug)]
+pub(crate) struct UrlRejected(pub(crate) &'static str);
+
+impl fmt::Display for UrlRejected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "URL rejected by policy: {}", self.0)
+    }
+}
+
+impl StdError for UrlRejected {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
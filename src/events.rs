@@ -0,0 +1,118 @@
+//! Connection lifecycle events
+//!
+//! A [`ConnectionEvents`] implementation installed with
+//! [`ClientBuilder::connection_events`](crate::ClientBuilder::connection_events) is notified as
+//! connections are established, handed back to the pool, reused and evicted, which is enough to
+//! build custom telemetry such as connect latency histograms or pool churn counters.
+//!
+//! ```
+//! use std::{
+//!     sync::atomic::{AtomicUsize, Ordering},
+//!     time::Duration,
+//! };
+//!
+//! use http::Uri;
+//! use wreq::events::ConnectionEvents;
+//!
+//! #[derive(Default)]
+//! struct Counters {
+//!     connects: AtomicUsize,
+//!     reuses: AtomicUsize,
+//! }
+//!
+//! impl ConnectionEvents for Counters {
+//!     fn connect_succeeded(&self, _uri: &Uri, _elapsed: Duration) {
+//!         self.connects.fetch_add(1, Ordering::Relaxed);
+//!     }
+//!
+//!     fn connection_reused(&self, _origin: &Uri) {
+//!         self.reuses.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! # fn run() -> wreq::Result<()> {
+//! let client = wreq::Client::builder()
+//!     .connection_events(Counters::default())
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{error::Error as StdError, time::Duration};
+
+use http::Uri;
+
+use crate::tls::{AlpnNegotiation, TlsInfo};
+
+/// A listener of connection lifecycle events.
+///
+/// All methods have empty default implementations. They are called synchronously from the
+/// connector and the connection pool, the pool events while holding the pool lock, so they
+/// should return quickly.
+pub trait ConnectionEvents: Send + Sync {
+    /// Called when a new connection to `uri` is started, before resolving its host.
+    fn connect_started(&self, uri: &Uri) {
+        let _ = uri;
+    }
+
+    /// Called when a new connection to `uri` is established, including the TLS handshake and any
+    /// proxy tunnel.
+    fn connect_succeeded(&self, uri: &Uri, elapsed: Duration) {
+        let _ = (uri, elapsed);
+    }
+
+    /// Called when a new connection to `uri` fails or times out.
+    fn connect_failed(&self, uri: &Uri, error: &(dyn StdError + 'static)) {
+        let _ = (uri, error);
+    }
+
+    /// Called when the TLS handshake of a new connection to `uri` is done, with the negotiated
+    /// parameters.
+    fn tls_handshake_done(&self, uri: &Uri, info: &TlsInfo) {
+        let _ = (uri, info);
+    }
+
+    /// Called when the ALPN negotiation of a new TLS connection to `uri` is done, with the
+    /// protocols offered and the one selected.
+    ///
+    /// Returning an error aborts the connection, failing the request with a connect error
+    /// caused by it, such as when HTTP/2 was required but the server fell back to HTTP/1.1.
+    fn alpn_negotiated(
+        &self,
+        uri: &Uri,
+        negotiation: &AlpnNegotiation,
+    ) -> Result<(), Box<dyn StdError + Send + Sync>> {
+        let _ = (uri, negotiation);
+        Ok(())
+    }
+
+    /// Called when a connection to `origin` is put in the pool of idle connections.
+    fn connection_pooled(&self, origin: &Uri) {
+        let _ = origin;
+    }
+
+    /// Called when a connection to `origin` is checked out of the pool for another request.
+    fn connection_reused(&self, origin: &Uri) {
+        let _ = origin;
+    }
+
+    /// Called when an idle connection to `origin` is dropped from the pool.
+    fn connection_evicted(&self, origin: &Uri, reason: EvictionReason) {
+        let _ = (origin, reason);
+    }
+}
+
+/// The reason an idle connection is dropped from the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EvictionReason {
+    /// The connection was closed by the peer.
+    Closed,
+    /// The connection was idle for longer than the pool idle timeout.
+    IdleTimeout,
+    /// The pool already holds the maximum number of idle connections for the host.
+    PoolFull,
+    /// The pool held the maximum number of idle connections across all hosts, and this
+    /// connection was idle the longest.
+    IdleLimit,
+}
@@ -0,0 +1,96 @@
+//! Idempotency Keys
+//!
+//! An `Idempotency-Key` header lets a server recognize a request it already processed, so that
+//! an unsafe request, such as a `POST` creating a payment, can be sent again without being
+//! applied twice.
+//!
+//! Once enabled with [`ClientBuilder::idempotency_key`](crate::ClientBuilder::idempotency_key),
+//! every request with an unsafe method gets a key from a [`KeyGenerator`], unless it already
+//! carries an `Idempotency-Key` header. The key is generated once per request: when the
+//! [retry](crate::retry) layer replays the request, the same key is sent again, and the request
+//! is retried like an idempotent one.
+//!
+//! ```
+//! use wreq::{header::HeaderValue, idempotency::UuidV4};
+//!
+//! # fn run() -> wreq::Result<()> {
+//! // Random UUIDs as keys.
+//! let client = wreq::Client::builder().idempotency_key(UuidV4).build()?;
+//!
+//! // Or keys from a closure.
+//! let client = wreq::Client::builder()
+//!     .idempotency_key(|| HeaderValue::from_static("order-42"))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use http::{HeaderName, HeaderValue};
+
+/// The `Idempotency-Key` header name.
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// A generator of the `Idempotency-Key` of a request.
+///
+/// Implemented for closures returning a [`HeaderValue`].
+pub trait KeyGenerator: Send + Sync {
+    /// Returns a new key, unique to the request it is attached to.
+    fn generate(&self) -> HeaderValue;
+}
+
+impl<F> KeyGenerator for F
+where
+    F: Fn() -> HeaderValue + Send + Sync,
+{
+    #[inline]
+    fn generate(&self) -> HeaderValue {
+        self()
+    }
+}
+
+/// Generates random version 4 UUIDs as keys, e.g. `5f0c2a1e-8b9d-4c3f-a7e6-1d2b3c4d5e6f`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UuidV4;
+
+impl KeyGenerator for UuidV4 {
+    fn generate(&self) -> HeaderValue {
+        let mut bytes = [0u8; 16];
+        boring2::rand::rand_bytes(&mut bytes).expect("failed to generate random bytes");
+        // Set the version and the RFC 9562 variant.
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        HeaderValue::from_str(&Uuid(bytes).to_string()).expect("a UUID is a valid header value")
+    }
+}
+
+struct Uuid([u8; 16]);
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid_v4() {
+        let key = UuidV4.generate();
+        let key = key.to_str().unwrap();
+        assert_eq!(key.len(), 36);
+        assert_eq!(&key[14..15], "4");
+        assert!(matches!(&key[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(UuidV4.generate(), UuidV4.generate());
+    }
+}
@@ -0,0 +1,351 @@
+//! streaming JSON responses
+
+use std::{fmt, marker::PhantomData};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::{Buf, BytesMut};
+#[cfg(not(target_arch = "wasm32"))]
+use futures_util::Stream;
+#[cfg(not(target_arch = "wasm32"))]
+use http_body::Body as HttpBody;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::Error;
+
+/// A stream of the values of a JSON response body, deserialized as the body arrives.
+///
+/// If the body is a JSON array, its elements are yielded one after the other. Otherwise the
+/// body is read as a sequence of JSON values separated by whitespace, such as newline-delimited
+/// JSON. Only the bytes of the value being received are held in memory, so that a very large
+/// array can be processed without buffering the whole body.
+///
+/// The stream ends after the first error. On `wasm32`, where there is no `Stream`, the values
+/// are read with [`JsonStream::next`] instead.
+///
+/// Created with [`Response::json_stream`](crate::Response::json_stream).
+///
+/// # Example
+///
+/// ```
+/// # use futures_util::StreamExt;
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// #[derive(serde::Deserialize)]
+/// struct Item {
+///     id: u64,
+/// }
+///
+/// let mut items = wreq::Client::new()
+///     .get("http://httpbin.org/stream/20")
+///     .send()
+///     .await?
+///     .json_stream::<Item>();
+///
+/// while let Some(item) = items.next().await {
+///     println!("item: {}", item?.id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct JsonStream<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    body: crate::Body,
+    // The response is read in chunks, with `Response::chunk`.
+    #[cfg(target_arch = "wasm32")]
+    body: crate::Response,
+    url: Url,
+    parser: Parser<T>,
+}
+
+/// The values parsed from the bytes of the body received so far.
+struct Parser<T> {
+    buf: BytesMut,
+    state: State,
+    eof: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the first value.
+    Start,
+    /// Right after the opening bracket of the array.
+    ArrayStart,
+    /// After a comma of the array, before an element.
+    Element,
+    /// After an element of the array, before a comma or the closing bracket.
+    ArrayNext,
+    /// In a sequence of values.
+    Values,
+    /// After the closing bracket of the array.
+    ArrayEnd,
+    /// After the last value, or an error.
+    Done,
+}
+
+/// The outcome of parsing the buffered bytes.
+enum Parsed<T> {
+    Value(T),
+    NeedMore,
+    End,
+}
+
+impl<T: DeserializeOwned> JsonStream<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn new(body: crate::Body, url: Url) -> JsonStream<T> {
+        JsonStream {
+            body,
+            url,
+            parser: Parser::new(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn new(body: crate::Response) -> JsonStream<T> {
+        JsonStream {
+            url: body.url().clone(),
+            body,
+            parser: Parser::new(),
+        }
+    }
+
+    /// Returns the next value of the body, or `None` once the values end.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn next(&mut self) -> Option<crate::Result<T>> {
+        loop {
+            if let Some(next) = self.parser.next(&self.url) {
+                return next;
+            }
+
+            match self.body.chunk().await {
+                Ok(Some(data)) => self.parser.buf.extend_from_slice(&data),
+                Ok(None) => self.parser.eof = true,
+                Err(err) => {
+                    self.parser.state = State::Done;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Parser<T> {
+    fn new() -> Parser<T> {
+        Parser {
+            buf: BytesMut::new(),
+            state: State::Start,
+            eof: false,
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the next value or the end of the values, or `None` if more of the body is needed.
+    fn next(&mut self, url: &Url) -> Option<Option<crate::Result<T>>> {
+        match self.parse() {
+            Ok(Parsed::Value(value)) => Some(Some(Ok(value))),
+            Ok(Parsed::NeedMore) => None,
+            Ok(Parsed::End) => {
+                self.state = State::Done;
+                Some(None)
+            }
+            Err(err) => {
+                self.state = State::Done;
+                Some(Some(Err(err.with_url(url.clone()))))
+            }
+        }
+    }
+
+    /// Parses the next value from the buffered bytes.
+    fn parse(&mut self) -> crate::Result<Parsed<T>> {
+        loop {
+            let len = self
+                .buf
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .count();
+            self.buf.advance(len);
+
+            match self.state {
+                State::Start => match self.buf.first() {
+                    Some(b'[') => {
+                        self.buf.advance(1);
+                        self.state = State::ArrayStart;
+                    }
+                    Some(_) => self.state = State::Values,
+                    None => return self.need_more(),
+                },
+                State::ArrayStart if self.buf.first() == Some(&b']') => {
+                    self.buf.advance(1);
+                    self.state = State::ArrayEnd;
+                }
+                State::ArrayStart | State::Element => return self.value(State::ArrayNext),
+                State::ArrayNext => match self.buf.first() {
+                    Some(b',') => {
+                        self.buf.advance(1);
+                        self.state = State::Element;
+                    }
+                    Some(b']') => {
+                        self.buf.advance(1);
+                        self.state = State::ArrayEnd;
+                    }
+                    Some(_) => return Err(Error::decode("expected `,` or `]` in JSON array")),
+                    None => return self.need_more(),
+                },
+                State::Values => return self.value(State::Values),
+                State::ArrayEnd if !self.buf.is_empty() => {
+                    return Err(Error::decode("trailing characters after JSON array"));
+                }
+                State::ArrayEnd => return self.need_more(),
+                State::Done => return Ok(Parsed::End),
+            }
+        }
+    }
+
+    /// Parses a value, moving to `next` once it is complete.
+    fn value(&mut self, next: State) -> crate::Result<Parsed<T>> {
+        if self.buf.is_empty() {
+            return self.need_more();
+        }
+
+        let mut values = serde_json::Deserializer::from_slice(&self.buf).into_iter::<T>();
+        match values.next() {
+            Some(Ok(value)) => {
+                let len = values.byte_offset();
+                // A number ending the buffered bytes may go on in the next chunk.
+                if !self.eof && len == self.buf.len() && self.buf[len - 1].is_ascii_digit() {
+                    return Ok(Parsed::NeedMore);
+                }
+                self.buf.advance(len);
+                self.state = next;
+                Ok(Parsed::Value(value))
+            }
+            Some(Err(err)) if err.is_eof() && !self.eof => Ok(Parsed::NeedMore),
+            Some(Err(err)) => Err(Error::decode(err)),
+            None => self.need_more(),
+        }
+    }
+
+    /// Waits for more of the body, or ends the stream if the body is complete.
+    fn need_more(&self) -> crate::Result<Parsed<T>> {
+        if !self.eof {
+            return Ok(Parsed::NeedMore);
+        }
+        match self.state {
+            State::Start | State::Values | State::ArrayEnd => Ok(Parsed::End),
+            _ => Err(Error::decode("unexpected end of JSON array")),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: DeserializeOwned> Stream for JsonStream<T> {
+    type Item = crate::Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(next) = this.parser.next(&this.url) {
+                return Poll::Ready(next);
+            }
+
+            match ready!(Pin::new(&mut this.body).poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        this.parser.buf.extend_from_slice(&data);
+                    }
+                }
+                Some(Err(err)) => {
+                    this.parser.state = State::Done;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                None => this.parser.eof = true,
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for JsonStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonStream")
+            .field("url", &self.url.as_str())
+            .field("buffered", &self.parser.buf.len())
+            .field("state", &self.parser.state)
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "stream"))]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::Body;
+
+    fn json_stream<T: DeserializeOwned>(chunks: &[&'static str]) -> JsonStream<T> {
+        let stream = futures_util::stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok::<_, std::io::Error>(Bytes::from_static(chunk.as_bytes())))
+                .collect::<Vec<_>>(),
+        );
+        JsonStream::new(
+            Body::wrap_stream(stream),
+            Url::parse("http://example.com").unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn array_elements() {
+        let values =
+            json_stream::<serde_json::Value>(&[" [ {\"a\": ", "1}, 12", "3 ,\"b\"", ", []] "])
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+        assert_eq!(
+            values,
+            [
+                serde_json::json!({"a": 1}),
+                serde_json::json!(123),
+                serde_json::json!("b"),
+                serde_json::json!([])
+            ]
+        );
+
+        let values = json_stream::<u32>(&["[", "]"])
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(values.is_empty());
+    }
+
+    #[tokio::test]
+    async fn value_sequence() {
+        let values = json_stream::<u32>(&["1\n2", "3\n", "4"])
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(values, [1, 23, 4]);
+    }
+
+    #[tokio::test]
+    async fn malformed_bodies() {
+        let mut stream = json_stream::<u32>(&["[1, 2"]);
+        assert_eq!(stream.try_next().await.unwrap(), Some(1));
+        assert_eq!(stream.try_next().await.unwrap(), Some(2));
+        assert!(stream.try_next().await.unwrap_err().is_decode());
+        assert!(stream.try_next().await.unwrap().is_none());
+
+        let mut stream = json_stream::<u32>(&["[1 2]"]);
+        assert_eq!(stream.try_next().await.unwrap(), Some(1));
+        assert!(stream.try_next().await.unwrap_err().is_decode());
+
+        let mut stream = json_stream::<u32>(&["[1] 2"]);
+        assert_eq!(stream.try_next().await.unwrap(), Some(1));
+        assert!(stream.try_next().await.unwrap_err().is_decode());
+    }
+}
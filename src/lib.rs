@@ -225,6 +225,9 @@
 //! * `socks4a://` is the scheme for socks4a proxy
 //! * `socks5://` is the scheme for socks5 proxy
 //! * `socks5h://` is the scheme for socks5h proxy
+//!
+//! SOCKS4 has no password authentication. The username of a `socks4://` or `socks4a://`
+//! proxy URL, e.g. `socks4a://ident@127.0.0.1:1080`, is sent as the ident user ID.
 //!  
 //! ## TLS
 //!
@@ -243,6 +246,13 @@
 //! Custom Certificate Store verification supports Root CA certificates, peer certificates, and
 //! self-signed certificate SSL pinning.
 //!
+//! ## WebAssembly
+//!
+//! On the `wasm32` target, the `Client` sends requests with the Fetch API of the browser. The
+//! [`Client`], [`RequestBuilder`] and [`Response`] types keep the methods that the browser can
+//! honor, so that crates can share the code making requests between both targets. Emulation,
+//! TLS, proxies, connection pooling and the middleware of native clients are not available.
+//!
 //! ## Optional Features
 //!
 //! The following are a list of [Cargo features][cargo-features] that can be
@@ -251,20 +261,32 @@
 //! - **full**: Enables all optional features.
 //! - **websocket**: Provides websocket support.
 //! - **cookies**: Provides cookie session support.
+//! - **psl**: Rejects cookies set for public suffixes using a Public Suffix List.
 //! - **gzip**: Provides response body gzip decompression.
 //! - **brotli**: Provides response body brotli decompression.
 //! - **zstd**: Provides response body zstd decompression.
 //! - **deflate**: Provides response body deflate decompression.
+//! - **zlib-ng**: Decompresses gzip and deflate bodies with the SIMD-accelerated zlib-ng
+//!   instead of miniz_oxide. Building it requires CMake.
+//! - **libdeflate**: Decompresses gzip bodies of known length up to 16 MiB in one shot with
+//!   libdeflate once they are complete, falling back to streaming decompression otherwise.
 //! - **json**: Provides serialization and deserialization for JSON bodies.
+//! - **macros**: Provides the [`request!`] macro, building requests with inline headers and
+//!   JSON body literals.
 //! - **multipart**: Provides functionality for multipart forms.
 //! - **charset** *(enabled by default)*: Improved support for decoding text.
 //! - **stream**: Adds support for `futures::Stream`.
+//! - **downloader**: Provides parallel segmented downloads.
 //! - **socks**: Provides SOCKS5 and SOCKS4 proxy support.
+//! - **blake3**: Adds BLAKE3 to the body checksum algorithms.
+//! - **capi**: Exports a C-compatible API to embed the client from other languages.
 //! - **hickory-dns**: Enables a hickory-dns async resolver instead of default threadpool using
 //!   `getaddrinfo`.
 //! - **webpki-roots** *(enabled by default)*: Use the webpki-roots crate for root certificates.
 //! - **system-proxy** *(enabled by default)*: Enable system proxy support.
 //! - **tracing**: Enable tracing logging support.
+//! - **metrics**: Enable recording metrics through the `metrics` crate.
+//! - **trace-context**: Enable propagating the W3C trace context of OpenTelemetry spans.
 //!
 //! [client]: ./struct.Client.html
 //! [response]: ./struct.Response.html
@@ -278,70 +300,147 @@
 #[macro_use]
 mod trace;
 
+macro_rules! if_wasm {
+    ($($item:item)*) => {$(
+        #[cfg(target_arch = "wasm32")]
+        $item
+    )*}
+}
+
+macro_rules! if_native {
+    ($($item:item)*) => {$(
+        #[cfg(not(target_arch = "wasm32"))]
+        $item
+    )*}
+}
+
 pub use http::{Method, StatusCode, Version, header};
 pub use url::Url;
 
+#[cfg(feature = "json")]
+mod api_error;
 mod error;
 mod into_url;
+#[cfg(feature = "json")]
+mod json_stream;
+#[cfg(feature = "macros")]
+mod macros;
 mod response;
-mod sync;
+mod util;
+
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use self::macros::__private;
+#[cfg(feature = "__bench")]
+#[doc(hidden)]
+pub use self::util::merge_default_headers as __merge_default_headers;
 
+#[cfg(feature = "json")]
+pub use self::{api_error::ApiError, json_stream::JsonStream};
 pub use self::{
     error::{Error, Result},
-    into_url::IntoUrl,
+    into_url::{IntoUrl, UrlPolicy},
     response::ResponseBuilderExt,
 };
 
-fn _assert_impls() {
-    fn assert_send<T: Send>() {}
-    fn assert_sync<T: Sync>() {}
-    fn assert_clone<T: Clone>() {}
+if_native! {
+    mod sync;
 
-    assert_send::<Client>();
-    assert_sync::<Client>();
-    assert_clone::<Client>();
+    fn _assert_impls() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+        fn assert_clone<T: Clone>() {}
 
-    assert_send::<Request>();
-    assert_send::<RequestBuilder>();
-    #[cfg(feature = "websocket")]
-    assert_send::<websocket::WebSocketRequestBuilder>();
+        assert_send::<Client>();
+        assert_sync::<Client>();
+        assert_clone::<Client>();
 
-    assert_send::<Response>();
-    #[cfg(feature = "websocket")]
-    assert_send::<websocket::WebSocketResponse>();
+        assert_send::<Request>();
+        assert_send::<RequestBuilder>();
+        #[cfg(feature = "websocket")]
+        assert_send::<websocket::WebSocketRequestBuilder>();
+
+        assert_send::<Response>();
+        #[cfg(feature = "websocket")]
+        assert_send::<websocket::WebSocketResponse>();
+        #[cfg(feature = "websocket")]
+        assert_send::<websocket::WebSocket>();
+
+        assert_send::<Error>();
+        assert_sync::<Error>();
+    }
+
+    #[cfg(feature = "__bench")]
+    #[doc(hidden)]
+    pub fn __share_default_headers<B>(
+        req: &mut http::Request<B>,
+        defaults: std::sync::Arc<http::HeaderMap>,
+    ) {
+        core::ext::SharedHeaders::push(req, defaults);
+    }
+
+    #[cfg(feature = "cookies")]
+    pub use self::client::Session;
+    #[cfg(feature = "multipart")]
+    pub use self::client::multipart;
     #[cfg(feature = "websocket")]
-    assert_send::<websocket::WebSocket>();
+    pub use self::client::websocket;
+    pub use self::{
+        client::{
+            Body, Brand, Chunk, ChunkedBody, Client, ClientBuilder, ClientHints,
+            ClientHintsBuilder, Conditional, ConfigHandle, DuplicateHeaders, EmulationProvider,
+            EmulationProviderFactory, FetchMode, FetchRanges, GetIfModified, PoolHandle,
+            PreparedRequest, Priority, Request, RequestBuilder, Response, RotationStrategy,
+            TransferStats, Upgraded, UserAgent, Validators,
+        },
+        core::{
+            client::{
+                PoolHealthCheck, PoolStats, PoolWaiterOrder,
+                config::{http1, http2},
+                connect::proxy::TunnelResponse,
+            },
+            ext::{ConnectionInfo, EmptyBody, RequestTarget, VersionPreference},
+            header::OriginalHeaders,
+        },
+        proxy::{NoProxy, Proxy, ProxyAuth, ProxyCredentials, ProxyInfo},
+    };
 
-    assert_send::<Error>();
-    assert_sync::<Error>();
-}
+    pub mod budget;
+    #[cfg(feature = "capi")]
+    pub mod capi;
+    mod client;
+    pub mod config;
+    mod connect;
+    #[cfg(feature = "cookies")]
+    pub mod cookie;
 
-#[cfg(feature = "multipart")]
-pub use self::client::multipart;
-#[cfg(feature = "websocket")]
-pub use self::client::websocket;
-pub use self::{
-    client::{
-        Body, Client, ClientBuilder, EmulationProvider, EmulationProviderFactory, Request,
-        RequestBuilder, Response, Upgraded,
-    },
-    core::{
-        client::config::{http1, http2},
-        header::OriginalHeaders,
-    },
-    proxy::{NoProxy, Proxy},
-};
+    mod core;
+    pub mod decoding;
+    pub mod digest;
+    pub mod dns;
+    #[cfg(feature = "downloader")]
+    pub mod downloader;
+    pub mod events;
+    pub mod idempotency;
+    pub mod logging;
+    #[cfg(feature = "metrics")]
+    mod metrics;
+    mod proxy;
 
-mod client;
-mod connect;
-#[cfg(feature = "cookies")]
-pub mod cookie;
+    pub mod profile;
+    pub mod redirect;
+    pub mod retry;
+    pub mod robots;
+    pub mod rt;
+    pub mod scan;
+    pub mod scheme;
+    pub mod sign;
 
-mod core;
-pub mod dns;
-mod proxy;
+    pub mod tls;
+}
 
-pub mod redirect;
+if_wasm! {
+    mod wasm;
 
-pub mod tls;
-mod util;
+    pub use self::wasm::{Body, Client, ClientBuilder, Request, RequestBuilder, Response};
+}
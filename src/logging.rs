@@ -0,0 +1,190 @@
+//! Structured request logging
+//!
+//! A [`Logging`] configuration installed with
+//! [`ClientBuilder::logging`](crate::ClientBuilder::logging) hands a [`LogRecord`] to a
+//! [`RequestLogger`] for every request sent on the wire, including each redirect hop and retry.
+//! Records are plain data implementing [`Serialize`], so they can be written out as JSON or fed
+//! to any structured logging backend.
+//!
+//! Only the headers selected with [`Logging::header`] are recorded. The values of sensitive
+//! headers, `Authorization`, `Proxy-Authorization`, `Cookie` and `Set-Cookie` by default, are
+//! replaced with `[REDACTED]`, and so is the password of the URL.
+//!
+//! ```
+//! use wreq::{header::CONTENT_TYPE, logging::Logging};
+//!
+//! # fn run() -> wreq::Result<()> {
+//! let client = wreq::Client::builder()
+//!     .logging(
+//!         Logging::new(|record: &wreq::logging::LogRecord| {
+//!             println!("{} {} -> {:?}", record.method, record.url, record.status);
+//!         })
+//!         .header(CONTENT_TYPE),
+//!     )
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{sync::Arc, time::Duration};
+
+use http::{
+    HeaderMap, HeaderName,
+    header::{AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, SET_COOKIE},
+};
+use serde::{Serialize, Serializer};
+
+/// The value recorded in place of a redacted value.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A receiver of [`LogRecord`]s.
+///
+/// It is called synchronously once the response headers are received or the request failed,
+/// so it should return quickly. It is implemented for closures taking a `&LogRecord`.
+pub trait RequestLogger: Send + Sync {
+    /// Called with the record of a finished request.
+    fn log(&self, record: &LogRecord);
+}
+
+impl<F> RequestLogger for F
+where
+    F: Fn(&LogRecord) + Send + Sync,
+{
+    fn log(&self, record: &LogRecord) {
+        self(record)
+    }
+}
+
+/// The record of a request sent on the wire.
+#[derive(Clone, Debug, Serialize)]
+#[non_exhaustive]
+pub struct LogRecord {
+    /// The request method.
+    pub method: String,
+    /// The request URL, with its password redacted.
+    pub url: String,
+    /// The response status, if a response was received.
+    pub status: Option<u16>,
+    /// The time from sending the request to receiving the response headers, or the error.
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+    /// The size of the request body, if known up front.
+    pub request_size: Option<u64>,
+    /// The size of the response body, if known from its headers.
+    pub response_size: Option<u64>,
+    /// The selected request headers, in order.
+    pub request_headers: Vec<(String, String)>,
+    /// The selected response headers, in order.
+    pub response_headers: Vec<(String, String)>,
+    /// The error the request failed with, if any.
+    pub error: Option<String>,
+}
+
+fn serialize_millis<S: Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64() * 1000.0)
+}
+
+/// The configuration of request logging.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone)]
+pub struct Logging {
+    pub(crate) logger: Arc<dyn RequestLogger>,
+    headers: Vec<HeaderName>,
+    redacted: Vec<HeaderName>,
+}
+
+impl Logging {
+    /// Creates a configuration handing records to `logger`.
+    ///
+    /// No header is recorded until selected with [`Logging::header`].
+    pub fn new<L>(logger: L) -> Logging
+    where
+        L: RequestLogger + 'static,
+    {
+        Logging {
+            logger: Arc::new(logger),
+            headers: Vec::new(),
+            redacted: vec![AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE, SET_COOKIE],
+        }
+    }
+
+    /// Records the header `name` of requests and responses.
+    pub fn header(mut self, name: HeaderName) -> Logging {
+        if !self.headers.contains(&name) {
+            self.headers.push(name);
+        }
+        self
+    }
+
+    /// Replaces the value of the header `name` with `[REDACTED]` when it is recorded.
+    pub fn redact(mut self, name: HeaderName) -> Logging {
+        if !self.redacted.contains(&name) {
+            self.redacted.push(name);
+        }
+        self
+    }
+
+    /// Records the value of the header `name` as is, even if it is redacted by default.
+    pub fn unredact(mut self, name: &HeaderName) -> Logging {
+        self.redacted.retain(|redacted| redacted != name);
+        self
+    }
+
+    /// Returns the selected headers of `headers`, falling back to the first of `fallbacks` that
+    /// has the ones that are missing.
+    pub(crate) fn select(
+        &self,
+        headers: &HeaderMap,
+        fallbacks: &[&HeaderMap],
+    ) -> Vec<(String, String)> {
+        let mut selected = Vec::new();
+        for name in &self.headers {
+            let mut values = headers.get_all(name).iter().peekable();
+            for fallback in fallbacks {
+                if values.peek().is_some() {
+                    break;
+                }
+                values = fallback.get_all(name).iter().peekable();
+            }
+
+            let redacted = self.redacted.contains(name);
+            for value in values {
+                let value = if redacted {
+                    REDACTED.to_owned()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                selected.push((name.as_str().to_owned(), value));
+            }
+        }
+        selected
+    }
+}
+
+impl std::fmt::Debug for Logging {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Logging")
+            .field("headers", &self.headers)
+            .field("redacted", &self.redacted)
+            .finish()
+    }
+}
+
+/// Returns `uri` with the password of its user info redacted.
+pub(crate) fn redact_uri(uri: &http::Uri) -> String {
+    let uri = uri.to_string();
+    let Some(start) = uri.find("://").map(|i| i + 3) else {
+        return uri;
+    };
+    let authority_end = uri[start..]
+        .find(['/', '?', '#'])
+        .map_or(uri.len(), |i| start + i);
+    let Some(at) = uri[start..authority_end].rfind('@').map(|i| start + i) else {
+        return uri;
+    };
+    match uri[start..at].find(':').map(|i| start + i) {
+        Some(colon) => format!("{}:{REDACTED}{}", &uri[..colon], &uri[at..]),
+        None => uri,
+    }
+}
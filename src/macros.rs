@@ -0,0 +1,71 @@
+//! The `request!` macro
+
+/// Builds a request with its headers, query and body written inline.
+///
+/// The macro takes a client, a method and a URL, then any of the following options, in any
+/// order, and returns the [`RequestBuilder`](crate::RequestBuilder) they describe:
+///
+/// - `headers: { "name": value, ... }` adds headers, as with
+///   [`RequestBuilder::header`](crate::RequestBuilder::header).
+/// - `query: { "name": value, ... }` appends query pairs, as with
+///   [`RequestBuilder::query`](crate::RequestBuilder::query).
+/// - `json: { ... }` sets a JSON body written as a literal, with the syntax of
+///   [`serde_json::json!`], which can refer to variables in scope.
+/// - `body: value` sets a plain body, as with
+///   [`RequestBuilder::body`](crate::RequestBuilder::body).
+///
+/// # Optional
+///
+/// This requires the optional `macros` feature enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> wreq::Result<()> {
+/// let client = wreq::Client::new();
+/// let token = "secret";
+///
+/// let res = wreq::request!(client, POST "https://example.com/items",
+///     headers: { "authorization": format!("Bearer {token}"), "x-trace": "1" },
+///     query: { "dry_run": true },
+///     json: { "name": "widget", "tags": ["a", "b"], "stock": { "count": 3 } },
+/// )
+/// .send()
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+#[cfg_attr(docsrs, doc(cfg(feature = "macros")))]
+macro_rules! request {
+    (@options $builder:ident $(,)?) => {
+        $builder
+    };
+    (@options $builder:ident, headers: { $($name:literal : $value:expr),* $(,)? }
+        $($rest:tt)*) => {{
+        let builder = $builder$(.header($name, $value))*;
+        $crate::request!(@options builder $($rest)*)
+    }};
+    (@options $builder:ident, query: { $($name:literal : $value:expr),* $(,)? }
+        $($rest:tt)*) => {{
+        let builder = $builder$(.query(&[($name, $value)]))*;
+        $crate::request!(@options builder $($rest)*)
+    }};
+    (@options $builder:ident, json: $json:tt $($rest:tt)*) => {{
+        let builder = $builder.json(&$crate::__private::json!($json));
+        $crate::request!(@options builder $($rest)*)
+    }};
+    (@options $builder:ident, body: $body:expr $(, $($rest:tt)*)?) => {{
+        let builder = $builder.body($body);
+        $crate::request!(@options builder $(, $($rest)*)?)
+    }};
+    ($client:expr, $method:ident $url:expr $(, $($options:tt)*)?) => {{
+        let builder = $client.request($crate::Method::$method, $url);
+        $crate::request!(@options builder $(, $($options)*)?)
+    }};
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::json;
+}
@@ -0,0 +1,111 @@
+//! Instrumentation through the `metrics` facade.
+//!
+//! The emitted metrics are listed on `ClientBuilder::metrics`.
+
+use std::{fmt, sync::Arc, time::Instant};
+
+use http::{Method, StatusCode};
+use metrics::{Gauge, Recorder, counter, gauge, histogram};
+
+use crate::dns::{Name, Resolve, Resolving};
+
+/// Where the metrics of a client are recorded.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    recorder: Option<Arc<dyn Recorder + Send + Sync>>,
+}
+
+impl Metrics {
+    /// Records metrics to the globally installed recorder.
+    pub(crate) fn global() -> Metrics {
+        Metrics { recorder: None }
+    }
+
+    /// Records metrics to the given recorder.
+    pub(crate) fn with_recorder(recorder: Arc<dyn Recorder + Send + Sync>) -> Metrics {
+        Metrics {
+            recorder: Some(recorder),
+        }
+    }
+
+    fn record<T>(&self, f: impl FnOnce() -> T) -> T {
+        match self.recorder {
+            Some(ref recorder) => metrics::with_local_recorder(&**recorder, f),
+            None => f(),
+        }
+    }
+
+    /// Records a completed request.
+    pub(crate) fn request(&self, method: &Method, status: Option<StatusCode>, start: Instant) {
+        let method = method.to_string();
+        let status = status.map_or_else(|| "error".to_owned(), |status| status.as_str().to_owned());
+        let elapsed = start.elapsed().as_secs_f64();
+
+        self.record(|| {
+            counter!("wreq_requests_total", "method" => method.clone(), "status" => status)
+                .increment(1);
+            histogram!("wreq_request_duration_seconds", "method" => method).record(elapsed);
+        });
+    }
+
+    /// Records whether a response was received on a reused connection.
+    pub(crate) fn connection_reused(&self, reused: bool) {
+        let reused = if reused { "true" } else { "false" };
+        self.record(|| counter!("wreq_connection_reuse_total", "reused" => reused).increment(1));
+    }
+
+    /// Records a retried request.
+    pub(crate) fn retry(&self) {
+        self.record(|| counter!("wreq_retries_total").increment(1));
+    }
+
+    /// Records a newly opened connection, until the returned guard is dropped.
+    pub(crate) fn open_connection(&self) -> OpenConnection {
+        let gauge = self.record(|| gauge!("wreq_open_connections"));
+        gauge.increment(1.0);
+        OpenConnection(gauge)
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics")
+            .field("local_recorder", &self.recorder.is_some())
+            .finish()
+    }
+}
+
+/// Counts an open connection in the `wreq_open_connections` gauge while alive.
+pub(crate) struct OpenConnection(Gauge);
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.0.decrement(1.0);
+    }
+}
+
+/// A resolver recording the latency of the lookups of the resolver it wraps.
+pub(crate) struct TimedResolver {
+    inner: Arc<dyn Resolve>,
+    metrics: Metrics,
+}
+
+impl TimedResolver {
+    pub(crate) fn new(inner: Arc<dyn Resolve>, metrics: Metrics) -> TimedResolver {
+        TimedResolver { inner, metrics }
+    }
+}
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let lookup = self.inner.resolve(name);
+        let metrics = self.metrics.clone();
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs = lookup.await;
+            let elapsed = start.elapsed().as_secs_f64();
+            metrics.record(|| histogram!("wreq_dns_lookup_duration_seconds").record(elapsed));
+            addrs
+        })
+    }
+}
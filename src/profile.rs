@@ -0,0 +1,308 @@
+//! Origin profiles
+//!
+//! A [`ProfileStore`] installed with
+//! [`ClientBuilder::origin_profiles`](crate::ClientBuilder::origin_profiles) keeps an
+//! [`OriginProfile`] of the facts learned about each origin the client talks to: the HTTP version
+//! and ALPN protocol it negotiated, its TLS version, its average time to first byte and the
+//! address of the last successful connection to it.
+//!
+//! The client uses the profile of an origin to try the last good address first when it opens a
+//! direct connection to it, if that address is still among the resolved ones. Other facts are
+//! recorded for inspection, and never change what the client offers during negotiation, so
+//! that an emulated fingerprint stays the same.
+//!
+//! Profiles are plain data implementing [`Serialize`] and [`Deserialize`], so a store can be
+//! persisted and loaded again on a later run.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use wreq::profile::MemoryProfileStore;
+//!
+//! # async fn run() -> wreq::Result<()> {
+//! let profiles = Arc::new(MemoryProfileStore::new());
+//! let client = wreq::Client::builder()
+//!     .origin_profiles(profiles.clone())
+//!     .build()?;
+//!
+//! client.get("https://hyper.rs").send().await?;
+//! if let Some(profile) = profiles.get("https://hyper.rs") {
+//!     println!("http2: {:?}, ttfb: {:?}", profile.supports_http2(), profile.average_ttfb());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use http::{Response, Version};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::client::connect::HttpInfo,
+    sync::Mutex,
+    tls::{TlsInfo, TlsVersion},
+};
+
+/// The weight of a new sample in the moving average of the time to first byte.
+const TTFB_WEIGHT: f64 = 0.125;
+
+/// The facts learned about an origin.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OriginProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alpn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tls_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ttfb_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_good_addr: Option<SocketAddr>,
+}
+
+impl OriginProfile {
+    /// Returns the HTTP version of the last response from the origin.
+    pub fn http_version(&self) -> Option<Version> {
+        match self.http_version.as_deref()? {
+            "HTTP/0.9" => Some(Version::HTTP_09),
+            "HTTP/1.0" => Some(Version::HTTP_10),
+            "HTTP/1.1" => Some(Version::HTTP_11),
+            "HTTP/2.0" => Some(Version::HTTP_2),
+            "HTTP/3.0" => Some(Version::HTTP_3),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the last response from the origin was received over HTTP/2.
+    pub fn supports_http2(&self) -> Option<bool> {
+        self.http_version()
+            .map(|version| version == Version::HTTP_2)
+    }
+
+    /// Returns the ALPN protocol negotiated with the origin, such as `h2`.
+    pub fn alpn_protocol(&self) -> Option<&str> {
+        self.alpn.as_deref()
+    }
+
+    /// Returns the TLS version negotiated with the origin.
+    ///
+    /// It is only known for clients with [`ClientBuilder::tls_info`](crate::ClientBuilder::tls_info)
+    /// enabled.
+    pub fn tls_version(&self) -> Option<TlsVersion> {
+        self.tls_version.as_deref().and_then(TlsVersion::from_name)
+    }
+
+    /// Returns a moving average of the time from sending a request to the origin to receiving
+    /// the response headers.
+    pub fn average_ttfb(&self) -> Option<Duration> {
+        self.ttfb_ms
+            .filter(|ms| ms.is_finite() && *ms >= 0.0)
+            .map(|ms| Duration::from_micros((ms * 1000.0).round() as u64))
+    }
+
+    /// Returns the address of the last direct connection a response was received on.
+    pub fn last_good_addr(&self) -> Option<SocketAddr> {
+        self.last_good_addr
+    }
+
+    /// Records a response received from the origin.
+    pub(crate) fn record(
+        &mut self,
+        version: Version,
+        alpn: Option<&[u8]>,
+        tls_version: Option<TlsVersion>,
+        ttfb: Duration,
+        addr: Option<SocketAddr>,
+    ) {
+        self.http_version = Some(format!("{version:?}"));
+        if let Some(alpn) = alpn {
+            self.alpn = Some(String::from_utf8_lossy(alpn).into_owned());
+        } else if version == Version::HTTP_2 {
+            self.alpn = Some("h2".to_owned());
+        }
+        if let Some(name) = tls_version.and_then(|version| version.name()) {
+            self.tls_version = Some(name.to_owned());
+        }
+
+        let ms = ttfb.as_secs_f64() * 1000.0;
+        self.ttfb_ms = Some(match self.ttfb_ms {
+            Some(average) => average + TTFB_WEIGHT * (ms - average),
+            None => ms,
+        });
+
+        if addr.is_some() {
+            self.last_good_addr = addr;
+        }
+    }
+}
+
+/// A store of [`OriginProfile`]s, keyed by the ASCII serialization of their origin, such as
+/// `https://example.com`.
+///
+/// The client loads the profile of an origin before each request to it, and stores it back
+/// with the facts learned from the response.
+pub trait ProfileStore: Send + Sync {
+    /// Returns the profile of `origin`, if any.
+    fn load(&self, origin: &str) -> Option<OriginProfile>;
+
+    /// Stores the profile of `origin`.
+    fn store(&self, origin: &str, profile: OriginProfile);
+}
+
+/// A [`ProfileStore`] keeping profiles in memory.
+#[derive(Debug)]
+pub struct MemoryProfileStore {
+    profiles: Mutex<HashMap<String, OriginProfile>>,
+}
+
+impl MemoryProfileStore {
+    /// Creates an empty store.
+    pub fn new() -> MemoryProfileStore {
+        MemoryProfileStore::from_profiles(HashMap::new())
+    }
+
+    /// Creates a store with the given profiles, such as those saved on a previous run.
+    pub fn from_profiles(profiles: HashMap<String, OriginProfile>) -> MemoryProfileStore {
+        MemoryProfileStore {
+            profiles: Mutex::new(profiles),
+        }
+    }
+
+    /// Returns the profile of `origin`, if any.
+    pub fn get(&self, origin: &str) -> Option<OriginProfile> {
+        self.profiles.lock().get(origin).cloned()
+    }
+
+    /// Returns a copy of all the profiles, to inspect or persist them.
+    pub fn profiles(&self) -> HashMap<String, OriginProfile> {
+        self.profiles.lock().clone()
+    }
+
+    /// Removes all the profiles.
+    pub fn clear(&self) {
+        self.profiles.lock().clear();
+    }
+}
+
+impl Default for MemoryProfileStore {
+    fn default() -> MemoryProfileStore {
+        MemoryProfileStore::new()
+    }
+}
+
+impl ProfileStore for MemoryProfileStore {
+    fn load(&self, origin: &str) -> Option<OriginProfile> {
+        self.get(origin)
+    }
+
+    fn store(&self, origin: &str, profile: OriginProfile) {
+        self.profiles.lock().insert(origin.to_owned(), profile);
+    }
+}
+
+/// A request being sent to an origin with a profile store.
+pub(crate) struct ProfileProbe {
+    store: Arc<dyn ProfileStore>,
+    origin: String,
+    profile: OriginProfile,
+    direct: bool,
+    start: Instant,
+}
+
+impl ProfileProbe {
+    /// Starts probing `origin`, `direct` telling whether the request bypasses proxies.
+    pub(crate) fn start(
+        store: Arc<dyn ProfileStore>,
+        origin: String,
+        direct: bool,
+    ) -> ProfileProbe {
+        let profile = store.load(&origin).unwrap_or_default();
+        ProfileProbe {
+            store,
+            origin,
+            profile,
+            direct,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the address to try first when connecting to the origin.
+    pub(crate) fn preferred_addr(&self) -> Option<SocketAddr> {
+        self.profile.last_good_addr.filter(|_| self.direct)
+    }
+
+    /// Records the response headers received from the origin in its stored profile.
+    pub(crate) fn finish<B>(mut self, res: &Response<B>) {
+        let tls = res.extensions().get::<TlsInfo>();
+        // The address of a proxied connection is the proxy's, not the origin's.
+        let addr = res
+            .extensions()
+            .get::<HttpInfo>()
+            .map(HttpInfo::remote_addr)
+            .filter(|_| self.direct);
+
+        self.profile.record(
+            res.version(),
+            tls.and_then(TlsInfo::alpn_protocol),
+            tls.and_then(TlsInfo::version),
+            self.start.elapsed(),
+            addr,
+        );
+        self.store.store(&self.origin, self.profile);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::Version;
+
+    use super::OriginProfile;
+    use crate::tls::TlsVersion;
+
+    #[test]
+    fn origin_profile_records_responses() {
+        let addr = "192.0.2.1:443".parse().unwrap();
+        let mut profile = OriginProfile::default();
+        profile.record(
+            Version::HTTP_2,
+            None,
+            Some(TlsVersion::TLS_1_3),
+            Duration::from_millis(80),
+            Some(addr),
+        );
+        profile.record(
+            Version::HTTP_2,
+            Some(&b"h2"[..]),
+            None,
+            Duration::from_millis(160),
+            None,
+        );
+
+        assert_eq!(profile.supports_http2(), Some(true));
+        assert_eq!(profile.alpn_protocol(), Some("h2"));
+        assert_eq!(profile.tls_version(), Some(TlsVersion::TLS_1_3));
+        assert_eq!(profile.average_ttfb(), Some(Duration::from_millis(90)));
+        assert_eq!(profile.last_good_addr(), Some(addr));
+
+        #[cfg(feature = "json")]
+        {
+            let json = serde_json::to_string(&profile).unwrap();
+            assert_eq!(
+                serde_json::from_str::<OriginProfile>(&json).unwrap(),
+                profile
+            );
+        }
+    }
+}
@@ -1,4 +1,10 @@
-use std::{error::Error as StdError, fmt};
+use std::{
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    hash::{DefaultHasher, Hash, Hasher},
+    pin::Pin,
+};
 
 #[cfg(feature = "socks")]
 use bytes::Bytes;
@@ -7,7 +13,7 @@ use http::{HeaderMap, Uri, header::HeaderValue, uri::Scheme};
 use crate::{
     Url,
     core::proxy::matcher,
-    error::{BadScheme, Error},
+    error::{BadScheme, BoxError, Error},
     into_url::{IntoUrl, IntoUrlSealed},
 };
 
@@ -71,6 +77,7 @@ pub struct NoProxy {
 struct Extra {
     auth: Option<HeaderValue>,
     misc: Option<HeaderMap>,
+    tunnel: Option<HeaderMap>,
 }
 
 impl std::hash::Hash for Extra {
@@ -82,16 +89,19 @@ impl std::hash::Hash for Extra {
             state.write_u8(0);
         }
 
-        // Hash the misc headers by name and value bytes, in sorted order for determinism
-        if let Some(ref misc) = self.misc {
-            let mut items: Vec<_> = misc.iter().collect();
-            items.sort_by_key(|(k, _)| k.as_str());
-            for (k, v) in items {
-                state.write(k.as_str().as_bytes());
-                state.write(v.as_bytes());
+        // Hash the misc and tunnel headers by name and value bytes, in sorted order for
+        // determinism
+        for headers in [&self.misc, &self.tunnel] {
+            if let Some(headers) = headers {
+                let mut items: Vec<_> = headers.iter().collect();
+                items.sort_by_key(|(k, _)| k.as_str());
+                for (k, v) in items {
+                    state.write(k.as_str().as_bytes());
+                    state.write(v.as_bytes());
+                }
+            } else {
+                state.write_u8(0);
             }
-        } else {
-            state.write_u8(0);
         }
     }
 }
@@ -104,6 +114,7 @@ pub(crate) struct Matcher {
     extra: Extra,
     maybe_has_http_auth: bool,
     maybe_has_http_custom_headers: bool,
+    system: bool,
 }
 
 /// Our own type, wrapping an `Intercept`, since we may have a few additional
@@ -116,6 +127,122 @@ pub(crate) struct Intercepted {
     extra: Extra,
 }
 
+/// The proxy a response was received through.
+///
+/// It is available in the [`Response`](crate::Response) extensions when the connection was
+/// established through a proxy.
+///
+/// # Example
+///
+/// ```rust
+/// # async fn run() -> wreq::Result<()> {
+/// let res = wreq::get("https://hyper.rs").send().await?;
+/// if let Some(proxy) = res.extensions().get::<wreq::ProxyInfo>() {
+///     println!("served through {}", proxy.uri());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProxyInfo {
+    uri: Uri,
+}
+
+impl ProxyInfo {
+    pub(crate) fn new(uri: Uri) -> ProxyInfo {
+        ProxyInfo { uri }
+    }
+
+    /// Returns the URI of the proxy, without its credentials.
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+}
+
+/// A provider of new credentials for a proxy that rejected its current ones.
+///
+/// Set with [`ClientBuilder::proxy_credentials`](crate::ClientBuilder::proxy_credentials), it
+/// is called when a proxy answers `407 Proxy Authentication Required`, for instance once the
+/// credentials of a session have expired. The new credentials replace the previous ones of the
+/// proxy for the requests sent afterwards, and the rejected request is sent again once, through
+/// a new tunnel.
+///
+/// Implemented for async functions and closures taking the URI of the proxy.
+///
+/// # Example
+///
+/// ```
+/// use http::Uri;
+/// use wreq::{Proxy, ProxyAuth};
+///
+/// async fn renew(
+///     proxy: Uri,
+/// ) -> Result<Option<ProxyAuth>, Box<dyn std::error::Error + Send + Sync>> {
+///     // ... fetch a new password for `proxy` from a vault.
+///     # let _ = proxy;
+///     Ok(Some(ProxyAuth::basic("user", "new password")))
+/// }
+///
+/// # fn run() -> wreq::Result<()> {
+/// let client = wreq::Client::builder()
+///     .proxy(Proxy::all("http://proxy.example:8080")?.basic_auth("user", "password"))
+///     .proxy_credentials(renew)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait ProxyCredentials: Send + Sync {
+    /// Returns the new credentials of `proxy`, or `None` to fail the request with the rejection
+    /// of the proxy.
+    ///
+    /// An error also fails the request with the rejection of the proxy.
+    fn refresh(
+        &self,
+        proxy: Uri,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<ProxyAuth>, BoxError>> + Send>>;
+}
+
+impl<F, Fut> ProxyCredentials for F
+where
+    F: Fn(Uri) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<ProxyAuth>, BoxError>> + Send + 'static,
+{
+    fn refresh(
+        &self,
+        proxy: Uri,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<ProxyAuth>, BoxError>> + Send>> {
+        Box::pin(self(proxy))
+    }
+}
+
+/// The credentials of a proxy, returned by a [`ProxyCredentials`] provider.
+#[derive(Clone)]
+pub struct ProxyAuth(HeaderValue);
+
+impl ProxyAuth {
+    /// Creates credentials sent with Basic auth, as with [`Proxy::basic_auth`].
+    pub fn basic(username: &str, password: &str) -> ProxyAuth {
+        ProxyAuth(encode_basic_auth(username, password))
+    }
+
+    /// Creates credentials sent as the given `Proxy-Authorization` value, as with
+    /// [`Proxy::custom_http_auth`].
+    pub fn header(mut value: HeaderValue) -> ProxyAuth {
+        value.set_sensitive(true);
+        ProxyAuth(value)
+    }
+
+    pub(crate) fn into_header(self) -> HeaderValue {
+        self.0
+    }
+}
+
+impl fmt::Debug for ProxyAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProxyAuth(..)")
+    }
+}
+
 /// Trait used for converting into a proxy scheme. This trait supports
 /// parsing from a URL-like type, whilst also supporting proxy schemes
 /// built directly using the factory methods.
@@ -230,6 +357,7 @@ impl Proxy {
             extra: Extra {
                 auth: None,
                 misc: None,
+                tunnel: None,
             },
             intercept,
             no_proxy: None,
@@ -306,6 +434,28 @@ impl Proxy {
         self
     }
 
+    /// Adds headers only sent on the `CONNECT` request opening a tunnel through this Proxy.
+    ///
+    /// These are merged with the [`custom_http_headers`](Proxy::custom_http_headers), taking
+    /// precedence over them, and are not sent on requests forwarded to an HTTP proxy.
+    ///
+    /// # Example
+    /// ```
+    /// # extern crate wreq;
+    /// # use wreq::header::*;
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("x-proxy-session", "1234".parse().unwrap());
+    /// let proxy = wreq::Proxy::https("http://localhost:1234")?.tunnel_headers(headers);
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub fn tunnel_headers(mut self, headers: HeaderMap) -> Proxy {
+        self.extra.tunnel = Some(headers);
+        self
+    }
+
     /// Adds a `No Proxy` exclusion list to this Proxy
     ///
     /// # Example
@@ -352,6 +502,7 @@ impl Proxy {
             extra,
             maybe_has_http_auth,
             maybe_has_http_custom_headers,
+            system: false,
         }
     }
 }
@@ -395,10 +546,15 @@ impl NoProxy {
     /// * Entries are expected to be comma-separated (whitespace between entries is ignored)
     /// * IP addresses (both IPv4 and IPv6) are allowed, as are optional subnet masks (by adding
     ///   /size, for example "`192.168.1.0/24`").
-    /// * An entry "`*`" matches all hostnames (this is the only wildcard allowed)
+    /// * An entry "`*`" matches all hostnames
+    /// * An entry starting with "`*.`" is a wildcard domain, for example `*.internal` is equivalent
+    ///   to `.internal`
     /// * Any other entry is considered a domain name (and may contain a leading dot, for example
     ///   `google.com` and `.google.com` are equivalent) and would match both that domain AND all
     ///   subdomains.
+    /// * Any entry may end with a port, for example `google.com:8080`, `10.0.0.0/8:443` or
+    ///   `[::1]:8080`, to only match destinations on that port. Without one, an entry matches
+    ///   all ports.
     ///
     /// For example, if `"NO_PROXY=google.com, 192.168.1.0/24"` was set, all the following would
     /// match (and therefore would bypass the proxy):
@@ -421,13 +577,57 @@ impl Matcher {
             extra: Extra {
                 auth: None,
                 misc: None,
+                tunnel: None,
             },
             // maybe env vars have auth!
             maybe_has_http_auth: true,
             maybe_has_http_custom_headers: true,
+            system: true,
         }
     }
 
+    /// Return whether this matcher was read from the system settings.
+    pub(crate) fn is_system(&self) -> bool {
+        self.system
+    }
+
+    /// Selects the proxy of `proxies` a request to `dst` is sent through.
+    ///
+    /// Without a session, the first matching proxy is used. Otherwise the matching proxies
+    /// are ranked by rendezvous hashing of the session, so a session keeps the same proxy as
+    /// long as it matches, and changing the list only moves the sessions of removed proxies.
+    pub(crate) fn select<'a>(
+        proxies: &'a [Matcher],
+        dst: &Uri,
+        session: Option<u64>,
+    ) -> Option<&'a Matcher> {
+        let mut matching = proxies
+            .iter()
+            .filter_map(|proxy| proxy.intercept(dst).map(|intercepted| (proxy, intercepted)));
+
+        let Some(session) = session else {
+            return matching.next().map(|(proxy, _)| proxy);
+        };
+
+        matching
+            .max_by_key(|(_, intercepted)| {
+                let mut hasher = DefaultHasher::new();
+                session.hash(&mut hasher);
+                intercepted.uri().hash(&mut hasher);
+                hasher.finish()
+            })
+            .map(|(proxy, _)| proxy)
+    }
+
+    /// Returns this matcher sending `auth` as the credentials of its proxy.
+    pub(crate) fn with_auth(&self, auth: HeaderValue) -> Matcher {
+        let mut matcher = self.clone();
+        matcher.extra.auth = Some(auth);
+        // A hint, which can't be wrong for the proxies of other schemes.
+        matcher.maybe_has_http_auth = true;
+        matcher
+    }
+
     pub(crate) fn intercept(&self, dst: &Uri) -> Option<Intercepted> {
         self.inner.intercept(dst).map(|inner| Intercepted {
             inner,
@@ -498,6 +698,10 @@ impl Intercepted {
         None
     }
 
+    pub(crate) fn tunnel_headers(&self) -> Option<&HeaderMap> {
+        self.extra.tunnel.as_ref()
+    }
+
     #[cfg(feature = "socks")]
     pub(crate) fn raw_auth(&self) -> Option<(Bytes, Bytes)> {
         self.inner.raw_auth()
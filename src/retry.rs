@@ -0,0 +1,52 @@
+//! Retry Handling
+//!
+//! By default, a `Client` retries a request up to 2 times when an HTTP/2 server reports that
+//! the request was not processed, either by gracefully shutting down the connection or by
+//! refusing the stream. Such requests are always safe to send again, whatever their method.
+//!
+//! Requests with an idempotent method, such as `GET` or `PUT`, or with an `Idempotency-Key`
+//! header (see the [`idempotency`](crate::idempotency) module) are also retried when the
+//! server shuts down the connection with `INTERNAL_ERROR` or `ENHANCE_YOUR_CALM`, or resets
+//! the stream with `NO_ERROR`, `CANCEL` or `INTERNAL_ERROR`, including while the request body
+//! is being sent. A connection shut down is never reused, so the request is sent again on a
+//! new one.
+//!
+//! When retries are exhausted, [`Error::http2_reason`](crate::Error::http2_reason) returns the
+//! error code of the last attempt, and [`Error::attempt`](crate::Error::attempt) its number.
+//!
+//! A `retry::Policy` can be set on the `ClientBuilder` for all requests, or on a
+//! `RequestBuilder` for a single request, e.g. to never retry a one-shot `POST` sent by a
+//! client that otherwise retries its bulk `GET` requests.
+
+/// A type that controls how many times a request is retried.
+///
+/// - `limited` can be used to set the maximum number of retries of a request.
+/// - `none` can be used to disable all retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    max_retries: usize,
+}
+
+impl Policy {
+    /// Create a `Policy` with a maximum number of retries.
+    pub const fn limited(max: usize) -> Self {
+        Self { max_retries: max }
+    }
+
+    /// Create a `Policy` that does not retry any request.
+    pub const fn none() -> Self {
+        Self::limited(0)
+    }
+
+    /// Returns the maximum number of retries of a request.
+    #[inline]
+    pub const fn max_retries(&self) -> usize {
+        self.max_retries
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy::limited(2)
+    }
+}
@@ -0,0 +1,318 @@
+//! robots.txt support
+//!
+//! A request sent with [`RequestBuilder::respect_robots`](crate::RequestBuilder::respect_robots)
+//! is checked against the [RFC 9309] robots.txt of its origin, and of the origin of every
+//! redirect it follows, before it is sent. A disallowed request fails with a
+//! [`RobotsDisallowed`] error, see [`Error::is_robots_disallowed`](crate::Error::is_robots_disallowed),
+//! so that a crawler can skip the URL.
+//!
+//! robots.txt files are fetched with the configuration of the request that needed them and
+//! cached per origin by the client, for 24 hours by default, see
+//! [`ClientBuilder::robots_cache_ttl`](crate::ClientBuilder::robots_cache_ttl). As specified,
+//! a missing robots.txt (a `4xx` status) allows everything, and an unreachable one (a `5xx`
+//! status) disallows everything.
+//!
+//! ```
+//! # async fn run() -> wreq::Result<()> {
+//! let client = wreq::Client::new();
+//!
+//! match client
+//!     .get("https://example.com/private/page")
+//!     .respect_robots("MyCrawler/1.0")
+//!     .send()
+//!     .await
+//! {
+//!     Ok(res) => println!("status: {}", res.status()),
+//!     Err(err) if err.is_robots_disallowed() => println!("skipped"),
+//!     Err(err) => return Err(err),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [RFC 9309]: https://www.rfc-editor.org/rfc/rfc9309
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::sync::Mutex;
+
+/// A parsed robots.txt file.
+#[derive(Clone, Debug, Default)]
+pub struct Robots {
+    groups: Vec<Group>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl Robots {
+    /// Parses the content of a robots.txt file.
+    ///
+    /// Unknown and malformed lines are ignored.
+    pub fn parse(content: &str) -> Robots {
+        let mut groups: Vec<Group> = Vec::new();
+        // Whether the current group has rules, so that a `user-agent` line starts a new one.
+        let mut in_rules = true;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        groups.push(Group::default());
+                        in_rules = false;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty `disallow` value disallows nothing.
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_owned(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    let delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+                    if let (Some(group), Some(delay)) = (groups.last_mut(), delay) {
+                        group.crawl_delay = Some(delay);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Robots { groups }
+    }
+
+    /// A robots.txt allowing everything.
+    pub(crate) fn allow_all() -> Robots {
+        Robots::default()
+    }
+
+    /// A robots.txt disallowing everything.
+    pub(crate) fn disallow_all() -> Robots {
+        Robots::parse("user-agent: *\ndisallow: /")
+    }
+
+    /// Returns whether `agent` may fetch `path`, a URL path with its optional query.
+    ///
+    /// The rules of the groups naming the product token of `agent`, such as `MyCrawler` for
+    /// `MyCrawler/1.0`, apply, or those of the `*` group if there are none. The longest
+    /// matching rule wins, `allow` rules winning ties.
+    pub fn is_allowed(&self, agent: &str, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        let mut verdict: Option<&Rule> = None;
+        for rule in self.groups_for(agent).flat_map(|group| &group.rules) {
+            if !matches(&rule.pattern, path) {
+                continue;
+            }
+            let better = match verdict {
+                Some(best) => {
+                    rule.pattern.len() > best.pattern.len()
+                        || (rule.pattern.len() == best.pattern.len() && rule.allow)
+                }
+                None => true,
+            };
+            if better {
+                verdict = Some(rule);
+            }
+        }
+        verdict.is_none_or(|rule| rule.allow)
+    }
+
+    /// Returns the `crawl-delay` requested for `agent`, if any.
+    pub fn crawl_delay(&self, agent: &str) -> Option<Duration> {
+        self.groups_for(agent).find_map(|group| group.crawl_delay)
+    }
+
+    fn groups_for<'a>(&'a self, agent: &str) -> impl Iterator<Item = &'a Group> {
+        let token = agent
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let named = self
+            .groups
+            .iter()
+            .any(|group| group.agents.contains(&token));
+        let token = if named { token } else { "*".to_owned() };
+        self.groups
+            .iter()
+            .filter(move |group| group.agents.contains(&token))
+    }
+}
+
+/// Returns whether `path` matches `pattern`, which may contain `*` wildcards and end with a
+/// `$` anchor.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    if parts.peek().is_none() {
+        return !anchored || rest.is_empty();
+    }
+
+    while let Some(part) = parts.next() {
+        if anchored && parts.peek().is_none() {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The error returned when a request is disallowed by the robots.txt of its origin.
+///
+/// See [`Error::is_robots_disallowed`](crate::Error::is_robots_disallowed).
+#[derive(Clone, Debug)]
+pub struct RobotsDisallowed {
+    agent: String,
+}
+
+impl RobotsDisallowed {
+    pub(crate) fn new(agent: String) -> RobotsDisallowed {
+        RobotsDisallowed { agent }
+    }
+
+    /// Returns the user agent the request was checked for.
+    pub fn agent(&self) -> &str {
+        &self.agent
+    }
+}
+
+impl fmt::Display for RobotsDisallowed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "disallowed by robots.txt for user agent {:?}",
+            self.agent
+        )
+    }
+}
+
+impl std::error::Error for RobotsDisallowed {}
+
+/// The robots.txt files of a client, per origin.
+pub(crate) struct RobotsCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Arc<Robots>, Instant)>>,
+}
+
+impl RobotsCache {
+    pub(crate) fn new(ttl: Duration) -> RobotsCache {
+        RobotsCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the robots.txt of `origin`, unless it is missing or expired.
+    pub(crate) fn get(&self, origin: &str) -> Option<Arc<Robots>> {
+        let mut entries = self.entries.lock();
+        match entries.get(origin) {
+            Some((robots, fetched)) if fetched.elapsed() < self.ttl => Some(robots.clone()),
+            Some(_) => {
+                entries.remove(origin);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, origin: String, robots: Arc<Robots>) {
+        self.entries.lock().insert(origin, (robots, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Robots, matches};
+
+    #[test]
+    fn robots_patterns() {
+        assert!(matches("/", "/anything"));
+        assert!(matches("/fish", "/fish.html"));
+        assert!(!matches("/fish", "/Fish"));
+        assert!(matches("/*.php", "/folder/index.php?x=1"));
+        assert!(matches("/*.php$", "/index.php"));
+        assert!(!matches("/*.php$", "/index.php?x=1"));
+        assert!(matches("/fish*$", "/fish/salmon"));
+        assert!(matches("/a$", "/a"));
+        assert!(!matches("/a$", "/ab"));
+    }
+
+    #[test]
+    fn robots_groups_and_precedence() {
+        let robots = Robots::parse(
+            "# comment\n\
+             User-agent: *\n\
+             Disallow: /private\n\
+             Allow: /private/public\n\
+             Crawl-delay: 2.5\n\
+             \n\
+             User-agent: MyCrawler\n\
+             User-agent: other\n\
+             Disallow: /\n\
+             Allow: /$\n\
+             Disallow:\n",
+        );
+
+        assert!(robots.is_allowed("AnyBot", "/"));
+        assert!(!robots.is_allowed("AnyBot", "/private/page"));
+        assert!(robots.is_allowed("AnyBot", "/private/public/page"));
+        assert_eq!(
+            robots.crawl_delay("AnyBot"),
+            Some(std::time::Duration::from_millis(2500))
+        );
+
+        assert!(robots.is_allowed("mycrawler/1.0", "/"));
+        assert!(!robots.is_allowed("MyCrawler/1.0", "/page"));
+        assert!(robots.is_allowed("MyCrawler/1.0", "/robots.txt"));
+        assert_eq!(robots.crawl_delay("MyCrawler"), None);
+
+        assert!(Robots::allow_all().is_allowed("AnyBot", "/x"));
+        assert!(!Robots::disallow_all().is_allowed("AnyBot", "/x"));
+    }
+}
@@ -0,0 +1,13 @@
+//! Runtime timers and clocks
+//!
+//! A client times its timeouts and pooled connections with a [`Timer`] and a [`Clock`],
+//! which can be replaced through [`ClientBuilder::timer`] and [`ClientBuilder::clock`], e.g.
+//! with a [`TokioClock`] to test time-dependent logic under `tokio::time::pause`.
+//!
+//! [`ClientBuilder::timer`]: crate::ClientBuilder::timer
+//! [`ClientBuilder::clock`]: crate::ClientBuilder::clock
+
+pub use crate::core::rt::{
+    Clock, Sleep, SystemClock, Timer,
+    tokio::{TokioClock, TokioTimer},
+};
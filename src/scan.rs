@@ -0,0 +1,209 @@
+//! Inline scanning of response bodies
+//!
+//! A [`BodyScanner`] set with [`ClientBuilder::body_scanner`](crate::ClientBuilder::body_scanner)
+//! sees the chunks of every response body as they are read, after they are decompressed, so that
+//! content can be inspected by an anti-virus engine or an ICAP server without buffering the
+//! whole body first. A scan rejecting the content fails the reading of the body with a
+//! [`ScanRejected`] error, see [`Error::scan_rejected`](crate::Error::scan_rejected).
+//!
+//! ```
+//! use wreq::{
+//!     StatusCode, Url,
+//!     header::HeaderMap,
+//!     scan::{BodyScan, ScanRejected},
+//! };
+//!
+//! /// Rejects the bodies containing the EICAR test signature.
+//! #[derive(Default)]
+//! struct Eicar(Vec<u8>);
+//!
+//! impl BodyScan for Eicar {
+//!     fn chunk(&mut self, chunk: &[u8]) -> Result<(), ScanRejected> {
+//!         self.0.extend_from_slice(chunk);
+//!         if self.0.windows(16).any(|w| w == b"EICAR-STANDARD-A") {
+//!             return Err(ScanRejected::new("EICAR test signature"));
+//!         }
+//!         // Keep the end of the chunk, in case the signature spans two chunks.
+//!         let keep = self.0.len().saturating_sub(15);
+//!         self.0.drain(..keep);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! /// Scans the bodies of all the responses.
+//! fn scan(_url: &Url, _status: StatusCode, _headers: &HeaderMap) -> Option<Box<dyn BodyScan>> {
+//!     Some(Box::new(Eicar::default()))
+//! }
+//!
+//! let client = wreq::Client::builder().body_scanner(scan).build()?;
+//! # Ok::<(), wreq::Error>(())
+//! ```
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use url::Url;
+
+use crate::{Error, error::BoxError};
+
+/// A scanner of response bodies, starting a [`BodyScan`] for each response to scan.
+///
+/// Implemented for functions and closures taking the URL, status and headers of the response.
+pub trait BodyScanner: Send + Sync {
+    /// Starts scanning the body of a response, or returns `None` to let it through unscanned.
+    fn scan(&self, url: &Url, status: StatusCode, headers: &HeaderMap)
+    -> Option<Box<dyn BodyScan>>;
+}
+
+impl<F> BodyScanner for F
+where
+    F: Fn(&Url, StatusCode, &HeaderMap) -> Option<Box<dyn BodyScan>> + Send + Sync,
+{
+    fn scan(
+        &self,
+        url: &Url,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Box<dyn BodyScan>> {
+        self(url, status, headers)
+    }
+}
+
+/// The scan of a response body.
+///
+/// A chunk is only yielded to the reader of the body once the scan has accepted it.
+pub trait BodyScan: Send + Sync {
+    /// Scans a decoded chunk of the body.
+    fn chunk(&mut self, chunk: &[u8]) -> Result<(), ScanRejected>;
+
+    /// Finishes the scan at the end of the body.
+    ///
+    /// The body only ends once the scan has accepted it, so that a verdict on the whole content
+    /// can still fail it.
+    fn finish(&mut self) -> Result<(), ScanRejected> {
+        Ok(())
+    }
+}
+
+/// The error failing a response body rejected by its scan.
+///
+/// It is the source of a [body error](crate::Error::is_body).
+#[derive(Debug)]
+pub struct ScanRejected {
+    reason: String,
+}
+
+impl ScanRejected {
+    /// Creates a rejection for `reason`, such as the name of the threat found.
+    pub fn new<R: Into<String>>(reason: R) -> ScanRejected {
+        ScanRejected {
+            reason: reason.into(),
+        }
+    }
+
+    /// Returns the reason of the rejection.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for ScanRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "response body rejected by scan: {}", self.reason)
+    }
+}
+
+impl std::error::Error for ScanRejected {}
+
+pin_project! {
+    /// A body passing its data through a scan before yielding it.
+    pub(crate) struct ScannedBody<B> {
+        #[pin]
+        inner: B,
+        state: State,
+    }
+}
+
+enum State {
+    Scanning(Box<dyn BodyScan>),
+    /// The scan has finished, or the inner body failed.
+    Done,
+    /// The scan has rejected the body, which yields nothing more.
+    Rejected,
+}
+
+impl<B> ScannedBody<B> {
+    pub(crate) fn new(inner: B, scan: Box<dyn BodyScan>) -> ScannedBody<B> {
+        ScannedBody {
+            inner,
+            state: State::Scanning(scan),
+        }
+    }
+}
+
+impl<B> Body for ScannedBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let scan = match this.state {
+            State::Scanning(scan) => scan,
+            State::Done => return this.inner.poll_frame(cx).map_err(Into::into),
+            State::Rejected => return Poll::Ready(None),
+        };
+
+        let verdict = match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => match frame.data_ref() {
+                Some(data) => scan.chunk(data).map(|()| Some(frame)),
+                None => Ok(Some(frame)),
+            },
+            Some(Err(err)) => {
+                *this.state = State::Done;
+                return Poll::Ready(Some(Err(err.into())));
+            }
+            None => {
+                let verdict = scan.finish().map(|()| None);
+                *this.state = State::Done;
+                verdict
+            }
+        };
+
+        match verdict {
+            Ok(frame) => Poll::Ready(frame.map(Ok)),
+            Err(rejected) => {
+                *this.state = State::Rejected;
+                Poll::Ready(Some(Err(Error::body(rejected).into())))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        match self.state {
+            // The end of the body must be polled for the scan to be finished.
+            State::Scanning(_) => false,
+            State::Done => self.inner.is_end_stream(),
+            State::Rejected => true,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Handlers of non-HTTP URL schemes
+//!
+//! A [`SchemeHandler`] registered with
+//! [`ClientBuilder::scheme_handler`](crate::ClientBuilder::scheme_handler) receives the requests
+//! to the URLs of its scheme, such as `ftp` or `sftp`, in place of the HTTP connection pool.
+//!
+//! The requests go through the client like any other: the URL policy, default headers,
+//! timeouts, redirects and every layer of the client apply to them. The handler is given a
+//! [`SchemeContext`] to reach the host of the URL the way the client would, with its DNS
+//! resolver and the proxy selected for the URL.
+//!
+//! ```no_run
+//! use wreq::{
+//!     Body,
+//!     scheme::SchemeContext,
+//! };
+//!
+//! async fn ftp(
+//!     req: http::Request<Body>,
+//!     ctx: SchemeContext,
+//! ) -> Result<http::Response<Body>, Box<dyn std::error::Error + Send + Sync>> {
+//!     let host = req.uri().host().unwrap_or_default();
+//!     let addrs = ctx.resolve(host, req.uri().port_u16().unwrap_or(21)).await?;
+//!     // ... connect to one of `addrs`, or through `ctx.proxy()`, and fetch the file.
+//!     # let _ = addrs;
+//!     Ok(http::Response::new(Body::from("file content")))
+//! }
+//!
+//! # async fn run() -> wreq::Result<()> {
+//! let client = wreq::Client::builder().scheme_handler("ftp", ftp).build()?;
+//! let text = client.get("ftp://ftp.example.com/README").send().await?.text().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::HashMap, fmt, future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+
+use http::{HeaderValue, Request, Response, Uri};
+
+use crate::{
+    Body,
+    dns::{DynResolver, Name},
+    error::BoxError,
+    proxy::Intercepted,
+};
+
+/// Alias for the `Future` type returned by a scheme handler.
+pub type Handling = Pin<Box<dyn Future<Output = Result<Response<Body>, BoxError>> + Send>>;
+
+/// A handler of the requests to the URLs of a scheme.
+///
+/// Implemented for async functions and closures taking the request and a [`SchemeContext`].
+pub trait SchemeHandler: Send + Sync {
+    /// Handles a request, returning its response.
+    ///
+    /// An error fails the request with an error for which
+    /// [`Error::is_request`](crate::Error::is_request) returns true.
+    fn handle(&self, request: Request<Body>, context: SchemeContext) -> Handling;
+}
+
+impl<F, Fut> SchemeHandler for F
+where
+    F: Fn(Request<Body>, SchemeContext) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Response<Body>, BoxError>> + Send + 'static,
+{
+    fn handle(&self, request: Request<Body>, context: SchemeContext) -> Handling {
+        Box::pin(self(request, context))
+    }
+}
+
+/// The means of the client given to a [`SchemeHandler`] to reach the host of a request.
+#[derive(Clone)]
+pub struct SchemeContext {
+    resolver: DynResolver,
+    proxy: Option<Intercepted>,
+}
+
+impl SchemeContext {
+    pub(crate) fn new(resolver: DynResolver, proxy: Option<Intercepted>) -> SchemeContext {
+        SchemeContext { resolver, proxy }
+    }
+
+    /// Resolves a host with the DNS resolver of the client, including its overrides.
+    ///
+    /// The addresses the resolver returns without a port are given `port`.
+    pub async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, BoxError> {
+        let name = host.parse::<Name>()?;
+        let addrs = self.resolver.resolve(name).await?;
+        Ok(addrs
+            .map(|mut addr| {
+                if addr.port() == 0 {
+                    addr.set_port(port);
+                }
+                addr
+            })
+            .collect())
+    }
+
+    /// Returns the URI of the proxy selected for the request, if any.
+    ///
+    /// It is the proxy that the client would tunnel an `https` request to the same host and
+    /// port through, such as one set with [`Proxy::all`](crate::Proxy::all).
+    pub fn proxy(&self) -> Option<&Uri> {
+        self.proxy.as_ref().map(Intercepted::uri)
+    }
+
+    /// Returns the `Proxy-Authorization` value of the proxy selected for the request, if any.
+    pub fn proxy_authorization(&self) -> Option<&HeaderValue> {
+        self.proxy.as_ref().and_then(Intercepted::basic_auth)
+    }
+}
+
+impl fmt::Debug for SchemeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchemeContext")
+            .field("proxy", &self.proxy())
+            .finish()
+    }
+}
+
+/// The scheme handlers of a client, by scheme.
+#[derive(Clone, Default)]
+pub(crate) struct SchemeHandlers {
+    handlers: HashMap<String, Arc<dyn SchemeHandler>>,
+}
+
+impl SchemeHandlers {
+    pub(crate) fn insert(&mut self, scheme: &str, handler: Arc<dyn SchemeHandler>) {
+        self.handlers.insert(scheme.to_ascii_lowercase(), handler);
+    }
+
+    pub(crate) fn get(&self, scheme: &str) -> Option<&Arc<dyn SchemeHandler>> {
+        self.handlers.get(scheme)
+    }
+}
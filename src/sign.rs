@@ -0,0 +1,150 @@
+//! Request Signing
+//!
+//! A `sign::Signer` is the last hook to see a request before it is handed to a connection: it
+//! runs after default headers, cookies, proxy headers and header ordering have been applied, on
+//! every hop of a redirect chain and on every retry. The signer receives the headers in the order
+//! they are written to the wire and a SHA-256 hash of the body, and returns the headers to add,
+//! such as an HMAC `Authorization` or a `Signature` header.
+//!
+//! ```
+//! use std::error::Error;
+//!
+//! use wreq::{
+//!     header::{HeaderMap, HeaderValue},
+//!     sign::SigningRequest,
+//! };
+//!
+//! fn sign(req: &SigningRequest<'_>) -> Result<HeaderMap, Box<dyn Error + Send + Sync>> {
+//!     let names: Vec<&str> = req.headers().map(|(name, _)| name.as_str()).collect();
+//!     let mut headers = HeaderMap::new();
+//!     headers.insert("x-signed-headers", HeaderValue::from_str(&names.join(";"))?);
+//!     Ok(headers)
+//! }
+//!
+//! # fn run() -> wreq::Result<()> {
+//! let client = wreq::Client::builder().signer(sign).build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{error::Error as StdError, fmt};
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Request, Uri, Version};
+
+use crate::{
+    client::Body,
+    core::ext::{RequestConfig, RequestOriginalHeaders},
+    error::BoxError,
+};
+
+/// A hook signing requests right before they are sent.
+///
+/// Implemented for closures taking a [`SigningRequest`] and returning the headers to add.
+pub trait Signer: Send + Sync {
+    /// Returns the headers to add to the request. Headers already present are replaced.
+    fn sign(
+        &self,
+        request: &SigningRequest<'_>,
+    ) -> Result<HeaderMap, Box<dyn StdError + Send + Sync>>;
+}
+
+impl<F> Signer for F
+where
+    F: Fn(&SigningRequest<'_>) -> Result<HeaderMap, Box<dyn StdError + Send + Sync>> + Send + Sync,
+{
+    #[inline]
+    fn sign(
+        &self,
+        request: &SigningRequest<'_>,
+    ) -> Result<HeaderMap, Box<dyn StdError + Send + Sync>> {
+        self(request)
+    }
+}
+
+/// An immutable view of a request about to be sent.
+pub struct SigningRequest<'a> {
+    method: &'a Method,
+    uri: &'a Uri,
+    version: Version,
+    headers: Vec<(&'a HeaderName, &'a HeaderValue)>,
+    body_sha256: Option<[u8; 32]>,
+}
+
+impl SigningRequest<'_> {
+    /// Returns the method of the request.
+    #[inline]
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// Returns the URI of the request.
+    #[inline]
+    pub fn uri(&self) -> &Uri {
+        self.uri
+    }
+
+    /// Returns the HTTP version of the request.
+    #[inline]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the headers of the request, in the order they are written.
+    ///
+    /// Headers computed by the connection itself, such as `Host` or `Content-Length`, are not
+    /// included.
+    pub fn headers(&self) -> impl Iterator<Item = (&HeaderName, &HeaderValue)> {
+        self.headers.iter().copied()
+    }
+
+    /// Returns the SHA-256 hash of the body, or `None` if the body is a stream.
+    ///
+    /// A request without a body has the hash of empty data.
+    #[inline]
+    pub fn body_sha256(&self) -> Option<&[u8; 32]> {
+        self.body_sha256.as_ref()
+    }
+}
+
+impl fmt::Debug for SigningRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningRequest")
+            .field("method", &self.method)
+            .field("uri", &self.uri)
+            .field("version", &self.version)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+/// Signs the request with the signer, adding the returned headers.
+pub(crate) fn sign(signer: &dyn Signer, req: &mut Request<Body>) -> Result<(), BoxError> {
+    let headers = req.headers();
+    let orig = RequestConfig::<RequestOriginalHeaders>::get(req.extensions());
+
+    // Ordered headers come first, followed by the others in insertion order.
+    let mut ordered = Vec::with_capacity(headers.len());
+    if let Some(orig) = orig {
+        for name in orig.keys() {
+            ordered.extend(headers.get_all(name).iter().map(|value| (name, value)));
+        }
+    }
+    ordered.extend(
+        headers
+            .iter()
+            .filter(|(name, _)| orig.is_none_or(|orig| orig.get_all(name).next().is_none())),
+    );
+
+    let body_sha256 = req.body().as_bytes().map(boring2::sha::sha256);
+
+    let signed = signer.sign(&SigningRequest {
+        method: req.method(),
+        uri: req.uri(),
+        version: req.version(),
+        headers: ordered,
+        body_sha256,
+    })?;
+
+    req.headers_mut().extend(signed);
+    Ok(())
+}
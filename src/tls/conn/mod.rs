@@ -16,7 +16,7 @@ use std::{
 use boring2::{
     error::ErrorStack,
     ex_data::Index,
-    ssl::{Ssl, SslConnector, SslMethod, SslOptions, SslSessionCacheMode},
+    ssl::{Ssl, SslConnector, SslMethod, SslOptions, SslRef, SslSessionCacheMode},
 };
 use bytes::Bytes;
 use cache::{SessionCache, SessionKey};
@@ -37,7 +37,7 @@ use crate::{
     error::BoxError,
     sync::Mutex,
     tls::{
-        CertStore, Identity, KeyLogPolicy, TlsConfig, TlsVersion,
+        AlpnProtocol, CertStore, Identity, KeyLogPolicy, TlsConfig, TlsVersion,
         conn::ext::{ConnectConfigurationExt, SslConnectorBuilderExt},
     },
 };
@@ -48,6 +48,16 @@ fn key_index() -> Result<Index<Ssl, SessionKey<ConnKey>>, ErrorStack> {
     IDX.clone()
 }
 
+fn alpn_index() -> Result<Index<Ssl, Bytes>, ErrorStack> {
+    static IDX: LazyLock<Result<Index<Ssl, Bytes>, ErrorStack>> = LazyLock::new(Ssl::new_ex_index);
+    IDX.clone()
+}
+
+/// Returns the wire-encoded ALPN protocols offered in the handshake of `ssl`.
+pub(crate) fn offered_alpn(ssl: &SslRef) -> Option<Bytes> {
+    alpn_index().ok().and_then(|idx| ssl.ex_data(idx)).cloned()
+}
+
 /// Builds for [`HandshakeConfig`].
 pub struct HandshakeConfigBuilder {
     settings: HandshakeConfig,
@@ -63,6 +73,7 @@ pub struct HandshakeConfig {
     alps_protos: Option<Bytes>,
     alps_use_new_codepoint: bool,
     random_aes_hw_override: bool,
+    alpn_protos: Option<Bytes>,
 }
 
 impl HandshakeConfigBuilder {
@@ -108,6 +119,12 @@ impl HandshakeConfigBuilder {
         self
     }
 
+    /// Sets the ALPN protocols the connector offers by default.
+    pub fn alpn_protos(mut self, protos: Option<Bytes>) -> Self {
+        self.settings.alpn_protos = protos;
+        self
+    }
+
     /// Builds the `HandshakeConfig`.
     pub fn build(self) -> HandshakeConfig {
         self.settings
@@ -133,6 +150,7 @@ impl Default for HandshakeConfig {
             alps_protos: None,
             alps_use_new_codepoint: false,
             random_aes_hw_override: false,
+            alpn_protos: None,
         }
     }
 }
@@ -193,7 +211,10 @@ where
 
 impl Inner {
     fn setup_ssl(&self, uri: Uri) -> Result<Ssl, BoxError> {
-        let cfg = self.ssl.configure()?;
+        let mut cfg = self.ssl.configure()?;
+        if let Some(ref alpn) = self.config.alpn_protos {
+            cfg.set_ex_data(alpn_index()?, alpn.clone());
+        }
         let host = uri.host().ok_or("URI missing host")?;
         let host = Self::normalize_host(host);
         let ssl = cfg.into_ssl(host)?;
@@ -222,8 +243,17 @@ impl Inner {
         )?;
 
         // Set ALPN protocols
-        if let Some(alpn) = req.ex_data().alpn_protocol() {
-            cfg.set_alpn_protos(&alpn.encode())?;
+        let alpn = match req.ex_data().alpn_protocols() {
+            Some(alpn) => {
+                let alpn = AlpnProtocol::encode_sequence(alpn);
+                cfg.set_alpn_protos(&alpn)?;
+                Some(alpn)
+            }
+            None => self.config.alpn_protos.clone(),
+        };
+        // Kept to report what was offered against what the server selected.
+        if let Some(alpn) = alpn {
+            cfg.set_ex_data(alpn_index()?, alpn);
         }
 
         let uri = req.uri().clone();
@@ -462,6 +492,7 @@ impl TlsConnectorBuilder {
             .tls_sni(self.tls_sni)
             .verify_hostname(self.verify_hostname)
             .random_aes_hw_override(cfg.random_aes_hw_override)
+            .alpn_protos(cfg.alpn_protos.clone())
             .build();
 
         // If the session cache is disabled, we don't need to set up any callbacks.
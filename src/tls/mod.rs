@@ -12,8 +12,12 @@ mod keylog;
 mod types;
 mod x509;
 
+use boring2::ssl::SslRef;
+use bytes::Bytes;
+
 pub(crate) use self::conn::{
     EstablishedConn, HttpsConnector, MaybeHttpsStream, TlsConnector, TlsConnectorBuilder,
+    offered_alpn,
 };
 pub use self::{
     config::TlsConfig,
@@ -28,12 +32,113 @@ pub use self::{
 /// Made available to clients on responses when `tls_info` is set.
 #[derive(Debug, Clone)]
 pub struct TlsInfo {
-    pub(crate) peer_certificate: Option<Vec<u8>>,
+    peer_certificate: Option<Vec<u8>>,
+    version: Option<TlsVersion>,
+    cipher: Option<&'static str>,
+    alpn_protocol: Option<Vec<u8>>,
+    session_reused: bool,
 }
 
 impl TlsInfo {
+    /// Collects the negotiated parameters of an established TLS session.
+    pub(crate) fn new(ssl: &SslRef) -> TlsInfo {
+        TlsInfo {
+            peer_certificate: ssl.peer_certificate().and_then(|c| c.to_der().ok()),
+            version: ssl.version2().map(TlsVersion),
+            cipher: ssl.current_cipher().map(|cipher| cipher.name()),
+            alpn_protocol: ssl.selected_alpn_protocol().map(<[u8]>::to_vec),
+            session_reused: ssl.session_reused(),
+        }
+    }
+
     /// Get the DER encoded leaf certificate of the peer.
     pub fn peer_certificate(&self) -> Option<&[u8]> {
         self.peer_certificate.as_ref().map(|der| &der[..])
     }
+
+    /// Get the negotiated TLS protocol version.
+    pub fn version(&self) -> Option<TlsVersion> {
+        self.version
+    }
+
+    /// Get the name of the negotiated cipher suite.
+    pub fn cipher(&self) -> Option<&str> {
+        self.cipher
+    }
+
+    /// Get the protocol negotiated with ALPN, such as `h2`.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// Returns whether the session was resumed from a previous one.
+    pub fn session_reused(&self) -> bool {
+        self.session_reused
+    }
+}
+
+/// The outcome of the ALPN negotiation of a TLS connection.
+///
+/// It is available in the [`Response`](crate::Response) extensions of requests sent on a TLS
+/// connection that offered ALPN protocols, and is passed to
+/// [`ConnectionEvents::alpn_negotiated`](crate::events::ConnectionEvents::alpn_negotiated).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlpnNegotiation {
+    offered: Bytes,
+    selected: Option<Vec<u8>>,
+}
+
+impl AlpnNegotiation {
+    /// Collects the ALPN protocols offered and selected in the handshake of `ssl`.
+    pub(crate) fn new(ssl: &SslRef) -> Option<AlpnNegotiation> {
+        let offered = offered_alpn(ssl).filter(|offered| !offered.is_empty())?;
+        Some(AlpnNegotiation {
+            offered,
+            selected: ssl.selected_alpn_protocol().map(<[u8]>::to_vec),
+        })
+    }
+
+    /// Returns the protocols offered to the server, in order of preference.
+    pub fn offered(&self) -> impl Iterator<Item = &[u8]> {
+        let mut rest = &self.offered[..];
+        std::iter::from_fn(move || {
+            let (&len, tail) = rest.split_first()?;
+            let (protocol, tail) = tail.split_at_checked(len as usize)?;
+            rest = tail;
+            Some(protocol)
+        })
+    }
+
+    /// Returns the protocol selected by the server, such as `h2`.
+    pub fn selected(&self) -> Option<&[u8]> {
+        self.selected.as_deref()
+    }
+
+    /// Returns whether the server didn't select the protocol offered first, either selecting
+    /// another one or none at all.
+    pub fn is_fallback(&self) -> bool {
+        self.offered().next() != self.selected()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlpnNegotiation, AlpnProtocol};
+
+    #[test]
+    fn alpn_fallback() {
+        let negotiation = AlpnNegotiation {
+            offered: AlpnProtocol::encode_sequence(&[AlpnProtocol::HTTP2, AlpnProtocol::HTTP1]),
+            selected: Some(b"http/1.1".to_vec()),
+        };
+        let offered: Vec<&[u8]> = negotiation.offered().collect();
+        assert_eq!(offered, [&b"h2"[..], &b"http/1.1"[..]]);
+        assert!(negotiation.is_fallback());
+
+        let negotiation = AlpnNegotiation {
+            selected: Some(b"h2".to_vec()),
+            ..negotiation
+        };
+        assert!(!negotiation.is_fallback());
+    }
 }
@@ -17,6 +17,36 @@ impl TlsVersion {
 
     /// Version 1.3 of the TLS protocol.
     pub const TLS_1_3: TlsVersion = TlsVersion(ssl::SslVersion::TLS1_3);
+
+    const ALL: [(TlsVersion, &'static str); 4] = [
+        (TlsVersion::TLS_1_0, "TLSv1"),
+        (TlsVersion::TLS_1_1, "TLSv1.1"),
+        (TlsVersion::TLS_1_2, "TLSv1.2"),
+        (TlsVersion::TLS_1_3, "TLSv1.3"),
+    ];
+
+    /// Returns the name of the version, such as `TLSv1.3`.
+    pub(crate) fn name(&self) -> Option<&'static str> {
+        TlsVersion::ALL
+            .iter()
+            .find(|(version, _)| version == self)
+            .map(|(_, name)| *name)
+    }
+
+    /// Returns the rank of the version, older versions ranking lower.
+    pub(crate) fn ordinal(&self) -> Option<usize> {
+        TlsVersion::ALL
+            .iter()
+            .position(|(version, _)| version == self)
+    }
+
+    /// Returns the version named `name`, such as `TLSv1.3`.
+    pub(crate) fn from_name(name: &str) -> Option<TlsVersion> {
+        TlsVersion::ALL
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(version, _)| *version)
+    }
 }
 
 /// A TLS ALPN protocol.
@@ -24,6 +24,7 @@ where
     header
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn fast_random() -> u64 {
     use std::{
         cell::Cell,
@@ -90,6 +91,37 @@ pub(crate) fn replace_headers(dst: &mut HeaderMap, src: HeaderMap) {
     }
 }
 
+/// Inserts the default headers that are not already present in the request.
+///
+/// The defaults the request lacks are copied into its map, though their values are reference
+/// counted: a request without headers of its own takes a copy of the whole map at once, and the
+/// others look each default name up a single time. The native client only does so for the
+/// requests that need all their headers in their own map, and otherwise shares its defaults
+/// with the request.
+pub fn merge_default_headers(headers: &mut HeaderMap, defaults: &HeaderMap) {
+    if defaults.is_empty() {
+        return;
+    }
+    if headers.is_empty() {
+        *headers = defaults.clone();
+        return;
+    }
+
+    headers.reserve(defaults.len());
+    // The values of a name are iterated in a row, and only added if the request has none.
+    let mut current = None;
+    let mut missing = false;
+    for (name, value) in defaults {
+        if current != Some(name) {
+            current = Some(name);
+            missing = !headers.contains_key(name);
+        }
+        if missing {
+            headers.append(name, value.clone());
+        }
+    }
+}
+
 pub(crate) struct Escape<'a>(&'a [u8]);
 
 impl<'a> Escape<'a> {
@@ -129,3 +161,35 @@ impl fmt::Display for Escape<'_> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use http::header::ACCEPT;
+
+    use super::*;
+
+    #[test]
+    fn default_headers_merged() {
+        let mut defaults = HeaderMap::new();
+        defaults.append(ACCEPT, HeaderValue::from_static("text/html"));
+        defaults.append(ACCEPT, HeaderValue::from_static("*/*"));
+        defaults.insert("x-a", HeaderValue::from_static("1"));
+        defaults.append("x-b", HeaderValue::from_static("2"));
+        defaults.append("x-b", HeaderValue::from_static("3"));
+
+        let mut headers = HeaderMap::new();
+        merge_default_headers(&mut headers, &defaults);
+        assert_eq!(headers, defaults);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-a", HeaderValue::from_static("own"));
+        merge_default_headers(&mut headers, &defaults);
+        assert_eq!(headers.len(), 5);
+        assert_eq!(headers["x-a"], "own");
+        assert_eq!(headers.get_all(ACCEPT).iter().count(), 2);
+        assert_eq!(
+            headers.get_all("x-b").iter().collect::<Vec<_>>(),
+            ["2", "3"]
+        );
+    }
+}
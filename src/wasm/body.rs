@@ -0,0 +1,69 @@
+use std::fmt;
+
+use bytes::Bytes;
+use js_sys::Uint8Array;
+use wasm_bindgen::JsValue;
+
+/// The body of a `Request`.
+///
+/// In the browser, a body is always sent from memory.
+#[derive(Clone)]
+pub struct Body {
+    inner: Bytes,
+}
+
+impl Body {
+    /// Returns a reference to the internal data of the `Body`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        Some(&self.inner)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub(crate) fn to_js_value(&self) -> JsValue {
+        Uint8Array::from(&self.inner[..]).into()
+    }
+}
+
+impl From<Bytes> for Body {
+    #[inline]
+    fn from(bytes: Bytes) -> Body {
+        Body { inner: bytes }
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    #[inline]
+    fn from(vec: Vec<u8>) -> Body {
+        Bytes::from(vec).into()
+    }
+}
+
+impl From<&'static [u8]> for Body {
+    #[inline]
+    fn from(s: &'static [u8]) -> Body {
+        Bytes::from_static(s).into()
+    }
+}
+
+impl From<String> for Body {
+    #[inline]
+    fn from(s: String) -> Body {
+        Bytes::from(s).into()
+    }
+}
+
+impl From<&'static str> for Body {
+    #[inline]
+    fn from(s: &'static str) -> Body {
+        s.as_bytes().into()
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Body").finish()
+    }
+}
@@ -0,0 +1,299 @@
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use http::{HeaderMap, HeaderValue, Method, header::USER_AGENT};
+use js_sys::Array;
+use url::Url;
+use wasm_bindgen::JsCast;
+
+use super::{AbortGuard, Request, RequestBuilder, Response, fetch_with_request, promise, wasm};
+use crate::{Error, IntoUrl, into_url::IntoUrlSealed};
+
+/// A client sending requests with the Fetch API of the browser.
+///
+/// The browser pools connections and stores cookies itself, so a `Client` only holds the
+/// default headers and timeout of its requests. Cloning it is cheap.
+#[derive(Clone)]
+pub struct Client {
+    config: Arc<Config>,
+}
+
+/// A `ClientBuilder` can be used to create a `Client` with custom configuration.
+#[must_use]
+pub struct ClientBuilder {
+    config: Config,
+}
+
+struct Config {
+    headers: HeaderMap,
+    timeout: Option<Duration>,
+    error: Option<Error>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// Constructs a new `ClientBuilder`.
+    ///
+    /// This is the same as `Client::builder()`.
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            config: Config {
+                headers: HeaderMap::new(),
+                timeout: None,
+                error: None,
+            },
+        }
+    }
+
+    /// Returns a `Client` that uses this `ClientBuilder` configuration.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if a header set on the builder is invalid.
+    pub fn build(self) -> crate::Result<Client> {
+        let mut config = self.config;
+        if let Some(err) = config.error.take() {
+            return Err(err);
+        }
+        Ok(Client {
+            config: Arc::new(config),
+        })
+    }
+
+    /// Sets the `User-Agent` header to be used by this client.
+    ///
+    /// Browsers may ignore the header, as it is forbidden for scripts in some of them.
+    pub fn user_agent<V>(mut self, value: V) -> ClientBuilder
+    where
+        V: TryInto<HeaderValue>,
+        V::Error: Into<http::Error>,
+    {
+        match value.try_into() {
+            Ok(value) => {
+                self.config.headers.insert(USER_AGENT, value);
+            }
+            Err(e) => {
+                self.config.error = Some(Error::builder(e.into()));
+            }
+        }
+        self
+    }
+
+    /// Sets the default headers for every request.
+    pub fn default_headers(mut self, headers: HeaderMap) -> ClientBuilder {
+        for (key, value) in headers.iter() {
+            self.config.headers.insert(key, value.clone());
+        }
+        self
+    }
+
+    /// Enables a total request timeout.
+    ///
+    /// The timeout is applied from when the request starts fetching until the response body has
+    /// finished. The request is then aborted. Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.timeout = Some(timeout);
+        self
+    }
+}
+
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("headers", &self.config.headers)
+            .field("timeout", &self.config.timeout)
+            .finish()
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// Constructs a new `Client`.
+    pub fn new() -> Client {
+        Client::builder().build().expect("Client::new()")
+    }
+
+    /// Creates a `ClientBuilder` to configure a `Client`.
+    ///
+    /// This is the same as `ClientBuilder::new()`.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// Convenience method to make a `GET` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn get<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Convenience method to make a `POST` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn post<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Convenience method to make a `PUT` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn put<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    /// Convenience method to make a `PATCH` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn patch<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::PATCH, url)
+    }
+
+    /// Convenience method to make a `DELETE` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn delete<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Convenience method to make a `HEAD` request to a URL.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn head<U: IntoUrl>(&self, url: U) -> RequestBuilder {
+        self.request(Method::HEAD, url)
+    }
+
+    /// Start building a `Request` with the `Method` and `Url`.
+    ///
+    /// Returns a `RequestBuilder`, which will allow setting headers and
+    /// the request body before sending.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the supplied `Url` cannot be parsed.
+    pub fn request<U: IntoUrl>(&self, method: Method, url: U) -> RequestBuilder {
+        let req = url.into_url().map(move |url| Request::new(method, url));
+        RequestBuilder::new(self.clone(), req)
+    }
+
+    /// Executes a `Request`.
+    ///
+    /// A `Request` can be built manually with `Request::new()` or obtained
+    /// from a RequestBuilder with `RequestBuilder::build()`.
+    ///
+    /// You should prefer to use the `RequestBuilder` and
+    /// `RequestBuilder::send()`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if there was an error while sending request,
+    /// or the browser refused to fetch the resource.
+    pub fn execute(&self, request: Request) -> impl Future<Output = crate::Result<Response>> {
+        self.execute_request(request)
+    }
+
+    pub(super) fn execute_request(
+        &self,
+        mut req: Request,
+    ) -> impl Future<Output = crate::Result<Response>> {
+        crate::util::merge_default_headers(req.headers_mut(), &self.config.headers);
+        if req.timeout().is_none() {
+            *req.timeout_mut() = self.config.timeout;
+        }
+
+        fetch(req)
+    }
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("headers", &self.config.headers)
+            .field("timeout", &self.config.timeout)
+            .finish()
+    }
+}
+
+async fn fetch(req: Request) -> crate::Result<Response> {
+    let url = req.url().clone();
+
+    let headers = web_sys::Headers::new()
+        .map_err(wasm)
+        .map_err(Error::builder)?;
+    for (name, value) in req.headers() {
+        let value = value.to_str().map_err(Error::builder)?;
+        headers
+            .append(name.as_str(), value)
+            .map_err(wasm)
+            .map_err(Error::builder)?;
+    }
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(req.method().as_str());
+    init.set_headers(&headers);
+    if let Some(body) = req.body().filter(|body| !body.is_empty()) {
+        init.set_body(&body.to_js_value());
+    }
+    if !req.cors {
+        init.set_mode(web_sys::RequestMode::NoCors);
+    }
+    if let Some(credentials) = req.credentials {
+        init.set_credentials(credentials);
+    }
+
+    let mut abort = AbortGuard::new()?;
+    if let Some(timeout) = req.timeout() {
+        abort.timeout(*timeout);
+    }
+    init.set_signal(Some(&abort.signal()));
+
+    let js_req = web_sys::Request::new_with_str_and_init(url.as_str(), &init)
+        .map_err(wasm)
+        .map_err(|e| Error::builder(e).with_url(url.clone()))?;
+
+    let js_resp = promise::<web_sys::Response>(fetch_with_request(&js_req))
+        .await
+        .map_err(|e| Error::request(abort.error(e)).with_url(url.clone()))?;
+
+    let mut resp = http::Response::builder().status(js_resp.status());
+    let entries = js_sys::try_iter(&js_resp.headers())
+        .map_err(wasm)
+        .map_err(|e| Error::request(e).with_url(url.clone()))?
+        .ok_or_else(|| Error::request("headers are not iterable").with_url(url.clone()))?;
+    for entry in entries {
+        let entry = entry
+            .map_err(wasm)
+            .map_err(|e| Error::request(e).with_url(url.clone()))?;
+        let pair = entry.unchecked_into::<Array>();
+        if let (Some(name), Some(value)) = (pair.get(0).as_string(), pair.get(1).as_string()) {
+            resp = resp.header(name, value);
+        }
+    }
+
+    // The URL of the response differs from the one requested when redirects were followed.
+    let url = Url::parse(&js_resp.url()).unwrap_or(url);
+    let resp = resp
+        .body(js_resp)
+        .map_err(|e| Error::request(e).with_url(url.clone()))?;
+    Ok(Response::new(resp, url, abort))
+}
@@ -0,0 +1,103 @@
+//! A client sending requests with the Fetch API of the browser.
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use js_sys::Function;
+use wasm_bindgen::{JsCast, JsValue, prelude::*};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortController, AbortSignal};
+
+mod body;
+mod client;
+mod request;
+mod response;
+
+pub(crate) use self::response::ReasonPhrase;
+pub use self::{
+    body::Body,
+    client::{Client, ClientBuilder},
+    request::{Request, RequestBuilder},
+    response::Response,
+};
+use crate::{Error, error::BoxError};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = "fetch")]
+    fn fetch_with_request(input: &web_sys::Request) -> js_sys::Promise;
+
+    #[wasm_bindgen(js_name = "setTimeout")]
+    fn set_timeout(handler: &Function, timeout: i32) -> JsValue;
+
+    #[wasm_bindgen(js_name = "clearTimeout")]
+    fn clear_timeout(handle: JsValue) -> JsValue;
+}
+
+/// Converts a JavaScript exception into an error.
+fn wasm(js: JsValue) -> BoxError {
+    format!("{js:?}").into()
+}
+
+/// Waits for a promise to resolve to a value of type `T`.
+async fn promise<T: JsCast>(promise: js_sys::Promise) -> Result<T, BoxError> {
+    let value = JsFuture::from(promise).await.map_err(wasm)?;
+    value
+        .dyn_into::<T>()
+        .map_err(|_| "promise resolved to an unexpected type".into())
+}
+
+/// Aborts the fetch of a request, with its response body, once dropped or once its timeout
+/// elapsed.
+pub(crate) struct AbortGuard {
+    ctrl: AbortController,
+    timeout: Option<(JsValue, Closure<dyn FnMut()>)>,
+    timed_out: Rc<Cell<bool>>,
+}
+
+impl AbortGuard {
+    fn new() -> crate::Result<AbortGuard> {
+        Ok(AbortGuard {
+            ctrl: AbortController::new()
+                .map_err(wasm)
+                .map_err(Error::builder)?,
+            timeout: None,
+            timed_out: Rc::new(Cell::new(false)),
+        })
+    }
+
+    fn signal(&self) -> AbortSignal {
+        self.ctrl.signal()
+    }
+
+    fn timeout(&mut self, timeout: Duration) {
+        let ctrl = self.ctrl.clone();
+        let timed_out = self.timed_out.clone();
+        let abort = Closure::once(move || {
+            timed_out.set(true);
+            ctrl.abort();
+        });
+        let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        let handle = set_timeout(abort.as_ref().unchecked_ref::<Function>(), millis);
+        if let Some((handle, _)) = self.timeout.replace((handle, abort)) {
+            clear_timeout(handle);
+        }
+    }
+
+    /// Maps the error of a fetch aborted by this guard, telling apart timeouts.
+    fn error(&self, err: BoxError) -> BoxError {
+        if self.timed_out.get() {
+            Box::new(crate::error::TimedOut)
+        } else {
+            err
+        }
+    }
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        self.ctrl.abort();
+        if let Some((handle, _)) = self.timeout.take() {
+            clear_timeout(handle);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::fmt;
+
+use bytes::Bytes;
+#[cfg(feature = "charset")]
+use encoding_rs::{Encoding, UTF_8};
+use http::{HeaderMap, StatusCode, Version};
+use js_sys::{Object, Reflect, Uint8Array};
+#[cfg(feature = "charset")]
+use mime::Mime;
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+use url::Url;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::ReadableStreamDefaultReader;
+
+use super::{AbortGuard, promise, wasm};
+use crate::Error;
+
+/// The reason phrase of a response, when it differs from the canonical one of its status.
+#[derive(Debug, Clone)]
+pub(crate) struct ReasonPhrase(String);
+
+impl ReasonPhrase {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+/// A Response to a submitted `Request`.
+///
+/// It has the methods of the native `Response` that the Fetch API can provide. The browser
+/// doesn't expose the connection of a response, so there is no `remote_addr`, `local_addr` or
+/// `transfer_stats`, and it decodes the body itself, so there is no `raw_bytes`. Its cookies
+/// are stored by the browser, and most are hidden from scripts, so there is no `cookies`
+/// either. Streaming the body is done with [`Response::chunk`], as there is no
+/// `bytes_stream`, and connections can't be upgraded.
+pub struct Response {
+    http: http::Response<web_sys::Response>,
+    // Aborts reading the body once the response is dropped.
+    abort: AbortGuard,
+    url: Box<Url>,
+    // The reader of the body, once it is read in chunks.
+    reader: Option<ReadableStreamDefaultReader>,
+}
+
+impl Response {
+    pub(super) fn new(
+        res: http::Response<web_sys::Response>,
+        url: Url,
+        abort: AbortGuard,
+    ) -> Response {
+        Response {
+            http: res,
+            url: Box::new(url),
+            abort,
+            reader: None,
+        }
+    }
+
+    /// Get the `StatusCode` of this `Response`.
+    #[inline]
+    pub fn status(&self) -> StatusCode {
+        self.http.status()
+    }
+
+    /// Get the HTTP `Version` of this `Response`.
+    ///
+    /// The Fetch API does not expose the version, so this is always `HTTP/1.1`.
+    #[inline]
+    pub fn version(&self) -> Version {
+        self.http.version()
+    }
+
+    /// Get the `Headers` of this `Response`.
+    #[inline]
+    pub fn headers(&self) -> &HeaderMap {
+        self.http.headers()
+    }
+
+    /// Get a mutable reference to the `Headers` of this `Response`.
+    #[inline]
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        self.http.headers_mut()
+    }
+
+    /// Get the content length of the response, if it is known.
+    ///
+    /// The value is read from the `Content-Length` header, as the browser does not expose the
+    /// size of the body before reading it.
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers()
+            .get(http::header::CONTENT_LENGTH)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Get the final `Url` of this `Response`.
+    #[inline]
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns a reference to the associated extensions.
+    #[inline]
+    pub fn extensions(&self) -> &http::Extensions {
+        self.http.extensions()
+    }
+
+    /// Returns a mutable reference to the associated extensions.
+    #[inline]
+    pub fn extensions_mut(&mut self) -> &mut http::Extensions {
+        self.http.extensions_mut()
+    }
+
+    /// Get the full response text.
+    ///
+    /// The encoding is determined from the `charset` parameter of the `Content-Type` header,
+    /// and defaults to `utf-8`, as with the native client. If the `charset` feature is disabled,
+    /// the browser decodes the body as UTF-8.
+    pub async fn text(self) -> crate::Result<String> {
+        #[cfg(feature = "charset")]
+        {
+            self.text_with_charset("utf-8").await
+        }
+
+        #[cfg(not(feature = "charset"))]
+        {
+            let promise = self.http.body().text().map_err(wasm).map_err(Error::body)?;
+            let text = promise::<JsValue>(promise)
+                .await
+                .map_err(|e| Error::body(self.abort.error(e)))?;
+            text.as_string()
+                .ok_or_else(|| Error::decode("response text is not a string"))
+        }
+    }
+
+    /// Get the full response text given a specific encoding.
+    ///
+    /// The `charset` parameter of the `Content-Type` header is still prioritized over
+    /// `default_encoding`. Malformed sequences are replaced with the
+    /// [`char::REPLACEMENT_CHARACTER`], and the BOM is stripped from the returned String.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `charset` feature enabled.
+    #[cfg(feature = "charset")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "charset")))]
+    pub async fn text_with_charset(self, default_encoding: &str) -> crate::Result<String> {
+        let content_type = self
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok());
+        let encoding_name = content_type
+            .as_ref()
+            .and_then(|mime| mime.get_param("charset").map(|charset| charset.as_str()))
+            .unwrap_or(default_encoding);
+        let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
+
+        let full = self.bytes().await?;
+
+        let (text, _, _) = encoding.decode(&full);
+        Ok(text.into_owned())
+    }
+
+    /// Try to deserialize the response body as JSON.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails whenever the response body is not in JSON format
+    /// or it cannot be properly deserialized to target type `T`.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
+        let full = self.bytes().await?;
+
+        serde_json::from_slice(&full).map_err(Error::decode)
+    }
+
+    /// Deserialize the response body as JSON, or the error body of a failed request as `E`.
+    ///
+    /// A response with a `2xx` status is deserialized to `T`, as with [`Response::json`]. Any
+    /// other response is deserialized to `E` and returned as
+    /// [`ApiError::Status`](crate::ApiError::Status). An error body that isn't an `E` is
+    /// reported as a status error, as with [`Response::error_for_status`].
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`ApiError::Request`](crate::ApiError::Request) if the body
+    /// couldn't be read, or if a success body couldn't be deserialized to `T`.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub async fn json_or_error<T, E>(self) -> Result<T, crate::ApiError<E>>
+    where
+        T: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self.json().await?);
+        }
+
+        let url = self.url().clone();
+        let reason = self.reason();
+        let full = self.bytes().await?;
+        match serde_json::from_slice(&full) {
+            Ok(error) => Err(crate::ApiError::Status { status, error }),
+            Err(_) => Err(Error::status_code(url, status, reason).into()),
+        }
+    }
+
+    /// Deserialize the response body as a stream of JSON values, as the body arrives.
+    ///
+    /// The body is read with [`Response::chunk`], and its values are read with
+    /// [`JsonStream::next`](crate::JsonStream::next). See [`JsonStream`](crate::JsonStream) for
+    /// details.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature enabled.
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json_stream<T: DeserializeOwned>(self) -> crate::JsonStream<T> {
+        crate::JsonStream::new(self)
+    }
+
+    /// Get the full response body as `Bytes`.
+    pub async fn bytes(self) -> crate::Result<Bytes> {
+        let promise = self
+            .http
+            .body()
+            .array_buffer()
+            .map_err(wasm)
+            .map_err(Error::body)?;
+        let buffer = promise::<js_sys::ArrayBuffer>(promise)
+            .await
+            .map_err(|e| Error::body(self.abort.error(e)))?;
+        Ok(Uint8Array::new(&buffer).to_vec().into())
+    }
+
+    /// Stream a chunk of the response body.
+    ///
+    /// When the response body has been exhausted, this will return `None`. Once a chunk has
+    /// been read, the rest of the body can only be read with this method.
+    pub async fn chunk(&mut self) -> crate::Result<Option<Bytes>> {
+        if self.reader.is_none() {
+            let Some(body) = self.http.body().body() else {
+                return Ok(None);
+            };
+            self.reader = Some(body.get_reader().unchecked_into());
+        }
+        let reader = self.reader.as_ref().expect("reader of the body is set");
+
+        let result = promise::<Object>(reader.read())
+            .await
+            .map_err(|e| Error::body(self.abort.error(e)))?;
+        let field = |name: &str| {
+            Reflect::get(&result, &JsValue::from_str(name))
+                .map_err(wasm)
+                .map_err(Error::body)
+        };
+        if field("done")?.is_truthy() {
+            return Ok(None);
+        }
+        let value = field("value")?
+            .dyn_into::<Uint8Array>()
+            .map_err(|_| Error::body("response body chunk is not a byte array"))?;
+        Ok(Some(value.to_vec().into()))
+    }
+
+    // util methods
+
+    /// Turn a response into an error if the server returned an error.
+    pub fn error_for_status(self) -> crate::Result<Self> {
+        let status = self.status();
+        if status.is_client_error() || status.is_server_error() {
+            let reason = self.reason();
+            Err(Error::status_code(*self.url, status, reason))
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Turn a reference to a response into an error if the server returned an error.
+    pub fn error_for_status_ref(&self) -> crate::Result<&Self> {
+        let status = self.status();
+        if status.is_client_error() || status.is_server_error() {
+            Err(Error::status_code(*self.url.clone(), status, self.reason()))
+        } else {
+            Ok(self)
+        }
+    }
+
+    fn reason(&self) -> Option<ReasonPhrase> {
+        let status_text = self.http.body().status_text();
+        if status_text.is_empty() || self.status().canonical_reason() == Some(status_text.as_str())
+        {
+            None
+        } else {
+            Some(ReasonPhrase(status_text))
+        }
+    }
+}
+
+impl fmt::Debug for Response {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Response")
+            .field("url", &self.url().as_str())
+            .field("status", &self.status())
+            .field("headers", self.headers())
+            .finish()
+    }
+}
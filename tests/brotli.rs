@@ -307,7 +307,7 @@ async fn test_chunked_fragmented_response_2() {
     assert!(start.elapsed() >= DELAY_BETWEEN_RESPONSE_PARTS - DELAY_MARGIN);
 }
 
-#[ignore = "tower-http decompression does not support this feature test"]
+#[ignore = "the brotli decoder drops the data past the end of the stream instead of failing"]
 #[tokio::test]
 async fn test_chunked_fragmented_response_with_extra_bytes() {
     const DELAY_BETWEEN_RESPONSE_PARTS: tokio::time::Duration =
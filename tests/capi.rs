@@ -0,0 +1,148 @@
+mod support;
+use std::ffi::{CStr, c_int, c_void};
+
+use support::server;
+use wreq::capi::{
+    WREQ_ABORTED, WREQ_INVALID_ARGUMENT, WREQ_OK, WreqCallbacks, WreqHeader, WreqRequest,
+    wreq_client_free, wreq_client_new, wreq_last_error, wreq_request_execute,
+};
+
+#[derive(Default)]
+struct Received {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+unsafe extern "C" fn on_status(user_data: *mut c_void, status: u16) {
+    let received = unsafe { &mut *(user_data as *mut Received) };
+    received.status = status;
+}
+
+unsafe extern "C" fn on_header(
+    user_data: *mut c_void,
+    name: *const u8,
+    name_len: usize,
+    value: *const u8,
+    value_len: usize,
+) {
+    let received = unsafe { &mut *(user_data as *mut Received) };
+    let name = unsafe { std::slice::from_raw_parts(name, name_len) };
+    let value = unsafe { std::slice::from_raw_parts(value, value_len) };
+    received.headers.push((
+        String::from_utf8_lossy(name).into_owned(),
+        String::from_utf8_lossy(value).into_owned(),
+    ));
+}
+
+unsafe extern "C" fn on_body(user_data: *mut c_void, data: *const u8, len: usize) -> c_int {
+    let received = unsafe { &mut *(user_data as *mut Received) };
+    received
+        .body
+        .extend_from_slice(unsafe { std::slice::from_raw_parts(data, len) });
+    0
+}
+
+unsafe extern "C" fn abort_body(_: *mut c_void, _: *const u8, _: usize) -> c_int {
+    1
+}
+
+fn request(url: &CStr) -> WreqRequest {
+    WreqRequest {
+        method: std::ptr::null(),
+        url: url.as_ptr(),
+        headers: std::ptr::null(),
+        headers_len: 0,
+        body: std::ptr::null(),
+        body_len: 0,
+        timeout_ms: 0,
+    }
+}
+
+#[test]
+fn execute_with_callbacks() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.method(), "POST");
+        assert_eq!(req.headers()["x-test"], "capi");
+
+        http::Response::builder()
+            .header("x-reply", "ok")
+            .body(wreq::Body::from("hello"))
+            .unwrap()
+    });
+
+    let url = std::ffi::CString::new(format!("http://{}/", server.addr())).unwrap();
+    let headers = [WreqHeader {
+        name: c"x-test".as_ptr(),
+        value: c"capi".as_ptr(),
+    }];
+    let body = b"payload";
+    let req = WreqRequest {
+        method: c"POST".as_ptr(),
+        headers: headers.as_ptr(),
+        headers_len: headers.len(),
+        body: body.as_ptr(),
+        body_len: body.len(),
+        ..request(&url)
+    };
+    let callbacks = WreqCallbacks {
+        on_status: Some(on_status),
+        on_header: Some(on_header),
+        on_body: Some(on_body),
+    };
+
+    let client = wreq_client_new();
+    assert!(!client.is_null());
+
+    let mut received = Received::default();
+    let code = unsafe {
+        wreq_request_execute(
+            client,
+            &req,
+            &callbacks,
+            &mut received as *mut Received as *mut c_void,
+        )
+    };
+    assert_eq!(code, WREQ_OK);
+    assert_eq!(received.status, 200);
+    assert!(
+        received
+            .headers
+            .contains(&("x-reply".to_owned(), "ok".to_owned()))
+    );
+    assert_eq!(received.body, b"hello");
+
+    // A body callback returning non-zero aborts the request.
+    let callbacks = WreqCallbacks {
+        on_body: Some(abort_body),
+        ..Default::default()
+    };
+    let req = request(&url);
+    let code = unsafe { wreq_request_execute(client, &req, &callbacks, std::ptr::null_mut()) };
+    assert_eq!(code, WREQ_ABORTED);
+
+    unsafe { wreq_client_free(client) };
+}
+
+#[test]
+fn invalid_arguments() {
+    let client = wreq_client_new();
+
+    let code = unsafe {
+        wreq_request_execute(
+            client,
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, WREQ_INVALID_ARGUMENT);
+
+    let req = request(c"not a url");
+    let code =
+        unsafe { wreq_request_execute(client, &req, std::ptr::null(), std::ptr::null_mut()) };
+    assert_eq!(code, WREQ_INVALID_ARGUMENT);
+    assert!(!wreq_last_error().is_null());
+
+    unsafe { wreq_client_free(client) };
+}
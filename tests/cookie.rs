@@ -186,3 +186,226 @@ async fn cookie_store_path() {
     let url = format!("http://{}/subpath", server.addr());
     client.get(&url).send().await.unwrap();
 }
+
+#[cfg(feature = "psl")]
+#[test]
+fn cookie_store_rejects_public_suffix() {
+    use wreq::{
+        Url,
+        cookie::{CookieStore, Jar, PublicSuffixList},
+    };
+
+    let list = "// ===BEGIN ICANN DOMAINS===\nuk\nco.uk\n// ===END ICANN DOMAINS===\n"
+        .parse::<PublicSuffixList>()
+        .unwrap();
+    assert!(list.is_public_suffix("co.uk"));
+    assert!(list.is_public_suffix(".CO.UK"));
+    assert!(!list.is_public_suffix("example.co.uk"));
+
+    let url = "https://www.example.co.uk".parse::<Url>().unwrap();
+
+    let jar = Jar::default();
+    jar.add_cookie_str("key=val; Domain=co.uk", &url);
+    assert!(jar.cookies(&url).is_some());
+
+    let jar = Jar::with_public_suffix_list(list.clone());
+    jar.add_cookie_str("key=val; Domain=co.uk", &url);
+    assert!(jar.cookies(&url).is_none());
+    jar.add_cookie_str("key=val; Domain=example.co.uk", &url);
+    assert_eq!(jar.cookies(&url).unwrap()[0], "key=val");
+
+    let jar = Jar::default();
+    jar.set_public_suffix_list(list);
+    jar.add_cookie_str("key=val; Domain=co.uk", &url);
+    assert!(jar.cookies(&url).is_none());
+}
+
+#[tokio::test(start_paused = true)]
+async fn cookie_jar_expires_by_clock() {
+    use wreq::{
+        Url,
+        cookie::{CookieStore, Jar},
+        rt::TokioClock,
+    };
+
+    let url = "https://example.com".parse::<Url>().unwrap();
+    let jar = Jar::default().with_clock(TokioClock::new());
+    jar.add_cookie_str("key=val; Max-Age=60", &url);
+    assert!(jar.cookies(&url).is_some());
+
+    tokio::time::advance(std::time::Duration::from_secs(61)).await;
+    assert!(jar.cookies(&url).is_none());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn cookie_jar_import_export() {
+    use wreq::{
+        Url,
+        cookie::{CookieFormat, CookieStore, Jar},
+    };
+
+    let browser = r#"[
+        {"domain":".example.com","expirationDate":4102444800,"hostOnly":false,"httpOnly":true,
+         "name":"sid","path":"/","sameSite":"lax","secure":true,"session":false,"value":"abc"},
+        {"domain":"www.example.com","hostOnly":true,"httpOnly":false,"name":"theme",
+         "path":"/","sameSite":"unspecified","secure":false,"session":true,"value":"dark"},
+        {"domain":".example.com","expirationDate":1,"name":"old","path":"/","value":"gone"}
+    ]"#;
+
+    let jar = Jar::default();
+    jar.import(CookieFormat::Browser, browser).unwrap();
+
+    let url = "https://www.example.com/".parse::<Url>().unwrap();
+    let mut cookies = jar
+        .cookies(&url)
+        .unwrap()
+        .into_iter()
+        .map(|v| v.to_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    cookies.sort();
+    assert_eq!(cookies, ["sid=abc", "theme=dark"]);
+
+    let sub = "https://api.example.com/".parse::<Url>().unwrap();
+    assert_eq!(jar.cookies(&sub).unwrap()[0], "sid=abc");
+
+    let state = jar.export(CookieFormat::StorageState).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&state).unwrap();
+    assert_eq!(value["cookies"].as_array().unwrap().len(), 2);
+
+    let copy = Jar::default();
+    copy.import(CookieFormat::StorageState, &state).unwrap();
+    assert_eq!(copy.cookies(&sub).unwrap()[0], "sid=abc");
+
+    assert!(copy.import(CookieFormat::Browser, "{").is_err());
+}
+
+#[test]
+fn cookie_filtered_store() {
+    use std::sync::Arc;
+
+    use wreq::{
+        Url,
+        cookie::{CookieStore, FilteredStore, Jar},
+        header::HeaderValue,
+    };
+
+    let jar = Arc::new(Jar::default());
+    let url = "https://www.example.com/".parse::<Url>().unwrap();
+    let other = "https://other.com/".parse::<Url>().unwrap();
+
+    let writer = FilteredStore::new(jar.clone())
+        .allow_domain("example.com")
+        .deny_name("tracking");
+    let values = [
+        HeaderValue::from_static("key=val"),
+        HeaderValue::from_static("tracking=1"),
+    ];
+    writer.set_cookies(&mut values.iter(), &url);
+    writer.set_cookies(&mut values.iter(), &other);
+    assert_eq!(jar.cookies(&url).unwrap(), ["key=val"]);
+    assert!(jar.cookies(&other).is_none());
+
+    let reader = FilteredStore::new(jar.clone()).read_only(true);
+    let values = [HeaderValue::from_static("new=1")];
+    reader.set_cookies(&mut values.iter(), &url);
+    assert_eq!(reader.cookies(&url).unwrap(), ["key=val"]);
+
+    let secure = FilteredStore::new(jar.clone()).secure_only(true);
+    let values = [
+        HeaderValue::from_static("plain=1"),
+        HeaderValue::from_static("safe=1; Secure"),
+    ];
+    secure.set_cookies(&mut values.iter(), &url);
+    assert!(
+        jar.cookies(&url)
+            .unwrap()
+            .contains(&"safe=1".parse().unwrap())
+    );
+    assert!(
+        !jar.cookies(&url)
+            .unwrap()
+            .contains(&"plain=1".parse().unwrap())
+    );
+
+    let http = "http://www.example.com/".parse::<Url>().unwrap();
+    assert!(secure.cookies(&http).is_none());
+
+    let names = FilteredStore::new(jar).allow_name("safe");
+    assert_eq!(names.cookies(&url).unwrap(), ["safe=1"]);
+}
+
+#[tokio::test]
+async fn session_cookies_and_referer() {
+    use wreq::cookie::CookieStore;
+
+    let server = server::http(move |req| async move {
+        match req.uri().path() {
+            "/login" => http::Response::builder()
+                .header("Set-Cookie", "session=1")
+                .body(Default::default())
+                .unwrap(),
+            _ => {
+                let host = req.headers()["host"].to_str().unwrap();
+                assert_eq!(req.headers()["cookie"], "session=1");
+                assert_eq!(req.headers()["referer"], format!("http://{host}/login"));
+                http::Response::default()
+            }
+        }
+    });
+
+    let client = wreq::Client::new();
+    let session = client.session();
+
+    let login = format!("http://{}/login", server.addr());
+    let home = format!("http://{}/home", server.addr());
+
+    session.navigate(&login).send().await.unwrap();
+    session.navigate(&home).send().await.unwrap();
+
+    assert_eq!(session.history().len(), 2);
+    assert_eq!(session.current_url().unwrap().as_str(), home);
+
+    // The client and other sessions do not see the session cookies.
+    let other = client.session();
+    let url = login.parse().unwrap();
+    assert!(session.cookie_jar().cookies(&url).is_some());
+    assert!(other.cookie_jar().cookies(&url).is_none());
+
+    session.clear();
+    assert!(session.current_url().is_none());
+    assert!(session.cookie_jar().cookies(&url).is_none());
+}
+
+#[tokio::test]
+async fn session_user_agent_rotation() {
+    let server = server::http(move |req| async move {
+        http::Response::builder()
+            .header("x-user-agent", req.headers()["user-agent"].clone())
+            .body(Default::default())
+            .unwrap()
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let client = wreq::Client::builder()
+        .no_proxy()
+        .user_agent_rotation(["a", "b", "c"], wreq::RotationStrategy::RoundRobin)
+        .build()
+        .unwrap();
+
+    let user_agent = |res: wreq::Response| res.headers()["x-user-agent"].clone();
+
+    // The client itself picks the first user agent, and each session the next one.
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(user_agent(res), "a");
+
+    let first = client.session();
+    let second = client.session();
+    for _ in 0..2 {
+        assert_eq!(user_agent(first.get(&url).send().await.unwrap()), "b");
+        assert_eq!(user_agent(second.get(&url).send().await.unwrap()), "c");
+    }
+
+    let res = first.get(&url).user_agent("d").send().await.unwrap();
+    assert_eq!(user_agent(res), "d");
+}
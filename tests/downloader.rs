@@ -0,0 +1,176 @@
+mod support;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use support::server;
+use wreq::{Client, downloader::Downloader};
+
+const ETAG: &str = "\"v1\"";
+
+fn content() -> Vec<u8> {
+    (0..10_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+fn partial_content(req: &http::Request<hyper::body::Incoming>) -> http::Response<wreq::Body> {
+    let content = content();
+    let range = req.headers()["range"].to_str().unwrap();
+    let (start, end) = range
+        .strip_prefix("bytes=")
+        .unwrap()
+        .split_once('-')
+        .unwrap();
+    let start: usize = start.parse().unwrap();
+    if start > 0 {
+        assert_eq!(req.headers()["if-range"], ETAG);
+    }
+    let end = end
+        .parse::<usize>()
+        .unwrap_or(usize::MAX)
+        .min(content.len() - 1);
+
+    http::Response::builder()
+        .status(http::StatusCode::PARTIAL_CONTENT)
+        .header("etag", ETAG)
+        .header(
+            "content-range",
+            format!("bytes {start}-{end}/{}", content.len()),
+        )
+        .body(wreq::Body::from(content[start..=end].to_vec()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn segmented_download() {
+    let failed = Arc::new(AtomicBool::new(false));
+    let server = server::http(move |req| {
+        let failed = failed.clone();
+        async move {
+            // Fail a middle segment once.
+            if req.headers()["range"] == "bytes=4096-6143" && !failed.swap(true, Ordering::SeqCst) {
+                return http::Response::builder()
+                    .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                    .body(wreq::Body::from(""))
+                    .unwrap();
+            }
+            partial_content(&req)
+        }
+    });
+
+    let downloaded = Arc::new(AtomicU64::new(0));
+    let progress = downloaded.clone();
+
+    let mut buf = Vec::new();
+    let written = Downloader::new(Client::new())
+        .segment_size(2048)
+        .concurrency(3)
+        .on_progress(move |p| {
+            assert_eq!(p.total(), Some(10_000));
+            progress.fetch_max(p.downloaded(), Ordering::SeqCst);
+        })
+        .download(format!("http://{}/file", server.addr()), &mut buf)
+        .await
+        .unwrap();
+
+    assert_eq!(written, 10_000);
+    assert_eq!(buf, content());
+    assert_eq!(downloaded.load(Ordering::SeqCst), 10_000);
+}
+
+#[tokio::test]
+async fn download_without_range_support() {
+    let server = server::http(move |_req| async move { http::Response::new(content().into()) });
+
+    let mut buf = Vec::new();
+    let written = Downloader::new(Client::new())
+        .segment_size(2048)
+        .download(format!("http://{}/file", server.addr()), &mut buf)
+        .await
+        .unwrap();
+
+    assert_eq!(written, 10_000);
+    assert_eq!(buf, content());
+}
+
+#[tokio::test]
+async fn download_fails_if_the_resource_changes() {
+    let server = server::http(move |req| async move {
+        // The resource changes after its first segment is sent.
+        if req.headers().contains_key("if-range") {
+            return http::Response::builder()
+                .header("etag", "\"v2\"")
+                .body(wreq::Body::from(content()))
+                .unwrap();
+        }
+        partial_content(&req)
+    });
+
+    let mut buf = Vec::new();
+    let err = Downloader::new(Client::new())
+        .segment_size(2048)
+        .download(format!("http://{}/file", server.addr()), &mut buf)
+        .await
+        .unwrap_err();
+
+    assert!(err.is_decode());
+    assert!(buf.len() <= 2048);
+}
+
+#[tokio::test]
+async fn download_empty_resource() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .status(http::StatusCode::RANGE_NOT_SATISFIABLE)
+            .header("content-range", "bytes */0")
+            .body(wreq::Body::from(""))
+            .unwrap()
+    });
+
+    let mut buf = Vec::new();
+    let written = Downloader::new(Client::new())
+        .download(format!("http://{}/file", server.addr()), &mut buf)
+        .await
+        .unwrap();
+
+    assert_eq!(written, 0);
+    assert!(buf.is_empty());
+}
+
+#[tokio::test]
+async fn download_of_unknown_size_streams_the_rest() {
+    let server = server::http(move |req| async move {
+        let range = req.headers()["range"].to_str().unwrap().to_owned();
+        let mut res = partial_content(&req);
+        let (start, end) = range
+            .strip_prefix("bytes=")
+            .unwrap()
+            .split_once('-')
+            .unwrap();
+        let start: usize = start.parse().unwrap();
+        let end = end.parse::<usize>().unwrap_or(9_999).min(9_999);
+        res.headers_mut().insert(
+            "content-range",
+            format!("bytes {start}-{end}/*").parse().unwrap(),
+        );
+
+        // The first response of the rest ends early, and is resumed from its last byte.
+        if range == "bytes=2048-" {
+            *res.body_mut() = wreq::Body::from(content()[2048..3048].to_vec());
+        } else if start > 0 {
+            assert_eq!(range, "bytes=3048-");
+        }
+        res
+    });
+
+    let mut buf = Vec::new();
+    let written = Downloader::new(Client::new())
+        .segment_size(2048)
+        .on_progress(|p| assert_eq!(p.total(), None))
+        .download(format!("http://{}/file", server.addr()), &mut buf)
+        .await
+        .unwrap();
+
+    assert_eq!(written, 10_000);
+    assert_eq!(buf, content());
+}
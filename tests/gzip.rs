@@ -90,6 +90,225 @@ async fn test_accept_encoding_header_is_not_changed_if_set() {
     assert_eq!(res.status(), wreq::StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_stacked_gzip_response() {
+    let server = server::http(move |_req| async move {
+        let body = gzip_compress(&gzip_compress(RESPONSE_CONTENT.as_bytes()));
+
+        http::Response::builder()
+            .header("content-encoding", "gzip, gzip")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = wreq::Client::new()
+        .get(format!("http://{}/stacked", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    assert!(res.headers().get("content-encoding").is_none());
+    assert_eq!(res.text().await.expect("text"), RESPONSE_CONTENT);
+}
+
+#[tokio::test]
+async fn test_gzip_multiple_members() {
+    let server = server::http(move |_req| async move {
+        let body = [
+            gzip_compress(b"first member, "),
+            gzip_compress(b"second member"),
+        ]
+        .concat();
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(body.into())
+            .unwrap()
+    });
+
+    let res = wreq::Client::new()
+        .get(format!("http://{}/members", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    assert_eq!(
+        res.text().await.expect("text"),
+        "first member, second member"
+    );
+}
+
+#[tokio::test]
+async fn test_gzip_raw_bytes() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(gzip_compress(RESPONSE_CONTENT.as_bytes()).into())
+            .unwrap()
+    });
+
+    let res = wreq::Client::new()
+        .get(format!("http://{}/raw", server.addr()))
+        .send()
+        .await
+        .expect("response");
+    assert!(res.headers().get("content-encoding").is_none());
+
+    // The coding headers are restored along with the raw body.
+    let compressed = gzip_compress(RESPONSE_CONTENT.as_bytes());
+    let res = res.without_decompression();
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(
+        res.headers()["content-length"],
+        compressed.len().to_string().as_str()
+    );
+
+    let raw = res.raw_bytes().await.expect("raw bytes");
+    assert_eq!(raw, compressed);
+}
+
+#[tokio::test]
+async fn test_gzip_decompress_on_demand() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(gzip_compress(RESPONSE_CONTENT.as_bytes()).into())
+            .unwrap()
+    });
+
+    let client = wreq::Client::builder()
+        .decompress_on_demand(true)
+        .build()
+        .unwrap();
+    let url = format!("http://{}/lazy", server.addr());
+
+    // Kept compressed, with the headers describing it, unless read through a decoding method.
+    let res = client.get(&url).send().await.expect("response");
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    let res: http::Response<wreq::Body> = res.into();
+    let raw = http_body_util::BodyExt::collect(res.into_body())
+        .await
+        .expect("raw body")
+        .to_bytes();
+    assert_eq!(raw, gzip_compress(RESPONSE_CONTENT.as_bytes()));
+
+    let res = client.get(&url).send().await.expect("response");
+    assert_eq!(res.text().await.expect("text"), RESPONSE_CONTENT);
+}
+
+#[tokio::test]
+async fn test_gzip_buffer_pool() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(gzip_compress(RESPONSE_CONTENT.as_bytes()).into())
+            .unwrap()
+    });
+
+    let client = wreq::Client::builder().buffer_pool(2, 16).build().unwrap();
+    let url = format!("http://{}/pooled", server.addr());
+
+    // The buffers recycled by the previous requests are reused.
+    for _ in 0..3 {
+        let res = client.get(&url).send().await.expect("response");
+        assert_eq!(res.text().await.expect("text"), RESPONSE_CONTENT);
+    }
+}
+
+#[tokio::test]
+async fn test_gzip_max_decompression_ratio() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(gzip_compress(&[0; 1024 * 1024]).into())
+            .unwrap()
+    });
+
+    let client = wreq::Client::builder()
+        .max_decompression_ratio(100)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/bomb", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    let err = res.bytes().await.expect_err("ratio limit must be exceeded");
+    assert!(err.is_decode());
+}
+
+#[tokio::test]
+async fn test_gzip_transfer_stats() {
+    let compressed = gzip_compress(RESPONSE_CONTENT.as_bytes());
+    let len = compressed.len() as u64;
+    let server = server::http(move |_req| {
+        let compressed = compressed.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(compressed.into())
+                .unwrap()
+        }
+    });
+
+    let client = wreq::Client::builder().no_proxy().build().unwrap();
+    let res = client
+        .get(format!("http://{}/stats", server.addr()))
+        .send()
+        .await
+        .expect("response");
+    let stats = res.transfer_stats();
+    assert_eq!(res.text().await.expect("text"), RESPONSE_CONTENT);
+
+    // The bytes received are counted encoded, and the bytes read decoded.
+    let stats_after = client.transfer_stats();
+    assert!(stats.response_header_bytes() > 0);
+    assert_eq!(stats_after.response_body_bytes(), len);
+    assert_eq!(
+        stats_after.response_decoded_body_bytes(),
+        RESPONSE_CONTENT.len() as u64
+    );
+}
+
+#[cfg(feature = "libdeflate")]
+#[tokio::test]
+async fn test_gzip_max_decompression_ratio_forged_trailer() {
+    // A trailer under-reporting the decoded size sends the body to the streaming fallback,
+    // which must still enforce the limit.
+    let mut bomb = gzip_compress(&[0; 1024 * 1024]);
+    let at = bomb.len() - 4;
+    bomb[at..].copy_from_slice(&1u32.to_le_bytes());
+
+    let server = server::http(move |_req| {
+        let bomb = bomb.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(bomb.into())
+                .unwrap()
+        }
+    });
+
+    let client = wreq::Client::builder()
+        .max_decompression_ratio(100)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(format!("http://{}/forged", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    let err = res.bytes().await.expect_err("ratio limit must be exceeded");
+    assert!(err.is_decode());
+    assert!(
+        format!("{err:?}").contains("decompression ratio"),
+        "{err:?}"
+    );
+}
+
 async fn gzip_case(response_size: usize, chunk_size: usize) {
     use futures_util::stream::StreamExt;
 
@@ -306,7 +525,6 @@ async fn test_chunked_fragmented_response_2() {
     assert!(start.elapsed() >= DELAY_BETWEEN_RESPONSE_PARTS - DELAY_MARGIN);
 }
 
-#[ignore = "tower-http decompression does not support this feature test"]
 #[tokio::test]
 async fn test_chunked_fragmented_response_with_extra_bytes() {
     const DELAY_BETWEEN_RESPONSE_PARTS: tokio::time::Duration =
@@ -361,3 +579,122 @@ async fn test_chunked_fragmented_response_with_extra_bytes() {
     assert!(err.is_decode());
     assert!(start.elapsed() >= DELAY_BETWEEN_RESPONSE_PARTS - DELAY_MARGIN);
 }
+
+/// Decodes the `reverse` coding, which reverses the whole body.
+#[derive(Default)]
+struct Reverse(Vec<u8>);
+
+impl wreq::decoding::ContentDecoder for Reverse {
+    fn decode(&mut self, input: &[u8], _out: &mut Vec<u8>) -> std::io::Result<()> {
+        self.0.extend_from_slice(input);
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> std::io::Result<()> {
+        out.extend(self.0.drain(..).rev());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_and_unknown_codings() {
+    let server = server::http(move |req| async move {
+        let coding = req.uri().path().trim_start_matches('/').to_owned();
+        if coding == "reverse,gzip" {
+            assert_eq!(req.headers()["accept-encoding"], "gzip, reverse");
+        }
+
+        let body = match coding.as_str() {
+            "reverse,gzip" => gzip_compress(b"olleh"),
+            _ => b"olleh".to_vec(),
+        };
+        http::Response::builder()
+            .header("content-encoding", coding)
+            .body(body.into())
+            .unwrap()
+    });
+    let url = |coding: &str| format!("http://{}/{coding}", server.addr());
+
+    let client = wreq::Client::builder()
+        .no_brotli()
+        .no_zstd()
+        .no_deflate()
+        .content_decoder("reverse", || Box::new(Reverse::default()))
+        .build()
+        .unwrap();
+
+    let res = client.get(url("reverse,gzip")).send().await.unwrap();
+    assert!(res.headers().get("content-encoding").is_none());
+    assert_eq!(res.text().await.unwrap(), "hello");
+
+    // Parameters are ignored, and unknown codings pass through by default.
+    let res = client.get(url("identity;q=0")).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "olleh");
+    let res = client.get(url("snappy")).send().await.unwrap();
+    assert_eq!(res.headers()["content-encoding"], "snappy");
+    assert_eq!(res.text().await.unwrap(), "olleh");
+
+    let client = wreq::Client::builder()
+        .unknown_encoding(wreq::decoding::UnknownEncoding::Error)
+        .build()
+        .unwrap();
+    let err = client
+        .get(url("snappy"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap_err();
+    assert!(err.is_decode(), "{err:?}");
+
+    let client = wreq::Client::builder()
+        .unknown_encoding(wreq::decoding::UnknownEncoding::decode_with(|coding| {
+            (coding == "x-reverse")
+                .then(|| Box::new(Reverse::default()) as Box<dyn wreq::decoding::ContentDecoder>)
+        }))
+        .build()
+        .unwrap();
+    let res = client.get(url("x-reverse")).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "hello");
+    let res = client.get(url("snappy")).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "olleh");
+
+    let err = wreq::Client::builder()
+        .content_decoder("bad coding", || Box::new(Reverse::default()))
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder(), "{err:?}");
+}
+
+#[tokio::test]
+async fn test_accept_encoding_override() {
+    let server = server::http(move |req| async move {
+        let body = match req.headers().get("accept-encoding") {
+            Some(value) if value == "br;q=1.0, gzip;q=0.8, *;q=0.1" => gzip_compress(b"overridden"),
+            _ => gzip_compress(b"default"),
+        };
+        http::Response::builder()
+            .header("content-encoding", "gzip")
+            .body(body.into())
+            .unwrap()
+    });
+    let url = format!("http://{}/", server.addr());
+
+    let client = wreq::Client::builder()
+        .accept_encoding("br;q=1.0, gzip;q=0.8, *;q=0.1")
+        .no_brotli()
+        .build()
+        .unwrap();
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "overridden");
+
+    // A header set on the request takes precedence.
+    let res = client
+        .get(&url)
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "default");
+}
@@ -173,6 +173,39 @@ async fn layer_insert_headers() {
     assert!(res.is_ok());
 }
 
+#[tokio::test]
+async fn layer_reads_default_extension() {
+    let _ = env_logger::try_init();
+
+    #[derive(Clone)]
+    struct TenantId(&'static str);
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["x-tenant"], "acme");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}", server.addr());
+
+    let client = wreq::Client::builder()
+        .default_extension(TenantId("acme"))
+        .layer(tower::util::MapRequestLayer::new(
+            move |mut req: http::Request<wreq::Body>| {
+                let tenant = req.extensions().get::<TenantId>().unwrap().0;
+                req.headers_mut()
+                    .insert("x-tenant", http::HeaderValue::from_static(tenant));
+                req
+            },
+        ))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let res = client.get(url).send().await;
+
+    assert!(res.is_ok());
+}
+
 #[tokio::test]
 async fn with_concurrency_limit_layer_timeout() {
     let _ = env_logger::try_init();
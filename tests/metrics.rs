@@ -0,0 +1,139 @@
+mod support;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder, SharedString,
+    Unit,
+};
+use support::server;
+
+/// Records the value of counters and gauges by name and labels.
+#[derive(Clone, Default)]
+struct TestRecorder(Arc<Mutex<BTreeMap<String, f64>>>);
+
+struct Handle {
+    values: Arc<Mutex<BTreeMap<String, f64>>>,
+    key: String,
+}
+
+impl Handle {
+    fn add(&self, value: f64) {
+        *self
+            .values
+            .lock()
+            .unwrap()
+            .entry(self.key.clone())
+            .or_default() += value;
+    }
+
+    fn set(&self, value: f64) {
+        self.values.lock().unwrap().insert(self.key.clone(), value);
+    }
+}
+
+impl CounterFn for Handle {
+    fn increment(&self, value: u64) {
+        self.add(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.set(value as f64);
+    }
+}
+
+impl GaugeFn for Handle {
+    fn increment(&self, value: f64) {
+        self.add(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.add(-value);
+    }
+
+    fn set(&self, value: f64) {
+        Handle::set(self, value);
+    }
+}
+
+impl TestRecorder {
+    fn handle(&self, key: &Key) -> Arc<Handle> {
+        let labels: Vec<String> = key
+            .labels()
+            .map(|label| format!("{}={}", label.key(), label.value()))
+            .collect();
+        Arc::new(Handle {
+            values: self.0.clone(),
+            key: format!("{}{{{}}}", key.name(), labels.join(",")),
+        })
+    }
+
+    fn get(&self, key: &str) -> f64 {
+        self.0.lock().unwrap().get(key).copied().unwrap_or_default()
+    }
+}
+
+impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.handle(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.handle(key))
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+#[tokio::test]
+async fn records_requests_and_connections() {
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/missing" {
+            http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(wreq::Body::default())
+                .unwrap()
+        } else {
+            http::Response::default()
+        }
+    });
+
+    let recorder = TestRecorder::default();
+    let client = wreq::Client::builder()
+        .metrics_recorder(recorder.clone())
+        .build()
+        .unwrap();
+
+    for path in ["ok", "ok", "missing"] {
+        let res = client
+            .get(format!("http://{}/{path}", server.addr()))
+            .send()
+            .await
+            .unwrap();
+        res.bytes().await.unwrap();
+    }
+
+    assert_eq!(
+        recorder.get("wreq_requests_total{method=GET,status=200}"),
+        2.0
+    );
+    assert_eq!(
+        recorder.get("wreq_requests_total{method=GET,status=404}"),
+        1.0
+    );
+
+    let new = recorder.get("wreq_connection_reuse_total{reused=false}");
+    let reused = recorder.get("wreq_connection_reuse_total{reused=true}");
+    assert_eq!(new + reused, 3.0);
+    assert_eq!(recorder.get("wreq_open_connections{}"), new);
+}
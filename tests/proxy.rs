@@ -31,6 +31,8 @@ async fn http_proxy() {
 
     assert_eq!(res.url().as_str(), url);
     assert_eq!(res.status(), wreq::StatusCode::OK);
+    // The peer of a proxied connection is the proxy.
+    assert_eq!(res.remote_addr(), Some(server.addr()));
 }
 
 #[tokio::test]
@@ -384,3 +386,273 @@ async fn tunnel_includes_user_agent() {
         "tunnel unsuccessful expected, got: {err:?}"
     );
 }
+
+#[tokio::test]
+async fn tunnel_headers_and_response() {
+    let url = "https://hyper.rs.local/prox";
+
+    let server = server::http(move |req| {
+        assert_eq!(req.method(), "CONNECT");
+        assert_eq!(req.headers()["x-proxy-region"], "eu");
+        assert_eq!(req.headers()["x-proxy-session"], "request");
+
+        async {
+            let mut res = http::Response::default();
+            *res.status_mut() = http::StatusCode::FORBIDDEN;
+            res.headers_mut()
+                .insert("x-proxy-error", "session-expired".parse().unwrap());
+            res
+        }
+    });
+
+    let proxy = format!("http://{}", server.addr());
+
+    let err = wreq::Client::builder()
+        .proxy(wreq::Proxy::https(&proxy).unwrap().tunnel_headers({
+            let mut headers = http::HeaderMap::new();
+            headers.insert("x-proxy-region", "eu".parse().unwrap());
+            headers.insert("x-proxy-session", "proxy".parse().unwrap());
+            headers
+        }))
+        .build()
+        .unwrap()
+        .get(url)
+        .tunnel_headers({
+            let mut headers = http::HeaderMap::new();
+            headers.insert("x-proxy-session", "request".parse().unwrap());
+            headers
+        })
+        .send()
+        .await
+        .unwrap_err();
+
+    let res = err.tunnel_response().expect("tunnel response");
+    assert_eq!(res.status(), wreq::StatusCode::FORBIDDEN);
+    assert_eq!(res.headers()["x-proxy-error"], "session-expired");
+}
+
+#[tokio::test]
+async fn http_proxy_refreshes_rejected_credentials() {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    let url = "http://hyper.rs.local/prox";
+    let server = server::http(move |req| {
+        let renewed = req.headers()["proxy-authorization"] == "Basic QWxhZGRpbjpyZW5ld2Vk";
+
+        async move {
+            let mut res = http::Response::default();
+            if !renewed {
+                *res.status_mut() = http::StatusCode::PROXY_AUTHENTICATION_REQUIRED;
+            }
+            res
+        }
+    });
+
+    let proxy = format!("http://{}", server.addr());
+    let refreshes = Arc::new(AtomicUsize::new(0));
+
+    let client = wreq::Client::builder()
+        .proxy(
+            wreq::Proxy::http(&proxy)
+                .unwrap()
+                .basic_auth("Aladdin", "expired"),
+        )
+        .proxy_credentials({
+            let refreshes = refreshes.clone();
+            let addr = server.addr().to_string();
+            move |uri: http::Uri| {
+                refreshes.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(uri.authority().map(|a| a.as_str()), Some(&*addr));
+                async { Ok(Some(wreq::ProxyAuth::basic("Aladdin", "renewed"))) }
+            }
+        })
+        .build()
+        .unwrap();
+
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.status(), wreq::StatusCode::OK);
+    assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+    // The new credentials are sent by the following requests.
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.status(), wreq::StatusCode::OK);
+    assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn http_proxy_reloaded_through_config_handle() {
+    let url = "http://hyper.rs.local/prox";
+    let server = server::http(move |req| {
+        assert_eq!(req.uri(), url);
+        assert_eq!(req.headers()["x-reloaded"], "1");
+
+        async { http::Response::default() }
+    });
+
+    let client = wreq::Client::builder().no_proxy().build().unwrap();
+
+    let handle = client.config_handle();
+    handle.set_proxies([wreq::Proxy::http(format!("http://{}", server.addr())).unwrap()]);
+    let mut headers = handle.default_headers();
+    headers.insert("x-reloaded", "1".parse().unwrap());
+    handle.set_default_headers(headers);
+
+    let res = client.get(url).send().await.unwrap();
+
+    assert_eq!(res.status(), wreq::StatusCode::OK);
+}
+
+#[cfg(feature = "socks")]
+#[tokio::test]
+async fn socks4a_proxy_sends_ident() {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufReader::new(stream);
+
+        // VN, CD, DSTPORT and a 0.0.0.x DSTIP announcing a SOCKS4a hostname.
+        let mut head = [0u8; 8];
+        stream.read_exact(&mut head).await.unwrap();
+        assert_eq!(head[..2], [4, 1]);
+        assert_eq!(u16::from_be_bytes([head[2], head[3]]), 80);
+        assert_eq!(head[4..7], [0, 0, 0]);
+
+        let mut userid = Vec::new();
+        stream.read_until(0, &mut userid).await.unwrap();
+        assert_eq!(userid, b"ident\0");
+
+        let mut domain = Vec::new();
+        stream.read_until(0, &mut domain).await.unwrap();
+        assert_eq!(domain, b"hyper.rs.local\0");
+
+        stream
+            .write_all(&[0, 0x5a, 0, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            stream.read_line(&mut line).await.unwrap();
+            if line == "\r\n" {
+                break;
+            }
+        }
+
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok")
+            .await
+            .unwrap();
+    });
+
+    let res = wreq::Client::builder()
+        .proxy(wreq::Proxy::http(format!("socks4a://ident@{addr}")).unwrap())
+        .build()
+        .unwrap()
+        .get("http://hyper.rs.local/prox")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), wreq::StatusCode::OK);
+    assert_eq!(res.text().await.unwrap(), "ok");
+
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn proxy_session_sticks_to_one_proxy() {
+    let url = "http://hyper.rs.local/prox";
+    let a = server::http(move |_req| async { http::Response::new("a".into()) });
+    let b = server::http(move |_req| async { http::Response::new("b".into()) });
+
+    let proxy_a = format!("http://{}", a.addr());
+    let proxy_b = format!("http://{}", b.addr());
+
+    let client = wreq::Client::builder()
+        .proxy(wreq::Proxy::http(&proxy_a).unwrap())
+        .proxy(wreq::Proxy::http(&proxy_b).unwrap())
+        .build()
+        .unwrap();
+
+    // Without a session, the first matching proxy is used.
+    let res = client.get(url).send().await.unwrap();
+    let proxy = res.extensions().get::<wreq::ProxyInfo>().unwrap();
+    assert_eq!(
+        proxy.uri().authority().unwrap().as_str(),
+        a.addr().to_string()
+    );
+    assert_eq!(res.text().await.unwrap(), "a");
+
+    // Find a session served by the second proxy, which must then keep using it.
+    let mut session = None;
+    for key in 0..64 {
+        let res = client.get(url).proxy_session(key).send().await.unwrap();
+        if res.text().await.unwrap() == "b" {
+            session = Some(key);
+            break;
+        }
+    }
+    let session = session.expect("a session served by the second proxy");
+
+    for _ in 0..5 {
+        let res = client.get(url).proxy_session(session).send().await.unwrap();
+        let proxy = res.extensions().get::<wreq::ProxyInfo>().unwrap();
+        assert_eq!(
+            proxy.uri().authority().unwrap().as_str(),
+            b.addr().to_string()
+        );
+        assert_eq!(res.text().await.unwrap(), "b");
+    }
+
+    // Connections to the same origin are pooled per proxy.
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(client.proxy_pool_stats(&proxy_a).unwrap().idle(), 1);
+    assert_eq!(client.proxy_pool_stats(&proxy_b).unwrap().idle(), 1);
+}
+
+#[tokio::test]
+async fn reload_system_proxy() {
+    let url = "http://hyper.rs.local/prox";
+    let first = server::http(move |_req| async { http::Response::new("first".into()) });
+    let second = server::http(move |_req| async { http::Response::new("second".into()) });
+
+    // avoid races with other tests that change "http_proxy"
+    let _env_lock = HTTP_PROXY_ENV_MUTEX.lock().await;
+
+    // save system setting first.
+    let system_proxy = env::var("http_proxy");
+
+    unsafe {
+        env::set_var("http_proxy", format!("http://{}", first.addr()));
+    }
+    let client = wreq::Client::new();
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "first");
+
+    // The environment is only read again once asked to.
+    unsafe {
+        env::set_var("http_proxy", format!("http://{}", second.addr()));
+    }
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "first");
+
+    client.reload_system_proxy();
+    let res = client.get(url).send().await.unwrap();
+    assert_eq!(res.text().await.unwrap(), "second");
+
+    // reset user setting.
+    unsafe {
+        match system_proxy {
+            Err(_) => env::remove_var("http_proxy"),
+            Ok(proxy) => env::set_var("http_proxy", proxy),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+mod support;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use support::server;
+use wreq::{Body, redirect::Policy};
+
+#[tokio::test]
+async fn respect_robots_disallows_and_caches() {
+    let _ = env_logger::try_init();
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let counter = fetches.clone();
+    let server = server::http(move |req| {
+        let counter = counter.clone();
+        async move {
+            match req.uri().path() {
+                "/robots.txt" => {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    http::Response::new(Body::from(
+                        "User-agent: *\nDisallow: /private\n\nUser-agent: TestBot\nDisallow: /\n",
+                    ))
+                }
+                "/private/page" => panic!("disallowed page fetched"),
+                "/go" => http::Response::builder()
+                    .status(302)
+                    .header("location", "/private/page")
+                    .body(Body::default())
+                    .unwrap(),
+                _ => http::Response::new(Body::from("ok")),
+            }
+        }
+    });
+
+    let client = wreq::Client::new();
+
+    let res = client
+        .get(format!("http://{}/public", server.addr()))
+        .respect_robots("MyCrawler/1.0")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "ok");
+
+    let err = client
+        .get(format!("http://{}/private/page", server.addr()))
+        .respect_robots("MyCrawler/1.0")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_robots_disallowed(), "{err:?}");
+
+    let err = client
+        .get(format!("http://{}/go", server.addr()))
+        .redirect(Policy::default())
+        .respect_robots("MyCrawler/1.0")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_robots_disallowed(), "{err:?}");
+
+    let err = client
+        .get(format!("http://{}/public", server.addr()))
+        .respect_robots("TestBot")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(err.is_robots_disallowed(), "{err:?}");
+
+    // Requests that don't opt in are not checked.
+    let res = client
+        .get(format!("http://{}/public", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn respect_robots_missing_file_allows_all() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/robots.txt" {
+            http::Response::builder()
+                .status(404)
+                .body(Body::default())
+                .unwrap()
+        } else {
+            http::Response::new(Body::from("ok"))
+        }
+    });
+
+    let res = wreq::Client::new()
+        .get(format!("http://{}/anything", server.addr()))
+        .respect_robots("MyCrawler/1.0")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+async fn respect_robots_honors_crawl_delay() {
+    use std::time::{Duration, Instant};
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/robots.txt" {
+            http::Response::new(Body::from("User-agent: *\nCrawl-delay: 0.1\n"))
+        } else {
+            http::Response::new(Body::from("ok"))
+        }
+    });
+
+    let client = wreq::Client::new();
+    let url = format!("http://{}/page", server.addr());
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        let res = client
+            .get(&url)
+            .respect_robots("MyCrawler/1.0")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
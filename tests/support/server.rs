@@ -1,7 +1,17 @@
 use std::{
-    convert::Infallible, future::Future, net, sync::mpsc as std_mpsc, thread, time::Duration,
+    convert::Infallible,
+    future::Future,
+    net,
+    sync::{Arc, mpsc as std_mpsc},
+    thread,
+    time::Duration,
 };
 
+use boring2::{
+    pkey::PKey,
+    ssl::{SslAcceptor, SslMethod},
+    x509::X509,
+};
 use tokio::{io::AsyncReadExt, net::TcpStream, runtime, sync::oneshot};
 
 pub struct Server {
@@ -53,9 +63,33 @@ where
     http_with_config(func, |_builder| {})
 }
 
+/// Serves HTTP over TLS, with the certificate of `testserver.com`, which isn't trusted.
+#[allow(unused)]
+pub fn https<F, Fut>(func: F) -> Server
+where
+    F: Fn(http::Request<hyper::body::Incoming>) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = http::Response<wreq::Body>> + Send + 'static,
+{
+    let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    let cert = X509::from_der(include_bytes!("server.cert")).unwrap();
+    let key = PKey::private_key_from_der(include_bytes!("server.key")).unwrap();
+    acceptor.set_certificate(&cert).unwrap();
+    acceptor.set_private_key(&key).unwrap();
+    serve(func, |_builder| {}, Some(Arc::new(acceptor.build())))
+}
+
 type Builder = hyper_util::server::conn::auto::Builder<hyper_util::rt::TokioExecutor>;
 
 pub fn http_with_config<F1, Fut, F2, Bu>(func: F1, apply_config: F2) -> Server
+where
+    F1: Fn(http::Request<hyper::body::Incoming>) -> Fut + Clone + Send + 'static,
+    Fut: Future<Output = http::Response<wreq::Body>> + Send + 'static,
+    F2: FnOnce(&mut Builder) -> Bu + Send + 'static,
+{
+    serve(func, apply_config, None)
+}
+
+fn serve<F1, Fut, F2, Bu>(func: F1, apply_config: F2, tls: Option<Arc<SslAcceptor>>) -> Server
 where
     F1: Fn(http::Request<hyper::body::Incoming>) -> Fut + Clone + Send + 'static,
     Fut: Future<Output = http::Response<wreq::Body>> + Send + 'static,
@@ -103,8 +137,18 @@ where
                                 });
                                 let builder = builder.clone();
                                 let events_tx = events_tx.clone();
+                                let tls = tls.clone();
                                 tokio::spawn(async move {
-                                    let _ = builder.serve_connection_with_upgrades(hyper_util::rt::TokioIo::new(io), svc).await;
+                                    match tls {
+                                        Some(acceptor) => {
+                                            if let Ok(io) = tokio_boring2::accept(&acceptor, io).await {
+                                                let _ = builder.serve_connection_with_upgrades(hyper_util::rt::TokioIo::new(io), svc).await;
+                                            }
+                                        }
+                                        None => {
+                                            let _ = builder.serve_connection_with_upgrades(hyper_util::rt::TokioIo::new(io), svc).await;
+                                        }
+                                    }
                                     let _ = events_tx.send(Event::ConnectionClosed);
                                 });
                             }
@@ -86,6 +86,52 @@ async fn connect_timeout() {
     assert!(err.is_timeout());
 }
 
+#[tokio::test]
+async fn request_connect_timeout() {
+    let _ = env_logger::try_init();
+
+    let client = wreq::Client::builder().no_proxy().build().unwrap();
+
+    let url = "http://192.0.2.1:81/slow";
+
+    let res = client
+        .get(url)
+        .connect_timeout(Duration::from_millis(100))
+        .timeout(Duration::from_millis(1000))
+        .send()
+        .await;
+
+    let err = res.unwrap_err();
+
+    assert!(err.is_connect() && err.is_timeout());
+}
+
+#[tokio::test]
+async fn pool_checkout_timeout() {
+    let _ = env_logger::try_init();
+
+    let client = wreq::Client::builder()
+        .pool_checkout_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = "http://192.0.2.1:81/slow";
+
+    let res = client
+        .get(url)
+        .timeout(Duration::from_millis(1000))
+        .send()
+        .await;
+
+    let err = res.unwrap_err();
+
+    assert!(err.is_pool_timeout() && err.is_timeout());
+    let stats = err.pool_stats().unwrap();
+    assert_eq!(stats.idle(), 0);
+    assert_eq!(stats.waiting(), 0);
+}
+
 #[tokio::test]
 async fn connect_many_timeout_succeeds() {
     let _ = env_logger::try_init();
@@ -275,6 +321,46 @@ async fn read_timeout_allows_slow_response_body() {
     assert_eq!(body, "012");
 }
 
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn body_stall_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // slow headers, then a body that stops sending after its first chunk
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            let stalled = futures_util::stream::unfold(0, |state| async move {
+                if state > 0 {
+                    tokio::time::sleep(Duration::from_millis(1000)).await;
+                }
+                Some((
+                    Ok::<_, std::convert::Infallible>(state.to_string()),
+                    state + 1,
+                ))
+            });
+            let body = wreq::Body::wrap_stream(stalled);
+
+            http::Response::new(body)
+        }
+    });
+
+    let client = wreq::Client::builder()
+        .body_stall_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    let mut res = client.get(&url).send().await.expect("Failed to get");
+
+    assert_eq!(res.chunk().await.unwrap().unwrap(), "0");
+    let err = res.chunk().await.unwrap_err();
+
+    assert!(err.is_timeout());
+}
+
 #[tokio::test]
 async fn response_body_timeout_forwards_size_hint() {
     let _ = env_logger::try_init();
@@ -294,3 +380,38 @@ async fn response_body_timeout_forwards_size_hint() {
 
     assert_eq!(res.content_length(), Some(5));
 }
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn read_timeout_keeps_partial_body() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        // the first chunk is sent right away, the second one is too late
+        let body = wreq::Body::wrap_stream(futures_util::stream::unfold(0, |state| async move {
+            match state {
+                0 => Some((Ok::<_, std::convert::Infallible>("Hello"), 1)),
+                1 => {
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    Some((Ok(" World"), 2))
+                }
+                _ => None,
+            }
+        }));
+
+        http::Response::new(body)
+    });
+
+    let client = wreq::Client::builder()
+        .read_timeout(Duration::from_millis(100))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    let res = client.get(&url).send().await.expect("Failed to get");
+    let (body, err) = res.bytes_lossy(1024).await;
+
+    assert_eq!(body, "Hello");
+    assert!(err.expect("body must be interrupted").is_timeout());
+}
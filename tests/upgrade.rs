@@ -50,6 +50,44 @@ async fn http_upgrade() {
     assert_eq!(buf, b"bar=foo");
 }
 
+#[tokio::test]
+async fn http1_connect_through_proxy() {
+    let server = server::http(move |req| {
+        assert_eq!(req.method(), http::Method::CONNECT);
+        assert_eq!(req.uri(), "hyper.rs.local:443");
+
+        tokio::spawn(async move {
+            let mut upgraded = hyper_util::rt::TokioIo::new(hyper::upgrade::on(req).await.unwrap());
+
+            let mut buf = vec![0; 7];
+            upgraded.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, b"foo=bar");
+
+            upgraded.write_all(b"bar=foo").await.unwrap();
+        });
+
+        async { http::Response::default() }
+    });
+
+    let res = wreq::Client::builder()
+        .proxy(wreq::Proxy::http(format!("http://{}", server.addr())).unwrap())
+        .build()
+        .unwrap()
+        .request(Method::CONNECT, "hyper.rs.local:443")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    let mut upgraded = res.upgrade().await.unwrap();
+
+    upgraded.write_all(b"foo=bar").await.unwrap();
+
+    let mut buf = vec![];
+    upgraded.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, b"bar=foo");
+}
+
 #[tokio::test]
 async fn http2_upgrade() {
     let server = server::http_with_config(
@@ -99,3 +137,46 @@ async fn http2_upgrade() {
     upgraded.read_to_end(&mut buf).await.unwrap();
     assert_eq!(buf, b"bar=foo");
 }
+
+#[cfg(feature = "websocket")]
+#[tokio::test]
+async fn websocket_uses_http2_extended_connect() {
+    let server = server::http_with_config(
+        move |req| async move {
+            if req.uri().path() == "/ws" {
+                assert_eq!(req.method(), http::Method::CONNECT);
+                assert_eq!(
+                    req.extensions()
+                        .get::<hyper::ext::Protocol>()
+                        .map(|protocol| protocol.as_str()),
+                    Some("websocket")
+                );
+                assert!(!req.headers().contains_key(http::header::SEC_WEBSOCKET_KEY));
+            }
+
+            http::Response::default()
+        },
+        |builder| {
+            let mut http2 = builder.http2();
+            http2.enable_connect_protocol();
+        },
+    );
+
+    let client = wreq::Client::builder().http2_only().build().unwrap();
+
+    // Receive the server settings on the pooled connection first.
+    client
+        .get(format!("http://{}/", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    let res = client
+        .websocket(format!("ws://{}/ws", server.addr()))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.version(), http::Version::HTTP_2);
+}
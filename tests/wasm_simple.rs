@@ -0,0 +1,98 @@
+#![cfg(target_arch = "wasm32")]
+use std::time::Duration;
+
+use wasm_bindgen_test::*;
+use wreq::Client;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+async fn simple_example() {
+    let res = Client::new()
+        .get("https://hyper.rs")
+        .send()
+        .await
+        .expect("http get example");
+    assert_eq!(res.status(), 200);
+
+    let body = res.text().await.expect("response to utf-8 text");
+    assert!(!body.is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn chunked_body() {
+    let mut res = Client::new()
+        .get("https://hyper.rs")
+        .send()
+        .await
+        .expect("http get example");
+
+    let mut len = 0;
+    while let Some(chunk) = res.chunk().await.expect("response chunk") {
+        len += chunk.len();
+    }
+    assert!(len > 0);
+    assert!(res.chunk().await.expect("response end").is_none());
+}
+
+#[wasm_bindgen_test]
+async fn request_with_timeout() {
+    let client = Client::builder()
+        .timeout(Duration::from_millis(1))
+        .build()
+        .expect("client builder");
+    let err = client
+        .get("https://hyper.rs")
+        .send()
+        .await
+        .expect_err("request timed out");
+
+    assert!(err.is_timeout());
+}
+
+#[wasm_bindgen_test]
+fn request_builder() {
+    let req = Client::new()
+        .post("https://hyper.rs/")
+        .header("x-test", "wasm")
+        .query(&[("q", "1")])
+        .body("body")
+        .build()
+        .expect("request builder");
+
+    assert_eq!(req.method(), "POST");
+    assert_eq!(req.url().as_str(), "https://hyper.rs/?q=1");
+    assert_eq!(req.headers()["x-test"], "wasm");
+    assert_eq!(
+        req.body().and_then(|body| body.as_bytes()),
+        Some(&b"body"[..])
+    );
+}
+
+#[cfg(feature = "json")]
+#[wasm_bindgen_test]
+async fn json_stream_and_error() {
+    let mut values = Client::new()
+        .get("https://httpbin.org/stream/3")
+        .send()
+        .await
+        .expect("http get stream")
+        .json_stream::<serde_json::Value>();
+
+    let mut count = 0;
+    while let Some(value) = values.next().await {
+        assert!(value.expect("json value").is_object());
+        count += 1;
+    }
+    assert_eq!(count, 3);
+
+    let err = Client::new()
+        .get("https://httpbin.org/status/404")
+        .send()
+        .await
+        .expect("http get status")
+        .json_or_error::<serde_json::Value, serde_json::Value>()
+        .await
+        .expect_err("error status");
+    assert_eq!(err.status(), Some(wreq::StatusCode::NOT_FOUND));
+}
@@ -0,0 +1,130 @@
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::{
+    self,
+    handshake::server::{ErrorResponse, Request, Response},
+    protocol::frame::coding::CloseCode,
+};
+
+#[tokio::test]
+async fn oversized_message_closes_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(tungstenite::Message::binary(vec![0; 1024]))
+            .await
+            .unwrap();
+
+        match ws.next().await {
+            Some(Ok(tungstenite::Message::Close(Some(frame)))) => frame.code,
+            other => panic!("expected a close frame, got {other:?}"),
+        }
+    });
+
+    let mut ws = wreq::Client::new()
+        .websocket(format!("ws://{addr}/"))
+        .max_message_size(512)
+        .send()
+        .await
+        .unwrap()
+        .into_websocket()
+        .await
+        .unwrap();
+
+    let err = ws.recv().await.unwrap().unwrap_err();
+    assert!(err.is_body());
+    assert_eq!(server.await.unwrap(), CloseCode::Size);
+}
+
+#[tokio::test]
+async fn negotiates_subprotocol() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let callback = |req: &Request, mut res: Response| -> Result<Response, ErrorResponse> {
+            assert_eq!(
+                req.headers()["sec-websocket-protocol"],
+                "graphql-transport-ws, graphql-ws"
+            );
+            assert_eq!(req.headers()["x-token"], "secret");
+            res.headers_mut()
+                .insert("sec-websocket-protocol", "graphql-ws".parse().unwrap());
+            Ok(res)
+        };
+        let _ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+            .await
+            .unwrap();
+    });
+
+    let ws = wreq::Client::new()
+        .websocket(format!("ws://{addr}/"))
+        .protocols(["graphql-transport-ws", "graphql-ws"])
+        .header("x-token", "secret")
+        .send()
+        .await
+        .unwrap()
+        .into_websocket()
+        .await
+        .unwrap();
+
+    assert_eq!(ws.protocol().unwrap(), "graphql-ws");
+}
+
+#[tokio::test]
+async fn rejects_invalid_subprotocol() {
+    let err = wreq::Client::new()
+        .websocket("ws://127.0.0.1:1/")
+        .protocols(["graphql ws"])
+        .send()
+        .await
+        .unwrap_err();
+
+    assert!(err.is_builder());
+}
+
+#[cfg(feature = "cookies")]
+#[tokio::test]
+async fn handshake_uses_cookie_store() {
+    use wreq::cookie::CookieStore;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let callback = |req: &Request, mut res: Response| -> Result<Response, ErrorResponse> {
+            assert_eq!(req.headers()["cookie"], "session=old");
+            res.headers_mut()
+                .insert("set-cookie", "session=new".parse().unwrap());
+            Ok(res)
+        };
+        let _ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+            .await
+            .unwrap();
+    });
+
+    let url = format!("http://{addr}/").parse().unwrap();
+    let jar = std::sync::Arc::new(wreq::cookie::Jar::default());
+    jar.add_cookie_str("session=old", &url);
+
+    let ws = wreq::Client::builder()
+        .cookie_provider(jar.clone())
+        .build()
+        .unwrap()
+        .websocket(format!("ws://{addr}/"))
+        .send()
+        .await
+        .unwrap()
+        .into_websocket()
+        .await
+        .unwrap();
+
+    assert_eq!(ws.headers()["set-cookie"], "session=new");
+    let cookies = jar.cookies(&url).unwrap();
+    assert_eq!(cookies[0], "session=new");
+}